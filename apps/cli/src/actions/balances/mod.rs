@@ -1,31 +1,52 @@
 use std::collections::HashMap;
 
-use crate::context::Context;
+use crate::{actions::chroma::get_chroma_info, context::Context};
 use bitcoin::Network;
 use color_eyre::eyre;
+use jsonrpsee::http_client::HttpClient;
 use yuv_pixels::Chroma;
 
 pub async fn run(mut ctx: Context) -> eyre::Result<()> {
     let wallet = ctx.wallet().await?;
     let network = ctx.config()?.network();
     let balances = wallet.balances().await?;
+    let client = ctx.yuv_client()?;
 
     println!("YUV balances:");
-    print_balances(balances.yuv, network);
+    print_balances(&client, balances.yuv, network).await?;
 
     #[cfg(feature = "bulletproof")]
     {
         println!("Bulletproof balances:");
-        print_balances(balances.bulletproof, network);
+        print_balances(&client, balances.bulletproof, network).await?;
     }
 
     println!("Tweaked satoshis: {}", balances.tweaked_satoshis);
 
+    if !balances.labels.is_empty() {
+        println!("Labeled outputs:");
+        for (outpoint, label) in balances.labels {
+            println!("{outpoint}: {label}");
+        }
+    }
+
     Ok(())
 }
 
-fn print_balances(balances: HashMap<Chroma, u128>, network: Network) {
-    for (chroma, balance) in balances.iter() {
-        println!("{}: {}", chroma.to_address(network), balance);
+async fn print_balances(
+    client: &HttpClient,
+    balances: HashMap<Chroma, u128>,
+    network: Network,
+) -> eyre::Result<()> {
+    for (chroma, balance) in balances {
+        let info = get_chroma_info(client, chroma).await?;
+
+        println!(
+            "{}: {}",
+            chroma.to_address(network),
+            info.format_amount(balance)
+        );
     }
+
+    Ok(())
 }
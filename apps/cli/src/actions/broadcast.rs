@@ -0,0 +1,292 @@
+use std::future::Future;
+use std::time::Duration;
+
+use bdk::blockchain::Blockchain;
+use bitcoin::{Transaction, Txid};
+use bitcoin_client::BitcoinRpcApi;
+use clap::Args;
+use color_eyre::eyre::{self, bail};
+use jsonrpsee::core::Error as ClientError;
+use jsonrpsee::http_client::HttpClient;
+use ydk::bitcoin_provider::BitcoinProviderConfig;
+use yuv_rpc_api::transactions::YuvTransactionsRpcClient;
+use yuv_types::YuvTransaction;
+
+use crate::context::Context;
+
+/// Default number of attempts to broadcast the transaction.
+const DEFAULT_BROADCAST_ATTEMPTS: u32 = 3;
+/// Default delay in seconds before the second attempt, it's doubled after each failed one.
+const DEFAULT_BROADCAST_RETRY_DELAY_SECS: u64 = 2;
+
+/// Errors of Bitcoin node meaning that the transaction is already broadcasted, e.g. by the
+/// previous attempt which response is lost.
+const ALREADY_BROADCASTED_ERRORS: &[&str] = &[
+    "txn-already-in-mempool",
+    "txn-already-known",
+    "transaction already in block chain",
+    "transaction outputs already in utxo set",
+];
+
+#[derive(Clone, Args, Debug)]
+pub struct BroadcastArgs {
+    /// Number of attempts to broadcast the transaction, if the Bitcoin node is not available.
+    #[clap(long, default_value_t = DEFAULT_BROADCAST_ATTEMPTS, value_parser = clap::value_parser!(u32).range(1..))]
+    pub broadcast_attempts: u32,
+
+    /// Delay in seconds before the second attempt to broadcast the transaction, it's doubled
+    /// after each failed attempt.
+    #[clap(long, default_value_t = DEFAULT_BROADCAST_RETRY_DELAY_SECS)]
+    pub broadcast_retry_delay: u64,
+}
+
+/// Broadcast the transaction, retrying with backoff if the Bitcoin node is not reachable. The
+/// transaction which is already in the mempool or in the chain is considered broadcasted.
+pub async fn broadcast_with_retry(
+    blockchain: &impl Blockchain,
+    tx: &Transaction,
+    args: &BroadcastArgs,
+) -> eyre::Result<()> {
+    let txid = tx.txid();
+
+    with_retry(txid, args, || async {
+        match blockchain.broadcast(tx) {
+            Ok(()) => Attempt::Done,
+            Err(err) if is_already_broadcasted(&err) => {
+                println!("Transaction {} is already broadcasted", txid);
+                Attempt::Done
+            }
+            Err(err) if is_transport_error(&err) => Attempt::Retry(err.to_string()),
+            Err(err) => Attempt::Failed(err.to_string()),
+        }
+    })
+    .await
+}
+
+/// Send the transaction to the YUV node, which broadcasts it and checks the proofs, retrying
+/// with backoff if the YUV node is not reachable.
+pub async fn send_raw_yuv_tx_with_retry(
+    client: &HttpClient,
+    tx: &YuvTransaction,
+    args: &BroadcastArgs,
+) -> eyre::Result<()> {
+    with_retry(tx.bitcoin_tx.txid(), args, || async {
+        match client.send_raw_yuv_tx(tx.clone(), None).await {
+            Ok(_) => Attempt::Done,
+            Err(err) if is_client_transport_error(&err) => Attempt::Retry(err.to_string()),
+            Err(err) => Attempt::Failed(err.to_string()),
+        }
+    })
+    .await
+}
+
+/// Outcome of a single attempt to broadcast the transaction.
+enum Attempt {
+    Done,
+    /// The node is not reachable, so the attempt could be repeated.
+    Retry(String),
+    /// The transaction is rejected, so repeating the attempt won't help.
+    Failed(String),
+}
+
+/// Make the attempts to broadcast the transaction until one is done or failed, sleeping between
+/// them with the doubled delay.
+async fn with_retry<F, Fut>(txid: Txid, args: &BroadcastArgs, mut attempt: F) -> eyre::Result<()>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Attempt>,
+{
+    let mut delay = Duration::from_secs(args.broadcast_retry_delay);
+    let mut attempt_num = 1;
+
+    loop {
+        println!(
+            "Broadcasting transaction {} (attempt {}/{})",
+            txid, attempt_num, args.broadcast_attempts
+        );
+
+        let err = match attempt().await {
+            Attempt::Done => return Ok(()),
+            Attempt::Failed(err) => bail!("Failed to broadcast transaction {}: {}", txid, err),
+            Attempt::Retry(err) => err,
+        };
+
+        if attempt_num >= args.broadcast_attempts {
+            bail!(
+                "Failed to broadcast transaction {} after {} attempts: {}",
+                txid,
+                attempt_num,
+                err
+            );
+        }
+
+        println!(
+            "Failed to broadcast transaction: {}, retrying in {}s",
+            err,
+            delay.as_secs()
+        );
+
+        tokio::time::sleep(delay).await;
+        delay *= 2;
+        attempt_num += 1;
+    }
+}
+
+/// Check with `testmempoolaccept` that the Bitcoin node would accept the transaction, failing
+/// with the rejection reason if it wouldn't. The check is skipped for Esplora, which has no
+/// such method.
+pub async fn check_mempool_acceptance(ctx: &mut Context, tx: &Transaction) -> eyre::Result<()> {
+    if let BitcoinProviderConfig::Esplora(_) = ctx.config()?.bitcoin_provider {
+        println!("Skipping mempool acceptance check, it's not available for Esplora");
+        return Ok(());
+    }
+
+    let bitcoin_client = ctx.bitcoin_client(None, None, None).await?;
+
+    let results = bitcoin_client.test_mempool_accept(&[tx]).await?;
+    let Some(result) = results.first() else {
+        bail!("Bitcoin node returned no result of the mempool acceptance check");
+    };
+
+    if !result.allowed {
+        bail!(
+            "Transaction {} would be rejected by the Bitcoin node: {}",
+            result.txid,
+            result.reject_reason.as_deref().unwrap_or("unknown reason")
+        );
+    }
+
+    Ok(())
+}
+
+fn is_already_broadcasted(err: &bdk::Error) -> bool {
+    let message = err.to_string().to_lowercase();
+
+    ALREADY_BROADCASTED_ERRORS
+        .iter()
+        .any(|error| message.contains(error))
+}
+
+/// Check if the Bitcoin node or Esplora is not reachable or is temporarily unavailable, unlike
+/// the errors of the rejected transaction.
+fn is_transport_error(err: &bdk::Error) -> bool {
+    use bdk::bitcoincore_rpc::{jsonrpc, Error as RpcError};
+    use bdk::blockchain::esplora::EsploraError;
+
+    match err {
+        bdk::Error::Rpc(RpcError::JsonRpc(jsonrpc::Error::Transport(_)) | RpcError::Io(_)) => true,
+        bdk::Error::Esplora(err) => match err.as_ref() {
+            EsploraError::Ureq(_) | EsploraError::Io(_) => true,
+            EsploraError::HttpResponse(status) => *status >= 500 || *status == 429,
+            _ => false,
+        },
+        _ => false,
+    }
+}
+
+/// Check if the YUV node is not reachable, unlike the errors returned by it.
+fn is_client_transport_error(err: &ClientError) -> bool {
+    matches!(
+        err,
+        ClientError::Transport(_) | ClientError::RequestTimeout | ClientError::RestartNeeded(_)
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::Cell;
+    use std::io;
+
+    use bdk::bitcoincore_rpc::Error as RpcError;
+    use bdk::blockchain::esplora::EsploraError;
+    use bitcoin::hashes::Hash;
+    use jsonrpsee::types::ErrorObject;
+
+    use super::*;
+
+    fn args(broadcast_attempts: u32) -> BroadcastArgs {
+        BroadcastArgs {
+            broadcast_attempts,
+            broadcast_retry_delay: 0,
+        }
+    }
+
+    #[test]
+    fn test_already_broadcasted_errors() {
+        let err = bdk::Error::Rpc(RpcError::ReturnedError(
+            "Transaction already in block chain".to_string(),
+        ));
+        assert!(is_already_broadcasted(&err));
+
+        let err = bdk::Error::Rpc(RpcError::ReturnedError(
+            "bad-txns-inputs-missingorspent".to_string(),
+        ));
+        assert!(!is_already_broadcasted(&err));
+    }
+
+    #[test]
+    fn test_only_transport_errors_are_retried() {
+        let refused = || io::Error::from(io::ErrorKind::ConnectionRefused);
+
+        assert!(is_transport_error(&bdk::Error::Rpc(
+            RpcError::Io(refused())
+        )));
+        assert!(is_transport_error(&bdk::Error::Esplora(Box::new(
+            EsploraError::HttpResponse(503)
+        ))));
+        assert!(!is_transport_error(&bdk::Error::Esplora(Box::new(
+            EsploraError::HttpResponse(400)
+        ))));
+        assert!(!is_transport_error(&bdk::Error::Rpc(
+            RpcError::ReturnedError("bad-txns-inputs-missingorspent".to_string())
+        )));
+
+        assert!(is_client_transport_error(&ClientError::RequestTimeout));
+        assert!(!is_client_transport_error(&ClientError::Call(
+            ErrorObject::owned(-32603, "Service is dead", Option::<()>::None)
+        )));
+    }
+
+    #[tokio::test]
+    async fn test_retry_stops_on_done_or_failed_attempt() {
+        let txid = Txid::all_zeros();
+
+        let attempts = Cell::new(0);
+        let result = with_retry(txid, &args(3), || async {
+            attempts.set(attempts.get() + 1);
+
+            if attempts.get() < 2 {
+                Attempt::Retry("connection refused".to_string())
+            } else {
+                Attempt::Done
+            }
+        })
+        .await;
+        assert!(result.is_ok());
+        assert_eq!(attempts.get(), 2);
+
+        let attempts = Cell::new(0);
+        let result = with_retry(txid, &args(3), || async {
+            attempts.set(attempts.get() + 1);
+
+            Attempt::Failed("bad-txns-inputs-missingorspent".to_string())
+        })
+        .await;
+        assert!(result.is_err());
+        assert_eq!(
+            attempts.get(),
+            1,
+            "rejected transaction must not be retried"
+        );
+
+        let attempts = Cell::new(0);
+        let result = with_retry(txid, &args(3), || async {
+            attempts.set(attempts.get() + 1);
+
+            Attempt::Retry("connection refused".to_string())
+        })
+        .await;
+        assert!(result.is_err());
+        assert_eq!(attempts.get(), 3);
+    }
+}
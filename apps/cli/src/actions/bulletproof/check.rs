@@ -32,7 +32,11 @@ pub async fn run(
     let config = context.config()?;
     let yuv_client = context.yuv_client()?;
 
-    let dh_key = ecdh(config.private_key, sender.public_key(), config.network())?;
+    let dh_key = ecdh(
+        context.private_key()?,
+        sender.public_key(),
+        config.network(),
+    )?;
 
     let raw_dh_key: [u8; 32] = dh_key
         .to_bytes()
@@ -20,7 +20,7 @@ pub fn run(DhArgs { recipient }: DhArgs, mut context: Context) -> eyre::Result<(
 
     let recipient = recipient.public_key();
 
-    let dh_key = ecdh(config.private_key, recipient, config.network())?;
+    let dh_key = ecdh(context.private_key()?, recipient, config.network())?;
 
     let pubkey = dh_key.public_key(context.secp_ctx());
 
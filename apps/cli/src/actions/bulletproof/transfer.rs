@@ -30,10 +30,9 @@ pub struct TransferArgs {
     /// satoshis to transfer to sender
     pub residual_satoshis: Vec<u64>,
 
-    /// Type of the token, public key of the issuer.
+    /// Type of the token, P2TR address of the issuer.
     #[clap(long, num_args = 1..)]
-    #[arg(value_parser = Chroma::from_address)]
-    pub chroma: Vec<Chroma>,
+    pub chroma: Vec<String>,
 
     /// The public key of the receiver.
     #[clap(long, num_args = 1..)]
@@ -72,6 +71,10 @@ pub async fn run(
     }
 
     let config = context.config()?;
+    let chroma = chroma
+        .iter()
+        .map(|chroma| context.chroma(chroma))
+        .collect::<eyre::Result<Vec<_>>>()?;
     let wallet = context.wallet().await?;
     let blockchain = context.blockchain()?;
     let yuv_client = context.yuv_client()?;
@@ -79,7 +82,7 @@ pub async fn run(
     let mut builder = wallet.build_transfer()?;
     // Add the input tx
     builder.manual_selected_only();
-    let sender = config.private_key.public_key(context.secp_ctx()).inner;
+    let sender = context.private_key()?.public_key(context.secp_ctx()).inner;
 
     for i in 0..chroma.len() {
         let recipient = recipient[i].public_key();
@@ -9,8 +9,8 @@ use yuv_types::announcements::ChromaAnnouncement;
 #[derive(Clone, Args, Debug)]
 pub struct AnnnouncementArgs {
     /// The [`Chroma`] to announce.
-    #[clap(long, short, value_parser = Chroma::from_address)]
-    pub chroma: Option<Chroma>,
+    #[clap(long, short)]
+    pub chroma: Option<String>,
     /// The name of the token.
     #[clap(long, short)]
     pub name: String,
@@ -47,7 +47,10 @@ pub async fn run(args: AnnnouncementArgs, mut context: Context) -> eyre::Result<
     let wallet = context.wallet().await?;
     let config = context.config()?;
 
-    let chroma = args.chroma.unwrap_or(Chroma::from(wallet.public_key()));
+    let chroma = match &args.chroma {
+        Some(chroma) => context.chroma(chroma)?,
+        None => Chroma::from(wallet.public_key()),
+    };
 
     let announcement = args.clone().try_into_announcement(chroma)?;
 
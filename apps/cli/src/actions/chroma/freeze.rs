@@ -0,0 +1,51 @@
+use crate::{
+    actions::broadcast::{broadcast_with_retry, BroadcastArgs},
+    context::Context,
+};
+use clap::Args;
+use color_eyre::eyre::{self, Context as EyreContext};
+use yuv_pixels::Chroma;
+
+/// Arguments to freeze or unfreeze all the outputs of the token. See
+/// [`ChromaFreezeAnnouncement`].
+///
+/// [`ChromaFreezeAnnouncement`]: yuv_types::announcements::ChromaFreezeAnnouncement
+#[derive(Clone, Args, Debug)]
+pub struct FreezeArgs {
+    /// The [`Chroma`] to freeze or unfreeze. Defaults to the one of the wallet.
+    #[clap(long, short)]
+    pub chroma: Option<String>,
+
+    #[clap(flatten)]
+    pub broadcast: BroadcastArgs,
+}
+
+pub async fn run(args: FreezeArgs, context: Context) -> eyre::Result<()> {
+    announce_freeze(args, true, context).await
+}
+
+pub async fn run_unfreeze(args: FreezeArgs, context: Context) -> eyre::Result<()> {
+    announce_freeze(args, false, context).await
+}
+
+async fn announce_freeze(args: FreezeArgs, frozen: bool, mut context: Context) -> eyre::Result<()> {
+    let blockchain = context.blockchain()?;
+
+    let wallet = context.wallet().await?;
+    let config = context.config()?;
+
+    let chroma = match &args.chroma {
+        Some(chroma) => context.chroma(chroma)?,
+        None => Chroma::from(wallet.public_key()),
+    };
+
+    let yuv_tx = wallet
+        .create_chroma_freeze(chroma, frozen, config.fee_rate_strategy, &blockchain)
+        .wrap_err("failed to create chroma freeze tx")?;
+
+    broadcast_with_retry(&blockchain, &yuv_tx.bitcoin_tx, &args.broadcast).await?;
+
+    println!("Transaction broadcasted: {}", yuv_tx.bitcoin_tx.txid());
+
+    Ok(())
+}
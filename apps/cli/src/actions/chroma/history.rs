@@ -0,0 +1,27 @@
+use crate::context::Context;
+use color_eyre::eyre;
+
+/// Print every token the wallet has issued, sent or received, including the ones with zero
+/// balance now.
+pub async fn run(mut context: Context) -> eyre::Result<()> {
+    let wallet = context.wallet().await?;
+    let network = context.config()?.network();
+
+    let history = wallet.transacted_chromas().await?;
+
+    if history.is_empty() {
+        println!("Wallet has no transactions with tokens");
+
+        return Ok(());
+    }
+
+    for (chroma, activity) in history {
+        println!("Chroma: {}", chroma.to_address(network));
+        println!("Transactions: {}", activity.txs_number());
+        println!("First transaction: {}", activity.first_txid);
+        println!("Last transaction: {}", activity.last_txid);
+        println!();
+    }
+
+    Ok(())
+}
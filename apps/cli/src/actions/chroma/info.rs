@@ -1,22 +1,24 @@
 use crate::context::Context;
 use clap::Args;
 use color_eyre::eyre;
-use yuv_pixels::Chroma;
 use yuv_rpc_api::transactions::YuvTransactionsRpcClient;
 
 /// Arguments to request the information about the token from YUV node by its [`Chroma`].
+///
+/// [`Chroma`]: yuv_pixels::Chroma
 #[derive(Clone, Args, Debug)]
 pub struct InfoArgs {
     /// The [`Chroma`] of the token to get the information about.
-    #[clap(long, short, value_parser = Chroma::from_address)]
-    pub chroma: Chroma,
+    #[clap(long, short)]
+    pub chroma: String,
 }
 
 pub async fn run(args: InfoArgs, mut context: Context) -> eyre::Result<()> {
     let client = context.yuv_client()?;
     let config = context.config()?;
+    let chroma = context.chroma(&args.chroma)?;
 
-    let chroma_info_opt = client.get_chroma_info(args.chroma).await?;
+    let chroma_info_opt = client.get_chroma_info(chroma).await?;
 
     let Some(chroma_info) = chroma_info_opt else {
         println!("Token info not found");
@@ -24,9 +26,9 @@ pub async fn run(args: InfoArgs, mut context: Context) -> eyre::Result<()> {
         return Ok(());
     };
 
-    println!("Chroma: {}", args.chroma.to_address(config.network()));
+    println!("Chroma: {}", chroma.to_address(config.network()));
 
-    if let Some(announcement) = chroma_info.announcement {
+    if let Some(announcement) = &chroma_info.announcement {
         println!("Name: {}", announcement.name);
         println!("Symbol: {}", announcement.symbol);
         println!("Decimal: {}", announcement.decimal);
@@ -34,13 +36,16 @@ pub async fn run(args: InfoArgs, mut context: Context) -> eyre::Result<()> {
         let max_supply = if announcement.max_supply == 0 {
             "unlimited".to_owned()
         } else {
-            announcement.max_supply.to_string()
+            announcement.format_amount(announcement.max_supply)
         };
         println!("Max supply: {}", max_supply);
         println!("Is freezable: {}", announcement.is_freezable);
     };
 
-    println!("Total supply: {}", chroma_info.total_supply);
+    println!(
+        "Total supply: {}",
+        chroma_info.format_amount(chroma_info.total_supply)
+    );
 
     Ok(())
 }
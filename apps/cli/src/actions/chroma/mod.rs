@@ -1,21 +1,60 @@
 use crate::context::Context;
 use clap::Subcommand;
 use color_eyre::eyre;
+use jsonrpsee::http_client::HttpClient;
+use yuv_pixels::Chroma;
+use yuv_rpc_api::transactions::YuvTransactionsRpcClient;
+use yuv_types::announcements::ChromaInfo;
 
 mod announcement;
+mod freeze;
+mod history;
 mod info;
+mod prove;
+mod verify;
 
 #[derive(Subcommand, Debug)]
 pub enum ChromaCommands {
     /// Make the Chroma announcement.
     Announcement(announcement::AnnnouncementArgs),
+    /// Freeze all the outputs of the token, including the ones created after the freeze.
+    Freeze(freeze::FreezeArgs),
+    /// Unfreeze the outputs of the token frozen with `chroma freeze`.
+    Unfreeze(freeze::FreezeArgs),
     /// Get the information about the token by its Chroma.
     Info(info::InfoArgs),
+    /// List every token the wallet has issued, sent or received, including the ones with zero
+    /// balance now.
+    History,
+    /// Prove the control over the Chroma by signing the challenge, without any transaction.
+    Prove(prove::ProveArgs),
+    /// Verify the proof of the control over the Chroma.
+    Verify(verify::VerifyArgs),
 }
 
 pub async fn run(cmd: ChromaCommands, context: Context) -> eyre::Result<()> {
     match cmd {
         ChromaCommands::Announcement(args) => announcement::run(args, context).await,
+        ChromaCommands::Freeze(args) => freeze::run(args, context).await,
+        ChromaCommands::Unfreeze(args) => freeze::run_unfreeze(args, context).await,
         ChromaCommands::Info(args) => info::run(args, context).await,
+        ChromaCommands::History => history::run(context).await,
+        ChromaCommands::Prove(args) => prove::run(args, context).await,
+        ChromaCommands::Verify(args) => verify::run(args, context).await,
     }
 }
+
+/// Get the [`ChromaInfo`] of the token from YUV node. If the node knows nothing about the token,
+/// it's treated as not announced, so its amounts have no decimal places.
+pub(crate) async fn get_chroma_info(
+    client: &HttpClient,
+    chroma: Chroma,
+) -> eyre::Result<ChromaInfo> {
+    let info = client.get_chroma_info(chroma).await?.unwrap_or(ChromaInfo {
+        announcement: None,
+        announcement_txid: None,
+        total_supply: 0,
+    });
+
+    Ok(info)
+}
@@ -0,0 +1,79 @@
+use crate::context::Context;
+use clap::Args;
+use color_eyre::eyre;
+
+/// Arguments to prove the control over the [`Chroma`] of the wallet's key by signing the
+/// challenge.
+///
+/// [`Chroma`]: yuv_pixels::Chroma
+#[derive(Clone, Args, Debug)]
+pub struct ProveArgs {
+    /// The challenge to sign, e.g. the one given by an explorer.
+    #[clap(long)]
+    pub challenge: String,
+}
+
+pub async fn run(args: ProveArgs, mut context: Context) -> eyre::Result<()> {
+    let config = context.config()?;
+    let wallet = context.wallet().await?;
+
+    let (chroma, signature) = wallet.prove_chroma_ownership(args.challenge.as_bytes())?;
+
+    println!("Chroma: {}", chroma.to_address(config.network()));
+    println!("Signature: {}", signature);
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use bitcoin::secp256k1::Secp256k1;
+    use bitcoin::{Network, PrivateKey};
+    use color_eyre::eyre;
+    use ydk::bitcoin_provider::{BitcoinProviderConfig, EsploraConfig};
+    use ydk::wallet::{StorageWallet, StorageWalletConfig, WalletConfig};
+    use yuv_pixels::Chroma;
+
+    use crate::actions::chroma::verify::verify_proof;
+
+    #[tokio::test]
+    async fn test_proof_of_wallet_is_verified() -> eyre::Result<()> {
+        let private_key = PrivateKey::from_slice(&[7; 32], Network::Regtest)?;
+        let storage_path =
+            std::env::temp_dir().join(format!("yuv-cli-chroma-prove-{}", std::process::id()));
+        std::fs::create_dir_all(&storage_path)?;
+
+        let wallet = StorageWallet::from_storage_config(StorageWalletConfig {
+            inner: WalletConfig {
+                privkey: private_key,
+                network: Network::Regtest,
+                bitcoin_provider: BitcoinProviderConfig::Esplora(EsploraConfig {
+                    url: "http://127.0.0.1:3000".to_string(),
+                    network: Network::Regtest,
+                    stop_gap: 20,
+                }),
+                yuv_url: "http://127.0.0.1:18333".to_string(),
+            },
+            storage_path: storage_path.clone(),
+        })
+        .await?;
+
+        let ctx = Secp256k1::new();
+        let (chroma, signature) = wallet.prove_chroma_ownership(b"challenge")?;
+        assert_eq!(
+            chroma,
+            Chroma::from(private_key.public_key(&ctx).inner.x_only_public_key().0)
+        );
+
+        verify_proof(&ctx, &chroma, b"challenge", &signature)?;
+        assert!(
+            verify_proof(&ctx, &chroma, b"other challenge", &signature).is_err(),
+            "proof must not be valid for the other challenge"
+        );
+
+        drop(wallet);
+        std::fs::remove_dir_all(storage_path)?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,48 @@
+use crate::context::Context;
+use bitcoin::secp256k1::{schnorr::Signature, All, Secp256k1};
+use clap::Args;
+use color_eyre::eyre::{self, bail};
+use yuv_pixels::{verify_chroma_ownership, Chroma};
+
+/// Arguments to verify the proof of the control over the [`Chroma`].
+#[derive(Clone, Args, Debug)]
+pub struct VerifyArgs {
+    /// The [`Chroma`] which ownership is proven.
+    #[clap(long, short)]
+    pub chroma: String,
+    /// The signed challenge.
+    #[clap(long)]
+    pub challenge: String,
+    /// The hex encoded signature of the challenge.
+    #[clap(long, short)]
+    pub signature: Signature,
+}
+
+pub async fn run(args: VerifyArgs, mut context: Context) -> eyre::Result<()> {
+    let chroma = context.chroma(&args.chroma)?;
+
+    verify_proof(
+        context.secp_ctx(),
+        &chroma,
+        args.challenge.as_bytes(),
+        &args.signature,
+    )?;
+
+    println!("Signature is valid");
+
+    Ok(())
+}
+
+/// Check that the `signature` of the `challenge` proves the control over the `chroma`.
+pub(crate) fn verify_proof(
+    ctx: &Secp256k1<All>,
+    chroma: &Chroma,
+    challenge: &[u8],
+    signature: &Signature,
+) -> eyre::Result<()> {
+    if verify_chroma_ownership(ctx, chroma, challenge, signature).is_err() {
+        bail!("Signature is not a valid proof of the chroma ownership");
+    }
+
+    Ok(())
+}
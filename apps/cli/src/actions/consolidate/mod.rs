@@ -0,0 +1,73 @@
+use bdk::blockchain::Blockchain;
+use clap::Args;
+use color_eyre::eyre;
+use ydk::types::ConsolidationPolicy;
+use yuv_rpc_api::transactions::YuvTransactionsRpcClient;
+
+use crate::context::Context;
+
+const DEFAULT_MAX_INPUTS: usize = 50;
+
+#[derive(Args, Debug)]
+pub struct ConsolidateArgs {
+    /// Type of the token, P2TR address of the issuer.
+    #[clap(long, short)]
+    pub chroma: String,
+
+    /// Maximum number of UTXOs to merge, the smallest ones are merged first.
+    #[clap(long, default_value_t = DEFAULT_MAX_INPUTS)]
+    pub max_inputs: usize,
+
+    /// Minimal number of UTXOs to merge.
+    #[clap(long, default_value_t = ConsolidationPolicy::default().min_utxos)]
+    pub min_utxos: usize,
+
+    /// Fee rate in sat/vb the future transfers are expected to pay. Consolidation is made only
+    /// if it saves more on them than it costs now.
+    #[clap(long, default_value_t = ConsolidationPolicy::default().expected_fee_rate)]
+    pub expected_fee_rate: f32,
+
+    /// Provide proof of the transaction to YUV node or not.
+    #[clap(long)]
+    pub do_not_provide_proofs: bool,
+}
+
+pub async fn run(
+    ConsolidateArgs {
+        chroma,
+        max_inputs,
+        min_utxos,
+        expected_fee_rate,
+        do_not_provide_proofs,
+    }: ConsolidateArgs,
+    mut ctx: Context,
+) -> eyre::Result<()> {
+    let wallet = ctx.wallet().await?;
+    let blockchain = ctx.blockchain()?;
+    let cfg = ctx.config()?;
+    let chroma = ctx.chroma(&chroma)?;
+
+    let policy = ConsolidationPolicy {
+        min_utxos,
+        expected_fee_rate,
+    };
+
+    let tx = wallet
+        .consolidate(chroma, max_inputs, policy, Some(cfg.fee_rate_strategy))
+        .await?;
+
+    let Some(tx) = tx else {
+        println!("Consolidation of the UTXOs doesn't save fees");
+        return Ok(());
+    };
+
+    if do_not_provide_proofs {
+        blockchain.broadcast(&tx.bitcoin_tx)?;
+    } else {
+        ctx.yuv_client()?.send_raw_yuv_tx(tx.clone(), None).await?;
+    }
+
+    println!("tx id: {}", tx.bitcoin_tx.txid());
+
+    Ok(())
+}
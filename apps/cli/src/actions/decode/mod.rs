@@ -0,0 +1,210 @@
+use std::io::Cursor;
+
+use bitcoin::{consensus::Decodable, hashes::hex::FromHex, OutPoint, Script, Txid};
+use clap::Args;
+use color_eyre::eyre::{self, bail, eyre};
+use serde::Serialize;
+use yuv_pixels::PixelProof;
+use yuv_tx_check::ProofKind;
+use yuv_types::{
+    announcements::{
+        announcement_from_script, ChromaAnnouncement, ChromaFreezeAnnouncement, IssueAnnouncement,
+    },
+    Announcement, ProofMap, YuvTransaction, YuvTxType,
+};
+
+#[derive(Args, Debug)]
+pub struct DecodeArgs {
+    /// Consensus encoded YUV transaction in hex or base64.
+    pub tx: String,
+}
+
+pub fn run(DecodeArgs { tx }: DecodeArgs) -> eyre::Result<()> {
+    let bytes = decode_bytes(&tx)?;
+    let yuv_tx = decode_yuv_tx(&bytes)?;
+
+    println!(
+        "{}",
+        serde_yaml::to_string(&DecodedTransaction::from(yuv_tx))?
+    );
+
+    Ok(())
+}
+
+/// Get raw bytes from the input, which is either hex or base64 string.
+pub(crate) fn decode_bytes(input: &str) -> eyre::Result<Vec<u8>> {
+    let input = input.trim();
+
+    if let Ok(bytes) = Vec::<u8>::from_hex(input) {
+        return Ok(bytes);
+    }
+
+    base64::decode(input).map_err(|err| eyre!("input is neither a valid hex nor base64: {err}"))
+}
+
+/// Consensus decode the [`YuvTransaction`], reporting the byte offset at
+/// which decoding has failed.
+pub(crate) fn decode_yuv_tx(bytes: &[u8]) -> eyre::Result<YuvTransaction> {
+    let mut cursor = Cursor::new(bytes);
+
+    let yuv_tx = YuvTransaction::consensus_decode(&mut cursor).map_err(|err| {
+        eyre!(
+            "failed to decode YUV transaction at byte offset {}: {err}",
+            cursor.position()
+        )
+    })?;
+
+    let offset = cursor.position() as usize;
+    if offset != bytes.len() {
+        bail!(
+            "unexpected {} trailing bytes after YUV transaction at byte offset {offset}",
+            bytes.len() - offset
+        );
+    }
+
+    Ok(yuv_tx)
+}
+
+#[derive(Serialize)]
+struct DecodedTransaction {
+    txid: Txid,
+    version: i32,
+    lock_time: u32,
+    #[serde(rename = "type")]
+    tx_type: &'static str,
+    inputs: Vec<DecodedInput>,
+    outputs: Vec<DecodedOutput>,
+}
+
+#[derive(Serialize)]
+struct DecodedInput {
+    vin: u32,
+    previous_output: OutPoint,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    proof: Option<DecodedProof>,
+}
+
+#[derive(Serialize)]
+struct DecodedOutput {
+    vout: u32,
+    value: u64,
+    script_pubkey: Script,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    proof: Option<DecodedProof>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    announcement: Option<DecodedAnnouncement>,
+}
+
+#[derive(Serialize)]
+struct DecodedProof {
+    #[serde(rename = "type")]
+    proof_type: ProofKind,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    chroma: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    amount: Option<u128>,
+}
+
+#[derive(Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+enum DecodedAnnouncement {
+    Chroma(ChromaAnnouncement),
+    /// Freeze announcement toggles the frozen state of the outpoint.
+    FreezeToggle {
+        outpoint: OutPoint,
+    },
+    Issue(IssueAnnouncement),
+    /// Chroma freeze announcement sets the frozen state of all the outputs of the chroma.
+    ChromaFreeze(ChromaFreezeAnnouncement),
+}
+
+impl From<Announcement> for DecodedAnnouncement {
+    fn from(announcement: Announcement) -> Self {
+        match announcement {
+            Announcement::Chroma(chroma) => Self::Chroma(chroma),
+            Announcement::Freeze(freeze) => Self::FreezeToggle {
+                outpoint: freeze.outpoint,
+            },
+            Announcement::Issue(issue) => Self::Issue(issue),
+            Announcement::ChromaFreeze(freeze) => Self::ChromaFreeze(freeze),
+        }
+    }
+}
+
+impl From<&PixelProof> for DecodedProof {
+    fn from(proof: &PixelProof) -> Self {
+        let proof_type = ProofKind::from(proof);
+
+        if proof.is_empty_pixelproof() {
+            return Self {
+                proof_type,
+                chroma: None,
+                amount: None,
+            };
+        }
+
+        let pixel = proof.pixel();
+
+        Self {
+            proof_type,
+            chroma: Some(pixel.chroma.to_string()),
+            amount: Some(pixel.luma.amount),
+        }
+    }
+}
+
+impl From<YuvTransaction> for DecodedTransaction {
+    fn from(yuv_tx: YuvTransaction) -> Self {
+        let empty_proofs = ProofMap::new();
+
+        let (tx_type, input_proofs, output_proofs) = match &yuv_tx.tx_type {
+            YuvTxType::Issue { output_proofs, .. } => (
+                "Issue",
+                &empty_proofs,
+                output_proofs.as_ref().unwrap_or(&empty_proofs),
+            ),
+            YuvTxType::Transfer {
+                input_proofs,
+                output_proofs,
+            } => ("Transfer", input_proofs, output_proofs),
+            YuvTxType::Announcement(_) => ("Announcement", &empty_proofs, &empty_proofs),
+        };
+
+        let tx = &yuv_tx.bitcoin_tx;
+
+        let inputs = tx
+            .input
+            .iter()
+            .zip(0u32..)
+            .map(|(txin, vin)| DecodedInput {
+                vin,
+                previous_output: txin.previous_output,
+                proof: input_proofs.get(&vin).map(DecodedProof::from),
+            })
+            .collect();
+
+        let outputs = tx
+            .output
+            .iter()
+            .zip(0u32..)
+            .map(|(txout, vout)| DecodedOutput {
+                vout,
+                value: txout.value,
+                script_pubkey: txout.script_pubkey.clone(),
+                proof: output_proofs.get(&vout).map(DecodedProof::from),
+                announcement: announcement_from_script(&txout.script_pubkey)
+                    .ok()
+                    .map(DecodedAnnouncement::from),
+            })
+            .collect();
+
+        Self {
+            txid: tx.txid(),
+            version: tx.version,
+            lock_time: tx.lock_time.0,
+            tx_type,
+            inputs,
+            outputs,
+        }
+    }
+}
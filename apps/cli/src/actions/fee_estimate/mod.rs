@@ -0,0 +1,164 @@
+use bdk::FeeRate;
+use clap::{Args, ValueEnum};
+use color_eyre::eyre::{self, bail, eyre};
+use ydk::types::{FeeRateBounds, FeeRateStrategy};
+
+use crate::context::Context;
+
+/// Virtual size of a typical transfer transaction with one YUV input and two
+/// YUV outputs (recipient and change) funded by one p2wpkh input.
+const TYPICAL_TRANSFER_VSIZE: &str = "250";
+
+/// Default number of blocks in which transaction should be confirmed.
+const DEFAULT_TARGET: usize = 2;
+
+#[derive(Clone, Copy, Debug, ValueEnum)]
+pub enum StrategyKind {
+    Estimate,
+    Manual,
+    TryEstimate,
+    AbsoluteFee,
+}
+
+#[derive(Args, Debug)]
+pub struct FeeEstimateArgs {
+    /// Fee rate strategy to resolve. If not specified, the one from the config is used.
+    #[clap(long, value_enum)]
+    pub strategy: Option<StrategyKind>,
+
+    /// Number of blocks in which transaction should be confirmed.
+    #[clap(long, default_value_t = DEFAULT_TARGET)]
+    pub target: usize,
+
+    /// Fee rate in sat/vB for `manual` strategy or fallback for `try-estimate` one.
+    #[clap(long)]
+    pub fee_rate: Option<f32>,
+
+    /// The same as `--fee-rate`, but in sat/kvB.
+    #[clap(long, conflicts_with = "fee_rate")]
+    pub fee_rate_kvb: Option<f32>,
+
+    /// Fee in satoshis for `absolute-fee` strategy.
+    #[clap(long)]
+    pub fee: Option<u64>,
+
+    /// Min estimated fee rate in sat/vB, the lower one is raised to it.
+    #[clap(long)]
+    pub min_fee_rate: Option<f32>,
+
+    /// Max estimated fee rate in sat/vB. The higher one is an error for `estimate` strategy,
+    /// and is replaced with the fallback for `try-estimate` one.
+    #[clap(long)]
+    pub max_fee_rate: Option<f32>,
+
+    /// Print projected fee for a transaction of this virtual size. If the value
+    /// is omitted, the size of a typical transfer is used.
+    #[clap(long, num_args = 0..=1, default_missing_value = TYPICAL_TRANSFER_VSIZE)]
+    pub vsize: Option<usize>,
+}
+
+pub async fn run(args: FeeEstimateArgs, mut context: Context) -> eyre::Result<()> {
+    let strategy = match args.strategy {
+        Some(kind) => {
+            let bounds = FeeRateBounds {
+                min: args.min_fee_rate,
+                max: args.max_fee_rate,
+                clamp_max: false,
+            };
+
+            let fee_rate = args.fee_rate.or(args.fee_rate_kvb.map(|kvb| kvb / 1000.0));
+
+            strategy_from_args(kind, args.target, fee_rate, args.fee, bounds)?
+        }
+        None => context.config()?.fee_rate_strategy,
+    };
+
+    if let FeeRateStrategy::AbsoluteFee { fee } = strategy {
+        println!("Strategy: absolute fee ({fee} sat)");
+
+        if let Some(vsize) = args.vsize {
+            println!(
+                "Projected fee rate: {} sat/vB for {vsize} vB",
+                fee as f32 / vsize as f32
+            );
+        }
+
+        return Ok(());
+    }
+
+    let blockchain = context.blockchain()?;
+
+    let (fee_rate, source) = match strategy {
+        FeeRateStrategy::Estimate { target, .. } => {
+            println!("Strategy: estimate (target {target} blocks)");
+
+            (strategy.get_fee_rate(&blockchain)?, "estimated")
+        }
+        FeeRateStrategy::Manual { .. } => {
+            println!("Strategy: manual");
+
+            (strategy.get_fee_rate(&blockchain)?, "manual")
+        }
+        FeeRateStrategy::TryEstimate {
+            fee_rate,
+            target,
+            bounds,
+        } => {
+            println!("Strategy: try estimate (target {target} blocks, fallback {fee_rate} sat/vB)");
+
+            // Resolve estimation separately to show if the fallback is used.
+            let estimate = FeeRateStrategy::Estimate { target, bounds };
+
+            match estimate.get_fee_rate(&blockchain) {
+                Ok(estimated) => (estimated, "estimated"),
+                Err(err) => {
+                    println!("Estimation failed: {err:#}");
+
+                    (strategy.get_fee_rate(&blockchain)?, "fallback")
+                }
+            }
+        }
+        FeeRateStrategy::AbsoluteFee { .. } => unreachable!("absolute fee is resolved above"),
+    };
+
+    println!("Fee rate: {} sat/vB ({source})", fee_rate.as_sat_per_vb());
+
+    if let Some(vsize) = args.vsize {
+        print_projected_fee(fee_rate, vsize);
+    }
+
+    Ok(())
+}
+
+fn strategy_from_args(
+    kind: StrategyKind,
+    target: usize,
+    fee_rate: Option<f32>,
+    fee: Option<u64>,
+    bounds: FeeRateBounds,
+) -> eyre::Result<FeeRateStrategy> {
+    let strategy = match (kind, fee_rate) {
+        (StrategyKind::Estimate, _) => FeeRateStrategy::Estimate { target, bounds },
+        (StrategyKind::Manual, Some(fee_rate)) => FeeRateStrategy::Manual { fee_rate },
+        (StrategyKind::TryEstimate, Some(fee_rate)) => FeeRateStrategy::TryEstimate {
+            fee_rate,
+            target,
+            bounds,
+        },
+        (StrategyKind::AbsoluteFee, _) => FeeRateStrategy::AbsoluteFee {
+            fee: fee.ok_or_else(|| eyre!("--fee is required for {kind:?} strategy"))?,
+        },
+        (StrategyKind::Manual | StrategyKind::TryEstimate, None) => {
+            bail!("--fee-rate or --fee-rate-kvb is required for {kind:?} strategy")
+        }
+    };
+
+    Ok(strategy)
+}
+
+fn print_projected_fee(fee_rate: FeeRate, vsize: usize) {
+    println!(
+        "Projected fee: {} sat for {vsize} vB",
+        fee_rate.fee_vb(vsize)
+    );
+}
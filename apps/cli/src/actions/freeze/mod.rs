@@ -1,9 +1,11 @@
-use bdk::blockchain::Blockchain;
 use bitcoin::{OutPoint, Txid};
 use clap::Args;
 use color_eyre::eyre::{self, Context as EyreContext};
 
-use crate::context::Context;
+use crate::{
+    actions::broadcast::{broadcast_with_retry, BroadcastArgs},
+    context::Context,
+};
 
 #[derive(Args, Debug)]
 pub struct FreezeArgs {
@@ -11,6 +13,8 @@ pub struct FreezeArgs {
     pub txid: Txid,
     /// Output index
     pub vout: u32,
+    #[clap(flatten)]
+    pub broadcast: BroadcastArgs,
 }
 pub type UnfreezeArgs = FreezeArgs;
 
@@ -23,9 +27,28 @@ pub async fn run(args: FreezeArgs, mut context: Context) -> eyre::Result<()> {
     let outpoint = OutPoint::new(args.txid, args.vout);
     let yuv_tx = wallet
         .create_freeze(outpoint, config.fee_rate_strategy, &blockchain)
+        .await
         .wrap_err("failed to create freeze transaction")?;
 
-    blockchain.broadcast(&yuv_tx.bitcoin_tx)?;
+    broadcast_with_retry(&blockchain, &yuv_tx.bitcoin_tx, &args.broadcast).await?;
+    println!("Transaction broadcasted: {}", yuv_tx.bitcoin_tx.txid());
+
+    Ok(())
+}
+
+pub async fn run_unfreeze(args: UnfreezeArgs, mut context: Context) -> eyre::Result<()> {
+    let blockchain = context.blockchain()?;
+    let wallet = context.wallet().await?;
+
+    let config = context.config()?;
+
+    let outpoint = OutPoint::new(args.txid, args.vout);
+    let yuv_tx = wallet
+        .create_unfreeze(outpoint, config.fee_rate_strategy, &blockchain)
+        .await
+        .wrap_err("failed to create unfreeze transaction")?;
+
+    broadcast_with_retry(&blockchain, &yuv_tx.bitcoin_tx, &args.broadcast).await?;
     println!("Transaction broadcasted: {}", yuv_tx.bitcoin_tx.txid());
 
     Ok(())
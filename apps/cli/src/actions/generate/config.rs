@@ -36,7 +36,8 @@ pub(crate) fn run(args: GenerateConfigArgs, context: Context) -> eyre::Result<()
     let (priv_key, _pubkey) = secp_ctx.generate_keypair(&mut thread_rng());
 
     let config = Config {
-        private_key: PrivateKey::new(priv_key, args.network),
+        private_key: Some(PrivateKey::new(priv_key, args.network)),
+        key_file: None,
         bitcoin_provider: BitcoinProviderConfig::BitcoinRpc(BitcoinRpcConfig {
             url: "http://127.0.0.1:18443".to_string(),
             network: args.network,
@@ -55,5 +56,7 @@ pub(crate) fn run(args: GenerateConfigArgs, context: Context) -> eyre::Result<()
 
     config.save_to_file(args.output)?;
 
+    println!("Private key is saved in plaintext, encrypt it with `wallet encrypt` before using it beyond testing");
+
     Ok(())
 }
@@ -0,0 +1,39 @@
+use color_eyre::eyre;
+use futures::{pin_mut, TryStreamExt};
+use ydk::{
+    history::{HistoryEntry, TxDirection},
+    labels::LabelRef,
+};
+use yuv_types::YuvTxType;
+
+use crate::context::Context;
+
+pub(crate) async fn run(mut context: Context) -> eyre::Result<()> {
+    let wallet = context.wallet().await?;
+    let labels = wallet.labels().await?;
+
+    let history = wallet.history()?;
+    pin_mut!(history);
+
+    while let Some(HistoryEntry { tx, direction }) = history.try_next().await? {
+        let txid = tx.bitcoin_tx.txid();
+
+        let direction = match direction {
+            TxDirection::Received => "received",
+            TxDirection::Sent => "sent",
+        };
+        let tx_type = match tx.tx_type {
+            YuvTxType::Issue { .. } => "issue",
+            YuvTxType::Transfer { .. } => "transfer",
+            YuvTxType::Announcement(_) => "announcement",
+        };
+        let label = labels
+            .get(LabelRef::Tx(txid))
+            .map(|label| format!(" ({label})"))
+            .unwrap_or_default();
+
+        println!("{txid} {direction} {tx_type}{label}");
+    }
+
+    Ok(())
+}
@@ -1,10 +1,16 @@
-use bdk::blockchain::Blockchain;
 use clap::Args;
 use color_eyre::eyre::{self, bail};
 use yuv_pixels::Chroma;
 use yuv_rpc_api::transactions::YuvTransactionsRpcClient;
 
-use crate::{actions::transfer::process_satoshis, context::Context};
+use crate::{
+    actions::{
+        broadcast::{broadcast_with_retry, check_mempool_acceptance, BroadcastArgs},
+        chroma::get_chroma_info,
+        transfer::process_satoshis,
+    },
+    context::Context,
+};
 
 pub const DEFAULT_SATOSHIS: u64 = 1000;
 
@@ -16,12 +22,13 @@ pub struct IssueArgs {
     /// used for all recipients.
     #[clap(long, short, num_args = 1.., default_values_t = vec![DEFAULT_SATOSHIS])]
     pub satoshis: Vec<u64>,
-    /// YUV token amount
+    /// YUV token amount, in the token's units respecting its decimals (e.g. `10.5`).
     #[clap(long = "amount", num_args = 1..)]
-    pub amounts: Vec<u128>,
-    /// Public key of the recipient.
-    #[clap(long = "recipient", num_args = 1.., value_parser = Chroma::from_address)]
-    pub recipients: Vec<Chroma>,
+    pub amounts: Vec<String>,
+    /// Public key or P2TR address of the recipient. Addresses with only a hash of the key
+    /// (e.g. P2WPKH) can't be used, as the pixel is tweaked by the key itself.
+    #[clap(long = "recipient", num_args = 1..)]
+    pub recipients: Vec<String>,
     /// Provide proof of the transaction to YUV node.
     #[clap(long)]
     pub do_not_provide_proofs: bool,
@@ -30,6 +37,12 @@ pub struct IssueArgs {
     /// It's worth noting that change from regular satoshis will be tweaked.
     #[clap(long)]
     pub drain_tweaked_satoshis: bool,
+    /// Check with `testmempoolaccept` that the Bitcoin node would accept the transaction before
+    /// broadcasting it, printing the rejection reason if it wouldn't.
+    #[clap(long, default_value_t = true, action = clap::ArgAction::Set)]
+    pub check_first: bool,
+    #[clap(flatten)]
+    pub broadcast: BroadcastArgs,
 }
 
 pub async fn run(
@@ -39,6 +52,8 @@ pub async fn run(
         satoshis,
         do_not_provide_proofs,
         drain_tweaked_satoshis,
+        check_first,
+        broadcast,
     }: IssueArgs,
     mut ctx: Context,
 ) -> eyre::Result<()> {
@@ -47,16 +62,27 @@ pub async fn run(
     }
 
     let satoshis = process_satoshis(satoshis, amounts.len())?;
+    let recipients = recipients
+        .iter()
+        .map(|recipient| ctx.recipient(recipient))
+        .collect::<eyre::Result<Vec<_>>>()?;
 
     let wallet = ctx.wallet().await?;
     let blockchain = ctx.blockchain()?;
     let cfg = ctx.config()?;
+    let client = ctx.yuv_client()?;
+
+    let info = get_chroma_info(&client, Chroma::from(wallet.public_key())).await?;
+    let amounts = amounts
+        .iter()
+        .map(|amount| info.parse_amount(amount))
+        .collect::<Result<Vec<_>, _>>()?;
 
     let tx = {
         let mut builder = wallet.build_issuance()?;
 
         for ((recipient, amount), satoshis) in recipients.iter().zip(amounts).zip(satoshis) {
-            builder.add_recipient(&recipient.public_key().inner, amount, satoshis);
+            builder.add_recipient(&recipient.inner, amount, satoshis);
         }
 
         builder
@@ -66,11 +92,13 @@ pub async fn run(
         builder.finish(&blockchain).await?
     };
 
+    if check_first {
+        check_mempool_acceptance(&mut ctx, &tx.bitcoin_tx).await?;
+    }
+
     let tx_type = tx.tx_type.clone();
-    blockchain.broadcast(&tx.bitcoin_tx)?;
+    broadcast_with_retry(&blockchain, &tx.bitcoin_tx, &broadcast).await?;
     if !do_not_provide_proofs {
-        let client = ctx.yuv_client()?;
-
         client.provide_yuv_proof(tx.clone()).await?;
     }
 
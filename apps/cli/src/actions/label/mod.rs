@@ -0,0 +1,89 @@
+use std::{path::PathBuf, str::FromStr};
+
+use bitcoin::{OutPoint, Txid};
+use clap::{Args, Subcommand};
+use color_eyre::eyre::{self, Context as _};
+use ydk::labels::LabelRef;
+
+use crate::context::Context;
+
+#[derive(Subcommand, Debug)]
+pub enum LabelCommands {
+    /// Attach the label to the transaction or output, replacing the previous one. The empty
+    /// label removes it.
+    Set(SetArgs),
+    /// List the labels of the transactions and outputs.
+    List,
+    /// Export the labels in BIP-329 format.
+    Export(ExportArgs),
+    /// Import the labels in BIP-329 format, replacing the existing ones of the same transactions
+    /// and outputs.
+    Import(ImportArgs),
+}
+
+#[derive(Args, Debug)]
+pub struct SetArgs {
+    /// Transaction id, or the output as `txid:vout`.
+    #[clap(value_parser = parse_label_ref)]
+    pub reference: LabelRef,
+    pub label: String,
+}
+
+#[derive(Args, Debug)]
+pub struct ExportArgs {
+    /// Path to the file to write the labels to. If not set, they are printed.
+    #[clap(long, short)]
+    pub output: Option<PathBuf>,
+}
+
+#[derive(Args, Debug)]
+pub struct ImportArgs {
+    /// Path to the file with the labels.
+    pub file: PathBuf,
+}
+
+pub async fn run(cmd: LabelCommands, mut context: Context) -> eyre::Result<()> {
+    let wallet = context.wallet().await?;
+
+    match cmd {
+        LabelCommands::Set(SetArgs { reference, label }) => {
+            wallet.set_label(reference, label).await?;
+        }
+        LabelCommands::List => {
+            let labels = wallet.labels().await?;
+
+            for (txid, label) in labels.txs {
+                println!("{txid} {label}");
+            }
+            for (OutPoint { txid, vout }, label) in labels.outputs {
+                println!("{txid}:{vout} {label}");
+            }
+        }
+        LabelCommands::Export(ExportArgs { output }) => {
+            let export = wallet.export_labels().await?;
+
+            match output {
+                Some(path) => std::fs::write(&path, export)
+                    .wrap_err_with(|| format!("failed to write {}", path.display()))?,
+                None => print!("{export}"),
+            }
+        }
+        LabelCommands::Import(ImportArgs { file }) => {
+            let import = std::fs::read_to_string(&file)
+                .wrap_err_with(|| format!("failed to read {}", file.display()))?;
+
+            let imported = wallet.import_labels(&import).await?;
+            println!("Imported {imported} labels");
+        }
+    }
+
+    Ok(())
+}
+
+fn parse_label_ref(reference: &str) -> eyre::Result<LabelRef> {
+    if reference.contains(':') {
+        return Ok(LabelRef::Output(OutPoint::from_str(reference)?));
+    }
+
+    Ok(LabelRef::Tx(Txid::from_str(reference)?))
+}
@@ -7,38 +7,53 @@ use log::LevelFilter;
 use simplelog::{ColorChoice, TermLogger, TerminalMode};
 
 use self::{
+    consolidate::ConsolidateArgs,
     convert::ConvertCommands,
+    decode::DecodeArgs,
+    fee_estimate::FeeEstimateArgs,
     freeze::{FreezeArgs, UnfreezeArgs},
     generate::GenerateCommands,
     get::GetArgs,
     issue::IssueArgs,
+    label::LabelCommands,
     provide::ProvideArgs,
     transfer::TransferArgs,
     utxos::UtxosArgs,
     validate::ValidateArgs,
+    verify::VerifyArgs,
     wallet::WalletCommands,
+    watch::WatchArgs,
 };
 use crate::context::Context;
 
 mod balances;
+mod broadcast;
 #[cfg(feature = "bulletproof")]
 mod bulletproof;
 mod chroma;
+mod consolidate;
 mod convert;
+mod decode;
+mod fee_estimate;
 mod freeze;
 mod generate;
 mod get;
+mod history;
 mod issue;
+mod label;
 mod p2tr;
 mod p2wpkh;
 mod proof;
 mod provide;
+pub(crate) mod recipient;
 mod rpc_args;
 mod sweep;
 mod transfer;
 mod utxos;
 mod validate;
+mod verify;
 mod wallet;
+mod watch;
 
 #[derive(Parser, Debug)]
 pub struct Cli {
@@ -51,6 +66,10 @@ pub struct Cli {
 
     #[clap(short, long, default_value = "config.toml")]
     pub config: PathBuf,
+
+    /// Allow the private key in the config to be in plaintext, which is meant for testing only.
+    #[clap(long, env = "YUV_CLI_PLAINTEXT_KEY")]
+    pub plaintext_key: bool,
 }
 
 #[derive(Subcommand, Debug)]
@@ -76,9 +95,17 @@ pub enum Commands {
     /// Outputs will be sweeped to a p2wpkh address.
     Sweep,
 
+    /// Merge the smallest UTXOs of the token into one, if it saves the fees of the future
+    /// transfers.
+    Consolidate(ConsolidateArgs),
+
     /// Validate pixel proof of provided transaction.
     Validate(ValidateArgs),
 
+    /// Verify the YUV transaction from the file offline, printing the result of each proof's
+    /// check. With `--onchain`, the proofs are checked against the transaction from the chain.
+    Verify(VerifyArgs),
+
     /// Send freeze transaction
     Freeze(FreezeArgs),
 
@@ -91,13 +118,32 @@ pub enum Commands {
     /// Get transaction from node
     Get(GetArgs),
 
+    /// Wait until the transaction is attached by the node, printing the changes of its status.
+    /// Fails if the transaction is rejected or isn't attached in time.
+    Watch(WatchArgs),
+
+    /// Decode consensus encoded YUV transaction and print it.
+    Decode(DecodeArgs),
+
+    /// Resolve the fee rate strategy and print the resulting fee rate.
+    FeeEstimate(FeeEstimateArgs),
+
     /// Get a list of unspent transaction outputs with amounts
     Utxos(UtxosArgs),
 
+    /// List the transactions sent and received by the wallet, in the order they are attached by
+    /// the node.
+    History,
+
     /// Provide abortion and syncing of the wallet
     #[command(subcommand)]
     Wallet(WalletCommands),
 
+    /// Attach local labels to the transactions and outputs, and export or import them in
+    /// BIP-329 format.
+    #[command(subcommand)]
+    Label(LabelCommands),
+
     /// Get the p2wpkh address of the current user.
     P2WPKH,
 
@@ -132,7 +178,7 @@ impl Cli {
             )?;
         }
 
-        let context = Context::new(self.config);
+        let context = Context::new(self.config, self.plaintext_key);
         execute_command(self.command, context).await
     }
 }
@@ -144,19 +190,26 @@ async fn execute_command(command: Commands, context: Context) -> eyre::Result<()
         Cmd::Issue(args) => issue::run(args, context).await,
         Cmd::Transfer(args) => transfer::run(args, context).await,
         Cmd::Validate(args) => validate::run(args, context).await,
+        Cmd::Verify(args) => verify::run(args, context).await,
         Cmd::Freeze(args) => freeze::run(args, context).await,
-        Cmd::Unfreeze(args) => freeze::run(args, context).await,
+        Cmd::Unfreeze(args) => freeze::run_unfreeze(args, context).await,
         Cmd::Provide(args) => provide::run(args, context).await,
         Cmd::Get(args) => get::run(args, context).await,
+        Cmd::Watch(args) => watch::run(args, context).await,
+        Cmd::Decode(args) => decode::run(args),
+        Cmd::FeeEstimate(args) => fee_estimate::run(args, context).await,
         Cmd::Balances => balances::run(context).await,
         Cmd::Utxos(args) => utxos::run(args, context).await,
+        Cmd::History => history::run(context).await,
         Cmd::Wallet(cmd) => wallet::run(cmd, context).await,
+        Cmd::Label(cmd) => label::run(cmd, context).await,
         #[cfg(feature = "bulletproof")]
         Cmd::Bulletproof(cmd) => bulletproof::run(cmd, context).await,
         Cmd::Convert(args) => convert::run(args),
         Cmd::P2WPKH => p2wpkh::run(context),
         Cmd::P2TR => p2tr::run(context),
         Cmd::Sweep => sweep::run(context).await,
+        Cmd::Consolidate(args) => consolidate::run(args, context).await,
         Cmd::Chroma(cmd) => chroma::run(cmd, context).await,
     }
 }
@@ -5,9 +5,9 @@ use crate::context::Context;
 
 pub(crate) fn run(mut context: Context) -> eyre::Result<()> {
     let config = context.config()?;
-    let ctx = context.secp_ctx();
+    let private_key = context.private_key()?;
 
-    let pubkey = config.private_key.public_key(ctx);
+    let pubkey = private_key.public_key(context.secp_ctx());
 
     let address = Chroma::from(pubkey).to_address(config.network());
 
@@ -6,9 +6,9 @@ use crate::context::Context;
 /// Get the P2WPKH address for the given config.
 pub(crate) fn run(mut context: Context) -> eyre::Result<()> {
     let config = context.config()?;
-    let ctx = context.secp_ctx();
+    let private_key = context.private_key()?;
 
-    let pubkey = config.private_key.public_key(ctx);
+    let pubkey = private_key.public_key(context.secp_ctx());
 
     let address = Address::p2wpkh(&pubkey, config.network())?;
 
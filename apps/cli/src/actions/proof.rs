@@ -1,6 +1,6 @@
 use std::collections::BTreeMap;
 
-use bitcoin::PublicKey;
+use bitcoin::{Network, PublicKey};
 use clap::Args;
 use color_eyre::eyre::{self, bail};
 use yuv_pixels::{Chroma, Pixel, PixelProof};
@@ -10,8 +10,7 @@ use yuv_types::TransferProofs;
 pub struct ProofListArgs {
     /// Chroma of the pixel.
     #[clap(long)]
-    #[arg(value_parser = Chroma::from_address)]
-    pub chroma: Chroma,
+    pub chroma: String,
 
     /// Number of the input in transaction.
     #[clap(long, num_args = 0..)]
@@ -31,7 +30,10 @@ pub struct ProofListArgs {
 }
 
 impl ProofListArgs {
-    pub(crate) fn into_proof_maps(self) -> eyre::Result<TransferProofs> {
+    /// Form the proofs, checking that the chroma is for the given `network`.
+    pub(crate) fn into_proof_maps(self, network: Network) -> eyre::Result<TransferProofs> {
+        let chroma = Chroma::from_address_for_network(&self.chroma, network)?;
+
         let inputs_number = self.vin.len();
         let outputs_number = self.vout.len();
 
@@ -59,7 +61,7 @@ impl ProofListArgs {
         // Convert inputs and outputs into [`PixelProof`]s
         let inputs = inputs
             .map(|((recipient, amount), vin)| {
-                let pixel = Pixel::new(*amount, self.chroma);
+                let pixel = Pixel::new(*amount, chroma);
 
                 (vin, PixelProof::sig(pixel, recipient.inner))
             })
@@ -67,7 +69,7 @@ impl ProofListArgs {
 
         let outputs = outputs
             .map(|((recipient, amount), vout)| {
-                let pixel = Pixel::new(*amount, self.chroma);
+                let pixel = Pixel::new(*amount, chroma);
 
                 (vout, PixelProof::sig(pixel, recipient.inner))
             })
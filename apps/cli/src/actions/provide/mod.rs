@@ -51,7 +51,7 @@ pub(crate) async fn run(
         );
     };
 
-    let TransferProofs { input, output } = proofs.into_proof_maps()?;
+    let TransferProofs { input, output } = proofs.into_proof_maps(context.config()?.network())?;
 
     let tx_type = if input.is_empty() {
         let announcement = form_issue_announcement(output.clone().into_values().collect())?;
@@ -0,0 +1,91 @@
+use std::str::FromStr;
+
+use bitcoin::{Network, PublicKey};
+use color_eyre::eyre::{self, bail, Context};
+use yuv_pixels::{Chroma, ChromaParseError};
+
+/// Parse the recipient of the pixel from either a hex encoded public key or a P2TR address,
+/// which must be for the given `network`.
+///
+/// Only P2TR addresses contain the key itself, for the others (e.g. P2WPKH) the public key must
+/// be provided instead, see [`ydk::txbuilder::TransferTransactionBuilder::add_recipient`].
+pub(crate) fn parse_recipient(recipient: &str, network: Network) -> eyre::Result<PublicKey> {
+    if let Ok(pubkey) = PublicKey::from_str(recipient) {
+        return Ok(pubkey);
+    }
+
+    match Chroma::from_address_for_network(recipient, network) {
+        Ok(chroma) => Ok(chroma.public_key()),
+        Err(ChromaParseError::InvalidAddress(_)) => {
+            bail!("'{recipient}' is neither a public key nor an address")
+        }
+        Err(
+            ChromaParseError::InvalidAddressType
+            | ChromaParseError::InvalidWitnessProgramVersion(_)
+            | ChromaParseError::InvalidWitnessProgramLength(_),
+        ) => bail!(
+            "Address '{recipient}' contains only a hash of the recipient's public key, \
+            but the pixel is tweaked by the key itself. Provide the recipient's public key \
+            or P2TR address instead"
+        ),
+        Err(err) => Err(err).wrap_err_with(|| format!("Invalid recipient '{recipient}'")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bitcoin::secp256k1::{Secp256k1, SecretKey};
+    use bitcoin::Address;
+
+    use super::*;
+
+    fn pubkey() -> PublicKey {
+        let secret_key = SecretKey::from_slice(&[3; 32]).unwrap();
+
+        PublicKey::new(secret_key.public_key(&Secp256k1::new()))
+    }
+
+    #[test]
+    fn test_recipient_is_parsed_from_pubkey_or_p2tr_address() {
+        let pubkey = pubkey();
+        assert_eq!(
+            parse_recipient(&pubkey.to_string(), Network::Regtest).unwrap(),
+            pubkey
+        );
+
+        let chroma = Chroma::from(pubkey);
+        let address = chroma.to_address(Network::Regtest).to_string();
+        assert_eq!(
+            parse_recipient(&address, Network::Regtest).unwrap(),
+            chroma.public_key()
+        );
+    }
+
+    #[test]
+    fn test_recipient_address_of_other_network_is_rejected() {
+        let address = Chroma::from(pubkey())
+            .to_address(Network::Bitcoin)
+            .to_string();
+
+        let err = parse_recipient(&address, Network::Regtest).unwrap_err();
+        assert!(
+            matches!(
+                err.downcast_ref::<ChromaParseError>(),
+                Some(ChromaParseError::NetworkMismatch { .. })
+            ),
+            "{err:?}"
+        );
+    }
+
+    #[test]
+    fn test_recipient_address_with_key_hash_is_rejected() {
+        let address = Address::p2wpkh(&pubkey(), Network::Regtest)
+            .unwrap()
+            .to_string();
+
+        let err = parse_recipient(&address, Network::Regtest).unwrap_err();
+        assert!(err.to_string().contains("only a hash"), "{err}");
+
+        assert!(parse_recipient("not a recipient", Network::Regtest).is_err());
+    }
+}
@@ -1,32 +1,43 @@
-use std::usize;
-
-use crate::{check_equal_lengths, context::Context};
-use bdk::blockchain::Blockchain;
+use crate::{
+    actions::{
+        broadcast::{
+            broadcast_with_retry, check_mempool_acceptance, send_raw_yuv_tx_with_retry,
+            BroadcastArgs,
+        },
+        chroma::get_chroma_info,
+    },
+    check_equal_lengths,
+    context::Context,
+};
 use clap::Args;
 use color_eyre::eyre::{self, Ok};
-use yuv_pixels::Chroma;
-use yuv_rpc_api::transactions::YuvTransactionsRpcClient;
 
 const DEFAULT_SATOSHIS: u64 = 1000;
 
 #[derive(Args, Debug)]
 pub struct TransferArgs {
-    /// Amount to send.
+    /// Amount to send, in the token's units respecting its decimals (e.g. `10.5`).
     #[clap(long, short, num_args = 1..)]
-    pub amount: Vec<u128>,
+    pub amount: Vec<String>,
 
     /// Satoshis to spend. Specify it either once to override the default,
     /// or per chroma to use a different number of satoshis in each output.
     #[clap(long, short, num_args = 1.., default_values_t = vec![DEFAULT_SATOSHIS])]
     pub satoshis: Vec<u64>,
 
-    /// Type of the token, public key of the issuer.
-    #[clap(long, short, num_args = 1.., value_parser = Chroma::from_address)]
-    pub chroma: Vec<Chroma>,
+    /// Type of the token, P2TR address of the issuer.
+    #[clap(long, short, num_args = 1..)]
+    pub chroma: Vec<String>,
+
+    /// The public key or P2TR address of the receiver. Addresses with only a hash of the key
+    /// (e.g. P2WPKH) can't be used, as the pixel is tweaked by the key itself.
+    #[clap(long, short, num_args = 1..)]
+    pub recipient: Vec<String>,
 
-    /// The public key of the receiver.
-    #[clap(long, short, num_args = 1.., value_parser = Chroma::from_address)]
-    pub recipient: Vec<Chroma>,
+    /// The public key or P2TR address the change of the tokens is sent to, e.g. of a cold
+    /// wallet. If not set, the change is sent back to the wallet.
+    #[clap(long)]
+    pub change_address: Option<String>,
 
     /// Provide proof of the transaction to YUV node or not.
     #[clap(long)]
@@ -37,6 +48,13 @@ pub struct TransferArgs {
     /// It's worth noting that change from regular satoshis will be tweaked.
     #[clap(long)]
     pub drain_tweaked_satoshis: bool,
+    /// Check with `testmempoolaccept` that the Bitcoin node would accept the transaction before
+    /// broadcasting it, printing the rejection reason if it wouldn't.
+    #[clap(long, default_value_t = true, action = clap::ArgAction::Set)]
+    pub check_first: bool,
+
+    #[clap(flatten)]
+    pub broadcast: BroadcastArgs,
 }
 
 // TODO: refactor this, please...
@@ -46,8 +64,11 @@ pub async fn run(
         satoshis,
         chroma,
         recipient,
+        change_address,
         do_not_provide_proofs,
         drain_tweaked_satoshis,
+        check_first,
+        broadcast,
     }: TransferArgs,
     mut ctx: Context,
 ) -> eyre::Result<()> {
@@ -57,17 +78,35 @@ pub async fn run(
     let satoshis = process_satoshis(satoshis, chroma.len())?;
     let blockchain = ctx.blockchain()?;
     let cfg = ctx.config()?;
+    let client = ctx.yuv_client()?;
+    let chroma = chroma
+        .iter()
+        .map(|chroma| ctx.chroma(chroma))
+        .collect::<eyre::Result<Vec<_>>>()?;
+    let recipient = recipient
+        .iter()
+        .map(|recipient| ctx.recipient(recipient))
+        .collect::<eyre::Result<Vec<_>>>()?;
+    let change_address = change_address
+        .map(|change_address| ctx.recipient(&change_address))
+        .transpose()?;
+
+    let mut raw_amounts = Vec::with_capacity(amount.len());
+    for (amount, chroma) in amount.iter().zip(&chroma) {
+        let info = get_chroma_info(&client, *chroma).await?;
+
+        raw_amounts.push(info.parse_amount(amount)?);
+    }
 
     let tx = {
         let mut builder = wallet.build_transfer()?;
 
         for i in 0..chroma.len() {
-            builder.add_recipient(
-                chroma[i],
-                &recipient[i].public_key().inner,
-                amount[i],
-                satoshis[i],
-            );
+            builder.add_recipient(chroma[i], &recipient[i].inner, raw_amounts[i], satoshis[i]);
+        }
+
+        if let Some(change_address) = change_address {
+            builder.set_change_recipient(&change_address.inner);
         }
 
         builder
@@ -77,12 +116,14 @@ pub async fn run(
         builder.finish(&blockchain).await?
     };
 
+    if check_first {
+        check_mempool_acceptance(&mut ctx, &tx.bitcoin_tx).await?;
+    }
+
     if do_not_provide_proofs {
-        blockchain.broadcast(&tx.bitcoin_tx)?;
+        broadcast_with_retry(&blockchain, &tx.bitcoin_tx, &broadcast).await?;
     } else {
-        let client = ctx.yuv_client()?;
-
-        client.send_raw_yuv_tx(tx.clone(), None).await?;
+        send_raw_yuv_tx_with_retry(&client, &tx, &broadcast).await?;
     }
 
     println!("tx id: {}", tx.bitcoin_tx.txid());
@@ -1,51 +1,71 @@
 use bitcoin::{Network, OutPoint};
 use clap::Args;
 use color_eyre::eyre;
-use ydk::wallet::StorageWallet;
+use ydk::{
+    labels::{LabelRef, WalletLabels},
+    wallet::StorageWallet,
+};
 use yuv_pixels::Chroma;
 
 use crate::context::Context;
 
 #[derive(Args, Debug)]
 pub struct UtxosArgs {
-    /// Chroma of the token
-    #[clap(long, value_parser = Chroma::from_address)]
-    pub chroma: Option<Chroma>,
+    /// Chroma of the token, P2TR address of the issuer.
+    #[clap(long)]
+    pub chroma: Option<String>,
 }
 
 pub async fn run(UtxosArgs { chroma }: UtxosArgs, mut ctx: Context) -> eyre::Result<()> {
     let wallet = ctx.wallet().await?;
+    let chroma = chroma.map(|chroma| ctx.chroma(&chroma)).transpose()?;
+    let labels = wallet.labels().await?;
 
     match chroma {
         Some(chroma) => {
-            show_utxos_by_chroma(&wallet, chroma);
+            show_utxos_by_chroma(&wallet, chroma, &labels);
         }
         None => {
-            show_all_utxos(&wallet, ctx.config()?.network());
+            show_all_utxos(&wallet, ctx.config()?.network(), &labels);
         }
     }
 
     Ok(())
 }
 
-fn show_all_utxos(wallet: &StorageWallet, network: Network) {
+fn show_all_utxos(wallet: &StorageWallet, network: Network, labels: &WalletLabels) {
     let utxos = wallet.yuv_utxos();
 
-    for (OutPoint { txid, vout }, proof) in utxos {
+    for (outpoint, proof) in utxos {
+        let OutPoint { txid, vout } = outpoint;
         let pixel = proof.pixel();
 
         println!(
-            "{txid}:{vout:0>2} {chroma} {amount}",
+            "{txid}:{vout:0>2} {chroma} {amount}{label}",
             chroma = pixel.chroma.to_address(network),
-            amount = pixel.luma.amount
+            amount = pixel.luma.amount,
+            label = format_label(labels, outpoint),
         );
     }
 }
 
-fn show_utxos_by_chroma(wallet: &StorageWallet, chroma: Chroma) {
+fn show_utxos_by_chroma(wallet: &StorageWallet, chroma: Chroma, labels: &WalletLabels) {
     let utxos = wallet.utxos_by_chroma(chroma);
 
-    for (OutPoint { txid, vout }, amount) in utxos {
-        println!("{}:{} {}", txid, vout, amount);
+    for (outpoint, amount) in utxos {
+        println!(
+            "{}:{} {}{}",
+            outpoint.txid,
+            outpoint.vout,
+            amount,
+            format_label(labels, outpoint)
+        );
     }
 }
+
+fn format_label(labels: &WalletLabels, outpoint: OutPoint) -> String {
+    labels
+        .get(LabelRef::Output(outpoint))
+        .map(|label| format!(" ({label})"))
+        .unwrap_or_default()
+}
@@ -5,7 +5,7 @@ use bitcoin::Txid;
 use clap::Args;
 use color_eyre::eyre;
 use ydk::txbuilder::form_issue_announcement;
-use yuv_tx_check::{check_transaction, CheckError};
+use yuv_tx_check::{check_transaction, check_transaction_collect_errors, CheckError};
 use yuv_types::{ProofMap, TransferProofs, YuvTransaction, YuvTxType};
 
 use crate::context::Context;
@@ -29,7 +29,7 @@ pub(crate) async fn run(
     let TransferProofs {
         input: input_proofs_map,
         output: output_proofs_map,
-    } = proofs.into_proof_maps()?;
+    } = proofs.into_proof_maps(context.config()?.network())?;
 
     log::debug!("Input proofs: {:?}", input_proofs_map);
     log::debug!("Output proofs: {:?}", output_proofs_map);
@@ -63,10 +63,28 @@ pub async fn check_p2wpkh_tx_by_id(
         },
     };
 
-    check_transaction(&YuvTransaction {
+    let yuv_tx = YuvTransaction {
         bitcoin_tx: tx,
         tx_type: yuv_tx_type,
-    })?;
+    };
+
+    // Report all the invalid proofs at once instead of only the first one.
+    let errors = check_transaction_collect_errors(&yuv_tx)?;
+    if !errors.is_empty() {
+        for (vin, err) in &errors.inputs {
+            eprintln!("Input {vin} has invalid proof: {err}");
+        }
+        for (vout, err) in &errors.outputs {
+            eprintln!("Output {vout} has invalid proof: {err}");
+        }
+
+        eyre::bail!(
+            "{} of the proofs are invalid",
+            errors.inputs.len() + errors.outputs.len()
+        );
+    }
+
+    check_transaction(&yuv_tx)?;
 
     Ok(())
 }
@@ -0,0 +1,100 @@
+use std::path::PathBuf;
+
+use bdk::blockchain::GetTx;
+use clap::Args;
+use color_eyre::eyre::{self, bail, Context as _};
+use yuv_tx_check::{check_transaction, check_transaction_collect_errors, CheckError};
+use yuv_types::{ProofMap, YuvTransaction, YuvTxType};
+
+use crate::context::Context;
+
+use super::decode::{decode_bytes, decode_yuv_tx};
+
+#[derive(Args, Debug)]
+pub struct VerifyArgs {
+    /// Path to the file with the YUV transaction, either in JSON or consensus encoded in hex
+    /// or base64.
+    pub file: PathBuf,
+
+    /// Fetch the Bitcoin transaction from the chain, and check the proofs against it instead of
+    /// the one from the file.
+    #[clap(long)]
+    pub onchain: bool,
+}
+
+pub(crate) async fn run(
+    VerifyArgs { file, onchain }: VerifyArgs,
+    mut context: Context,
+) -> eyre::Result<()> {
+    let content = std::fs::read_to_string(&file)
+        .wrap_err_with(|| format!("failed to read {}", file.display()))?;
+
+    let mut yuv_tx = parse_yuv_tx(&content)?;
+    let txid = yuv_tx.bitcoin_tx.txid();
+
+    println!("Transaction {txid}");
+
+    if onchain {
+        let blockchain = context.blockchain()?;
+
+        let Some(bitcoin_tx) = blockchain.get_tx(&txid)? else {
+            return Err(CheckError::TxNotFound(txid).into());
+        };
+
+        println!("Found on chain, checking the proofs against it");
+        yuv_tx.bitcoin_tx = bitcoin_tx;
+    }
+
+    let errors = check_transaction_collect_errors(&yuv_tx)?;
+
+    let (input_proofs, output_proofs) = proof_maps(&yuv_tx.tx_type);
+    for vin in input_proofs.keys() {
+        match errors.inputs.iter().find(|(index, _)| index == vin) {
+            Some((_, err)) => println!("Input {vin}: FAIL: {err}"),
+            None => println!("Input {vin}: OK"),
+        }
+    }
+    for vout in output_proofs.keys() {
+        match errors.outputs.iter().find(|(index, _)| index == vout) {
+            Some((_, err)) => println!("Output {vout}: FAIL: {err}"),
+            None => println!("Output {vout}: OK"),
+        }
+    }
+
+    if !errors.is_empty() {
+        bail!(
+            "{} of the proofs are invalid",
+            errors.inputs.len() + errors.outputs.len()
+        );
+    }
+
+    check_transaction(&yuv_tx)?;
+
+    println!("Transaction is valid!");
+
+    Ok(())
+}
+
+/// Parse the transaction from JSON, or decode it from the consensus encoded hex or base64.
+fn parse_yuv_tx(content: &str) -> eyre::Result<YuvTransaction> {
+    let content = content.trim();
+
+    if content.starts_with('{') {
+        return serde_json::from_str(content).wrap_err("invalid YUV transaction JSON");
+    }
+
+    decode_yuv_tx(&decode_bytes(content)?)
+}
+
+fn proof_maps(tx_type: &YuvTxType) -> (ProofMap, ProofMap) {
+    match tx_type {
+        YuvTxType::Issue { output_proofs, .. } => {
+            (ProofMap::new(), output_proofs.clone().unwrap_or_default())
+        }
+        YuvTxType::Transfer {
+            input_proofs,
+            output_proofs,
+        } => (input_proofs.clone(), output_proofs.clone()),
+        YuvTxType::Announcement(_) => (ProofMap::new(), ProofMap::new()),
+    }
+}
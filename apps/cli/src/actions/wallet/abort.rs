@@ -13,7 +13,7 @@ pub async fn run(mut ctx: Context) -> eyre::Result<()> {
         bail!("The wallet abort command is not available for Esplora");
     };
 
-    let wallet_name = get_wallet_name(cfg.private_key)?;
+    let wallet_name = get_wallet_name(ctx.private_key()?)?;
     let route = format!("/wallet/{}", wallet_name);
     let bitcoin_client = ctx.bitcoin_client(None, None, Some(route)).await?;
 
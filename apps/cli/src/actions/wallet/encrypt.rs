@@ -0,0 +1,101 @@
+use std::path::{Path, PathBuf};
+
+use clap::Args;
+use color_eyre::eyre::{self, bail, ensure, OptionExt, WrapErr};
+
+use crate::config::{read_passphrase, Config, KeyFile};
+use crate::context::Context;
+
+#[derive(Args, Debug)]
+pub struct EncryptArgs {
+    /// Path to the key file to create. Defaults to the config path with the `key` extension.
+    #[clap(long)]
+    pub key_file: Option<PathBuf>,
+}
+
+/// Encrypt the plaintext private key from the config to the key file, and replace the key in
+/// the config with the path to the file.
+pub fn run(EncryptArgs { key_file }: EncryptArgs, mut ctx: Context) -> eyre::Result<()> {
+    let mut config = ctx.config()?;
+
+    if config.key_file.is_some() {
+        bail!("The config already has the key file");
+    }
+
+    let private_key = config
+        .private_key
+        .ok_or_eyre("The config has no plaintext private key to encrypt")?;
+
+    let passphrase = read_passphrase("New passphrase: ")?;
+    ensure!(!passphrase.is_empty(), "Passphrase must not be empty");
+
+    let confirmation = read_passphrase("Repeat passphrase: ")?;
+    ensure!(passphrase == confirmation, "Passphrases do not match");
+
+    let config_path = ctx.config_path().clone();
+    let key_file = key_file.unwrap_or_else(|| config_path.with_extension("key"));
+
+    ensure!(
+        !key_file.exists(),
+        "The key file {} already exists",
+        key_file.display()
+    );
+
+    // Both the files are written to the temporary ones first, and replace the original ones
+    // only when both are written, so the config never points to a missing key file, and the
+    // key is never lost from both of them.
+    let key_file_tmp = tmp_path(&key_file);
+    let config_tmp = tmp_path(&config_path);
+
+    KeyFile::encrypt(&private_key, &passphrase)?.save_to_file(&key_file_tmp)?;
+
+    let saved = save_config(
+        &mut config,
+        &key_file,
+        &key_file_tmp,
+        &config_tmp,
+        &config_path,
+    );
+    if saved.is_err() {
+        let _ = std::fs::remove_file(&key_file_tmp);
+        let _ = std::fs::remove_file(&config_tmp);
+    }
+    saved?;
+
+    println!("Private key is encrypted to {}", key_file.display());
+
+    Ok(())
+}
+
+/// Save the config with the key file to the temporary file, and move both of them in place.
+fn save_config(
+    config: &mut Config,
+    key_file: &Path,
+    key_file_tmp: &Path,
+    config_tmp: &Path,
+    config_path: &Path,
+) -> eyre::Result<()> {
+    let file_name = key_file
+        .file_name()
+        .ok_or_eyre("The key file path has no file name")?;
+
+    config.private_key = None;
+    config.key_file = Some(key_file_tmp.canonicalize()?.with_file_name(file_name));
+    config.save_to_file(config_tmp.to_path_buf())?;
+
+    std::fs::rename(key_file_tmp, key_file).wrap_err("Failed to move the key file")?;
+    if let Err(err) = std::fs::rename(config_tmp, config_path) {
+        let _ = std::fs::remove_file(key_file);
+        return Err(err).wrap_err("Failed to replace the config");
+    }
+
+    Ok(())
+}
+
+/// Path of the temporary file the content is written to before replacing the one at `path`.
+fn tmp_path(path: &Path) -> PathBuf {
+    let mut tmp = path.as_os_str().to_owned();
+    tmp.push(".tmp");
+
+    PathBuf::from(tmp)
+}
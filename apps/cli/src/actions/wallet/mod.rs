@@ -3,7 +3,10 @@ use color_eyre::eyre;
 
 use crate::context::Context;
 
+use self::encrypt::EncryptArgs;
+
 pub mod abort;
+pub mod encrypt;
 pub mod sync;
 
 #[derive(Subcommand, Debug)]
@@ -12,11 +15,15 @@ pub enum WalletCommands {
     AbortRescan,
     /// Syncs yuv and bitcoin wallets  
     Sync,
+    /// Encrypts the plaintext private key from the config with a passphrase, and replaces it
+    /// with the path to the encrypted key file
+    Encrypt(EncryptArgs),
 }
 
 pub async fn run(cmd: WalletCommands, context: Context) -> eyre::Result<()> {
     match cmd {
         WalletCommands::AbortRescan => abort::run(context).await,
         WalletCommands::Sync => sync::run(context).await,
+        WalletCommands::Encrypt(args) => encrypt::run(args, context),
     }
 }
@@ -0,0 +1,85 @@
+use std::time::{Duration, Instant, SystemTime};
+
+use bitcoin::Txid;
+use clap::Args;
+use color_eyre::eyre::{self, bail};
+use yuv_rpc_api::transactions::{GetRawYuvTransactionResponse, YuvTransactionsRpcClient};
+
+use crate::context::Context;
+
+/// Default time in seconds to wait for the transaction to be attached.
+const DEFAULT_TIMEOUT_SECS: u64 = 600;
+/// Interval between the first requests to the node.
+const MIN_POLL_INTERVAL: Duration = Duration::from_secs(1);
+/// Max interval between the requests, the interval is doubled after each request until it.
+const MAX_POLL_INTERVAL: Duration = Duration::from_secs(30);
+
+#[derive(Args, Debug)]
+pub struct WatchArgs {
+    /// Id of the transaction to watch.
+    pub txid: Txid,
+
+    /// Time in seconds to wait for the transaction to be attached.
+    #[clap(long, default_value_t = DEFAULT_TIMEOUT_SECS)]
+    pub timeout: u64,
+}
+
+pub async fn run(WatchArgs { txid, timeout }: WatchArgs, mut context: Context) -> eyre::Result<()> {
+    let client = context.yuv_client()?;
+
+    let started_at = Instant::now();
+    let deadline = started_at + Duration::from_secs(timeout);
+    let mut interval = MIN_POLL_INTERVAL;
+    let mut last_status = None;
+
+    loop {
+        let response = client.get_raw_yuv_transaction(txid).await?;
+        let status = status_name(&response);
+
+        if last_status != Some(status) {
+            let timestamp = SystemTime::now()
+                .duration_since(SystemTime::UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_secs();
+
+            println!(
+                "{} (+{}s): {}",
+                timestamp,
+                started_at.elapsed().as_secs(),
+                status
+            );
+
+            last_status = Some(status);
+        }
+
+        match response {
+            GetRawYuvTransactionResponse::Attached(_) => return Ok(()),
+            GetRawYuvTransactionResponse::None => {
+                if let Some(invalid) = client.get_invalid_tx(txid).await? {
+                    bail!(
+                        "Transaction is rejected by the node: {}",
+                        invalid.reason.as_deref().unwrap_or("unknown reason")
+                    );
+                }
+            }
+            GetRawYuvTransactionResponse::Pending | GetRawYuvTransactionResponse::Checked => {}
+        }
+
+        let now = Instant::now();
+        if now >= deadline {
+            bail!("Transaction is not attached in {}s", timeout);
+        }
+
+        tokio::time::sleep(interval.min(deadline - now)).await;
+        interval = (interval * 2).min(MAX_POLL_INTERVAL);
+    }
+}
+
+fn status_name(response: &GetRawYuvTransactionResponse) -> &'static str {
+    match response {
+        GetRawYuvTransactionResponse::None => "none",
+        GetRawYuvTransactionResponse::Pending => "pending",
+        GetRawYuvTransactionResponse::Checked => "checked",
+        GetRawYuvTransactionResponse::Attached(_) => "attached",
+    }
+}
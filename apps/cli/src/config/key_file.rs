@@ -0,0 +1,200 @@
+//! Private key of the wallet stored encrypted with a passphrase.
+//!
+//! The key used for encryption is derived from the passphrase with Argon2id, and the WIF of the
+//! private key is encrypted with AES-256-GCM.
+
+use std::path::Path;
+use std::str::FromStr;
+
+use aes_gcm::aead::rand_core::RngCore;
+use aes_gcm::aead::{Aead, AeadCore, KeyInit, OsRng};
+use aes_gcm::{Aes256Gcm, Key, Nonce};
+use argon2::{Algorithm, Argon2, Params, Version};
+use bitcoin::PrivateKey;
+use color_eyre::eyre::{self, eyre, Context};
+use serde::{Deserialize, Serialize};
+use zeroize::Zeroizing;
+
+/// Version of the key file format.
+const KEY_FILE_VERSION: u8 = 1;
+/// Size of the salt for the key derivation in bytes.
+const SALT_SIZE: usize = 16;
+/// Size of the AES-256 key in bytes.
+const KEY_SIZE: usize = 32;
+/// Environment variable to take the passphrase from instead of prompting it.
+const PASSPHRASE_ENV: &str = "YUV_CLI_PASSPHRASE";
+
+/// Content of the encrypted key file. Binary fields are base64 encoded.
+#[derive(Serialize, Deserialize)]
+pub struct KeyFile {
+    pub version: u8,
+    pub kdf: KdfParams,
+    pub salt: String,
+    pub nonce: String,
+    pub ciphertext: String,
+}
+
+/// Parameters of the Argon2id key derivation, kept in the file so they could be changed without
+/// breaking the existing files.
+#[derive(Serialize, Deserialize)]
+pub struct KdfParams {
+    /// Memory size in KiB.
+    pub memory: u32,
+    pub iterations: u32,
+    pub parallelism: u32,
+}
+
+impl Default for KdfParams {
+    fn default() -> Self {
+        Self {
+            memory: Params::DEFAULT_M_COST,
+            iterations: Params::DEFAULT_T_COST,
+            parallelism: Params::DEFAULT_P_COST,
+        }
+    }
+}
+
+impl KeyFile {
+    /// Encrypt the private key with the key derived from the passphrase.
+    pub fn encrypt(private_key: &PrivateKey, passphrase: &str) -> eyre::Result<Self> {
+        let kdf = KdfParams::default();
+
+        let mut salt = [0u8; SALT_SIZE];
+        OsRng.fill_bytes(&mut salt);
+
+        let cipher = cipher(&kdf, passphrase, &salt)?;
+        let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+
+        let wif = Zeroizing::new(private_key.to_wif());
+        let ciphertext = cipher
+            .encrypt(&nonce, wif.as_bytes())
+            .map_err(|_| eyre!("Failed to encrypt the private key"))?;
+
+        Ok(Self {
+            version: KEY_FILE_VERSION,
+            kdf,
+            salt: base64::encode(salt),
+            nonce: base64::encode(nonce),
+            ciphertext: base64::encode(ciphertext),
+        })
+    }
+
+    /// Decrypt the private key with the key derived from the passphrase.
+    pub fn decrypt(&self, passphrase: &str) -> eyre::Result<PrivateKey> {
+        if self.version != KEY_FILE_VERSION {
+            eyre::bail!("Unsupported key file version {}", self.version);
+        }
+
+        let salt = base64::decode(&self.salt).wrap_err("Invalid salt")?;
+        let nonce = base64::decode(&self.nonce).wrap_err("Invalid nonce")?;
+        let ciphertext = base64::decode(&self.ciphertext).wrap_err("Invalid ciphertext")?;
+
+        eyre::ensure!(nonce.len() == 12, "Invalid nonce size {}", nonce.len());
+
+        let cipher = cipher(&self.kdf, passphrase, &salt)?;
+        let wif = Zeroizing::new(
+            cipher
+                .decrypt(Nonce::from_slice(&nonce), ciphertext.as_slice())
+                .map_err(|_| eyre!("Failed to decrypt the private key, wrong passphrase?"))?,
+        );
+
+        let wif = std::str::from_utf8(&wif).wrap_err("Decrypted private key is not a WIF")?;
+
+        PrivateKey::from_str(wif).wrap_err("Decrypted private key is not a WIF")
+    }
+
+    pub fn from_path(path: &Path) -> eyre::Result<Self> {
+        let content = std::fs::read_to_string(path)
+            .wrap_err_with(|| format!("Failed to read the key file {}", path.display()))?;
+
+        serde_json::from_str(&content).wrap_err("Invalid key file")
+    }
+
+    /// Save the key file, which is readable only by the owner on Unix.
+    pub fn save_to_file(&self, path: &Path) -> eyre::Result<()> {
+        use std::io::Write;
+
+        let serialized = serde_json::to_string_pretty(self)?;
+
+        let mut options = std::fs::OpenOptions::new();
+        options.write(true).create_new(true);
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::OpenOptionsExt;
+            options.mode(0o600);
+        }
+
+        options
+            .open(path)
+            .wrap_err_with(|| format!("Failed to create the key file {}", path.display()))?
+            .write_all(serialized.as_bytes())?;
+
+        Ok(())
+    }
+}
+
+/// Read the passphrase from the `YUV_CLI_PASSPHRASE` environment variable, or prompt it from
+/// the terminal if it's not set.
+pub fn read_passphrase(prompt: &str) -> eyre::Result<Zeroizing<String>> {
+    if let Ok(passphrase) = std::env::var(PASSPHRASE_ENV) {
+        return Ok(Zeroizing::new(passphrase));
+    }
+
+    let passphrase =
+        rpassword::prompt_password(prompt).wrap_err("Failed to read the passphrase")?;
+
+    Ok(Zeroizing::new(passphrase))
+}
+
+/// Derive the AES-256 key from the passphrase and create the cipher with it.
+fn cipher(kdf: &KdfParams, passphrase: &str, salt: &[u8]) -> eyre::Result<Aes256Gcm> {
+    let params = Params::new(kdf.memory, kdf.iterations, kdf.parallelism, Some(KEY_SIZE))
+        .map_err(|err| eyre!("Invalid key derivation params: {err}"))?;
+
+    let mut key = Zeroizing::new([0u8; KEY_SIZE]);
+    Argon2::new(Algorithm::Argon2id, Version::V0x13, params)
+        .hash_password_into(passphrase.as_bytes(), salt, key.as_mut())
+        .map_err(|err| eyre!("Failed to derive the key from the passphrase: {err}"))?;
+
+    Ok(Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(key.as_ref())))
+}
+
+#[cfg(test)]
+mod tests {
+    use bitcoin::Network;
+
+    use super::*;
+
+    fn private_key() -> PrivateKey {
+        PrivateKey::from_slice(&[7; 32], Network::Testnet).unwrap()
+    }
+
+    #[test]
+    fn test_key_file_round_trip() -> eyre::Result<()> {
+        let key_file = KeyFile::encrypt(&private_key(), "passphrase")?;
+
+        let serialized = serde_json::to_string(&key_file)?;
+        assert!(
+            !serialized.contains(&private_key().to_wif()),
+            "key file must not contain the plaintext key"
+        );
+
+        let key_file: KeyFile = serde_json::from_str(&serialized)?;
+        assert_eq!(key_file.decrypt("passphrase")?, private_key());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_wrong_passphrase_is_rejected() -> eyre::Result<()> {
+        let key_file = KeyFile::encrypt(&private_key(), "passphrase")?;
+
+        let err = key_file
+            .decrypt("wrong passphrase")
+            .expect_err("key must not be decrypted with the wrong passphrase");
+        assert!(err.to_string().contains("wrong passphrase"), "{err}");
+
+        Ok(())
+    }
+}
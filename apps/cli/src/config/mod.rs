@@ -4,16 +4,24 @@ use bitcoin::{Network, PrivateKey};
 use color_eyre::eyre;
 use serde::{Deserialize, Serialize};
 
+pub use key_file::{read_passphrase, KeyFile};
 use ydk::bitcoin_provider::BitcoinProviderConfig;
 use ydk::types::FeeRateStrategy;
-use ydk::wallet::WalletConfig;
 pub use yuvnode::YuvNodeConfig;
 
+mod key_file;
 mod yuvnode;
 
 #[derive(Deserialize, Clone, Serialize)]
 pub struct Config {
-    pub private_key: PrivateKey,
+    /// Private key of the wallet in plaintext, which is meant for testing only, so it's used
+    /// only with the `--plaintext-key` flag. Encrypt it to the `key_file` otherwise.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub private_key: Option<PrivateKey>,
+
+    /// Path to the [`KeyFile`] with the private key of the wallet encrypted with a passphrase.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub key_file: Option<PathBuf>,
 
     pub bitcoin_provider: BitcoinProviderConfig,
 
@@ -50,14 +58,3 @@ impl Config {
         Ok(())
     }
 }
-
-impl From<Config> for WalletConfig {
-    fn from(value: Config) -> Self {
-        Self {
-            privkey: value.private_key,
-            network: value.network(),
-            bitcoin_provider: value.bitcoin_provider,
-            yuv_url: value.yuv_rpc.url,
-        }
-    }
-}
@@ -2,17 +2,21 @@ use std::time::Duration;
 use std::{path::PathBuf, sync::Arc};
 
 use bitcoin::secp256k1::{All, Secp256k1};
+use bitcoin::{Network, PrivateKey, PublicKey};
 use color_eyre::eyre::{self, bail, Context as EyreContext};
 use indicatif::{ProgressBar, ProgressStyle};
 use jsonrpsee::http_client::{HttpClient, HttpClientBuilder};
+use zeroize::Zeroizing;
 
-use crate::config::Config;
+use crate::actions::recipient::parse_recipient;
+use crate::config::{read_passphrase, Config, KeyFile};
 use bdk::blockchain::rpc::Auth;
 use bdk::blockchain::AnyBlockchain;
 use bitcoin_client::{BitcoinRpcAuth, BitcoinRpcClient};
 use ydk::bitcoin_provider::{BitcoinProvider, BitcoinProviderConfig};
 use ydk::wallet::{StorageWallet, StorageWalletConfig, SyncOptions, WalletConfig};
 use ydk::AnyBitcoinProvider;
+use yuv_pixels::Chroma;
 
 /// Context is a struct which holds all information that could be used globally, like info from
 /// configuration file. All the data taken from context is evaluated lazily, so it's not a problem
@@ -27,6 +31,12 @@ pub struct Context {
     /// Loaded configuration file.
     config: Option<Config>,
 
+    /// Whether the plaintext private key from the config is allowed to be used.
+    plaintext_key: bool,
+
+    /// Private key of the wallet, decrypted from the key file or taken from the config.
+    private_key: Option<WalletKey>,
+
     /// Yuv RPC client.
     yuv_client: Option<HttpClient>,
 
@@ -38,13 +48,15 @@ pub struct Context {
 }
 
 impl Context {
-    pub fn new(config: PathBuf) -> Self {
+    pub fn new(config: PathBuf, plaintext_key: bool) -> Self {
         let secp_ctx = Secp256k1::new();
 
         Self {
             config_path: config,
             secp_ctx,
             config: None,
+            plaintext_key,
+            private_key: None,
             yuv_client: None,
             bitcoin_provider: None,
             yuv_wallet: None,
@@ -64,6 +76,63 @@ impl Context {
         Ok(cfg)
     }
 
+    pub fn config_path(&self) -> &PathBuf {
+        &self.config_path
+    }
+
+    /// Private key of the wallet. If the config has the [`KeyFile`], the passphrase is prompted
+    /// to decrypt it once, and the key is kept in memory until the context is dropped.
+    ///
+    /// Only the copy kept by the context is zeroized, the returned ones are not.
+    ///
+    /// The plaintext key from the config is used only with the `--plaintext-key` flag.
+    pub fn private_key(&mut self) -> eyre::Result<PrivateKey> {
+        if let Some(key) = &self.private_key {
+            return key.private_key();
+        }
+
+        let config = self.config()?;
+
+        let private_key = match (config.private_key, config.key_file) {
+            (Some(_), Some(_)) => {
+                bail!("Both `private_key` and `key_file` are set in the config, keep only one")
+            }
+            (Some(private_key), None) if self.plaintext_key => private_key,
+            (Some(_), None) => bail!(
+                "The private key in the config is not encrypted, encrypt it with `wallet encrypt` \
+                or pass `--plaintext-key` to use it for testing"
+            ),
+            (None, Some(path)) => {
+                let key_file = KeyFile::from_path(&path)?;
+                let passphrase = read_passphrase("Passphrase: ")?;
+
+                key_file.decrypt(&passphrase)?
+            }
+            (None, None) => bail!("Neither `private_key` nor `key_file` is set in the config"),
+        };
+
+        let key = WalletKey::new(&private_key);
+        self.private_key = Some(key);
+
+        Ok(private_key)
+    }
+
+    /// Parse the [`Chroma`] from the P2TR address, which must be for the network from the config.
+    pub fn chroma(&mut self, address: &str) -> eyre::Result<Chroma> {
+        let network = self.config()?.network();
+
+        Chroma::from_address_for_network(address, network)
+            .wrap_err_with(|| format!("Invalid chroma '{address}'"))
+    }
+
+    /// Parse the recipient's public key from the public key or the P2TR address, which must be
+    /// for the network from the config.
+    pub fn recipient(&mut self, recipient: &str) -> eyre::Result<PublicKey> {
+        let network = self.config()?.network();
+
+        parse_recipient(recipient, network)
+    }
+
     pub fn secp_ctx(&self) -> &Secp256k1<All> {
         &self.secp_ctx
     }
@@ -122,9 +191,7 @@ impl Context {
             return Ok(provider.clone());
         }
 
-        let config = self.config()?;
-
-        let wallet_cfg: WalletConfig = config.into();
+        let wallet_cfg = self.wallet_config()?;
         let provider = Arc::new(AnyBitcoinProvider::from_config(wallet_cfg.try_into()?)?);
 
         self.bitcoin_provider = Some(provider.clone());
@@ -137,18 +204,12 @@ impl Context {
             return Ok(wallet.clone());
         }
 
-        let config = self.config()?;
-
-        let wallet = StorageWallet::from_storage_config(StorageWalletConfig {
-            inner: WalletConfig {
-                privkey: config.private_key,
-                bitcoin_provider: config.bitcoin_provider.clone(),
-                yuv_url: config.yuv_rpc.url.clone(),
-                network: config.network(),
-            },
-            storage_path: config.storage.clone(),
+        let mut wallet = StorageWallet::from_storage_config(StorageWalletConfig {
+            inner: self.wallet_config()?,
+            storage_path: self.config()?.storage,
         })
         .await?;
+        wallet.enable_change_keychain().await?;
 
         let pb = setup_progress_bar("Syncing yuv and bitcoin wallets...".into());
         wallet.sync(SyncOptions::default()).await?;
@@ -159,6 +220,41 @@ impl Context {
 
         Ok(wallet)
     }
+
+    fn wallet_config(&mut self) -> eyre::Result<WalletConfig> {
+        let config = self.config()?;
+
+        Ok(WalletConfig {
+            privkey: self.private_key()?,
+            network: config.network(),
+            bitcoin_provider: config.bitcoin_provider,
+            yuv_url: config.yuv_rpc.url,
+        })
+    }
+}
+
+/// Secret bytes of the wallet's private key, which are zeroized on drop.
+struct WalletKey {
+    secret: Zeroizing<[u8; 32]>,
+    network: Network,
+    compressed: bool,
+}
+
+impl WalletKey {
+    fn new(private_key: &PrivateKey) -> Self {
+        Self {
+            secret: Zeroizing::new(private_key.inner.secret_bytes()),
+            network: private_key.network,
+            compressed: private_key.compressed,
+        }
+    }
+
+    fn private_key(&self) -> eyre::Result<PrivateKey> {
+        let mut private_key = PrivateKey::from_slice(self.secret.as_ref(), self.network)?;
+        private_key.compressed = self.compressed;
+
+        Ok(private_key)
+    }
 }
 
 /// Setups progress bar that will appear in console for an adjusted while
@@ -179,10 +179,12 @@ impl Account {
             .set_fee_rate_strategy(FEE_RATE_STARTEGY)
             .set_drain_tweaked_satoshis(true);
 
-        match &self.rpc_blockchain {
-            Some(bc) => builder.finish(bc).await,
-            None => builder.finish(&self.esplora).await,
-        }
+        let tx = match &self.rpc_blockchain {
+            Some(bc) => builder.finish(bc).await?,
+            None => builder.finish(&self.esplora).await?,
+        };
+
+        Ok(tx)
     }
 
     /// Transfer tokens to a random recipient.
@@ -210,10 +212,12 @@ impl Account {
             .set_fee_rate_strategy(FEE_RATE_STARTEGY)
             .set_drain_tweaked_satoshis(true);
 
-        match &self.rpc_blockchain {
-            Some(bc) => builder.finish(bc).await,
-            None => builder.finish(&self.esplora).await,
-        }
+        let tx = match &self.rpc_blockchain {
+            Some(bc) => builder.finish(bc).await?,
+            None => builder.finish(&self.esplora).await?,
+        };
+
+        Ok(tx)
     }
 
     /// `send_balances` sends the actual balances of the address to the `tx-checker` after the cancellation received.
@@ -1,3 +1,6 @@
+use eyre::Context;
+use std::fs::File;
+use std::io::BufWriter;
 use std::sync::Arc;
 use tokio::select;
 use tokio::signal::unix;
@@ -16,9 +19,10 @@ use tracing_subscriber::{
     util::SubscriberInitExt,
     EnvFilter,
 };
+use yuv_storage::{ConsistencyReport, Snapshot};
 
 pub async fn run(args: arguments::Run) -> eyre::Result<()> {
-    let config = NodeConfig::from_path(args.config)?;
+    let config = NodeConfig::from_path(args.config.clone())?;
 
     let level_filter = config.logger.level;
 
@@ -47,6 +51,11 @@ pub async fn run(args: arguments::Run) -> eyre::Result<()> {
     // like RPC, p2p until indexer will be initialized.
 
     let node = Arc::new(Node::new(config).await?);
+
+    if let Some(snapshot_path) = args.snapshot {
+        node.import_snapshot(&snapshot_path).await?;
+    }
+
     let node_clone = node.clone();
 
     tokio::spawn(async move {
@@ -60,16 +69,35 @@ pub async fn run(args: arguments::Run) -> eyre::Result<()> {
         unix::signal(SignalKind::terminate()).expect("Failed to create SIGTERM signal handler");
     let mut sigint =
         unix::signal(SignalKind::interrupt()).expect("Failed to create SIGINT signal handler");
-
-    select! {
-        _ = node.cancelled() => {
-            tracing::info!("Node run failed");
-        }
-        _ = sigterm.recv() => {
-            tracing::info!("Received SIGTERM signal");
-        }
-        _ = sigint.recv() => {
-            tracing::info!("Received SIGINT signal");
+    let mut sighup =
+        unix::signal(SignalKind::hangup()).expect("Failed to create SIGHUP signal handler");
+
+    loop {
+        select! {
+            _ = node.cancelled() => {
+                tracing::info!("Node run failed");
+                break;
+            }
+            _ = sigterm.recv() => {
+                tracing::info!("Received SIGTERM signal");
+                break;
+            }
+            _ = sigint.recv() => {
+                tracing::info!("Received SIGINT signal");
+                break;
+            }
+            _ = sighup.recv() => {
+                tracing::info!("Received SIGHUP signal, reloading the chroma filter");
+
+                let result = match NodeConfig::from_path(args.config.clone()) {
+                    Ok(config) => node.reload_chroma_filter(&config.checkers).await,
+                    Err(err) => Err(err),
+                };
+
+                if let Err(err) = result {
+                    tracing::error!("Failed to reload the chroma filter: {:#}", err);
+                }
+            }
         }
     }
 
@@ -78,6 +106,61 @@ pub async fn run(args: arguments::Run) -> eyre::Result<()> {
     Ok(())
 }
 
+pub async fn export_snapshot(args: arguments::ExportSnapshot) -> eyre::Result<()> {
+    let config = NodeConfig::from_path(args.config)?;
+
+    let (txs_storage, state_storage) = Node::init_storage(config.storage)?;
+    let snapshot = Snapshot::export(&txs_storage, &state_storage).await?;
+
+    let file = File::create(&args.output)
+        .wrap_err_with(|| format!("failed to create file {:?}", args.output))?;
+    snapshot.write_to(BufWriter::new(file))?;
+
+    println!(
+        "Exported {} transactions at block {} to {:?}",
+        snapshot.txs.len(),
+        snapshot.block_hash,
+        args.output
+    );
+
+    Ok(())
+}
+
+pub async fn fsck(args: arguments::Fsck) -> eyre::Result<()> {
+    let config = NodeConfig::from_path(args.config)?;
+
+    let (txs_storage, state_storage) = Node::init_storage(config.storage)?;
+    let report = ConsistencyReport::check(&txs_storage, &state_storage, args.fix).await?;
+
+    for (page_num, txid) in &report.dangling_page_entries {
+        println!("Page {page_num} references missing transaction {txid}");
+    }
+    for (txid, parent) in &report.missing_parents {
+        println!("Transfer {txid} has missing parent {parent}");
+    }
+    for (outpoint, freeze_txid) in &report.orphaned_freezes {
+        println!("Freeze entry of {outpoint} references missing transaction {freeze_txid}");
+    }
+
+    println!(
+        "Dangling page entries: {}",
+        report.dangling_page_entries.len()
+    );
+    println!("Missing parents: {}", report.missing_parents.len());
+    println!("Orphaned freeze toggles: {}", report.orphaned_freezes.len());
+
+    if args.fix {
+        txs_storage.flush().await?;
+        state_storage.flush().await?;
+
+        if !report.dangling_page_entries.is_empty() || !report.orphaned_freezes.is_empty() {
+            println!("Dangling page entries and orphaned freeze toggles are removed");
+        }
+    }
+
+    Ok(())
+}
+
 struct YuvTracer;
 
 impl<S> Layer<S> for YuvTracer
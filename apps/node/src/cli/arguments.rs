@@ -7,4 +7,32 @@ pub struct Run {
     /// Path to config file
     #[clap(short, long, default_value = "config.toml")]
     pub config: PathBuf,
+
+    /// Path to the snapshot file to import to the empty storage before the start. Indexing
+    /// continues from the snapshot's block instead of the YUV genesis one.
+    #[clap(long)]
+    pub snapshot: Option<PathBuf>,
+}
+
+#[derive(Args, Debug, Clone)]
+pub struct ExportSnapshot {
+    /// Path to config file
+    #[clap(short, long, default_value = "config.toml")]
+    pub config: PathBuf,
+
+    /// Path to the file to write the snapshot to.
+    #[clap(short, long)]
+    pub output: PathBuf,
+}
+
+#[derive(Args, Debug, Clone)]
+pub struct Fsck {
+    /// Path to config file
+    #[clap(short, long, default_value = "config.toml")]
+    pub config: PathBuf,
+
+    /// Remove dangling page entries and orphaned freeze toggles. Missing parents of the
+    /// transfers can't be fixed and are only reported.
+    #[clap(long)]
+    pub fix: bool,
 }
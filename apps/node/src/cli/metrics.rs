@@ -0,0 +1,114 @@
+use std::convert::Infallible;
+use std::net::SocketAddr;
+use std::time::Duration;
+
+use event_bus::EventBus;
+use hyper::{
+    header,
+    service::{make_service_fn, service_fn},
+    Body, Method, Request, Response, Server, StatusCode,
+};
+use tokio_util::sync::CancellationToken;
+use yuv_metrics::{Encoder, METRICS};
+use yuv_p2p::client::handle::Handle;
+
+/// Path at which the metrics are served.
+const METRICS_PATH: &str = "/metrics";
+/// Content type of the Prometheus text format.
+const METRICS_CONTENT_TYPE: &str = "text/plain; version=0.0.4";
+/// Max time to wait for the list of peers from the P2P client, so the scrape
+/// doesn't hang if the P2P client is stuck.
+const GET_PEERS_TIMEOUT: Duration = Duration::from_millis(500);
+
+/// Serve the node's metrics in the Prometheus text format at `address` until
+/// the cancellation.
+pub async fn run_server<P>(
+    address: SocketAddr,
+    full_event_bus: EventBus,
+    p2p_handle: P,
+    cancellation: CancellationToken,
+) where
+    P: Handle + 'static,
+{
+    let make_service = make_service_fn(move |_conn| {
+        let event_bus = full_event_bus.clone();
+        let p2p_handle = p2p_handle.clone();
+
+        async move {
+            Ok::<_, Infallible>(service_fn(move |request| {
+                handle_request(request, event_bus.clone(), p2p_handle.clone())
+            }))
+        }
+    });
+
+    let server = match Server::try_bind(&address) {
+        Ok(builder) => builder.serve(make_service),
+        Err(err) => {
+            tracing::error!("Failed to bind metrics server to {}: {}", address, err);
+            return;
+        }
+    };
+
+    tracing::info!("Metrics are served at http://{}{}", address, METRICS_PATH);
+
+    if let Err(err) = server
+        .with_graceful_shutdown(cancellation.cancelled())
+        .await
+    {
+        tracing::error!("Metrics server failed: {}", err);
+    }
+}
+
+async fn handle_request<P: Handle>(
+    request: Request<Body>,
+    event_bus: EventBus,
+    p2p_handle: P,
+) -> Result<Response<Body>, Infallible> {
+    if request.method() != Method::GET || request.uri().path() != METRICS_PATH {
+        let mut response = Response::new(Body::empty());
+        *response.status_mut() = StatusCode::NOT_FOUND;
+
+        return Ok(response);
+    }
+
+    let metrics = encode_metrics(&event_bus, &p2p_handle).await;
+
+    let mut response = Response::new(Body::from(metrics));
+    response.headers_mut().insert(
+        header::CONTENT_TYPE,
+        header::HeaderValue::from_static(METRICS_CONTENT_TYPE),
+    );
+
+    Ok(response)
+}
+
+/// Encode the metrics updated by the services, with the ones that are taken
+/// at the moment of the scrape.
+async fn encode_metrics<P: Handle>(event_bus: &EventBus, p2p_handle: &P) -> String {
+    let mut encoder = Encoder::default();
+    METRICS.encode(&mut encoder);
+
+    let queue_stats = event_bus.queue_stats();
+    encoder.labeled_gauge(
+        "yuv_event_bus_queue_length",
+        "Number of events waiting in the event bus channel.",
+        "event",
+        queue_stats
+            .iter()
+            .map(|stats| (stats.name, stats.len as u64)),
+    );
+
+    match tokio::time::timeout(GET_PEERS_TIMEOUT, p2p_handle.get_peers()).await {
+        Ok(Ok(peers)) => {
+            encoder.gauge(
+                "yuv_peers",
+                "Number of connected peers.",
+                peers.len() as u64,
+            );
+        }
+        Ok(Err(err)) => tracing::warn!("Failed to get peers for metrics: {}", err),
+        Err(_) => tracing::warn!("Timed out getting peers for metrics"),
+    }
+
+    encoder.finish()
+}
@@ -1,5 +1,7 @@
 mod actions;
 mod arguments;
+#[cfg(feature = "metrics")]
+mod metrics;
 mod node;
 use clap::Parser;
 
@@ -8,12 +10,21 @@ use clap::Parser;
 pub enum Cli {
     /// Run p2p node, see `node --help` for more information
     Run(arguments::Run),
+    /// Export the snapshot of the stopped node's storage at the last indexed block, which can be
+    /// imported with `run --snapshot`
+    ExportSnapshot(arguments::ExportSnapshot),
+    /// Check the consistency of the stopped node's storage: pages must reference attached
+    /// transactions, attached transfers must have their parents, and freeze entries must
+    /// reference stored toggles
+    Fsck(arguments::Fsck),
 }
 
 impl Cli {
     pub async fn exec(self) -> eyre::Result<()> {
         match self {
             Self::Run(args) => actions::run(args).await,
+            Self::ExportSnapshot(args) => actions::export_snapshot(args).await,
+            Self::Fsck(args) => actions::fsck(args).await,
         }
     }
 }
@@ -1,33 +1,43 @@
+use std::fs::File;
+use std::io::BufReader;
+use std::path::Path;
 use std::sync::Arc;
 use std::time::Duration;
 
-use crate::config::{NodeConfig, StorageConfig};
-use bitcoin_client::BitcoinRpcClient;
+use crate::config::{CheckersConfig, NodeConfig, StorageConfig};
+use bitcoin_client::{BitcoinRpcApi, BitcoinRpcClient};
 use event_bus::EventBus;
 use eyre::{Context, Ok};
 use tokio::select;
 use tokio::time::sleep;
 use tokio_util::sync::CancellationToken;
 use tokio_util::task::TaskTracker;
-use tracing::{error, info};
-use yuv_controller::Controller;
-use yuv_indexers::{AnnouncementsIndexer, BitcoinBlockIndexer, ConfirmationIndexer, RunParams};
+use tracing::{error, info, warn};
+use yuv_controller::{Controller, DeadLetters, PartialProofs, Webhook};
+use yuv_indexers::{
+    AnnouncementsIndexer, BitcoinBlockIndexer, ConfirmationIndexer, IndexerError, RunParams,
+};
 
 use yuv_p2p::{
     client::{Handle, P2PClient},
     net::{ReactorTcp, Waker},
 };
 use yuv_rpc_server::ServerConfig;
-use yuv_storage::{FlushStrategy, LevelDB, LevelDbOptions, TxStatesStorage};
-use yuv_tx_attach::GraphBuilder;
-use yuv_tx_check::{Config as CheckerConfig, TxCheckerWorkerPool};
+use yuv_storage::{FlushStrategy, LevelDB, LevelDbOptions, Snapshot, TxStatesStorage};
+use yuv_tx_attach::{FreezeSinks, GraphBuilder, GraphSnapshot};
+use yuv_tx_check::{AliveWorkers, ChromaFilter, Config as CheckerConfig, TxCheckerWorkerPool};
 use yuv_tx_confirm::TxConfirmator;
-use yuv_types::{ControllerMessage, GraphBuilderMessage, TxCheckerMessage, TxConfirmMessage};
+use yuv_types::{
+    ControllerMessage, GraphBuilderMessage, TxCheckerMessage, TxCheckerPoolMessage,
+    TxConfirmMessage,
+};
 
 /// Default size of the channel for the event bus.
 const DEFAULT_CHANNEL_SIZE: usize = 1000;
 /// The limit of time to wait for the node to shutdown.
 const DEFAULT_SHUTDOWN_TIMEOUT_SECS: u64 = 30;
+/// The limit of the delay between attempts to connect to the Bitcoin node at startup.
+const MAX_BITCOIN_CONNECT_DELAY: Duration = Duration::from_secs(60);
 // TODO: Temporary solution. Need to be removed after the refactoring of the TxCheckerWorkerPool.
 const TX_CHECKERS_POOL_SIZE: usize = 1;
 
@@ -38,6 +48,11 @@ pub struct Node {
     txs_storage: LevelDB,
     state_storage: LevelDB,
     txs_states_storage: TxStatesStorage,
+    freeze_sinks: FreezeSinks,
+    dead_letters: DeadLetters,
+    partial_proofs: PartialProofs,
+    graph_snapshot: Option<GraphSnapshot>,
+    chroma_filter: ChromaFilter,
     btc_client: Arc<BitcoinRpcClient>,
 
     cancelation: CancellationToken,
@@ -50,14 +65,14 @@ impl Node {
         let (txs_storage, state_storage) = Self::init_storage(config.storage.clone())?;
         let tx_states_storage = TxStatesStorage::default();
 
-        let btc_client = Arc::new(
-            BitcoinRpcClient::new(
-                config.bnode.auth().clone(),
-                config.bnode.url.clone(),
-                config.bnode.timeout,
-            )
-            .await?,
-        );
+        let btc_client = Arc::new(Self::connect_bitcoin_node(&config).await?);
+        let graph_snapshot = config.rpc.enable_debug_methods.then(GraphSnapshot::default);
+        let chroma_filter = ChromaFilter::new(config.checkers.chroma_lists()?);
+        config.indexer.validate_subindexers()?;
+        let partial_proofs = PartialProofs::new(Duration::from_secs(
+            config.controller.partial_proofs_max_age,
+        ))
+        .with_max_txs(config.controller.partial_proofs_max_txs);
 
         Ok(Self {
             config,
@@ -65,12 +80,128 @@ impl Node {
             txs_storage,
             state_storage,
             txs_states_storage: tx_states_storage,
+            freeze_sinks: FreezeSinks::default(),
+            dead_letters: DeadLetters::default(),
+            partial_proofs,
+            graph_snapshot,
+            chroma_filter,
             btc_client,
             cancelation: CancellationToken::new(),
             task_tracker: TaskTracker::new(),
         })
     }
 
+    /// Connect to the Bitcoin node, retrying with exponential backoff starting from the indexer's
+    /// `restart_interval`, so the node survives restarts where bitcoind isn't up yet. Fails after
+    /// `max_restart_attempts` attempts.
+    async fn connect_bitcoin_node(config: &NodeConfig) -> eyre::Result<BitcoinRpcClient> {
+        let max_attempts = config.indexer.max_restart_attempts;
+        let mut delay = config.indexer.restart_interval;
+        let mut attempt = 1;
+
+        loop {
+            match Self::try_connect_bitcoin_node(config).await {
+                Err(err) if attempt < max_attempts => {
+                    warn!(
+                        "Bitcoin node is unavailable, retrying {attempt}/{} in {} secs: {err:#}",
+                        max_attempts - 1,
+                        delay.as_secs(),
+                    );
+
+                    sleep(delay).await;
+
+                    delay = delay.saturating_mul(2).min(MAX_BITCOIN_CONNECT_DELAY);
+                    attempt += 1;
+                }
+                result => {
+                    return result.wrap_err_with(|| {
+                        format!("failed to connect to the Bitcoin node in {attempt} attempts")
+                    })
+                }
+            }
+        }
+    }
+
+    /// Create the Bitcoin RPC client and check that the node responds.
+    async fn try_connect_bitcoin_node(config: &NodeConfig) -> eyre::Result<BitcoinRpcClient> {
+        let client = BitcoinRpcClient::new(
+            config.bnode.auth().clone(),
+            config.bnode.url.clone(),
+            config.bnode.timeout,
+        )
+        .await
+        .wrap_err("failed to create Bitcoin RPC client")?;
+
+        client
+            .get_blockchain_info()
+            .await
+            .wrap_err("failed to get blockchain info")?;
+
+        Ok(client)
+    }
+
+    /// Replace the chroma allowlist and denylist of the running checkers with
+    /// the ones from the `config`. Other settings are applied only on restart.
+    pub async fn reload_chroma_filter(&self, config: &CheckersConfig) -> eyre::Result<()> {
+        let lists = config.chroma_lists()?;
+
+        info!(
+            allowlist = ?lists.allowlist.as_ref().map(|allowlist| allowlist.len()),
+            denylist = lists.denylist.len(),
+            "Reloaded chroma filter"
+        );
+
+        self.chroma_filter.update(lists);
+        self.event_bus
+            .send(ControllerMessage::ChromaFilterReloaded)
+            .await;
+
+        Ok(())
+    }
+
+    /// Import the [`Snapshot`] from the file to the empty storage, so the indexer continues from
+    /// its block. The block must be in the current chain of the Bitcoin node.
+    pub async fn import_snapshot(&self, path: &Path) -> eyre::Result<()> {
+        let file =
+            File::open(path).wrap_err_with(|| format!("failed to open snapshot {:?}", path))?;
+        let snapshot = Snapshot::read_from(BufReader::new(file))?;
+
+        let block_hash = snapshot.block_hash;
+        let header = self
+            .btc_client
+            .get_block_header_info(&block_hash)
+            .await
+            .wrap_err_with(|| format!("snapshot block {} is not found", block_hash))?;
+
+        let best_chain_hash = self.btc_client.get_block_hash(header.height as u64).await?;
+        if best_chain_hash != block_hash {
+            eyre::bail!(
+                "Snapshot block {} at height {} is not in the current chain",
+                block_hash,
+                header.height
+            );
+        }
+
+        let txs_number = snapshot.txs.len();
+        snapshot
+            .import(
+                &self.txs_storage,
+                &self.state_storage,
+                self.config.storage.tx_per_page,
+            )
+            .await
+            .wrap_err("failed to import snapshot")?;
+
+        info!(
+            height = header.height,
+            %block_hash,
+            "Imported snapshot with {} transactions",
+            txs_number
+        );
+
+        Ok(())
+    }
+
     /// Wait for the signal from any node's service about the cancellation.
     pub async fn cancelled(&self) {
         self.cancelation.cancelled().await
@@ -79,15 +210,16 @@ impl Node {
     /// The order of service starting is important if you want to index blocks first and then start
     /// listen to inbound messages.
     pub async fn run(&self) -> eyre::Result<()> {
-        self.spawn_graph_builder();
-        self.spawn_tx_checkers_worker_pool()?;
+        self.spawn_graph_builder().await?;
+        let checkers = self.spawn_tx_checkers_worker_pool()?;
         self.spawn_tx_confirmator();
         self.spawn_indexer().await?;
 
         let p2p_handle = self.spawn_p2p()?;
-        self.spawn_controller(p2p_handle);
+        self.spawn_controller(p2p_handle.clone())?;
+        self.spawn_metrics(p2p_handle.clone());
 
-        self.spawn_rpc();
+        self.spawn_rpc(p2p_handle, checkers);
 
         self.task_tracker.close();
 
@@ -109,8 +241,8 @@ impl Node {
         Ok(handle)
     }
 
-    fn spawn_controller(&self, handle: Handle<Waker>) {
-        let controller = Controller::new(
+    fn spawn_controller(&self, handle: Handle<Waker>) -> eyre::Result<()> {
+        let mut controller = Controller::new(
             &self.event_bus,
             self.txs_storage.clone(),
             self.state_storage.clone(),
@@ -120,38 +252,97 @@ impl Node {
         .set_inv_sharing_interval(Duration::from_secs(
             self.config.controller.inv_sharing_interval,
         ))
-        .set_max_inv_size(self.config.controller.max_inv_size);
+        .set_max_inv_size(self.config.controller.max_inv_size)
+        .set_invalid_txs_expiry_interval(Duration::from_secs(
+            self.config.controller.invalid_txs_expiry_interval,
+        ))
+        .set_invalid_txs_max_age(Duration::from_secs(
+            self.config.controller.invalid_txs_max_age,
+        ))
+        .set_dead_letters(self.dead_letters.clone())
+        .set_partial_proofs(self.partial_proofs.clone());
+
+        if let Some(config) = &self.config.controller.webhook {
+            let (webhook, sink) =
+                Webhook::new(config.to_controller_config()?, self.txs_storage.clone())
+                    .wrap_err("failed to create webhook")?;
+
+            info!("Attached txs are posted to the webhook");
+
+            controller = controller.set_webhook(sink);
+            self.task_tracker
+                .spawn(webhook.run(self.cancelation.clone()));
+        }
 
         self.task_tracker
             .spawn(controller.run(self.cancelation.clone()));
+
+        Ok(())
     }
 
-    fn spawn_graph_builder(&self) {
-        let graph_builder = GraphBuilder::new(
+    async fn spawn_graph_builder(&self) -> eyre::Result<()> {
+        let mut graph_builder = GraphBuilder::new(
             self.txs_storage.clone(),
             &self.event_bus,
+            self.btc_client.clone(),
             self.config.storage.tx_per_page,
-        );
+        )
+        .with_min_confirmations(self.config.indexer.min_confirmations)
+        .with_freeze_sinks(self.freeze_sinks.clone());
+
+        if let Some(retention_blocks) = self.config.storage.prune_retention_blocks {
+            graph_builder = graph_builder
+                .with_prune_retention(retention_blocks)
+                .with_prune_period(self.config.storage.prune_period);
+        }
+
+        if let Some(graph_snapshot) = &self.graph_snapshot {
+            graph_builder = graph_builder.with_graph_snapshot(graph_snapshot.clone());
+        }
+
+        if let Some(water_marks) = self
+            .config
+            .checkers
+            .water_marks()
+            .wrap_err("invalid checkers back-pressure config")?
+        {
+            graph_builder = graph_builder.with_water_marks(water_marks);
+        }
+
+        graph_builder
+            .init_page_size()
+            .await
+            .wrap_err("failed to initialize pagination")?;
 
         self.task_tracker
             .spawn(graph_builder.run(self.cancelation.clone()));
+
+        Ok(())
     }
 
-    fn spawn_tx_checkers_worker_pool(&self) -> eyre::Result<()> {
+    fn spawn_tx_checkers_worker_pool(&self) -> eyre::Result<AliveWorkers> {
         let worker_pool = TxCheckerWorkerPool::from_config(
             TX_CHECKERS_POOL_SIZE,
             CheckerConfig {
                 full_event_bus: self.event_bus.clone(),
                 txs_storage: self.txs_storage.clone(),
                 state_storage: self.state_storage.clone(),
+                allowed_proof_kinds: self.config.checkers.allowed_proof_kinds.clone(),
+                require_chroma_announcement: self.config.checkers.require_chroma_announcement,
+                max_dependency_depth: self.config.checkers.max_dependency_depth,
+                max_inputs: self.config.checkers.max_inputs,
+                max_outputs: self.config.checkers.max_outputs,
+                chroma_filter: self.chroma_filter.clone(),
             },
         )
         .wrap_err("TxCheckers worker pool must run successfully")?;
 
+        let checkers = worker_pool.alive_workers();
+
         self.task_tracker
             .spawn(worker_pool.run(self.cancelation.clone()));
 
-        Ok(())
+        Ok(checkers)
     }
 
     fn spawn_tx_confirmator(&self) {
@@ -161,13 +352,39 @@ impl Node {
             self.config.indexer.max_confirmation_time,
             self.config.indexer.clean_up_interval,
             self.config.indexer.confirmations_number,
-        );
+        )
+        .with_lookup_batch_size(self.config.indexer.confirmation_lookup_batch_size)
+        .with_activation_height(self.config.indexer.activation_height);
 
         self.task_tracker
             .spawn(tx_confirmator.run(self.cancelation.clone()));
     }
 
-    fn spawn_rpc(&self) {
+    #[cfg(feature = "metrics")]
+    fn spawn_metrics(&self, p2p_handle: Handle<Waker>) {
+        let Some(metrics) = &self.config.metrics else {
+            return;
+        };
+
+        self.task_tracker.spawn(super::metrics::run_server(
+            metrics.address,
+            self.event_bus.clone(),
+            p2p_handle,
+            self.cancelation.clone(),
+        ));
+    }
+
+    #[cfg(not(feature = "metrics"))]
+    fn spawn_metrics(&self, _p2p_handle: Handle<Waker>) {
+        if let Some(metrics) = &self.config.metrics {
+            tracing::warn!(
+                address = %metrics.address,
+                "Metrics are configured, but the node is built without `metrics` feature"
+            );
+        }
+    }
+
+    fn spawn_rpc(&self, p2p_handle: Handle<Waker>, checkers: AliveWorkers) {
         let address = self.config.rpc.address.to_string();
         let max_items_per_request = self.config.rpc.max_items_per_request;
 
@@ -175,12 +392,23 @@ impl Node {
             ServerConfig {
                 address,
                 max_items_per_request,
+                max_request_body_size: self.config.rpc.max_request_body_size,
+                confirmations_number: self.config.indexer.confirmations_number,
+                rate_limits: self.config.rpc.rate_limits.clone(),
+                max_inputs: self.config.checkers.max_inputs,
+                max_outputs: self.config.checkers.max_outputs,
             },
             self.txs_storage.clone(),
             self.state_storage.clone(),
             self.event_bus.clone(),
             self.txs_states_storage.clone(),
+            self.partial_proofs.clone(),
             self.btc_client.clone(),
+            p2p_handle,
+            checkers,
+            self.freeze_sinks.clone(),
+            self.dead_letters.clone(),
+            self.graph_snapshot.clone(),
             self.cancelation.clone(),
         ));
     }
@@ -191,10 +419,22 @@ impl Node {
             self.state_storage.clone(),
             self.config.indexer.confirmations_number,
             self.config.network,
-        );
-
-        indexer.add_subindexer(AnnouncementsIndexer::new(&self.event_bus));
-        indexer.add_subindexer(ConfirmationIndexer::new(&self.event_bus));
+        )
+        .with_retry_config(self.config.indexer.rpc_retry.clone())
+        .with_skip_pruned_blocks(self.config.indexer.skip_pruned_blocks);
+
+        let subindexers = &self.config.indexer.subindexers;
+        if subindexers.announcements {
+            indexer.add_subindexer(
+                AnnouncementsIndexer::new(&self.event_bus, self.state_storage.clone())
+                    .with_activation_height(self.config.indexer.activation_height),
+            );
+        } else {
+            warn!("Announcements indexer is disabled, freezes won't be enforced");
+        }
+        if subindexers.confirmations {
+            indexer.add_subindexer(ConfirmationIndexer::new(&self.event_bus));
+        }
 
         let restart_interval = self.config.indexer.restart_interval;
         let mut current_attempt = 1;
@@ -207,8 +447,13 @@ impl Node {
             )
             .await
         {
+            // The node is shutting down, so there is no reason to index blocks anymore.
+            if matches!(err, IndexerError::Cancelled) {
+                return Ok(());
+            }
+
             if current_attempt >= self.config.indexer.max_restart_attempts {
-                return Err(err);
+                return Err(err.into());
             }
 
             current_attempt += 1;
@@ -230,7 +475,7 @@ impl Node {
         Ok(())
     }
 
-    fn init_storage(config: StorageConfig) -> eyre::Result<(LevelDB, LevelDB)> {
+    pub(crate) fn init_storage(config: StorageConfig) -> eyre::Result<(LevelDB, LevelDB)> {
         // Create directory if it does not exist
         if !config.path.exists() {
             std::fs::create_dir_all(&config.path)
@@ -244,6 +489,9 @@ impl Node {
             flush_strategy: FlushStrategy::Ticker {
                 period: config.flush_period,
             },
+            write_buffer_bytes: config.write_buffer_bytes,
+            max_open_files: config.max_open_files,
+            block_cache_bytes: config.block_cache_bytes,
         };
         let txs_storage = LevelDB::from_opts(opt).wrap_err("failed to initialize storage")?;
 
@@ -254,6 +502,9 @@ impl Node {
             flush_strategy: FlushStrategy::Ticker {
                 period: config.flush_period,
             },
+            write_buffer_bytes: config.write_buffer_bytes,
+            max_open_files: config.max_open_files,
+            block_cache_bytes: config.block_cache_bytes,
         };
         let state_storage = LevelDB::from_opts(opt).wrap_err("failed to initialize storage")?;
 
@@ -264,6 +515,7 @@ impl Node {
         let mut event_bus = EventBus::default();
         event_bus.register::<TxCheckerMessage>(Some(DEFAULT_CHANNEL_SIZE));
         event_bus.register::<GraphBuilderMessage>(Some(DEFAULT_CHANNEL_SIZE));
+        event_bus.register::<TxCheckerPoolMessage>(Some(DEFAULT_CHANNEL_SIZE));
         event_bus.register::<ControllerMessage>(Some(DEFAULT_CHANNEL_SIZE));
         event_bus.register::<TxConfirmMessage>(Some(DEFAULT_CHANNEL_SIZE));
 
@@ -1,4 +1,11 @@
+use std::collections::HashSet;
+use std::str::FromStr;
+
+use eyre::WrapErr;
 use serde::Deserialize;
+use yuv_pixels::Chroma;
+use yuv_tx_attach::WaterMarks;
+use yuv_tx_check::{ChromaLists, ProofKind};
 
 /// Default number of tx checker workers.
 pub const DEFAULT_POOL_SIZE: usize = 2;
@@ -8,6 +15,99 @@ pub struct CheckersConfig {
     /// Number of checkers in working pool
     #[serde(default = "default_pool_size")]
     pub pool_size: usize,
+
+    /// Kinds of pixel proofs the checkers accept. If not set, all kinds are
+    /// accepted.
+    #[serde(default)]
+    pub allowed_proof_kinds: Option<Vec<ProofKind>>,
+
+    /// Require the chroma announcement to be indexed from a confirmed block
+    /// before accepting issuances of the chroma. Issuances of not announced
+    /// chromas are kept pending until the indexed announcement is checked.
+    #[serde(default)]
+    pub require_chroma_announcement: bool,
+
+    /// Max number of the missing parents in a chain the checkers request
+    /// from the peers before the transfer is rejected. Unlimited if not set.
+    #[serde(default)]
+    pub max_dependency_depth: Option<u32>,
+
+    /// Max number of the inputs of the transaction the checkers accept,
+    /// which bounds the work on the proofs provided via RPC. Unlimited if not
+    /// set.
+    #[serde(default)]
+    pub max_inputs: Option<usize>,
+
+    /// Max number of the outputs of the transaction the checkers accept.
+    /// Unlimited if not set.
+    #[serde(default)]
+    pub max_outputs: Option<usize>,
+
+    /// Chromas which issuances and transfers are checked and stored by the
+    /// node, as P2TR addresses or hex x-only public keys. If not set, all
+    /// chromas are allowed. Reloaded on `SIGHUP`.
+    #[serde(default)]
+    pub chroma_allowlist: Option<Vec<String>>,
+
+    /// Chromas which issuances and transfers are ignored by the node, even
+    /// if they're in the allowlist. Reloaded on `SIGHUP`.
+    #[serde(default)]
+    pub chroma_denylist: Vec<String>,
+
+    /// Number of the transactions waiting for their parents or confirmations
+    /// in the graph builder above which the checkers stop taking new
+    /// transactions to check. Checkers are never paused if not set.
+    #[serde(default)]
+    pub pause_high_water_mark: Option<usize>,
+
+    /// Number of the pending transactions in the graph builder below which
+    /// the paused checkers resume. Half of the high-water mark if not set.
+    #[serde(default)]
+    pub pause_low_water_mark: Option<usize>,
+}
+
+impl CheckersConfig {
+    /// Parse the chroma allowlist and denylist.
+    pub fn chroma_lists(&self) -> eyre::Result<ChromaLists> {
+        let allowlist = self
+            .chroma_allowlist
+            .as_ref()
+            .map(|chromas| parse_chromas(chromas))
+            .transpose()
+            .wrap_err("invalid chroma in the allowlist")?;
+        let denylist =
+            parse_chromas(&self.chroma_denylist).wrap_err("invalid chroma in the denylist")?;
+
+        Ok(ChromaLists {
+            allowlist,
+            denylist,
+        })
+    }
+
+    /// Water marks the checkers are paused and resumed at, if the back-pressure is enabled.
+    pub fn water_marks(&self) -> eyre::Result<Option<WaterMarks>> {
+        let Some(high) = self.pause_high_water_mark else {
+            return Ok(None);
+        };
+
+        let low = self.pause_low_water_mark.unwrap_or(high / 2);
+        if low > high {
+            eyre::bail!("pause low-water mark {low} is above the high-water mark {high}");
+        }
+
+        Ok(Some(WaterMarks { high, low }))
+    }
+}
+
+pub(crate) fn parse_chromas(chromas: &[String]) -> eyre::Result<HashSet<Chroma>> {
+    chromas
+        .iter()
+        .map(|chroma| {
+            Chroma::from_address(chroma)
+                .or_else(|_| Chroma::from_str(chroma))
+                .wrap_err_with(|| format!("failed to parse chroma {chroma}"))
+        })
+        .collect()
 }
 
 fn default_pool_size() -> usize {
@@ -18,6 +118,15 @@ impl Default for CheckersConfig {
     fn default() -> Self {
         Self {
             pool_size: default_pool_size(),
+            allowed_proof_kinds: None,
+            require_chroma_announcement: false,
+            max_dependency_depth: None,
+            max_inputs: None,
+            max_outputs: None,
+            chroma_allowlist: None,
+            chroma_denylist: Vec::new(),
+            pause_high_water_mark: None,
+            pause_low_water_mark: None,
         }
     }
 }
@@ -1,7 +1,20 @@
+use std::time::Duration;
+
+use eyre::WrapErr;
 use serde::Deserialize;
+use yuv_controller::WebhookConfig as ControllerWebhookConfig;
+
+use super::checkers::parse_chromas;
 
 pub const DEFAULT_MAX_INV_SIZE: usize = 100;
 pub const DEFAULT_INV_SHARING_INTERVAL: u64 = 10;
+pub const DEFAULT_INVALID_TXS_EXPIRY_INTERVAL: u64 = 600;
+pub const DEFAULT_INVALID_TXS_MAX_AGE: u64 = 86400;
+pub const DEFAULT_PARTIAL_PROOFS_MAX_AGE: u64 = 600;
+pub const DEFAULT_PARTIAL_PROOFS_MAX_TXS: usize = yuv_controller::DEFAULT_PARTIAL_PROOFS_MAX_TXS;
+pub const DEFAULT_WEBHOOK_MAX_RETRIES: u32 = 5;
+pub const DEFAULT_WEBHOOK_RETRY_DELAY_MS: u64 = 500;
+pub const DEFAULT_WEBHOOK_TIMEOUT: u64 = 10;
 
 #[derive(Deserialize)]
 pub struct ControllerConfig {
@@ -11,6 +24,63 @@ pub struct ControllerConfig {
     /// Interval between inventory sharing in seconds
     #[serde(default = "default_inv_sharing_interval")]
     pub inv_sharing_interval: u64,
+    /// Interval between expirations of invalid transactions in seconds
+    #[serde(default = "default_invalid_txs_expiry_interval")]
+    pub invalid_txs_expiry_interval: u64,
+    /// Age in seconds after which invalid transactions are expired
+    #[serde(default = "default_invalid_txs_max_age")]
+    pub invalid_txs_max_age: u64,
+    /// Age in seconds after which transactions which proofs are provided partially are dropped
+    #[serde(default = "default_partial_proofs_max_age")]
+    pub partial_proofs_max_age: u64,
+    /// Max number of transactions which proofs are provided partially kept at the same time
+    #[serde(default = "default_partial_proofs_max_txs")]
+    pub partial_proofs_max_txs: usize,
+    /// Webhook the attached transactions are posted to. Disabled if not set.
+    #[serde(default)]
+    pub webhook: Option<WebhookConfig>,
+}
+
+#[derive(Deserialize)]
+pub struct WebhookConfig {
+    /// URL the attached transactions are posted to as JSON
+    pub url: String,
+    /// Chromas (addresses or hex) which transactions are posted, all if not set
+    #[serde(default)]
+    pub chromas: Option<Vec<String>>,
+    /// Secret the HMAC-SHA256 signature of the payload is computed with, not signed if not set
+    #[serde(default)]
+    pub secret: Option<String>,
+    /// Number of retries of the failed delivery, after which the transactions are dropped
+    #[serde(default = "default_webhook_max_retries")]
+    pub max_retries: u32,
+    /// Delay before the first retry in milliseconds, doubled with each next one
+    #[serde(default = "default_webhook_retry_delay")]
+    pub retry_delay: u64,
+    /// Timeout of a single delivery attempt in seconds
+    #[serde(default = "default_webhook_timeout")]
+    pub timeout: u64,
+}
+
+impl WebhookConfig {
+    /// Parse the watched chromas and convert to the controller's webhook config.
+    pub fn to_controller_config(&self) -> eyre::Result<ControllerWebhookConfig> {
+        let chromas = self
+            .chromas
+            .as_ref()
+            .map(|chromas| parse_chromas(chromas))
+            .transpose()
+            .wrap_err("invalid chroma in the webhook filter")?;
+
+        Ok(ControllerWebhookConfig {
+            url: self.url.clone(),
+            chromas,
+            secret: self.secret.clone(),
+            max_retries: self.max_retries,
+            retry_delay: Duration::from_millis(self.retry_delay),
+            timeout: Duration::from_secs(self.timeout),
+        })
+    }
 }
 
 fn default_max_inv_size() -> usize {
@@ -21,11 +91,44 @@ fn default_inv_sharing_interval() -> u64 {
     DEFAULT_INV_SHARING_INTERVAL
 }
 
+fn default_invalid_txs_expiry_interval() -> u64 {
+    DEFAULT_INVALID_TXS_EXPIRY_INTERVAL
+}
+
+fn default_invalid_txs_max_age() -> u64 {
+    DEFAULT_INVALID_TXS_MAX_AGE
+}
+
+fn default_partial_proofs_max_age() -> u64 {
+    DEFAULT_PARTIAL_PROOFS_MAX_AGE
+}
+
+fn default_partial_proofs_max_txs() -> usize {
+    DEFAULT_PARTIAL_PROOFS_MAX_TXS
+}
+
+fn default_webhook_max_retries() -> u32 {
+    DEFAULT_WEBHOOK_MAX_RETRIES
+}
+
+fn default_webhook_retry_delay() -> u64 {
+    DEFAULT_WEBHOOK_RETRY_DELAY_MS
+}
+
+fn default_webhook_timeout() -> u64 {
+    DEFAULT_WEBHOOK_TIMEOUT
+}
+
 impl Default for ControllerConfig {
     fn default() -> Self {
         Self {
             max_inv_size: default_max_inv_size(),
             inv_sharing_interval: default_inv_sharing_interval(),
+            invalid_txs_expiry_interval: default_invalid_txs_expiry_interval(),
+            invalid_txs_max_age: default_invalid_txs_max_age(),
+            partial_proofs_max_age: default_partial_proofs_max_age(),
+            partial_proofs_max_txs: default_partial_proofs_max_txs(),
+            webhook: None,
         }
     }
 }
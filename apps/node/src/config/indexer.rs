@@ -2,7 +2,8 @@ use std::time::Duration;
 
 use bitcoin::BlockHash;
 use serde::Deserialize;
-use yuv_indexers::{BlockLoaderConfig, IndexingParams};
+use yuv_indexers::{BlockLoaderConfig, IndexingParams, RpcRetryConfig};
+use yuv_tx_confirm::DEFAULT_LOOKUP_BATCH_SIZE;
 
 pub const DEFAULT_POLLING_PERIOD: Duration = Duration::from_secs(5);
 
@@ -30,9 +31,19 @@ pub struct IndexerConfig {
     #[serde(default = "default_clean_up_interval")]
     pub clean_up_interval: Duration,
 
+    /// Max number of the transactions waiting for confirmation to look up through Bitcoin RPC
+    /// at each clean up, while they haven't been seen in the indexed blocks. Zero disables the
+    /// lookups.
+    #[serde(default = "default_confirmation_lookup_batch_size")]
+    pub confirmation_lookup_batch_size: usize,
+
     #[serde(default)]
     pub blockloader: BlockLoaderConfig,
 
+    /// Retries of Bitcoin RPC calls made by the indexer on transient errors.
+    #[serde(default)]
+    pub rpc_retry: RpcRetryConfig,
+
     #[serde(default = "default_restart_interval")]
     pub restart_interval: Duration,
 
@@ -41,6 +52,50 @@ pub struct IndexerConfig {
 
     #[serde(default)]
     pub confirmations_number: Option<u8>,
+
+    /// Start indexing from the earliest available block if the blocks to index are pruned by
+    /// the Bitcoin node, instead of failing.
+    #[serde(default)]
+    pub skip_pruned_blocks: bool,
+
+    /// Minimal number of confirmations of the Bitcoin transaction before
+    /// YUV transaction is marked as attached. Zero disables the check.
+    #[serde(default)]
+    pub min_confirmations: u8,
+
+    /// Height of the block from which the YUV protocol is active. Announcements and issuances in
+    /// the blocks below it are ignored, as if they didn't exist.
+    #[serde(default)]
+    pub activation_height: u64,
+
+    /// Sub-indexers which process the indexed blocks.
+    #[serde(default)]
+    pub subindexers: SubindexersConfig,
+}
+
+/// Switches of the sub-indexers.
+#[derive(Clone, Deserialize)]
+pub struct SubindexersConfig {
+    /// Index the announcements, including freezes. If disabled, the freezes
+    /// aren't enforced by the node, and the chroma announcements aren't stored.
+    #[serde(default = "default_true")]
+    pub announcements: bool,
+
+    /// Count the confirmations of the transactions from the indexed blocks. If
+    /// disabled, the transactions are confirmed only by the lookups through
+    /// Bitcoin RPC, see `confirmation_lookup_batch_size`, and `min_confirmations`
+    /// must be zero, as only this sub-indexer notifies about the new blocks.
+    #[serde(default = "default_true")]
+    pub confirmations: bool,
+}
+
+impl Default for SubindexersConfig {
+    fn default() -> Self {
+        Self {
+            announcements: true,
+            confirmations: true,
+        }
+    }
 }
 
 fn default_polling_period() -> Duration {
@@ -59,10 +114,50 @@ fn default_max_restart_attempts() -> u32 {
     MAX_RESTART_ATTEMPTS
 }
 
+fn default_true() -> bool {
+    true
+}
+
 fn default_clean_up_interval() -> Duration {
     DEFAULT_CLEAN_UP_INTERVAL
 }
 
+fn default_confirmation_lookup_batch_size() -> usize {
+    DEFAULT_LOOKUP_BATCH_SIZE
+}
+
+impl IndexerConfig {
+    /// Check that the enabled sub-indexers keep the node working.
+    pub fn validate_subindexers(&self) -> eyre::Result<()> {
+        let SubindexersConfig {
+            announcements,
+            confirmations,
+        } = self.subindexers;
+
+        if !announcements && !confirmations {
+            eyre::bail!("at least one of the sub-indexers must be enabled");
+        }
+
+        if !confirmations && self.confirmation_lookup_batch_size == 0 {
+            eyre::bail!(
+                "confirmation sub-indexer is disabled while the confirmation lookups are too, \
+                 so the transactions would never be confirmed"
+            );
+        }
+
+        // Only the confirmation sub-indexer notifies the graph builder about the new blocks,
+        // which attach the transactions that got enough confirmations.
+        if !confirmations && self.min_confirmations > 0 {
+            eyre::bail!(
+                "confirmation sub-indexer is disabled while min_confirmations is set, \
+                 so the transactions would never be attached"
+            );
+        }
+
+        Ok(())
+    }
+}
+
 impl From<IndexerConfig> for IndexingParams {
     fn from(value: IndexerConfig) -> Self {
         Self {
@@ -78,10 +173,16 @@ impl Default for IndexerConfig {
             starting_block: Default::default(),
             max_confirmation_time: default_max_confirmation_time(),
             blockloader: BlockLoaderConfig::default(),
+            rpc_retry: RpcRetryConfig::default(),
             restart_interval: default_restart_interval(),
             max_restart_attempts: default_max_restart_attempts(),
             clean_up_interval: default_clean_up_interval(),
+            confirmation_lookup_batch_size: default_confirmation_lookup_batch_size(),
             confirmations_number: Default::default(),
+            skip_pruned_blocks: Default::default(),
+            min_confirmations: Default::default(),
+            activation_height: Default::default(),
+            subindexers: SubindexersConfig::default(),
         }
     }
 }
@@ -0,0 +1,10 @@
+use std::net::SocketAddr;
+
+use serde::Deserialize;
+
+#[derive(Deserialize)]
+pub struct MetricsConfig {
+    /// Address to serve the metrics in the Prometheus format at, separate
+    /// from the RPC one
+    pub address: SocketAddr,
+}
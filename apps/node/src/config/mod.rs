@@ -29,6 +29,9 @@ mod controller;
 
 pub use controller::ControllerConfig;
 
+mod metrics;
+pub use metrics::MetricsConfig;
+
 #[derive(Deserialize)]
 pub struct NodeConfig {
     #[serde(default = "default_network")]
@@ -53,6 +56,11 @@ pub struct NodeConfig {
 
     #[serde(default)]
     pub controller: ControllerConfig,
+
+    /// Metrics endpoint, which is available if the node is built with the
+    /// `metrics` feature. Disabled if not set.
+    #[serde(default)]
+    pub metrics: Option<MetricsConfig>,
 }
 
 fn default_network() -> Network {
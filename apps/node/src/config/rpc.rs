@@ -1,6 +1,7 @@
 use std::net::SocketAddr;
 
 use serde::{Deserialize, Serialize};
+use yuv_rpc_server::{rate_limit::RateLimitConfig, DEFAULT_MAX_REQUEST_BODY_SIZE};
 
 #[derive(Serialize, Deserialize)]
 pub struct RpcConfig {
@@ -10,8 +11,25 @@ pub struct RpcConfig {
     /// Maximum number of items per list request
     #[serde(default = "default_max_items_per_request")]
     pub max_items_per_request: usize,
+
+    /// Maximum size of the request body in bytes
+    #[serde(default = "default_max_request_body_size")]
+    pub max_request_body_size: usize,
+
+    /// Limits of the rate of requests per method and per client
+    #[serde(default)]
+    pub rate_limits: RateLimitConfig,
+
+    /// Serve the debug methods, which expose the internal state of the node
+    /// and prune its storage
+    #[serde(default)]
+    pub enable_debug_methods: bool,
 }
 
 fn default_max_items_per_request() -> usize {
     50
 }
+
+fn default_max_request_body_size() -> usize {
+    DEFAULT_MAX_REQUEST_BODY_SIZE
+}
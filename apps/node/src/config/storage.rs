@@ -1,9 +1,12 @@
 use std::path::PathBuf;
+use std::time::Duration;
 
 use serde::{Deserialize, Serialize};
 use yuv_storage::DEFAULT_FLUSH_PERIOD_SECS;
 
 pub const DEFAULT_TX_PER_PAGE: u64 = 100;
+/// Default period of pruning the storage, one day.
+pub const DEFAULT_PRUNE_PERIOD: Duration = Duration::from_secs(60 * 60 * 24);
 
 #[derive(Serialize, Deserialize, Clone)]
 pub struct StorageConfig {
@@ -21,6 +24,30 @@ pub struct StorageConfig {
     /// Transactions per one page
     #[serde(default = "default_tx_per_page")]
     pub tx_per_page: u64,
+
+    /// Number of blocks after which attached transfers with all outputs spent
+    /// are pruned. Pruning is disabled if not set.
+    #[serde(default)]
+    pub prune_retention_blocks: Option<u32>,
+
+    /// Period of pruning the storage
+    #[serde(default = "default_prune_period")]
+    pub prune_period: Duration,
+
+    /// Size in bytes of the LevelDB's buffer of the writes. LevelDB's default
+    /// if not set.
+    #[serde(default)]
+    pub write_buffer_bytes: Option<usize>,
+
+    /// Max number of files each LevelDB database keeps open. LevelDB's default
+    /// if not set.
+    #[serde(default)]
+    pub max_open_files: Option<usize>,
+
+    /// Size in bytes of the LevelDB's cache of the read blocks. LevelDB's
+    /// default if not set.
+    #[serde(default)]
+    pub block_cache_bytes: Option<usize>,
 }
 
 fn default_flush_period() -> u64 {
@@ -31,6 +58,10 @@ fn default_tx_per_page() -> u64 {
     DEFAULT_TX_PER_PAGE
 }
 
+fn default_prune_period() -> Duration {
+    DEFAULT_PRUNE_PERIOD
+}
+
 fn default_create_if_missing() -> bool {
     true
 }
@@ -20,7 +20,7 @@ use yuv_tx_check::{Config, TxCheckerWorkerPool};
 use yuv_types::messages::p2p::Inventory;
 use yuv_types::{
     ControllerMessage, ControllerP2PMessage, GraphBuilderMessage, TxCheckerMessage,
-    TxConfirmMessage, YuvTransaction,
+    TxCheckerPoolMessage, TxConfirmMessage, YuvTransaction,
 };
 
 mod common;
@@ -110,6 +110,7 @@ pub fn init_event_bus() -> EventBus {
     event_bus.register::<TxConfirmMessage>(None);
     event_bus.register::<ControllerMessage>(None);
     event_bus.register::<GraphBuilderMessage>(None);
+    event_bus.register::<TxCheckerPoolMessage>(None);
 
     event_bus
 }
@@ -119,7 +120,8 @@ fn spawn_graph_builder(
     txs_storage: LevelDB,
     cancellation: CancellationToken,
 ) {
-    let graph_builder = GraphBuilder::new(txs_storage, event_bus, 100);
+    let graph_builder =
+        GraphBuilder::new(txs_storage, event_bus, Arc::new(MockRpcApi::default()), 100);
 
     tokio::spawn(graph_builder.run(cancellation.clone()));
 }
@@ -137,6 +139,12 @@ fn spawn_tx_checker_worker_pool(
             full_event_bus: event_bus.clone(),
             txs_storage: txs_storage.clone(),
             state_storage: state_storage.clone(),
+            allowed_proof_kinds: None,
+            require_chroma_announcement: false,
+            max_dependency_depth: None,
+            max_inputs: None,
+            max_outputs: None,
+            chroma_filter: Default::default(),
         },
     )
     .wrap_err("TxCheckers worker pool must run successfully")?;
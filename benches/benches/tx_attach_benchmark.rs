@@ -1,6 +1,9 @@
 #[macro_use]
 extern crate criterion;
 
+use std::sync::Arc;
+
+use bitcoin_client::MockRpcApi;
 use criterion::async_executor::FuturesExecutor;
 use criterion::{black_box, BatchSize, Criterion};
 use event_bus::{BusEvent, EventBus};
@@ -9,7 +12,7 @@ use tokio_util::sync::CancellationToken;
 
 use yuv_storage::LevelDB;
 use yuv_tx_attach::GraphBuilder;
-use yuv_types::{ControllerMessage, GraphBuilderMessage};
+use yuv_types::{ControllerMessage, GraphBuilderMessage, TxCheckerPoolMessage};
 
 use crate::tx_generator::TxGenerator;
 
@@ -25,6 +28,7 @@ pub fn init_event_bus() -> EventBus {
 
     event_bus.register::<ControllerMessage>(None);
     event_bus.register::<GraphBuilderMessage>(None);
+    event_bus.register::<TxCheckerPoolMessage>(None);
 
     event_bus
 }
@@ -54,7 +58,8 @@ async fn spawn_graph_builder(
     txs_storage: LevelDB,
     cancellation: CancellationToken,
 ) {
-    let graph_builder = GraphBuilder::new(txs_storage, event_bus, 100);
+    let graph_builder =
+        GraphBuilder::new(txs_storage, event_bus, Arc::new(MockRpcApi::default()), 100);
 
     tokio::spawn(graph_builder.run(cancellation));
 }
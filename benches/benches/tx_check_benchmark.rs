@@ -12,7 +12,7 @@ use tokio_util::sync::CancellationToken;
 
 use yuv_storage::LevelDB;
 use yuv_tx_check::{Config, TxCheckerWorkerPool};
-use yuv_types::{ControllerMessage, GraphBuilderMessage, TxCheckerMessage};
+use yuv_types::{ControllerMessage, GraphBuilderMessage, TxCheckerMessage, TxCheckerPoolMessage};
 
 use crate::tx_generator::TxGenerator;
 
@@ -67,6 +67,12 @@ async fn spawn_tx_checker_worker_pool(
             full_event_bus: event_bus.clone(),
             txs_storage: txs_storage.clone(),
             state_storage: state_storage.clone(),
+            allowed_proof_kinds: None,
+            require_chroma_announcement: false,
+            max_dependency_depth: None,
+            max_inputs: None,
+            max_outputs: None,
+            chroma_filter: Default::default(),
         },
     )
     .wrap_err("TxCheckers worker pool must run successfully")?;
@@ -79,6 +85,7 @@ pub fn init_event_bus() -> EventBus {
     let mut event_bus = EventBus::default();
 
     event_bus.register::<GraphBuilderMessage>(None);
+    event_bus.register::<TxCheckerPoolMessage>(None);
     event_bus.register::<ControllerMessage>(None);
     event_bus.register::<TxCheckerMessage>(None);
 
@@ -57,9 +57,12 @@ pub struct Client {
 }
 
 impl Client {
-    /// Creates a client to a bitcoind JSON-RPC server.
+    /// Creates a client to a bitcoind JSON-RPC server at `url`, which requests time out after
+    /// `timeout` if it's set. The connections to the server are kept alive and reused by the
+    /// requests of the client.
     ///
-    /// Can only return [Err] when using cookie authentication.
+    /// Returns [Err] if the cookie file of the `auth` couldn't be read, or the pooled HTTP
+    /// client couldn't be built.
     pub async fn new(auth: Auth, url: String, timeout: Option<Duration>) -> Result<Self> {
         let mut client = jsonrpc::http::reqwest_http::Builder::new()
             .url(&url)
@@ -73,8 +76,10 @@ impl Client {
             client = client.timeout(timeout);
         }
 
+        let transport = client.build().map_err(|e| Error::JsonRpc(e.into()))?;
+
         Ok(Self {
-            client: jsonrpc::client::Client::with_transport(client.build()),
+            client: jsonrpc::client::Client::with_transport(transport),
         })
     }
 
@@ -109,6 +114,40 @@ impl BitcoinRpcApi for Client {
         log_response(cmd, &resp);
         Ok(resp?.result()?)
     }
+
+    /// Call an `cmd` rpc with each of the `args` lists in one JSON-RPC batch
+    async fn call_batch<T: for<'a> de::Deserialize<'a> + Send + 'static>(
+        &self,
+        cmd: &str,
+        args: &[Vec<serde_json::Value>],
+    ) -> Result<Vec<Result<T>>> {
+        if args.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let v_args = args
+            .iter()
+            .map(|args| {
+                args.iter()
+                    .map(serde_json::value::to_raw_value)
+                    .collect::<std::result::Result<Vec<_>, serde_json::Error>>()
+            })
+            .collect::<std::result::Result<Vec<_>, serde_json::Error>>()?;
+        let reqs = v_args
+            .iter()
+            .map(|args| self.client.build_request(cmd, &args[..]))
+            .collect::<Vec<_>>();
+        if log_enabled!(Debug) {
+            debug!(target: "bitcoincore_rpc", "JSON-RPC batch request: {} x{}", cmd, reqs.len());
+        }
+
+        let resps = self.client.send_batch(&reqs).await?;
+
+        Ok(resps
+            .into_iter()
+            .map(|resp| Ok(resp.ok_or(JsonRpcError::WrongBatchResponseSize)?.result()?))
+            .collect())
+    }
 }
 
 fn log_response(cmd: &str, resp: &Result<jsonrpc::Response>) {
@@ -172,6 +211,23 @@ pub enum Error {
     UnsupportedVersion,
 }
 
+/// Error code returned by Bitcoin Core while it's still loading.
+const RPC_IN_WARMUP: i32 = -28;
+
+impl Error {
+    /// Returns `true` if the error is caused by a connection problem or Bitcoin Core is not
+    /// ready yet, so the same request may succeed later. Error responses like block not found
+    /// are permanent.
+    pub fn is_transient(&self) -> bool {
+        match self {
+            Self::Io(_) => true,
+            Self::JsonRpc(JsonRpcError::Transport(_)) => true,
+            Self::JsonRpc(JsonRpcError::Rpc(err)) => err.code == RPC_IN_WARMUP,
+            _ => false,
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -179,6 +235,25 @@ mod tests {
     use bitcoin::hashes::hex::FromHex;
     use bitcoin::Transaction;
 
+    #[test]
+    fn test_is_transient() {
+        let io_err = Error::Io(std::io::ErrorKind::ConnectionReset.into());
+        assert!(io_err.is_transient());
+
+        let rpc_err = |code| {
+            Error::JsonRpc(JsonRpcError::Rpc(jsonrpc::error::RpcError {
+                code,
+                message: String::new(),
+                data: None,
+            }))
+        };
+        assert!(rpc_err(RPC_IN_WARMUP).is_transient());
+        // Block not found
+        assert!(!rpc_err(-5).is_transient());
+
+        assert!(!Error::UnexpectedStructure.is_transient());
+    }
+
     #[tokio::test]
     async fn test_raw_tx() {
         use bitcoin::consensus::encode;
@@ -181,6 +181,21 @@ pub trait RpcApi: Sized {
         args: &[serde_json::Value],
     ) -> Result<T>;
 
+    /// Call a `cmd` rpc with each of the `args` lists in one batch request. The results are in
+    /// the order of the `args`, and the error of one call doesn't fail the others.
+    async fn call_batch<T: for<'a> de::Deserialize<'a> + Send + 'static>(
+        &self,
+        cmd: &str,
+        args: &[Vec<serde_json::Value>],
+    ) -> Result<Vec<Result<T>>> {
+        let mut results = Vec::with_capacity(args.len());
+        for args in args {
+            results.push(self.call(cmd, args).await);
+        }
+
+        Ok(results)
+    }
+
     /// Query an object implementing `Querable` type
     async fn get_by_id<T: queryable::Queryable<Self>>(
         &self,
@@ -594,6 +609,20 @@ pub trait RpcApi: Sized {
             .await
     }
 
+    /// Return the raw transaction infos of the `txids` in one batch request, see
+    /// [`get_raw_transaction_info`](RpcApi::get_raw_transaction_info).
+    async fn get_raw_transaction_infos(
+        &self,
+        txids: &[bitcoin::Txid],
+    ) -> Result<Vec<Result<json::GetRawTransactionResult>>> {
+        let args = txids
+            .iter()
+            .map(|txid| Ok(vec![into_json(txid)?, into_json(true)?]))
+            .collect::<Result<Vec<_>>>()?;
+
+        self.call_batch("getrawtransaction", &args).await
+    }
+
     /// Retrieve a BIP 157 content filter for a particular block.
     ///
     /// # Parameters
@@ -765,6 +794,36 @@ pub trait RpcApi: Sized {
         )
     }
 
+    /// Returns details about the unspent transaction `outpoints` in one batch request, see
+    /// [`get_tx_out`](RpcApi::get_tx_out).
+    async fn get_tx_outs(
+        &self,
+        outpoints: &[bitcoin::OutPoint],
+        include_mempool: Option<bool>,
+    ) -> Result<Vec<Result<Option<json::GetTxOutResult>>>> {
+        let args = outpoints
+            .iter()
+            .map(|outpoint| {
+                let mut args = [
+                    into_json(outpoint.txid)?,
+                    into_json(outpoint.vout)?,
+                    opt_into_json(include_mempool)?,
+                ];
+
+                Ok(handle_defaults(&mut args, &[null()]).to_vec())
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        let results = self
+            .call_batch::<serde_json::Value>("gettxout", &args)
+            .await?;
+
+        Ok(results
+            .into_iter()
+            .map(|result| opt_result(result?))
+            .collect())
+    }
+
     /// Returns a hex-encoded proof that “txid” was included in a block.
     ///
     /// # Parameters
@@ -1653,6 +1712,50 @@ mockall::mock! {
     }
 }
 
+#[cfg(any(test, feature = "mocks"))]
+impl MockRpcApi {
+    /// Mock that returns every transaction requested with `getrawtransaction` as the one in the
+    /// `blockhash` block with the `confirmations`, or as not mined if they're `None`. If the
+    /// `lookups` are set, exactly that number of the requests is expected.
+    pub fn with_raw_txs(
+        blockhash: Option<bitcoin::BlockHash>,
+        confirmations: Option<u32>,
+        lookups: Option<usize>,
+    ) -> Self {
+        use bitcoin::hashes::Hash;
+
+        let mut client = Self::new();
+
+        let expectation = client
+            .expect_call::<json::GetRawTransactionResult>()
+            .withf(|method, _| method == "getrawtransaction")
+            .returning(move |_, _| {
+                Ok(json::GetRawTransactionResult {
+                    in_active_chain: None,
+                    hex: Vec::new(),
+                    txid: bitcoin::Txid::all_zeros(),
+                    hash: bitcoin::Wtxid::all_zeros(),
+                    size: 0,
+                    vsize: 0,
+                    version: 2,
+                    locktime: 0,
+                    vin: Vec::new(),
+                    vout: Vec::new(),
+                    blockhash,
+                    confirmations,
+                    time: None,
+                    blocktime: None,
+                })
+            });
+
+        if let Some(lookups) = lookups {
+            expectation.times(lookups);
+        }
+
+        client
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
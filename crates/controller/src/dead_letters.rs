@@ -0,0 +1,130 @@
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use std::time::SystemTime;
+
+/// Default number of the dead letters kept in the log.
+const DEFAULT_DEAD_LETTERS_CAPACITY: usize = 100;
+
+/// Message the [`Controller`](crate::Controller) failed to handle.
+#[derive(Clone, Debug)]
+pub struct DeadLetter {
+    /// Time the message was dropped at.
+    pub timestamp: SystemTime,
+    /// Kind of the message, e.g. `P2P::Inv`.
+    pub kind: &'static str,
+    /// Error the handling of the message failed with, with its causes.
+    pub error: String,
+}
+
+/// Log of the recently dropped messages, that is kept in memory for the diagnostics.
+///
+/// The oldest letter is dropped from the log when it's full.
+#[derive(Clone, Debug)]
+pub struct DeadLetters {
+    letters: Arc<Mutex<VecDeque<DeadLetter>>>,
+    capacity: usize,
+}
+
+impl DeadLetters {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            letters: Arc::new(Mutex::new(VecDeque::with_capacity(capacity))),
+            capacity,
+        }
+    }
+
+    /// Put the letter to the log, dropping the oldest one if the log is full.
+    pub fn push(&self, letter: DeadLetter) {
+        if self.capacity == 0 {
+            return;
+        }
+
+        let mut letters = self.letters.lock().expect("dead letters lock is poisoned");
+
+        if letters.len() >= self.capacity {
+            letters.pop_front();
+        }
+
+        letters.push_back(letter);
+    }
+
+    /// Get the letters in the log, from the oldest to the newest.
+    pub fn list(&self) -> Vec<DeadLetter> {
+        let letters = self.letters.lock().expect("dead letters lock is poisoned");
+
+        letters.iter().cloned().collect()
+    }
+}
+
+impl Default for DeadLetters {
+    fn default() -> Self {
+        Self::new(DEFAULT_DEAD_LETTERS_CAPACITY)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn letter(kind: &'static str) -> DeadLetter {
+        DeadLetter {
+            timestamp: SystemTime::now(),
+            kind,
+            error: format!("failed to handle {kind}"),
+        }
+    }
+
+    fn kinds(dead_letters: &DeadLetters) -> Vec<&'static str> {
+        dead_letters
+            .list()
+            .into_iter()
+            .map(|letter| letter.kind)
+            .collect()
+    }
+
+    #[test]
+    fn test_letters_are_listed_from_oldest() {
+        let dead_letters = DeadLetters::new(3);
+        assert!(dead_letters.list().is_empty());
+
+        dead_letters.push(letter("P2P::Inv"));
+        dead_letters.push(letter("P2P::GetData"));
+
+        let letters = dead_letters.list();
+        assert_eq!(kinds(&dead_letters), vec!["P2P::Inv", "P2P::GetData"]);
+        assert_eq!(letters[0].error, "failed to handle P2P::Inv");
+    }
+
+    #[test]
+    fn test_oldest_letters_expire_when_log_is_full() {
+        let dead_letters = DeadLetters::new(2);
+
+        dead_letters.push(letter("P2P::Inv"));
+        dead_letters.push(letter("P2P::GetData"));
+        dead_letters.push(letter("P2P::YuvTx"));
+
+        assert_eq!(kinds(&dead_letters), vec!["P2P::GetData", "P2P::YuvTx"]);
+
+        dead_letters.push(letter("InvalidTxs"));
+
+        assert_eq!(kinds(&dead_letters), vec!["P2P::YuvTx", "InvalidTxs"]);
+    }
+
+    #[test]
+    fn test_clones_share_the_log() {
+        let dead_letters = DeadLetters::new(2);
+
+        dead_letters.clone().push(letter("P2P::Inv"));
+
+        assert_eq!(kinds(&dead_letters), vec!["P2P::Inv"]);
+    }
+
+    #[test]
+    fn test_log_of_zero_capacity_keeps_nothing() {
+        let dead_letters = DeadLetters::new(0);
+
+        dead_letters.push(letter("P2P::Inv"));
+
+        assert!(dead_letters.list().is_empty());
+    }
+}
@@ -1,5 +1,6 @@
+use std::collections::HashMap;
 use std::net::SocketAddr;
-use std::time::Duration;
+use std::time::{Duration, Instant, SystemTime};
 
 use bitcoin::Txid;
 use event_bus::{typeid, EventBus};
@@ -8,18 +9,28 @@ use tokio_util::sync::CancellationToken;
 use tracing::trace;
 
 use yuv_p2p::client::handle::Handle as ClientHandle;
-use yuv_storage::{InventoryStorage, TransactionsStorage, TxState, TxStatesStorage};
+use yuv_storage::{
+    InvalidTxsStorage, InventoryStorage, TransactionsStorage, TxState, TxStatesStorage,
+};
 use yuv_types::{
     messages::p2p::Inventory, Announcement, ControllerMessage, ControllerP2PMessage,
     TxConfirmMessage, YuvTransaction, YuvTxType,
 };
 
+use crate::{DeadLetter, DeadLetters, PartialProofs, WebhookSink};
+
 /// Default inventory size.
 const DEFAULT_INV_SIZE: usize = 100;
 
 /// Default inventory sharing interval in seconds.
 const DEFAULT_INV_SHARE_INTERVAL: u64 = 5;
 
+/// Default interval between expirations of invalid transactions in seconds.
+const DEFAULT_INVALID_TXS_EXPIRY_INTERVAL: u64 = 10 * 60;
+
+/// Default age after which invalid transactions are expired in seconds.
+const DEFAULT_INVALID_TXS_MAX_AGE: u64 = 24 * 60 * 60;
+
 /// Controller handles Inv, GetData, YuvTx P2P methods. Selects new transactions from outside
 /// and provides it to the TransactionChecker.
 #[derive(Clone)]
@@ -47,14 +58,34 @@ where
     /// Inventory sharing interval
     inv_sharing_interval: Duration,
 
+    /// Interval between expirations of invalid transactions
+    invalid_txs_expiry_interval: Duration,
+
+    /// Age after which invalid transactions are expired
+    invalid_txs_max_age: Duration,
+
     /// P2P handle which is used for sending messages to other peers
     p2p_handle: P2pClient,
+
+    /// Log of the messages that failed to be handled
+    dead_letters: DeadLetters,
+
+    /// Transactions which proofs are provided partially, expired with the invalid ones
+    partial_proofs: PartialProofs,
+
+    /// Sink of the attached transactions posted to the webhook, if it's configured
+    webhook: Option<WebhookSink>,
+
+    /// Transactions ignored by the checkers with the time they were ignored at, which are not
+    /// requested from the peers again until the chroma filter is reloaded or they expire with
+    /// the invalid ones
+    ignored_txs: HashMap<Txid, Instant>,
 }
 
 impl<TS, SS, P2P> Controller<TS, SS, P2P>
 where
     TS: TransactionsStorage + Send + Sync + Clone + 'static,
-    SS: InventoryStorage + Send + Sync + Clone + 'static,
+    SS: InventoryStorage + InvalidTxsStorage + Send + Sync + Clone + 'static,
     P2P: ClientHandle + Send + Sync + Clone + 'static,
 {
     pub fn new(
@@ -74,8 +105,14 @@ where
             handling_txs: txstates_storage,
             max_inv_size: DEFAULT_INV_SIZE,
             inv_sharing_interval: Duration::from_secs(DEFAULT_INV_SHARE_INTERVAL),
+            invalid_txs_expiry_interval: Duration::from_secs(DEFAULT_INVALID_TXS_EXPIRY_INTERVAL),
+            invalid_txs_max_age: Duration::from_secs(DEFAULT_INVALID_TXS_MAX_AGE),
             event_bus,
             p2p_handle,
+            dead_letters: DeadLetters::default(),
+            partial_proofs: PartialProofs::default(),
+            webhook: None,
+            ignored_txs: HashMap::new(),
         }
     }
 
@@ -93,11 +130,48 @@ where
         self
     }
 
-    /// Runs the Controller. It listens to the events from the event bus to handle and
-    /// inventory interval timer to share inventory.
+    /// Sets interval between expirations of invalid transactions.
+    pub fn set_invalid_txs_expiry_interval(mut self, interval: Duration) -> Self {
+        self.invalid_txs_expiry_interval = interval;
+
+        self
+    }
+
+    /// Sets age after which invalid transactions are expired.
+    pub fn set_invalid_txs_max_age(mut self, max_age: Duration) -> Self {
+        self.invalid_txs_max_age = max_age;
+
+        self
+    }
+
+    /// Sets log the messages that failed to be handled are put to.
+    pub fn set_dead_letters(mut self, dead_letters: DeadLetters) -> Self {
+        self.dead_letters = dead_letters;
+
+        self
+    }
+
+    /// Sets buffer of the partially proven transactions to expire.
+    pub fn set_partial_proofs(mut self, partial_proofs: PartialProofs) -> Self {
+        self.partial_proofs = partial_proofs;
+
+        self
+    }
+
+    /// Sets sink of the attached transactions posted to the webhook.
+    pub fn set_webhook(mut self, webhook: WebhookSink) -> Self {
+        self.webhook = Some(webhook);
+
+        self
+    }
+
+    /// Runs the Controller. It listens to the events from the event bus to handle,
+    /// inventory interval timer to share inventory and expiry interval timer to drop
+    /// outdated invalid and partially proven transactions.
     pub async fn run(mut self, cancellation: CancellationToken) {
         let events = self.event_bus.subscribe::<ControllerMessage>();
         let mut inv_ticker = tokio::time::interval(self.inv_sharing_interval);
+        let mut expiry_ticker = tokio::time::interval(self.invalid_txs_expiry_interval);
 
         loop {
             tokio::select! {
@@ -107,8 +181,16 @@ where
                         return;
                     };
 
+                    let kind = message_kind(&event);
+
                     if let Err(err) = self.handle_event(event).await {
                         tracing::error!("Failed to handle an event: {}", err);
+
+                        self.dead_letters.push(DeadLetter {
+                            timestamp: SystemTime::now(),
+                            kind,
+                            error: format!("{:#}", err),
+                        });
                     }
                 }
                 _ = inv_ticker.tick() => {
@@ -116,6 +198,13 @@ where
                         tracing::error!("Failed to share an inventory: {}", err);
                     }
                 }
+                _ = expiry_ticker.tick() => {
+                    if let Err(err) = self.expire_invalid_txs().await {
+                        tracing::error!("Failed to expire invalid txs: {}", err);
+                    }
+                    self.expire_partial_proofs();
+                    self.expire_ignored_txs();
+                }
                 _ = cancellation.cancelled() => {
                     trace!("Cancellation received, stopping controller");
                     return;
@@ -152,7 +241,13 @@ where
                 .handle_new_yuv_txs(txs, None)
                 .await
                 .wrap_err("failed to handle transaction to confirm")?,
+            Message::IgnoredTxs(tx_ids) => self.handle_ignored_txs(tx_ids).await,
+            Message::ChromaFilterReloaded => self.handle_chroma_filter_reloaded(),
             Message::CheckedAnnouncement(txid) => self.handle_checked_announcement(txid).await,
+            Message::ShareInventory(txids) => self
+                .handle_share_inventory(txids)
+                .await
+                .wrap_err("failed to share requested inventory")?,
         }
 
         Ok(())
@@ -203,6 +298,46 @@ where
         Ok(())
     }
 
+    /// Drops the invalid transactions that are older than
+    /// [`invalid_txs_max_age`](Controller::invalid_txs_max_age).
+    async fn expire_invalid_txs(&self) -> Result<()> {
+        let expired = self
+            .state_storage
+            .expire_invalid_txs(self.invalid_txs_max_age)
+            .await?;
+
+        if expired > 0 {
+            tracing::debug!("Expired {} invalid txs", expired);
+        }
+
+        Ok(())
+    }
+
+    /// Drops the partially proven transactions which proofs aren't completed in time.
+    fn expire_partial_proofs(&self) {
+        let expired = self.partial_proofs.expire();
+
+        if expired > 0 {
+            tracing::debug!("Expired {} partially proven txs", expired);
+        }
+    }
+
+    /// Forgets the ignored transactions that are older than
+    /// [`invalid_txs_max_age`](Controller::invalid_txs_max_age), so they don't pile up when the
+    /// chroma filter is never reloaded.
+    fn expire_ignored_txs(&mut self) {
+        let max_age = self.invalid_txs_max_age;
+        let len = self.ignored_txs.len();
+
+        self.ignored_txs
+            .retain(|_, ignored_at| ignored_at.elapsed() < max_age);
+
+        let expired = len - self.ignored_txs.len();
+        if expired > 0 {
+            tracing::debug!("Expired {} ignored txs", expired);
+        }
+    }
+
     /// Shares inventory with the network.
     async fn share_inv(&self) -> Result<()> {
         let inv: Vec<Inventory> = self
@@ -223,6 +358,49 @@ where
         Ok(())
     }
 
+    /// Shares the inventory of the given transactions with the network right away. Only the first
+    /// [`max_inv_size`](Controller::max_inv_size) transactions are shared, and the ones that
+    /// aren't attached are skipped, as they couldn't be provided to the peers.
+    async fn handle_share_inventory(&self, txids: Vec<Txid>) -> Result<()> {
+        if txids.len() > self.max_inv_size {
+            tracing::warn!(
+                "Only {} of {} requested txs are shared",
+                self.max_inv_size,
+                txids.len()
+            );
+        }
+
+        let mut inv = Vec::new();
+
+        for txid in txids.into_iter().take(self.max_inv_size) {
+            let yuv_tx = self
+                .txs_storage
+                .get_yuv_tx(&txid)
+                .await
+                .wrap_err("failed to get yuv tx")?;
+
+            if yuv_tx.is_none() {
+                tracing::debug!("Tx {} isn't attached, so it's not shared", txid);
+                continue;
+            }
+
+            inv.push(Inventory::Ytx(txid));
+        }
+
+        if inv.is_empty() {
+            return Ok(());
+        }
+
+        self.p2p_handle
+            .send_inv(inv.clone())
+            .await
+            .wrap_err_with(|| format!("failed to share inventory; inv={:?}", inv))?;
+
+        tracing::info!("Requested inventory of {} txs has been shared", inv.len());
+
+        Ok(())
+    }
+
     /// Handles an inv message from the network. It checks if the transaction is already
     /// handled. If not, it will request the transaction from the [`Inv`] sender.
     async fn handle_inv(&mut self, inv: Vec<Inventory>, sender: SocketAddr) -> Result<()> {
@@ -335,6 +513,11 @@ where
     /// Handles attached transactions. It removes them from the handling_txs list and update
     /// inventory in [`InventoryStorage`].
     pub async fn handle_attached_txs(&mut self, txids: Vec<Txid>) -> Result<()> {
+        // The webhook is only queued, so its failures don't hold the attached txs.
+        if let Some(webhook) = &self.webhook {
+            webhook.notify(txids.clone());
+        }
+
         let mut inv = self.state_storage.get_inventory().await?;
 
         for txid in txids {
@@ -356,6 +539,30 @@ where
         Ok(())
     }
 
+    /// Handles transactions ignored by the checkers. It removes them from the handling_txs
+    /// list and remembers them, so they aren't requested from the peers again until the chroma
+    /// filter is reloaded or they expire.
+    pub async fn handle_ignored_txs(&mut self, tx_ids: Vec<Txid>) {
+        self.handling_txs.remove_many(&tx_ids).await;
+
+        let now = Instant::now();
+        self.ignored_txs
+            .extend(tx_ids.iter().map(|txid| (*txid, now)));
+
+        tracing::debug!("Ignored txs are dropped: {:?}", tx_ids);
+    }
+
+    /// Handles reload of the chroma filter. It forgets the ignored transactions, so they could
+    /// be received again and checked with the new filter.
+    pub fn handle_chroma_filter_reloaded(&mut self) {
+        tracing::debug!(
+            "Chroma filter is reloaded, forgetting {} ignored txs",
+            self.ignored_txs.len()
+        );
+
+        self.ignored_txs.clear();
+    }
+
     /// Handles checked announcement. It removes it from the handling_txs list.
     pub async fn handle_checked_announcement(&mut self, txid: Txid) {
         self.handling_txs.remove(&txid).await;
@@ -385,7 +592,7 @@ where
     }
 
     async fn is_tx_exist(&self, tx_id: &Txid) -> Result<bool> {
-        if self.handling_txs.get(tx_id).await.is_some() {
+        if self.ignored_txs.contains_key(tx_id) || self.handling_txs.get(tx_id).await.is_some() {
             return Ok(true);
         }
 
@@ -408,3 +615,158 @@ where
         Ok(false)
     }
 }
+
+/// Name of the message's kind to put to the dead letters.
+fn message_kind(message: &ControllerMessage) -> &'static str {
+    match message {
+        ControllerMessage::InvalidTxs { .. } => "InvalidTxs",
+        ControllerMessage::GetData { .. } => "GetData",
+        ControllerMessage::ConfirmBatchTx(_) => "ConfirmBatchTx",
+        ControllerMessage::IgnoredTxs(_) => "IgnoredTxs",
+        ControllerMessage::ChromaFilterReloaded => "ChromaFilterReloaded",
+        ControllerMessage::CheckedAnnouncement(_) => "CheckedAnnouncement",
+        ControllerMessage::AttachedTxs(_) => "AttachedTxs",
+        ControllerMessage::P2P(ControllerP2PMessage::Inv { .. }) => "P2P::Inv",
+        ControllerMessage::P2P(ControllerP2PMessage::GetData { .. }) => "P2P::GetData",
+        ControllerMessage::P2P(ControllerP2PMessage::YuvTx { .. }) => "P2P::YuvTx",
+        ControllerMessage::ShareInventory(_) => "ShareInventory",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use yuv_p2p::client::handle::MockHandle;
+    use yuv_storage::LevelDB;
+    use yuv_types::mocks::yuv_tx;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_requested_inventory_is_shared_right_away() -> eyre::Result<()> {
+        let storage = LevelDB::in_memory()?;
+
+        let mut event_bus = EventBus::default();
+        event_bus.register::<TxConfirmMessage>(Some(100));
+        event_bus.register::<ControllerMessage>(Some(100));
+
+        let attached_txids = [yuv_tx(1), yuv_tx(2), yuv_tx(3)]
+            .into_iter()
+            .map(|tx| tx.bitcoin_tx.txid())
+            .collect::<Vec<_>>();
+        for version in 1..=3 {
+            storage.put_yuv_tx(yuv_tx(version)).await?;
+        }
+        let not_attached_txid = yuv_tx(4).bitcoin_tx.txid();
+
+        // Only the attached txs within the max inventory size are shared.
+        let expected_inv = attached_txids[..2]
+            .iter()
+            .map(|txid| Inventory::Ytx(*txid))
+            .collect::<Vec<_>>();
+
+        let mut p2p_handle = MockHandle::new();
+        p2p_handle
+            .expect_send_inv()
+            .withf(move |inv| *inv == expected_inv)
+            .times(1)
+            .returning(|_| Ok(()));
+
+        let mut controller = Controller::new(
+            &event_bus,
+            storage.clone(),
+            storage,
+            TxStatesStorage::default(),
+            p2p_handle,
+        )
+        .set_max_inv_size(3);
+
+        let mut txids = vec![not_attached_txid];
+        txids.extend(&attached_txids);
+
+        controller
+            .handle_event(ControllerMessage::ShareInventory(txids))
+            .await?;
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_ignored_txs_are_not_requested_until_filter_is_reloaded() -> eyre::Result<()> {
+        let storage = LevelDB::in_memory()?;
+
+        let mut event_bus = EventBus::default();
+        event_bus.register::<TxConfirmMessage>(Some(100));
+        event_bus.register::<ControllerMessage>(Some(100));
+
+        let txid = yuv_tx(1).bitcoin_tx.txid();
+        let sender = SocketAddr::from(([127, 0, 0, 1], 8333));
+
+        // The tx is requested only once, after the chroma filter is reloaded.
+        let mut p2p_handle = MockHandle::new();
+        p2p_handle
+            .expect_send_get_data()
+            .withf(move |inv, _| *inv == vec![Inventory::Ytx(txid)])
+            .times(1)
+            .returning(|_, _| Ok(()));
+
+        let mut controller = Controller::new(
+            &event_bus,
+            storage.clone(),
+            storage,
+            TxStatesStorage::default(),
+            p2p_handle,
+        );
+
+        let inv = || ControllerP2PMessage::Inv {
+            inv: vec![Inventory::Ytx(txid)],
+            sender,
+        };
+
+        controller
+            .handle_event(ControllerMessage::IgnoredTxs(vec![txid]))
+            .await?;
+        controller
+            .handle_event(ControllerMessage::P2P(inv()))
+            .await?;
+
+        controller
+            .handle_event(ControllerMessage::ChromaFilterReloaded)
+            .await?;
+        controller
+            .handle_event(ControllerMessage::P2P(inv()))
+            .await?;
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_ignored_txs_expire_with_invalid_ones() -> eyre::Result<()> {
+        let storage = LevelDB::in_memory()?;
+
+        let mut event_bus = EventBus::default();
+        event_bus.register::<TxConfirmMessage>(Some(100));
+        event_bus.register::<ControllerMessage>(Some(100));
+
+        let txid = yuv_tx(1).bitcoin_tx.txid();
+
+        let mut controller = Controller::new(
+            &event_bus,
+            storage.clone(),
+            storage,
+            TxStatesStorage::default(),
+            MockHandle::new(),
+        )
+        .set_invalid_txs_max_age(Duration::ZERO);
+
+        controller
+            .handle_event(ControllerMessage::IgnoredTxs(vec![txid]))
+            .await?;
+        assert!(controller.is_tx_exist(&txid).await?);
+
+        controller.expire_ignored_txs();
+        assert!(controller.ignored_txs.is_empty());
+        assert!(!controller.is_tx_exist(&txid).await?);
+
+        Ok(())
+    }
+}
@@ -1,2 +1,12 @@
+mod dead_letters;
 mod handler;
+mod partial_proofs;
+mod webhook;
+pub use dead_letters::{DeadLetter, DeadLetters};
 pub use handler::Controller;
+pub use partial_proofs::{
+    unproven_outputs, PartialProofs, PartialProofsError, DEFAULT_PARTIAL_PROOFS_MAX_TXS,
+};
+pub use webhook::{
+    sign_payload, Webhook, WebhookConfig, WebhookSink, SIGNATURE_HEADER, TIMESTAMP_HEADER,
+};
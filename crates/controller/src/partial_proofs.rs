@@ -0,0 +1,365 @@
+use std::collections::hash_map::Entry;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use bitcoin::{Transaction, Txid};
+use yuv_types::{ProofMap, YuvTransaction, YuvTxType};
+
+/// Default age after which the partially proven transactions are dropped.
+const DEFAULT_PARTIAL_PROOFS_MAX_AGE: Duration = Duration::from_secs(10 * 60);
+
+/// Default maximum number of the partially proven transactions kept at once.
+pub const DEFAULT_PARTIAL_PROOFS_MAX_TXS: usize = 10_000;
+
+#[derive(Debug, thiserror::Error, PartialEq, Eq)]
+pub enum PartialProofsError {
+    #[error("Input {vin} of tx {txid} already has a different proof")]
+    ConflictingInputProof { txid: Txid, vin: u32 },
+
+    #[error("Output {vout} of tx {txid} already has a different proof")]
+    ConflictingOutputProof { txid: Txid, vout: u32 },
+
+    #[error("Input {vin} of tx {txid} already has a different witness")]
+    ConflictingWitness { txid: Txid, vin: u32 },
+
+    #[error("Type of tx {txid} differs from the one of the provided proofs")]
+    MismatchedTxType { txid: Txid },
+
+    #[error("Announcement {txid} has no proofs to provide partially")]
+    Announcement { txid: Txid },
+
+    #[error("Too many partially proven txs, max amount is {max_txs}")]
+    Full { max_txs: usize },
+}
+
+/// Transaction which proofs are received so far.
+#[derive(Debug)]
+struct PartialTx {
+    yuv_tx: YuvTransaction,
+    received_at: Instant,
+}
+
+/// Buffer of the transactions which proofs are provided incrementally, e.g. by the different
+/// participants of the multisig, until all of them are received.
+///
+/// It's shared by the RPC, which merges the proofs, and the [`Controller`](crate::Controller),
+/// which drops the transactions that aren't completed in time.
+#[derive(Clone, Debug)]
+pub struct PartialProofs {
+    txs: Arc<Mutex<HashMap<Txid, PartialTx>>>,
+    max_age: Duration,
+    max_txs: usize,
+}
+
+impl PartialProofs {
+    pub fn new(max_age: Duration) -> Self {
+        Self {
+            txs: Arc::new(Mutex::new(HashMap::new())),
+            max_age,
+            max_txs: DEFAULT_PARTIAL_PROOFS_MAX_TXS,
+        }
+    }
+
+    /// Set the maximum number of the transactions kept at once. Proofs of the new transactions
+    /// are rejected when it's reached, until the kept ones are completed or expire.
+    pub fn with_max_txs(mut self, max_txs: usize) -> Self {
+        self.max_txs = max_txs;
+        self
+    }
+
+    /// Merge the proofs of the transaction with the ones received before, and return the
+    /// transaction with all the proofs received so far.
+    ///
+    /// Witnesses of the inputs are merged the same way, as the participants could sign different
+    /// inputs of the transaction.
+    ///
+    /// The proofs are not merged if any of them or the witnesses conflicts with the received one
+    /// for the same input or output, or if it's a new transaction and the buffer is full.
+    pub fn merge(&self, yuv_tx: YuvTransaction) -> Result<YuvTransaction, PartialProofsError> {
+        let txid = yuv_tx.bitcoin_tx.txid();
+
+        if let YuvTxType::Announcement(_) = yuv_tx.tx_type {
+            return Err(PartialProofsError::Announcement { txid });
+        }
+
+        let mut txs = self.txs.lock().expect("partial proofs lock is poisoned");
+
+        if !txs.contains_key(&txid) && txs.len() >= self.max_txs {
+            txs.retain(|_, tx| tx.received_at.elapsed() < self.max_age);
+
+            if txs.len() >= self.max_txs {
+                return Err(PartialProofsError::Full {
+                    max_txs: self.max_txs,
+                });
+            }
+        }
+
+        match txs.entry(txid) {
+            Entry::Vacant(entry) => {
+                entry.insert(PartialTx {
+                    yuv_tx: yuv_tx.clone(),
+                    received_at: Instant::now(),
+                });
+
+                Ok(yuv_tx)
+            }
+            Entry::Occupied(mut entry) => {
+                let received = &entry.get().yuv_tx;
+                let bitcoin_tx = merge_witnesses(txid, &received.bitcoin_tx, yuv_tx.bitcoin_tx)?;
+                let tx_type = merge_tx_types(txid, &received.tx_type, yuv_tx.tx_type)?;
+
+                entry.get_mut().yuv_tx = YuvTransaction::new(bitcoin_tx, tx_type);
+
+                Ok(entry.get().yuv_tx.clone())
+            }
+        }
+    }
+
+    /// Drop the transaction, e.g. when all its proofs are received.
+    pub fn remove(&self, txid: &Txid) {
+        self.txs
+            .lock()
+            .expect("partial proofs lock is poisoned")
+            .remove(txid);
+    }
+
+    /// Drop the transactions that are received earlier than the max age ago. Returns the number
+    /// of the dropped transactions.
+    pub fn expire(&self) -> usize {
+        let mut txs = self.txs.lock().expect("partial proofs lock is poisoned");
+        let len = txs.len();
+
+        txs.retain(|_, tx| tx.received_at.elapsed() < self.max_age);
+
+        len - txs.len()
+    }
+
+    /// Number of the transactions which proofs are being received.
+    pub fn len(&self) -> usize {
+        self.txs
+            .lock()
+            .expect("partial proofs lock is poisoned")
+            .len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl Default for PartialProofs {
+    fn default() -> Self {
+        Self::new(DEFAULT_PARTIAL_PROOFS_MAX_AGE)
+    }
+}
+
+/// Outputs of the transaction that have no proofs yet, except the OP_RETURN ones, which can't
+/// hold pixels.
+pub fn unproven_outputs(yuv_tx: &YuvTransaction) -> Vec<u32> {
+    let output_proofs = yuv_tx.tx_type.output_proofs();
+
+    (0..yuv_tx.bitcoin_tx.output.len() as u32)
+        .filter(|vout| {
+            !yuv_tx.bitcoin_tx.output[*vout as usize]
+                .script_pubkey
+                .is_op_return()
+        })
+        .filter(|vout| !output_proofs.is_some_and(|proofs| proofs.contains_key(vout)))
+        .collect()
+}
+
+fn merge_tx_types(
+    txid: Txid,
+    received: &YuvTxType,
+    provided: YuvTxType,
+) -> Result<YuvTxType, PartialProofsError> {
+    match (received, provided) {
+        (
+            YuvTxType::Issue {
+                output_proofs: received_outputs,
+                announcement: received_announcement,
+            },
+            YuvTxType::Issue {
+                output_proofs,
+                announcement,
+            },
+        ) if *received_announcement == announcement => {
+            let output_proofs = merge_proofs(
+                received_outputs.clone().unwrap_or_default(),
+                output_proofs.unwrap_or_default(),
+            )
+            .map_err(|vout| PartialProofsError::ConflictingOutputProof { txid, vout })?;
+
+            Ok(YuvTxType::Issue {
+                output_proofs: Some(output_proofs),
+                announcement,
+            })
+        }
+        (
+            YuvTxType::Transfer {
+                input_proofs: received_inputs,
+                output_proofs: received_outputs,
+            },
+            YuvTxType::Transfer {
+                input_proofs,
+                output_proofs,
+            },
+        ) => {
+            let input_proofs = merge_proofs(received_inputs.clone(), input_proofs)
+                .map_err(|vin| PartialProofsError::ConflictingInputProof { txid, vin })?;
+            let output_proofs = merge_proofs(received_outputs.clone(), output_proofs)
+                .map_err(|vout| PartialProofsError::ConflictingOutputProof { txid, vout })?;
+
+            Ok(YuvTxType::Transfer {
+                input_proofs,
+                output_proofs,
+            })
+        }
+        _ => Err(PartialProofsError::MismatchedTxType { txid }),
+    }
+}
+
+/// Merge the witnesses of the inputs to the received ones, which are empty for the inputs not
+/// signed yet. The transactions have the same txid, so they differ only in the witnesses.
+fn merge_witnesses(
+    txid: Txid,
+    received: &Transaction,
+    mut provided: Transaction,
+) -> Result<Transaction, PartialProofsError> {
+    for (vin, (received_input, provided_input)) in received
+        .input
+        .iter()
+        .zip(provided.input.iter_mut())
+        .enumerate()
+    {
+        if provided_input.witness.is_empty() {
+            provided_input.witness = received_input.witness.clone();
+        } else if !received_input.witness.is_empty()
+            && received_input.witness != provided_input.witness
+        {
+            return Err(PartialProofsError::ConflictingWitness {
+                txid,
+                vin: vin as u32,
+            });
+        }
+    }
+
+    Ok(provided)
+}
+
+/// Merge the proofs to the received ones. Returns the index of the first proof that differs from
+/// the received one.
+fn merge_proofs(mut received: ProofMap, provided: ProofMap) -> Result<ProofMap, u32> {
+    for (index, proof) in provided {
+        match received.get(&index) {
+            Some(received_proof) if *received_proof != proof => return Err(index),
+            Some(_) => {}
+            None => {
+                received.insert(index, proof);
+            }
+        }
+    }
+
+    Ok(received)
+}
+
+#[cfg(test)]
+mod tests {
+    use bitcoin::{PackedLockTime, Script, TxIn, TxOut, Witness};
+
+    use super::*;
+
+    fn transfer(version: i32) -> YuvTransaction {
+        YuvTransaction::new(
+            Transaction {
+                version,
+                lock_time: PackedLockTime::ZERO,
+                input: Vec::new(),
+                output: vec![
+                    TxOut::default(),
+                    TxOut {
+                        value: 0,
+                        script_pubkey: Script::new_op_return(&[]),
+                    },
+                ],
+            },
+            YuvTxType::Transfer {
+                input_proofs: ProofMap::new(),
+                output_proofs: ProofMap::new(),
+            },
+        )
+    }
+
+    #[test]
+    fn test_op_return_outputs_need_no_proofs() {
+        assert_eq!(unproven_outputs(&transfer(1)), vec![0]);
+    }
+
+    #[test]
+    fn test_witnesses_are_merged() {
+        let partial_proofs = PartialProofs::default();
+
+        let mut unsigned = transfer(1);
+        unsigned.bitcoin_tx.input = vec![TxIn::default(), TxIn::default()];
+
+        let mut first = unsigned.clone();
+        first.bitcoin_tx.input[0].witness = Witness::from_vec(vec![vec![1]]);
+        let mut second = unsigned.clone();
+        second.bitcoin_tx.input[1].witness = Witness::from_vec(vec![vec![2]]);
+
+        partial_proofs.merge(first.clone()).unwrap();
+        let merged = partial_proofs.merge(second.clone()).unwrap();
+        assert_eq!(
+            merged.bitcoin_tx.input[0].witness,
+            first.bitcoin_tx.input[0].witness
+        );
+        assert_eq!(
+            merged.bitcoin_tx.input[1].witness,
+            second.bitcoin_tx.input[1].witness
+        );
+
+        // The submission without the witnesses keeps the received ones.
+        let merged = partial_proofs.merge(unsigned.clone()).unwrap();
+        assert_eq!(
+            merged.bitcoin_tx.input[1].witness,
+            second.bitcoin_tx.input[1].witness
+        );
+
+        let mut conflicting = unsigned;
+        conflicting.bitcoin_tx.input[0].witness = Witness::from_vec(vec![vec![3]]);
+        assert_eq!(
+            partial_proofs.merge(conflicting),
+            Err(PartialProofsError::ConflictingWitness {
+                txid: first.bitcoin_tx.txid(),
+                vin: 0,
+            })
+        );
+    }
+
+    #[test]
+    fn test_new_txs_are_rejected_when_full() {
+        let partial_proofs = PartialProofs::default().with_max_txs(1);
+
+        partial_proofs.merge(transfer(1)).unwrap();
+        // Proofs of the kept transaction are still merged.
+        partial_proofs.merge(transfer(1)).unwrap();
+
+        assert_eq!(
+            partial_proofs.merge(transfer(2)),
+            Err(PartialProofsError::Full { max_txs: 1 })
+        );
+
+        partial_proofs.remove(&transfer(1).bitcoin_tx.txid());
+        partial_proofs.merge(transfer(2)).unwrap();
+    }
+
+    #[test]
+    fn test_expired_txs_make_room_for_new_ones() {
+        let partial_proofs = PartialProofs::new(Duration::ZERO).with_max_txs(1);
+
+        partial_proofs.merge(transfer(1)).unwrap();
+        partial_proofs.merge(transfer(2)).unwrap();
+        assert_eq!(partial_proofs.len(), 1);
+    }
+}
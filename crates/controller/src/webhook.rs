@@ -0,0 +1,486 @@
+use std::collections::{BTreeSet, HashSet};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use bitcoin::hashes::{hex::ToHex, hmac, sha256, Hash, HashEngine};
+use bitcoin::Txid;
+use eyre::{ensure, WrapErr};
+use serde::Serialize;
+use tokio::sync::mpsc;
+use tokio_util::sync::CancellationToken;
+use tracing::trace;
+
+use yuv_pixels::Chroma;
+use yuv_storage::TransactionsStorage;
+use yuv_types::YuvTransaction;
+
+/// Header with the hex encoded HMAC-SHA256 of the timestamp and the payload signed with the
+/// webhook's secret, see [`sign_payload`].
+pub const SIGNATURE_HEADER: &str = "X-Yuv-Signature";
+
+/// Header with the UNIX timestamp in seconds the payload is signed at. Receivers should reject
+/// the payloads with the old timestamps, so the captured requests can't be replayed.
+pub const TIMESTAMP_HEADER: &str = "X-Yuv-Timestamp";
+
+/// Default number of the attached batches waiting for the delivery.
+const DEFAULT_WEBHOOK_QUEUE_SIZE: usize = 1000;
+
+/// The limit of the delay between the delivery attempts.
+const MAX_RETRY_DELAY: Duration = Duration::from_secs(60);
+
+/// Settings of the webhook the attached transactions are posted to.
+#[derive(Clone, Debug)]
+pub struct WebhookConfig {
+    /// URL the JSON payload is posted to.
+    pub url: String,
+    /// If set, only the transactions of these chromas are posted.
+    pub chromas: Option<HashSet<Chroma>>,
+    /// Secret the payload is signed with, see [`SIGNATURE_HEADER`].
+    pub secret: Option<String>,
+    /// Number of retries of the failed delivery, after which the payload is dropped.
+    pub max_retries: u32,
+    /// Delay before the first retry, doubled on each next one.
+    pub retry_delay: Duration,
+    /// Timeout of a single delivery attempt.
+    pub timeout: Duration,
+}
+
+/// Attached transaction as it's posted to the webhook.
+#[derive(Serialize)]
+struct AttachedTx<'a> {
+    txid: Txid,
+    chromas: BTreeSet<Chroma>,
+    tx: &'a YuvTransaction,
+}
+
+/// Payload posted to the webhook.
+#[derive(Serialize)]
+struct WebhookPayload<'a> {
+    txs: Vec<AttachedTx<'a>>,
+}
+
+/// Sink of the attached transactions routed by the [`Controller`](crate::Controller) to the
+/// [`Webhook`].
+///
+/// The batch is dropped if the queue is full, so the slow webhook doesn't block the attach
+/// pipeline.
+#[derive(Clone, Debug)]
+pub struct WebhookSink {
+    sender: mpsc::Sender<Vec<Txid>>,
+}
+
+impl WebhookSink {
+    /// Queue the attached transactions for the delivery. Returns `false` if they are dropped.
+    pub fn notify(&self, txids: Vec<Txid>) -> bool {
+        match self.sender.try_send(txids) {
+            Ok(()) => true,
+            Err(mpsc::error::TrySendError::Full(txids)) => {
+                tracing::warn!(
+                    "Webhook queue is full, dropping {} attached txs",
+                    txids.len()
+                );
+                false
+            }
+            Err(mpsc::error::TrySendError::Closed(_)) => false,
+        }
+    }
+}
+
+/// Posts the attached transactions of the watched chromas to the configured URL, retrying the
+/// failed deliveries with exponential backoff.
+pub struct Webhook<TxsStorage> {
+    config: WebhookConfig,
+    url: reqwest::Url,
+    client: reqwest::Client,
+    txs_storage: TxsStorage,
+    receiver: mpsc::Receiver<Vec<Txid>>,
+}
+
+impl<TS> Webhook<TS>
+where
+    TS: TransactionsStorage + Send + Sync + 'static,
+{
+    pub fn new(config: WebhookConfig, txs_storage: TS) -> eyre::Result<(Self, WebhookSink)> {
+        let url = reqwest::Url::parse(&config.url)
+            .wrap_err_with(|| format!("invalid webhook URL {}", config.url))?;
+        let client = reqwest::Client::builder()
+            .timeout(config.timeout)
+            .build()
+            .wrap_err("failed to create webhook HTTP client")?;
+
+        let (sender, receiver) = mpsc::channel(DEFAULT_WEBHOOK_QUEUE_SIZE);
+
+        let webhook = Self {
+            config,
+            url,
+            client,
+            txs_storage,
+            receiver,
+        };
+
+        Ok((webhook, WebhookSink { sender }))
+    }
+
+    /// Runs the Webhook. It delivers the queued attached transactions one batch at a time.
+    pub async fn run(mut self, cancellation: CancellationToken) {
+        loop {
+            let txids = tokio::select! {
+                received = self.receiver.recv() => {
+                    let Some(txids) = received else {
+                        trace!("Webhook sink is dropped");
+                        return;
+                    };
+
+                    txids
+                }
+                _ = cancellation.cancelled() => {
+                    trace!("Cancellation received, stopping webhook");
+                    return;
+                }
+            };
+
+            tokio::select! {
+                _ = self.deliver(txids) => {}
+                _ = cancellation.cancelled() => {
+                    trace!("Cancellation received, stopping webhook");
+                    return;
+                }
+            }
+        }
+    }
+
+    /// Post the watched ones of the attached transactions, dropping them if all the attempts fail.
+    async fn deliver(&self, txids: Vec<Txid>) {
+        let mut txs = Vec::with_capacity(txids.len());
+        for txid in txids {
+            match self.txs_storage.get_yuv_tx(&txid).await {
+                Ok(Some(tx)) => txs.push(tx),
+                Ok(None) => tracing::warn!("Attached tx {} is not found for the webhook", txid),
+                Err(err) => {
+                    tracing::warn!(
+                        "Failed to get attached tx {} for the webhook: {}",
+                        txid,
+                        err
+                    )
+                }
+            }
+        }
+
+        let payload = WebhookPayload {
+            txs: txs.iter().filter_map(|tx| self.attached_tx(tx)).collect(),
+        };
+        if payload.txs.is_empty() {
+            return;
+        }
+
+        let body = match serde_json::to_vec(&payload) {
+            Ok(body) => body,
+            Err(err) => {
+                tracing::error!("Failed to serialize webhook payload: {}", err);
+                return;
+            }
+        };
+
+        let mut delay = self.config.retry_delay;
+        for attempt in 0..=self.config.max_retries {
+            match self.post(&body).await {
+                Ok(()) => {
+                    tracing::debug!("Posted {} attached txs to the webhook", payload.txs.len());
+                    return;
+                }
+                Err(err) if attempt < self.config.max_retries => {
+                    tracing::warn!(
+                        "Failed to post to the webhook, retrying {}/{} in {} ms: {:#}",
+                        attempt + 1,
+                        self.config.max_retries,
+                        delay.as_millis(),
+                        err
+                    );
+
+                    tokio::time::sleep(delay).await;
+
+                    delay = delay.saturating_mul(2).min(MAX_RETRY_DELAY);
+                }
+                Err(err) => {
+                    tracing::error!(
+                        "Failed to post {} attached txs to the webhook, dropping them: {:#}",
+                        payload.txs.len(),
+                        err
+                    );
+                }
+            }
+        }
+    }
+
+    /// Form the posted entry of the transaction if it has any of the watched chromas.
+    fn attached_tx<'a>(&self, tx: &'a YuvTransaction) -> Option<AttachedTx<'a>> {
+        let chromas = tx.tx_type.chromas().into_iter().collect::<BTreeSet<_>>();
+
+        if let Some(watched) = &self.config.chromas {
+            if !chromas.iter().any(|chroma| watched.contains(chroma)) {
+                return None;
+            }
+        }
+
+        Some(AttachedTx {
+            txid: tx.bitcoin_tx.txid(),
+            chromas,
+            tx,
+        })
+    }
+
+    async fn post(&self, body: &[u8]) -> eyre::Result<()> {
+        let mut request = self
+            .client
+            .post(self.url.clone())
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .body(body.to_vec());
+
+        if let Some(secret) = &self.config.secret {
+            let timestamp = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .wrap_err("system time is before the UNIX epoch")?
+                .as_secs();
+
+            request = request
+                .header(TIMESTAMP_HEADER, timestamp)
+                .header(SIGNATURE_HEADER, sign_payload(secret, timestamp, body));
+        }
+
+        let response = request.send().await.wrap_err("failed to send request")?;
+
+        ensure!(
+            response.status().is_success(),
+            "webhook responded with status {}",
+            response.status()
+        );
+
+        Ok(())
+    }
+}
+
+/// Hex encoded HMAC-SHA256 of the `{timestamp}.{payload}` with the `secret` as the key.
+pub fn sign_payload(secret: &str, timestamp: u64, payload: &[u8]) -> String {
+    let mut engine = hmac::HmacEngine::<sha256::Hash>::new(secret.as_bytes());
+    engine.input(format!("{timestamp}.").as_bytes());
+    engine.input(payload);
+
+    hmac::Hmac::<sha256::Hash>::from_engine(engine).to_hex()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::convert::Infallible;
+    use std::net::SocketAddr;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    use bitcoin::secp256k1::{Secp256k1, SecretKey};
+    use hyper::{
+        service::{make_service_fn, service_fn},
+        Body, Request, Response, Server, StatusCode,
+    };
+    use yuv_storage::LevelDB;
+    use yuv_types::mocks;
+
+    use super::*;
+
+    fn issuer(byte: u8) -> SecretKey {
+        SecretKey::from_slice(&[byte; 32]).unwrap()
+    }
+
+    fn chroma(byte: u8) -> Chroma {
+        Chroma::from(issuer(byte).x_only_public_key(&Secp256k1::new()).0)
+    }
+
+    /// Issuance of the tokens of the `byte` issuer to itself.
+    fn issuance(byte: u8) -> YuvTransaction {
+        let issuer = issuer(byte);
+
+        mocks::issuance(&issuer, 100, issuer.public_key(&Secp256k1::new()))
+    }
+
+    fn config(chromas: Option<HashSet<Chroma>>) -> WebhookConfig {
+        WebhookConfig {
+            url: "http://127.0.0.1:8080".to_string(),
+            chromas,
+            secret: None,
+            max_retries: 0,
+            retry_delay: Duration::from_millis(10),
+            timeout: Duration::from_secs(1),
+        }
+    }
+
+    /// Request received by the [`webhook_server`].
+    struct ReceivedRequest {
+        timestamp: Option<String>,
+        signature: Option<String>,
+        body: Vec<u8>,
+    }
+
+    /// Start the local HTTP server failing the first `failures` requests, and sending all the
+    /// received ones to the returned channel.
+    fn webhook_server(failures: usize) -> (SocketAddr, mpsc::UnboundedReceiver<ReceivedRequest>) {
+        let (sender, receiver) = mpsc::unbounded_channel();
+        let received = Arc::new(AtomicUsize::new(0));
+
+        let make_service = make_service_fn(move |_conn| {
+            let sender = sender.clone();
+            let received = Arc::clone(&received);
+
+            async move {
+                Ok::<_, Infallible>(service_fn(move |request: Request<Body>| {
+                    let sender = sender.clone();
+                    let received = Arc::clone(&received);
+
+                    async move {
+                        let header = |name: &str| {
+                            request
+                                .headers()
+                                .get(name)
+                                .map(|value| value.to_str().unwrap().to_string())
+                        };
+                        let timestamp = header(TIMESTAMP_HEADER);
+                        let signature = header(SIGNATURE_HEADER);
+                        let body = hyper::body::to_bytes(request.into_body()).await.unwrap();
+
+                        sender
+                            .send(ReceivedRequest {
+                                timestamp,
+                                signature,
+                                body: body.to_vec(),
+                            })
+                            .unwrap();
+
+                        let mut response = Response::new(Body::empty());
+                        if received.fetch_add(1, Ordering::SeqCst) < failures {
+                            *response.status_mut() = StatusCode::INTERNAL_SERVER_ERROR;
+                        }
+
+                        Ok::<_, Infallible>(response)
+                    }
+                }))
+            }
+        });
+
+        let server = Server::bind(&([127, 0, 0, 1], 0).into()).serve(make_service);
+        let address = server.local_addr();
+        tokio::spawn(server);
+
+        (address, receiver)
+    }
+
+    async fn receive(requests: &mut mpsc::UnboundedReceiver<ReceivedRequest>) -> ReceivedRequest {
+        tokio::time::timeout(Duration::from_secs(5), requests.recv())
+            .await
+            .expect("webhook must post the payload")
+            .expect("server must be running")
+    }
+
+    #[test]
+    fn test_payload_signature() {
+        let signature = sign_payload("Jefe", 1700000000, b"what do ya want for nothing?");
+
+        assert_eq!(
+            signature,
+            "1cdd0650c8be1cb0974b1788d458b1e781206cfef59b85faafc582d2e182c57e"
+        );
+        assert_ne!(
+            signature,
+            sign_payload("Jefe", 1700000001, b"what do ya want for nothing?"),
+            "signature must depend on the timestamp"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_delivery_is_retried_and_signed() -> eyre::Result<()> {
+        let (address, mut requests) = webhook_server(1);
+
+        let storage = LevelDB::in_memory()?;
+        let tx = issuance(1);
+        let txid = tx.bitcoin_tx.txid();
+        storage.put_yuv_tx(tx).await?;
+
+        let config = WebhookConfig {
+            url: format!("http://{address}"),
+            secret: Some("secret".to_string()),
+            max_retries: 2,
+            ..config(None)
+        };
+        let (webhook, sink) = Webhook::new(config, storage)?;
+
+        let cancellation = CancellationToken::new();
+        tokio::spawn(webhook.run(cancellation.clone()));
+
+        assert!(sink.notify(vec![txid]));
+
+        let failed = receive(&mut requests).await;
+        let retried = receive(&mut requests).await;
+
+        assert_eq!(
+            failed.body, retried.body,
+            "retry must post the same payload"
+        );
+
+        let payload: serde_json::Value = serde_json::from_slice(&retried.body)?;
+        assert_eq!(payload["txs"][0]["txid"], txid.to_string());
+
+        let timestamp = retried.timestamp.expect("timestamp header must be set");
+        assert_eq!(
+            retried.signature.expect("signature header must be set"),
+            sign_payload("secret", timestamp.parse()?, &retried.body)
+        );
+
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert!(
+            requests.try_recv().is_err(),
+            "delivered payload must not be posted again"
+        );
+
+        cancellation.cancel();
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_only_watched_chromas_are_posted() -> eyre::Result<()> {
+        let watched = issuance(1);
+        let other = issuance(2);
+
+        let (webhook, _sink) = Webhook::new(
+            config(Some(HashSet::from([chroma(1)]))),
+            LevelDB::in_memory()?,
+        )?;
+
+        let attached = webhook
+            .attached_tx(&watched)
+            .expect("tx of the watched chroma must be posted");
+        assert_eq!(attached.txid, watched.bitcoin_tx.txid());
+        assert_eq!(attached.chromas, BTreeSet::from([chroma(1)]));
+
+        assert!(webhook.attached_tx(&other).is_none());
+
+        let (webhook, _sink) = Webhook::new(config(None), LevelDB::in_memory()?)?;
+        assert!(
+            webhook.attached_tx(&other).is_some(),
+            "all the txs must be posted without the filter"
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_full_queue_drops_batch() -> eyre::Result<()> {
+        let (_webhook, sink) = Webhook::new(config(None), LevelDB::in_memory()?)?;
+
+        for _ in 0..DEFAULT_WEBHOOK_QUEUE_SIZE {
+            assert!(sink.notify(vec![issuance(1).bitcoin_tx.txid()]));
+        }
+
+        assert!(
+            !sink.notify(vec![issuance(1).bitcoin_tx.txid()]),
+            "batch must be dropped instead of blocking the controller"
+        );
+
+        Ok(())
+    }
+}
@@ -0,0 +1,161 @@
+//! Transaction history of the wallet, streamed page by page from YUV node.
+
+use std::collections::HashSet;
+
+use bitcoin::{OutPoint, XOnlyPublicKey};
+use eyre::Context;
+use futures::{stream, Stream, TryStreamExt};
+use yuv_rpc_api::transactions::YuvTransactionsRpcClient;
+use yuv_types::{YuvTransaction, YuvTxType};
+
+use crate::sync::indexer::proof_keys;
+
+/// Direction of the wallet's transaction.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TxDirection {
+    /// The transaction has outputs to the wallet, but doesn't spend its ones.
+    Received,
+    /// The transaction spends the wallet's outputs or issues its tokens.
+    Sent,
+}
+
+/// Transaction touching the wallet's keys with its direction.
+#[derive(Clone, Debug)]
+pub struct HistoryEntry {
+    pub tx: YuvTransaction,
+    pub direction: TxDirection,
+}
+
+/// Picks the transactions touching the user's keys in the order they are
+/// attached by the node.
+///
+/// Only the outpoints of the user are kept between the pages, so the memory
+/// usage doesn't grow with the number of the node's transactions.
+pub(crate) struct HistoryTracker {
+    /// X-only public keys of the user.
+    keys: Vec<XOnlyPublicKey>,
+    /// Outputs of the user found in the previous transactions.
+    user_outpoints: HashSet<OutPoint>,
+}
+
+impl HistoryTracker {
+    pub(crate) fn new(keys: Vec<XOnlyPublicKey>) -> Self {
+        Self {
+            keys,
+            user_outpoints: HashSet::new(),
+        }
+    }
+
+    /// Return the direction of the transaction if it touches the user's keys,
+    /// remembering its outputs to the user.
+    pub(crate) fn track(&mut self, tx: &YuvTransaction) -> Option<TxDirection> {
+        let txid = tx.bitcoin_tx.txid();
+
+        let mut is_received = false;
+        if let Some(output_proofs) = tx.tx_type.output_proofs() {
+            for (vout, proof) in output_proofs {
+                if proof_keys(proof).iter().any(|key| self.is_user_key(key)) {
+                    self.user_outpoints.insert(OutPoint::new(txid, *vout));
+                    is_received = true;
+                }
+            }
+        }
+
+        let mut is_sent = false;
+        for input in &tx.bitcoin_tx.input {
+            is_sent |= self.user_outpoints.remove(&input.previous_output);
+        }
+
+        if let YuvTxType::Issue { announcement, .. } = &tx.tx_type {
+            is_sent |= self.is_user_key(announcement.chroma.xonly());
+        }
+
+        if is_sent {
+            Some(TxDirection::Sent)
+        } else if is_received {
+            Some(TxDirection::Received)
+        } else {
+            None
+        }
+    }
+
+    fn is_user_key(&self, key: &XOnlyPublicKey) -> bool {
+        self.keys.contains(key)
+    }
+}
+
+/// Stream the transactions touching the `keys` from the node, fetching the
+/// next page only when the previous one is consumed.
+pub(crate) fn history_stream<C>(
+    client: C,
+    keys: Vec<XOnlyPublicKey>,
+) -> impl Stream<Item = eyre::Result<HistoryEntry>>
+where
+    C: YuvTransactionsRpcClient + Send + Sync,
+{
+    let state = (client, HistoryTracker::new(keys), Some(0u64));
+
+    stream::try_unfold(state, |(client, mut tracker, page)| async move {
+        let Some(page) = page else {
+            return Ok::<_, eyre::Report>(None);
+        };
+
+        let txs = client
+            .list_yuv_transactions(page, None, None)
+            .await
+            .wrap_err("Failed to fetch transactions from node")?;
+
+        let next_page = (!txs.is_empty()).then_some(page + 1);
+
+        let entries = txs
+            .into_iter()
+            .filter_map(|tx| {
+                let direction = tracker.track(&tx)?;
+                Some(Ok(HistoryEntry { tx, direction }))
+            })
+            .collect::<Vec<_>>();
+
+        Ok(Some((stream::iter(entries), (client, tracker, next_page))))
+    })
+    .try_flatten()
+}
+
+#[cfg(test)]
+mod tests {
+    use bitcoin::secp256k1::{Secp256k1, SecretKey};
+    use yuv_pixels::{Chroma, Pixel};
+    use yuv_types::mocks::{issuance, transfer};
+
+    use super::*;
+
+    fn secret(byte: u8) -> SecretKey {
+        SecretKey::from_slice(&[byte; 32]).unwrap()
+    }
+
+    fn pubkey(byte: u8) -> bitcoin::secp256k1::PublicKey {
+        secret(byte).public_key(&Secp256k1::new())
+    }
+
+    #[test]
+    fn test_directions_of_transactions() {
+        let user = pubkey(1);
+        let other = pubkey(2);
+        let issuer = secret(3);
+        let pixel = Pixel::new(100, Chroma::from(pubkey(3).x_only_public_key().0));
+        let mut tracker = HistoryTracker::new(vec![user.x_only_public_key().0]);
+
+        let received = issuance(&issuer, 100, user);
+        assert_eq!(tracker.track(&received), Some(TxDirection::Received));
+
+        let unrelated = issuance(&issuer, 100, other);
+        assert_eq!(tracker.track(&unrelated), None);
+
+        let received_outpoint = OutPoint::new(received.bitcoin_tx.txid(), 0);
+        let sent = transfer(received_outpoint, &secret(1), pixel, other, 2);
+        assert_eq!(tracker.track(&sent), Some(TxDirection::Sent));
+
+        // The output is already spent, so spending it again isn't the user's one.
+        let double_spend = transfer(received_outpoint, &secret(1), pixel, other, 3);
+        assert_eq!(tracker.track(&double_spend), None);
+    }
+}
@@ -0,0 +1,148 @@
+use std::sync::{Arc, RwLock};
+
+use bitcoin::{
+    secp256k1::{All, Secp256k1},
+    util::bip32::{ChildNumber, ExtendedPrivKey},
+    PrivateKey,
+};
+use eyre::{eyre, Context};
+
+use crate::wallet::DESCRIPTOR_KEYS_LOOKAHEAD;
+
+/// Keychain of the keys which the residual YUV coins of the transfers are sent
+/// to, so each transfer gets a fresh change key instead of the primary one.
+///
+/// Keys are derived from the master key made of the primary key's secret
+/// (`m/<index>`), so the wallet restored from the same primary key re-derives
+/// them. The index of the next change key is stored by the wallet, and the
+/// keys up to it and [`DESCRIPTOR_KEYS_LOOKAHEAD`] ones after it are derived
+/// in advance to track the change of the previous runs.
+///
+/// The keychain is shared by the wallet and its transaction builders, so the
+/// keys given to the builders are tracked and spent by the wallet too.
+#[derive(Clone, Debug)]
+pub struct ChangeKeychain(Arc<RwLock<ChangeKeys>>);
+
+#[derive(Debug)]
+struct ChangeKeys {
+    master: ExtendedPrivKey,
+    /// Keys derived so far, the key at the position is derived with that index.
+    keys: Vec<PrivateKey>,
+    /// Index of the key which is given for the next change.
+    next_index: u32,
+}
+
+impl ChangeKeychain {
+    /// Create the keychain which gives the key with `next_index` for the next
+    /// change, e.g. the one stored by the previous runs of the wallet.
+    pub fn new(
+        secp_ctx: &Secp256k1<All>,
+        primary_key: &PrivateKey,
+        next_index: u32,
+    ) -> eyre::Result<Self> {
+        let master =
+            ExtendedPrivKey::new_master(primary_key.network, &primary_key.inner.secret_bytes())
+                .wrap_err("Failed to create master key of change keychain")?;
+
+        let mut keys = ChangeKeys {
+            master,
+            keys: Vec::new(),
+            next_index,
+        };
+        keys.derive_lookahead(secp_ctx)?;
+
+        Ok(Self(Arc::new(RwLock::new(keys))))
+    }
+
+    /// Return the key which wasn't given for a change before, deriving it if needed.
+    pub fn next_key(&self, secp_ctx: &Secp256k1<All>) -> eyre::Result<PrivateKey> {
+        let mut keys = self.0.write().map_err(|_| eyre!("Poisoned lock"))?;
+
+        let index = keys.next_index;
+        let key = keys.derive(secp_ctx, index)?;
+        keys.next_index += 1;
+        keys.derive_lookahead(secp_ctx)?;
+
+        Ok(key)
+    }
+
+    /// Index of the key which is given for the next change.
+    pub fn next_index(&self) -> eyre::Result<u32> {
+        Ok(self
+            .0
+            .read()
+            .map_err(|_| eyre!("Poisoned lock"))?
+            .next_index)
+    }
+
+    /// All the keys derived so far, which outputs are tracked and spent by the wallet.
+    pub fn keys(&self) -> eyre::Result<Vec<PrivateKey>> {
+        Ok(self
+            .0
+            .read()
+            .map_err(|_| eyre!("Poisoned lock"))?
+            .keys
+            .clone())
+    }
+}
+
+impl ChangeKeys {
+    /// Derive the keys up to the next change one and [`DESCRIPTOR_KEYS_LOOKAHEAD`] ones after it.
+    fn derive_lookahead(&mut self, secp_ctx: &Secp256k1<All>) -> eyre::Result<()> {
+        let last_index = self
+            .next_index
+            .saturating_add(DESCRIPTOR_KEYS_LOOKAHEAD)
+            .saturating_sub(1);
+        self.derive(secp_ctx, last_index)?;
+
+        Ok(())
+    }
+
+    /// Derive the key with the `index` and the ones before it which aren't derived yet.
+    fn derive(&mut self, secp_ctx: &Secp256k1<All>, index: u32) -> eyre::Result<PrivateKey> {
+        while self.keys.len() <= index as usize {
+            let child = ChildNumber::from_normal_idx(self.keys.len() as u32)?;
+            let child_key = self
+                .master
+                .ckd_priv(secp_ctx, child)
+                .wrap_err("Failed to derive change key")?;
+
+            let mut key = child_key.to_priv();
+            key.network = self.master.network;
+            self.keys.push(key);
+        }
+
+        Ok(self.keys[index as usize])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bitcoin::{secp256k1::SecretKey, Network};
+
+    use super::*;
+
+    #[test]
+    fn test_keys_up_to_next_index_are_derived() {
+        let ctx = Secp256k1::new();
+        let primary_key =
+            PrivateKey::new(SecretKey::from_slice(&[1; 32]).unwrap(), Network::Regtest);
+
+        let next_index = DESCRIPTOR_KEYS_LOOKAHEAD + 5;
+
+        let keychain = ChangeKeychain::new(&ctx, &primary_key, 0).unwrap();
+        let given_keys = (0..next_index)
+            .map(|_| keychain.next_key(&ctx).unwrap())
+            .collect::<Vec<_>>();
+
+        let restored = ChangeKeychain::new(&ctx, &primary_key, next_index).unwrap();
+        let keys = restored.keys().unwrap();
+
+        assert!(
+            given_keys.iter().all(|key| keys.contains(key)),
+            "keys given before the next index must be tracked"
+        );
+        assert_eq!(keys.len() as u32, next_index + DESCRIPTOR_KEYS_LOOKAHEAD);
+        assert!(!given_keys.contains(&restored.next_key(&ctx).unwrap()));
+    }
+}
@@ -0,0 +1,192 @@
+//! Local labels of the wallet's transactions and outputs, which can be exported
+//! and imported in the [BIP-329] format.
+//!
+//! [BIP-329]: https://github.com/bitcoin/bips/blob/master/bip-0329.mediawiki
+
+use std::collections::BTreeMap;
+
+use bitcoin::{OutPoint, Txid};
+use eyre::Context;
+use serde::{Deserialize, Serialize};
+
+/// Transaction or output the label is attached to.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LabelRef {
+    Tx(Txid),
+    Output(OutPoint),
+}
+
+/// Labels attached by the user to the transactions and outputs. They are local
+/// metadata of the wallet, which isn't shared with anyone.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq)]
+pub struct WalletLabels {
+    pub txs: BTreeMap<Txid, String>,
+    pub outputs: BTreeMap<OutPoint, String>,
+}
+
+/// Record of the [BIP-329] export. The records of the types other than
+/// transactions and outputs (e.g. addresses) are skipped on import.
+///
+/// [BIP-329]: https://github.com/bitcoin/bips/blob/master/bip-0329.mediawiki
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(tag = "type", rename_all = "lowercase")]
+enum Bip329Record {
+    Tx {
+        #[serde(rename = "ref")]
+        txid: Txid,
+        label: String,
+    },
+    Output {
+        #[serde(rename = "ref")]
+        outpoint: OutPoint,
+        label: String,
+    },
+    #[serde(other)]
+    Unsupported,
+}
+
+impl WalletLabels {
+    pub fn get(&self, reference: LabelRef) -> Option<&String> {
+        match reference {
+            LabelRef::Tx(txid) => self.txs.get(&txid),
+            LabelRef::Output(outpoint) => self.outputs.get(&outpoint),
+        }
+    }
+
+    /// Attach the label, replacing the previous one. The empty label removes it.
+    pub fn set(&mut self, reference: LabelRef, label: String) {
+        match reference {
+            LabelRef::Tx(txid) => set_or_remove(&mut self.txs, txid, label),
+            LabelRef::Output(outpoint) => set_or_remove(&mut self.outputs, outpoint, label),
+        }
+    }
+
+    /// Export the labels as [BIP-329] JSON Lines.
+    ///
+    /// [BIP-329]: https://github.com/bitcoin/bips/blob/master/bip-0329.mediawiki
+    pub fn to_bip329(&self) -> eyre::Result<String> {
+        let txs = self.txs.iter().map(|(txid, label)| Bip329Record::Tx {
+            txid: *txid,
+            label: label.clone(),
+        });
+        let outputs = self
+            .outputs
+            .iter()
+            .map(|(outpoint, label)| Bip329Record::Output {
+                outpoint: *outpoint,
+                label: label.clone(),
+            });
+
+        let mut export = String::new();
+        for record in txs.chain(outputs) {
+            export.push_str(&serde_json::to_string(&record)?);
+            export.push('\n');
+        }
+
+        Ok(export)
+    }
+
+    /// Import the labels from [BIP-329] JSON Lines, replacing the existing ones
+    /// of the same transactions and outputs. Returns the number of the imported
+    /// labels.
+    ///
+    /// [BIP-329]: https://github.com/bitcoin/bips/blob/master/bip-0329.mediawiki
+    pub fn extend_from_bip329(&mut self, import: &str) -> eyre::Result<usize> {
+        let mut imported = 0;
+
+        for (index, line) in import.lines().enumerate() {
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            let record: Bip329Record = serde_json::from_str(line)
+                .wrap_err_with(|| format!("invalid BIP-329 record at line {}", index + 1))?;
+
+            match record {
+                Bip329Record::Tx { txid, label } => self.set(LabelRef::Tx(txid), label),
+                Bip329Record::Output { outpoint, label } => {
+                    self.set(LabelRef::Output(outpoint), label)
+                }
+                Bip329Record::Unsupported => continue,
+            }
+
+            imported += 1;
+        }
+
+        Ok(imported)
+    }
+}
+
+fn set_or_remove<K: Ord>(labels: &mut BTreeMap<K, String>, key: K, label: String) {
+    if label.is_empty() {
+        labels.remove(&key);
+    } else {
+        labels.insert(key, label);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use super::*;
+
+    const TXID: &str = "f91d0a8a78462bc59398f2c5d7a84fcff491c26ba54c4833478b202796c8aafd";
+
+    #[test]
+    fn test_bip329_roundtrip() {
+        let txid = Txid::from_str(TXID).unwrap();
+
+        let mut labels = WalletLabels::default();
+        labels.set(LabelRef::Tx(txid), "Salary".to_string());
+        labels.set(
+            LabelRef::Output(OutPoint::new(txid, 1)),
+            "Change".to_string(),
+        );
+
+        let export = labels.to_bip329().unwrap();
+        assert_eq!(
+            export,
+            format!(
+                "{{\"type\":\"tx\",\"ref\":\"{TXID}\",\"label\":\"Salary\"}}\n\
+                 {{\"type\":\"output\",\"ref\":\"{TXID}:1\",\"label\":\"Change\"}}\n"
+            )
+        );
+
+        let mut imported = WalletLabels::default();
+        assert_eq!(imported.extend_from_bip329(&export).unwrap(), 2);
+        assert_eq!(imported, labels);
+    }
+
+    #[test]
+    fn test_bip329_import_skips_unsupported_records() {
+        let import = format!(
+            "{{\"type\":\"addr\",\"ref\":\"bc1q34aq5drpuwy3wgl9lhup9892qp6svr8ldzyy7c\",\"label\":\"Address\"}}\n\
+             \n\
+             {{\"type\":\"output\",\"ref\":\"{TXID}:0\",\"label\":\"Coins\",\"spendable\":false}}\n"
+        );
+
+        let mut labels = WalletLabels::default();
+        assert_eq!(labels.extend_from_bip329(&import).unwrap(), 1);
+        assert_eq!(
+            labels.get(LabelRef::Output(OutPoint::new(
+                Txid::from_str(TXID).unwrap(),
+                0
+            ))),
+            Some(&"Coins".to_string())
+        );
+
+        assert!(labels.extend_from_bip329("not a json").is_err());
+    }
+
+    #[test]
+    fn test_empty_label_removes_it() {
+        let reference = LabelRef::Tx(Txid::from_str(TXID).unwrap());
+
+        let mut labels = WalletLabels::default();
+        labels.set(reference, "Rent".to_string());
+        labels.set(reference, String::new());
+
+        assert_eq!(labels.get(reference), None);
+    }
+}
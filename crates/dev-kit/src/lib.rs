@@ -1,17 +1,16 @@
 #![doc = include_str!("../README.md")]
-pub mod types;
-
-pub mod yuv_coin_selection;
-
-pub mod sync;
-pub mod wallet;
-pub use wallet::Wallet;
-
+pub mod bitcoin_provider;
 pub mod database;
-
+pub mod history;
+pub mod keychain;
+pub mod labels;
+pub mod sync;
 pub mod txbuilder;
+pub mod txsigner;
+pub mod types;
+pub mod wallet;
+pub mod yuv_coin_selection;
 
-pub mod bitcoin_provider;
 pub use bitcoin_provider::AnyBitcoinProvider;
-
-pub mod txsigner;
+pub use keychain::ChangeKeychain;
+pub use wallet::Wallet;
@@ -1,14 +1,15 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
 use bdk::miniscript::ToPublicKey;
-use bitcoin::{OutPoint, PublicKey};
+use bitcoin::{OutPoint, PublicKey, XOnlyPublicKey};
 use eyre::Context;
-use yuv_pixels::PixelProof;
+use yuv_pixels::{Chroma, PixelProof};
 use yuv_rpc_api::transactions::YuvTransactionsRpcClient;
 use yuv_storage::{PagesNumberStorage, TransactionsStorage};
-use yuv_types::YuvTransaction;
+use yuv_types::{YuvTransaction, YuvTxType};
 
-use super::storage::UnspentYuvOutPointsStorage;
+use super::storage::{ChromaHistoryStorage, UnspentYuvOutPointsStorage};
+use crate::types::ChromaActivity;
 
 /// Indexer of YUV transactions got from YUV node.
 pub struct YuvTransactionsIndexer<YuvRpcClient, TransactionStorage> {
@@ -25,8 +26,11 @@ pub struct YuvTransactionsIndexer<YuvRpcClient, TransactionStorage> {
     /// Out points of current user
     user_outpoints: HashMap<OutPoint, PixelProof>,
 
-    /// Public key of the user we are searching UTXOs
-    pubkey: PublicKey,
+    /// Activity of the user in each token it has ever held
+    chroma_history: HashMap<Chroma, ChromaActivity>,
+
+    /// Public keys of the user we are searching UTXOs
+    pubkeys: Vec<PublicKey>,
 
     /// Last indexed page number.
     last_page_number: u64,
@@ -38,18 +42,20 @@ where
     TS: TransactionsStorage
         + PagesNumberStorage
         + UnspentYuvOutPointsStorage
+        + ChromaHistoryStorage
         + Send
         + Sync
         + 'static,
 {
-    pub fn new(client: C, txs_storage: TS, pubkey: PublicKey) -> Self {
+    pub fn new(client: C, txs_storage: TS, pubkeys: Vec<PublicKey>) -> Self {
         Self {
             node_client: client,
             txs_storage,
             indexed_txs: HashMap::new(),
             last_page_number: 0,
             user_outpoints: HashMap::default(),
-            pubkey,
+            chroma_history: HashMap::default(),
+            pubkeys,
         }
     }
 
@@ -62,11 +68,12 @@ where
             .saturating_sub(1);
 
         self.user_outpoints = self.txs_storage.get_unspent_yuv_outpoints().await?;
+        self.chroma_history = self.txs_storage.get_chroma_history().await?;
 
         loop {
             let txs = self
                 .node_client
-                .list_yuv_transactions(self.last_page_number)
+                .list_yuv_transactions(self.last_page_number, None, None)
                 .await
                 .wrap_err("Failed to fetch transactions from node")?;
 
@@ -98,12 +105,22 @@ where
             .put_unspent_yuv_outpoints(self.user_outpoints.clone())
             .await?;
 
+        self.txs_storage
+            .put_chroma_history(self.chroma_history.clone())
+            .await?;
+
         Ok(utxos)
     }
 
-    /// Go through all outputs of current transactions and add them
-    /// as indexed, then go through all inputs and mark outputs as spend.
+    /// Index the outpoints of the transaction, and record the user's activity in its tokens.
     fn index_transaction(&mut self, tx: &YuvTransaction) {
+        self.index_outpoints(tx);
+        self.record_chroma_activity(tx);
+    }
+
+    /// Add the outputs of the transaction as indexed and the ones with the user's proofs to the
+    /// user's outpoints, then mark the spent outputs.
+    fn index_outpoints(&mut self, tx: &YuvTransaction) {
         let txid = tx.bitcoin_tx.txid();
         let outpoints = tx
             .bitcoin_tx
@@ -118,69 +135,18 @@ where
             return;
         };
 
-        let (self_x_only_pubkey, _parity) = self.pubkey.inner.x_only_public_key();
-
         for outpoint in outpoints {
             let Some(output_proof) = output_proofs.get(&outpoint.vout) else {
                 continue;
             };
 
-            match output_proof {
-                PixelProof::Sig(proof) => {
-                    let (proof_x_key, _parity) = proof.inner_key.x_only_public_key();
-
-                    if proof_x_key == self_x_only_pubkey {
-                        self.user_outpoints.insert(outpoint, output_proof.clone());
-                    }
-                }
-                PixelProof::Multisig(proof) => {
-                    let x_only_pubkeys = proof
-                        .inner_keys
-                        .iter()
-                        .map(|key| key.x_only_public_key().0)
-                        .collect::<Vec<_>>();
-
-                    if x_only_pubkeys.contains(&self_x_only_pubkey) {
-                        self.user_outpoints.insert(outpoint, output_proof.clone());
-                    }
-                }
-                PixelProof::Lightning(proof) => {
-                    let x_only = proof.local_delayed_pubkey.x_only_public_key().0;
-
-                    if x_only == self.pubkey.inner.x_only_public_key().0 {
-                        tracing::debug!("Adding lightning output proof: {:?}", output_proof);
-
-                        self.user_outpoints.insert(outpoint, output_proof.clone());
-                    }
-                }
-                #[cfg(feature = "bulletproof")]
-                PixelProof::Bulletproof(proof) => {
-                    let (proof_x_key, _parity) = proof.inner_key.x_only_public_key();
-
-                    if proof_x_key == self_x_only_pubkey {
-                        self.user_outpoints.insert(outpoint, output_proof.clone());
-                    }
-                }
-                PixelProof::LightningHtlc(htlc_proof) => {
-                    // NOTE: Lightning HTLC is spend only by LDK node.
-                    let used_keys = [
-                        htlc_proof.data.remote_htlc_key.to_x_only_pubkey(),
-                        htlc_proof.data.local_htlc_key.to_x_only_pubkey(),
-                    ];
-
-                    if used_keys.contains(&self_x_only_pubkey) {
-                        tracing::debug!("Adding lightning htlc output proof: {:?}", output_proof);
-
-                        self.user_outpoints.insert(outpoint, output_proof.clone());
-                    }
-                }
-                PixelProof::EmptyPixel(proof) => {
-                    let (proof_x_only_pubkey, _parity) = proof.inner_key.x_only_public_key();
-
-                    if proof_x_only_pubkey == self_x_only_pubkey {
-                        self.user_outpoints.insert(outpoint, output_proof.clone());
-                    }
-                }
+            if proof_keys(output_proof)
+                .iter()
+                .any(|key| self.is_user_key(key))
+            {
+                tracing::debug!("Adding output proof: {:?}", output_proof);
+
+                self.user_outpoints.insert(outpoint, output_proof.clone());
             }
 
             self.indexed_txs.entry(outpoint).or_insert(false);
@@ -191,6 +157,47 @@ where
         }
     }
 
+    /// Record the transaction in the history of the tokens the user has issued, sent or
+    /// received in it. Must be called after the outputs are indexed, and before the spent
+    /// outputs are removed from the user's ones.
+    fn record_chroma_activity(&mut self, tx: &YuvTransaction) {
+        let txid = tx.bitcoin_tx.txid();
+
+        let spent = tx
+            .bitcoin_tx
+            .input
+            .iter()
+            .map(|input| input.previous_output);
+        let received = (0..tx.bitcoin_tx.output.len()).map(|vout| OutPoint::new(txid, vout as u32));
+
+        let mut chromas = spent
+            .chain(received)
+            .filter_map(|outpoint| self.user_outpoints.get(&outpoint))
+            .filter(|proof| !proof.is_empty_pixelproof())
+            .map(|proof| proof.pixel().chroma)
+            .collect::<HashSet<_>>();
+
+        if let YuvTxType::Issue { announcement, .. } = &tx.tx_type {
+            if self.is_user_key(announcement.chroma.xonly()) {
+                chromas.insert(announcement.chroma);
+            }
+        }
+
+        for chroma in chromas {
+            self.chroma_history
+                .entry(chroma)
+                .and_modify(|activity| activity.record(txid))
+                .or_insert_with(|| ChromaActivity::new(txid));
+        }
+    }
+
+    /// Check if the key is one of the user's ones.
+    fn is_user_key(&self, key: &XOnlyPublicKey) -> bool {
+        self.pubkeys
+            .iter()
+            .any(|pubkey| pubkey.inner.x_only_public_key().0 == *key)
+    }
+
     /// Clean up transaction that are spent, and not owned by user
     async fn cleanup(&mut self) -> eyre::Result<Vec<(OutPoint, PixelProof)>> {
         let mut utxos = Vec::new();
@@ -222,3 +229,110 @@ where
         Ok(utxos)
     }
 }
+
+/// Return the keys of the proof's owners. Empty for [`PixelProof::GenericScript`],
+/// as the owner of the custom script can't be told by the proof.
+pub(crate) fn proof_keys(proof: &PixelProof) -> Vec<XOnlyPublicKey> {
+    match proof {
+        PixelProof::Sig(proof) => vec![proof.inner_key.x_only_public_key().0],
+        PixelProof::Multisig(proof) => proof
+            .inner_keys
+            .iter()
+            .map(|key| key.x_only_public_key().0)
+            .collect(),
+        PixelProof::Lightning(proof) => vec![proof.local_delayed_pubkey.x_only_public_key().0],
+        #[cfg(feature = "bulletproof")]
+        PixelProof::Bulletproof(proof) => vec![proof.inner_key.x_only_public_key().0],
+        // NOTE: Lightning HTLC is spend only by LDK node.
+        PixelProof::LightningHtlc(htlc_proof) => vec![
+            htlc_proof.data.remote_htlc_key.to_x_only_pubkey(),
+            htlc_proof.data.local_htlc_key.to_x_only_pubkey(),
+        ],
+        PixelProof::Timelock(proof) => vec![proof.inner_key.x_only_public_key().0],
+        PixelProof::EmptyPixel(proof) => vec![proof.inner_key.x_only_public_key().0],
+        PixelProof::GenericScript(_) => Vec::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bitcoin::secp256k1::{self, Secp256k1, SecretKey};
+    use jsonrpsee::http_client::{HttpClient, HttpClientBuilder};
+    use yuv_pixels::Pixel;
+    use yuv_storage::LevelDB;
+    use yuv_types::mocks::{issuance, transfer};
+
+    use super::*;
+
+    fn secret(byte: u8) -> SecretKey {
+        SecretKey::from_slice(&[byte; 32]).unwrap()
+    }
+
+    fn pubkey(byte: u8) -> secp256k1::PublicKey {
+        secret(byte).public_key(&Secp256k1::new())
+    }
+
+    fn indexer(user: secp256k1::PublicKey) -> YuvTransactionsIndexer<HttpClient, LevelDB> {
+        YuvTransactionsIndexer::new(
+            HttpClientBuilder::new()
+                .build("http://127.0.0.1:18333")
+                .unwrap(),
+            LevelDB::in_memory().unwrap(),
+            vec![PublicKey::new(user)],
+        )
+    }
+
+    #[tokio::test]
+    async fn test_chroma_activity_is_recorded_once() {
+        let user = pubkey(1);
+        let other = pubkey(2);
+        let chroma = Chroma::from(user.x_only_public_key().0);
+        let pixel = Pixel::new(100, chroma);
+
+        let issued = issuance(&secret(1), 100, user);
+        let sent = transfer(
+            OutPoint::new(issued.bitcoin_tx.txid(), 0),
+            &secret(1),
+            pixel,
+            other,
+            2,
+        );
+        let unrelated = transfer(OutPoint::null(), &secret(2), pixel, other, 2);
+
+        let mut indexer = indexer(user);
+        // The last page is fetched again by the next sync.
+        for tx in [&issued, &sent, &unrelated, &issued, &sent] {
+            indexer.index_transaction(tx);
+        }
+
+        let activity = &indexer.chroma_history[&chroma];
+        assert_eq!(activity.first_txid, issued.bitcoin_tx.txid());
+        assert_eq!(activity.last_txid, sent.bitcoin_tx.txid());
+        assert_eq!(activity.txs_number(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_others_issuance_is_recorded_only_if_received() {
+        let user = pubkey(1);
+        let issuer = pubkey(2);
+        let chroma = Chroma::from(issuer.x_only_public_key().0);
+
+        let mut indexer = indexer(user);
+        let issued = issuance(&secret(2), 100, issuer);
+        indexer.index_transaction(&issued);
+        assert!(indexer.chroma_history.is_empty());
+
+        let received = transfer(
+            OutPoint::new(issued.bitcoin_tx.txid(), 0),
+            &secret(2),
+            Pixel::new(100, chroma),
+            user,
+            2,
+        );
+        indexer.index_transaction(&received);
+        assert_eq!(
+            indexer.chroma_history.get(&chroma),
+            Some(&ChromaActivity::new(received.bitcoin_tx.txid()))
+        );
+    }
+}
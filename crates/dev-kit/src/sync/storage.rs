@@ -2,12 +2,24 @@ use std::collections::HashMap;
 
 use bitcoin::OutPoint;
 use jsonrpsee::core::async_trait;
-use yuv_pixels::PixelProof;
+use yuv_pixels::{Chroma, PixelProof};
 use yuv_storage::KeyValueStorage;
 
+use crate::labels::WalletLabels;
+use crate::types::ChromaActivity;
+
 const UNSPENT_YUV_OUTPOINTS_KEY: &[u8; 15] = b"unspent_yuv_txs";
 const UNSPENT_YUV_OUTPOINTS_KEY_LEN: usize = UNSPENT_YUV_OUTPOINTS_KEY.len();
 
+const CHROMA_HISTORY_KEY: &[u8; 14] = b"chroma_history";
+const CHROMA_HISTORY_KEY_LEN: usize = CHROMA_HISTORY_KEY.len();
+
+const WALLET_LABELS_KEY: &[u8; 13] = b"wallet_labels";
+const WALLET_LABELS_KEY_LEN: usize = WALLET_LABELS_KEY.len();
+
+const CHANGE_KEYCHAIN_INDEX_KEY: &[u8; 21] = b"change_keychain_index";
+const CHANGE_KEYCHAIN_INDEX_KEY_LEN: usize = CHANGE_KEYCHAIN_INDEX_KEY.len();
+
 #[async_trait]
 pub trait UnspentYuvOutPointsStorage:
     KeyValueStorage<&'static [u8; UNSPENT_YUV_OUTPOINTS_KEY_LEN], HashMap<OutPoint, PixelProof>>
@@ -36,3 +48,115 @@ impl<T> UnspentYuvOutPointsStorage for T where
     T: KeyValueStorage<&'static [u8; UNSPENT_YUV_OUTPOINTS_KEY_LEN], HashMap<OutPoint, PixelProof>>
 {
 }
+
+/// Storage of the wallet's activity in each token it has ever held.
+#[async_trait]
+pub trait ChromaHistoryStorage:
+    KeyValueStorage<&'static [u8; CHROMA_HISTORY_KEY_LEN], HashMap<Chroma, ChromaActivity>>
+{
+    async fn get_chroma_history(&self) -> eyre::Result<HashMap<Chroma, ChromaActivity>> {
+        let entry = self.get(CHROMA_HISTORY_KEY).await?.unwrap_or_default();
+
+        Ok(entry)
+    }
+
+    async fn put_chroma_history(
+        &self,
+        chroma_history: HashMap<Chroma, ChromaActivity>,
+    ) -> eyre::Result<()> {
+        self.put(CHROMA_HISTORY_KEY, chroma_history).await?;
+
+        Ok(())
+    }
+}
+
+impl<T> ChromaHistoryStorage for T where
+    T: KeyValueStorage<&'static [u8; CHROMA_HISTORY_KEY_LEN], HashMap<Chroma, ChromaActivity>>
+{
+}
+
+/// Storage of the labels the user attached to the wallet's transactions and outputs.
+#[async_trait]
+pub trait WalletLabelsStorage:
+    KeyValueStorage<&'static [u8; WALLET_LABELS_KEY_LEN], WalletLabels>
+{
+    async fn get_wallet_labels(&self) -> eyre::Result<WalletLabels> {
+        let entry = self.get(WALLET_LABELS_KEY).await?.unwrap_or_default();
+
+        Ok(entry)
+    }
+
+    async fn put_wallet_labels(&self, labels: WalletLabels) -> eyre::Result<()> {
+        self.put(WALLET_LABELS_KEY, labels).await?;
+
+        Ok(())
+    }
+}
+
+impl<T> WalletLabelsStorage for T where
+    T: KeyValueStorage<&'static [u8; WALLET_LABELS_KEY_LEN], WalletLabels>
+{
+}
+
+/// Storage of the index of the [`ChangeKeychain`] key which is given for the next change.
+///
+/// [`ChangeKeychain`]: crate::keychain::ChangeKeychain
+#[async_trait]
+pub trait ChangeKeychainStorage:
+    KeyValueStorage<&'static [u8; CHANGE_KEYCHAIN_INDEX_KEY_LEN], u32>
+{
+    async fn get_change_keychain_index(&self) -> eyre::Result<u32> {
+        let entry = self
+            .get(CHANGE_KEYCHAIN_INDEX_KEY)
+            .await?
+            .unwrap_or_default();
+
+        Ok(entry)
+    }
+
+    async fn put_change_keychain_index(&self, index: u32) -> eyre::Result<()> {
+        self.put(CHANGE_KEYCHAIN_INDEX_KEY, index).await?;
+
+        Ok(())
+    }
+}
+
+impl<T> ChangeKeychainStorage for T where
+    T: KeyValueStorage<&'static [u8; CHANGE_KEYCHAIN_INDEX_KEY_LEN], u32>
+{
+}
+
+#[cfg(test)]
+mod tests {
+    use bitcoin::{
+        hashes::Hash,
+        secp256k1::{Secp256k1, SecretKey},
+        Txid,
+    };
+    use yuv_storage::LevelDB;
+
+    use super::*;
+
+    fn chroma() -> Chroma {
+        let pubkey = SecretKey::from_slice(&[1; 32])
+            .unwrap()
+            .public_key(&Secp256k1::new());
+
+        Chroma::from(pubkey.x_only_public_key().0)
+    }
+
+    #[tokio::test]
+    async fn test_chroma_history_is_stored() -> eyre::Result<()> {
+        let storage = LevelDB::in_memory()?;
+        assert!(storage.get_chroma_history().await?.is_empty());
+
+        let mut activity = ChromaActivity::new(Txid::from_inner([1; 32]));
+        activity.record(Txid::from_inner([2; 32]));
+        let history = HashMap::from([(chroma(), activity)]);
+
+        storage.put_chroma_history(history.clone()).await?;
+        assert_eq!(storage.get_chroma_history().await?, history);
+
+        Ok(())
+    }
+}
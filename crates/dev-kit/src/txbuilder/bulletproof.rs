@@ -19,6 +19,7 @@ use super::{
     BuilderInput, BuilderOutput, IssuanceTransactionBuilder, TransactionBuilder,
     TransferTransactionBuilder,
 };
+use crate::sync::storage::ChangeKeychainStorage;
 
 #[derive(Clone, Copy)]
 pub struct BulletproofRecipientParameters {
@@ -29,7 +30,7 @@ pub struct BulletproofRecipientParameters {
 
 impl<YTDB, BDB> IssuanceTransactionBuilder<YTDB, BDB>
 where
-    YTDB: YuvTransactionsStorage + Clone + Send + Sync + 'static,
+    YTDB: YuvTransactionsStorage + ChangeKeychainStorage + Clone + Send + Sync + 'static,
     BDB: bdk::database::BatchDatabase + Clone + Send,
 {
     /// Add recipient to the transaction with bulletproof.
@@ -46,7 +47,7 @@ where
 
 impl<YTDB, BDB> TransferTransactionBuilder<YTDB, BDB>
 where
-    YTDB: YuvTransactionsStorage + Clone + Send + Sync + 'static,
+    YTDB: YuvTransactionsStorage + ChangeKeychainStorage + Clone + Send + Sync + 'static,
     BDB: bdk::database::BatchDatabase + Clone + Send,
 {
     /// Add recipient to the transaction with bulletproof.
@@ -65,7 +66,7 @@ where
 
 impl<YTDB, BDB> TransactionBuilder<YTDB, BDB>
 where
-    YTDB: YuvTransactionsStorage + Clone + Send + Sync + 'static,
+    YTDB: YuvTransactionsStorage + ChangeKeychainStorage + Clone + Send + Sync + 'static,
     BDB: bdk::database::BatchDatabase + Clone + Send,
 {
     /// Add recipient to the transaction with bulletproof.
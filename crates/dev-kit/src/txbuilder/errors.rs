@@ -0,0 +1,50 @@
+//! This module provides [`TxBuilderError`] returned by the transaction builders.
+
+use bitcoin::OutPoint;
+use yuv_pixels::Chroma;
+
+/// Result of the transaction builders' operations.
+pub type TxBuilderResult<T> = Result<T, TxBuilderError>;
+
+/// Error returned by the transaction builders, so the callers could tell the
+/// reason of the failure, e.g. to show an actionable message to the user.
+#[derive(Debug, thiserror::Error)]
+pub enum TxBuilderError {
+    /// YUV UTXOs of the wallet don't cover the amount of the token to send.
+    #[error("Insufficient balance of {chroma}: {available} available of {needed} needed")]
+    InsufficientBalance {
+        chroma: Chroma,
+        needed: u128,
+        available: u128,
+    },
+    /// Satoshis of the wallet, or of the fee source if it's set, don't cover
+    /// the outputs and the fee.
+    #[error("Insufficient satoshis: {available} sat available of {needed} sat needed")]
+    InsufficientSatoshis { needed: u64, available: u64 },
+    /// Proof of the spent output isn't found in the synced YUV transactions.
+    #[error("Proof of the output {0} is not found in synced YUV txs")]
+    MissingProof(OutPoint),
+    /// Absolute fee is lower than the transaction pays at the min fee rate,
+    /// so it wouldn't be relayed by the nodes.
+    #[error("Absolute fee {fee} sat is below the min relay fee {min_fee} sat")]
+    FeeBelowMinRelay { fee: u64, min_fee: u64 },
+    /// Fee rate strategy has failed to give the fee rate.
+    #[error("Failed to estimate fee: {0:#}")]
+    FeeEstimation(eyre::Report),
+    /// Inputs of the transaction couldn't be signed.
+    #[error("Failed to sign transaction: {0:#}")]
+    Signing(eyre::Report),
+    /// Any other failure of the transaction building.
+    #[error("{0:#}")]
+    Other(eyre::Report),
+}
+
+impl From<eyre::Report> for TxBuilderError {
+    /// Recover the [`TxBuilderError`] raised inside the builder and propagated
+    /// as [`eyre::Report`], or wrap the report into [`TxBuilderError::Other`].
+    fn from(report: eyre::Report) -> Self {
+        report
+            .downcast::<TxBuilderError>()
+            .unwrap_or_else(Self::Other)
+    }
+}
@@ -5,11 +5,15 @@ use std::{
 };
 
 use bitcoin::{
+    blockdata::{
+        opcodes::{Class, ClassifyContext},
+        script::{read_scriptint, Instruction},
+    },
     psbt::{self, serialize::Serialize},
     secp256k1::{self, All, Secp256k1},
-    OutPoint, PrivateKey, PublicKey, Script, Transaction, TxOut, XOnlyPublicKey,
+    LockTime, OutPoint, PrivateKey, PublicKey, Script, Transaction, TxOut, Txid, XOnlyPublicKey,
 };
-use eyre::{bail, eyre, Context, OptionExt};
+use eyre::{bail, ensure, eyre, Context, OptionExt};
 #[cfg(feature = "bulletproof")]
 use {
     bitcoin::secp256k1::schnorr::Signature,
@@ -18,7 +22,8 @@ use {
 };
 
 use bdk::{
-    blockchain::Blockchain,
+    blockchain::{Blockchain, GetTx},
+    database::Database,
     descriptor,
     miniscript::{psbt::PsbtInputExt, Descriptor, DescriptorPublicKey, ToPublicKey},
     wallet::tx_builder::TxOrdering,
@@ -26,8 +31,8 @@ use bdk::{
 };
 
 use yuv_pixels::{
-    Chroma, EmptyPixelProof, MultisigPixelProof, Pixel, PixelKey, PixelProof, SigPixelProof,
-    ToEvenPublicKey,
+    derive_pixel_script_pubkey, Chroma, EmptyPixelProof, MultisigPixelProof, Pixel, PixelKey,
+    PixelProof, SigPixelProof, TimelockPixelProof, ToEvenPublicKey,
 };
 
 use yuv_storage::TransactionsStorage as YuvTransactionsStorage;
@@ -36,6 +41,8 @@ use yuv_types::{ProofMap, YuvTransaction, YuvTxType};
 
 use crate::{
     bitcoin_provider::BitcoinProvider,
+    keychain::ChangeKeychain,
+    sync::storage::ChangeKeychainStorage,
     txsigner::TransactionSigner,
     types::{FeeRateStrategy, Utxo, WeightedUtxo, YuvTxOut, YuvUtxo},
     yuv_coin_selection::{YUVCoinSelectionAlgorithm, YuvLargestFirstCoinSelection},
@@ -47,6 +54,26 @@ mod bulletproof;
 #[cfg(feature = "bulletproof")]
 pub use bulletproof::BulletproofRecipientParameters;
 
+mod errors;
+pub use errors::{TxBuilderError, TxBuilderResult};
+
+mod sponsor;
+pub use sponsor::{
+    SponsorTransactionBuilder, SponsoredInput, SponsoredTransfer, SponsorshipRequest,
+};
+
+/// Default minimal amount of satoshis in the change output for YUV coins, which
+/// is the dust limit of P2WPKH output.
+pub const DEFAULT_DUST_THRESHOLD: u64 = 294;
+
+/// Default minimal fee rate in sat/vb, which is the min relay fee rate of
+/// Bitcoin Core, so the transactions are propagated by the nodes.
+pub const DEFAULT_MIN_FEE_RATE: f32 = 1.0;
+
+/// Number of confirmations after which the coinbase output can be spent by the
+/// transaction in the next block.
+const COINBASE_MATURITY: u32 = 100;
+
 #[allow(clippy::large_enum_variant)]
 #[derive(Debug)]
 enum BuilderInput {
@@ -57,6 +84,14 @@ enum BuilderInput {
     Pixel {
         outpoint: OutPoint,
     },
+    /// Pixel output wrapped into P2SH (P2SH-P2WPKH).
+    PixelShWpkh {
+        outpoint: OutPoint,
+    },
+    /// Pixel output locked until the locktime (P2WSH with `OP_CHECKLOCKTIMEVERIFY`).
+    TimelockedPixel {
+        outpoint: OutPoint,
+    },
     TweakedSatoshis {
         outpoint: OutPoint,
     },
@@ -71,6 +106,8 @@ impl BuilderInput {
         match self {
             BuilderInput::Multisig2x2 { outpoint, .. }
             | BuilderInput::Pixel { outpoint }
+            | BuilderInput::PixelShWpkh { outpoint }
+            | BuilderInput::TimelockedPixel { outpoint }
             | BuilderInput::TweakedSatoshis { outpoint } => *outpoint,
             #[cfg(feature = "bulletproof")]
             BuilderInput::BulletproofPixel { outpoint, .. } => *outpoint,
@@ -98,6 +135,14 @@ enum BuilderOutput {
         participants: Vec<secp256k1::PublicKey>,
         required_signatures: u8,
     },
+    /// Pixel output that can't be spent before the `locktime`.
+    TimelockedPixel {
+        chroma: Chroma,
+        satoshis: u64,
+        amount: u128,
+        recipient: secp256k1::PublicKey,
+        locktime: u32,
+    },
     #[cfg(feature = "bulletproof")]
     BulletproofPixel {
         chroma: Chroma,
@@ -116,9 +161,9 @@ impl BuilderOutput {
     fn amount(&self) -> u128 {
         match self {
             BuilderOutput::Satoshis { .. } => 0,
-            BuilderOutput::Pixel { amount, .. } | BuilderOutput::MultisigPixel { amount, .. } => {
-                *amount
-            }
+            BuilderOutput::Pixel { amount, .. }
+            | BuilderOutput::MultisigPixel { amount, .. }
+            | BuilderOutput::TimelockedPixel { amount, .. } => *amount,
             #[cfg(feature = "bulletproof")]
             BuilderOutput::BulletproofPixel { .. } => 0,
         }
@@ -129,6 +174,7 @@ impl BuilderOutput {
             BuilderOutput::Satoshis { .. } => None,
             BuilderOutput::Pixel { chroma, .. } => Some(*chroma),
             BuilderOutput::MultisigPixel { chroma, .. } => Some(*chroma),
+            BuilderOutput::TimelockedPixel { chroma, .. } => Some(*chroma),
             #[cfg(feature = "bulletproof")]
             BuilderOutput::BulletproofPixel { chroma, .. } => Some(*chroma),
         }
@@ -148,20 +194,43 @@ struct TransactionBuilder<YuvTxsDatabase, BitcoinTxsDatabase> {
     /// Value of satoshis that will be attached to change output for YUV coins.
     change_satoshis: u64,
 
+    /// Minimal value of satoshis in the change output for YUV coins. If
+    /// [`Self::change_satoshis`] is below it, the residual YUV coins are folded
+    /// into a recipient output of the same chroma instead.
+    dust_threshold: u64,
+
     /// The fee rate strategy. Possible values:
     /// - Estimate: The fee rate is fetched from Bitcoin RPC. If an error
     ///   occurs, the tx building process is interrupted.
     /// - Manual: Default fee rate is used.
     /// - TryEstimate: The fee rate is fetched
     /// automatically from Bitcoin RPC. If an error occurs, the default fee rate is used.
+    /// - AbsoluteFee: The fixed fee is paid, see [`Self::build_tx`].
     /// NOTE: fee_rate is measured in sat/vb.
     fee_rate_strategy: FeeRateStrategy,
 
+    /// Minimal fee rate in sat/vb the fee rate of any strategy is raised to.
+    /// If `None`, the `min` of the strategy's bounds is used.
+    min_fee_rate: Option<f32>,
+
     yuv_txs_storage: YuvTxsDatabase,
 
     /// Inner wallet which will sign result transaction.
     inner_wallet: Arc<RwLock<bdk::Wallet<BitcoinTxsDatabase>>>,
+    /// Primary private key of the wallet, which is used for issuances.
     private_key: PrivateKey,
+    /// All private keys of the wallet, which can be used to spend the inputs.
+    signer_keys: Vec<PrivateKey>,
+
+    /// Keychain of the wallet the change key is taken from. If `None`, the
+    /// change is sent to the primary key.
+    change_keychain: Option<ChangeKeychain>,
+    /// Key the change of the transaction is sent to, taken from the
+    /// [`Self::change_keychain`] on the first change output.
+    change_key: Option<PrivateKey>,
+    /// Recipient of the change set by the user, e.g. a cold wallet. Overrides
+    /// the wallet's change key.
+    change_recipient: Option<secp256k1::PublicKey>,
 
     /// Storage of transactions outputs that could be spent
     yuv_utxos: Arc<RwLock<HashMap<OutPoint, PixelProof>>>,
@@ -190,6 +259,26 @@ struct TransactionBuilder<YuvTxsDatabase, BitcoinTxsDatabase> {
 
     /// Instructs txbuilder to add tweaked satoshis as transaction inputs
     should_drain_tweaked_satoshis: bool,
+
+    /// Parent transactions which fee is bumped by the transaction (CPFP).
+    cpfp_parents: Vec<Txid>,
+
+    /// Bitcoin wallet's outputs which are the only ones allowed to be spent
+    /// to pay the fee. If `None`, any of the wallet's outputs could be used.
+    fee_source: Option<Vec<OutPoint>>,
+
+    /// Bitcoin wallet's outputs which are all spent to pay the fee, and the
+    /// only ones, see [`Self::set_satoshi_inputs`].
+    satoshi_inputs: Option<Vec<OutPoint>>,
+
+    /// Key the change of the satoshis is sent to with the empty pixel proof
+    /// instead of a new address of the Bitcoin wallet, see
+    /// [`Self::set_satoshi_inputs`].
+    satoshi_change_recipient: Option<secp256k1::PublicKey>,
+
+    /// Bitcoin wallet's outputs which must not be spent, as they are already
+    /// spent by the previous transactions of the batch.
+    unspendable: Vec<OutPoint>,
 }
 
 unsafe impl<YuvTxsDatabase, BitcoinTxsDatabase> Sync
@@ -214,7 +303,7 @@ pub struct SweepTransactionBuilder<YuvTxsDatabase, BitcoinTxsDatabase>(
 
 impl<YTDB, BDB, YC, BP> TryFrom<&Wallet<YC, YTDB, BP, BDB>> for SweepTransactionBuilder<YTDB, BDB>
 where
-    YTDB: YuvTransactionsStorage + Clone + Send + Sync + 'static,
+    YTDB: YuvTransactionsStorage + ChangeKeychainStorage + Clone + Send + Sync + 'static,
     BDB: bdk::database::BatchDatabase + Clone + Send,
     BP: BitcoinProvider,
 {
@@ -227,7 +316,7 @@ where
 
 impl<YTDB, BDB> SweepTransactionBuilder<YTDB, BDB>
 where
-    YTDB: YuvTransactionsStorage + Clone + Send + Sync + 'static,
+    YTDB: YuvTransactionsStorage + ChangeKeychainStorage + Clone + Send + Sync + 'static,
     BDB: bdk::database::BatchDatabase + Clone + Send,
 {
     /// Override the fee rate strategy.
@@ -237,10 +326,21 @@ where
         self
     }
 
+    /// Override the minimal fee rate in sat/vb, which applies to all the fee
+    /// rate strategies.
+    pub fn set_min_fee_rate(&mut self, min_fee_rate: f32) -> &mut Self {
+        self.0.set_min_fee_rate(min_fee_rate);
+
+        self
+    }
+
     /// Finish sweep building, and create a Bitcoin transaction.
     /// If the address has no tweaked Bitcoin outputs, `None` is returned.
-    pub async fn finish(self, blockchain: &impl Blockchain) -> eyre::Result<Option<Transaction>> {
-        self.0.build_sweep(blockchain).await
+    pub async fn finish(
+        self,
+        blockchain: &impl Blockchain,
+    ) -> TxBuilderResult<Option<Transaction>> {
+        Ok(self.0.build_sweep(blockchain).await?)
     }
 }
 
@@ -251,7 +351,7 @@ pub struct IssuanceTransactionBuilder<YuvTxsDatabase, BitcoinTxsDatabase>(
 impl<YTDB, BDB, YC, BP> TryFrom<&Wallet<YC, YTDB, BP, BDB>>
     for IssuanceTransactionBuilder<YTDB, BDB>
 where
-    YTDB: YuvTransactionsStorage + Clone + Send + Sync + 'static,
+    YTDB: YuvTransactionsStorage + ChangeKeychainStorage + Clone + Send + Sync + 'static,
     BDB: bdk::database::BatchDatabase + Clone + Send,
     BP: BitcoinProvider,
 {
@@ -264,10 +364,11 @@ where
 
 impl<YTDB, BDB> IssuanceTransactionBuilder<YTDB, BDB>
 where
-    YTDB: YuvTransactionsStorage + Clone + Send + Sync + 'static,
+    YTDB: YuvTransactionsStorage + ChangeKeychainStorage + Clone + Send + Sync + 'static,
     BDB: bdk::database::BatchDatabase + Clone + Send,
 {
-    /// Add recipient to the transaction.
+    /// Add recipient to the transaction, see
+    /// [`TransferTransactionBuilder::add_recipient`].
     pub fn add_recipient(
         &mut self,
         recipient: &secp256k1::PublicKey,
@@ -291,13 +392,36 @@ where
         self
     }
 
+    /// Override the minimal fee rate in sat/vb, which applies to all the fee
+    /// rate strategies.
+    pub fn set_min_fee_rate(&mut self, min_fee_rate: f32) -> &mut Self {
+        self.0.set_min_fee_rate(min_fee_rate);
+
+        self
+    }
+
     // Override spending tweaked satoshis
     pub fn set_drain_tweaked_satoshis(&mut self, should_drain_tweaked_satoshis: bool) -> &mut Self {
         self.0.should_drain_tweaked_satoshis = should_drain_tweaked_satoshis;
         self
     }
 
-    /// Add satoshi recipient.
+    /// Spend exactly the given Bitcoin wallet's outputs to pay the fee, see
+    /// [`TransferTransactionBuilder::set_satoshi_inputs`].
+    ///
+    /// The issuance with the fixed satoshi inputs could not be batched.
+    pub fn set_satoshi_inputs(
+        &mut self,
+        outpoints: Vec<OutPoint>,
+        change_recipient: &secp256k1::PublicKey,
+    ) -> &mut Self {
+        self.0.set_satoshi_inputs(outpoints, *change_recipient);
+
+        self
+    }
+
+    /// Add satoshi recipient. The output has the [`EmptyPixelProof`], see
+    /// [`TransferTransactionBuilder::add_sats_recipient`].
     pub fn add_sats_recipient(
         &mut self,
         recipient: &secp256k1::PublicKey,
@@ -330,10 +454,51 @@ where
         self
     }
 
+    /// Add recipient to the transaction, which can't spend the output before
+    /// the `locktime` (block height or timestamp, as `nLockTime`).
+    ///
+    /// The transaction output will be formed as P2WSH output with
+    /// `OP_CHECKLOCKTIMEVERIFY` followed by the tweaked recipient's key check.
+    pub fn add_timelocked_recipient(
+        &mut self,
+        recipient: &secp256k1::PublicKey,
+        amount: u128,
+        satoshis: u64,
+        locktime: u32,
+    ) -> &mut Self {
+        self.0.add_timelocked_recipient(
+            self.0.issuance_chroma(),
+            recipient,
+            amount,
+            satoshis,
+            locktime,
+        );
+
+        self
+    }
+
     /// Finish issuance building, and create Bitcoin transactions with attached
     /// proofs for it in [`YuvTransaction`].
-    pub async fn finish(self, blockchain: &impl Blockchain) -> eyre::Result<YuvTransaction> {
-        self.0.finish(blockchain).await
+    pub async fn finish(self, blockchain: &impl Blockchain) -> TxBuilderResult<YuvTransaction> {
+        Ok(self.0.finish(blockchain).await?)
+    }
+
+    /// Finish issuance building, splitting the YUV recipients across multiple
+    /// issuance transactions with at most `max_outputs_per_tx` of them each.
+    ///
+    /// Every transaction is a standalone issuance that announces the amount of
+    /// its own outputs, and spends other Bitcoin outputs than the previous ones,
+    /// so all of them could be broadcasted at once. Satoshi recipients and
+    /// selected inputs are added to the first transaction.
+    pub async fn finish_batched(
+        self,
+        blockchain: &impl Blockchain,
+        max_outputs_per_tx: usize,
+    ) -> TxBuilderResult<Vec<YuvTransaction>> {
+        Ok(self
+            .0
+            .finish_batched(blockchain, max_outputs_per_tx)
+            .await?)
     }
 }
 
@@ -344,7 +509,7 @@ pub struct TransferTransactionBuilder<YuvTxsDatabase, BitcoinTxsDatabase>(
 impl<YTDB, BDB, YC, BP> TryFrom<&Wallet<YC, YTDB, BP, BDB>>
     for TransferTransactionBuilder<YTDB, BDB>
 where
-    YTDB: YuvTransactionsStorage + Clone + Send + Sync + 'static,
+    YTDB: YuvTransactionsStorage + ChangeKeychainStorage + Clone + Send + Sync + 'static,
     BDB: bdk::database::BatchDatabase + Clone + Send,
     BP: BitcoinProvider,
 {
@@ -357,10 +522,14 @@ where
 
 impl<YTDB, BDB> TransferTransactionBuilder<YTDB, BDB>
 where
-    YTDB: YuvTransactionsStorage + Clone + Send + Sync + 'static,
+    YTDB: YuvTransactionsStorage + ChangeKeychainStorage + Clone + Send + Sync + 'static,
     BDB: bdk::database::BatchDatabase + Clone + Send,
 {
     /// Add recipient to the transaction.
+    ///
+    /// The pixel is tweaked by the recipient's public key, so it's required
+    /// even when the recipient is known only by an address. Addresses that
+    /// commit only to a hash of the key (e.g. P2WPKH) are not enough.
     pub fn add_recipient(
         &mut self,
         chroma: Chroma,
@@ -386,6 +555,13 @@ where
         self
     }
 
+    /// Override the minimal fee rate in sat/vb, which applies to all the fee
+    /// rate strategies.
+    pub fn set_min_fee_rate(&mut self, min_fee_rate: f32) -> &mut Self {
+        self.0.min_fee_rate = Some(min_fee_rate);
+        self
+    }
+
     // Override spending tweaked satoshis
     pub fn set_drain_tweaked_satoshis(&mut self, should_drain_tweaked_satoshis: bool) -> &mut Self {
         self.0.should_drain_tweaked_satoshis = should_drain_tweaked_satoshis;
@@ -393,6 +569,10 @@ where
     }
 
     /// Add satoshi recipient.
+    ///
+    /// The output is locked to the recipient's key tweaked by the empty pixel and has the
+    /// [`EmptyPixelProof`], so it carries no tokens, but stays in the YUV key family and is
+    /// spent with the proof, see [`add_tweaked_satoshis_input`](Self::add_tweaked_satoshis_input).
     pub fn add_sats_recipient(
         &mut self,
         recipient: &secp256k1::PublicKey,
@@ -432,6 +612,25 @@ where
         self
     }
 
+    /// Add recipient to the transaction, which can't spend the output before
+    /// the `locktime` (block height or timestamp, as `nLockTime`).
+    ///
+    /// The transaction output will be formed as P2WSH output with
+    /// `OP_CHECKLOCKTIMEVERIFY` followed by the tweaked recipient's key check.
+    pub fn add_timelocked_recipient(
+        &mut self,
+        chroma: Chroma,
+        recipient: &secp256k1::PublicKey,
+        amount: u128,
+        satoshis: u64,
+        locktime: u32,
+    ) -> &mut Self {
+        self.0
+            .add_timelocked_recipient(chroma, recipient, amount, satoshis, locktime);
+
+        self
+    }
+
     /// Set flag that only selected inputs will be used for transaction
     pub fn manual_selected_only(&mut self) {
         self.0.manual_selected_only();
@@ -444,6 +643,28 @@ where
         self
     }
 
+    /// Send the residual YUV coins to the `recipient`, e.g. a cold wallet,
+    /// instead of the wallet's own key. The change of the satoshis paying the
+    /// fee still goes to the wallet.
+    pub fn set_change_recipient(&mut self, recipient: &secp256k1::PublicKey) -> &mut Self {
+        self.0.change_recipient = Some(*recipient);
+
+        self
+    }
+
+    /// Set minimal amount of satoshis in the residual output for YUV coins.
+    ///
+    /// If the change satoshis are below the threshold, the residual YUV coins
+    /// are added to a recipient output of the same chroma instead of creating
+    /// a dust change output. If there is no such recipient, the change output
+    /// is created with the threshold amount of satoshis, as residual coins
+    /// can't be dropped without violating the conservation rules.
+    pub fn set_dust_threshold(&mut self, satoshis: u64) -> &mut Self {
+        self.0.set_dust_threshold(satoshis);
+
+        self
+    }
+
     /// Add pixel input to the transaction with given outpoint.
     pub fn add_pixel_input(&mut self, outpoint: OutPoint) -> &mut Self {
         self.0.add_pixel_input(outpoint);
@@ -451,46 +672,126 @@ where
         self
     }
 
+    /// Add input with the satoshis locked to the wallet's key tweaked by the empty
+    /// pixel, e.g. the output of [`add_sats_recipient`](Self::add_sats_recipient).
+    pub fn add_tweaked_satoshis_input(&mut self, outpoint: OutPoint) -> &mut Self {
+        self.0.add_tweaked_satoshis_input(outpoint);
+
+        self
+    }
+
+    /// Add P2SH-wrapped (P2SH-P2WPKH) pixel input to the transaction with
+    /// given outpoint.
+    pub fn add_sh_wpkh_pixel_input(&mut self, outpoint: OutPoint) -> &mut Self {
+        self.0.add_sh_wpkh_pixel_input(outpoint);
+
+        self
+    }
+
+    /// Add timelocked pixel input to the transaction with given outpoint.
+    ///
+    /// The `nLockTime` of the transaction is set to the locktime of the input,
+    /// so the transaction can't be mined before it.
+    pub fn add_timelocked_pixel_input(&mut self, outpoint: OutPoint) -> &mut Self {
+        self.0.add_timelocked_pixel_input(outpoint);
+
+        self
+    }
+
+    /// Restrict the Bitcoin wallet's outputs which could be spent to pay the fee.
+    ///
+    /// Only the outputs needed to cover the fee are chosen from the given ones.
+    /// Building the transaction fails if they can't cover the fee at the chosen
+    /// fee rate, instead of spending other outputs of the wallet.
+    pub fn set_fee_source(&mut self, outpoints: Vec<OutPoint>) -> &mut Self {
+        self.0.fee_source = Some(outpoints);
+
+        self
+    }
+
+    /// Spend exactly the given Bitcoin wallet's outputs to pay the fee, and
+    /// send the change of the satoshis to `change_recipient` with the
+    /// [`EmptyPixelProof`].
+    ///
+    /// Unlike [`Self::manual_selected_only`], which fixes only the YUV inputs,
+    /// neither the satoshi inputs are selected by BDK nor a new change address
+    /// is derived, so the same wallet state always gives the same transaction,
+    /// e.g. for the regression tests comparing the raw bytes.
+    pub fn set_satoshi_inputs(
+        &mut self,
+        outpoints: Vec<OutPoint>,
+        change_recipient: &secp256k1::PublicKey,
+    ) -> &mut Self {
+        self.0.set_satoshi_inputs(outpoints, *change_recipient);
+
+        self
+    }
+
+    /// Bump the fee of the parent transaction by child-pays-for-parent.
+    ///
+    /// One of the parent's outputs owned by the wallet is added as input, and
+    /// the fee of the transaction is increased, so the parent and the child
+    /// together pay the fee rate from the fee rate strategy.
+    ///
+    /// Returns an error if the wallet doesn't own any spendable output of the parent.
+    pub async fn add_cpfp_parent(&mut self, txid: Txid) -> TxBuilderResult<&mut Self> {
+        self.0.add_cpfp_parent(txid).await?;
+
+        Ok(self)
+    }
+
     /// Finish transfer building, and create Bitcoin transactions with attached
     /// proofs for it in [`YuvTransaction`].
-    pub async fn finish(self, blockchain: &impl Blockchain) -> eyre::Result<YuvTransaction> {
-        self.0.finish(blockchain).await
+    pub async fn finish(self, blockchain: &impl Blockchain) -> TxBuilderResult<YuvTransaction> {
+        Ok(self.0.finish(blockchain).await?)
     }
 }
 
 impl<YTDB, BDB> TransactionBuilder<YTDB, BDB>
 where
-    YTDB: YuvTransactionsStorage + Clone + Send + Sync + 'static,
+    YTDB: YuvTransactionsStorage + ChangeKeychainStorage + Clone + Send + Sync + 'static,
     BDB: bdk::database::BatchDatabase + Clone + Send,
 {
     fn new<YC, BC>(is_issuance: bool, wallet: &Wallet<YC, YTDB, BC, BDB>) -> eyre::Result<Self> {
         let bitcoin_wallet = wallet.bitcoin_wallet.clone();
 
         let ctx = { bitcoin_wallet.read().unwrap().secp_ctx().clone() };
+        let signer_key = wallet.signer_key()?;
 
         Ok(Self {
             is_issuance,
             chromas: Vec::new(),
             change_satoshis: 1000,
+            dust_threshold: DEFAULT_DUST_THRESHOLD,
             fee_rate_strategy: FeeRateStrategy::default(),
+            min_fee_rate: None,
             inner_wallet: bitcoin_wallet,
-            private_key: wallet.signer_key,
+            private_key: signer_key,
+            signer_keys: wallet.all_signer_keys()?,
+            change_keychain: wallet.change_keychain.clone(),
+            change_key: None,
+            change_recipient: None,
             yuv_txs_storage: wallet.yuv_txs_storage.clone(),
             yuv_utxos: wallet.utxos.clone(),
             outputs: Vec::new(),
             #[cfg(feature = "bulletproof")]
             bulletproof_outputs: BTreeMap::new(),
             inputs: Vec::new(),
-            tx_signer: TransactionSigner::new(ctx, wallet.signer_key),
+            tx_signer: TransactionSigner::new(ctx, signer_key),
             is_inputs_selected: false,
             should_drain_tweaked_satoshis: false,
+            cpfp_parents: Vec::new(),
+            fee_source: None,
+            satoshi_inputs: None,
+            satoshi_change_recipient: None,
+            unspendable: Vec::new(),
         })
     }
 }
 
 impl<YTDB, BDB> TransactionBuilder<YTDB, BDB>
 where
-    YTDB: YuvTransactionsStorage + Clone + Send + Sync + 'static,
+    YTDB: YuvTransactionsStorage + ChangeKeychainStorage + Clone + Send + Sync + 'static,
     BDB: bdk::database::BatchDatabase + Clone + Send,
 {
     fn add_sats_recipient(&mut self, recipient: &secp256k1::PublicKey, satoshis: u64) -> &mut Self {
@@ -543,20 +844,145 @@ where
         self
     }
 
+    /// Add timelocked recipient to the transaction.
+    fn add_timelocked_recipient(
+        &mut self,
+        chroma: Chroma,
+        recipient: &secp256k1::PublicKey,
+        amount: u128,
+        satoshis: u64,
+        locktime: u32,
+    ) -> &mut Self {
+        self.outputs.push(BuilderOutput::TimelockedPixel {
+            chroma,
+            satoshis,
+            amount,
+            recipient: *recipient,
+            locktime,
+        });
+
+        self.chromas.push(chroma);
+
+        self
+    }
+
     /// Add pixel input to the transaction with given outpoint.
     fn add_pixel_input(&mut self, outpoint: OutPoint) -> &mut Self {
         self.inputs.push(BuilderInput::Pixel { outpoint });
         self
     }
 
+    /// Add input with the empty pixel proof to the transaction with given outpoint.
+    fn add_tweaked_satoshis_input(&mut self, outpoint: OutPoint) -> &mut Self {
+        self.inputs.push(BuilderInput::TweakedSatoshis { outpoint });
+        self
+    }
+
+    /// Add P2SH-wrapped pixel input to the transaction with given outpoint.
+    fn add_sh_wpkh_pixel_input(&mut self, outpoint: OutPoint) -> &mut Self {
+        self.inputs.push(BuilderInput::PixelShWpkh { outpoint });
+        self
+    }
+
+    /// Add timelocked pixel input to the transaction with given outpoint.
+    fn add_timelocked_pixel_input(&mut self, outpoint: OutPoint) -> &mut Self {
+        self.inputs.push(BuilderInput::TimelockedPixel { outpoint });
+        self
+    }
+
+    /// Add owned output of the parent transaction as input and remember the
+    /// parent to bump its fee on finish.
+    async fn add_cpfp_parent(&mut self, txid: Txid) -> eyre::Result<&mut Self> {
+        let mut owned_outpoints = self
+            .yuv_utxos
+            .read()
+            .unwrap()
+            .iter()
+            .filter(|(outpoint, proof)| {
+                outpoint.txid == txid
+                    && matches!(proof, PixelProof::Sig(_) | PixelProof::EmptyPixel(_))
+            })
+            .map(|(outpoint, _)| *outpoint)
+            .collect::<Vec<OutPoint>>();
+
+        owned_outpoints.sort_by_key(|outpoint| outpoint.vout);
+
+        let Some(outpoint) = owned_outpoints.first().copied() else {
+            bail!(
+                "No spendable outputs of the parent transaction {} are owned by the wallet",
+                txid
+            );
+        };
+
+        // Proof is taken from synced transactions, so the child stays a valid YUV transaction.
+        let (proof, _output) = get_output_from_storage(&self.yuv_txs_storage, outpoint).await?;
+
+        if proof.is_empty_pixelproof() {
+            self.inputs.push(BuilderInput::TweakedSatoshis { outpoint });
+        } else {
+            self.inputs.push(BuilderInput::Pixel { outpoint });
+
+            // Add the chroma, so the amount of the parent's output is returned as change.
+            let chroma = proof.pixel().chroma;
+            if !self.chromas.contains(&chroma) {
+                self.chromas.push(chroma);
+            }
+        }
+
+        self.cpfp_parents.push(txid);
+
+        Ok(self)
+    }
+
+    /// Calculate the fee that the child should pay in addition, so that each
+    /// of [`Self::cpfp_parents`] pays the given fee rate.
+    fn cpfp_fee(&self, blockchain: &impl GetTx, fee_rate: BdkFeeRate) -> eyre::Result<u64> {
+        let mut cpfp_fee = 0;
+
+        for txid in &self.cpfp_parents {
+            let parent = blockchain
+                .get_tx(txid)?
+                .ok_or_else(|| eyre!("Parent transaction {} was not found", txid))?;
+
+            let mut inputs_sum = 0;
+            for input in &parent.input {
+                let OutPoint { txid, vout } = input.previous_output;
+
+                let tx = blockchain
+                    .get_tx(&txid)?
+                    .ok_or_else(|| eyre!("Transaction {} was not found", txid))?;
+
+                let output = tx
+                    .output
+                    .get(vout as usize)
+                    .ok_or_else(|| eyre!("Transaction {} doesn't contain vout {}", txid, vout))?;
+
+                inputs_sum += output.value;
+            }
+
+            let outputs_sum = parent.output.iter().map(|output| output.value).sum::<u64>();
+            let parent_fee = inputs_sum.saturating_sub(outputs_sum);
+
+            cpfp_fee += fee_rate.fee_wu(parent.weight()).saturating_sub(parent_fee);
+        }
+
+        Ok(cpfp_fee)
+    }
+
     fn add_tweaked_satoshi_inputs(&mut self) {
+        let selected_outpoints = self
+            .inputs
+            .iter()
+            .map(BuilderInput::outpoint)
+            .collect::<Vec<OutPoint>>();
+
         let tweaked_outputs = self
             .yuv_utxos
             .read()
             .unwrap()
             .iter()
             .filter_map(|(outpoint, proof)| {
-                if proof.is_empty_pixelproof() {
+                if proof.is_empty_pixelproof() && !selected_outpoints.contains(outpoint) {
                     Some(*outpoint)
                 } else {
                     None
@@ -576,12 +1002,83 @@ where
         self
     }
 
+    /// Set minimal amount of satoshis in the residual output for YUV coins.
+    fn set_dust_threshold(&mut self, satoshis: u64) -> &mut Self {
+        self.dust_threshold = satoshis;
+        self
+    }
+
     /// Override the fee rate strategy.
     fn set_fee_rate_strategy(&mut self, fee_rate_strategy: FeeRateStrategy) -> &mut Self {
         self.fee_rate_strategy = fee_rate_strategy;
         self
     }
 
+    /// Override the minimal fee rate.
+    fn set_min_fee_rate(&mut self, min_fee_rate: f32) -> &mut Self {
+        self.min_fee_rate = Some(min_fee_rate);
+        self
+    }
+
+    /// Minimal fee rate in sat/vb, which is the one set to the builder, the
+    /// `min` of the strategy's [`FeeRateBounds`](crate::types::FeeRateBounds),
+    /// or [`DEFAULT_MIN_FEE_RATE`] if neither is set.
+    fn min_fee_rate(&self) -> f32 {
+        self.min_fee_rate
+            .or_else(|| {
+                self.fee_rate_strategy
+                    .bounds()
+                    .and_then(|bounds| bounds.min)
+            })
+            .unwrap_or(DEFAULT_MIN_FEE_RATE)
+    }
+
+    /// Get the fee rate from the strategy, raised to the minimal one, so the
+    /// transaction isn't dropped by the nodes as the one below the min relay
+    /// fee rate.
+    ///
+    /// For [`FeeRateStrategy::AbsoluteFee`] the minimal fee rate is returned,
+    /// as the actual one is known only after the transaction is built.
+    fn get_fee_rate(&self, blockchain: &impl Blockchain) -> eyre::Result<BdkFeeRate> {
+        if self.fee_rate_strategy.absolute_fee().is_some() {
+            return Ok(BdkFeeRate::from_sat_per_vb(self.min_fee_rate()));
+        }
+
+        let fee_rate = self
+            .fee_rate_strategy
+            .get_fee_rate(blockchain)
+            .map_err(TxBuilderError::FeeEstimation)?;
+
+        let min_fee_rate = self.min_fee_rate();
+        if fee_rate.as_sat_per_vb() < min_fee_rate {
+            tracing::warn!(
+                "Fee rate {} sat/vb is below the min one, raising it to {} sat/vb",
+                fee_rate.as_sat_per_vb(),
+                min_fee_rate,
+            );
+
+            return Ok(BdkFeeRate::from_sat_per_vb(min_fee_rate));
+        }
+
+        Ok(fee_rate)
+    }
+
+    /// Check that the absolute fee of the signed transaction, if it's set, pays
+    /// the minimal fee rate for its size and the `cpfp_fee` for its parents.
+    fn check_absolute_fee(&self, tx: &Transaction, cpfp_fee: u64) -> TxBuilderResult<()> {
+        let Some(fee) = self.fee_rate_strategy.absolute_fee() else {
+            return Ok(());
+        };
+
+        let min_fee =
+            BdkFeeRate::from_sat_per_vb(self.min_fee_rate()).fee_vb(tx.vsize()) + cpfp_fee;
+        if fee < min_fee {
+            return Err(TxBuilderError::FeeBelowMinRelay { fee, min_fee });
+        }
+
+        Ok(())
+    }
+
     fn issuance_chroma(&self) -> Chroma {
         self.private_key
             .public_key(&Secp256k1::new())
@@ -591,10 +1088,7 @@ where
 
     // === Finish transaction building ===
     async fn finish(mut self, blockchain: &impl Blockchain) -> eyre::Result<YuvTransaction> {
-        let fee_rate = self
-            .fee_rate_strategy
-            .get_fee_rate(blockchain)
-            .wrap_err("failed to estimate fee")?;
+        let fee_rate = self.get_fee_rate(blockchain)?;
 
         if !self.is_inputs_selected {
             if self.should_drain_tweaked_satoshis {
@@ -607,7 +1101,74 @@ where
             }
         }
 
-        self.build_tx(fee_rate).await
+        let cpfp_fee = self
+            .cpfp_fee(blockchain, fee_rate)
+            .wrap_err("failed to calculate CPFP fee")?;
+
+        self.build_tx(fee_rate, cpfp_fee).await
+    }
+
+    async fn finish_batched(
+        mut self,
+        blockchain: &impl Blockchain,
+        max_outputs_per_tx: usize,
+    ) -> eyre::Result<Vec<YuvTransaction>> {
+        ensure!(self.is_issuance, "only issuance could be batched");
+        ensure!(
+            max_outputs_per_tx > 0,
+            "max number of outputs per transaction must be positive"
+        );
+        #[cfg(feature = "bulletproof")]
+        ensure!(
+            self.bulletproof_outputs.is_empty(),
+            "bulletproof issuance could not be batched"
+        );
+        ensure!(
+            self.satoshi_inputs.is_none(),
+            "issuance with fixed satoshi inputs could not be batched"
+        );
+
+        let fee_rate = self.get_fee_rate(blockchain)?;
+
+        if !self.is_inputs_selected && self.should_drain_tweaked_satoshis {
+            self.add_tweaked_satoshi_inputs();
+        }
+
+        let total_amount: u128 = self.outputs.iter().map(BuilderOutput::amount).sum();
+        let batches = batch_outputs(mem::take(&mut self.outputs), max_outputs_per_tx);
+
+        let mut txs = Vec::with_capacity(batches.len());
+        for batch in batches {
+            self.outputs = batch;
+
+            let tx = self.build_tx(fee_rate, 0).await?;
+
+            // Only the first transaction spends the selected inputs.
+            self.inputs.clear();
+            self.unspendable.extend(
+                tx.bitcoin_tx
+                    .input
+                    .iter()
+                    .map(|input| input.previous_output),
+            );
+
+            txs.push(tx);
+        }
+
+        let announced_amount: u128 = txs
+            .iter()
+            .filter_map(|tx| match &tx.tx_type {
+                YuvTxType::Issue { announcement, .. } => Some(announcement.amount),
+                _ => None,
+            })
+            .sum();
+
+        ensure!(
+            announced_amount == total_amount,
+            "announced amount {announced_amount} doesn't match the requested {total_amount}"
+        );
+
+        Ok(txs)
     }
 
     /// Fill [`Self::inputs`] with missing utxos that will be used to satisfy
@@ -636,7 +1197,7 @@ where
             let residual_amount = input_sum.saturating_sub(output_sum);
 
             // If remaining amount is not zero, add self-recipient
-            self.add_change_output(chroma, residual_amount)?;
+            self.add_change_output(chroma, residual_amount).await?;
 
             return Ok(());
         }
@@ -650,13 +1211,27 @@ where
             .await?;
 
         let optional_utxos = {
-            let outpoints = {
+            let outpoints: Vec<OutPoint> = {
                 let yuv_utxos = self.yuv_utxos.read().unwrap();
 
                 yuv_utxos.keys().cloned().collect()
             };
 
-            self.form_weighted_utxos(outpoints, chroma).await?
+            // Immature coinbase outputs are skipped, as the transaction
+            // spending them can't be mined in the next block.
+            let mut mature_outpoints = Vec::with_capacity(outpoints.len());
+            let synced_height = self.synced_height()?;
+            for outpoint in outpoints {
+                if let Some(tip_height) = synced_height {
+                    if self.is_immature_coinbase(outpoint, tip_height).await? {
+                        continue;
+                    }
+                }
+
+                mature_outpoints.push(outpoint);
+            }
+
+            self.form_weighted_utxos(mature_outpoints, chroma).await?
         };
 
         let target_amount = output_sum.saturating_sub(input_sum);
@@ -681,36 +1256,98 @@ where
         let filled_input_sum = input_sum + selection_result.amount;
 
         if filled_input_sum < output_sum {
-            bail!(
-                "Insufficient balance: inputs sum: {} output sum: {}",
-                filled_input_sum,
-                output_sum
-            );
+            return Err(TxBuilderError::InsufficientBalance {
+                chroma,
+                needed: output_sum,
+                available: filled_input_sum,
+            }
+            .into());
         }
 
         let change_amount = filled_input_sum.saturating_sub(output_sum);
 
         // If remaining amount is not zero, add self-recipient
         if change_amount > 0 {
-            self.add_change_output(chroma, change_amount)?;
+            self.add_change_output(chroma, change_amount).await?;
         }
 
         Ok(())
     }
 
-    fn add_change_output(&mut self, chroma: Chroma, residual_amount: u128) -> eyre::Result<()> {
+    async fn add_change_output(
+        &mut self,
+        chroma: Chroma,
+        residual_amount: u128,
+    ) -> eyre::Result<()> {
         debug_assert!(residual_amount > 0, "Residual amount is zero");
 
-        let ctx = Secp256k1::new();
+        let mut satoshis = self.change_satoshis;
+
+        if satoshis < self.dust_threshold {
+            // Change output would be a dust, so give the residual amount to a
+            // recipient of the same chroma, which keeps inputs and outputs
+            // balanced.
+            let recipient_amount = self.outputs.iter_mut().find_map(|output| match output {
+                BuilderOutput::Pixel {
+                    chroma: output_chroma,
+                    amount,
+                    ..
+                } if *output_chroma == chroma => Some(amount),
+                _ => None,
+            });
 
-        self.outputs.push(BuilderOutput::Pixel {
-            chroma,
-            satoshis: self.change_satoshis,
-            amount: residual_amount,
-            recipient: self.private_key.public_key(&ctx).inner,
-        });
+            if let Some(amount) = recipient_amount {
+                *amount += residual_amount;
 
-        Ok(())
+                return Ok(());
+            }
+
+            // Residual coins can't be dropped without breaking the
+            // conservation rules, so make the change output non-dust.
+            satoshis = self.dust_threshold;
+        }
+
+        let recipient = match self.change_recipient {
+            Some(recipient) => recipient,
+            None => {
+                let ctx = Secp256k1::new();
+
+                self.change_key(&ctx).await?.public_key(&ctx).inner
+            }
+        };
+
+        self.outputs.push(BuilderOutput::Pixel {
+            chroma,
+            satoshis,
+            amount: residual_amount,
+            recipient,
+        });
+
+        Ok(())
+    }
+
+    /// Return the key the change of the transaction is sent to. All change
+    /// outputs of the transaction share the fresh key from the keychain, or the
+    /// primary key if there is no keychain.
+    async fn change_key(&mut self, ctx: &Secp256k1<All>) -> eyre::Result<PrivateKey> {
+        let Some(keychain) = &self.change_keychain else {
+            return Ok(self.private_key);
+        };
+
+        if let Some(change_key) = self.change_key {
+            return Ok(change_key);
+        }
+
+        let change_key = keychain.next_key(ctx)?;
+        self.change_key = Some(change_key);
+
+        // The next runs of the wallet start from the stored index, so the key isn't given again
+        // and the change sent to it is tracked.
+        self.yuv_txs_storage
+            .put_change_keychain_index(keychain.next_index()?)
+            .await?;
+
+        Ok(change_key)
     }
 
     async fn inputs_sum(&self, chroma: Chroma) -> eyre::Result<u128> {
@@ -733,6 +1370,42 @@ where
         Ok(sum)
     }
 
+    /// Height of the best block at the last sync of the Bitcoin wallet, if it
+    /// was synced.
+    fn synced_height(&self) -> eyre::Result<Option<u32>> {
+        let bitcoin_wallet = self.inner_wallet.read().unwrap();
+        let sync_time = bitcoin_wallet.database().get_sync_time()?;
+
+        Ok(sync_time.map(|sync_time| sync_time.block_time.height))
+    }
+
+    /// Check if the output is of the coinbase transaction, which has less than
+    /// [`COINBASE_MATURITY`] confirmations with the best block at `tip_height`.
+    ///
+    /// The height of the coinbase is taken from its script sig (BIP34), so the
+    /// coinbases without it are considered mature.
+    async fn is_immature_coinbase(
+        &self,
+        outpoint: OutPoint,
+        tip_height: u32,
+    ) -> eyre::Result<bool> {
+        let Some(tx) = self.yuv_txs_storage.get_yuv_tx(&outpoint.txid).await? else {
+            return Ok(false);
+        };
+
+        if !tx.bitcoin_tx.is_coin_base() {
+            return Ok(false);
+        }
+
+        let Some(height) = coinbase_height(&tx.bitcoin_tx) else {
+            return Ok(false);
+        };
+
+        let confirmations = (tip_height + 1).saturating_sub(height);
+
+        Ok(confirmations < COINBASE_MATURITY)
+    }
+
     /// Form [`WeightedUtxo`] for YUV coins from given [`OutPoint`]s from
     /// unspent transaction outputs.
     async fn form_weighted_utxos(
@@ -751,6 +1424,12 @@ where
                 continue;
             }
 
+            // Timelocked outputs are spent only when added explicitly, as the
+            // transaction can't be mined before their locktime.
+            if matches!(proof, PixelProof::Timelock(_)) {
+                continue;
+            }
+
             if pixel.chroma != chroma {
                 continue;
             }
@@ -777,6 +1456,17 @@ where
         Ok(weighted_utxos)
     }
 
+    /// Pay the fee only with the given satoshi inputs, and send their change
+    /// to the drain output of `change_recipient` with the [`EmptyPixelProof`].
+    fn set_satoshi_inputs(
+        &mut self,
+        outpoints: Vec<OutPoint>,
+        change_recipient: secp256k1::PublicKey,
+    ) {
+        self.satoshi_inputs = Some(outpoints);
+        self.satoshi_change_recipient = Some(change_recipient);
+    }
+
     /// Set flag that only selected inputs will be used for transaction
     fn manual_selected_only(&mut self) {
         self.is_inputs_selected = true;
@@ -801,18 +1491,31 @@ where
             output_proofs.len()
         };
 
-        tx_outs.iter_mut().skip(offset).for_each(|tx_out| {
+        let change = self
+            .satoshi_change_recipient
+            .map(get_empty_pixel_proof)
+            .transpose()?;
+
+        for tx_out in tx_outs.iter_mut().skip(offset) {
+            // The change of the fixed satoshi inputs already has the script of
+            // its recipient, see `Self::set_satoshi_inputs`.
+            if let Some((change_proof, change_script)) = &change {
+                if &tx_out.script_pubkey == change_script {
+                    output_proofs.push(change_proof.clone());
+                    continue;
+                }
+            }
+
             let (pixel_proof, script_pubkey) = get_empty_pixel_proof(
                 self.private_key
                     .public_key(&ctx)
                     .even_public_key(&ctx)
                     .inner,
-            )
-            .expect("Failed to get empty pixelproof");
+            )?;
 
             output_proofs.push(pixel_proof);
             tx_out.script_pubkey = script_pubkey;
-        });
+        }
 
         Ok(())
     }
@@ -821,10 +1524,7 @@ where
         mut self,
         blockchain: &impl Blockchain,
     ) -> eyre::Result<Option<Transaction>> {
-        let fee_rate = self
-            .fee_rate_strategy
-            .get_fee_rate(blockchain)
-            .wrap_err("failed to estimate fee")?;
+        let fee_rate = self.get_fee_rate(blockchain)?;
         let ctx = Secp256k1::new();
 
         // Get the tweaked UTXOs.
@@ -855,7 +1555,10 @@ where
         let bitcoin_wallet = self.inner_wallet.read().unwrap();
         let mut tx_builder = bitcoin_wallet.build_tx();
         tx_builder.only_witness_utxo();
-        tx_builder.fee_rate(fee_rate);
+        match self.fee_rate_strategy.absolute_fee() {
+            Some(fee) => tx_builder.fee_absolute(fee),
+            None => tx_builder.fee_rate(fee_rate),
+        };
 
         for (outpoint, psbt_input, weight) in &inputs {
             tx_builder.add_foreign_utxo(*outpoint, psbt_input.clone(), *weight)?;
@@ -881,24 +1584,40 @@ where
             total_weight += weight;
         }
 
-        let fee = fee_rate.as_sat_per_vb() as u64 * total_weight as u64;
-        let output_sum = inputs_sum - fee;
+        let fee = match self.fee_rate_strategy.absolute_fee() {
+            Some(fee) => fee,
+            None => fee_rate.as_sat_per_vb() as u64 * total_weight as u64,
+        };
+        let Some(output_sum) = inputs_sum.checked_sub(fee) else {
+            return Err(TxBuilderError::InsufficientSatoshis {
+                needed: fee,
+                available: inputs_sum,
+            }
+            .into());
+        };
 
         let pubkey = self.private_key.public_key(&ctx);
         let script_pubkey = Script::new_v0_p2wpkh(&pubkey.wpubkey_hash().unwrap());
 
         tx_builder.add_recipient(script_pubkey, output_sum);
 
-        let (mut psbt, _details) = tx_builder.finish()?;
+        let (mut psbt, _details) = match tx_builder.finish() {
+            Err(bdk::Error::InsufficientFunds { needed, available }) => {
+                return Err(TxBuilderError::InsufficientSatoshis { needed, available }.into())
+            }
+            result => result?,
+        };
 
-        bitcoin_wallet.sign(
-            &mut psbt,
-            SignOptions {
-                try_finalize: true,
-                trust_witness_utxo: true,
-                ..Default::default()
-            },
-        )?;
+        bitcoin_wallet
+            .sign(
+                &mut psbt,
+                SignOptions {
+                    try_finalize: true,
+                    trust_witness_utxo: true,
+                    ..Default::default()
+                },
+            )
+            .map_err(|err| TxBuilderError::Signing(err.into()))?;
 
         let input_proofs = tweaked_outputs
             .iter()
@@ -906,12 +1625,27 @@ where
             .map(|(i, (_, proof))| (i as u32, proof.clone()))
             .collect::<ProofMap>();
 
-        self.tx_signer.sign(&mut psbt, &input_proofs)?;
+        self.tx_signer
+            .sign(&mut psbt, &input_proofs)
+            .map_err(TxBuilderError::Signing)?;
 
-        Ok(Some(psbt.extract_tx()))
+        let tx = psbt.extract_tx();
+        self.check_absolute_fee(&tx, 0)?;
+
+        Ok(Some(tx))
     }
 
-    async fn build_tx(mut self, fee_rate: BdkFeeRate) -> eyre::Result<YuvTransaction> {
+    /// Build and sign the transaction paying the `fee_rate` and the
+    /// `cpfp_fee` for the parents.
+    ///
+    /// With [`FeeRateStrategy::AbsoluteFee`] the transaction pays exactly the
+    /// fixed fee instead, which must cover the min fee rate for the final size
+    /// and the `cpfp_fee`.
+    async fn build_tx(
+        &mut self,
+        fee_rate: BdkFeeRate,
+        cpfp_fee: u64,
+    ) -> eyre::Result<YuvTransaction> {
         let ctx = Secp256k1::new();
 
         // Gather inputs as foreighn utxos with proofs for BDK wallet.
@@ -943,30 +1677,103 @@ where
             self.process_output(output, &mut output_proofs, &mut outputs)?;
         }
 
+        let locktime = inputs_locktime(input_proofs.values())?;
+
         let bitcoin_wallet = self.inner_wallet.read().unwrap();
-        let mut tx_builder = bitcoin_wallet.build_tx();
 
-        // Do not sort inputs and outputs to make proofs valid
-        tx_builder.ordering(TxOrdering::Untouched);
-        tx_builder.only_witness_utxo();
-        tx_builder.fee_rate(fee_rate);
+        // Wallet's outputs which BDK must not choose from to pay the fee, as
+        // they aren't in the fee source.
+        let outside_fee_source = match &self.fee_source {
+            Some(fee_source) => {
+                let utxos = bitcoin_wallet.list_unspent()?;
+                ensure!(
+                    fee_source
+                        .iter()
+                        .all(|outpoint| utxos.iter().any(|utxo| utxo.outpoint == *outpoint)),
+                    "fee source has outputs unknown to the wallet"
+                );
+
+                utxos
+                    .into_iter()
+                    .map(|utxo| utxo.outpoint)
+                    .filter(|outpoint| !fee_source.contains(outpoint))
+                    .collect()
+            }
+            None => Vec::new(),
+        };
 
-        if self.is_issuance {
-            let announcement = form_issue_announcement(output_proofs.clone())?;
+        let build_psbt = |absolute_fee: Option<u64>| -> eyre::Result<_> {
+            let mut tx_builder = bitcoin_wallet.build_tx();
 
-            tx_builder.add_recipient(announcement.to_script(), 0);
-        }
-        // Fill tx_builder with formed inputs and outputs
-        for (script_pubkey, amount) in outputs {
-            tx_builder.add_recipient(script_pubkey, amount);
-        }
-        for (outpoint, psbt_input, weight) in inputs {
-            tx_builder.add_foreign_utxo(outpoint, psbt_input, weight)?;
-        }
+            // Do not sort inputs and outputs to make proofs valid
+            tx_builder.ordering(TxOrdering::Untouched);
+            tx_builder.only_witness_utxo();
+            tx_builder.unspendable(self.unspendable.clone());
+            for outpoint in &outside_fee_source {
+                tx_builder.add_unspendable(*outpoint);
+            }
 
-        // Form transaction with satoshi inputs to satisfy consideration rules
-        // of Bitcoin.
-        let (mut psbt, _details) = tx_builder.finish()?;
+            // Timelocked inputs can be spent only by the transaction with the
+            // same or greater locktime. BDK also makes the sequence of inputs
+            // non-final, so the locktime is enforced.
+            if let Some(locktime) = locktime {
+                tx_builder.nlocktime(locktime);
+            }
+
+            match absolute_fee {
+                Some(fee) => tx_builder.fee_absolute(fee),
+                None => tx_builder.fee_rate(fee_rate),
+            };
+
+            if self.is_issuance {
+                let announcement = form_issue_announcement(output_proofs.clone())?;
+
+                tx_builder.add_recipient(announcement.to_script(), 0);
+            }
+            // Fill tx_builder with formed inputs and outputs
+            for (script_pubkey, amount) in &outputs {
+                tx_builder.add_recipient(script_pubkey.clone(), *amount);
+            }
+            for (outpoint, psbt_input, weight) in &inputs {
+                tx_builder.add_foreign_utxo(*outpoint, psbt_input.clone(), *weight)?;
+            }
+
+            // Only the fixed satoshi inputs are spent in addition to the
+            // inputs above. Otherwise, BDK adds satoshi inputs to satisfy
+            // consideration rules of Bitcoin.
+            if let Some(satoshi_inputs) = &self.satoshi_inputs {
+                tx_builder
+                    .add_utxos(satoshi_inputs)
+                    .wrap_err("satoshi inputs have outputs unknown to the wallet")?
+                    .manually_selected_only();
+            }
+            if let Some(change_recipient) = self.satoshi_change_recipient {
+                let (_, change_script) = get_empty_pixel_proof(change_recipient)?;
+                tx_builder.drain_to(change_script);
+            }
+
+            match tx_builder.finish() {
+                Err(bdk::Error::InsufficientFunds { needed, available }) => {
+                    Err(TxBuilderError::InsufficientSatoshis { needed, available }.into())
+                }
+                result => Ok(result?),
+            }
+        };
+
+        let (mut psbt, details) = build_psbt(None)?;
+
+        // The absolute fee replaces the one at the min fee rate the inputs
+        // were selected with. BDK adds more satoshi inputs if it's needed to
+        // cover the fee, so the final size is checked after signing.
+        if let Some(fee) = self.fee_rate_strategy.absolute_fee() {
+            (psbt, _) = build_psbt(Some(fee))?;
+        } else if cpfp_fee > 0 {
+            // Child pays for its parents, so the fee is increased by the amount
+            // the parents lack to pay the fee rate.
+            let fee = details.fee.unwrap_or_default() + cpfp_fee;
+
+            (psbt, _) = build_psbt(Some(fee))?;
+        }
 
         self.insert_empty_pixelproofs(&mut output_proofs, &mut psbt.unsigned_tx.output)?;
 
@@ -978,19 +1785,23 @@ where
         )?;
 
         // Sign non YUV inputs with BDK wallet.
-        bitcoin_wallet.sign(
-            &mut psbt,
-            SignOptions {
-                try_finalize: true,
-                trust_witness_utxo: true,
-                ..Default::default()
-            },
-        )?;
+        bitcoin_wallet
+            .sign(
+                &mut psbt,
+                SignOptions {
+                    try_finalize: true,
+                    trust_witness_utxo: true,
+                    ..Default::default()
+                },
+            )
+            .map_err(|err| TxBuilderError::Signing(err.into()))?;
 
         // We need to sign inputs in case of transfer transaction as there are always YUV inputs.
         // We also need to sign issue transaction inputs if it spends tweaked satoshis.
         if let YuvTxType::Transfer { input_proofs, .. } = &tx_type {
-            self.tx_signer.sign(&mut psbt, input_proofs)?;
+            self.tx_signer
+                .sign(&mut psbt, input_proofs)
+                .map_err(TxBuilderError::Signing)?;
         } else if let YuvTxType::Issue { .. } = &tx_type {
             // Offset is basically the number of regular Bitcoin inputs that we need to skip
             // while constructing input proofs.
@@ -1001,10 +1812,13 @@ where
                 .map(|(index, proof)| ((index + offset) as u32, proof))
                 .collect();
 
-            self.tx_signer.sign(&mut psbt, &input_proofs)?;
+            self.tx_signer
+                .sign(&mut psbt, &input_proofs)
+                .map_err(TxBuilderError::Signing)?;
         }
 
         let tx = psbt.extract_tx();
+        self.check_absolute_fee(&tx, cpfp_fee)?;
 
         Ok(YuvTransaction {
             bitcoin_tx: tx,
@@ -1051,7 +1865,7 @@ where
 
             // Get descriptor and secret keys depending on the input type
             let (descriptor, secret_keys) =
-                self.get_descriptor_and_keys_for_input(ctx, input, &proof)?;
+                self.get_descriptor_and_keys_for_input(ctx, input, &proof, &output.script_pubkey)?;
 
             // Extend list of signers
             self.tx_signer.extend_signers(secret_keys);
@@ -1061,12 +1875,27 @@ where
             psbt_input.update_with_descriptor_unchecked(&derived)?;
 
             // Some additional processing for psbt input
-            if let BuilderInput::Multisig2x2 { .. } = input {
-                let PixelProof::Multisig(multisig_proof) = proof else {
-                    bail!("Invalid input proof type: proof is not multisig");
-                };
+            match input {
+                BuilderInput::Multisig2x2 { .. } => {
+                    let PixelProof::Multisig(multisig_proof) = proof else {
+                        bail!("Invalid input proof type: proof is not multisig");
+                    };
 
-                psbt_input.redeem_script = Some(multisig_proof.to_reedem_script()?);
+                    psbt_input.redeem_script = Some(multisig_proof.to_reedem_script()?);
+                }
+                BuilderInput::PixelShWpkh { .. } => {
+                    let signer_key =
+                        self.find_signer_key(ctx, proof.pixel(), &output.script_pubkey)?;
+                    let pubkey = signer_key.public_key(ctx);
+                    let pixel_key = PixelKey::new_with_ctx(proof.pixel(), &pubkey.inner, ctx)?;
+
+                    psbt_input.redeem_script = Some(
+                        pixel_key
+                            .to_p2wpkh()
+                            .ok_or_eyre("Pixel key is not compressed")?,
+                    );
+                }
+                _ => {}
             }
 
             let weight = derived.max_satisfaction_weight()?;
@@ -1079,11 +1908,15 @@ where
 
     /// Return descriptor for input and return map of keys that will be used for
     /// singing input after transaction is built.
+    ///
+    /// The input is signed with the wallet's key that the pixel locked in the
+    /// `script_pubkey` is tweaked with.
     fn get_descriptor_and_keys_for_input(
         &self,
         ctx: &Secp256k1<All>,
         input: &BuilderInput,
         proof: &PixelProof,
+        script_pubkey: &Script,
     ) -> eyre::Result<(
         Descriptor<DescriptorPublicKey>,
         HashMap<XOnlyPublicKey, secp256k1::SecretKey>,
@@ -1091,8 +1924,25 @@ where
         // Store private keys for future signing.
         let mut keys = HashMap::new();
 
-        let pubkey1 = self.private_key.public_key(ctx);
-        keys.insert(pubkey1.inner.into(), self.private_key.inner);
+        let signer_key = match (input, proof) {
+            (BuilderInput::Multisig2x2 { .. }, _) => self.private_key,
+            (BuilderInput::TimelockedPixel { .. }, PixelProof::Timelock(timelock_proof)) => self
+                .signer_keys
+                .iter()
+                .find(|key| key.public_key(ctx).inner == timelock_proof.inner_key)
+                .copied()
+                .ok_or_eyre("Timelocked input is not locked to any of the wallet's keys")?,
+            (BuilderInput::TimelockedPixel { .. }, _) => {
+                bail!("Invalid input proof type: proof is not timelock")
+            }
+            (BuilderInput::TweakedSatoshis { .. }, proof) if !proof.is_empty_pixelproof() => {
+                bail!("Invalid input proof type: proof is not empty pixel")
+            }
+            _ => self.find_signer_key(ctx, proof.pixel(), script_pubkey)?,
+        };
+
+        let pubkey1 = signer_key.public_key(ctx);
+        keys.insert(pubkey1.inner.into(), signer_key.inner);
 
         // Keys keys depending of input type, and create descriptors on that.
         let (descriptor, _secret_keys, _) = match input {
@@ -1101,6 +1951,23 @@ where
 
                 descriptor!(wpkh(tweaked_pubkey))?
             }
+            BuilderInput::PixelShWpkh { .. } => {
+                let tweaked_pubkey = PixelKey::new_with_ctx(proof.pixel(), &pubkey1.inner, ctx)?;
+
+                descriptor!(sh(wpkh(tweaked_pubkey)))?
+            }
+            BuilderInput::TimelockedPixel { .. } => {
+                let PixelProof::Timelock(timelock_proof) = proof else {
+                    bail!("Invalid input proof type: proof is not timelock");
+                };
+
+                let tweaked_pubkey = PixelKey::new_with_ctx(proof.pixel(), &pubkey1.inner, ctx)?;
+
+                descriptor!(wsh(and_v(
+                    v: after(timelock_proof.locktime),
+                    pk(tweaked_pubkey.0)
+                )))?
+            }
             BuilderInput::TweakedSatoshis { .. } => {
                 let tweaked_pubkey = PixelKey::new_with_ctx(Pixel::empty(), &pubkey1.inner, ctx)?;
 
@@ -1128,6 +1995,28 @@ where
         Ok((descriptor, keys))
     }
 
+    /// Find the wallet's key which, being tweaked by the `pixel`, locks the
+    /// `script_pubkey`. Falls back to the primary key if there is no such.
+    fn find_signer_key(
+        &self,
+        ctx: &Secp256k1<All>,
+        pixel: Pixel,
+        script_pubkey: &Script,
+    ) -> eyre::Result<PrivateKey> {
+        for signer_key in &self.signer_keys {
+            let pubkey = signer_key.public_key(ctx);
+            let pixel_key = PixelKey::new_with_ctx(pixel, &pubkey.inner, ctx)?;
+
+            if pixel_key.to_p2wpkh().as_ref() == Some(script_pubkey)
+                || pixel_key.to_p2sh_p2wpkh().as_ref() == Some(script_pubkey)
+            {
+                return Ok(*signer_key);
+            }
+        }
+
+        Ok(self.private_key)
+    }
+
     /// Add output to the bitcoin transactions and list of output proofs.
     fn process_output(
         &self,
@@ -1135,6 +2024,18 @@ where
         output_proofs: &mut Vec<PixelProof>,
         outputs: &mut Vec<(Script, u64)>,
     ) -> eyre::Result<()> {
+        // Outputs without tokens must have the empty pixel proof, so zero-amount pixels are
+        // rejected by the nodes.
+        if let BuilderOutput::Pixel { amount, .. }
+        | BuilderOutput::MultisigPixel { amount, .. }
+        | BuilderOutput::TimelockedPixel { amount, .. } = output
+        {
+            ensure!(
+                *amount > 0,
+                "Amount of the pixel output must be greater than zero"
+            );
+        }
+
         let (script_pubkey, satoshis) = match output {
             // For satoshis output no addtion processing is required
             BuilderOutput::Satoshis {
@@ -1154,13 +2055,7 @@ where
                 recipient,
             } => {
                 let pixel = Pixel::new(*amount, *chroma);
-                let pixel_key = PixelKey::new(pixel, recipient)?;
-
-                let pubkey_hash = &pixel_key
-                    .wpubkey_hash()
-                    .ok_or_eyre("Pixel key is not compressed")?;
-
-                let script_pubkey = Script::new_v0_p2wpkh(pubkey_hash);
+                let script_pubkey = derive_pixel_script_pubkey(pixel, recipient)?;
 
                 let pixel_proof = SigPixelProof::new(pixel, *recipient);
 
@@ -1186,6 +2081,23 @@ where
 
                 (script_pubkey, *satoshis)
             }
+            // For timelocked pixel, form P2WSH script and push proof of it to the list
+            BuilderOutput::TimelockedPixel {
+                chroma,
+                satoshis,
+                amount,
+                recipient,
+                locktime,
+            } => {
+                let pixel = Pixel::new(*amount, *chroma);
+
+                let timelock_proof = TimelockPixelProof::new(pixel, *recipient, *locktime);
+                let script_pubkey = timelock_proof.to_script_pubkey()?;
+
+                output_proofs.push(timelock_proof.into());
+
+                (script_pubkey, *satoshis)
+            }
             // For bulletproof pixel, form script and push proof of it to the list
             #[cfg(feature = "bulletproof")]
             BuilderOutput::BulletproofPixel {
@@ -1232,23 +2144,41 @@ where
     }
 }
 
+/// Get the height of the block from the first push of the coinbase script sig,
+/// as required by BIP34.
+fn coinbase_height(coinbase: &Transaction) -> Option<u32> {
+    let input = coinbase.input.first()?;
+
+    let height = match input.script_sig.instructions_minimal().next()?.ok()? {
+        Instruction::PushBytes(bytes) => read_scriptint(bytes).ok()?,
+        Instruction::Op(op) => match op.classify(ClassifyContext::Legacy) {
+            Class::PushNum(num) => i64::from(num),
+            _ => return None,
+        },
+    };
+
+    u32::try_from(height).ok()
+}
+
 pub(crate) async fn get_output_from_storage<YTDB>(
     yuv_txs_storage: &YTDB,
     OutPoint { txid, vout }: OutPoint,
 ) -> eyre::Result<(PixelProof, TxOut)>
 where
-    YTDB: YuvTransactionsStorage + Clone + Send + Sync + 'static,
+    YTDB: YuvTransactionsStorage + ChangeKeychainStorage + Clone + Send + Sync + 'static,
 {
-    let Some(tx) = yuv_txs_storage.get_yuv_tx(&txid).await? else {
-        bail!("Transaction is not found in synced YUV txs: {}", txid);
-    };
+    let missing_proof = || TxBuilderError::MissingProof(OutPoint::new(txid, vout));
 
-    let Some(output_proofs) = tx.tx_type.output_proofs() else {
-        bail!("Transaction {} has no output proofs", txid);
+    let Some(tx) = yuv_txs_storage.get_yuv_tx(&txid).await? else {
+        return Err(missing_proof().into());
     };
 
-    let Some(proof) = output_proofs.get(&vout) else {
-        bail!("Input is not found in synced YUV txs: {}:{}", txid, vout);
+    let Some(proof) = tx
+        .tx_type
+        .output_proofs()
+        .and_then(|output_proofs| output_proofs.get(&vout))
+    else {
+        return Err(missing_proof().into());
     };
 
     let Some(output) = tx.bitcoin_tx.output.get(vout as usize) else {
@@ -1304,6 +2234,60 @@ fn sort_and_tweak(
     Ok((key1_tweaked, public_key2))
 }
 
+/// Split the outputs into batches with at most `max_yuv_outputs` YUV outputs
+/// each. Satoshi outputs are put into the first batch.
+fn batch_outputs(outputs: Vec<BuilderOutput>, max_yuv_outputs: usize) -> Vec<Vec<BuilderOutput>> {
+    let (yuv_outputs, sats_outputs): (Vec<_>, Vec<_>) = outputs
+        .into_iter()
+        .partition(|output| output.chroma().is_some());
+
+    let mut batches = vec![sats_outputs];
+    for output in yuv_outputs {
+        match batches.last_mut() {
+            Some(batch)
+                if batch
+                    .iter()
+                    .filter(|output| output.chroma().is_some())
+                    .count()
+                    < max_yuv_outputs =>
+            {
+                batch.push(output)
+            }
+            _ => batches.push(vec![output]),
+        }
+    }
+
+    batches
+}
+
+/// Get the locktime the transaction must have to spend the timelocked inputs,
+/// which is the latest of their locktimes.
+fn inputs_locktime<'a>(
+    input_proofs: impl IntoIterator<Item = &'a PixelProof>,
+) -> eyre::Result<Option<LockTime>> {
+    let mut result: Option<LockTime> = None;
+
+    for proof in input_proofs {
+        let PixelProof::Timelock(timelock_proof) = proof else {
+            continue;
+        };
+
+        let locktime = LockTime::from_consensus(timelock_proof.locktime);
+
+        result = match result {
+            Some(current) if !current.is_same_unit(locktime) => {
+                bail!("Timelocked inputs with block height and timestamp locktimes can't be spent together")
+            }
+            Some(current) if current.to_consensus_u32() >= locktime.to_consensus_u32() => {
+                Some(current)
+            }
+            _ => Some(locktime),
+        };
+    }
+
+    Ok(result)
+}
+
 /// Generate an empty pixel proof using the given `PublicKey` and an empty `Pixel`.
 fn get_empty_pixel_proof(recipient: secp256k1::PublicKey) -> eyre::Result<(PixelProof, Script)> {
     let pixel_key = PixelKey::new(Pixel::empty(), &recipient)?;
@@ -1363,10 +2347,22 @@ fn form_tx_type(
 
 #[cfg(test)]
 mod tests {
-    use bdk::database::MemoryDatabase;
+    use bdk::{
+        blockchain::{esplora::EsploraBlockchainConfig, AnyBlockchainConfig},
+        database::{BatchOperations, MemoryDatabase, SyncTime},
+        wallet::AddressIndex,
+        BlockTime, LocalUtxo, TransactionDetails,
+    };
+    use bitcoin::{
+        blockdata::script::Builder, hashes::Hash, secp256k1::Message, util::sighash::SighashCache,
+        EcdsaSig, EcdsaSighashType, Network, PackedLockTime, TxIn,
+    };
+    use jsonrpsee::http_client::HttpClientBuilder;
+    use yuv_pixels::{CheckableProof, PixelPrivateKey};
     use yuv_storage::LevelDB;
 
     use super::*;
+    use crate::{database::wrapper::DatabaseWrapper, types::FeeRateBounds, wallet::MemoryWallet};
 
     fn check_is_sync<T: Sync>() {}
     fn check_is_send<T: Send>() {}
@@ -1376,4 +2372,1097 @@ mod tests {
         check_is_sync::<TransactionBuilder<LevelDB, MemoryDatabase>>();
         check_is_send::<TransactionBuilder<LevelDB, MemoryDatabase>>();
     }
+
+    #[test]
+    fn test_batch_outputs() {
+        let recipient =
+            PrivateKey::from_wif("cNMMXcLoM65N5GaULU7ct2vexmQnJ5i5j3Sjc6iNnEF18vY7gzn9")
+                .unwrap()
+                .public_key(&Secp256k1::new())
+                .inner;
+        let chroma = Chroma::from(recipient.x_only_public_key().0);
+
+        let mut outputs = (1..=5)
+            .map(|amount| BuilderOutput::Pixel {
+                chroma,
+                satoshis: 1000,
+                amount,
+                recipient,
+            })
+            .collect::<Vec<_>>();
+        outputs.insert(
+            2,
+            BuilderOutput::Satoshis {
+                satoshis: 1000,
+                recipient,
+            },
+        );
+
+        let batches = batch_outputs(outputs, 2);
+
+        let amounts = batches
+            .iter()
+            .map(|batch| batch.iter().map(BuilderOutput::amount).collect::<Vec<_>>())
+            .collect::<Vec<_>>();
+
+        assert_eq!(amounts, vec![vec![0, 1, 2], vec![3, 4], vec![5]]);
+    }
+
+    /// Regtest key with every byte of the secret set to `byte`.
+    pub(super) fn test_key(byte: u8) -> PrivateKey {
+        PrivateKey::new(
+            secp256k1::SecretKey::from_slice(&[byte; 32]).unwrap(),
+            Network::Regtest,
+        )
+    }
+
+    pub(super) fn memory_wallet(signer_key: PrivateKey) -> eyre::Result<MemoryWallet> {
+        memory_wallet_with_database(
+            signer_key,
+            LevelDB::in_memory()?,
+            DatabaseWrapper::new(MemoryDatabase::default()),
+        )
+    }
+
+    /// Create the wallet with the given YUV storage and Bitcoin database, so
+    /// its state could be shared with the other wallet, and its outputs could
+    /// be added by the test.
+    fn memory_wallet_with_database(
+        signer_key: PrivateKey,
+        yuv_txs_storage: LevelDB,
+        database: DatabaseWrapper<MemoryDatabase>,
+    ) -> eyre::Result<MemoryWallet> {
+        let bitcoin_provider = BitcoinProvider::from_config(AnyBlockchainConfig::Esplora(
+            EsploraBlockchainConfig::new("http://127.0.0.1:3000".to_string(), 20),
+        ))?;
+
+        MemoryWallet::new(
+            signer_key,
+            Network::Regtest,
+            HttpClientBuilder::new().build("http://127.0.0.1:18333")?,
+            yuv_txs_storage,
+            bitcoin_provider,
+            database,
+        )
+    }
+
+    /// Put the output of `value` satoshis to the wallet's address to the
+    /// Bitcoin database, so it could pay the fee.
+    fn put_satoshi_output(
+        wallet: &MemoryWallet,
+        database: &mut DatabaseWrapper<MemoryDatabase>,
+        value: u64,
+    ) -> eyre::Result<OutPoint> {
+        let script_pubkey = wallet
+            .bitcoin_wallet
+            .read()
+            .unwrap()
+            .get_address(AddressIndex::New)?
+            .address
+            .script_pubkey();
+
+        // Differ the transactions by the value, so the outpoints are unique.
+        let tx = Transaction {
+            version: 2,
+            lock_time: PackedLockTime::ZERO,
+            input: vec![TxIn::default()],
+            output: vec![TxOut {
+                value,
+                script_pubkey,
+            }],
+        };
+        let outpoint = OutPoint::new(tx.txid(), 0);
+
+        // The details are needed for BDK to choose the output by itself.
+        database.set_tx(&TransactionDetails {
+            transaction: Some(tx.clone()),
+            txid: tx.txid(),
+            received: value,
+            sent: 0,
+            fee: None,
+            confirmation_time: None,
+        })?;
+        database.set_utxo(&LocalUtxo {
+            outpoint,
+            txout: tx.output[0].clone(),
+            keychain: bdk::KeychainKind::External,
+            is_spent: false,
+        })?;
+
+        Ok(outpoint)
+    }
+
+    /// Put the issuance of the `pixel` to the `owner` to the wallet's storage,
+    /// so it can be spent.
+    pub(super) async fn put_pixel_output(
+        wallet: &MemoryWallet,
+        pixel: Pixel,
+        owner: &PrivateKey,
+    ) -> eyre::Result<OutPoint> {
+        put_pixel_output_with_input(wallet, pixel, owner, TxIn::default()).await
+    }
+
+    /// Put the issuance of the `pixel` to the `owner` with the given `input`
+    /// to the wallet's storage, e.g. the coinbase one with the block height.
+    async fn put_pixel_output_with_input(
+        wallet: &MemoryWallet,
+        pixel: Pixel,
+        owner: &PrivateKey,
+        input: TxIn,
+    ) -> eyre::Result<OutPoint> {
+        let ctx = Secp256k1::new();
+        let pubkey = owner.public_key(&ctx).inner;
+        let pixel_key = PixelKey::new(pixel, &pubkey)?;
+
+        let bitcoin_tx = Transaction {
+            version: 2,
+            lock_time: PackedLockTime::ZERO,
+            input: vec![input],
+            output: vec![TxOut {
+                value: 10_000,
+                script_pubkey: pixel_key.to_p2wpkh().ok_or_eyre("not compressed")?,
+            }],
+        };
+        let txid = bitcoin_tx.txid();
+
+        wallet
+            .yuv_txs_storage
+            .put_yuv_tx(YuvTransaction {
+                bitcoin_tx,
+                tx_type: YuvTxType::Issue {
+                    output_proofs: Some(ProofMap::from([(
+                        0,
+                        SigPixelProof::new(pixel, pubkey).into(),
+                    )])),
+                    announcement: IssueAnnouncement::new(pixel.chroma, pixel.luma.amount),
+                },
+            })
+            .await?;
+
+        Ok(OutPoint::new(txid, 0))
+    }
+
+    #[tokio::test]
+    async fn test_fee_rate_is_raised_to_min() -> eyre::Result<()> {
+        let wallet = memory_wallet(test_key(1))?;
+        let blockchain = wallet.bitcoin_provider.blockchain();
+
+        let mut builder = TransferTransactionBuilder::try_from(&wallet)?;
+        builder.set_fee_rate_strategy(FeeRateStrategy::Manual { fee_rate: 0.5 });
+
+        let fee_rate = builder.0.get_fee_rate(&blockchain)?;
+        assert_eq!(fee_rate.as_sat_per_vb(), DEFAULT_MIN_FEE_RATE);
+
+        builder.set_fee_rate_strategy(FeeRateStrategy::TryEstimate {
+            fee_rate: 0.5,
+            target: 2,
+            bounds: FeeRateBounds {
+                min: Some(0.25),
+                ..FeeRateBounds::NONE
+            },
+        });
+        let fee_rate = builder.0.get_fee_rate(&blockchain)?;
+        assert_eq!(
+            fee_rate.as_sat_per_vb(),
+            0.5,
+            "fee rate above the min one must not be changed"
+        );
+
+        builder
+            .set_fee_rate_strategy(FeeRateStrategy::Manual { fee_rate: 1.5 })
+            .set_min_fee_rate(2.0);
+        let fee_rate = builder.0.get_fee_rate(&blockchain)?;
+        assert_eq!(
+            fee_rate.as_sat_per_vb(),
+            2.0,
+            "manual fee rate must be raised to the min one set to the builder"
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_transfer_spends_inputs_locked_to_different_signer_keys() {
+        let ctx = Secp256k1::new();
+        let old_key = test_key(1);
+        let new_key = test_key(2);
+        let recipient = test_key(3).public_key(&ctx).inner;
+
+        let mut wallet = memory_wallet(new_key).unwrap();
+        wallet.add_signer_key(old_key);
+
+        let chroma = Chroma::from(recipient.x_only_public_key().0);
+        let pixel = Pixel::new(500, chroma);
+
+        let mut outpoints = Vec::new();
+        for owner in [old_key, new_key] {
+            outpoints.push(put_pixel_output(&wallet, pixel, &owner).await.unwrap());
+        }
+
+        let mut builder = wallet.build_transfer().unwrap();
+        for outpoint in &outpoints {
+            builder.add_pixel_input(*outpoint);
+        }
+        builder
+            .add_recipient(chroma, &recipient, 1000, 1000)
+            .set_fee_rate_strategy(FeeRateStrategy::Manual { fee_rate: 1.0 });
+
+        let blockchain = wallet.bitcoin_provider.blockchain();
+        let yuv_tx = builder.finish(&blockchain).await.unwrap();
+
+        let YuvTxType::Transfer { input_proofs, .. } = &yuv_tx.tx_type else {
+            panic!("transaction must be a transfer");
+        };
+
+        let tx = &yuv_tx.bitcoin_tx;
+        for (index, owner) in [old_key, new_key].iter().enumerate() {
+            let txin = &tx.input[index];
+            assert_eq!(txin.previous_output, outpoints[index]);
+
+            let proof = input_proofs.get(&(index as u32)).expect("proof must exist");
+            proof
+                .checked_check_by_input(txin)
+                .expect("input must be signed by the owner's tweaked key");
+
+            // The signature must be valid for the tweaked key of the owner.
+            let tweaked_key = PixelPrivateKey::new_with_ctx(pixel, &owner.inner, &ctx).unwrap();
+            let tweaked_pubkey = PublicKey::new(tweaked_key.0.public_key(&ctx));
+            let script_code = Script::new_v0_p2wpkh(&tweaked_pubkey.wpubkey_hash().unwrap())
+                .p2wpkh_script_code()
+                .unwrap();
+
+            let sighash = SighashCache::new(tx)
+                .segwit_signature_hash(index, &script_code, 10_000, EcdsaSighashType::All)
+                .unwrap();
+            let signature = EcdsaSig::from_slice(&txin.witness.to_vec()[0]).unwrap();
+
+            ctx.verify_ecdsa(
+                &Message::from_slice(&sighash[..]).unwrap(),
+                &signature.sig,
+                &tweaked_pubkey.inner,
+            )
+            .expect("signature must be valid");
+        }
+    }
+
+    #[tokio::test]
+    async fn test_transfers_send_change_to_fresh_keys() {
+        let ctx = Secp256k1::new();
+        let owner = test_key(1);
+        let recipient = test_key(3).public_key(&ctx).inner;
+
+        let mut wallet = memory_wallet(owner).unwrap();
+        wallet.enable_change_keychain().await.unwrap();
+
+        let chroma = Chroma::from(recipient.x_only_public_key().0);
+
+        let mut change_outpoints = Vec::new();
+        let mut change_keys = Vec::new();
+        for amount in [1000, 900] {
+            let outpoint = put_pixel_output(&wallet, Pixel::new(amount, chroma), &owner)
+                .await
+                .unwrap();
+
+            let mut builder = wallet.build_transfer().unwrap();
+            builder
+                .add_pixel_input(outpoint)
+                .add_recipient(chroma, &recipient, 400, 1000);
+            builder.0.fill_missing_amount(chroma).await.unwrap();
+
+            let yuv_tx = builder
+                .0
+                .build_tx(BdkFeeRate::from_sat_per_vb(1.0), 0)
+                .await
+                .unwrap();
+
+            // Find the key the change is sent to among the wallet's ones.
+            let change = Pixel::new(amount - 400, chroma);
+            let (vout, change_key) = yuv_tx
+                .bitcoin_tx
+                .output
+                .iter()
+                .enumerate()
+                .find_map(|(vout, output)| {
+                    wallet
+                        .all_signer_keys()
+                        .unwrap()
+                        .into_iter()
+                        .find_map(|key| {
+                            let pixel_key =
+                                PixelKey::new(change, &key.public_key(&ctx).inner).unwrap();
+
+                            (pixel_key.to_p2wpkh().as_ref() == Some(&output.script_pubkey))
+                                .then_some((vout, key))
+                        })
+                })
+                .expect("change must be sent to the wallet's key");
+
+            change_outpoints.push(OutPoint::new(yuv_tx.bitcoin_tx.txid(), vout as u32));
+            change_keys.push(change_key);
+
+            wallet.yuv_txs_storage.put_yuv_tx(yuv_tx).await.unwrap();
+        }
+
+        assert_ne!(change_keys[0], change_keys[1], "change keys must be fresh");
+        assert!(
+            !change_keys.contains(&owner),
+            "change must not go to the primary key"
+        );
+
+        // Both change outputs are spent by the wallet.
+        let mut builder = wallet.build_transfer().unwrap();
+        for outpoint in &change_outpoints {
+            builder.add_pixel_input(*outpoint);
+        }
+        builder.add_recipient(chroma, &recipient, 1100, 1000);
+
+        let yuv_tx = builder
+            .0
+            .build_tx(BdkFeeRate::from_sat_per_vb(1.0), 0)
+            .await
+            .unwrap();
+
+        let YuvTxType::Transfer { input_proofs, .. } = &yuv_tx.tx_type else {
+            panic!("transaction must be a transfer");
+        };
+
+        for (index, outpoint) in change_outpoints.iter().enumerate() {
+            let txin = &yuv_tx.bitcoin_tx.input[index];
+            assert_eq!(txin.previous_output, *outpoint);
+
+            input_proofs
+                .get(&(index as u32))
+                .expect("proof must exist")
+                .checked_check_by_input(txin)
+                .expect("input must be signed by the change key");
+        }
+    }
+
+    #[tokio::test]
+    async fn test_change_keychain_continues_from_stored_index() {
+        let ctx = Secp256k1::new();
+        let owner = test_key(1);
+        let recipient = test_key(3).public_key(&ctx).inner;
+        let chroma = Chroma::from(recipient.x_only_public_key().0);
+
+        let storage = LevelDB::in_memory().unwrap();
+        let mut wallet = memory_wallet_with_database(
+            owner,
+            storage.clone(),
+            DatabaseWrapper::new(MemoryDatabase::default()),
+        )
+        .unwrap();
+        wallet.enable_change_keychain().await.unwrap();
+
+        let outpoint = put_pixel_output(&wallet, Pixel::new(1000, chroma), &owner)
+            .await
+            .unwrap();
+
+        let mut builder = wallet.build_transfer().unwrap();
+        builder
+            .add_pixel_input(outpoint)
+            .add_recipient(chroma, &recipient, 400, 1000);
+        builder.0.fill_missing_amount(chroma).await.unwrap();
+        let change_key = builder.0.change_key.expect("change key must be given");
+
+        // The next run of the wallet over the same storage.
+        let mut wallet = memory_wallet_with_database(
+            owner,
+            storage,
+            DatabaseWrapper::new(MemoryDatabase::default()),
+        )
+        .unwrap();
+        wallet.enable_change_keychain().await.unwrap();
+
+        let keychain = wallet.change_keychain().unwrap();
+        assert_eq!(keychain.next_index().unwrap(), 1);
+        assert!(
+            wallet.all_signer_keys().unwrap().contains(&change_key),
+            "given change key must be tracked"
+        );
+        assert_ne!(
+            keychain.next_key(&ctx).unwrap(),
+            change_key,
+            "change key must not be given again"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_transfer_sends_change_to_set_recipient() {
+        let ctx = Secp256k1::new();
+        let owner = test_key(1);
+        let recipient = test_key(3).public_key(&ctx).inner;
+        let cold_wallet = test_key(4).public_key(&ctx).inner;
+
+        let wallet = memory_wallet(owner).unwrap();
+
+        let chroma = Chroma::from(recipient.x_only_public_key().0);
+        let outpoint = put_pixel_output(&wallet, Pixel::new(1000, chroma), &owner)
+            .await
+            .unwrap();
+
+        let mut builder = wallet.build_transfer().unwrap();
+        builder
+            .add_pixel_input(outpoint)
+            .add_recipient(chroma, &recipient, 400, 1000)
+            .set_change_recipient(&cold_wallet);
+        builder.0.fill_missing_amount(chroma).await.unwrap();
+
+        let yuv_tx = builder
+            .0
+            .build_tx(BdkFeeRate::from_sat_per_vb(1.0), 0)
+            .await
+            .unwrap();
+
+        let YuvTxType::Transfer { output_proofs, .. } = &yuv_tx.tx_type else {
+            panic!("transaction must be a transfer");
+        };
+
+        let change = Pixel::new(600, chroma);
+        let change_script = PixelKey::new(change, &cold_wallet)
+            .unwrap()
+            .to_p2wpkh()
+            .unwrap();
+
+        let (vout, _) = yuv_tx
+            .bitcoin_tx
+            .output
+            .iter()
+            .enumerate()
+            .find(|(_, output)| output.script_pubkey == change_script)
+            .expect("change must be sent to the set recipient");
+
+        let proof = output_proofs.get(&(vout as u32)).expect("proof must exist");
+        assert_eq!(proof.pixel(), change);
+    }
+
+    #[tokio::test]
+    async fn test_dust_change_is_folded_into_recipient() {
+        let ctx = Secp256k1::new();
+        let owner = test_key(1);
+        let recipient = test_key(3).public_key(&ctx).inner;
+
+        let wallet = memory_wallet(owner).unwrap();
+
+        let chroma = Chroma::from(recipient.x_only_public_key().0);
+        let outpoint = put_pixel_output(&wallet, Pixel::new(1000, chroma), &owner)
+            .await
+            .unwrap();
+
+        let mut builder = wallet.build_transfer().unwrap();
+        builder
+            .add_pixel_input(outpoint)
+            .add_recipient(chroma, &recipient, 400, 1000)
+            .set_change_satoshis(DEFAULT_DUST_THRESHOLD - 1);
+        builder.0.fill_missing_amount(chroma).await.unwrap();
+
+        let amounts = builder
+            .0
+            .outputs
+            .iter()
+            .map(BuilderOutput::amount)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            amounts,
+            vec![1000],
+            "residual amount must be given to the recipient"
+        );
+
+        let yuv_tx = builder
+            .0
+            .build_tx(BdkFeeRate::from_sat_per_vb(1.0), 0)
+            .await
+            .unwrap();
+
+        let YuvTxType::Transfer { output_proofs, .. } = &yuv_tx.tx_type else {
+            panic!("transaction must be a transfer");
+        };
+        let output_amount = output_proofs
+            .values()
+            .filter(|proof| !proof.is_empty_pixelproof())
+            .map(|proof| proof.pixel().luma.amount)
+            .sum::<u128>();
+        assert_eq!(output_amount, 1000, "inputs and outputs must be balanced");
+    }
+
+    #[tokio::test]
+    async fn test_dust_change_without_recipient_is_raised_to_threshold() {
+        let ctx = Secp256k1::new();
+        let owner = test_key(1);
+        let chroma = Chroma::from(test_key(3).public_key(&ctx).inner.x_only_public_key().0);
+
+        let wallet = memory_wallet(owner).unwrap();
+
+        let mut builder = wallet.build_transfer().unwrap();
+        builder.set_change_satoshis(DEFAULT_DUST_THRESHOLD - 1);
+        builder.0.add_change_output(chroma, 600).await.unwrap();
+
+        let [BuilderOutput::Pixel {
+            satoshis, amount, ..
+        }] = builder.0.outputs.as_slice()
+        else {
+            panic!("change output must be added");
+        };
+        assert_eq!(*amount, 600);
+        assert_eq!(*satoshis, DEFAULT_DUST_THRESHOLD);
+    }
+
+    #[tokio::test]
+    async fn test_insufficient_balance_error() {
+        let ctx = Secp256k1::new();
+        let owner = test_key(1);
+        let recipient = test_key(3).public_key(&ctx).inner;
+
+        let wallet = memory_wallet(owner).unwrap();
+
+        let chroma = Chroma::from(recipient.x_only_public_key().0);
+        let pixel = Pixel::new(1000, chroma);
+        let outpoint = put_pixel_output(&wallet, pixel, &owner).await.unwrap();
+        wallet.utxos.write().unwrap().insert(
+            outpoint,
+            SigPixelProof::new(pixel, owner.public_key(&ctx).inner).into(),
+        );
+
+        let mut builder = wallet.build_transfer().unwrap();
+        builder.add_recipient(chroma, &recipient, 1500, 1000);
+
+        let err = builder.0.fill_missing_amount(chroma).await.unwrap_err();
+
+        assert!(matches!(
+            TxBuilderError::from(err),
+            TxBuilderError::InsufficientBalance {
+                chroma: err_chroma,
+                needed: 1500,
+                available: 1000,
+            } if err_chroma == chroma
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_immature_coinbase_outputs_are_not_selected() {
+        const TIP_HEIGHT: u32 = 150;
+
+        let ctx = Secp256k1::new();
+        let owner = test_key(1);
+        let recipient = test_key(3).public_key(&ctx).inner;
+
+        let mut database = DatabaseWrapper::new(MemoryDatabase::default());
+        database
+            .set_sync_time(SyncTime {
+                block_time: BlockTime {
+                    height: TIP_HEIGHT,
+                    timestamp: 0,
+                },
+            })
+            .unwrap();
+        let wallet =
+            memory_wallet_with_database(owner, LevelDB::in_memory().unwrap(), database).unwrap();
+
+        let chroma = Chroma::from(recipient.x_only_public_key().0);
+
+        // The larger output would be selected first, if it was mature.
+        let mut outpoints = Vec::new();
+        for (amount, confirmations) in [(1000, COINBASE_MATURITY - 1), (600, COINBASE_MATURITY)] {
+            let height = i64::from(TIP_HEIGHT + 1 - confirmations);
+            let coinbase_input = TxIn {
+                script_sig: Builder::new().push_int(height).into_script(),
+                ..Default::default()
+            };
+
+            let pixel = Pixel::new(amount, chroma);
+            let outpoint = put_pixel_output_with_input(&wallet, pixel, &owner, coinbase_input)
+                .await
+                .unwrap();
+            wallet.utxos.write().unwrap().insert(
+                outpoint,
+                SigPixelProof::new(pixel, owner.public_key(&ctx).inner).into(),
+            );
+
+            outpoints.push(outpoint);
+        }
+
+        let mut builder = wallet.build_transfer().unwrap();
+        builder.add_recipient(chroma, &recipient, 500, 1000);
+        builder.0.fill_missing_amount(chroma).await.unwrap();
+
+        let inputs = builder
+            .0
+            .inputs
+            .iter()
+            .map(BuilderInput::outpoint)
+            .collect::<Vec<_>>();
+        assert_eq!(inputs, vec![outpoints[1]]);
+    }
+
+    #[tokio::test]
+    async fn test_missing_proof_error() {
+        let ctx = Secp256k1::new();
+        let owner = test_key(1);
+        let recipient = owner.public_key(&ctx).inner;
+
+        let wallet = memory_wallet(owner).unwrap();
+
+        let chroma = Chroma::from(recipient.x_only_public_key().0);
+        let unknown = OutPoint::new(Txid::all_zeros(), 1);
+
+        let mut builder = wallet.build_transfer().unwrap();
+        builder
+            .add_pixel_input(unknown)
+            .add_recipient(chroma, &recipient, 100, 1000);
+
+        let err = builder.0.fill_missing_amount(chroma).await.unwrap_err();
+
+        assert!(matches!(
+            TxBuilderError::from(err),
+            TxBuilderError::MissingProof(outpoint) if outpoint == unknown
+        ));
+    }
+
+    /// Build the transfer of the whole pixel output of 10 000 sat with the
+    /// absolute `fee`.
+    async fn build_transfer_with_absolute_fee(fee: u64) -> eyre::Result<YuvTransaction> {
+        let ctx = Secp256k1::new();
+        let owner = test_key(1);
+        let recipient = test_key(3).public_key(&ctx).inner;
+
+        let wallet = memory_wallet(owner)?;
+        let blockchain = wallet.bitcoin_provider.blockchain();
+
+        let chroma = Chroma::from(recipient.x_only_public_key().0);
+        let pixel = Pixel::new(500, chroma);
+        let outpoint = put_pixel_output(&wallet, pixel, &owner).await?;
+
+        let mut builder = wallet.build_transfer()?;
+        builder
+            .add_pixel_input(outpoint)
+            .add_recipient(chroma, &recipient, 500, 1000)
+            .set_fee_rate_strategy(FeeRateStrategy::AbsoluteFee { fee });
+
+        let fee_rate = builder.0.get_fee_rate(&blockchain)?;
+
+        builder.0.build_tx(fee_rate, 0).await
+    }
+
+    #[tokio::test]
+    async fn test_absolute_fee_is_paid_exactly() {
+        for fee in [500, 1500, 4321] {
+            let yuv_tx = build_transfer_with_absolute_fee(fee).await.unwrap();
+
+            let outputs_sum = yuv_tx
+                .bitcoin_tx
+                .output
+                .iter()
+                .map(|output| output.value)
+                .sum::<u64>();
+
+            assert_eq!(
+                10_000 - outputs_sum,
+                fee,
+                "transaction must pay the absolute fee"
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn test_absolute_fee_below_min_relay_error() {
+        let err = build_transfer_with_absolute_fee(10).await.unwrap_err();
+
+        assert!(matches!(
+            TxBuilderError::from(err),
+            TxBuilderError::FeeBelowMinRelay { fee: 10, min_fee } if min_fee > 10
+        ));
+    }
+
+    /// Blockchain that knows only the given transactions.
+    struct TxsBlockchain(HashMap<Txid, Transaction>);
+
+    impl GetTx for TxsBlockchain {
+        fn get_tx(&self, txid: &Txid) -> Result<Option<Transaction>, bdk::Error> {
+            Ok(self.0.get(txid).cloned())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_cpfp_child_pays_parent_deficit() {
+        const PARENT_FEE: u64 = 100;
+
+        let ctx = Secp256k1::new();
+        let owner = test_key(1);
+        let recipient = test_key(3).public_key(&ctx).inner;
+
+        let wallet = memory_wallet(owner).unwrap();
+
+        // The parent spends the output of 10 100 sat and pays only 100 sat.
+        let funding = Transaction {
+            version: 2,
+            lock_time: PackedLockTime::ZERO,
+            input: vec![TxIn::default()],
+            output: vec![TxOut {
+                value: 10_000 + PARENT_FEE,
+                script_pubkey: Script::new(),
+            }],
+        };
+        let parent_input = TxIn {
+            previous_output: OutPoint::new(funding.txid(), 0),
+            ..Default::default()
+        };
+
+        let chroma = Chroma::from(recipient.x_only_public_key().0);
+        let pixel = Pixel::new(500, chroma);
+        let outpoint = put_pixel_output_with_input(&wallet, pixel, &owner, parent_input)
+            .await
+            .unwrap();
+        wallet.utxos.write().unwrap().insert(
+            outpoint,
+            SigPixelProof::new(pixel, owner.public_key(&ctx).inner).into(),
+        );
+
+        let parent = wallet
+            .yuv_txs_storage
+            .get_yuv_tx(&outpoint.txid)
+            .await
+            .unwrap()
+            .unwrap()
+            .bitcoin_tx;
+        let blockchain = TxsBlockchain(HashMap::from([
+            (funding.txid(), funding),
+            (parent.txid(), parent.clone()),
+        ]));
+
+        let mut builder = wallet.build_transfer().unwrap();
+        builder
+            .add_cpfp_parent(outpoint.txid)
+            .await
+            .unwrap()
+            .add_recipient(chroma, &recipient, 500, 1000);
+
+        let fee_rate = BdkFeeRate::from_sat_per_vb(10.0);
+        let cpfp_fee = builder.0.cpfp_fee(&blockchain, fee_rate).unwrap();
+        assert_eq!(
+            cpfp_fee,
+            fee_rate.fee_wu(parent.weight()) - PARENT_FEE,
+            "child must pay what the parent lacks to the fee rate"
+        );
+
+        let child = builder
+            .0
+            .build_tx(fee_rate, cpfp_fee)
+            .await
+            .unwrap()
+            .bitcoin_tx;
+        assert_eq!(child.input[0].previous_output, outpoint);
+
+        let outputs_sum = child.output.iter().map(|output| output.value).sum::<u64>();
+        let child_fee = 10_000 - outputs_sum;
+        assert!(
+            child_fee + PARENT_FEE >= fee_rate.fee_wu(parent.weight() + child.weight()),
+            "package must pay the fee rate"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_transfer_with_satoshi_inputs_is_deterministic() {
+        let ctx = Secp256k1::new();
+        let owner = test_key(1);
+        let recipient = test_key(3).public_key(&ctx).inner;
+        let (change_proof, change_script) = get_empty_pixel_proof(recipient).unwrap();
+
+        let mut database = DatabaseWrapper::new(MemoryDatabase::default());
+        let wallet =
+            memory_wallet_with_database(owner, LevelDB::in_memory().unwrap(), database.clone())
+                .unwrap();
+
+        let chroma = Chroma::from(recipient.x_only_public_key().0);
+        let pixel = Pixel::new(500, chroma);
+        let pixel_outpoint = put_pixel_output(&wallet, pixel, &owner).await.unwrap();
+
+        let satoshi_outpoints = [20_000, 30_000, 40_000]
+            .into_iter()
+            .map(|value| put_satoshi_output(&wallet, &mut database, value).unwrap())
+            .collect::<Vec<_>>();
+
+        let mut raw_txs = Vec::new();
+        for _ in 0..2 {
+            let mut builder = wallet.build_transfer().unwrap();
+            builder
+                .add_pixel_input(pixel_outpoint)
+                .add_recipient(chroma, &recipient, 500, 1000)
+                .set_satoshi_inputs(vec![satoshi_outpoints[1]], &recipient);
+
+            let yuv_tx = builder
+                .0
+                .build_tx(BdkFeeRate::from_sat_per_vb(1.0), 0)
+                .await
+                .unwrap();
+
+            let tx = &yuv_tx.bitcoin_tx;
+            let inputs = tx
+                .input
+                .iter()
+                .map(|input| input.previous_output)
+                .collect::<Vec<_>>();
+            assert_eq!(inputs, vec![pixel_outpoint, satoshi_outpoints[1]]);
+            let change_vout = tx
+                .output
+                .iter()
+                .position(|output| output.script_pubkey == change_script)
+                .expect("change must be sent to the given recipient");
+            let YuvTxType::Transfer { output_proofs, .. } = &yuv_tx.tx_type else {
+                panic!("tx must be a transfer");
+            };
+            assert_eq!(
+                output_proofs.get(&(change_vout as u32)),
+                Some(&change_proof)
+            );
+
+            raw_txs.push(bitcoin::consensus::serialize(tx));
+        }
+
+        assert_eq!(raw_txs[0], raw_txs[1], "raw transactions must be identical");
+    }
+
+    #[tokio::test]
+    async fn test_fee_is_paid_from_fee_source_only() {
+        let ctx = Secp256k1::new();
+        let owner = test_key(1);
+        let owner_pubkey = owner.public_key(&ctx).inner;
+        let recipient = test_key(3).public_key(&ctx).inner;
+
+        let mut database = DatabaseWrapper::new(MemoryDatabase::default());
+        let wallet =
+            memory_wallet_with_database(owner, LevelDB::in_memory().unwrap(), database.clone())
+                .unwrap();
+
+        let chroma = Chroma::from(recipient.x_only_public_key().0);
+        let pixel = Pixel::new(500, chroma);
+        let pixel_outpoint = put_pixel_output(&wallet, pixel, &owner).await.unwrap();
+
+        let satoshi_outpoints = [20_000, 30_000, 40_000]
+            .into_iter()
+            .map(|value| put_satoshi_output(&wallet, &mut database, value).unwrap())
+            .collect::<Vec<_>>();
+
+        let build_tx = |fee_source: Vec<OutPoint>, sats_amount: u64| {
+            let mut builder = wallet.build_transfer().unwrap();
+            builder
+                .add_pixel_input(pixel_outpoint)
+                .add_recipient(chroma, &recipient, 500, 1000)
+                .add_sats_recipient(&owner_pubkey, sats_amount)
+                .set_fee_source(fee_source);
+
+            async move {
+                builder
+                    .0
+                    .build_tx(BdkFeeRate::from_sat_per_vb(1.0), 0)
+                    .await
+            }
+        };
+
+        // Any of the fee source outputs covers the fee, so only one is spent.
+        let yuv_tx = build_tx(vec![satoshi_outpoints[0], satoshi_outpoints[2]], 15_000)
+            .await
+            .unwrap();
+        let inputs = yuv_tx
+            .bitcoin_tx
+            .input
+            .iter()
+            .map(|input| input.previous_output)
+            .collect::<Vec<_>>();
+        assert_eq!(inputs.len(), 2, "only one fee source output must be spent");
+        assert!(inputs.contains(&pixel_outpoint));
+        assert!(
+            inputs.contains(&satoshi_outpoints[0]) || inputs.contains(&satoshi_outpoints[2]),
+            "fee must be paid from the fee source"
+        );
+
+        // The other outputs of the wallet are not spent even if the fee source
+        // can't cover the fee.
+        let err = build_tx(vec![satoshi_outpoints[0]], 35_000)
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            TxBuilderError::from(err),
+            TxBuilderError::InsufficientSatoshis { .. }
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_transfer_spends_tweaked_satoshis_output() {
+        let ctx = Secp256k1::new();
+        let owner = test_key(1);
+        let owner_pubkey = owner.public_key(&ctx).inner;
+        let recipient = test_key(3).public_key(&ctx).inner;
+
+        let wallet = memory_wallet(owner).unwrap();
+
+        let chroma = Chroma::from(recipient.x_only_public_key().0);
+        let pixel = Pixel::new(500, chroma);
+        let pixel_outpoint = put_pixel_output(&wallet, pixel, &owner).await.unwrap();
+
+        // Transfer the tokens, and keep the satoshis tweaked by the empty pixel.
+        let mut builder = wallet.build_transfer().unwrap();
+        builder
+            .add_pixel_input(pixel_outpoint)
+            .add_recipient(chroma, &recipient, 500, 1000)
+            .add_sats_recipient(&owner_pubkey, 5000);
+
+        let yuv_tx = builder
+            .0
+            .build_tx(BdkFeeRate::from_sat_per_vb(1.0), 0)
+            .await
+            .unwrap();
+
+        let YuvTxType::Transfer { output_proofs, .. } = &yuv_tx.tx_type else {
+            panic!("transaction must be a transfer");
+        };
+
+        let (vout, proof) = output_proofs
+            .iter()
+            .find(|(_, proof)| proof.is_empty_pixelproof())
+            .expect("satoshis output must have the empty pixel proof");
+        let tweaked_output = &yuv_tx.bitcoin_tx.output[*vout as usize];
+
+        assert_eq!(tweaked_output.value, 5000);
+        assert_eq!(proof.pixel(), Pixel::empty());
+        proof
+            .checked_check_by_output(tweaked_output)
+            .expect("output must be locked to the key tweaked by the empty pixel");
+
+        let tweaked_outpoint = OutPoint::new(yuv_tx.bitcoin_tx.txid(), *vout);
+        wallet.yuv_txs_storage.put_yuv_tx(yuv_tx).await.unwrap();
+
+        // Spend the tweaked satoshis back to the owner.
+        let mut builder = wallet.build_transfer().unwrap();
+        builder
+            .add_tweaked_satoshis_input(tweaked_outpoint)
+            .add_sats_recipient(&owner_pubkey, 4000);
+
+        let yuv_tx = builder
+            .0
+            .build_tx(BdkFeeRate::from_sat_per_vb(1.0), 0)
+            .await
+            .unwrap();
+
+        let YuvTxType::Transfer { input_proofs, .. } = &yuv_tx.tx_type else {
+            panic!("transaction must be a transfer");
+        };
+
+        let txin = &yuv_tx.bitcoin_tx.input[0];
+        assert_eq!(txin.previous_output, tweaked_outpoint);
+
+        let input_proof = input_proofs.get(&0).expect("proof must exist");
+        assert!(input_proof.is_empty_pixelproof());
+        input_proof
+            .checked_check_by_input(txin)
+            .expect("input must be signed by the owner's key tweaked by the empty pixel");
+
+        // Outputs with tokens can't be spent as tweaked satoshis.
+        let mut builder = wallet.build_transfer().unwrap();
+        builder.add_tweaked_satoshis_input(pixel_outpoint);
+
+        assert!(builder
+            .0
+            .build_tx(BdkFeeRate::from_sat_per_vb(1.0), 0)
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn test_transfer_spends_timelocked_input() {
+        const LOCKTIME: u32 = 500;
+
+        let ctx = Secp256k1::new();
+        let owner = test_key(1);
+        let recipient = test_key(3).public_key(&ctx).inner;
+
+        let wallet = memory_wallet(owner).unwrap();
+
+        let chroma = Chroma::from(recipient.x_only_public_key().0);
+        let timelock_proof = TimelockPixelProof::new(
+            Pixel::new(500, chroma),
+            owner.public_key(&ctx).inner,
+            LOCKTIME,
+        );
+
+        let bitcoin_tx = Transaction {
+            version: 2,
+            lock_time: PackedLockTime::ZERO,
+            input: vec![TxIn::default()],
+            output: vec![TxOut {
+                value: 10_000,
+                script_pubkey: timelock_proof.to_script_pubkey().unwrap(),
+            }],
+        };
+        let outpoint = OutPoint::new(bitcoin_tx.txid(), 0);
+
+        wallet
+            .yuv_txs_storage
+            .put_yuv_tx(YuvTransaction {
+                bitcoin_tx,
+                tx_type: YuvTxType::Issue {
+                    output_proofs: Some(ProofMap::from([(0, timelock_proof.clone().into())])),
+                    announcement: IssueAnnouncement::new(chroma, 500),
+                },
+            })
+            .await
+            .unwrap();
+
+        let mut builder = wallet.build_transfer().unwrap();
+        builder.add_timelocked_pixel_input(outpoint);
+        builder.add_timelocked_recipient(chroma, &recipient, 500, 1000, LOCKTIME + 100);
+
+        let yuv_tx = builder
+            .0
+            .build_tx(BdkFeeRate::from_sat_per_vb(1.0), 0)
+            .await
+            .unwrap();
+
+        let YuvTxType::Transfer {
+            input_proofs,
+            output_proofs,
+        } = &yuv_tx.tx_type
+        else {
+            panic!("transaction must be a transfer");
+        };
+
+        // The transaction can't be mined before the locktime of the input.
+        let tx = &yuv_tx.bitcoin_tx;
+        assert_eq!(tx.lock_time.0, LOCKTIME);
+
+        let txin = &tx.input[0];
+        assert_eq!(txin.previous_output, outpoint);
+        assert!(txin.sequence.enables_absolute_lock_time());
+
+        input_proofs
+            .get(&0)
+            .expect("proof must exist")
+            .checked_check_by_input(txin)
+            .expect("input must reveal the redeem script");
+
+        // The signature must be valid for the tweaked key of the owner.
+        let tweaked_key =
+            PixelPrivateKey::new_with_ctx(timelock_proof.pixel, &owner.inner, &ctx).unwrap();
+        let script_code = timelock_proof.to_redeem_script().unwrap();
+
+        let sighash = SighashCache::new(tx)
+            .segwit_signature_hash(0, &script_code, 10_000, EcdsaSighashType::All)
+            .unwrap();
+        let signature = EcdsaSig::from_slice(&txin.witness.to_vec()[0]).unwrap();
+
+        ctx.verify_ecdsa(
+            &Message::from_slice(&sighash[..]).unwrap(),
+            &signature.sig,
+            &tweaked_key.0.public_key(&ctx),
+        )
+        .expect("signature must be valid");
+
+        // The output to the recipient is locked with the new locktime.
+        let output_proof = output_proofs.get(&0).expect("proof must exist");
+        assert!(
+            matches!(output_proof, PixelProof::Timelock(proof) if proof.locktime == LOCKTIME + 100)
+        );
+        output_proof
+            .checked_check_by_output(&tx.output[0])
+            .expect("output must be locked by the proof");
+    }
 }
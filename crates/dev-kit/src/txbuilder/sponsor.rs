@@ -0,0 +1,472 @@
+//! Transfers which satoshis (for the outputs and the fee) are supplied by the
+//! sponsor's wallet, so the owner of the pixel inputs doesn't need satoshis.
+//!
+//! The transfer is built by the owner and the sponsor together:
+//!
+//! 1. The owner selects the pixel inputs and the outputs of the transfer and
+//!    creates [`SponsorshipRequest`] with [`TransferTransactionBuilder::finish_sponsored`].
+//! 2. The sponsor adds the inputs and the change output of its own wallet to
+//!    pay the fee, signs only these inputs and returns [`SponsoredTransfer`]
+//!    with [`SponsorTransactionBuilder::finish`].
+//! 3. The owner checks that the inputs and outputs of the request are left
+//!    untouched, and signs the pixel inputs with
+//!    [`Wallet::sign_sponsored_transfer`](crate::Wallet::sign_sponsored_transfer).
+//!
+//! Both parties sign with `SIGHASH_ALL`, so the transaction can't be changed
+//! after the sponsor signs it.
+
+use std::collections::HashMap;
+
+use bdk::{
+    blockchain::Blockchain, wallet::tx_builder::TxOrdering, FeeRate as BdkFeeRate, SignOptions,
+};
+use bitcoin::{
+    psbt::{self, PartiallySignedTransaction},
+    secp256k1::Secp256k1,
+    OutPoint, TxOut,
+};
+use eyre::{bail, ensure};
+use yuv_pixels::PixelProof;
+use yuv_storage::TransactionsStorage as YuvTransactionsStorage;
+use yuv_types::{YuvTransaction, YuvTxType};
+
+use super::{
+    form_tx_type, inputs_locktime, BuilderInput, TransactionBuilder, TransferTransactionBuilder,
+};
+use crate::{
+    bitcoin_provider::BitcoinProvider, sync::storage::ChangeKeychainStorage,
+    types::FeeRateStrategy, Wallet,
+};
+
+/// Pixel inputs and outputs of the transfer, which satoshis are supplied by
+/// the sponsor.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct SponsorshipRequest {
+    /// Pixel inputs of the owner.
+    pub inputs: Vec<SponsoredInput>,
+    /// Outputs of the transfer with their proofs, which go first in the
+    /// transaction in the same order.
+    pub outputs: Vec<(TxOut, PixelProof)>,
+}
+
+/// Pixel input of the owner in the [`SponsorshipRequest`].
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct SponsoredInput {
+    pub outpoint: OutPoint,
+    /// PSBT input with the spent output, which is signed by the owner.
+    pub psbt_input: psbt::Input,
+    /// Max weight of the witness that satisfies the input, used by the sponsor
+    /// to estimate the fee.
+    pub satisfaction_weight: usize,
+    pub proof: PixelProof,
+}
+
+/// Transfer with the sponsor's inputs signed, which pixel inputs are left to
+/// be signed by the owner.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct SponsoredTransfer {
+    pub psbt: PartiallySignedTransaction,
+    pub tx_type: YuvTxType,
+}
+
+impl SponsorshipRequest {
+    /// Check that the sponsored transfer spends the inputs and has the outputs
+    /// of the request with the same proofs, and every output added by the
+    /// sponsor has the empty pixel proof, so the tokens aren't burnt.
+    pub fn verify(&self, sponsored: &SponsoredTransfer) -> eyre::Result<()> {
+        let YuvTxType::Transfer {
+            input_proofs,
+            output_proofs,
+        } = &sponsored.tx_type
+        else {
+            bail!("Sponsored transaction is not a transfer");
+        };
+
+        let tx = &sponsored.psbt.unsigned_tx;
+
+        ensure!(
+            input_proofs.len() == self.inputs.len(),
+            "Sponsored transfer has {} pixel inputs instead of {}",
+            input_proofs.len(),
+            self.inputs.len()
+        );
+
+        for input in &self.inputs {
+            let index = tx
+                .input
+                .iter()
+                .position(|txin| txin.previous_output == input.outpoint);
+
+            let Some(index) = index else {
+                bail!("Sponsored transfer doesn't spend input {}", input.outpoint);
+            };
+
+            ensure!(
+                input_proofs.get(&(index as u32)) == Some(&input.proof),
+                "Proof of input {} is changed by the sponsor",
+                input.outpoint
+            );
+        }
+
+        for (index, (txout, proof)) in self.outputs.iter().enumerate() {
+            ensure!(
+                tx.output.get(index) == Some(txout),
+                "Output {} is changed by the sponsor",
+                index
+            );
+            ensure!(
+                output_proofs.get(&(index as u32)) == Some(proof),
+                "Proof of output {} is changed by the sponsor",
+                index
+            );
+        }
+
+        ensure!(
+            output_proofs.len() == tx.output.len(),
+            "Sponsored transfer has {} output proofs for {} outputs",
+            output_proofs.len(),
+            tx.output.len()
+        );
+
+        for index in self.outputs.len()..tx.output.len() {
+            ensure!(
+                matches!(
+                    output_proofs.get(&(index as u32)),
+                    Some(PixelProof::EmptyPixel(_))
+                ),
+                "Output {} added by the sponsor has no empty pixel proof",
+                index
+            );
+        }
+
+        Ok(())
+    }
+}
+
+impl<YTDB, BDB> TransferTransactionBuilder<YTDB, BDB>
+where
+    YTDB: YuvTransactionsStorage + ChangeKeychainStorage + Clone + Send + Sync + 'static,
+    BDB: bdk::database::BatchDatabase + Clone + Send,
+{
+    /// Finish transfer building without the satoshis to pay the fee, which are
+    /// supplied by the sponsor, and create the request to it.
+    ///
+    /// The wallet's satoshis are not spent, so the satoshis of the outputs are
+    /// covered by the ones of the pixel inputs and the sponsor's inputs.
+    pub async fn finish_sponsored(self) -> eyre::Result<SponsorshipRequest> {
+        self.0.finish_sponsored().await
+    }
+}
+
+pub struct SponsorTransactionBuilder<YuvTxsDatabase, BitcoinTxsDatabase>(
+    TransactionBuilder<YuvTxsDatabase, BitcoinTxsDatabase>,
+);
+
+impl<YTDB, BDB, YC, BP> TryFrom<&Wallet<YC, YTDB, BP, BDB>> for SponsorTransactionBuilder<YTDB, BDB>
+where
+    YTDB: YuvTransactionsStorage + ChangeKeychainStorage + Clone + Send + Sync + 'static,
+    BDB: bdk::database::BatchDatabase + Clone + Send,
+    BP: BitcoinProvider,
+{
+    type Error = eyre::Error;
+
+    fn try_from(wallet: &Wallet<YC, YTDB, BP, BDB>) -> Result<Self, Self::Error> {
+        Ok(Self(TransactionBuilder::new(false, wallet)?))
+    }
+}
+
+impl<YTDB, BDB> SponsorTransactionBuilder<YTDB, BDB>
+where
+    YTDB: YuvTransactionsStorage + ChangeKeychainStorage + Clone + Send + Sync + 'static,
+    BDB: bdk::database::BatchDatabase + Clone + Send,
+{
+    /// Override the fee rate strategy.
+    pub fn set_fee_rate_strategy(&mut self, fee_rate_strategy: FeeRateStrategy) -> &mut Self {
+        self.0.set_fee_rate_strategy(fee_rate_strategy);
+
+        self
+    }
+
+    /// Override the minimal fee rate in sat/vb, which applies to all the fee
+    /// rate strategies.
+    pub fn set_min_fee_rate(&mut self, min_fee_rate: f32) -> &mut Self {
+        self.0.set_min_fee_rate(min_fee_rate);
+
+        self
+    }
+
+    /// Finish building of the sponsored transfer: add the wallet's inputs and
+    /// change output to pay the fee of the `request`, and sign the wallet's
+    /// inputs.
+    pub async fn finish(
+        self,
+        request: &SponsorshipRequest,
+        blockchain: &impl Blockchain,
+    ) -> eyre::Result<SponsoredTransfer> {
+        ensure!(
+            self.0.fee_rate_strategy.absolute_fee().is_none(),
+            "absolute fee is not supported for sponsored transfers"
+        );
+
+        let fee_rate = self.0.get_fee_rate(blockchain)?;
+
+        self.0.build_sponsored(request, fee_rate)
+    }
+}
+
+impl<YTDB, BDB> TransactionBuilder<YTDB, BDB>
+where
+    YTDB: YuvTransactionsStorage + ChangeKeychainStorage + Clone + Send + Sync + 'static,
+    BDB: bdk::database::BatchDatabase + Clone + Send,
+{
+    async fn finish_sponsored(mut self) -> eyre::Result<SponsorshipRequest> {
+        ensure!(!self.is_issuance, "only transfer could be sponsored");
+        #[cfg(feature = "bulletproof")]
+        ensure!(
+            self.bulletproof_outputs.is_empty(),
+            "bulletproof transfer could not be sponsored"
+        );
+
+        if !self.is_inputs_selected {
+            if self.should_drain_tweaked_satoshis {
+                self.add_tweaked_satoshi_inputs();
+            }
+            for chroma in &self.chromas.clone() {
+                self.fill_missing_amount(*chroma).await?;
+            }
+        }
+
+        // The second key of the multisig input isn't kept by the wallet, so
+        // the owner couldn't sign it after the sponsor.
+        if self
+            .inputs
+            .iter()
+            .any(|input| matches!(input, BuilderInput::Multisig2x2 { .. }))
+        {
+            bail!("Transfer with multisig inputs could not be sponsored");
+        }
+
+        let ctx = Secp256k1::new();
+
+        let mut input_proofs = HashMap::new();
+        let mut inputs = Vec::new();
+
+        self.process_inputs(&ctx, &mut input_proofs, &mut inputs)
+            .await?;
+
+        let mut output_proofs = Vec::new();
+        let mut outputs = Vec::new();
+
+        for output in &self.outputs {
+            self.process_output(output, &mut output_proofs, &mut outputs)?;
+        }
+
+        let inputs = inputs
+            .into_iter()
+            .map(
+                |(outpoint, psbt_input, satisfaction_weight)| SponsoredInput {
+                    outpoint,
+                    psbt_input,
+                    satisfaction_weight,
+                    proof: input_proofs[&outpoint].clone(),
+                },
+            )
+            .collect();
+
+        let outputs = outputs
+            .into_iter()
+            .zip(output_proofs)
+            .map(|((script_pubkey, value), proof)| {
+                (
+                    TxOut {
+                        value,
+                        script_pubkey,
+                    },
+                    proof,
+                )
+            })
+            .collect();
+
+        Ok(SponsorshipRequest { inputs, outputs })
+    }
+
+    fn build_sponsored(
+        &self,
+        request: &SponsorshipRequest,
+        fee_rate: BdkFeeRate,
+    ) -> eyre::Result<SponsoredTransfer> {
+        let input_proofs = request
+            .inputs
+            .iter()
+            .map(|input| (input.outpoint, input.proof.clone()))
+            .collect::<HashMap<_, _>>();
+
+        let locktime = inputs_locktime(input_proofs.values())?;
+
+        let bitcoin_wallet = self.inner_wallet.read().unwrap();
+        let mut tx_builder = bitcoin_wallet.build_tx();
+
+        // Do not sort inputs and outputs to keep the proofs of the request valid.
+        tx_builder.ordering(TxOrdering::Untouched);
+        tx_builder.only_witness_utxo();
+        tx_builder.fee_rate(fee_rate);
+
+        if let Some(locktime) = locktime {
+            tx_builder.nlocktime(locktime);
+        }
+
+        for (txout, _proof) in &request.outputs {
+            tx_builder.add_recipient(txout.script_pubkey.clone(), txout.value);
+        }
+        for input in &request.inputs {
+            tx_builder.add_foreign_utxo(
+                input.outpoint,
+                input.psbt_input.clone(),
+                input.satisfaction_weight,
+            )?;
+        }
+
+        let (mut psbt, _details) = tx_builder.finish()?;
+
+        let mut output_proofs = request
+            .outputs
+            .iter()
+            .map(|(_txout, proof)| proof.clone())
+            .collect();
+
+        // The sponsor's change is put after the outputs of the request.
+        self.insert_empty_pixelproofs(&mut output_proofs, &mut psbt.unsigned_tx.output)?;
+
+        let tx_type = form_tx_type(&psbt.unsigned_tx, &input_proofs, &output_proofs, false)?;
+
+        // Sign only the sponsor's inputs, the pixel inputs are signed by the owner.
+        bitcoin_wallet.sign(
+            &mut psbt,
+            SignOptions {
+                try_finalize: true,
+                trust_witness_utxo: true,
+                ..Default::default()
+            },
+        )?;
+
+        Ok(SponsoredTransfer { psbt, tx_type })
+    }
+}
+
+impl SponsoredTransfer {
+    /// Extract the signed transaction with its proofs.
+    pub(crate) fn into_yuv_tx(self) -> YuvTransaction {
+        YuvTransaction {
+            bitcoin_tx: self.psbt.extract_tx(),
+            tx_type: self.tx_type,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use yuv_pixels::{CheckableProof, Chroma, Pixel, ToEvenPublicKey};
+
+    use super::*;
+    use crate::txbuilder::tests::{memory_wallet, put_pixel_output, test_key};
+
+    #[tokio::test]
+    async fn test_sponsored_transfer() {
+        let ctx = Secp256k1::new();
+        let owner = test_key(1);
+        let sponsor = test_key(2);
+        let recipient = test_key(3).public_key(&ctx).inner;
+
+        let owner_wallet = memory_wallet(owner).unwrap();
+        let sponsor_wallet = memory_wallet(sponsor).unwrap();
+
+        let chroma = Chroma::from(recipient.x_only_public_key().0);
+        let outpoint = put_pixel_output(&owner_wallet, Pixel::new(500, chroma), &owner)
+            .await
+            .unwrap();
+
+        let mut builder = owner_wallet.build_transfer().unwrap();
+        builder.add_pixel_input(outpoint);
+        builder.add_recipient(chroma, &recipient, 500, 1000);
+
+        let request = builder.finish_sponsored().await.unwrap();
+
+        // The request and the response are passed between the parties serialized.
+        let request: SponsorshipRequest =
+            serde_json::from_str(&serde_json::to_string(&request).unwrap()).unwrap();
+
+        let sponsored = sponsor_wallet
+            .build_sponsorship()
+            .unwrap()
+            .0
+            .build_sponsored(&request, BdkFeeRate::from_sat_per_vb(1.0))
+            .unwrap();
+        let sponsored: SponsoredTransfer =
+            serde_json::from_str(&serde_json::to_string(&sponsored).unwrap()).unwrap();
+
+        // The owner doesn't sign the transfer with the outputs changed by the sponsor.
+        let mut tampered = sponsored.clone();
+        tampered.psbt.unsigned_tx.output[0].value += 1;
+        assert!(owner_wallet
+            .sign_sponsored_transfer(&request, tampered)
+            .is_err());
+
+        // Nor the one with the proof of the sponsor's change replaced or removed.
+        let with_change_proof = |proof: Option<PixelProof>| {
+            let mut tampered = sponsored.clone();
+            if let YuvTxType::Transfer { output_proofs, .. } = &mut tampered.tx_type {
+                let change_index = *output_proofs.keys().last().unwrap();
+                match proof {
+                    Some(proof) => output_proofs.insert(change_index, proof),
+                    None => output_proofs.remove(&change_index),
+                };
+            }
+            tampered
+        };
+
+        let burning_proof =
+            PixelProof::sig(Pixel::new(500, chroma), sponsor.public_key(&ctx).inner);
+        assert!(request
+            .verify(&with_change_proof(Some(burning_proof)))
+            .is_err());
+        assert!(request.verify(&with_change_proof(None)).is_err());
+        request
+            .verify(&sponsored)
+            .expect("untouched transfer must be verified");
+
+        let yuv_tx = owner_wallet
+            .sign_sponsored_transfer(&request, sponsored)
+            .unwrap();
+
+        let YuvTxType::Transfer {
+            input_proofs,
+            output_proofs,
+        } = &yuv_tx.tx_type
+        else {
+            panic!("transaction must be a transfer");
+        };
+
+        let tx = &yuv_tx.bitcoin_tx;
+        assert_eq!(tx.input[0].previous_output, outpoint);
+        input_proofs
+            .get(&0)
+            .expect("proof must exist")
+            .checked_check_by_input(&tx.input[0])
+            .expect("input must be signed by the owner");
+
+        for (index, output) in tx.output.iter().enumerate() {
+            output_proofs
+                .get(&(index as u32))
+                .expect("every output must have a proof")
+                .checked_check_by_output(output)
+                .expect("output must be locked by the proof");
+        }
+
+        // The change of the sponsor is put after the outputs of the request.
+        let sponsor_pubkey = sponsor.public_key(&ctx).even_public_key(&ctx).inner;
+        assert!(matches!(
+            output_proofs.values().last(),
+            Some(PixelProof::EmptyPixel(proof)) if proof.inner_key == sponsor_pubkey
+        ));
+    }
+}
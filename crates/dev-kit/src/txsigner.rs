@@ -6,6 +6,7 @@ use bdk::{
     SignOptions,
 };
 use bitcoin::{
+    blockdata::script::Builder,
     psbt::PartiallySignedTransaction,
     secp256k1::{self, All, Secp256k1},
     PrivateKey, Script, XOnlyPublicKey,
@@ -13,7 +14,7 @@ use bitcoin::{
 use eyre::bail;
 use yuv_pixels::{
     LightningCommitmentProof, LightningCommitmentWitness, MultisigPixelProof, MultisigWintessData,
-    P2WPKHWintessData, Pixel, PixelPrivateKey, PixelProof,
+    P2WPKHWintessData, Pixel, PixelPrivateKey, PixelProof, TimelockPixelProof, TimelockWitnessData,
 };
 use yuv_types::ProofMap;
 
@@ -42,7 +43,7 @@ impl TransactionSigner {
     }
 
     pub fn sign(
-        self,
+        &self,
         psbt: &mut PartiallySignedTransaction,
         input_proofs: &ProofMap,
     ) -> Result<(), eyre::ErrReport> {
@@ -70,6 +71,12 @@ impl TransactionSigner {
                 PixelProof::EmptyPixel(proof) => {
                     self.sign_input(Pixel::empty(), &proof.inner_key, psbt, *index)?;
                 }
+                PixelProof::Timelock(proof) => {
+                    self.sign_timelock_input(proof, psbt, *index)?;
+                }
+                PixelProof::GenericScript(_script_proof) => {
+                    bail!("Inputs with custom witness script can't be signed by the wallet")
+                }
             };
         }
 
@@ -187,6 +194,58 @@ impl TransactionSigner {
         Ok(())
     }
 
+    /// Add witness (signature, redeem script) for the timelocked pixel P2WSH
+    /// input, signed by the key tweaked with the pixel.
+    fn sign_timelock_input(
+        &self,
+        proof: &TimelockPixelProof,
+        psbt: &mut PartiallySignedTransaction,
+        index: u32,
+    ) -> eyre::Result<()> {
+        let Some(signing_key) = self.signers.get(&XOnlyPublicKey::from(proof.inner_key)) else {
+            bail!(
+                "Signing key for timelocked pixel input {} is not found",
+                index
+            );
+        };
+
+        let tweaked_key = PixelPrivateKey::new_with_ctx(proof.pixel, signing_key, &self.ctx)?;
+        let tweaked_pubkey = tweaked_key.0.public_key(&self.ctx).to_public_key();
+
+        let signer = SignerWrapper::new(
+            PrivateKey::new(tweaked_key.0, self.private_key.network),
+            SignerContext::Segwitv0,
+        );
+
+        signer.sign_input(
+            psbt,
+            index as usize,
+            &SignOptions {
+                try_finalize: false,
+                trust_witness_utxo: true,
+                ..Default::default()
+            },
+            &self.ctx,
+        )?;
+
+        let signed_input = psbt
+            .inputs
+            .get_mut(index as usize)
+            .expect("Signed input should exist");
+
+        let signature = signed_input
+            .partial_sigs
+            .get(&tweaked_pubkey)
+            .expect("Signature should exist");
+
+        let witness = TimelockWitnessData::new(*signature, proof.to_redeem_script()?);
+
+        signed_input.final_script_sig = Some(Script::new());
+        signed_input.final_script_witness = Some(witness.into());
+
+        Ok(())
+    }
+
     fn sign_input(
         &self,
         pixel: Pixel,
@@ -229,8 +288,16 @@ impl TransactionSigner {
         // And finalize it with witness data.
         let witness = P2WPKHWintessData::new(*signature, tweaked_pubkey);
 
+        // Inputs spending P2SH-wrapped outputs must reveal the redeem script.
+        let script_sig = match &signed_input.redeem_script {
+            Some(redeem_script) => Builder::new()
+                .push_slice(redeem_script.as_bytes())
+                .into_script(),
+            None => Script::new(),
+        };
+
         signed_input.final_script_witness = Some(witness.into());
-        signed_input.final_script_sig = Some(Script::new());
+        signed_input.final_script_sig = Some(script_sig);
 
         Ok(())
     }
@@ -1,4 +1,4 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::convert::AsRef;
 use std::ops::Sub;
 
@@ -38,37 +38,136 @@ impl KeychainKind {
 #[serde(tag = "type", rename_all = "snake_case")]
 pub enum FeeRateStrategy {
     /// Fee rate is estimated using `estimatesmartfee` RPC call.
-    Estimate { target: usize },
+    Estimate {
+        target: usize,
+        #[serde(default)]
+        bounds: FeeRateBounds,
+    },
 
     /// Set fee rate manually.
     Manual { fee_rate: f32 },
 
-    /// Fee rate is estimated using `estimatesmartfee` RPC call with fallback to `fee_rate`.
-    TryEstimate { fee_rate: f32, target: usize },
+    /// Fee rate is estimated using `estimatesmartfee` RPC call with fallback to `fee_rate`, which
+    /// is also used if the estimated fee rate is higher than the max one.
+    TryEstimate {
+        fee_rate: f32,
+        target: usize,
+        #[serde(default)]
+        bounds: FeeRateBounds,
+    },
+
+    /// Pay the fixed fee in satoshis regardless of the transaction size.
+    ///
+    /// The fee rate is known only after the transaction is built, so the
+    /// builder spreads the fee over the final virtual size of the transaction.
+    AbsoluteFee { fee: u64 },
 }
 
 impl Default for FeeRateStrategy {
     fn default() -> Self {
         FeeRateStrategy::Estimate {
             target: DEFAULT_TARGET,
+            bounds: FeeRateBounds::NONE,
         }
     }
 }
 
 impl FeeRateStrategy {
+    /// Return the fee in satoshis if the strategy is [`FeeRateStrategy::AbsoluteFee`].
+    pub fn absolute_fee(&self) -> Option<u64> {
+        match self {
+            FeeRateStrategy::AbsoluteFee { fee } => Some(*fee),
+            _ => None,
+        }
+    }
+
+    /// Bounds of the estimated fee rate, if the strategy estimates it.
+    pub fn bounds(&self) -> Option<&FeeRateBounds> {
+        match self {
+            FeeRateStrategy::Estimate { bounds, .. }
+            | FeeRateStrategy::TryEstimate { bounds, .. } => Some(bounds),
+            FeeRateStrategy::Manual { .. } | FeeRateStrategy::AbsoluteFee { .. } => None,
+        }
+    }
+
+    /// Resolve the fee rate of the strategy.
+    ///
+    /// Returns an error for [`FeeRateStrategy::AbsoluteFee`], as its fee rate
+    /// depends on the size of the transaction, see [`Self::absolute_fee`].
     pub fn get_fee_rate(self, blockchain: &impl Blockchain) -> eyre::Result<BdkFeeRate> {
         match self {
-            FeeRateStrategy::Estimate { target } => blockchain
+            FeeRateStrategy::Estimate { target, bounds } => blockchain
                 .estimate_fee(target)
-                .wrap_err("failed to estimate feerate"),
+                .wrap_err("failed to estimate feerate")
+                .and_then(|fee_rate| bounds.apply(fee_rate)),
             FeeRateStrategy::Manual { fee_rate } => Ok(BdkFeeRate::from_sat_per_vb(fee_rate)),
-            FeeRateStrategy::TryEstimate { fee_rate, target } => blockchain
+            FeeRateStrategy::TryEstimate {
+                fee_rate,
+                target,
+                bounds,
+            } => blockchain
                 .estimate_fee(target)
+                .wrap_err("failed to estimate feerate")
+                .and_then(|fee_rate| bounds.apply(fee_rate))
                 .or_else(|_| Ok(BdkFeeRate::from_sat_per_vb(fee_rate))),
+            FeeRateStrategy::AbsoluteFee { fee } => Err(eyre::eyre!(
+                "absolute fee of {} sat has no fee rate until the transaction size is known",
+                fee
+            )),
         }
     }
 }
 
+/// Bounds of the estimated fee rate in sat/vb, which guard against the fee spikes.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq)]
+pub struct FeeRateBounds {
+    /// The estimated fee rate lower than this one is raised to it.
+    #[serde(default)]
+    pub min: Option<f32>,
+    /// The estimated fee rate higher than this one is rejected, or lowered to it if `clamp_max`
+    /// is set.
+    #[serde(default)]
+    pub max: Option<f32>,
+    /// Lower the estimated fee rate to `max` instead of rejecting it.
+    #[serde(default)]
+    pub clamp_max: bool,
+}
+
+impl FeeRateBounds {
+    /// Bounds that don't limit the fee rate.
+    pub const NONE: Self = Self {
+        min: None,
+        max: None,
+        clamp_max: false,
+    };
+
+    /// Apply the bounds to the estimated fee rate.
+    ///
+    /// Returns an error if the fee rate is higher than `max` and `clamp_max` isn't set.
+    pub fn apply(&self, fee_rate: BdkFeeRate) -> eyre::Result<BdkFeeRate> {
+        let mut sat_per_vb = fee_rate.as_sat_per_vb();
+
+        if let Some(max) = self.max {
+            if sat_per_vb > max {
+                eyre::ensure!(
+                    self.clamp_max,
+                    "estimated fee rate {} sat/vb is higher than max {} sat/vb",
+                    sat_per_vb,
+                    max
+                );
+
+                sat_per_vb = max;
+            }
+        }
+
+        if let Some(min) = self.min {
+            sat_per_vb = sat_per_vb.max(min);
+        }
+
+        Ok(BdkFeeRate::from_sat_per_vb(sat_per_vb))
+    }
+}
+
 impl AsRef<[u8]> for KeychainKind {
     fn as_ref(&self) -> &[u8] {
         match self {
@@ -291,6 +390,13 @@ impl Utxo {
             Utxo::Yuv(yuv) => &yuv.txout,
         }
     }
+
+    /// Get the confirmation time of the transaction containing the UTXO
+    pub fn confirmation_time(&self) -> Option<&BlockTime> {
+        match &self {
+            Utxo::Yuv(yuv) => yuv.confirmation_time.as_ref(),
+        }
+    }
 }
 
 /// A wallet transaction
@@ -428,6 +534,41 @@ impl std::iter::Sum for Balance {
     }
 }
 
+/// Activity of the wallet in the token, in the order the transactions are attached by the node.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct ChromaActivity {
+    /// The first transaction that issued, sent or received the token.
+    pub first_txid: Txid,
+    /// The last transaction that issued, sent or received the token.
+    pub last_txid: Txid,
+    /// Transactions with the token, so the ones fetched again by the next sync are not
+    /// recorded twice.
+    pub txids: HashSet<Txid>,
+}
+
+impl ChromaActivity {
+    pub fn new(txid: Txid) -> Self {
+        Self {
+            first_txid: txid,
+            last_txid: txid,
+            txids: HashSet::from([txid]),
+        }
+    }
+
+    /// Number of the transactions with the token.
+    pub fn txs_number(&self) -> usize {
+        self.txids.len()
+    }
+
+    /// Record the transaction attached after the previous ones. Already recorded transactions
+    /// are skipped.
+    pub fn record(&mut self, txid: Txid) {
+        if self.txids.insert(txid) {
+            self.last_txid = txid;
+        }
+    }
+}
+
 /// YUV balances separated into various asset types.
 #[derive(Clone, Debug)]
 pub struct YuvBalances {
@@ -443,13 +584,157 @@ pub struct YuvBalances {
     #[cfg(feature = "bulletproof")]
     /// Bulletproof YUV balances.
     pub bulletproof: HashMap<Chroma, u128>,
+
+    /// Labels of the unspent outputs counted in the balances, see [`Wallet::set_label`].
+    ///
+    /// [`Wallet::set_label`]: crate::Wallet::set_label
+    pub labels: HashMap<OutPoint, String>,
+}
+
+/// Virtual size of the P2WPKH input in vbytes, which most of the pixel inputs are.
+const P2WPKH_INPUT_VBYTES: u64 = 68;
+
+/// Virtual size of the consolidation transaction without the pixel inputs: header, Bitcoin input
+/// paying the fee, and the pixel and Bitcoin change outputs.
+const CONSOLIDATION_OVERHEAD_VBYTES: u64 = 11 + P2WPKH_INPUT_VBYTES + 31 + 31;
+
+/// Heuristic which decides if merging the pixel UTXOs into one is worth it.
+///
+/// Consolidation pays for spending the UTXOs now, but saves spending all of them but one in
+/// the future transfers. It's done only if the future savings at [`Self::expected_fee_rate`]
+/// are greater than the fee paid for the consolidation now.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy)]
+pub struct ConsolidationPolicy {
+    /// Minimal number of UTXOs to consolidate.
+    pub min_utxos: usize,
+
+    /// Fee rate in sat/vb the future transfers are expected to pay.
+    pub expected_fee_rate: f32,
+}
+
+impl Default for ConsolidationPolicy {
+    fn default() -> Self {
+        Self {
+            min_utxos: 2,
+            expected_fee_rate: 10.0,
+        }
+    }
+}
+
+impl ConsolidationPolicy {
+    /// Estimated virtual size of the consolidation of `utxos_number` UTXOs.
+    pub fn consolidation_vbytes(utxos_number: usize) -> u64 {
+        utxos_number as u64 * P2WPKH_INPUT_VBYTES + CONSOLIDATION_OVERHEAD_VBYTES
+    }
+
+    /// Check if consolidation of `utxos_number` UTXOs at `fee_rate` in sat/vb saves the fees of
+    /// the future transfers.
+    pub fn is_worth(&self, utxos_number: usize, fee_rate: f32) -> bool {
+        if utxos_number < self.min_utxos.max(2) {
+            return false;
+        }
+
+        let fee = Self::consolidation_vbytes(utxos_number) as f32 * fee_rate;
+        let savings =
+            ((utxos_number as u64 - 1) * P2WPKH_INPUT_VBYTES) as f32 * self.expected_fee_rate;
+
+        savings > fee
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::*;
     use bitcoin::hashes::Hash;
 
+    use super::*;
+
+    #[test]
+    fn test_chroma_activity_records_txs_once() {
+        let txids = [1, 2, 3].map(|byte| Txid::from_inner([byte; 32]));
+
+        let mut activity = ChromaActivity::new(txids[0]);
+        activity.record(txids[1]);
+        activity.record(txids[2]);
+
+        // The last page is fetched again by the next sync.
+        activity.record(txids[1]);
+        activity.record(txids[2]);
+
+        assert_eq!(activity.first_txid, txids[0]);
+        assert_eq!(activity.last_txid, txids[2]);
+        assert_eq!(activity.txs_number(), 3);
+    }
+
+    #[test]
+    fn test_consolidation_policy() {
+        let policy = ConsolidationPolicy {
+            min_utxos: 3,
+            expected_fee_rate: 10.0,
+        };
+
+        // Not enough UTXOs to merge.
+        assert!(!policy.is_worth(2, 1.0));
+        // Cheap now and expensive later.
+        assert!(policy.is_worth(3, 1.0));
+        assert!(policy.is_worth(100, 5.0));
+        // Fees are expected to be the same as now, so consolidation never pays off.
+        assert!(!policy.is_worth(100, 10.0));
+        assert!(!policy.is_worth(3, 20.0));
+    }
+
+    #[test]
+    fn test_fee_rate_bounds_clamp_min() {
+        let bounds = FeeRateBounds {
+            min: Some(2.0),
+            ..FeeRateBounds::NONE
+        };
+
+        let fee_rate = bounds.apply(BdkFeeRate::from_sat_per_vb(1.0)).unwrap();
+        assert_eq!(fee_rate.as_sat_per_vb(), 2.0);
+
+        let fee_rate = bounds.apply(BdkFeeRate::from_sat_per_vb(5.0)).unwrap();
+        assert_eq!(fee_rate.as_sat_per_vb(), 5.0);
+    }
+
+    #[test]
+    fn test_fee_rate_bounds_max() {
+        let mut bounds = FeeRateBounds {
+            min: Some(2.0),
+            max: Some(100.0),
+            clamp_max: false,
+        };
+
+        let fee_rate = bounds.apply(BdkFeeRate::from_sat_per_vb(100.0)).unwrap();
+        assert_eq!(fee_rate.as_sat_per_vb(), 100.0);
+
+        // The fee spike is rejected.
+        assert!(bounds.apply(BdkFeeRate::from_sat_per_vb(500.0)).is_err());
+
+        // Or lowered to the max one.
+        bounds.clamp_max = true;
+        let fee_rate = bounds.apply(BdkFeeRate::from_sat_per_vb(500.0)).unwrap();
+        assert_eq!(fee_rate.as_sat_per_vb(), 100.0);
+    }
+
+    #[test]
+    fn test_fee_rate_bounds_are_optional_in_config() {
+        let strategy: FeeRateStrategy =
+            serde_json::from_str(r#"{"type": "estimate", "target": 2}"#).unwrap();
+        assert!(matches!(
+            strategy,
+            FeeRateStrategy::Estimate { target: 2, bounds } if bounds == FeeRateBounds::NONE
+        ));
+
+        let strategy: FeeRateStrategy = serde_json::from_str(
+            r#"{"type": "try_estimate", "fee_rate": 1.0, "target": 2, "bounds": {"max": 50.0}}"#,
+        )
+        .unwrap();
+        assert!(matches!(
+            strategy,
+            FeeRateStrategy::TryEstimate { bounds: FeeRateBounds { max: Some(max), .. }, .. } if max == 50.0
+        ));
+    }
+
     #[test]
     fn sort_block_time() {
         let block_time_a = BlockTime {
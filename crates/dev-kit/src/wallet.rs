@@ -1,5 +1,5 @@
 use std::{
-    collections::HashMap,
+    collections::{HashMap, HashSet},
     path::PathBuf,
     sync::{Arc, RwLock},
 };
@@ -9,43 +9,66 @@ use bdk::{
         esplora::EsploraBlockchainConfig, rpc::RpcSyncParams, AnyBlockchainConfig, Blockchain,
         RpcConfig,
     },
-    database::{MemoryDatabase, SqliteDatabase},
+    database::{Database, MemoryDatabase, SqliteDatabase},
     descriptor,
+    miniscript::{
+        descriptor::{DescriptorSecretKey, Wildcard},
+        Descriptor, DescriptorPublicKey, ForEachKey,
+    },
     wallet::wallet_name_from_descriptor,
     Balance, LocalUtxo, SignOptions,
 };
 use bitcoin::{
-    secp256k1::{self, All, Secp256k1},
-    Address, Network, OutPoint, PrivateKey, PublicKey,
+    secp256k1::{self, schnorr, All, KeyPair, Secp256k1},
+    util::bip32::ChildNumber,
+    Address, Network, OutPoint, PrivateKey, PublicKey, Script,
 };
-use eyre::{eyre, Context};
+use eyre::{bail, eyre, Context};
+use futures::Stream;
 use jsonrpsee::http_client::{HttpClient, HttpClientBuilder};
-use yuv_pixels::{Chroma, LightningCommitmentProof, Pixel, PixelProof, ToEvenPublicKey};
+use yuv_pixels::{
+    sign_chroma_ownership, Chroma, LightningCommitmentProof, Pixel, PixelProof, ToEvenPublicKey,
+};
 use yuv_rpc_api::transactions::YuvTransactionsRpcClient;
 use yuv_storage::{
     FlushStrategy, LevelDB, LevelDbOptions, PagesNumberStorage,
     TransactionsStorage as YuvTransactionsStorage,
 };
-use yuv_types::announcements::FreezeAnnouncement;
-use yuv_types::{Announcement, YuvTransaction};
+use yuv_types::announcements::{ChromaFreezeAnnouncement, FreezeAnnouncement};
+use yuv_types::{Announcement, YuvTransaction, YuvTxType};
 
 use crate::{
     bitcoin_provider::{BitcoinProvider, BitcoinProviderConfig, TxOutputStatus},
     database::wrapper::DatabaseWrapper,
-    sync::{indexer::YuvTransactionsIndexer, storage::UnspentYuvOutPointsStorage},
+    history::{history_stream, HistoryEntry},
+    keychain::ChangeKeychain,
+    labels::{LabelRef, WalletLabels},
+    sync::{
+        indexer::YuvTransactionsIndexer,
+        storage::{
+            ChangeKeychainStorage, ChromaHistoryStorage, UnspentYuvOutPointsStorage,
+            WalletLabelsStorage,
+        },
+    },
     txbuilder::{
-        get_output_from_storage, IssuanceTransactionBuilder, SweepTransactionBuilder,
-        TransferTransactionBuilder,
+        get_output_from_storage, IssuanceTransactionBuilder, SponsorTransactionBuilder,
+        SponsoredTransfer, SponsorshipRequest, SweepTransactionBuilder, TransferTransactionBuilder,
     },
-    types::{FeeRateStrategy, YuvBalances},
+    txsigner::TransactionSigner,
+    types::{ChromaActivity, ConsolidationPolicy, FeeRateBounds, FeeRateStrategy, YuvBalances},
     AnyBitcoinProvider,
 };
 
 pub const DEFAULT_FEE_RATE_STRATEGY: FeeRateStrategy = FeeRateStrategy::TryEstimate {
     fee_rate: 1.0,
     target: 2,
+    bounds: FeeRateBounds::NONE,
 };
 
+/// Number of the keys derived from each wildcard private key of the descriptor
+/// after the last revealed one, see [`Wallet::from_descriptor`].
+pub const DESCRIPTOR_KEYS_LOOKAHEAD: u32 = 20;
+
 pub type MemoryWallet =
     Wallet<HttpClient, LevelDB, AnyBitcoinProvider, DatabaseWrapper<MemoryDatabase>>;
 
@@ -150,6 +173,9 @@ impl StorageWallet {
             path: config.storage_path.join(YUV_TXS_DIR_NAME),
             create_if_missing: true,
             flush_strategy: FlushStrategy::Disabled,
+            write_buffer_bytes: None,
+            max_open_files: None,
+            block_cache_bytes: None,
         })?;
 
         let bitcoin_txs_storage = DatabaseWrapper::new(SqliteDatabase::new(
@@ -229,8 +255,17 @@ pub struct Wallet<YuvRpcClient, YuvTxsDB, BitcoinProvider, BitcoinTxsDB> {
     /// Global wallet context used for internal operations on curve.
     pub(crate) secp_ctx: Secp256k1<All>,
 
-    /// Private key of the user.
-    pub(crate) signer_key: PrivateKey,
+    /// Private keys of the user. The first one is the primary key, which is
+    /// used for issuances and holds Bitcoins. If empty, the wallet is
+    /// watch-only.
+    pub(crate) signer_keys: Vec<PrivateKey>,
+
+    /// Public keys of the user which outputs are tracked by the wallet.
+    pub(crate) pubkeys: Vec<PublicKey>,
+
+    /// Keychain of the fresh keys the change of the transfers is sent to. If
+    /// `None`, the change is sent to the primary key.
+    pub(crate) change_keychain: Option<ChangeKeychain>,
     pub(crate) network: Network,
 
     /// Internal storage for YUV UTXOs.
@@ -249,12 +284,80 @@ pub struct Wallet<YuvRpcClient, YuvTxsDB, BitcoinProvider, BitcoinTxsDB> {
     pub(crate) bitcoin_wallet: Arc<RwLock<bdk::Wallet<BitcoinTxsDB>>>,
 }
 
+impl<YC, YTDB, BP, BTDB> Wallet<YC, YTDB, BP, BTDB> {
+    /// Check if the wallet doesn't hold the private key.
+    pub fn is_watch_only(&self) -> bool {
+        self.signer_keys.is_empty()
+    }
+
+    /// Return primary private key of the user, or an error if the wallet is watch-only.
+    pub(crate) fn signer_key(&self) -> eyre::Result<PrivateKey> {
+        self.signer_keys
+            .first()
+            .copied()
+            .ok_or_else(|| eyre!("Wallet is watch-only, spending transactions can't be built"))
+    }
+
+    /// Add a private key which outputs are tracked and spent by the wallet, e.g.
+    /// the previous key of the user after its rotation.
+    ///
+    /// The primary key is kept, so issuances are still made with it. If the
+    /// wallet is watch-only, the added key becomes the primary one.
+    pub fn add_signer_key(&mut self, signer_key: PrivateKey) {
+        if self.signer_keys.contains(&signer_key) {
+            return;
+        }
+
+        let pubkey = signer_key.public_key(&self.secp_ctx);
+        if !self.pubkeys.contains(&pubkey) {
+            self.pubkeys.push(pubkey);
+        }
+
+        self.signer_keys.push(signer_key);
+    }
+
+    /// Return the keychain of the change keys, if enabled.
+    pub fn change_keychain(&self) -> Option<&ChangeKeychain> {
+        self.change_keychain.as_ref()
+    }
+
+    /// Return all the private keys which outputs could be spent by the wallet:
+    /// the signer keys and the change ones.
+    pub(crate) fn all_signer_keys(&self) -> eyre::Result<Vec<PrivateKey>> {
+        let mut keys = self.signer_keys.clone();
+        if let Some(keychain) = &self.change_keychain {
+            keys.extend(keychain.keys()?);
+        }
+
+        Ok(keys)
+    }
+
+    /// Prove the control over the chroma of the primary key by signing the `challenge`,
+    /// without making any transaction. See [`verify_chroma_ownership`].
+    ///
+    /// [`verify_chroma_ownership`]: yuv_pixels::verify_chroma_ownership
+    pub fn prove_chroma_ownership(
+        &self,
+        challenge: &[u8],
+    ) -> eyre::Result<(Chroma, schnorr::Signature)> {
+        let signer_key = self.signer_key()?;
+        let keypair = KeyPair::from_secret_key(&self.secp_ctx, &signer_key.inner);
+
+        let signature = sign_chroma_ownership(&self.secp_ctx, &keypair, challenge);
+
+        Ok((Chroma::from(keypair.x_only_public_key().0), signature))
+    }
+}
+
 impl<YC, YTDB, BP, BTDB> Wallet<YC, YTDB, BP, BTDB>
 where
     YC: YuvTransactionsRpcClient + Clone + Send + Sync + 'static,
     YTDB: YuvTransactionsStorage
         + PagesNumberStorage
         + UnspentYuvOutPointsStorage
+        + ChromaHistoryStorage
+        + WalletLabelsStorage
+        + ChangeKeychainStorage
         + Clone
         + Send
         + Sync
@@ -278,9 +381,123 @@ where
         )
         .wrap_err("Failed to initialize wallet")?;
 
+        let secp_ctx = Secp256k1::new();
+        let pubkey = privkey.public_key(&secp_ctx);
+
+        Ok(Self {
+            secp_ctx,
+            signer_keys: vec![privkey],
+            pubkeys: vec![pubkey],
+            change_keychain: None,
+            network,
+            utxos: Arc::new(RwLock::new(HashMap::new())),
+            yuv_client,
+            yuv_txs_storage,
+            bitcoin_provider,
+            bitcoin_wallet: Arc::new(RwLock::new(bitcoin_wallet)),
+        })
+    }
+
+    /// Create a wallet from the output descriptors, e.g. `wpkh(tprv.../84'/1'/0'/0/*)`,
+    /// which are used by the internal Bitcoin wallet as is.
+    ///
+    /// Signer keys are derived from the private keys of the descriptors, for the
+    /// wildcard ones the children up to the last revealed index plus
+    /// [`DESCRIPTOR_KEYS_LOOKAHEAD`] are taken, so YUV outputs sent to them
+    /// (tweaked by pixels too) are tracked and spent by the wallet. The first key
+    /// derived from the `descriptor` is the primary one.
+    pub fn from_descriptor(
+        descriptor: &str,
+        change_descriptor: Option<&str>,
+        network: Network,
+        yuv_client: YC,
+        yuv_txs_storage: YTDB,
+        bitcoin_provider: BP,
+        bitcoin_txs_storage: BTDB,
+    ) -> eyre::Result<Self> {
+        let bitcoin_wallet =
+            bdk::Wallet::<BTDB>::new(descriptor, change_descriptor, network, bitcoin_txs_storage)
+                .wrap_err("Failed to initialize wallet")?;
+
+        let secp_ctx = Secp256k1::new();
+
+        let (last_index, last_change_index) = {
+            let database = bitcoin_wallet.database();
+            (
+                database
+                    .get_last_index(bdk::KeychainKind::External)
+                    .wrap_err("Failed to get the last revealed index")?,
+                database
+                    .get_last_index(bdk::KeychainKind::Internal)
+                    .wrap_err("Failed to get the last revealed change index")?,
+            )
+        };
+
+        let mut signer_keys = descriptor_signer_keys(&secp_ctx, descriptor, last_index, network)?;
+        if let Some(change_descriptor) = change_descriptor {
+            for key in
+                descriptor_signer_keys(&secp_ctx, change_descriptor, last_change_index, network)?
+            {
+                if !signer_keys.contains(&key) {
+                    signer_keys.push(key);
+                }
+            }
+        }
+
+        if signer_keys.is_empty() {
+            bail!("Descriptor has no private keys, use watch-only wallet instead");
+        }
+
+        let pubkeys = signer_keys
+            .iter()
+            .map(|key| key.public_key(&secp_ctx))
+            .collect();
+
+        Ok(Self {
+            secp_ctx,
+            signer_keys,
+            pubkeys,
+            change_keychain: None,
+            network,
+            utxos: Arc::new(RwLock::new(HashMap::new())),
+            yuv_client,
+            yuv_txs_storage,
+            bitcoin_provider,
+            bitcoin_wallet: Arc::new(RwLock::new(bitcoin_wallet)),
+        })
+    }
+
+    /// Create a watch-only wallet which tracks the outputs of given public keys
+    /// without holding the private ones.
+    ///
+    /// The wallet syncs and reports balances and UTXOs, but refuses to build
+    /// spending transactions. Bitcoin balances are tracked only for the first
+    /// of the `pubkeys`, which is also used as the wallet's address.
+    pub fn watch_only(
+        pubkeys: Vec<PublicKey>,
+        network: Network,
+        yuv_client: YC,
+        yuv_txs_storage: YTDB,
+        bitcoin_provider: BP,
+        bitcoin_txs_storage: BTDB,
+    ) -> eyre::Result<Self> {
+        let Some(first_pubkey) = pubkeys.first().copied() else {
+            bail!("At least one public key is required for watch-only wallet");
+        };
+
+        let bitcoin_wallet = bdk::Wallet::<BTDB>::new(
+            descriptor!(wpkh(first_pubkey))?,
+            None,
+            network,
+            bitcoin_txs_storage,
+        )
+        .wrap_err("Failed to initialize wallet")?;
+
         Ok(Self {
             secp_ctx: Secp256k1::new(),
-            signer_key: privkey,
+            signer_keys: Vec::new(),
+            pubkeys,
+            change_keychain: None,
             network,
             utxos: Arc::new(RwLock::new(HashMap::new())),
             yuv_client,
@@ -290,6 +507,24 @@ where
         })
     }
 
+    /// Send the change of each transfer to a fresh key of the [`ChangeKeychain`]
+    /// derived from the primary key, instead of the primary key itself.
+    ///
+    /// Outputs of the change keys are tracked and spent by the wallet as the
+    /// ones of the signer keys. The keychain continues from the index stored
+    /// by the previous runs, so enable it before the sync.
+    pub async fn enable_change_keychain(&mut self) -> eyre::Result<()> {
+        if self.change_keychain.is_some() {
+            return Ok(());
+        }
+
+        let next_index = self.yuv_txs_storage.get_change_keychain_index().await?;
+        let keychain = ChangeKeychain::new(&self.secp_ctx, &self.signer_key()?, next_index)?;
+        self.change_keychain = Some(keychain);
+
+        Ok(())
+    }
+
     /// Synchronize from YUV node all unspent outpoints and sync the internal bitcoin wallet
     /// database with the blockchain
     pub async fn sync(&self, opts: SyncOptions) -> eyre::Result<()> {
@@ -305,12 +540,24 @@ where
             return Ok(());
         }
 
-        let pubkey = self.signer_key.even_public_key(&self.secp_ctx);
+        let mut pubkeys: Vec<_> = self
+            .pubkeys
+            .iter()
+            .map(|pubkey| pubkey.even_public_key(&self.secp_ctx))
+            .collect();
+        if let Some(keychain) = &self.change_keychain {
+            pubkeys.extend(
+                keychain
+                    .keys()?
+                    .iter()
+                    .map(|key| key.even_public_key(&self.secp_ctx)),
+            );
+        }
 
         let utxos = YuvTransactionsIndexer::new(
             self.yuv_client.clone(),
             self.yuv_txs_storage.clone(),
-            pubkey,
+            pubkeys,
         )
         .sync()
         .await
@@ -324,14 +571,124 @@ where
         Ok(())
     }
 
+    /// Rebuild the set of YUV UTXOs from scratch by fetching all the transactions
+    /// from the YUV node and picking the outputs with proofs to one of the `scripts`.
+    ///
+    /// Unlike [`Wallet::sync`], it doesn't rely on the locally stored state of
+    /// the previous syncs, so it can be used to recover the wallet from a fresh
+    /// node. Outputs spent by the node's transactions, frozen ones, and the ones
+    /// already spent on Bitcoin (including in-flight transfers in mempool) are
+    /// not counted.
+    pub async fn sync_from_node<C>(&self, client: &C, scripts: &[Script]) -> eyre::Result<()>
+    where
+        C: YuvTransactionsRpcClient + Send + Sync,
+    {
+        let scripts = scripts.iter().collect::<HashSet<_>>();
+
+        let mut owned_outpoints = HashMap::new();
+        let mut spent_outpoints = HashSet::new();
+
+        for page in 0.. {
+            let txs = client
+                .list_yuv_transactions(page, None, None)
+                .await
+                .wrap_err("Failed to fetch transactions from node")?;
+
+            if txs.is_empty() {
+                break;
+            }
+
+            for tx in txs {
+                spent_outpoints.extend(tx.bitcoin_tx.input.iter().map(|txin| txin.previous_output));
+
+                if let Some(output_proofs) = tx.tx_type.output_proofs() {
+                    let txid = tx.bitcoin_tx.txid();
+
+                    for (vout, proof) in output_proofs {
+                        let Some(txout) = tx.bitcoin_tx.output.get(*vout as usize) else {
+                            continue;
+                        };
+
+                        if scripts.contains(&txout.script_pubkey) {
+                            owned_outpoints.insert(OutPoint::new(txid, *vout), proof.clone());
+                        }
+                    }
+                }
+
+                self.yuv_txs_storage
+                    .put_yuv_tx(tx)
+                    .await
+                    .wrap_err("Failed to insert transaction")?;
+            }
+        }
+
+        let mut utxos = Vec::new();
+
+        for (outpoint, proof) in owned_outpoints {
+            if spent_outpoints.contains(&outpoint) {
+                continue;
+            }
+
+            let is_outpoint_frozen = client
+                .is_yuv_txout_frozen(outpoint.txid, outpoint.vout)
+                .await?;
+
+            if is_outpoint_frozen {
+                continue;
+            }
+
+            utxos.push((outpoint, proof));
+        }
+
+        let utxos = self
+            .filter_spent_utxos(utxos)
+            .await?
+            .into_iter()
+            .collect::<HashMap<_, _>>();
+
+        self.yuv_txs_storage
+            .put_unspent_yuv_outpoints(utxos.clone())
+            .await?;
+
+        let mut guard = self.utxos.write().map_err(|_| eyre!("Poisoned lock"))?;
+        *guard = utxos;
+
+        Ok(())
+    }
+
+    /// Stream the YUV transactions touching the wallet's keys with their
+    /// directions, in the order they are attached by the node.
+    ///
+    /// The transactions are fetched from the node page by page, as the stream
+    /// is consumed, so the whole history isn't loaded at once.
+    pub fn history(&self) -> eyre::Result<impl Stream<Item = eyre::Result<HistoryEntry>>> {
+        let mut keys: Vec<_> = self
+            .pubkeys
+            .iter()
+            .map(|pubkey| pubkey.inner.x_only_public_key().0)
+            .collect();
+        if let Some(keychain) = &self.change_keychain {
+            keys.extend(
+                keychain
+                    .keys()?
+                    .iter()
+                    .map(|key| key.public_key(&self.secp_ctx).inner.x_only_public_key().0),
+            );
+        }
+
+        Ok(history_stream(self.yuv_client.clone(), keys))
+    }
+
     pub fn address(&self) -> eyre::Result<Address> {
-        let addr = Address::p2wpkh(&self.signer_key.public_key(&self.secp_ctx), self.network)?;
+        let addr = Address::p2wpkh(&self.public_key(), self.network)?;
 
         Ok(addr)
     }
 
+    /// Return the public key of the user. For watch-only wallet, it's the first
+    /// of the tracked keys.
     pub fn public_key(&self) -> PublicKey {
-        self.signer_key.public_key(&self.secp_ctx)
+        self.pubkeys[0]
     }
 
     pub fn bitcoin_provider(&self) -> BP {
@@ -396,11 +753,17 @@ where
                 #[cfg(feature = "bulletproof")]
                 PixelProof::Bulletproof(..) => filtered.push((OutPoint::new(txid, vout), proof)),
                 PixelProof::EmptyPixel(..) => filtered.push((OutPoint::new(txid, vout), proof)),
+                // Timelocked outputs are spendable only after the locktime, which
+                // is checked by Bitcoin node on broadcast of the spending transaction.
+                PixelProof::Timelock(..) => filtered.push((OutPoint::new(txid, vout), proof)),
                 // NOTE: We skip these types of outputs as they are not spendable without
                 // additional information.
                 //
-                // `LightningHtlc` and `Multisig` are usually spent by Lightning node and not by user.
-                PixelProof::LightningHtlc(..) | PixelProof::Multisig(..) => {}
+                // `LightningHtlc` and `Multisig` are usually spent by Lightning node and not by user,
+                // and `GenericScript` needs the witness for the custom script.
+                PixelProof::LightningHtlc(..)
+                | PixelProof::Multisig(..)
+                | PixelProof::GenericScript(..) => {}
             }
         }
 
@@ -414,6 +777,9 @@ where
         #[cfg(feature = "bulletproof")]
         let mut bulletproof_balances = HashMap::new();
         let mut tweaked_satoshis_balances = 0;
+        let mut labels = HashMap::new();
+
+        let wallet_labels = self.yuv_txs_storage.get_wallet_labels().await?;
 
         // Collect the data while holding the read lock.
         let utxos: Vec<(OutPoint, PixelProof)> = {
@@ -425,6 +791,10 @@ where
         };
 
         for (outpoint, proof) in utxos {
+            if let Some(label) = wallet_labels.get(LabelRef::Output(outpoint)) {
+                labels.insert(outpoint, label.clone());
+            }
+
             if proof.is_empty_pixelproof() {
                 let (_pixel_proof, txout) =
                     get_output_from_storage(&self.yuv_txs_storage, outpoint).await?;
@@ -448,9 +818,59 @@ where
             tweaked_satoshis: tweaked_satoshis_balances,
             #[cfg(feature = "bulletproof")]
             bulletproof: bulletproof_balances,
+            labels,
         })
     }
 
+    /// Get every token the wallet has issued, sent or received, including the ones with zero
+    /// balance now, with the first and last transactions of each.
+    ///
+    /// The history is collected by [`Wallet::sync`] from the transactions it indexes, so the
+    /// transactions indexed before it was introduced aren't counted.
+    pub async fn transacted_chromas(&self) -> eyre::Result<HashMap<Chroma, ChromaActivity>> {
+        self.yuv_txs_storage.get_chroma_history().await
+    }
+
+    /// Get the label attached to the transaction or output.
+    pub async fn get_label(&self, reference: LabelRef) -> eyre::Result<Option<String>> {
+        let labels = self.yuv_txs_storage.get_wallet_labels().await?;
+
+        Ok(labels.get(reference).cloned())
+    }
+
+    /// Attach the label to the transaction or output, replacing the previous one.
+    /// The empty label removes it.
+    ///
+    /// Labels are local metadata of the wallet, stored in its YUV transactions
+    /// storage.
+    pub async fn set_label(&self, reference: LabelRef, label: String) -> eyre::Result<()> {
+        let mut labels = self.yuv_txs_storage.get_wallet_labels().await?;
+        labels.set(reference, label);
+
+        self.yuv_txs_storage.put_wallet_labels(labels).await
+    }
+
+    /// Get all the labels attached to the transactions and outputs.
+    pub async fn labels(&self) -> eyre::Result<WalletLabels> {
+        self.yuv_txs_storage.get_wallet_labels().await
+    }
+
+    /// Export the labels in BIP-329 format, see [`WalletLabels::to_bip329`].
+    pub async fn export_labels(&self) -> eyre::Result<String> {
+        self.labels().await?.to_bip329()
+    }
+
+    /// Import the labels in BIP-329 format, replacing the existing ones of the same
+    /// transactions and outputs. Returns the number of the imported labels.
+    pub async fn import_labels(&self, import: &str) -> eyre::Result<usize> {
+        let mut labels = self.yuv_txs_storage.get_wallet_labels().await?;
+        let imported = labels.extend_from_bip329(import)?;
+
+        self.yuv_txs_storage.put_wallet_labels(labels).await?;
+
+        Ok(imported)
+    }
+
     /// Get Bitcoin balances.
     pub fn bitcoin_balances(&self) -> eyre::Result<Balance> {
         Ok(self.bitcoin_wallet.read().unwrap().get_balance()?)
@@ -522,6 +942,42 @@ where
         SweepTransactionBuilder::try_from(self)
     }
 
+    /// Return a builder for paying the fee of the other wallet's transfer,
+    /// which is requested by [`SponsorshipRequest`].
+    pub fn build_sponsorship(&self) -> eyre::Result<SponsorTransactionBuilder<YTDB, BTDB>> {
+        SponsorTransactionBuilder::try_from(self)
+    }
+
+    /// Sign the pixel inputs of the transfer which fee is paid by the sponsor.
+    ///
+    /// The transfer must spend the inputs and have the outputs of the `request`
+    /// the wallet has created with the same proofs.
+    pub fn sign_sponsored_transfer(
+        &self,
+        request: &SponsorshipRequest,
+        mut sponsored: SponsoredTransfer,
+    ) -> eyre::Result<YuvTransaction> {
+        request
+            .verify(&sponsored)
+            .wrap_err("sponsored transfer doesn't match the request")?;
+
+        let YuvTxType::Transfer { input_proofs, .. } = &sponsored.tx_type else {
+            bail!("Sponsored transaction is not a transfer");
+        };
+
+        let mut tx_signer = TransactionSigner::new(self.secp_ctx.clone(), self.signer_key()?);
+        tx_signer.extend_signers(
+            self.all_signer_keys()?
+                .iter()
+                .map(|key| (key.public_key(&self.secp_ctx).inner.into(), key.inner))
+                .collect(),
+        );
+
+        tx_signer.sign(&mut sponsored.psbt, input_proofs)?;
+
+        Ok(sponsored.into_yuv_tx())
+    }
+
     /// Create funding lightning transaction from:
     ///
     /// * `funding_pixel` - chroma and amount that will be in Lightning Network
@@ -598,6 +1054,107 @@ where
         Ok(yuv_tx)
     }
 
+    /// Create a transfer that merges the smallest pixel UTXOs of the [`Chroma`] into a single
+    /// output to the wallet itself, so the future transfers spend fewer inputs.
+    ///
+    /// At most `max_inputs` UTXOs are merged. Frozen outputs, and the ones already spent on
+    /// Bitcoin are skipped.
+    ///
+    /// # Returns
+    ///
+    /// Returns `None` if the consolidation doesn't save the fees of the future transfers
+    /// according to the `policy`.
+    pub async fn consolidate(
+        &self,
+        chroma: Chroma,
+        max_inputs: usize,
+        policy: ConsolidationPolicy,
+        fee_rate_strategy: Option<FeeRateStrategy>,
+    ) -> eyre::Result<Option<YuvTransaction>> {
+        let fee_rate_strategy = fee_rate_strategy.unwrap_or(DEFAULT_FEE_RATE_STRATEGY);
+        let blockchain = self.bitcoin_provider.blockchain();
+
+        let mut utxos = self
+            .yuv_utxos()
+            .into_iter()
+            .filter(|(_, proof)| matches!(proof, PixelProof::Sig(_)))
+            .filter(|(_, proof)| proof.pixel().chroma == chroma)
+            .collect::<Vec<_>>();
+
+        utxos.sort_by_key(|(outpoint, proof)| (proof.pixel().luma.amount, *outpoint));
+
+        // UTXOs are checked one by one, so only the smallest ones are requested.
+        let mut selected = Vec::new();
+        for (outpoint, proof) in utxos {
+            if selected.len() >= max_inputs {
+                break;
+            }
+
+            let amount = proof.pixel().luma.amount;
+
+            if self
+                .filter_spent_utxos(vec![(outpoint, proof)])
+                .await?
+                .is_empty()
+            {
+                continue;
+            }
+
+            if self.is_output_frozen(outpoint).await? {
+                tracing::debug!("UTXO {} is frozen", outpoint);
+                continue;
+            }
+
+            selected.push((outpoint, amount));
+        }
+
+        let fee_rate = match fee_rate_strategy.absolute_fee() {
+            // The fixed fee is compared by the rate it pays for the estimated size.
+            Some(fee) => {
+                fee as f32 / ConsolidationPolicy::consolidation_vbytes(selected.len()) as f32
+            }
+            None => fee_rate_strategy
+                .get_fee_rate(&blockchain)
+                .wrap_err("failed to estimate fee")?
+                .as_sat_per_vb(),
+        };
+
+        if !policy.is_worth(selected.len(), fee_rate) {
+            return Ok(None);
+        }
+
+        let mut tx_builder = self
+            .build_transfer()
+            .wrap_err("failed to init transaction builder")?;
+
+        let mut amount = 0u128;
+        for (outpoint, utxo_amount) in selected {
+            let (_proof, output) = get_output_from_storage(&self.yuv_txs_storage, outpoint).await?;
+
+            if output.script_pubkey.is_p2sh() {
+                tx_builder.add_sh_wpkh_pixel_input(outpoint);
+            } else {
+                tx_builder.add_pixel_input(outpoint);
+            }
+
+            amount = amount
+                .checked_add(utxo_amount)
+                .ok_or_else(|| eyre!("Inputs sum overflow"))?;
+        }
+
+        tx_builder
+            .add_recipient(chroma, &self.public_key().inner, amount, 1000)
+            .set_fee_rate_strategy(fee_rate_strategy)
+            .manual_selected_only();
+
+        let yuv_tx = tx_builder
+            .finish(&blockchain)
+            .await
+            .wrap_err("failed to build yuv transaction")?;
+
+        Ok(Some(yuv_tx))
+    }
+
     /// Create YUV [`Announcement`] transaction for given [`Announcement`].
     pub fn create_announcement_tx(
         &self,
@@ -605,17 +1162,23 @@ where
         fee_rate_strategy: FeeRateStrategy,
         blockchain: &impl Blockchain,
     ) -> eyre::Result<YuvTransaction> {
+        self.signer_key()?;
+
         let tx = {
             let wallet = self.bitcoin_wallet.read().unwrap();
             let mut builder = wallet.build_tx();
 
-            let fee_rate = fee_rate_strategy
-                .get_fee_rate(blockchain)
-                .wrap_err("failed to estimate fee")?;
+            match fee_rate_strategy.absolute_fee() {
+                Some(fee) => builder.fee_absolute(fee),
+                None => builder.fee_rate(
+                    fee_rate_strategy
+                        .get_fee_rate(blockchain)
+                        .wrap_err("failed to estimate fee")?,
+                ),
+            };
 
             builder
                 .add_recipient(announcement.to_script(), 0)
-                .fee_rate(fee_rate)
                 .allow_dust(true);
 
             let (mut psbt, _) = builder.finish()?;
@@ -628,13 +1191,20 @@ where
         Ok(YuvTransaction::new(tx, announcement.into()))
     }
 
-    /// Create YUV freeze transaction for given [`OutPoint`].
-    pub fn create_freeze(
+    /// Create YUV freeze transaction for given not frozen [`OutPoint`].
+    ///
+    /// The freeze of the frozen output would unfreeze it, so the current state is checked on
+    /// the YUV node, see [`Self::create_unfreeze`].
+    pub async fn create_freeze(
         &self,
         outpoint: OutPoint,
         fee_rate_strategy: FeeRateStrategy,
         blockchain: &impl Blockchain,
     ) -> eyre::Result<YuvTransaction> {
+        if self.is_output_frozen(outpoint).await? {
+            bail!("Output {} is already frozen", outpoint);
+        }
+
         let tx_freeze = FreezeAnnouncement::from(outpoint);
 
         let yuv_tx =
@@ -642,6 +1212,127 @@ where
 
         Ok(yuv_tx)
     }
+
+    /// Create YUV unfreeze transaction for given frozen [`OutPoint`].
+    ///
+    /// Freeze and unfreeze transactions are the same toggle of the frozen state, so the
+    /// current state is checked on the YUV node to not freeze the output again instead.
+    pub async fn create_unfreeze(
+        &self,
+        outpoint: OutPoint,
+        fee_rate_strategy: FeeRateStrategy,
+        blockchain: &impl Blockchain,
+    ) -> eyre::Result<YuvTransaction> {
+        if !self.is_output_frozen(outpoint).await? {
+            bail!("Output {} is not frozen", outpoint);
+        }
+
+        let tx_unfreeze = FreezeAnnouncement::from(outpoint);
+
+        let yuv_tx =
+            self.create_announcement_tx(tx_unfreeze.into(), fee_rate_strategy, blockchain)?;
+
+        Ok(yuv_tx)
+    }
+
+    /// Create YUV transaction that freezes or unfreezes all the outputs of the given [`Chroma`].
+    ///
+    /// Unlike the freeze of the [`OutPoint`], it sets the frozen state instead of toggling it,
+    /// so it takes effect for the outputs that are created after it too.
+    pub fn create_chroma_freeze(
+        &self,
+        chroma: Chroma,
+        frozen: bool,
+        fee_rate_strategy: FeeRateStrategy,
+        blockchain: &impl Blockchain,
+    ) -> eyre::Result<YuvTransaction> {
+        let announcement = ChromaFreezeAnnouncement::new(chroma, frozen);
+
+        let yuv_tx =
+            self.create_announcement_tx(announcement.into(), fee_rate_strategy, blockchain)?;
+
+        Ok(yuv_tx)
+    }
+
+    /// Check on the YUV node if the [`OutPoint`] is frozen.
+    pub async fn is_output_frozen(&self, outpoint: OutPoint) -> eyre::Result<bool> {
+        self.yuv_client
+            .is_yuv_txout_frozen(outpoint.txid, outpoint.vout)
+            .await
+            .wrap_err("failed to get frozen state of the output from YUV node")
+    }
+}
+
+/// Derive the private keys from the private ones of the descriptor, in the
+/// order of their public keys in the descriptor. Children of the wildcard keys
+/// are derived up to the `last_index` revealed by the wallet plus
+/// [`DESCRIPTOR_KEYS_LOOKAHEAD`].
+fn descriptor_signer_keys(
+    secp_ctx: &Secp256k1<All>,
+    descriptor: &str,
+    last_index: Option<u32>,
+    network: Network,
+) -> eyre::Result<Vec<PrivateKey>> {
+    let (descriptor, keymap) =
+        Descriptor::<DescriptorPublicKey>::parse_descriptor(secp_ctx, descriptor)
+            .wrap_err("Invalid descriptor")?;
+
+    let mut public_keys = Vec::new();
+    descriptor.for_each_key(|key| {
+        if !public_keys.contains(key) {
+            public_keys.push(key.clone());
+        }
+
+        true
+    });
+
+    let children_number = last_index
+        .map_or(0, |index| index.saturating_add(1))
+        .saturating_add(DESCRIPTOR_KEYS_LOOKAHEAD);
+
+    let mut signer_keys = Vec::new();
+    for public_key in public_keys {
+        let Some(secret_key) = keymap.get(&public_key) else {
+            continue;
+        };
+
+        match secret_key {
+            DescriptorSecretKey::Single(single) => signer_keys.push(single.key),
+            DescriptorSecretKey::XPrv(xprv) => {
+                let xkey = xprv
+                    .xkey
+                    .derive_priv(secp_ctx, &xprv.derivation_path)
+                    .wrap_err("Failed to derive key from descriptor")?;
+
+                let children = match xprv.wildcard {
+                    Wildcard::None => {
+                        signer_keys.push(xkey.to_priv());
+                        continue;
+                    }
+                    Wildcard::Unhardened => (0..children_number)
+                        .map(ChildNumber::from_normal_idx)
+                        .collect::<Result<Vec<_>, _>>()?,
+                    Wildcard::Hardened => (0..children_number)
+                        .map(ChildNumber::from_hardened_idx)
+                        .collect::<Result<Vec<_>, _>>()?,
+                };
+
+                for child in children {
+                    let child_key = xkey
+                        .ckd_priv(secp_ctx, child)
+                        .wrap_err("Failed to derive key from descriptor")?;
+
+                    signer_keys.push(child_key.to_priv());
+                }
+            }
+        }
+    }
+
+    for key in &mut signer_keys {
+        key.network = network;
+    }
+
+    Ok(signer_keys)
 }
 
 #[cfg(test)]
@@ -660,4 +1351,231 @@ mod tests {
         assert_sync::<StorageWallet>();
         assert_send::<StorageWallet>();
     }
+
+    fn watch_only_wallet(pubkeys: Vec<PublicKey>) -> eyre::Result<MemoryWallet> {
+        let bitcoin_provider = BitcoinProvider::from_config(AnyBlockchainConfig::Esplora(
+            EsploraBlockchainConfig::new("http://127.0.0.1:3000".to_string(), 20),
+        ))?;
+
+        MemoryWallet::watch_only(
+            pubkeys,
+            Network::Regtest,
+            HttpClientBuilder::new().build("http://127.0.0.1:18333")?,
+            LevelDB::in_memory()?,
+            bitcoin_provider,
+            DatabaseWrapper::new(MemoryDatabase::default()),
+        )
+    }
+
+    #[tokio::test]
+    async fn test_watch_only_wallet_refuses_to_build_transactions() {
+        let ctx = Secp256k1::new();
+        let pubkey = PrivateKey::new(
+            secp256k1::SecretKey::from_slice(&[1; 32]).unwrap(),
+            Network::Regtest,
+        )
+        .public_key(&ctx);
+
+        let wallet = watch_only_wallet(vec![pubkey]).unwrap();
+
+        assert!(wallet.is_watch_only());
+        assert_eq!(wallet.public_key(), pubkey);
+
+        let err = wallet.build_transfer().err().expect("transfer must fail");
+        assert!(
+            err.to_string().contains("watch-only"),
+            "unexpected error: {err}"
+        );
+        assert!(wallet.build_issuance().is_err());
+        assert!(wallet.build_sweep().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_wallet_labels_are_stored() {
+        use bitcoin::hashes::Hash;
+        use bitcoin::Txid;
+
+        let pubkey = PrivateKey::new(
+            secp256k1::SecretKey::from_slice(&[1; 32]).unwrap(),
+            Network::Regtest,
+        )
+        .public_key(&Secp256k1::new());
+        let wallet = watch_only_wallet(vec![pubkey]).unwrap();
+
+        let txid = Txid::from_inner([7; 32]);
+        let output = LabelRef::Output(OutPoint::new(txid, 0));
+
+        wallet
+            .set_label(LabelRef::Tx(txid), "Payment".to_string())
+            .await
+            .unwrap();
+        wallet
+            .set_label(output, "Change".to_string())
+            .await
+            .unwrap();
+
+        assert_eq!(
+            wallet.get_label(LabelRef::Tx(txid)).await.unwrap(),
+            Some("Payment".to_string())
+        );
+        assert_eq!(
+            wallet.get_label(output).await.unwrap(),
+            Some("Change".to_string())
+        );
+
+        let export = wallet.export_labels().await.unwrap();
+        wallet.set_label(output, String::new()).await.unwrap();
+        assert_eq!(wallet.get_label(output).await.unwrap(), None);
+
+        assert_eq!(wallet.import_labels(&export).await.unwrap(), 2);
+        assert_eq!(
+            wallet.get_label(output).await.unwrap(),
+            Some("Change".to_string())
+        );
+    }
+
+    #[tokio::test]
+    async fn test_watch_only_wallet_requires_pubkeys() {
+        assert!(watch_only_wallet(Vec::new()).is_err());
+    }
+
+    fn descriptor_wallet(
+        descriptor: &str,
+        change_descriptor: Option<&str>,
+    ) -> eyre::Result<MemoryWallet> {
+        descriptor_wallet_with_database(descriptor, change_descriptor, MemoryDatabase::default())
+    }
+
+    fn descriptor_wallet_with_database(
+        descriptor: &str,
+        change_descriptor: Option<&str>,
+        database: MemoryDatabase,
+    ) -> eyre::Result<MemoryWallet> {
+        let bitcoin_provider = BitcoinProvider::from_config(AnyBlockchainConfig::Esplora(
+            EsploraBlockchainConfig::new("http://127.0.0.1:3000".to_string(), 20),
+        ))?;
+
+        MemoryWallet::from_descriptor(
+            descriptor,
+            change_descriptor,
+            Network::Regtest,
+            HttpClientBuilder::new().build("http://127.0.0.1:18333")?,
+            LevelDB::in_memory()?,
+            bitcoin_provider,
+            DatabaseWrapper::new(database),
+        )
+    }
+
+    #[tokio::test]
+    async fn test_wallet_from_descriptor() {
+        use bdk::wallet::AddressIndex;
+        use bitcoin::util::bip32::{DerivationPath, ExtendedPrivKey, ExtendedPubKey};
+        use std::str::FromStr;
+
+        let ctx = Secp256k1::new();
+        let xprv = ExtendedPrivKey::new_master(Network::Regtest, &[7; 32]).unwrap();
+
+        let wallet = descriptor_wallet(
+            &format!("wpkh({xprv}/84'/1'/0'/0/*)"),
+            Some(&format!("wpkh({xprv}/84'/1'/0'/1/*)")),
+        )
+        .unwrap();
+
+        assert!(!wallet.is_watch_only());
+        assert_eq!(
+            wallet.signer_keys.len(),
+            2 * DESCRIPTOR_KEYS_LOOKAHEAD as usize
+        );
+
+        let first_key = xprv
+            .derive_priv(&ctx, &DerivationPath::from_str("m/84'/1'/0'/0/0").unwrap())
+            .unwrap()
+            .to_priv();
+        assert_eq!(wallet.signer_key().unwrap(), first_key);
+        assert_eq!(wallet.public_key(), first_key.public_key(&ctx));
+
+        let change_key = xprv
+            .derive_priv(&ctx, &DerivationPath::from_str("m/84'/1'/0'/1/5").unwrap())
+            .unwrap()
+            .to_priv();
+        assert!(wallet.signer_keys.contains(&change_key));
+        assert!(wallet.pubkeys.contains(&change_key.public_key(&ctx)));
+
+        let bdk_address = wallet
+            .bitcoin_wallet
+            .read()
+            .unwrap()
+            .get_address(AddressIndex::Peek(0))
+            .unwrap()
+            .address;
+        assert_eq!(wallet.address().unwrap(), bdk_address);
+
+        let xpub = ExtendedPubKey::from_priv(&ctx, &xprv);
+        assert!(descriptor_wallet(&format!("wpkh({xpub}/0/*)"), None).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_descriptor_keys_are_derived_after_last_revealed_index() {
+        use bdk::database::BatchOperations;
+        use bitcoin::util::bip32::{DerivationPath, ExtendedPrivKey};
+        use std::str::FromStr;
+
+        let ctx = Secp256k1::new();
+        let xprv = ExtendedPrivKey::new_master(Network::Regtest, &[7; 32]).unwrap();
+
+        let last_index = 2 * DESCRIPTOR_KEYS_LOOKAHEAD;
+        let mut database = MemoryDatabase::default();
+        database
+            .set_last_index(bdk::KeychainKind::External, last_index)
+            .unwrap();
+
+        let wallet =
+            descriptor_wallet_with_database(&format!("wpkh({xprv}/84'/1'/0'/0/*)"), None, database)
+                .unwrap();
+
+        assert_eq!(
+            wallet.signer_keys.len(),
+            (last_index + 1 + DESCRIPTOR_KEYS_LOOKAHEAD) as usize
+        );
+
+        for index in [last_index, last_index + DESCRIPTOR_KEYS_LOOKAHEAD] {
+            let key = xprv
+                .derive_priv(
+                    &ctx,
+                    &DerivationPath::from_str(&format!("m/84'/1'/0'/0/{index}")).unwrap(),
+                )
+                .unwrap()
+                .to_priv();
+            assert!(
+                wallet.signer_keys.contains(&key),
+                "key at index {index} above the lookahead must be derived"
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn test_descriptor_keys_keep_descriptor_order() {
+        use bitcoin::util::bip32::{DerivationPath, ExtendedPrivKey};
+        use std::str::FromStr;
+
+        let ctx = Secp256k1::new();
+        let xprvs = [[7; 32], [8; 32]]
+            .map(|seed| ExtendedPrivKey::new_master(Network::Regtest, &seed).unwrap());
+
+        for [first, second] in [[xprvs[0], xprvs[1]], [xprvs[1], xprvs[0]]] {
+            let wallet =
+                descriptor_wallet(&format!("wsh(multi(1,{first}/0/*,{second}/0/*))"), None)
+                    .unwrap();
+
+            let first_key = first
+                .derive_priv(&ctx, &DerivationPath::from_str("m/0/0").unwrap())
+                .unwrap()
+                .to_priv();
+            assert_eq!(
+                wallet.signer_key().unwrap(),
+                first_key,
+                "primary key must be the first one of the descriptor"
+            );
+        }
+    }
 }
@@ -1,3 +1,5 @@
+use core::cmp::Ordering;
+
 use crate::types::{Utxo, WeightedUtxo};
 use bdk::Error;
 use bitcoin::Script;
@@ -89,14 +91,22 @@ impl YUVCoinSelectionAlgorithm for YuvLargestFirstCoinSelection {
                 && !wu.utxo.yuv_txout().script_pubkey.is_op_return()
         });
 
-        // We put the "required UTXOs" first and make sure the optional UTXOs are sorted,
-        // initially smallest to largest, before being reversed with `.rev()`.
+        // We put the "required UTXOs" first and make sure the optional UTXOs are sorted from
+        // largest to smallest. Ties are broken by outpoint, so the selection is deterministic.
         let utxos = {
-            optional_utxos.sort_unstable_by_key(|wu| wu.utxo.yuv_txout().pixel.luma.amount); // Sorting by amount now
+            optional_utxos.sort_unstable_by(|a, b| {
+                let a_amount = a.utxo.yuv_txout().pixel.luma.amount;
+                let b_amount = b.utxo.yuv_txout().pixel.luma.amount;
+
+                b_amount
+                    .cmp(&a_amount)
+                    .then_with(|| a.utxo.outpoint().cmp(&b.utxo.outpoint()))
+            });
+
             required_utxos
                 .into_iter()
                 .map(|utxo| (true, utxo))
-                .chain(optional_utxos.into_iter().rev().map(|utxo| (false, utxo)))
+                .chain(optional_utxos.into_iter().map(|utxo| (false, utxo)))
         };
 
         select_sorted_utxos(utxos, target_amount, drain_script)
@@ -122,9 +132,20 @@ impl YUVCoinSelectionAlgorithm for YUVOldestFirstCoinSelection {
         // We put the "required UTXOs" first and make sure the optional UTXOs are sorted from
         // oldest to newest according to blocktime
         // For utxo that doesn't exist in DB, they will have lowest priority to be selected
+        // Ties are broken by outpoint, so the selection is deterministic.
         let utxos = {
             optional_utxos.retain(|wu| wu.utxo.yuv_txout().pixel.chroma == target_chroma);
 
+            optional_utxos.sort_unstable_by(|a, b| {
+                match (a.utxo.confirmation_time(), b.utxo.confirmation_time()) {
+                    (Some(a_time), Some(b_time)) => a_time.cmp(b_time),
+                    (Some(_), None) => Ordering::Less,
+                    (None, Some(_)) => Ordering::Greater,
+                    (None, None) => Ordering::Equal,
+                }
+                .then_with(|| a.utxo.outpoint().cmp(&b.utxo.outpoint()))
+            });
+
             required_utxos
                 .into_iter()
                 .map(|utxo| (true, utxo))
@@ -259,4 +280,58 @@ mod test {
         assert_eq!(result.selected.len(), 3);
         assert_eq!(result.selected_amount(), 790_000);
     }
+
+    #[test]
+    fn test_coin_selection_is_deterministic_for_tied_utxos() {
+        let token = bitcoin::PublicKey::from_str(
+            "02ba604e6ad9d3864eda8dc41c62668514ef7d5417d3b6db46e45cc4533bff001c",
+        )
+        .expect("pubkey");
+        let chroma = Chroma::from(token);
+        let drain_script = Script::default();
+
+        // All the UTXOs have the same amount and confirmation time, so only two of them
+        // with the lowest outpoints must be selected.
+        let utxos = (0..5)
+            .map(|index| utxo(100_000, 1_000, token, index))
+            .collect::<Vec<_>>();
+
+        let algorithms: [&dyn YUVCoinSelectionAlgorithm; 2] =
+            [&YuvLargestFirstCoinSelection, &YUVOldestFirstCoinSelection];
+
+        for algorithm in algorithms {
+            let mut optional_utxos = utxos.clone();
+
+            let expected = algorithm
+                .coin_select(vec![], optional_utxos.clone(), 2_000, &drain_script, chroma)
+                .unwrap()
+                .selected
+                .iter()
+                .map(Utxo::outpoint)
+                .collect::<Vec<_>>();
+
+            assert_eq!(
+                expected,
+                vec![utxos[0].utxo.outpoint(), utxos[1].utxo.outpoint()],
+                "expected ties to be broken by outpoint"
+            );
+
+            for _ in 0..utxos.len() {
+                optional_utxos.rotate_left(1);
+
+                let selected = algorithm
+                    .coin_select(vec![], optional_utxos.clone(), 2_000, &drain_script, chroma)
+                    .unwrap()
+                    .selected
+                    .iter()
+                    .map(Utxo::outpoint)
+                    .collect::<Vec<_>>();
+
+                assert_eq!(
+                    selected, expected,
+                    "expected the same inputs to be selected"
+                );
+            }
+        }
+    }
 }
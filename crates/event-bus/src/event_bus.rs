@@ -4,15 +4,22 @@ use std::{
     any::{Any, TypeId},
     collections::HashMap,
     fmt::{Debug, Display, Formatter},
+    time::Duration,
 };
 
 use flume as channel;
+use tokio::time::Instant;
 
 pub type EventBusResult<T> = Result<T, Error>;
 
 type InnerSender = channel::Sender<Box<dyn BusEvent>>;
 type InnerReceiver = channel::Receiver<Box<dyn BusEvent>>;
 
+/// Delay before the first attempt to resend the event to the full channel, which is doubled with
+/// each next one up to [`MAX_RESEND_DELAY`].
+const INITIAL_RESEND_DELAY: Duration = Duration::from_millis(1);
+const MAX_RESEND_DELAY: Duration = Duration::from_millis(50);
+
 /// Trait for events that can be sent through event bus. Use [`event_bus_macros::Event`] derive
 /// macro to implement it.
 ///
@@ -67,6 +74,20 @@ pub struct EventBus {
 
     /// Map of event type id to channel receiver.
     rxs: HashMap<TypeId, InnerReceiver>,
+
+    /// Map of event type id to name of the event type.
+    names: HashMap<TypeId, &'static str>,
+}
+
+/// Statistics of the channel for one event type.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct QueueStats {
+    /// Name of the event type without module path.
+    pub name: &'static str,
+    /// Number of events in the channel waiting to be received.
+    pub len: usize,
+    /// Max number of events in the channel. `None` if the channel is unbounded.
+    pub capacity: Option<usize>,
 }
 
 impl EventBus {
@@ -86,6 +107,7 @@ impl EventBus {
 
         self.txs.insert(tid::<E>(), tx);
         self.rxs.insert(tid::<E>(), rx);
+        self.names.insert(tid::<E>(), short_type_name::<E>());
 
         false
     }
@@ -98,9 +120,48 @@ impl EventBus {
         Ok(Self {
             txs: new_hashmap_with::<InnerSender>(&self.txs, tx_ids)?,
             rxs: new_hashmap_with::<InnerReceiver>(&self.rxs, rx_ids)?,
+            names: self.names.clone(),
         })
     }
 
+    /// Get the number of queued events in every channel which sender is presented in the event
+    /// bus, sorted by the event name.
+    ///
+    /// # Examples
+    /// ```
+    /// use event_bus::{EventBus, Event};
+    ///
+    /// #[derive(Clone, Event)]
+    /// struct MyEvent;
+    ///
+    /// # tokio_test::block_on(async {
+    /// let mut event_bus = EventBus::default();
+    /// event_bus.register::<MyEvent>(Some(10));
+    ///
+    /// event_bus.send(MyEvent).await;
+    ///
+    /// let stats = event_bus.queue_stats();
+    /// assert_eq!(stats[0].name, "MyEvent");
+    /// assert_eq!(stats[0].len, 1);
+    /// assert_eq!(stats[0].capacity, Some(10));
+    /// # });
+    /// ```
+    pub fn queue_stats(&self) -> Vec<QueueStats> {
+        let mut stats = self
+            .txs
+            .iter()
+            .map(|(id, tx)| QueueStats {
+                name: self.names.get(id).copied().unwrap_or_default(),
+                len: tx.len(),
+                capacity: tx.capacity(),
+            })
+            .collect::<Vec<_>>();
+
+        stats.sort_by_key(|stats| stats.name);
+
+        stats
+    }
+
     /// Subscribe to channel by returning [`Receiver`] for specified event type. If channel for
     /// specified event type doesn't exist, method will panic. Use [`EventBus::try_subscribe`] to
     /// avoid panic.
@@ -158,6 +219,81 @@ impl EventBus {
 
         Ok(())
     }
+
+    /// Send event to channel, waiting for at most `timeout` if the channel is full. If channels
+    /// for specified event isn't registered ([`EventBus::register`]), method will panic.
+    ///
+    /// Unlike [`EventBus::send`], the event is returned in the error if the channel is still full
+    /// after the timeout or closed, so the caller can send it again later instead of losing it.
+    ///
+    /// # Examples
+    /// ```
+    /// use std::time::Duration;
+    ///
+    /// use event_bus::{EventBus, Event, SendTimeoutError};
+    ///
+    /// #[derive(Clone, Debug, PartialEq, Event)]
+    /// struct MyEvent(u32);
+    ///
+    /// # tokio_test::block_on(async {
+    /// let mut event_bus = EventBus::default();
+    /// event_bus.register::<MyEvent>(Some(1));
+    ///
+    /// event_bus.send(MyEvent(1)).await;
+    ///
+    /// let result = event_bus.send_timeout(MyEvent(2), Duration::from_millis(10)).await;
+    /// assert_eq!(result, Err(SendTimeoutError::Timeout(MyEvent(2))));
+    /// # });
+    /// ```
+    pub async fn send_timeout<E: BusEvent + Clone + 'static>(
+        &self,
+        event: E,
+        timeout: Duration,
+    ) -> Result<(), SendTimeoutError<E>> {
+        let channel = self
+            .txs
+            .get(&tid::<E>())
+            .expect("channel for event must be presented");
+
+        let deadline = Instant::now() + timeout;
+        let mut delay = INITIAL_RESEND_DELAY;
+        let mut event: Box<dyn BusEvent> = Box::new(event);
+
+        loop {
+            match channel.try_send(event) {
+                Ok(()) => return Ok(()),
+                Err(channel::TrySendError::Disconnected(event)) => {
+                    return Err(SendTimeoutError::Closed(downcast(event)));
+                }
+                Err(channel::TrySendError::Full(returned)) => {
+                    let now = Instant::now();
+                    if now >= deadline {
+                        return Err(SendTimeoutError::Timeout(downcast(returned)));
+                    }
+
+                    event = returned;
+                    tokio::time::sleep(delay.min(deadline - now)).await;
+                    delay = (delay * 2).min(MAX_RESEND_DELAY);
+                }
+            }
+        }
+    }
+}
+
+/// Get the event of the concrete type back from the channel's one.
+fn downcast<E: Clone + 'static>(event: Box<dyn BusEvent>) -> E {
+    event
+        .as_any()
+        .downcast_ref::<E>()
+        .expect("event must be of the channel's type")
+        .clone()
+}
+
+/// Get the name of the type without module path.
+fn short_type_name<T>() -> &'static str {
+    let name = std::any::type_name::<T>();
+
+    name.rsplit("::").next().unwrap_or(name)
 }
 
 fn new_hashmap_with<Channel: Clone>(
@@ -198,3 +334,107 @@ impl Display for Error {
 }
 
 impl std::error::Error for Error {}
+
+/// Error of [`EventBus::send_timeout`] with the event that wasn't sent.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SendTimeoutError<E> {
+    /// Channel is still full after the timeout.
+    Timeout(E),
+    /// All the receivers of the channel are dropped.
+    Closed(E),
+}
+
+impl<E> SendTimeoutError<E> {
+    /// Get the event that wasn't sent.
+    pub fn into_inner(self) -> E {
+        match self {
+            Self::Timeout(event) | Self::Closed(event) => event,
+        }
+    }
+}
+
+impl<E> Display for SendTimeoutError<E> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Timeout(_) => write!(f, "channel is full, timed out sending message"),
+            Self::Closed(_) => write!(f, "channel is closed"),
+        }
+    }
+}
+
+impl<E: Debug> std::error::Error for SendTimeoutError<E> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TIMEOUT: Duration = Duration::from_millis(20);
+
+    #[derive(Clone, Debug, PartialEq)]
+    struct TestEvent(u32);
+
+    impl BusEvent for TestEvent {
+        fn as_any(&self) -> &dyn Any {
+            self
+        }
+    }
+
+    #[test]
+    fn test_send_timeout_returns_event_if_channel_is_full() {
+        tokio_test::block_on(async {
+            let mut event_bus = EventBus::default();
+            event_bus.register::<TestEvent>(Some(1));
+            let receiver = event_bus.subscribe::<TestEvent>();
+
+            // The consumer is backed up, so the only slot is taken.
+            event_bus.send_timeout(TestEvent(1), TIMEOUT).await.unwrap();
+
+            let result = event_bus.send_timeout(TestEvent(2), TIMEOUT).await;
+            assert_eq!(result, Err(SendTimeoutError::Timeout(TestEvent(2))));
+
+            // The event isn't lost and can be sent again when the consumer catches up.
+            let event = result.unwrap_err().into_inner();
+            assert_eq!(receiver.recv().await.unwrap(), TestEvent(1));
+            event_bus.send_timeout(event, TIMEOUT).await.unwrap();
+            assert_eq!(receiver.recv().await.unwrap(), TestEvent(2));
+        });
+    }
+
+    #[test]
+    fn test_send_timeout_waits_for_capacity() {
+        tokio_test::block_on(async {
+            let mut event_bus = EventBus::default();
+            event_bus.register::<TestEvent>(Some(1));
+            let receiver = event_bus.subscribe::<TestEvent>();
+
+            event_bus.send_timeout(TestEvent(1), TIMEOUT).await.unwrap();
+
+            let (sent, received) = tokio::join!(
+                event_bus.send_timeout(TestEvent(2), Duration::from_secs(5)),
+                async {
+                    tokio::time::sleep(TIMEOUT).await;
+                    receiver.recv().await.unwrap()
+                }
+            );
+
+            assert_eq!(sent, Ok(()));
+            assert_eq!(received, TestEvent(1));
+            assert_eq!(receiver.recv().await.unwrap(), TestEvent(2));
+        });
+    }
+
+    #[test]
+    fn test_send_timeout_returns_event_if_channel_is_closed() {
+        tokio_test::block_on(async {
+            let mut event_bus = EventBus::default();
+            event_bus.register::<TestEvent>(Some(1));
+
+            // Keep only the sender of the channel.
+            let senders = event_bus.extract(&[tid::<TestEvent>()], &[]).unwrap();
+            drop(event_bus);
+
+            let result = senders.send_timeout(TestEvent(1), TIMEOUT).await;
+            assert_eq!(result, Err(SendTimeoutError::Closed(TestEvent(1))));
+        });
+    }
+}
@@ -1,7 +1,7 @@
 #![doc = include_str!("../README.md")]
 mod event_bus;
 
-pub use crate::event_bus::{BusEvent, Error, EventBus};
+pub use crate::event_bus::{BusEvent, Error, EventBus, QueueStats, SendTimeoutError};
 use std::any::TypeId;
 
 mod macros;
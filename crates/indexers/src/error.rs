@@ -0,0 +1,82 @@
+//! This module provides [`IndexerError`] returned by [`BitcoinBlockIndexer`].
+//!
+//! [`BitcoinBlockIndexer`]: crate::BitcoinBlockIndexer
+
+use bitcoin::BlockHash;
+use yuv_storage::KeyValueError;
+
+/// Result of [`BitcoinBlockIndexer`] operations.
+///
+/// [`BitcoinBlockIndexer`]: crate::BitcoinBlockIndexer
+pub type IndexerResult<T> = Result<T, IndexerError>;
+
+/// Error returned by [`BitcoinBlockIndexer`].
+///
+/// [`BitcoinBlockIndexer`]: crate::BitcoinBlockIndexer
+#[derive(Debug, thiserror::Error)]
+pub enum IndexerError {
+    /// Loaded block doesn't follow the last indexed one.
+    #[error(
+        "Blocks must be sequential, indexer_last_block_height: {expected} != block height: {got}"
+    )]
+    NonSequentialBlocks { expected: usize, got: usize },
+    /// Bitcoin RPC call has failed.
+    #[error("Bitcoin RPC error: {0}")]
+    Rpc(#[from] bitcoin_client::Error),
+    /// Indexer storage has failed.
+    #[error("Storage error: {0}")]
+    Storage(#[from] KeyValueError),
+    /// Indexing was stopped by the cancellation token.
+    #[error("Cancelled node running, failed to index new blocks")]
+    Cancelled,
+    /// Latest confirmed block is not a parent of the next block to index.
+    #[error(
+        "Latest confirmed block is not a parent of the next block to index. Possibly the \
+        confirmation number is too low and reorg happened. Confirmed block hash: \
+        {confirmed_block_hash:?}, new confirmed block previous hash: {previous_block_hash:?}, \
+        next block hash: {block_hash:?}"
+    )]
+    Reorg {
+        confirmed_block_hash: Option<BlockHash>,
+        previous_block_hash: Option<BlockHash>,
+        block_hash: BlockHash,
+    },
+    /// Blocks to index are pruned by the Bitcoin node and skipping them is disabled.
+    #[error(
+        "Blocks from height {height} are pruned by the Bitcoin node, the earliest available \
+        block is at height {prune_height}. Use a not pruned node or enable `skip_pruned_blocks` \
+        to start indexing from the earliest available block"
+    )]
+    PrunedBlocks { height: u64, prune_height: u64 },
+    /// [`Subindexer`](crate::Subindexer) has failed to handle a block.
+    #[error("Failed to handle new block: {0:#}")]
+    Subindexer(eyre::Report),
+    /// [`BlockLoader`](crate::BlockLoader) has failed to load blocks.
+    #[error("BlockLoader error: {0:#}")]
+    BlockLoader(eyre::Report),
+}
+
+impl IndexerError {
+    /// Returns `true` if the error is caused by a Bitcoin RPC failure that is likely to go away
+    /// on its own, e.g. the node is temporarily unreachable.
+    pub fn is_transient(&self) -> bool {
+        matches!(self, Self::Rpc(err) if err.is_transient())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_only_transient_rpc_errors_are_transient() {
+        let transient = IndexerError::Rpc(bitcoin_client::Error::Io(
+            std::io::ErrorKind::ConnectionRefused.into(),
+        ));
+        let permanent = IndexerError::Rpc(bitcoin_client::Error::UnexpectedStructure);
+
+        assert!(transient.is_transient());
+        assert!(!permanent.is_transient());
+        assert!(!IndexerError::Cancelled.is_transient());
+    }
+}
@@ -2,7 +2,6 @@
 
 use bitcoin::BlockHash;
 use bitcoin_client::{json::GetBlockTxResult, BitcoinRpcApi, BitcoinRpcClient};
-use eyre::{bail, Context};
 use futures::TryFutureExt;
 use std::sync::Arc;
 use std::time::Duration;
@@ -10,12 +9,15 @@ use tokio::sync::mpsc;
 use tokio::time;
 use tokio_util::sync::CancellationToken;
 use tracing::instrument;
+use yuv_metrics::METRICS;
 use yuv_storage::{BlockIndexerStorage, IsIndexedStorage};
 use yuv_types::{network::Network, DEFAULT_CONFIRMATIONS_NUMBER};
 
 use crate::{
     blockloader::{BlockLoaderConfig, IndexBlocksEvent},
+    error::{IndexerError, IndexerResult},
     params::RunParams,
+    retry::{with_retry, RpcRetryConfig},
     BlockLoader, IndexingParams, Subindexer,
 };
 
@@ -48,6 +50,10 @@ where
     confirmed_block_height: usize,
     /// Contains the hash of the best confirmed block.
     confirmed_block_hash: Option<BlockHash>,
+    /// Retries of Bitcoin RPC calls made while polling new blocks.
+    retry_config: RpcRetryConfig,
+    /// If `true`, blocks pruned by the Bitcoin node are skipped instead of failing indexing.
+    skip_pruned_blocks: bool,
 }
 
 impl<BS, BC> BitcoinBlockIndexer<BS, BC>
@@ -69,9 +75,24 @@ where
             confirmed_block_height: 0,
             confirmed_block_hash: None,
             network,
+            retry_config: RpcRetryConfig::default(),
+            skip_pruned_blocks: false,
         }
     }
 
+    /// Start indexing from the earliest available block if the blocks to index are pruned by
+    /// the Bitcoin node, instead of failing with an error.
+    pub fn with_skip_pruned_blocks(mut self, skip_pruned_blocks: bool) -> Self {
+        self.skip_pruned_blocks = skip_pruned_blocks;
+        self
+    }
+
+    /// Set the configuration of retries of Bitcoin RPC calls made while polling new blocks.
+    pub fn with_retry_config(mut self, retry_config: RpcRetryConfig) -> Self {
+        self.retry_config = retry_config;
+        self
+    }
+
     /// Add a new [`Subindexer`] to the indexer.
     pub fn add_subindexer<I>(&mut self, indexer: I)
     where
@@ -83,6 +104,11 @@ where
     /// Start indexing missed blocks from Bitcoin.
     ///
     /// At start of the node, call this functions to index missed blocks and be up to date.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`IndexerError::Cancelled`] if the `cancellation` was triggered before the missed
+    /// blocks were indexed.
     #[instrument(skip_all)]
     pub async fn init(
         &mut self,
@@ -90,7 +116,7 @@ where
         block_loader_config: BlockLoaderConfig,
         bitcoin_client: Arc<BitcoinRpcClient>,
         cancellation: CancellationToken,
-    ) -> eyre::Result<()> {
+    ) -> IndexerResult<()> {
         let starting_block_height = self.get_starting_block_height(&params).await?;
 
         tracing::info!(
@@ -113,7 +139,8 @@ where
             block_loader_config.worker_time_sleep as u64,
             cancellation.child_token(),
         ))
-        .map_err(|err| eyre::eyre!("failed to run block loader: {}", err));
+        .map_err(|err| eyre::eyre!("failed to run block loader: {}", err))
+        .map_err(IndexerError::BlockLoader);
 
         let (blockloader_result, indexer_result) = tokio::join!(
             handle,
@@ -126,16 +153,17 @@ where
         // 3 conditon - Either received only blockloader join handle error or only blockloader error
         match (blockloader_result, indexer_result) {
             (Ok(Ok(_)), Err(indexer_error)) => return Err(indexer_error),
-            (Ok(Err(blockloader_error)), Err(indexer_error))
-            | (Err(blockloader_error), Err(indexer_error)) => {
-                bail!(
-                    "BlockLoader error: {}, Indexer error: {}",
-                    blockloader_error,
-                    indexer_error
-                )
+            (Ok(Err(blockloader_error)), Err(indexer_error)) => {
+                tracing::error!("BlockLoader error: {:#}", blockloader_error);
+                return Err(indexer_error);
             }
-            (Err(blockloader_error), Ok(_)) | (Ok(Err(blockloader_error)), Ok(_)) => {
-                return Err(blockloader_error)
+            (Err(blockloader_error), Err(indexer_error)) => {
+                tracing::error!("{}", blockloader_error);
+                return Err(indexer_error);
+            }
+            (Err(blockloader_error), Ok(_)) => return Err(blockloader_error),
+            (Ok(Err(blockloader_error)), Ok(_)) => {
+                return Err(IndexerError::BlockLoader(blockloader_error))
             }
 
             _ => {}
@@ -169,7 +197,7 @@ where
     /// if [`IndexingParams::starting_block_hash`] is not provided and there is no `last_indexed_hash` in the storage.
     /// Returns `last_indexed_height` if `starting_block_hash` is not provided
     /// and vice versa
-    async fn get_starting_block_height(&self, params: &IndexingParams) -> eyre::Result<usize> {
+    async fn get_starting_block_height(&self, params: &IndexingParams) -> IndexerResult<usize> {
         // Starting block height depends on the YUV genesis block for the given network.
         // If the genesis block is not defined for the given network, e.g. `network::Regtest`,
         // the height is set to 0.
@@ -200,9 +228,44 @@ where
             starting_block_height = self.get_block_height(&staring_block_hash).await?;
         }
 
+        if let Some(prune_height) = self.get_prune_height().await? {
+            if (starting_block_height as u64) < prune_height {
+                self.ensure_pruned_blocks_skipped(starting_block_height as u64, prune_height)?;
+
+                starting_block_height = prune_height as usize;
+            }
+        }
+
         Ok(starting_block_height)
     }
 
+    /// Returns the height of the first block that is not pruned by the Bitcoin node, or `None`
+    /// if the node is not pruned.
+    async fn get_prune_height(&self) -> IndexerResult<Option<u64>> {
+        let info = self.bitcoin_client.get_blockchain_info().await?;
+
+        Ok(info.prune_height.filter(|_| info.pruned))
+    }
+
+    /// Returns an error if the blocks from `height` to `prune_height` can't be skipped, otherwise
+    /// logs the gap of not indexed blocks.
+    fn ensure_pruned_blocks_skipped(&self, height: u64, prune_height: u64) -> IndexerResult<()> {
+        if !self.skip_pruned_blocks {
+            return Err(IndexerError::PrunedBlocks {
+                height,
+                prune_height,
+            });
+        }
+
+        tracing::warn!(
+            from = height,
+            to = prune_height - 1,
+            "Blocks are pruned by the Bitcoin node and won't be indexed",
+        );
+
+        Ok(())
+    }
+
     /// Run indexer in loop, polling new blocks from Bitcoin RPC.
     pub async fn run(mut self, params: RunParams, cancellation: CancellationToken) {
         tracing::info!("Starting bitcoin indexer, parameters: {:?}", params);
@@ -220,6 +283,11 @@ where
             }
 
             if let Err(err) = self.handle_new_blocks().await {
+                if matches!(err, IndexerError::Cancelled) {
+                    tracing::trace!("Cancellation received, stopping indexer");
+                    return;
+                }
+
                 if restart_number >= MAX_NUMBER_OF_RESTART_ATTEMPTS {
                     tracing::error!("Indexer restart attempts number exceeded");
                     break;
@@ -259,7 +327,7 @@ where
         &mut self,
         mut rx_indexer: mpsc::Receiver<IndexBlocksEvent>,
         mut indexer_last_block_height: usize,
-    ) -> eyre::Result<()> {
+    ) -> IndexerResult<()> {
         while let Some(event) = rx_indexer.recv().await {
             match event {
                 IndexBlocksEvent::FinishLoading => {
@@ -270,9 +338,7 @@ where
                     self.init_blocks_handle(blocks, &mut indexer_last_block_height)
                         .await?;
                 }
-                IndexBlocksEvent::Cancelled => {
-                    bail!("Cancelled node running, failed to index new blocks")
-                }
+                IndexBlocksEvent::Cancelled => return Err(IndexerError::Cancelled),
             }
         }
 
@@ -285,14 +351,13 @@ where
         &mut self,
         blocks: Vec<GetBlockTxResult>,
         indexer_last_block_height: &mut usize,
-    ) -> eyre::Result<()> {
+    ) -> IndexerResult<()> {
         for block in blocks {
             if block.block_data.height.ne(indexer_last_block_height) {
-                bail!(
-                    "Blocks must be sequential, indexer_last_block_height: {} != block height: {}",
-                    indexer_last_block_height,
-                    block.block_data.height
-                );
+                return Err(IndexerError::NonSequentialBlocks {
+                    expected: *indexer_last_block_height,
+                    got: block.block_data.height,
+                });
             }
 
             self.index_block(&block).await?;
@@ -310,18 +375,21 @@ where
     }
 
     /// Takes block, indexes it and puts its hash to storage as a `last_indexed_hash`.
-    async fn index_block(&mut self, block: &GetBlockTxResult) -> eyre::Result<()> {
+    async fn index_block(&mut self, block: &GetBlockTxResult) -> IndexerResult<()> {
         for indexer in self.subindexers.iter_mut() {
             indexer
                 .index(block)
                 .await
-                .wrap_err("failed to handle new block")?;
+                .map_err(IndexerError::Subindexer)?;
         }
 
         self.storage
             .put_last_indexed_hash(block.block_data.hash)
             .await?;
 
+        METRICS.blocks_indexed.inc();
+        METRICS.indexed_height.set(block.block_data.height as u64);
+
         Ok(())
     }
 
@@ -334,21 +402,32 @@ where
     ///     - If there is a new confirmed block, then go to step 2.
     /// 2. Get the next block by height [confirmed block height] + 1.
     /// 3. Check if the hash of the latest confirmed block is equal to the previous hash of the new
-    ///    block.
+    ///    block. If it's not, [disconnect] the blocks reorged out of the best chain and go to the
+    ///    step 1.
     /// 4. Provide the block to every subindexer and update the storage.
     /// 5. Go to the step 1.
     ///
     /// [confirmed block height]: BitcoinBlockIndexer::check_new_confirmed_block
-    async fn handle_new_blocks(&mut self) -> eyre::Result<()> {
+    /// [disconnect]: BitcoinBlockIndexer::disconnect_stale_blocks
+    async fn handle_new_blocks(&mut self) -> IndexerResult<()> {
         loop {
             if !self.check_new_confirmed_block().await? {
                 break;
             }
 
-            let block = self
-                .get_block_by_height(self.confirmed_block_height as u64 + 1)
-                .await
-                .wrap_err("failed to get block by hash")?;
+            let next_block_height = self.confirmed_block_height as u64 + 1;
+            let block = match self.get_block_by_height(next_block_height).await {
+                Ok(block) => block,
+                // The block could have been pruned by the Bitcoin node while the indexer was
+                // behind, so skip to the earliest available one if it's allowed.
+                Err(err) => {
+                    if self.skip_pruned_blocks_from(next_block_height).await? {
+                        continue;
+                    }
+
+                    return Err(err);
+                }
+            };
 
             let confirmed_block_hash = self.confirmed_block_hash;
             let new_block_previous_hash = block.block_data.previousblockhash;
@@ -356,14 +435,16 @@ where
             let new_block_height = block.block_data.height;
 
             if confirmed_block_hash != new_block_previous_hash {
-                bail!(
-                    "Latest confirmed block is not a parent of the next block to index. Possibly \
-                    the confirmation number is too low and reorg happened. Confirmed block hash: \
-                    {:?}, new confirmed block previous hash: {:?}, next block hash: {:?}",
-                    confirmed_block_hash,
-                    new_block_previous_hash,
-                    new_block_hash,
-                );
+                let Some(confirmed_block_hash) = confirmed_block_hash else {
+                    return Err(IndexerError::Reorg {
+                        confirmed_block_hash,
+                        previous_block_hash: new_block_previous_hash,
+                        block_hash: new_block_hash,
+                    });
+                };
+
+                self.disconnect_stale_blocks(confirmed_block_hash).await?;
+                continue;
             }
 
             tracing::trace!(
@@ -381,6 +462,91 @@ where
         Ok(())
     }
 
+    /// Disconnect the indexed blocks which are no longer in the best chain, from the block with
+    /// `hash` down to the common ancestor, which becomes the latest confirmed block. The blocks
+    /// of the new best chain are indexed after it as usual.
+    ///
+    /// If the new best chain is shorter than the indexed one, the blocks above its tip are
+    /// disconnected without the lookup in it.
+    async fn disconnect_stale_blocks(&mut self, mut hash: BlockHash) -> IndexerResult<()> {
+        let mut height = self.confirmed_block_height;
+        let best_block_height = self.get_best_block_height().await?;
+
+        loop {
+            if height <= best_block_height {
+                let best_chain_hash = with_retry(&self.retry_config, "getblockhash", || {
+                    self.bitcoin_client.get_block_hash(height as u64)
+                })
+                .await?;
+
+                if best_chain_hash == hash {
+                    break;
+                }
+            }
+
+            let block = self.get_block(hash).await?;
+
+            tracing::warn!(height, hash = ?hash, "Disconnecting block reorged out of the best chain");
+
+            for indexer in self.subindexers.iter_mut() {
+                indexer
+                    .disconnect(&block)
+                    .await
+                    .map_err(IndexerError::Subindexer)?;
+            }
+
+            let Some(previous_hash) = block.block_data.previousblockhash else {
+                return Err(IndexerError::Reorg {
+                    confirmed_block_hash: self.confirmed_block_hash,
+                    previous_block_hash: None,
+                    block_hash: hash,
+                });
+            };
+
+            hash = previous_hash;
+            height -= 1;
+        }
+
+        self.storage.put_last_indexed_hash(hash).await?;
+
+        self.confirmed_block_height = height;
+        self.confirmed_block_hash = Some(hash);
+
+        Ok(())
+    }
+
+    /// Move the latest confirmed block to the one before the earliest available block, if the
+    /// block at `height` is pruned by the Bitcoin node.
+    ///
+    /// # Returns
+    ///
+    /// - `Ok(true)` if the pruned blocks are skipped.
+    /// - `Ok(false)` if the block at `height` is not pruned.
+    async fn skip_pruned_blocks_from(&mut self, height: u64) -> IndexerResult<bool> {
+        let Some(prune_height) = self.get_prune_height().await? else {
+            return Ok(false);
+        };
+
+        if height >= prune_height {
+            return Ok(false);
+        }
+
+        self.ensure_pruned_blocks_skipped(height, prune_height)?;
+
+        // Headers of pruned blocks are still available, so the parent check of the next block
+        // to index will pass.
+        let last_pruned_height = prune_height - 1;
+        let last_pruned_hash = with_retry(&self.retry_config, "getblockhash", || {
+            self.bitcoin_client.get_block_hash(last_pruned_height)
+        })
+        .await?;
+
+        self.confirmed_block_height = last_pruned_height as usize;
+        self.confirmed_block_hash = Some(last_pruned_hash);
+
+        Ok(true)
+    }
+
     /// Check if there is a block with height [confirmed block height] + [confirmation number], that
     /// means there is a new confirmed block.
     ///
@@ -391,32 +557,222 @@ where
     ///
     /// [confirmation number]: BitcoinBlockIndexer::confirmation_number
     /// [confirmed block height]: BitcoinBlockIndexer::confirmed_block_height
-    async fn check_new_confirmed_block(&self) -> eyre::Result<bool> {
+    async fn check_new_confirmed_block(&self) -> IndexerResult<bool> {
         let new_confirmation_height =
             self.confirmed_block_height + self.confirmation_number as usize;
 
-        let best_block_height = self.bitcoin_client.get_block_count().await?;
+        let best_block_height = self.get_best_block_height().await?;
 
-        Ok(new_confirmation_height <= best_block_height as usize)
+        Ok(new_confirmation_height <= best_block_height)
+    }
+
+    /// Returns the height of the best block.
+    async fn get_best_block_height(&self) -> IndexerResult<usize> {
+        let best_block_height = with_retry(&self.retry_config, "getblockcount", || {
+            self.bitcoin_client.get_block_count()
+        })
+        .await?;
+        METRICS.best_height.set(best_block_height);
+
+        Ok(best_block_height as usize)
     }
 
     /// Returns the best block height by block hash.
-    async fn get_block_height(&self, hash: &BlockHash) -> eyre::Result<usize> {
+    async fn get_block_height(&self, hash: &BlockHash) -> IndexerResult<usize> {
         let block = self.bitcoin_client.get_block_info(hash).await?;
         Ok(block.block_data.height)
     }
 
     /// Returns the block with transactions by height.
-    async fn get_block_by_height(&self, height: u64) -> eyre::Result<GetBlockTxResult> {
-        let block_hash = self.bitcoin_client.get_block_hash(height).await?;
+    async fn get_block_by_height(&self, height: u64) -> IndexerResult<GetBlockTxResult> {
+        let block_hash = with_retry(&self.retry_config, "getblockhash", || {
+            self.bitcoin_client.get_block_hash(height)
+        })
+        .await?;
+
         self.get_block(block_hash).await
     }
 
     /// Returns block with transactions by block hash.
-    async fn get_block(&self, hash: BlockHash) -> eyre::Result<GetBlockTxResult> {
-        self.bitcoin_client
-            .get_block_txs(&hash)
-            .await
-            .wrap_err("failed to get block info by hash")
+    async fn get_block(&self, hash: BlockHash) -> IndexerResult<GetBlockTxResult> {
+        let block = with_retry(&self.retry_config, "getblock", || {
+            self.bitcoin_client.get_block_txs(&hash)
+        })
+        .await?;
+
+        Ok(block)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use std::sync::Mutex;
+
+    use bitcoin::hashes::Hash;
+    use bitcoin::TxMerkleNode;
+    use bitcoin_client::json::BlockData;
+    use bitcoin_client::MockRpcApi;
+    use yuv_storage::LevelDB;
+
+    use super::*;
+
+    /// Sub-indexer which records the hashes of the indexed and disconnected blocks.
+    #[derive(Clone, Default)]
+    struct RecordingSubindexer {
+        indexed: Arc<Mutex<Vec<BlockHash>>>,
+        disconnected: Arc<Mutex<Vec<BlockHash>>>,
+    }
+
+    #[async_trait::async_trait]
+    impl Subindexer for RecordingSubindexer {
+        async fn index(&mut self, block: &GetBlockTxResult) -> eyre::Result<()> {
+            self.indexed.lock().unwrap().push(block.block_data.hash);
+            Ok(())
+        }
+
+        async fn disconnect(&mut self, block: &GetBlockTxResult) -> eyre::Result<()> {
+            self.disconnected
+                .lock()
+                .unwrap()
+                .push(block.block_data.hash);
+            Ok(())
+        }
+    }
+
+    fn hash(byte: u8) -> BlockHash {
+        BlockHash::from_inner([byte; 32])
+    }
+
+    fn block(height: usize, hash: BlockHash, previous_hash: BlockHash) -> GetBlockTxResult {
+        GetBlockTxResult {
+            block_data: BlockData {
+                hash,
+                confirmations: 1,
+                size: 0,
+                strippedsize: None,
+                weight: 0,
+                height,
+                version: 2,
+                version_hex: None,
+                merkleroot: TxMerkleNode::all_zeros(),
+                time: 0,
+                mediantime: None,
+                nonce: 0,
+                bits: String::new(),
+                difficulty: 0.0,
+                chainwork: Vec::new(),
+                n_tx: 0,
+                previousblockhash: Some(previous_hash),
+                nextblockhash: None,
+            },
+            tx: Vec::new(),
+        }
+    }
+
+    /// Bitcoin RPC with the best chain `best_chain` (hashes by height) and the `blocks`.
+    fn bitcoin_client(
+        best_chain: Vec<BlockHash>,
+        blocks: Vec<GetBlockTxResult>,
+    ) -> Arc<MockRpcApi> {
+        let mut client = MockRpcApi::new();
+
+        let best_height = best_chain.len() as u64 - 1;
+        client
+            .expect_call::<u64>()
+            .withf(|method, _| method == "getblockcount")
+            .returning(move |_, _| Ok(best_height));
+
+        client
+            .expect_get_block_hash()
+            .returning(move |height| Ok(best_chain[height as usize]));
+
+        let blocks: HashMap<_, _> = blocks
+            .into_iter()
+            .map(|block| (block.block_data.hash, block))
+            .collect();
+        client
+            .expect_get_block_txs()
+            .returning(move |hash| Ok(blocks[hash].clone()));
+
+        Arc::new(client)
+    }
+
+    #[tokio::test]
+    async fn test_reorged_blocks_are_disconnected_and_new_chain_is_indexed() {
+        let (genesis, ancestor) = (hash(0), hash(1));
+        let stale = hash(2);
+        let (new_second, new_third) = (hash(3), hash(4));
+
+        let client = bitcoin_client(
+            vec![genesis, ancestor, new_second, new_third],
+            vec![
+                block(1, ancestor, genesis),
+                block(2, stale, ancestor),
+                block(2, new_second, ancestor),
+                block(3, new_third, new_second),
+            ],
+        );
+
+        let storage = LevelDB::in_memory().unwrap();
+        let mut indexer =
+            BitcoinBlockIndexer::new(client, storage.clone(), Some(1), Network::Regtest);
+        let subindexer = RecordingSubindexer::default();
+        indexer.add_subindexer(subindexer.clone());
+
+        indexer.confirmed_block_height = 2;
+        indexer.confirmed_block_hash = Some(stale);
+
+        indexer.handle_new_blocks().await.unwrap();
+
+        assert_eq!(*subindexer.disconnected.lock().unwrap(), vec![stale]);
+        assert_eq!(
+            *subindexer.indexed.lock().unwrap(),
+            vec![new_second, new_third]
+        );
+        assert_eq!(indexer.confirmed_block_height, 3);
+        assert_eq!(
+            storage.get_last_indexed_hash().await.unwrap(),
+            Some(new_third)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_blocks_above_shorter_best_chain_are_disconnected() {
+        let (genesis, ancestor) = (hash(0), hash(1));
+        let (stale_second, stale_third) = (hash(2), hash(3));
+        let new_second = hash(4);
+
+        let client = bitcoin_client(
+            vec![genesis, ancestor, new_second],
+            vec![
+                block(1, ancestor, genesis),
+                block(2, stale_second, ancestor),
+                block(3, stale_third, stale_second),
+                block(2, new_second, ancestor),
+            ],
+        );
+
+        let storage = LevelDB::in_memory().unwrap();
+        let mut indexer =
+            BitcoinBlockIndexer::new(client, storage.clone(), Some(1), Network::Regtest);
+        let subindexer = RecordingSubindexer::default();
+        indexer.add_subindexer(subindexer.clone());
+
+        indexer.confirmed_block_height = 3;
+        indexer.confirmed_block_hash = Some(stale_third);
+
+        indexer.disconnect_stale_blocks(stale_third).await.unwrap();
+
+        assert_eq!(
+            *subindexer.disconnected.lock().unwrap(),
+            vec![stale_third, stale_second]
+        );
+        assert_eq!(indexer.confirmed_block_height, 1);
+        assert_eq!(indexer.confirmed_block_hash, Some(ancestor));
+        assert_eq!(
+            storage.get_last_indexed_hash().await.unwrap(),
+            Some(ancestor)
+        );
     }
 }
@@ -4,6 +4,9 @@
 mod params;
 pub use params::{IndexingParams, RunParams};
 
+mod error;
+pub use error::{IndexerError, IndexerResult};
+
 mod indexer;
 pub use indexer::BitcoinBlockIndexer;
 
@@ -12,3 +15,6 @@ pub use subindexer::{AnnouncementsIndexer, ConfirmationIndexer, Subindexer};
 
 mod blockloader;
 pub use blockloader::{BlockLoader, BlockLoaderConfig};
+
+mod retry;
+pub use retry::RpcRetryConfig;
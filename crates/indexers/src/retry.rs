@@ -0,0 +1,134 @@
+//! This module provides retries with exponential backoff for Bitcoin RPC calls.
+
+use std::future::Future;
+use std::time::Duration;
+
+use serde::Deserialize;
+
+/// Configuration of retries of failed Bitcoin RPC calls.
+#[derive(Deserialize, Clone, Debug)]
+pub struct RpcRetryConfig {
+    /// Max number of attempts to make a call, including the first one.
+    #[serde(default = "default_max_attempts")]
+    pub max_attempts: u32,
+    /// Delay before the first retry, which is doubled with each next one.
+    #[serde(default = "default_base_delay")]
+    pub base_delay: Duration,
+}
+
+fn default_max_attempts() -> u32 {
+    4
+}
+
+fn default_base_delay() -> Duration {
+    Duration::from_millis(500)
+}
+
+impl Default for RpcRetryConfig {
+    fn default() -> Self {
+        Self {
+            max_attempts: default_max_attempts(),
+            base_delay: default_base_delay(),
+        }
+    }
+}
+
+/// Make a Bitcoin RPC call, retrying it with exponential backoff if it has failed with a
+/// [transient] error. Permanent errors are returned immediately.
+///
+/// [transient]: bitcoin_client::Error::is_transient
+pub(crate) async fn with_retry<T, F, Fut>(
+    config: &RpcRetryConfig,
+    method: &str,
+    mut call: F,
+) -> bitcoin_client::Result<T>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = bitcoin_client::Result<T>>,
+{
+    let mut delay = config.base_delay;
+    let mut attempt = 1;
+
+    loop {
+        match call().await {
+            Err(err) if err.is_transient() && attempt < config.max_attempts => {
+                tracing::warn!(
+                    "Bitcoin RPC call {method} failed, retrying {attempt}/{} in {}ms: {err}",
+                    config.max_attempts - 1,
+                    delay.as_millis(),
+                );
+
+                tokio::time::sleep(delay).await;
+
+                delay = delay.saturating_mul(2);
+                attempt += 1;
+            }
+            result => return result,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    use bitcoin_client::Error;
+
+    use super::*;
+
+    const TEST_CONFIG: RpcRetryConfig = RpcRetryConfig {
+        max_attempts: 3,
+        base_delay: Duration::from_millis(1),
+    };
+
+    fn transient_error() -> Error {
+        Error::Io(std::io::ErrorKind::ConnectionRefused.into())
+    }
+
+    #[tokio::test]
+    async fn test_transient_errors_are_retried() {
+        let calls = AtomicU32::new(0);
+
+        let result = with_retry(&TEST_CONFIG, "getblockcount", || async {
+            if calls.fetch_add(1, Ordering::SeqCst) < 2 {
+                return Err(transient_error());
+            }
+
+            Ok(42)
+        })
+        .await;
+
+        assert_eq!(result.unwrap(), 42);
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn test_attempts_are_bounded() {
+        let calls = AtomicU32::new(0);
+
+        let result = with_retry(&TEST_CONFIG, "getblockcount", || async {
+            calls.fetch_add(1, Ordering::SeqCst);
+
+            Err::<u64, _>(transient_error())
+        })
+        .await;
+
+        assert!(result.is_err());
+        assert_eq!(calls.load(Ordering::SeqCst), TEST_CONFIG.max_attempts);
+    }
+
+    #[tokio::test]
+    async fn test_permanent_errors_are_not_retried() {
+        let calls = AtomicU32::new(0);
+
+        let result = with_retry(&TEST_CONFIG, "getblock", || async {
+            calls.fetch_add(1, Ordering::SeqCst);
+
+            Err::<u64, _>(Error::UnexpectedStructure)
+        })
+        .await;
+
+        assert!(matches!(result, Err(Error::UnexpectedStructure)));
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+    }
+}
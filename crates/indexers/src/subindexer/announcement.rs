@@ -2,30 +2,69 @@
 
 use async_trait::async_trait;
 
+use bitcoin::Transaction;
 use bitcoin_client::json::GetBlockTxResult;
 use event_bus::{typeid, EventBus};
-use yuv_types::announcements::{announcement_from_script, ParseOpReturnError};
-use yuv_types::{ControllerMessage, YuvTransaction, YuvTxType};
+use yuv_storage::{ChromaInfoStorage, FrozenTxsStorage};
+use yuv_types::announcements::{
+    announcement_from_witness, ChromaAnnouncement, ParseOpReturnError, ParseWitnessError,
+    YuvOpReturn,
+};
+use yuv_types::{Announcement, ControllerMessage, TxCheckerMessage, YuvTransaction, YuvTxType};
 
 use super::Subindexer;
 
 /// A sub-indexer which gets announcements from blocks and sends them to message handler.
-pub struct AnnouncementsIndexer {
-    /// Event bus to notify controller about new announcements.
+///
+/// It also stores the heights of the freeze announcements, so the frozen state could be got as
+/// of the given height, and of the chroma announcements, so the issuances could require the
+/// announcement of their chroma to be indexed. The issuances that wait for the announcement are
+/// sent to the checkers again once it's indexed.
+///
+/// Announcements in the blocks below the activation height are ignored, as the YUV protocol
+/// wasn't active at that time.
+pub struct AnnouncementsIndexer<StateStorage> {
+    /// Event bus to notify controller about new announcements, and checkers about reorged
+    /// freezes.
     event_bus: EventBus,
+    /// Storage of the freezes' and chroma announcements' heights.
+    state_storage: StateStorage,
+    /// Height of the first block which announcements are indexed.
+    activation_height: u64,
 }
 
-impl AnnouncementsIndexer {
-    pub fn new(full_event_bus: &EventBus) -> Self {
+impl<SS> AnnouncementsIndexer<SS>
+where
+    SS: FrozenTxsStorage + ChromaInfoStorage + Send + Sync + 'static,
+{
+    pub fn new(full_event_bus: &EventBus, state_storage: SS) -> Self {
         let event_bus = full_event_bus
-            .extract(&typeid![ControllerMessage], &[])
+            .extract(&typeid![ControllerMessage, TxCheckerMessage], &[])
             .expect("message to message handler must be registered");
 
-        Self { event_bus }
+        Self {
+            event_bus,
+            state_storage,
+            activation_height: 0,
+        }
+    }
+
+    /// Set the height of the first block which announcements are indexed.
+    pub fn with_activation_height(mut self, activation_height: u64) -> Self {
+        self.activation_height = activation_height;
+        self
     }
 
     /// Finds announcements in a block and sends them to message handler.
     async fn find_announcements(&self, block: &GetBlockTxResult) -> eyre::Result<()> {
+        if (block.block_data.height as u64) < self.activation_height {
+            tracing::trace!(
+                height = block.block_data.height,
+                "Skipping announcements of the block below the activation height"
+            );
+            return Ok(());
+        }
+
         let mut txs = Vec::new();
 
         // For each transaction, try to find announcements.
@@ -34,28 +73,28 @@ impl AnnouncementsIndexer {
                 continue;
             }
 
-            let mut announcement_opt = None;
-
-            // In each transaction output: If it's not an OP_RETURN script - skip it, otherwise
-            // push it to announcements.
-            for output in tx.output.iter() {
-                match announcement_from_script(&output.script_pubkey) {
-                    Ok(announcement) => {
-                        announcement_opt = Some(announcement.clone());
-                    }
-                    Err(ParseOpReturnError::InvaliOpReturnData(err)) => {
-                        tracing::debug!("Found invalid announcement: {err}");
-                    }
-                    _ => {}
-                };
-            }
-
-            let Some(announcement) = announcement_opt else {
+            let Some(announcement) = find_announcement(tx) else {
                 continue;
             };
 
             tracing::debug!("found announcement in tx {}", tx.txid());
 
+            let height = block.block_data.height as u64;
+            match &announcement {
+                Announcement::Freeze(_) | Announcement::ChromaFreeze(_) => {
+                    self.state_storage
+                        .put_freeze_height(&tx.txid(), height)
+                        .await?;
+                }
+                Announcement::Chroma(chroma_announcement) => {
+                    self.state_storage
+                        .put_chroma_announcement_height(&chroma_announcement.chroma, height)
+                        .await?;
+                    self.resubmit_pending_issuances(chroma_announcement).await?;
+                }
+                Announcement::Issue(_) => {}
+            }
+
             txs.push(YuvTransaction {
                 bitcoin_tx: tx.clone(),
                 tx_type: YuvTxType::Announcement(announcement),
@@ -70,11 +109,344 @@ impl AnnouncementsIndexer {
 
         Ok(())
     }
+
+    /// Sends the issuances that waited for the chroma announcement to be indexed to the checkers.
+    ///
+    /// The checkers resubmit them only when they check the announcement, which doesn't happen
+    /// for the indexed one if the announcement was received from the peers or RPC before, as the
+    /// controller drops the known transactions.
+    async fn resubmit_pending_issuances(
+        &self,
+        announcement: &ChromaAnnouncement,
+    ) -> eyre::Result<()> {
+        let txs = self
+            .state_storage
+            .take_pending_issuances(&announcement.chroma)
+            .await?;
+        if txs.is_empty() {
+            return Ok(());
+        }
+
+        tracing::debug!(
+            "Resubmitting {} pending issuances of Chroma {} after its announcement is indexed",
+            txs.len(),
+            announcement.chroma
+        );
+
+        self.event_bus
+            .send(TxCheckerMessage::NewTxs { txs, sender: None })
+            .await;
+
+        Ok(())
+    }
+
+    /// Finds freeze announcements in a block disconnected by a reorg and sends them to checkers
+    /// to revert.
+    async fn revert_freezes(&self, block: &GetBlockTxResult) -> eyre::Result<()> {
+        if (block.block_data.height as u64) < self.activation_height {
+            return Ok(());
+        }
+
+        let mut txs = Vec::new();
+
+        for tx in &block.tx {
+            if tx.is_coin_base() {
+                continue;
+            }
+
+            let Some(announcement @ (Announcement::Freeze(_) | Announcement::ChromaFreeze(_))) =
+                find_announcement(tx)
+            else {
+                continue;
+            };
+
+            tracing::debug!("reverting freeze announcement in tx {}", tx.txid());
+
+            self.state_storage.delete_freeze_height(&tx.txid()).await?;
+
+            txs.push(YuvTransaction {
+                bitcoin_tx: tx.clone(),
+                tx_type: YuvTxType::Announcement(announcement),
+            })
+        }
+
+        if !txs.is_empty() {
+            self.event_bus
+                .send(TxCheckerMessage::ReorgedFreezes(txs))
+                .await;
+        }
+
+        Ok(())
+    }
+}
+
+/// Finds the announcement of the transaction.
+fn find_announcement(tx: &Transaction) -> Option<Announcement> {
+    let mut announcement_opt = None;
+
+    // In each transaction output: If it's not an OP_RETURN script - skip it, otherwise
+    // push it to announcements.
+    for output in tx.output.iter() {
+        match YuvOpReturn::from_script(&output.script_pubkey) {
+            Ok(op_return) => {
+                if let Some(announcement) = op_return.into_announcement() {
+                    announcement_opt = Some(announcement);
+                }
+            }
+            Err(ParseOpReturnError::InvaliOpReturnData(err)) => {
+                tracing::debug!("Found invalid announcement: {err}");
+            }
+            _ => {}
+        };
+    }
+
+    // Announcements in `OP_RETURN` outputs take precedence over the ones committed in
+    // the Taproot witness envelopes of the inputs.
+    announcement_opt.or_else(|| find_witness_announcement(tx))
+}
+
+/// Finds the first announcement committed in the Taproot witness envelope of the transaction's
+/// inputs.
+fn find_witness_announcement(tx: &Transaction) -> Option<Announcement> {
+    for input in &tx.input {
+        match announcement_from_witness(&input.witness) {
+            Ok(announcement) => return Some(announcement),
+            Err(ParseWitnessError::InvalidEnvelopeData(err)) => {
+                tracing::debug!("Found invalid announcement in witness: {err}");
+            }
+            _ => {}
+        }
+    }
+
+    None
 }
 
 #[async_trait]
-impl Subindexer for AnnouncementsIndexer {
+impl<SS> Subindexer for AnnouncementsIndexer<SS>
+where
+    SS: FrozenTxsStorage + ChromaInfoStorage + Send + Sync + 'static,
+{
     async fn index(&mut self, block: &GetBlockTxResult) -> eyre::Result<()> {
         self.find_announcements(block).await
     }
+
+    async fn disconnect(&mut self, block: &GetBlockTxResult) -> eyre::Result<()> {
+        self.revert_freezes(block).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use bitcoin::hashes::Hash;
+    use bitcoin::secp256k1::{Secp256k1, SecretKey};
+    use bitcoin::{BlockHash, OutPoint, PackedLockTime, Sequence, TxIn, TxMerkleNode, TxOut};
+    use bitcoin_client::json::BlockData;
+    use tokio_util::sync::CancellationToken;
+    use yuv_controller::Controller;
+    use yuv_p2p::client::handle::MockHandle;
+    use yuv_storage::{LevelDB, TransactionsStorage, TxStatesStorage};
+    use yuv_types::announcements::FreezeAnnouncement;
+    use yuv_types::{mocks, TxConfirmMessage};
+
+    use super::*;
+
+    const ACTIVATION_HEIGHT: usize = 100;
+
+    fn freeze_tx() -> Transaction {
+        announcement_tx(FreezeAnnouncement::new(OutPoint::default()).into())
+    }
+
+    fn announcement_tx(announcement: Announcement) -> Transaction {
+        Transaction {
+            version: 2,
+            lock_time: PackedLockTime::ZERO,
+            input: vec![TxIn {
+                previous_output: OutPoint::new(Hash::all_zeros(), 1),
+                sequence: Sequence::MAX,
+                ..Default::default()
+            }],
+            output: vec![TxOut {
+                value: 0,
+                script_pubkey: announcement.to_script(),
+            }],
+        }
+    }
+
+    fn block_at(height: usize, tx: Vec<Transaction>) -> GetBlockTxResult {
+        GetBlockTxResult {
+            block_data: BlockData {
+                hash: BlockHash::all_zeros(),
+                confirmations: 1,
+                size: 0,
+                strippedsize: None,
+                weight: 0,
+                height,
+                version: 2,
+                version_hex: None,
+                merkleroot: TxMerkleNode::all_zeros(),
+                time: 0,
+                mediantime: None,
+                nonce: 0,
+                bits: String::new(),
+                difficulty: 0.0,
+                chainwork: Vec::new(),
+                n_tx: tx.len(),
+                previousblockhash: None,
+                nextblockhash: None,
+            },
+            tx,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_freezes_below_activation_height_are_ignored() {
+        let mut event_bus = EventBus::default();
+        event_bus.register::<ControllerMessage>(Some(100));
+        event_bus.register::<TxCheckerMessage>(Some(100));
+        let receiver = event_bus.subscribe::<ControllerMessage>();
+
+        let storage = LevelDB::in_memory().unwrap();
+        let mut indexer = AnnouncementsIndexer::new(&event_bus, storage.clone())
+            .with_activation_height(ACTIVATION_HEIGHT as u64);
+
+        let tx = freeze_tx();
+        indexer
+            .index(&block_at(ACTIVATION_HEIGHT - 1, vec![tx.clone()]))
+            .await
+            .unwrap();
+
+        assert_eq!(storage.get_freeze_height(&tx.txid()).await.unwrap(), None);
+        assert!(
+            receiver.is_empty(),
+            "announcement below the activation height must not be sent"
+        );
+
+        indexer
+            .index(&block_at(ACTIVATION_HEIGHT, vec![tx.clone()]))
+            .await
+            .unwrap();
+
+        assert_eq!(
+            storage.get_freeze_height(&tx.txid()).await.unwrap(),
+            Some(ACTIVATION_HEIGHT as u64)
+        );
+        let message = receiver.recv().await.unwrap();
+        assert!(
+            matches!(message, ControllerMessage::ConfirmBatchTx(txs) if txs.len() == 1),
+            "announcement at the activation height must be sent"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_disconnected_freezes_are_sent_to_checkers() {
+        let mut event_bus = EventBus::default();
+        event_bus.register::<ControllerMessage>(Some(100));
+        event_bus.register::<TxCheckerMessage>(Some(100));
+        let receiver = event_bus.subscribe::<TxCheckerMessage>();
+
+        let storage = LevelDB::in_memory().unwrap();
+        let mut indexer = AnnouncementsIndexer::new(&event_bus, storage.clone())
+            .with_activation_height(ACTIVATION_HEIGHT as u64);
+
+        let tx = freeze_tx();
+        let block = block_at(ACTIVATION_HEIGHT, vec![tx.clone()]);
+        indexer.index(&block).await.unwrap();
+        indexer.disconnect(&block).await.unwrap();
+
+        assert_eq!(storage.get_freeze_height(&tx.txid()).await.unwrap(), None);
+        let message = receiver.recv().await.unwrap();
+        assert!(
+            matches!(message, TxCheckerMessage::ReorgedFreezes(txs) if txs.len() == 1),
+            "disconnected freeze must be sent to checkers"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_pending_issuances_are_resubmitted_when_known_announcement_is_indexed() {
+        let mut event_bus = EventBus::default();
+        event_bus.register::<ControllerMessage>(Some(100));
+        event_bus.register::<TxCheckerMessage>(Some(100));
+        event_bus.register::<TxConfirmMessage>(Some(100));
+        let checker_receiver = event_bus.subscribe::<TxCheckerMessage>();
+        let confirm_receiver = event_bus.subscribe::<TxConfirmMessage>();
+
+        let storage = LevelDB::in_memory().unwrap();
+
+        let issuer = SecretKey::from_slice(&[1; 32]).unwrap();
+        let issuance = mocks::issuance(&issuer, 100, issuer.public_key(&Secp256k1::new()));
+        let YuvTxType::Issue { announcement, .. } = &issuance.tx_type else {
+            panic!("issuance must be of the issue type");
+        };
+        let chroma = announcement.chroma;
+
+        let chroma_announcement =
+            ChromaAnnouncement::new(chroma, "Test Token".into(), "TEST".into(), 8, 1000, true)
+                .unwrap();
+        let tx = announcement_tx(chroma_announcement.clone().into());
+
+        // The announcement is received from the peers and checked before it's indexed, so the
+        // issuance waits for it to be indexed.
+        storage
+            .put_yuv_tx(YuvTransaction {
+                bitcoin_tx: tx.clone(),
+                tx_type: YuvTxType::Announcement(chroma_announcement.clone().into()),
+            })
+            .await
+            .unwrap();
+        storage
+            .put_chroma_info(&chroma, Some(chroma_announcement), Some(tx.txid()), 0)
+            .await
+            .unwrap();
+        storage
+            .put_pending_issuance(&chroma, issuance.clone())
+            .await
+            .unwrap();
+
+        let mut p2p_handle = MockHandle::new();
+        p2p_handle.expect_send_inv().returning(|_| Ok(()));
+
+        let controller = Controller::new(
+            &event_bus,
+            storage.clone(),
+            storage.clone(),
+            TxStatesStorage::default(),
+            p2p_handle,
+        );
+        let cancellation = CancellationToken::new();
+        let controller_task = tokio::spawn(controller.run(cancellation.clone()));
+
+        let mut indexer = AnnouncementsIndexer::new(&event_bus, storage.clone())
+            .with_activation_height(ACTIVATION_HEIGHT as u64);
+        indexer
+            .index(&block_at(ACTIVATION_HEIGHT, vec![tx]))
+            .await
+            .unwrap();
+
+        let message = tokio::time::timeout(Duration::from_secs(1), checker_receiver.recv())
+            .await
+            .expect("pending issuance must be resubmitted")
+            .unwrap();
+        assert!(
+            matches!(&message, TxCheckerMessage::NewTxs { txs, .. } if *txs == vec![issuance]),
+            "only the pending issuance must be resubmitted: {message:?}"
+        );
+
+        // The controller drops the indexed copy of the known announcement.
+        assert!(
+            tokio::time::timeout(Duration::from_millis(100), confirm_receiver.recv())
+                .await
+                .is_err(),
+            "known announcement must not be checked again"
+        );
+        assert!(storage
+            .take_pending_issuances(&chroma)
+            .await
+            .unwrap()
+            .is_empty());
+
+        cancellation.cancel();
+        controller_task.await.unwrap();
+    }
 }
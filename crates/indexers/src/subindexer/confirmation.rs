@@ -2,7 +2,7 @@ use crate::Subindexer;
 use async_trait::async_trait;
 use bitcoin_client::json::GetBlockTxResult;
 use event_bus::{typeid, EventBus};
-use yuv_types::TxConfirmMessage;
+use yuv_types::{GraphBuilderMessage, TxConfirmMessage};
 
 /// Is responsible for waiting confirmations of transactions in Bitcoin.
 pub struct ConfirmationIndexer {
@@ -12,7 +12,7 @@ pub struct ConfirmationIndexer {
 impl ConfirmationIndexer {
     pub fn new(full_event_bus: &EventBus) -> Self {
         let event_bus = full_event_bus
-            .extract(&typeid![TxConfirmMessage], &typeid![])
+            .extract(&typeid![TxConfirmMessage, GraphBuilderMessage], &typeid![])
             .expect("event channels must be presented");
 
         Self { event_bus }
@@ -25,9 +25,16 @@ impl ConfirmationIndexer {
         let confirmed_txids = block.tx.iter().map(|tx| tx.txid()).collect::<Vec<_>>();
 
         self.event_bus
-            .send(TxConfirmMessage::ConfirmedTxIds(confirmed_txids))
+            .send(TxConfirmMessage::ConfirmedTxIds {
+                block_height: block.block_data.height as u64,
+                tx_ids: confirmed_txids,
+            })
             .await;
 
+        // Depth of already checked transactions has changed, so `GraphBuilder`
+        // could attach the ones that have reached enough confirmations.
+        self.event_bus.send(GraphBuilderMessage::NewBlock).await;
+
         Ok(())
     }
 }
@@ -14,4 +14,10 @@ mod confirmation;
 #[async_trait]
 pub trait Subindexer: Send + Sync + 'static {
     async fn index(&mut self, block: &GetBlockTxResult) -> eyre::Result<()>;
+
+    /// Revert the items indexed from a block which is disconnected from the best chain by a
+    /// reorg.
+    async fn disconnect(&mut self, _block: &GetBlockTxResult) -> eyre::Result<()> {
+        Ok(())
+    }
 }
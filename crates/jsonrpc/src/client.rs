@@ -10,6 +10,7 @@ use std::collections::HashMap;
 use std::fmt;
 use std::hash::{Hash, Hasher};
 use std::sync::atomic;
+use std::time::Duration;
 
 use serde_json::value::RawValue;
 use serde_json::Value;
@@ -23,6 +24,16 @@ use async_trait::async_trait;
 pub trait Transport: Send + Sync + 'static {
     /// Sends an RPC request over the transport.
     async fn send_request(&self, _: Request<'_>) -> Result<Response, Error>;
+    /// Sends an RPC request over the transport with the timeout overriding the default one.
+    ///
+    /// Transports that don't support timeouts send the request as is.
+    async fn send_request_with_timeout(
+        &self,
+        req: Request<'_>,
+        _timeout: Duration,
+    ) -> Result<Response, Error> {
+        self.send_request(req).await
+    }
     /// Sends a batch of RPC requests over the transport.
     async fn send_batch(&self, _: &[Request<'_>]) -> Result<Vec<Response>, Error>;
     /// Formats the target of this transport. I.e. the URL/socket/...
@@ -128,6 +139,35 @@ impl Client {
         let id = request.id.clone();
 
         let response = self.send_request(request).await?;
+
+        Self::response_result(response, id)
+    }
+
+    /// Makes a request with the timeout overriding the transport's default one, and deserializes
+    /// the response.
+    ///
+    /// Useful for the methods that are known to take longer than the others.
+    pub async fn call_with_timeout<R: for<'a> serde::de::Deserialize<'a>>(
+        &self,
+        method: &str,
+        args: &[Box<RawValue>],
+        timeout: Duration,
+    ) -> Result<R, Error> {
+        let request = self.build_request(method, args);
+        let id = request.id.clone();
+
+        let response = self
+            .transport
+            .send_request_with_timeout(request, timeout)
+            .await?;
+
+        Self::response_result(response, id)
+    }
+
+    fn response_result<R: for<'a> serde::de::Deserialize<'a>>(
+        response: Response,
+        id: Value,
+    ) -> Result<R, Error> {
         if response.jsonrpc.is_some() && response.jsonrpc != Some(From::from("2.0")) {
             return Err(Error::VersionMismatch);
         }
@@ -4,3 +4,6 @@ pub mod reqwest_http;
 const DEFAULT_URL: &str = "http://127.0.0.1";
 const DEFAULT_PORT: u16 = 8332; // the default RPC port for bitcoind.
 const DEFAULT_TIMEOUT_SECONDS: u64 = 15;
+const DEFAULT_POOL_MAX_IDLE_PER_HOST: usize = 16;
+const DEFAULT_POOL_IDLE_TIMEOUT_SECONDS: u64 = 90;
+const DEFAULT_TCP_KEEPALIVE_SECONDS: u64 = 60;
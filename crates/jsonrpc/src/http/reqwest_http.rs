@@ -14,11 +14,19 @@ use reqwest::{Body, Method, Url};
 use crate::client::Transport;
 use crate::{Request, Response};
 
-use super::{DEFAULT_PORT, DEFAULT_TIMEOUT_SECONDS, DEFAULT_URL};
+use super::{
+    DEFAULT_POOL_IDLE_TIMEOUT_SECONDS, DEFAULT_POOL_MAX_IDLE_PER_HOST, DEFAULT_PORT,
+    DEFAULT_TCP_KEEPALIVE_SECONDS, DEFAULT_TIMEOUT_SECONDS, DEFAULT_URL,
+};
 
 /// An HTTP transport that uses [`reqwest`] and is useful for running a bitcoind RPC client.
+///
+/// The connections to the server are kept alive and reused by the requests. Clones of the
+/// transport share the same pool of connections.
 #[derive(Clone, Debug)]
 pub struct ReqwestHttpTransport {
+    /// Pooled HTTP client the requests are sent with.
+    client: reqwest::Client,
     /// URL of the RPC server.
     url: String,
     /// timeout only supports second granularity.
@@ -29,11 +37,9 @@ pub struct ReqwestHttpTransport {
 
 impl Default for ReqwestHttpTransport {
     fn default() -> Self {
-        ReqwestHttpTransport {
-            url: format!("{}:{}", DEFAULT_URL, DEFAULT_PORT),
-            timeout: Duration::from_secs(DEFAULT_TIMEOUT_SECONDS),
-            auth: None,
-        }
+        Builder::new()
+            .build()
+            .expect("HTTP client with default parameters should be built")
     }
 }
 
@@ -43,11 +49,30 @@ impl ReqwestHttpTransport {
         ReqwestHttpTransport::default()
     }
 
+    /// Returns the transport with the given timeout, which shares the connections with this one.
+    pub fn with_timeout(&self, timeout: Duration) -> Self {
+        ReqwestHttpTransport {
+            timeout,
+            ..self.clone()
+        }
+    }
+
     async fn request<R>(&self, body: impl serde::Serialize) -> Result<R, Error>
     where
         R: serde::de::DeserializeOwned,
     {
-        let mut request = self.form_request(body)?;
+        self.request_with_timeout(body, self.timeout).await
+    }
+
+    async fn request_with_timeout<R>(
+        &self,
+        body: impl serde::Serialize,
+        timeout: Duration,
+    ) -> Result<R, Error>
+    where
+        R: serde::de::DeserializeOwned,
+    {
+        let mut request = self.form_request(body, timeout)?;
         if let Some(auth) = &self.auth {
             request.headers_mut().insert(
                 "Authorization",
@@ -55,17 +80,21 @@ impl ReqwestHttpTransport {
             );
         }
 
-        let response = reqwest::Client::new().execute(request).await?;
+        let response = self.client.execute(request).await?;
         Ok(serde_json::from_str(&response.text().await?)?)
     }
 
-    fn form_request(&self, body: impl serde::Serialize) -> Result<reqwest::Request, Error> {
+    fn form_request(
+        &self,
+        body: impl serde::Serialize,
+        timeout: Duration,
+    ) -> Result<reqwest::Request, Error> {
         let mut request = reqwest::Request::new(
             Method::POST,
             Url::from_str(&self.url).expect("URL should be valid"),
         );
 
-        *request.timeout_mut() = Some(self.timeout);
+        *request.timeout_mut() = Some(timeout);
         *request.body_mut() = Some(Body::from(serde_json::to_string(&body)?));
 
         Ok(request)
@@ -78,6 +107,14 @@ impl Transport for ReqwestHttpTransport {
         Ok(self.request(req).await?)
     }
 
+    async fn send_request_with_timeout(
+        &self,
+        req: Request<'_>,
+        timeout: Duration,
+    ) -> Result<Response, crate::Error> {
+        Ok(self.request_with_timeout(req, timeout).await?)
+    }
+
     async fn send_batch(&self, reqs: &[Request<'_>]) -> Result<Vec<Response>, crate::Error> {
         Ok(self.request(reqs).await?)
     }
@@ -90,26 +127,36 @@ impl Transport for ReqwestHttpTransport {
 /// Builder for simple bitcoind [`ReqwestHttpTransport`].
 #[derive(Clone, Debug)]
 pub struct Builder {
-    tp: ReqwestHttpTransport,
+    url: String,
+    timeout: Duration,
+    auth: Option<String>,
+    pool_max_idle_per_host: usize,
+    pool_idle_timeout: Option<Duration>,
+    tcp_keepalive: Option<Duration>,
 }
 
 impl Builder {
     /// Constructs a new [`Builder`] with default configuration and the URL to use.
     pub fn new() -> Builder {
         Builder {
-            tp: ReqwestHttpTransport::new(),
+            url: format!("{}:{}", DEFAULT_URL, DEFAULT_PORT),
+            timeout: Duration::from_secs(DEFAULT_TIMEOUT_SECONDS),
+            auth: None,
+            pool_max_idle_per_host: DEFAULT_POOL_MAX_IDLE_PER_HOST,
+            pool_idle_timeout: Some(Duration::from_secs(DEFAULT_POOL_IDLE_TIMEOUT_SECONDS)),
+            tcp_keepalive: Some(Duration::from_secs(DEFAULT_TCP_KEEPALIVE_SECONDS)),
         }
     }
 
     /// Sets the timeout after which requests will abort if they aren't finished.
     pub fn timeout(mut self, timeout: Duration) -> Self {
-        self.tp.timeout = timeout;
+        self.timeout = timeout;
         self
     }
 
     /// Sets the URL of the server to the transport.
     pub fn url(mut self, url: &str) -> Result<Self, Error> {
-        self.tp.url = url.to_owned();
+        self.url = url.to_owned();
         Ok(self)
     }
 
@@ -120,13 +167,43 @@ impl Builder {
         if let Some(ref pass) = pass {
             s.push_str(pass.as_ref());
         }
-        self.tp.auth = Some(format!("Basic {}", &base64::encode(s.as_bytes())));
+        self.auth = Some(format!("Basic {}", &base64::encode(s.as_bytes())));
+        self
+    }
+
+    /// Sets the maximum number of idle connections to the server kept in the pool.
+    pub fn pool_max_idle_per_host(mut self, max_idle: usize) -> Self {
+        self.pool_max_idle_per_host = max_idle;
+        self
+    }
+
+    /// Sets the time after which idle connections are closed. `None` keeps them open until the
+    /// server closes them.
+    pub fn pool_idle_timeout(mut self, timeout: Option<Duration>) -> Self {
+        self.pool_idle_timeout = timeout;
         self
     }
 
-    /// Builds the final [`ReqwestHttpTransport`].
-    pub fn build(self) -> ReqwestHttpTransport {
-        self.tp
+    /// Sets the interval of TCP keep-alive probes on the connections. `None` disables them.
+    pub fn tcp_keepalive(mut self, interval: Option<Duration>) -> Self {
+        self.tcp_keepalive = interval;
+        self
+    }
+
+    /// Builds the final [`ReqwestHttpTransport`] with the pool of connections.
+    pub fn build(self) -> Result<ReqwestHttpTransport, Error> {
+        let client = reqwest::Client::builder()
+            .pool_max_idle_per_host(self.pool_max_idle_per_host)
+            .pool_idle_timeout(self.pool_idle_timeout)
+            .tcp_keepalive(self.tcp_keepalive)
+            .build()?;
+
+        Ok(ReqwestHttpTransport {
+            client,
+            url: self.url,
+            timeout: self.timeout,
+            auth: self.auth,
+        })
     }
 }
 
@@ -195,7 +272,21 @@ mod tests {
             .url("http://localhost:22")
             .unwrap()
             .auth("user".to_string(), None)
-            .build();
+            .pool_max_idle_per_host(4)
+            .pool_idle_timeout(None)
+            .tcp_keepalive(Some(Duration::from_secs(30)))
+            .build()
+            .unwrap();
         let _ = Client::with_transport(tp);
     }
+
+    #[test]
+    fn with_timeout() {
+        let tp = Builder::new().build().unwrap();
+        let other = tp.with_timeout(Duration::from_secs(1));
+
+        assert_eq!(tp.timeout, Duration::from_secs(DEFAULT_TIMEOUT_SECONDS));
+        assert_eq!(other.timeout, Duration::from_secs(1));
+        assert_eq!(other.url, tp.url);
+    }
 }
@@ -0,0 +1,223 @@
+#![doc = include_str!("../README.md")]
+
+use std::fmt::Write;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// Metrics of the node's services, which are updated by the services themselves.
+pub static METRICS: Metrics = Metrics::new();
+
+/// Monotonically increasing value, e.g. the number of handled items.
+#[derive(Debug, Default)]
+pub struct Counter(AtomicU64);
+
+impl Counter {
+    pub const fn new() -> Self {
+        Self(AtomicU64::new(0))
+    }
+
+    pub fn inc(&self) {
+        self.inc_by(1);
+    }
+
+    pub fn inc_by(&self, value: u64) {
+        self.0.fetch_add(value, Ordering::Relaxed);
+    }
+
+    pub fn get(&self) -> u64 {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+/// Value that can go up and down, e.g. the number of items in a queue.
+#[derive(Debug, Default)]
+pub struct Gauge(AtomicU64);
+
+impl Gauge {
+    pub const fn new() -> Self {
+        Self(AtomicU64::new(0))
+    }
+
+    pub fn set(&self, value: u64) {
+        self.0.store(value, Ordering::Relaxed);
+    }
+
+    pub fn get(&self) -> u64 {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+/// Metrics of the node's services.
+#[derive(Debug, Default)]
+pub struct Metrics {
+    /// Number of blocks indexed since the start.
+    pub blocks_indexed: Counter,
+    /// Height of the last indexed block.
+    pub indexed_height: Gauge,
+    /// Height of the best block of the Bitcoin node, as seen by the indexer.
+    pub best_height: Gauge,
+    /// Number of transactions checked by the tx checkers.
+    pub txs_checked: Counter,
+    /// Number of transactions found invalid by the tx checkers.
+    pub txs_invalid: Counter,
+    /// Number of transactions waiting in the graph builder for their parents or confirmations.
+    pub graph_builder_pending: Gauge,
+}
+
+impl Metrics {
+    pub const fn new() -> Self {
+        Self {
+            blocks_indexed: Counter::new(),
+            indexed_height: Gauge::new(),
+            best_height: Gauge::new(),
+            txs_checked: Counter::new(),
+            txs_invalid: Counter::new(),
+            graph_builder_pending: Gauge::new(),
+        }
+    }
+
+    /// Encode the metrics with the encoder.
+    pub fn encode(&self, encoder: &mut Encoder) {
+        let indexed_height = self.indexed_height.get();
+        let best_height = self.best_height.get();
+
+        encoder
+            .counter(
+                "yuv_blocks_indexed_total",
+                "Number of blocks indexed since the start.",
+                self.blocks_indexed.get(),
+            )
+            .gauge(
+                "yuv_indexed_block_height",
+                "Height of the last indexed block.",
+                indexed_height,
+            )
+            .gauge(
+                "yuv_best_block_height",
+                "Height of the best block of the Bitcoin node.",
+                best_height,
+            )
+            .gauge(
+                "yuv_indexer_lag_blocks",
+                "Number of blocks the indexer is behind the Bitcoin node.",
+                best_height.saturating_sub(indexed_height),
+            )
+            .counter(
+                "yuv_txs_checked_total",
+                "Number of transactions checked by the tx checkers.",
+                self.txs_checked.get(),
+            )
+            .counter(
+                "yuv_txs_invalid_total",
+                "Number of transactions found invalid by the tx checkers.",
+                self.txs_invalid.get(),
+            )
+            .gauge(
+                "yuv_graph_builder_pending_txs",
+                "Number of transactions waiting in the graph builder.",
+                self.graph_builder_pending.get(),
+            );
+    }
+}
+
+/// Encoder of the metrics in the Prometheus text format.
+#[derive(Debug, Default)]
+pub struct Encoder {
+    buffer: String,
+}
+
+impl Encoder {
+    /// Add the counter without labels.
+    pub fn counter(&mut self, name: &str, help: &str, value: u64) -> &mut Self {
+        self.family(name, help, "counter", [(None, value)])
+    }
+
+    /// Add the gauge without labels.
+    pub fn gauge(&mut self, name: &str, help: &str, value: u64) -> &mut Self {
+        self.family(name, help, "gauge", [(None, value)])
+    }
+
+    /// Add the gauge with a value per the label's value.
+    pub fn labeled_gauge<'a>(
+        &mut self,
+        name: &str,
+        help: &str,
+        label: &str,
+        values: impl IntoIterator<Item = (&'a str, u64)>,
+    ) -> &mut Self {
+        let samples = values
+            .into_iter()
+            .map(|(label_value, value)| (Some((label, label_value)), value));
+
+        self.family(name, help, "gauge", samples)
+    }
+
+    fn family<'a>(
+        &mut self,
+        name: &str,
+        help: &str,
+        kind: &str,
+        samples: impl IntoIterator<Item = (Option<(&'a str, &'a str)>, u64)>,
+    ) -> &mut Self {
+        // Writing to the `String` can't fail.
+        let _ = writeln!(self.buffer, "# HELP {name} {help}");
+        let _ = writeln!(self.buffer, "# TYPE {name} {kind}");
+
+        for (label, value) in samples {
+            let _ = match label {
+                Some((label, label_value)) => writeln!(
+                    self.buffer,
+                    "{name}{{{label}=\"{}\"}} {value}",
+                    escape_label_value(label_value)
+                ),
+                None => writeln!(self.buffer, "{name} {value}"),
+            };
+        }
+
+        self
+    }
+
+    /// Get the encoded metrics.
+    pub fn finish(self) -> String {
+        self.buffer
+    }
+}
+
+fn escape_label_value(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_metrics_are_encoded_in_text_format() {
+        let metrics = Metrics::new();
+        metrics.blocks_indexed.inc_by(3);
+        metrics.indexed_height.set(100);
+        metrics.best_height.set(105);
+
+        let mut encoder = Encoder::default();
+        metrics.encode(&mut encoder);
+        encoder.labeled_gauge(
+            "yuv_event_bus_queue_length",
+            "Number of queued events.",
+            "event",
+            [("ControllerMessage", 2), ("Quoted\"Event", 0)],
+        );
+        let text = encoder.finish();
+
+        assert!(text.contains(
+            "# HELP yuv_blocks_indexed_total Number of blocks indexed since the start.\n\
+            # TYPE yuv_blocks_indexed_total counter\n\
+            yuv_blocks_indexed_total 3\n"
+        ));
+        assert!(text.contains("yuv_indexer_lag_blocks 5\n"));
+        assert!(text.contains("# TYPE yuv_event_bus_queue_length gauge\n"));
+        assert!(text.contains("yuv_event_bus_queue_length{event=\"ControllerMessage\"} 2\n"));
+        assert!(text.contains("yuv_event_bus_queue_length{event=\"Quoted\\\"Event\"} 0\n"));
+    }
+}
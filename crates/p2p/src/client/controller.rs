@@ -241,4 +241,15 @@ impl<W: NetWaker> handle::Handle for Handle<W> {
     async fn ban_peer(&self, addr: SocketAddr) -> Result<(), handle::Error> {
         self.command(Command::BanPeer(addr)).await
     }
+
+    async fn get_peers(&self) -> Result<Vec<Peer>, handle::Error> {
+        let (transmit, receive) = chan::bounded::<Vec<Peer>>(1);
+        self.command(Command::GetPeers(ServiceFlags::NONE, transmit))
+            .await?;
+
+        match receive.recv_async().await {
+            Ok(peers) => Ok(peers),
+            Err(_) => Err(handle::Error::Timeout),
+        }
+    }
 }
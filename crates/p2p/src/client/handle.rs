@@ -69,6 +69,8 @@ pub trait Handle: Sized + Send + Sync + Clone {
         addr: PeerId,
     ) -> Result<(), handle::Error>;
     async fn ban_peer(&self, addr: SocketAddr) -> Result<(), handle::Error>;
+    /// Get the list of connected peers the handshake was negotiated with.
+    async fn get_peers(&self) -> Result<Vec<Peer>, handle::Error>;
 }
 
 #[cfg(any(test, feature = "mocks"))]
@@ -97,6 +99,7 @@ mockall::mock! {
             addr: PeerId,
         ) -> Result<(), handle::Error>;
         async fn ban_peer(&self, addr: SocketAddr) -> Result<(), handle::Error>;
+        async fn get_peers(&self) -> Result<Vec<Peer>, handle::Error>;
     }
 }
 
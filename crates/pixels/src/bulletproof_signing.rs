@@ -1,6 +1,5 @@
 use alloc::vec::Vec;
 
-use bdk::miniscript::ToPublicKey;
 use bitcoin::{
     hashes::{sha256, Hash},
     secp256k1::{self, schnorr::Signature, All, Secp256k1},
@@ -28,7 +27,7 @@ pub fn tweak_signing_keys(
     recipients: Vec<(PublicKey, u128)>,
     commitments: &mut Vec<(Chroma, CommitmentResult)>,
 ) -> Result<(), BulletproofError> {
-    let input_dh_key = ecdh(private_key, bulletproof.sender_key.to_public_key(), network)
+    let input_dh_key = ecdh(private_key, PublicKey::new(bulletproof.sender_key), network)
         .map_err(|_e| BulletproofError::InvalidRangeProof)?;
 
     // For the inputs, we combine the secret keys.
@@ -50,7 +49,7 @@ pub fn tweak_signing_keys(
                 commitment,
                 proof_hash,
             },
-        ) = get_commitment(private_key, recipient.to_public_key(), network, amount)?;
+        ) = get_commitment(private_key, recipient, network, amount)?;
 
         // For the outputs, we negate the secret keys.
         tweak(
@@ -83,8 +82,8 @@ pub fn get_commitment(
     network: bitcoin::Network,
     amount: u128,
 ) -> Result<(PrivateKey, CommitmentResult), BulletproofError> {
-    let dh_key = ecdh(private_key, public_key.to_public_key(), network)
-        .map_err(|_e| BulletproofError::InvalidRangeProof)?;
+    let dh_key =
+        ecdh(private_key, public_key, network).map_err(|_e| BulletproofError::InvalidRangeProof)?;
     let raw_dh_key: [u8; 32] = dh_key
         .to_bytes()
         .as_slice()
@@ -17,11 +17,14 @@ use bitcoin::{
     hashes::hash160,
     psbt::serialize::{Deserialize, Serialize},
     secp256k1::{self, constants::PUBLIC_KEY_SIZE},
+    Script,
 };
 
 use crate::{
     proof::{
+        generic_script::GenericScriptPixelProof,
         htlc::{HtlcScriptKind, LightningHtlcData, LightningHtlcProof},
+        timelock::TimelockPixelProof,
         EmptyPixelProof,
     },
     LightningCommitmentProof, MultisigPixelProof, Pixel, PixelProof, SigPixelProof, PIXEL_SIZE,
@@ -35,6 +38,8 @@ const LIGHTNING_HTLC_FLAG: u8 = 3u8;
 #[cfg(feature = "bulletproof")]
 const BULLETPROOF_FLAG: u8 = 4u8;
 const EMPTY_PIXEL_FLAG: u8 = 5u8;
+const TIMELOCK_FLAG: u8 = 6u8;
+const GENERIC_SCRIPT_FLAG: u8 = 7u8;
 
 // Htlc script flags
 const OFFERED_CONSENSUS_FLAG: u8 = 0u8;
@@ -89,6 +94,14 @@ impl Encodable for PixelProof {
                 len += EMPTY_PIXEL_FLAG.consensus_encode(writer)?;
                 len += empty_pixelproof.consensus_encode(writer)?;
             }
+            PixelProof::Timelock(timelock_proof) => {
+                len += TIMELOCK_FLAG.consensus_encode(writer)?;
+                len += timelock_proof.consensus_encode(writer)?;
+            }
+            PixelProof::GenericScript(script_proof) => {
+                len += GENERIC_SCRIPT_FLAG.consensus_encode(writer)?;
+                len += script_proof.consensus_encode(writer)?;
+            }
         }
 
         Ok(len)
@@ -125,6 +138,14 @@ impl Decodable for PixelProof {
                 let proof: EmptyPixelProof = Decodable::consensus_decode(reader)?;
                 Ok(PixelProof::EmptyPixel(proof))
             }
+            TIMELOCK_FLAG => {
+                let proof: TimelockPixelProof = Decodable::consensus_decode(reader)?;
+                Ok(PixelProof::Timelock(proof))
+            }
+            GENERIC_SCRIPT_FLAG => {
+                let proof: GenericScriptPixelProof = Decodable::consensus_decode(reader)?;
+                Ok(PixelProof::GenericScript(proof))
+            }
             _ => Err(EncodeError::ParseFailed("Unknown pixel proof")),
         }
     }
@@ -236,6 +257,52 @@ impl Decodable for EmptyPixelProof {
     }
 }
 
+impl Encodable for TimelockPixelProof {
+    fn consensus_encode<W: io::Write + ?Sized>(&self, writer: &mut W) -> Result<usize, io::Error> {
+        let mut len = 0;
+
+        len += self.pixel.consensus_encode(writer)?;
+        len += writer.write(&self.inner_key.serialize())?;
+        len += self.locktime.consensus_encode(writer)?;
+
+        Ok(len)
+    }
+}
+
+impl Decodable for TimelockPixelProof {
+    fn consensus_decode<R: io::Read + ?Sized>(reader: &mut R) -> Result<Self, EncodeError> {
+        let pixel: Pixel = Decodable::consensus_decode(reader)?;
+
+        let mut bytes = [0u8; PUBLIC_KEY_SIZE];
+        reader.read_exact(&mut bytes)?;
+        let inner_key = secp256k1::PublicKey::deserialize(&bytes)?;
+
+        let locktime: u32 = Decodable::consensus_decode(reader)?;
+
+        Ok(TimelockPixelProof::new(pixel, inner_key, locktime))
+    }
+}
+
+impl Encodable for GenericScriptPixelProof {
+    fn consensus_encode<W: io::Write + ?Sized>(&self, writer: &mut W) -> Result<usize, io::Error> {
+        let mut len = 0;
+
+        len += self.pixel.consensus_encode(writer)?;
+        len += self.witness_script.consensus_encode(writer)?;
+
+        Ok(len)
+    }
+}
+
+impl Decodable for GenericScriptPixelProof {
+    fn consensus_decode<R: io::Read + ?Sized>(reader: &mut R) -> Result<Self, EncodeError> {
+        let pixel: Pixel = Decodable::consensus_decode(reader)?;
+        let witness_script: Script = Decodable::consensus_decode(reader)?;
+
+        Ok(GenericScriptPixelProof::new(pixel, witness_script))
+    }
+}
+
 impl Encodable for MultisigPixelProof {
     fn consensus_encode<W: io::Write + ?Sized>(&self, writer: &mut W) -> Result<usize, io::Error> {
         let mut len = 0;
@@ -417,15 +484,18 @@ mod tests {
     use core::str::FromStr;
 
     use bitcoin::{
+        blockdata::{opcodes::all::OP_CHECKSIG, script::Builder},
         consensus::{Decodable, Encodable},
         hashes::hash160,
         secp256k1, XOnlyPublicKey,
     };
     use once_cell::sync::Lazy;
 
+    use crate::proof::generic_script::GenericScriptPixelProof;
     use crate::proof::htlc;
     use crate::proof::htlc::LightningHtlcData;
     use crate::proof::htlc::LightningHtlcProof;
+    use crate::proof::timelock::TimelockPixelProof;
     #[cfg(feature = "bulletproof")]
     use crate::Bulletproof;
     use crate::Chroma;
@@ -567,6 +637,54 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_timelock_proof_consensus_encode() {
+        let chroma = Chroma::new(*X_ONLY_PUBKEY);
+        let pixel = Pixel::new(100, chroma);
+
+        let proof = TimelockPixelProof::new(pixel, *PUBKEY, 800_000);
+
+        let mut bytes = Vec::new();
+
+        proof
+            .consensus_encode(&mut bytes)
+            .expect("failed to encode the proof");
+
+        let decoded_proof = TimelockPixelProof::consensus_decode(&mut bytes.as_slice())
+            .expect("failed to decode the proof");
+
+        assert_eq!(
+            proof, decoded_proof,
+            "Converting back and forth should work"
+        );
+    }
+
+    #[test]
+    fn test_generic_script_proof_consensus_encode() {
+        let chroma = Chroma::new(*X_ONLY_PUBKEY);
+        let pixel = Pixel::new(100, chroma);
+
+        let script = Builder::new()
+            .push_key(&bitcoin::PublicKey::new(*PUBKEY))
+            .push_opcode(OP_CHECKSIG)
+            .into_script();
+        let proof = GenericScriptPixelProof::commit(pixel, &script);
+
+        let mut bytes = Vec::new();
+
+        proof
+            .consensus_encode(&mut bytes)
+            .expect("failed to encode the proof");
+
+        let decoded_proof = GenericScriptPixelProof::consensus_decode(&mut bytes.as_slice())
+            .expect("failed to decode the proof");
+
+        assert_eq!(
+            proof, decoded_proof,
+            "Converting back and forth should work"
+        );
+    }
+
     #[test]
     #[cfg(feature = "bulletproof")]
     fn test_bulletproof_consensus_encode() {
@@ -623,6 +741,14 @@ mod tests {
                     htlc::HtlcScriptKind::Received { cltv_expiry: 100 },
                 ),
             )),
+            PixelProof::Timelock(TimelockPixelProof::new(pixel, *PUBKEY, 800_000)),
+            PixelProof::GenericScript(GenericScriptPixelProof::commit(
+                pixel,
+                &Builder::new()
+                    .push_key(&bitcoin::PublicKey::new(*PUBKEY))
+                    .push_opcode(OP_CHECKSIG)
+                    .into_script(),
+            )),
             #[cfg(feature = "bulletproof")]
             PixelProof::Bulletproof(Box::new(Bulletproof::new(
                 pixel,
@@ -5,9 +5,11 @@ use core::fmt::{self, Display};
 
 use bitcoin::blockdata::{opcodes, script};
 use bitcoin::secp256k1::scalar::OutOfRangeError;
-use bitcoin::{secp256k1, EcdsaSigError, PublicKey, Script};
+use bitcoin::{secp256k1, EcdsaSigError, Network, PublicKey, Script};
 
+use crate::proof::generic_script::GenericScriptPixelProofError;
 use crate::proof::htlc::LightningHtlcProofError;
+use crate::proof::timelock::TimelockPixelProofError;
 #[cfg(feature = "bulletproof")]
 use crate::proof::BulletproofError;
 use crate::{CHROMA_SIZE, PIXEL_SIZE};
@@ -18,6 +20,9 @@ pub enum PixelKeyError {
     Secp256k1(secp256k1::Error),
     PublicKeyError(bitcoin::util::key::Error),
     UncompressedKey,
+    /// The output script isn't the one of the pixel key, e.g. the key is tweaked
+    /// with the odd-parity inner key.
+    MismatchedScript,
 }
 
 impl Display for PixelKeyError {
@@ -27,6 +32,9 @@ impl Display for PixelKeyError {
             PixelKeyError::Secp256k1(e) => write!(f, "Secp256k1 error: {}", e),
             PixelKeyError::PublicKeyError(e) => write!(f, "Failed to decode public key: {}", e),
             PixelKeyError::UncompressedKey => write!(f, "Uncompressed key"),
+            PixelKeyError::MismatchedScript => {
+                write!(f, "Output script doesn't match the pixel key")
+            }
         }
     }
 }
@@ -38,7 +46,7 @@ impl std::error::Error for PixelKeyError {
             PixelKeyError::ScalarOutOfRange(e) => Some(e),
             PixelKeyError::Secp256k1(e) => Some(e),
             PixelKeyError::PublicKeyError(e) => Some(e),
-            PixelKeyError::UncompressedKey => None,
+            PixelKeyError::UncompressedKey | PixelKeyError::MismatchedScript => None,
         }
     }
 }
@@ -128,6 +136,8 @@ pub enum PixelProofError {
     #[cfg(feature = "bulletproof")]
     BulletproofError(BulletproofError),
     LightningHtlcError(LightningHtlcProofError),
+    TimelockPixelProofError(TimelockPixelProofError),
+    GenericScriptPixelProofError(GenericScriptPixelProofError),
 }
 
 impl Display for PixelProofError {
@@ -144,6 +154,12 @@ impl Display for PixelProofError {
             PixelProofError::BulletproofError(e) => write!(f, "BulletproofError: {}", e),
             PixelProofError::LightningHtlcError(e) => write!(f, "LightningHtlcError: {}", e),
             PixelProofError::EmptyPixelProofError(e) => write!(f, "EmptyPixelProofError: {}", e),
+            PixelProofError::TimelockPixelProofError(e) => {
+                write!(f, "TimelockPixelProofError: {}", e)
+            }
+            PixelProofError::GenericScriptPixelProofError(e) => {
+                write!(f, "GenericScriptPixelProofError: {}", e)
+            }
         }
     }
 }
@@ -159,6 +175,8 @@ impl std::error::Error for PixelProofError {
             PixelProofError::BulletproofError(e) => Some(e),
             PixelProofError::LightningHtlcError(e) => Some(e),
             PixelProofError::EmptyPixelProofError(e) => Some(e),
+            PixelProofError::TimelockPixelProofError(e) => Some(e),
+            PixelProofError::GenericScriptPixelProofError(e) => Some(e),
         }
     }
 }
@@ -528,6 +546,12 @@ pub enum ChromaParseError {
     InvalidAddressType,
     InvalidWitnessProgramVersion(WitnessVersion),
     InvalidWitnessProgramLength(usize),
+    InvalidAddress(bitcoin::util::address::Error),
+    /// Address is for the other network than the expected one.
+    NetworkMismatch {
+        expected: Network,
+        found: Network,
+    },
 }
 
 impl Display for ChromaParseError {
@@ -552,6 +576,10 @@ impl Display for ChromaParseError {
                     length, SCHNORR_PUBLIC_KEY_SIZE
                 )
             }
+            ChromaParseError::InvalidAddress(e) => write!(f, "Invalid address: {}", e),
+            ChromaParseError::NetworkMismatch { expected, found } => {
+                write!(f, "Address is for {} network, expected {}", found, expected)
+            }
         }
     }
 }
@@ -565,6 +593,8 @@ impl std::error::Error for ChromaParseError {
             ChromaParseError::InvalidAddressType => None,
             ChromaParseError::InvalidWitnessProgramVersion(_) => None,
             ChromaParseError::InvalidWitnessProgramLength(_) => None,
+            ChromaParseError::InvalidAddress(e) => Some(e),
+            ChromaParseError::NetworkMismatch { .. } => None,
         }
     }
 }
@@ -575,6 +605,12 @@ impl From<secp256k1::Error> for ChromaParseError {
     }
 }
 
+impl From<bitcoin::util::address::Error> for ChromaParseError {
+    fn from(err: bitcoin::util::address::Error) -> Self {
+        ChromaParseError::InvalidAddress(err)
+    }
+}
+
 #[derive(Debug)]
 pub enum MultisigPixelProofError {
     PixelKeyError(PixelKeyError),
@@ -672,3 +708,15 @@ impl From<LightningHtlcProofError> for PixelProofError {
         PixelProofError::LightningHtlcError(err)
     }
 }
+
+impl From<TimelockPixelProofError> for PixelProofError {
+    fn from(err: TimelockPixelProofError) -> Self {
+        PixelProofError::TimelockPixelProofError(err)
+    }
+}
+
+impl From<GenericScriptPixelProofError> for PixelProofError {
+    fn from(err: GenericScriptPixelProofError) -> Self {
+        PixelProofError::GenericScriptPixelProofError(err)
+    }
+}
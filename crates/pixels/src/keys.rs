@@ -1,5 +1,5 @@
 use bitcoin::hashes::{sha256::Hash as Sha256Hash, Hash, HashEngine};
-use bitcoin::secp256k1::Parity;
+use bitcoin::secp256k1::{Parity, XOnlyPublicKey};
 use bitcoin::Script;
 use bitcoin::{
     self,
@@ -15,6 +15,16 @@ use crate::PixelHash;
 ///
 /// Defined as: `PXK = hash(PXH, Pk) * G + P_{B}`,
 /// where `Pk` is owner's public key (coin inner key).
+///
+/// # Parity
+///
+/// The inner key is always taken with the even parity, as keys are compared
+/// by their x coordinates (e.g. the chroma's key in issuer's inputs), so both
+/// keys of the same x coordinate own the same pixels. [`PixelKey::new`] and
+/// [`PixelPrivateKey::new`] normalize the key, while a key tweaked with the
+/// odd-parity inner key gives the output the checker rejects. Use
+/// [`PixelKey::validate_against_output`] to check the output before
+/// broadcasting the transaction.
 #[derive(Debug, Clone, PartialEq, Eq)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct PixelKey(pub PublicKey);
@@ -71,6 +81,55 @@ impl PixelKey {
 
         Some(Script::new_v0_p2wpkh(&pubkey_hash))
     }
+
+    /// Create P2SH-wrapped P2WPKH script (P2SH-P2WPKH) from the tweaked key.
+    pub fn to_p2sh_p2wpkh(&self) -> Option<Script> {
+        let redeem_script = self.to_p2wpkh()?;
+
+        Some(Script::new_p2sh(&redeem_script.script_hash()))
+    }
+
+    /// Check that the output `script` is the P2WPKH or P2SH-P2WPKH one of the
+    /// key, which is what the checker expects from the output of the pixel.
+    ///
+    /// Fails with [`PixelKeyError::MismatchedScript`] if the key is tweaked
+    /// with the other parity of the inner key, see [parity rule](PixelKey#parity).
+    pub fn validate_against_output(&self, script: &Script) -> Result<(), PixelKeyError> {
+        let p2wpkh = self.to_p2wpkh().ok_or(PixelKeyError::UncompressedKey)?;
+
+        if *script == p2wpkh || *script == Script::new_p2sh(&p2wpkh.script_hash()) {
+            return Ok(());
+        }
+
+        Err(PixelKeyError::MismatchedScript)
+    }
+}
+
+/// Derive the P2WPKH script of the output which holds the `pixel` for the
+/// `recipient`, so the receiving script could be computed without building
+/// the transaction.
+///
+/// The script is created from the [`PixelKey`], which is tweaked with the
+/// even-parity `recipient` key, so both keys of the same x coordinate give
+/// the same script.
+pub fn derive_pixel_script_pubkey(
+    pxh: impl Into<PixelHash>,
+    recipient: &secp256k1::PublicKey,
+) -> Result<Script, PixelKeyError> {
+    PixelKey::new(pxh, recipient)?
+        .to_p2wpkh()
+        .ok_or(PixelKeyError::UncompressedKey)
+}
+
+/// Derive the P2WPKH script of the output which holds the `pixel` for the
+/// x-only `recipient` key, e.g. the internal key of a Taproot address.
+///
+/// See [`derive_pixel_script_pubkey`].
+pub fn derive_pixel_script_pubkey_from_xonly(
+    pxh: impl Into<PixelHash>,
+    recipient: &XOnlyPublicKey,
+) -> Result<Script, PixelKeyError> {
+    derive_pixel_script_pubkey(pxh, &recipient.public_key(Parity::Even))
 }
 
 /// Calculates: `sha256(PXH || Pk)`
@@ -183,11 +242,17 @@ impl ToEvenPublicKey for PublicKey {
 mod tests {
     use core::str::FromStr;
 
+    use bitcoin::hashes::Hash;
     use bitcoin::secp256k1::SecretKey;
-    use bitcoin::{secp256k1::Secp256k1, PublicKey};
+    use bitcoin::{secp256k1::Secp256k1, PublicKey, Script};
     use once_cell::sync::Lazy;
 
-    use crate::{Pixel, PixelKey, PixelPrivateKey};
+    use crate::{
+        derive_pixel_script_pubkey, derive_pixel_script_pubkey_from_xonly, errors::PixelKeyError,
+        Pixel, PixelKey, PixelPrivateKey,
+    };
+
+    use super::{pixelhash_pubkey_hash, Parity, PixelHash, Scalar};
 
     static ISSUER: Lazy<PublicKey> = Lazy::new(|| {
         PublicKey::from_str("036a5e3a83f0b2bdfb2f874c6f4679dc02568deb8987d11314a36bceacb569ad8e")
@@ -236,4 +301,76 @@ mod tests {
 
         assert!(pixel_key.to_p2wpkh().is_none());
     }
+
+    #[test]
+    fn test_derive_pixel_script_pubkey() {
+        let ctx = Secp256k1::new();
+        let pixel = Pixel::new(100, *ISSUER);
+        let recipient = RECIPIENT_SECRET.public_key(&ctx);
+
+        let script = derive_pixel_script_pubkey(pixel, &recipient).unwrap();
+
+        assert_eq!(
+            script,
+            Script::from_str("0014aefc7a911eb763d6de436c9fe68e22b5596c7fb4").unwrap()
+        );
+        assert_eq!(
+            Some(script.clone()),
+            PixelKey::new(pixel, &recipient).unwrap().to_p2wpkh()
+        );
+        assert_eq!(
+            derive_pixel_script_pubkey_from_xonly(pixel, &recipient.x_only_public_key().0).unwrap(),
+            script
+        );
+    }
+
+    /// Tweak the `inner_key` as is, without changing its parity to even.
+    fn tweak_without_normalization(
+        pixel: Pixel,
+        inner_key: &bitcoin::secp256k1::PublicKey,
+    ) -> PixelKey {
+        let ctx = Secp256k1::new();
+        let pxh_b = pixelhash_pubkey_hash(&PixelHash::from(pixel), inner_key);
+        let pxh_b = Scalar::from_be_bytes(*pxh_b.as_inner()).unwrap();
+
+        PixelKey(PublicKey::new(
+            inner_key.add_exp_tweak(&ctx, &pxh_b).unwrap(),
+        ))
+    }
+
+    #[test]
+    fn test_validate_against_output_even_parity() {
+        let ctx = Secp256k1::new();
+        let pixel = Pixel::new(100, *ISSUER);
+        let (xonly, _) = RECIPIENT_SECRET.public_key(&ctx).x_only_public_key();
+        let even_key = xonly.public_key(Parity::Even);
+
+        let script = derive_pixel_script_pubkey(pixel, &even_key).unwrap();
+        let pixel_key = tweak_without_normalization(pixel, &even_key);
+
+        assert!(pixel_key.validate_against_output(&script).is_ok());
+        assert!(pixel_key
+            .validate_against_output(&pixel_key.to_p2sh_p2wpkh().unwrap())
+            .is_ok());
+    }
+
+    #[test]
+    fn test_validate_against_output_odd_parity() {
+        let ctx = Secp256k1::new();
+        let pixel = Pixel::new(100, *ISSUER);
+        let (xonly, _) = RECIPIENT_SECRET.public_key(&ctx).x_only_public_key();
+        let odd_key = xonly.public_key(Parity::Odd);
+
+        let script = derive_pixel_script_pubkey(pixel, &odd_key).unwrap();
+
+        // Normalized key of the odd-parity inner key is the even one's.
+        let pixel_key = PixelKey::new(pixel, &odd_key).unwrap();
+        assert!(pixel_key.validate_against_output(&script).is_ok());
+
+        let pixel_key = tweak_without_normalization(pixel, &odd_key);
+        assert!(matches!(
+            pixel_key.validate_against_output(&script),
+            Err(PixelKeyError::MismatchedScript)
+        ));
+    }
 }
@@ -13,10 +13,18 @@ pub use errors::{
     PixelKeyError, PixelParseError, PixelProofError, SigPixelProofError, ToLocalScriptParseError,
 };
 pub use hash::PixelHash;
-pub use keys::{PixelKey, PixelPrivateKey, ToEvenPublicKey};
+pub use keys::{
+    derive_pixel_script_pubkey, derive_pixel_script_pubkey_from_xonly, PixelKey, PixelPrivateKey,
+    ToEvenPublicKey,
+};
+pub use ownership::{
+    chroma_ownership_message, sign_chroma_ownership, verify_chroma_ownership, CHROMA_OWNERSHIP_TAG,
+};
 pub use pixel::{Chroma, Luma, Pixel, BLINDING_FACTOR_SIZE, CHROMA_SIZE, LUMA_SIZE, PIXEL_SIZE};
 pub use proof::{
+    generic_script::{GenericScriptPixelProof, GenericScriptPixelProofError},
     htlc::{HtlcScriptKind, LightningHtlcData, LightningHtlcProof, LightningHtlcScript},
+    timelock::{TimelockPixelProof, TimelockPixelProofError, TimelockWitnessData},
     CheckableProof, EmptyPixelProof, LightningCommitmentProof, LightningCommitmentWitness,
     MultisigPixelProof, MultisigWintessData, P2WPKHWintessData, PixelProof, SigPixelProof,
 };
@@ -36,6 +44,7 @@ pub mod bulletproof_signing;
 mod errors;
 mod hash;
 mod keys;
+mod ownership;
 mod pixel;
 mod proof;
 mod script;
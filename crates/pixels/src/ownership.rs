@@ -0,0 +1,110 @@
+//! Off-chain proofs of the control over the [`Chroma`], made by signing an arbitrary challenge
+//! (e.g. a nonce given by an explorer) with the issuer's key.
+
+use bitcoin::hashes::{sha256::Hash as Sha256Hash, Hash, HashEngine};
+use bitcoin::secp256k1::{schnorr::Signature, KeyPair, Message, Secp256k1, Signing, Verification};
+
+use crate::Chroma;
+
+/// Tag of the BIP-340 tagged hash of the signed challenge.
+///
+/// Transactions are signed over the double SHA-256 (ECDSA) or `TapSighash` tagged hash (Schnorr)
+/// of their data, so the ownership proofs can't be replayed as transaction signatures and vice
+/// versa.
+pub const CHROMA_OWNERSHIP_TAG: &[u8] = b"YUV/ChromaOwnership";
+
+/// Get the message signed to prove the ownership of the chroma, which is the tagged hash of the
+/// chroma and the challenge, so the proof is bound to both of them.
+pub fn chroma_ownership_message(chroma: &Chroma, challenge: &[u8]) -> Message {
+    let tag_hash = Sha256Hash::hash(CHROMA_OWNERSHIP_TAG);
+
+    let mut engine = Sha256Hash::engine();
+    engine.input(tag_hash.as_inner());
+    engine.input(tag_hash.as_inner());
+    engine.input(&chroma.to_bytes());
+    engine.input(challenge);
+
+    let hash = Sha256Hash::from_engine(engine);
+
+    Message::from_slice(hash.as_inner()).expect("hash is 32 bytes long")
+}
+
+/// Sign the challenge with the key of the chroma's issuer, which chroma is the x-only public key
+/// of the `keypair`.
+pub fn sign_chroma_ownership<C: Signing>(
+    ctx: &Secp256k1<C>,
+    keypair: &KeyPair,
+    challenge: &[u8],
+) -> Signature {
+    let chroma = Chroma::from(keypair.x_only_public_key().0);
+    let message = chroma_ownership_message(&chroma, challenge);
+
+    ctx.sign_schnorr_no_aux_rand(&message, keypair)
+}
+
+/// Verify that the challenge is signed by the issuer of the chroma.
+pub fn verify_chroma_ownership<C: Verification>(
+    ctx: &Secp256k1<C>,
+    chroma: &Chroma,
+    challenge: &[u8],
+    signature: &Signature,
+) -> Result<(), bitcoin::secp256k1::Error> {
+    let message = chroma_ownership_message(chroma, challenge);
+
+    ctx.verify_schnorr(signature, &message, chroma.xonly())
+}
+
+#[cfg(test)]
+mod tests {
+    use bitcoin::secp256k1::SecretKey;
+
+    use super::*;
+
+    const CHALLENGE: &[u8] = b"explorer-nonce-42";
+
+    #[test]
+    fn test_chroma_ownership_proof() {
+        let ctx = Secp256k1::new();
+
+        // Keys with both parities of the public key, as the chroma is x-only.
+        for secret in [[1u8; 32], [3u8; 32]] {
+            let keypair = KeyPair::from_secret_key(&ctx, &SecretKey::from_slice(&secret).unwrap());
+            let chroma = Chroma::from(keypair.x_only_public_key().0);
+
+            let signature = sign_chroma_ownership(&ctx, &keypair, CHALLENGE);
+
+            assert!(verify_chroma_ownership(&ctx, &chroma, CHALLENGE, &signature).is_ok());
+            assert!(
+                verify_chroma_ownership(&ctx, &chroma, b"other-challenge", &signature).is_err()
+            );
+        }
+    }
+
+    #[test]
+    fn test_chroma_ownership_proof_of_other_key_is_rejected() {
+        let ctx = Secp256k1::new();
+
+        let keypair = KeyPair::from_secret_key(&ctx, &SecretKey::from_slice(&[1; 32]).unwrap());
+        let other = KeyPair::from_secret_key(&ctx, &SecretKey::from_slice(&[2; 32]).unwrap());
+        let chroma = Chroma::from(keypair.x_only_public_key().0);
+
+        let signature = sign_chroma_ownership(&ctx, &other, CHALLENGE);
+
+        assert!(verify_chroma_ownership(&ctx, &chroma, CHALLENGE, &signature).is_err());
+    }
+
+    #[test]
+    fn test_plain_schnorr_signature_of_challenge_is_rejected() {
+        let ctx = Secp256k1::new();
+
+        let keypair = KeyPair::from_secret_key(&ctx, &SecretKey::from_slice(&[1; 32]).unwrap());
+        let chroma = Chroma::from(keypair.x_only_public_key().0);
+
+        // Signature of the untagged hash, like the one of a transaction, isn't a valid proof.
+        let hash = Sha256Hash::hash(CHALLENGE);
+        let message = Message::from_slice(hash.as_inner()).unwrap();
+        let signature = ctx.sign_schnorr_no_aux_rand(&message, &keypair);
+
+        assert!(verify_chroma_ownership(&ctx, &chroma, CHALLENGE, &signature).is_err());
+    }
+}
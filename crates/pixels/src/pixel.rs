@@ -163,9 +163,35 @@ impl Chroma {
         }
     }
 
+    /// Parse the chroma from the P2TR address of any network.
+    ///
+    /// See [`Chroma::from_address_for_network`] to check the network of the address too.
     pub fn from_address(address: &str) -> Result<Self, ChromaParseError> {
-        let address = Address::from_str(address).unwrap();
+        let address = Address::from_str(address)?;
 
+        Self::from_parsed_address(address)
+    }
+
+    /// Parse the chroma from the P2TR address, which must be for the given `network`.
+    ///
+    /// Testnet and signet addresses share the prefix, so they are accepted for both networks.
+    pub fn from_address_for_network(
+        address: &str,
+        network: Network,
+    ) -> Result<Self, ChromaParseError> {
+        let address = Address::from_str(address)?;
+
+        if !address.is_valid_for_network(network) {
+            return Err(ChromaParseError::NetworkMismatch {
+                expected: network,
+                found: address.network,
+            });
+        }
+
+        Self::from_parsed_address(address)
+    }
+
+    fn from_parsed_address(address: Address) -> Result<Self, ChromaParseError> {
         let (version, program) = match address.payload {
             Payload::WitnessProgram { version, program } => (version, program),
             _ => return Err(ChromaParseError::InvalidAddressType),
@@ -324,6 +350,60 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_chroma_parsing_address_for_network() {
+        let chroma = Chroma::from(*X_ONLY_PUBKEY);
+
+        for network in [
+            Network::Bitcoin,
+            Network::Testnet,
+            Network::Signet,
+            Network::Regtest,
+        ] {
+            let address = chroma.to_address(network).to_string();
+
+            assert_eq!(
+                chroma,
+                Chroma::from_address_for_network(&address, network).unwrap(),
+                "Address should be valid for its network"
+            );
+        }
+
+        // Testnet and signet addresses can't be told apart.
+        let testnet = chroma.to_address(Network::Testnet).to_string();
+        assert!(Chroma::from_address_for_network(&testnet, Network::Signet).is_ok());
+
+        for (address_network, network) in [
+            (Network::Testnet, Network::Bitcoin),
+            (Network::Regtest, Network::Bitcoin),
+            (Network::Bitcoin, Network::Testnet),
+            (Network::Regtest, Network::Testnet),
+            (Network::Bitcoin, Network::Regtest),
+            (Network::Testnet, Network::Regtest),
+        ] {
+            let address = chroma.to_address(address_network).to_string();
+
+            assert!(
+                matches!(
+                    Chroma::from_address_for_network(&address, network),
+                    Err(ChromaParseError::NetworkMismatch { expected, .. }) if expected == network
+                ),
+                "{address_network} address must be rejected for {network}"
+            );
+
+            // Network-agnostic parsing still accepts it.
+            assert_eq!(chroma, Chroma::from_address(&address).unwrap());
+        }
+    }
+
+    #[test]
+    fn test_chroma_parsing_invalid_address() {
+        assert!(matches!(
+            Chroma::from_address("not an address"),
+            Err(ChromaParseError::InvalidAddress(_))
+        ));
+    }
+
     #[test]
     fn test_pixel_parsing() {
         let pixel = Pixel::new(100, *X_ONLY_PUBKEY);
@@ -35,9 +35,13 @@ use crate::errors::{
 use crate::script::ToLocalScript;
 use crate::{Pixel, PixelKey};
 
+use self::generic_script::GenericScriptPixelProof;
 use self::htlc::{LightningHtlcData, LightningHtlcProof};
+use self::timelock::TimelockPixelProof;
 
+pub mod generic_script;
 pub mod htlc;
+pub mod timelock;
 
 /// The proof of ownership that user brings to check and attach particular transaction.
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
@@ -67,6 +71,14 @@ pub enum PixelProof {
 
     /// Proof for spending lightning HTLC output at force-close.
     LightningHtlc(LightningHtlcProof),
+
+    /// The proof of ownership with single signature of the output that is
+    /// locked until the locktime, using P2WSH script.
+    Timelock(TimelockPixelProof),
+
+    /// The proof of ownership of the output locked by an arbitrary P2WSH
+    /// script that commits to the pixel.
+    GenericScript(GenericScriptPixelProof),
 }
 
 impl PixelProof {
@@ -79,6 +91,8 @@ impl PixelProof {
             #[cfg(feature = "bulletproof")]
             Self::Bulletproof(bulletproof) => bulletproof.pixel,
             Self::LightningHtlc(htlc) => htlc.pixel,
+            Self::Timelock(timelock_proof) => timelock_proof.pixel,
+            Self::GenericScript(script_proof) => script_proof.pixel,
             Self::EmptyPixel(_) => Pixel::empty(),
         }
     }
@@ -95,6 +109,18 @@ impl PixelProof {
         Self::LightningHtlc(LightningHtlcProof::new(pixel.into(), data))
     }
 
+    pub fn timelock(
+        pixel: impl Into<Pixel>,
+        inner_key: secp256k1::PublicKey,
+        locktime: u32,
+    ) -> Self {
+        Self::Timelock(TimelockPixelProof::new(pixel, inner_key, locktime))
+    }
+
+    pub fn generic_script(pixel: impl Into<Pixel>, script: &Script) -> Self {
+        Self::GenericScript(GenericScriptPixelProof::commit(pixel, script))
+    }
+
     pub fn lightning(
         pixel: impl Into<Pixel>,
         revocation_pubkey: PublicKey,
@@ -181,6 +207,8 @@ impl CheckableProof for PixelProof {
             #[cfg(feature = "bulletproof")]
             Self::Bulletproof(bulletproof) => bulletproof.checked_check_by_input(txin)?,
             Self::LightningHtlc(htlc) => htlc.checked_check_by_input(txin)?,
+            Self::Timelock(timelock_proof) => timelock_proof.checked_check_by_input(txin)?,
+            Self::GenericScript(script_proof) => script_proof.checked_check_by_input(txin)?,
             Self::EmptyPixel(empty_pixelproof) => empty_pixelproof.checked_check_by_input(txin)?,
         };
 
@@ -195,6 +223,8 @@ impl CheckableProof for PixelProof {
             #[cfg(feature = "bulletproof")]
             Self::Bulletproof(bulletproof) => bulletproof.checked_check_by_output(txout)?,
             Self::LightningHtlc(htlc) => htlc.checked_check_by_output(txout)?,
+            Self::Timelock(timelock_proof) => timelock_proof.checked_check_by_output(txout)?,
+            Self::GenericScript(script_proof) => script_proof.checked_check_by_output(txout)?,
             Self::EmptyPixel(empty_pixelproof) => {
                 empty_pixelproof.checked_check_by_output(txout)?
             }
@@ -567,16 +597,40 @@ impl CheckableProof for SigPixelProof {
 
     /// Get from input witness signature and public key and check that public
     /// key is equal to the tweaked one from proof.
+    ///
+    /// If the input spends P2SH-wrapped output, the `script_sig` must contain
+    /// only the push of P2WPKH redeem script of the tweaked key.
     fn checked_check_by_input(&self, txin: &TxIn) -> Result<(), Self::Error> {
         let data = P2WPKHWintessData::from_witness(&txin.witness)?;
 
         self.check_by_parsed_witness_data(&data.signature, &data.pubkey)?;
 
+        if !txin.script_sig.is_empty() {
+            let pixel_key = PixelKey::new(self.pixel, &self.inner_key)?;
+
+            let redeem_script = pixel_key
+                .to_p2wpkh()
+                .ok_or(PixelKeyError::UncompressedKey)?;
+
+            let expected_script_sig = Builder::new()
+                .push_slice(redeem_script.as_bytes())
+                .into_script();
+
+            if txin.script_sig != expected_script_sig {
+                return Err(SigPixelProofError::InvalidScript(
+                    txin.script_sig.clone(),
+                    expected_script_sig,
+                ));
+            }
+        }
+
         Ok(())
     }
 
     /// Get from transaction output `script_pubkey` and create P2WPKH script
     /// from tweaked public key from proof and compare it with `script_pubkey`.
+    ///
+    /// P2SH-wrapped P2WPKH script of the same key is accepted too.
     fn checked_check_by_output(&self, txout: &TxOut) -> Result<(), Self::Error> {
         let pixel_key = PixelKey::new(self.pixel, &self.inner_key)?;
 
@@ -584,6 +638,21 @@ impl CheckableProof for SigPixelProof {
             .to_p2wpkh()
             .ok_or(PixelKeyError::UncompressedKey)?;
 
+        if txout.script_pubkey.is_p2sh() {
+            let expected_p2sh = pixel_key
+                .to_p2sh_p2wpkh()
+                .ok_or(PixelKeyError::UncompressedKey)?;
+
+            if txout.script_pubkey != expected_p2sh {
+                return Err(SigPixelProofError::InvalidScript(
+                    txout.script_pubkey.clone(),
+                    expected_p2sh,
+                ));
+            }
+
+            return Ok(());
+        }
+
         if txout.script_pubkey != expected_script_pubkey {
             return Err(SigPixelProofError::InvalidScript(
                 txout.script_pubkey.clone(),
@@ -0,0 +1,38 @@
+use alloc::fmt;
+use bitcoin::Script;
+
+#[derive(Debug)]
+pub enum GenericScriptPixelProofError {
+    /// Witness script doesn't start with the commitment to the pixel.
+    NoPixelCommitment,
+    /// Witness of the input is empty, so it doesn't reveal the witness script.
+    EmptyWitness,
+    /// Witness script in the input differs from the one of the proof.
+    WitnessScriptMismatch { expected: Script, found: Script },
+    /// `script_pubkey` of the output differs from the one built from the proof.
+    ScriptPubkeyMismatch { expected: Script, found: Script },
+}
+
+impl fmt::Display for GenericScriptPixelProofError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NoPixelCommitment => {
+                write!(f, "Witness script doesn't commit to the pixel")
+            }
+            Self::EmptyWitness => write!(f, "Witness is empty"),
+            Self::WitnessScriptMismatch { expected, found } => write!(
+                f,
+                "Witness script mismatch, expected: {}, found: {}",
+                expected, found
+            ),
+            Self::ScriptPubkeyMismatch { expected, found } => write!(
+                f,
+                "Script pubkey mismatch, expected: {}, found: {}",
+                expected, found
+            ),
+        }
+    }
+}
+
+#[cfg(not(feature = "no-std"))]
+impl std::error::Error for GenericScriptPixelProofError {}
@@ -0,0 +1,145 @@
+//! This module provides definitions for type of proof for pixel outputs locked
+//! by an arbitrary P2WSH witness script, e.g. custom covenants, that commits to
+//! the pixel by the prefix:
+//!
+//! ```text
+//! OP_PUSHBYTES_32 <pixel_hash> OP_DROP <script>
+//! ```
+//!
+//! where `pixel_hash` is the [`PixelHash`] of the pixel. The prefix doesn't
+//! change the spending conditions of the `script`, as the pushed hash is
+//! dropped from the stack right away.
+
+use bitcoin::{
+    blockdata::{opcodes::all::OP_DROP, script::Builder},
+    hashes::Hash,
+    Script, TxIn, TxOut,
+};
+
+use crate::{CheckableProof, Pixel, PixelHash};
+
+#[cfg(test)]
+mod tests;
+
+mod errors;
+pub use self::errors::GenericScriptPixelProofError;
+
+/// Size of the pixel commitment prefix of the witness script in bytes.
+const COMMITMENT_PREFIX_SIZE: usize = 1 + 32 + 1;
+
+/// The proof of ownership of the pixel locked by an arbitrary witness script
+/// which commits to the pixel.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct GenericScriptPixelProof {
+    /// Pixel that proof verifies.
+    pub pixel: Pixel,
+    /// Witness script of the output, which starts with the pixel commitment.
+    pub witness_script: Script,
+}
+
+impl GenericScriptPixelProof {
+    pub fn new(pixel: impl Into<Pixel>, witness_script: Script) -> Self {
+        Self {
+            pixel: pixel.into(),
+            witness_script,
+        }
+    }
+
+    /// Create the proof with the witness script which is the `script` prefixed
+    /// with the commitment to the pixel.
+    pub fn commit(pixel: impl Into<Pixel>, script: &Script) -> Self {
+        let pixel = pixel.into();
+
+        let mut witness_script = commitment_prefix(&pixel).into_bytes();
+        witness_script.extend_from_slice(script.as_bytes());
+
+        Self::new(pixel, Script::from(witness_script))
+    }
+
+    /// Check that the witness script starts with the commitment to the pixel.
+    pub fn check_commitment(&self) -> Result<(), GenericScriptPixelProofError> {
+        let prefix = commitment_prefix(&self.pixel);
+
+        if !self
+            .witness_script
+            .as_bytes()
+            .starts_with(prefix.as_bytes())
+        {
+            return Err(GenericScriptPixelProofError::NoPixelCommitment);
+        }
+
+        Ok(())
+    }
+
+    /// The witness script without the pixel commitment prefix.
+    pub fn inner_script(&self) -> Result<Script, GenericScriptPixelProofError> {
+        self.check_commitment()?;
+
+        Ok(Script::from(
+            self.witness_script.as_bytes()[COMMITMENT_PREFIX_SIZE..].to_vec(),
+        ))
+    }
+
+    pub fn to_script_pubkey(&self) -> Result<Script, GenericScriptPixelProofError> {
+        self.check_commitment()?;
+
+        Ok(self.witness_script.to_v0_p2wsh())
+    }
+}
+
+/// Build the prefix of the witness script that commits to the pixel.
+fn commitment_prefix(pixel: &Pixel) -> Script {
+    let pixel_hash = PixelHash::from(pixel);
+
+    Builder::new()
+        .push_slice(&pixel_hash.into_inner())
+        .push_opcode(OP_DROP)
+        .into_script()
+}
+
+impl From<GenericScriptPixelProof> for crate::PixelProof {
+    fn from(value: GenericScriptPixelProof) -> Self {
+        Self::GenericScript(value)
+    }
+}
+
+impl CheckableProof for GenericScriptPixelProof {
+    type Error = GenericScriptPixelProofError;
+
+    /// Check that the input reveals the witness script of the proof as the
+    /// last element of the witness.
+    ///
+    /// The rest of the witness is checked by Bitcoin consensus rules, as it
+    /// must satisfy the script.
+    fn checked_check_by_input(&self, txin: &TxIn) -> Result<(), Self::Error> {
+        self.check_commitment()?;
+
+        let witness_script = txin
+            .witness
+            .last()
+            .ok_or(GenericScriptPixelProofError::EmptyWitness)?;
+
+        if witness_script != self.witness_script.as_bytes() {
+            return Err(GenericScriptPixelProofError::WitnessScriptMismatch {
+                expected: self.witness_script.clone(),
+                found: Script::from(witness_script.to_vec()),
+            });
+        }
+
+        Ok(())
+    }
+
+    fn checked_check_by_output(&self, txout: &TxOut) -> Result<(), Self::Error> {
+        let expected = self.to_script_pubkey()?;
+
+        if txout.script_pubkey != expected {
+            return Err(GenericScriptPixelProofError::ScriptPubkeyMismatch {
+                expected,
+                found: txout.script_pubkey.clone(),
+            });
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,134 @@
+use core::str::FromStr;
+
+use alloc::vec;
+use bitcoin::{
+    blockdata::opcodes::all::{OP_CHECKSIG, OP_CSV, OP_DROP},
+    secp256k1::PublicKey,
+    TxIn, TxOut, Witness,
+};
+use once_cell::sync::Lazy;
+
+use crate::{Chroma, PixelProof};
+
+use super::*;
+
+static INNER_KEY: Lazy<PublicKey> = Lazy::new(|| {
+    PublicKey::from_str("036a5e3a83f0b2bdfb2f874c6f4679dc02568deb8987d11314a36bceacb569ad8e")
+        .expect("Should be valid public key")
+});
+
+static CHROMA: Lazy<Chroma> = Lazy::new(|| Chroma::from(INNER_KEY.x_only_public_key().0));
+
+/// Custom script, which is spendable by the key after 144 blocks.
+fn script() -> Script {
+    Builder::new()
+        .push_int(144)
+        .push_opcode(OP_CSV)
+        .push_opcode(OP_DROP)
+        .push_key(&bitcoin::PublicKey::new(*INNER_KEY))
+        .push_opcode(OP_CHECKSIG)
+        .into_script()
+}
+
+fn proof(amount: u128) -> GenericScriptPixelProof {
+    GenericScriptPixelProof::commit(Pixel::new(amount, *CHROMA), &script())
+}
+
+#[test]
+fn test_commitment_prefix() {
+    let proof = proof(100);
+    let pixel_hash = PixelHash::from(proof.pixel);
+
+    let mut instructions = proof.witness_script.instructions();
+    assert_eq!(
+        instructions.next().unwrap().unwrap(),
+        bitcoin::blockdata::script::Instruction::PushBytes(&pixel_hash.into_inner())
+    );
+    assert_eq!(
+        instructions.next().unwrap().unwrap(),
+        bitcoin::blockdata::script::Instruction::Op(OP_DROP)
+    );
+
+    assert_eq!(proof.inner_script().unwrap(), script());
+}
+
+#[test]
+fn test_generic_script_proof_check_by_output() {
+    let proof = proof(100);
+
+    let txout = TxOut {
+        value: 1000,
+        script_pubkey: proof.witness_script.to_v0_p2wsh(),
+    };
+    assert!(PixelProof::from(proof.clone())
+        .checked_check_by_output(&txout)
+        .is_ok());
+
+    // Output with the script committed to the other pixel isn't the one of the proof.
+    let other_txout = TxOut {
+        value: 1000,
+        script_pubkey: self::proof(101).to_script_pubkey().unwrap(),
+    };
+    assert!(matches!(
+        proof.checked_check_by_output(&other_txout),
+        Err(GenericScriptPixelProofError::ScriptPubkeyMismatch { .. })
+    ));
+}
+
+#[test]
+fn test_generic_script_proof_check_by_input() {
+    let proof = proof(100);
+
+    let txin = TxIn {
+        witness: Witness::from_vec(vec![vec![1; 72], proof.witness_script.to_bytes()]),
+        ..Default::default()
+    };
+    assert!(PixelProof::from(proof.clone())
+        .checked_check_by_input(&txin)
+        .is_ok());
+
+    let other_txin = TxIn {
+        witness: Witness::from_vec(vec![
+            vec![1; 72],
+            self::proof(101).witness_script.to_bytes(),
+        ]),
+        ..Default::default()
+    };
+    assert!(matches!(
+        proof.checked_check_by_input(&other_txin),
+        Err(GenericScriptPixelProofError::WitnessScriptMismatch { .. })
+    ));
+
+    assert!(matches!(
+        proof.checked_check_by_input(&TxIn::default()),
+        Err(GenericScriptPixelProofError::EmptyWitness)
+    ));
+}
+
+#[test]
+fn test_script_without_commitment_is_rejected() {
+    let pixel = Pixel::new(100, *CHROMA);
+
+    // Script without the commitment, or committed to the other pixel.
+    for witness_script in [script(), proof(101).witness_script] {
+        let proof = GenericScriptPixelProof::new(pixel, witness_script.clone());
+
+        let txout = TxOut {
+            value: 1000,
+            script_pubkey: witness_script.to_v0_p2wsh(),
+        };
+        assert!(matches!(
+            proof.checked_check_by_output(&txout),
+            Err(GenericScriptPixelProofError::NoPixelCommitment)
+        ));
+
+        let txin = TxIn {
+            witness: Witness::from_vec(vec![vec![1; 72], witness_script.to_bytes()]),
+            ..Default::default()
+        };
+        assert!(matches!(
+            proof.checked_check_by_input(&txin),
+            Err(GenericScriptPixelProofError::NoPixelCommitment)
+        ));
+    }
+}
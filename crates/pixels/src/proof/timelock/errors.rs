@@ -0,0 +1,72 @@
+use alloc::fmt;
+use bitcoin::{EcdsaSigError, Script};
+
+use crate::errors::PixelKeyError;
+
+#[derive(Debug)]
+pub enum TimelockPixelProofError {
+    PixelKeyError(PixelKeyError),
+    /// Locktime of the proof is zero, which can't be checked by `OP_CHECKLOCKTIMEVERIFY`.
+    ZeroLocktime,
+    /// Witness of the input isn't a signature followed by the redeem script.
+    InvalidWitnessStructure,
+    InvalidSignature(EcdsaSigError),
+    /// Redeem script in the witness differs from the one built from the proof.
+    RedeemScriptMismatch {
+        expected: Script,
+        found: Script,
+    },
+    /// `script_pubkey` of the output differs from the one built from the proof.
+    ScriptPubkeyMismatch {
+        expected: Script,
+        found: Script,
+    },
+    /// Sequence of the input is final, so the locktime of the transaction is disabled
+    /// and `OP_CHECKLOCKTIMEVERIFY` fails.
+    FinalSequence,
+}
+
+impl fmt::Display for TimelockPixelProofError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::PixelKeyError(err) => write!(f, "Failed to create pixel key: {}", err),
+            Self::ZeroLocktime => write!(f, "Locktime must be greater than zero"),
+            Self::InvalidWitnessStructure => write!(f, "Invalid witness structure"),
+            Self::InvalidSignature(err) => write!(f, "Invalid signature: {}", err),
+            Self::RedeemScriptMismatch { expected, found } => write!(
+                f,
+                "Redeem script mismatch, expected: {}, found: {}",
+                expected, found
+            ),
+            Self::ScriptPubkeyMismatch { expected, found } => write!(
+                f,
+                "Script pubkey mismatch, expected: {}, found: {}",
+                expected, found
+            ),
+            Self::FinalSequence => write!(f, "Input sequence disables the transaction locktime"),
+        }
+    }
+}
+
+#[cfg(not(feature = "no-std"))]
+impl std::error::Error for TimelockPixelProofError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::PixelKeyError(err) => Some(err),
+            Self::InvalidSignature(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<PixelKeyError> for TimelockPixelProofError {
+    fn from(err: PixelKeyError) -> Self {
+        Self::PixelKeyError(err)
+    }
+}
+
+impl From<EcdsaSigError> for TimelockPixelProofError {
+    fn from(err: EcdsaSigError) -> Self {
+        Self::InvalidSignature(err)
+    }
+}
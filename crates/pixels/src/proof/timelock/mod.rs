@@ -0,0 +1,175 @@
+//! This module provides definitions for type of proof for pixel outputs that
+//! can't be spent before the locktime (e.g. vesting), using P2WSH script:
+//!
+//! ```text
+//! <locktime> OP_CHECKLOCKTIMEVERIFY OP_VERIFY <tweaked_pubkey> OP_CHECKSIG
+//! ```
+//!
+//! which is the same as `and_v(v:after(locktime),pk(tweaked_pubkey))` miniscript,
+//! so the output can be spent by wallets that support descriptors.
+
+use bitcoin::{
+    blockdata::{
+        opcodes::all::{OP_CHECKSIG, OP_CLTV, OP_VERIFY},
+        script::Builder,
+    },
+    secp256k1,
+    util::ecdsa::EcdsaSig as Signature,
+    Script, TxIn, TxOut, Witness,
+};
+
+use crate::{CheckableProof, Pixel, PixelKey};
+
+#[cfg(test)]
+mod tests;
+
+mod errors;
+pub use self::errors::TimelockPixelProofError;
+
+/// The proof of ownership of the pixel that is locked until the `locktime`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TimelockPixelProof {
+    /// Pixel that proof verifies.
+    pub pixel: Pixel,
+    /// Key of current owner of the pixel.
+    pub inner_key: secp256k1::PublicKey,
+    /// Block height or timestamp (same as `nLockTime` of the transaction) after
+    /// which the output can be spent.
+    pub locktime: u32,
+}
+
+impl TimelockPixelProof {
+    pub fn new(pixel: impl Into<Pixel>, inner_key: secp256k1::PublicKey, locktime: u32) -> Self {
+        Self {
+            pixel: pixel.into(),
+            inner_key,
+            locktime,
+        }
+    }
+
+    /// Tweak the inner key with pixel and create the redeem script from it and
+    /// the locktime.
+    pub fn to_redeem_script(&self) -> Result<Script, TimelockPixelProofError> {
+        if self.locktime == 0 {
+            return Err(TimelockPixelProofError::ZeroLocktime);
+        }
+
+        let pixel_key = PixelKey::new(self.pixel, &self.inner_key)?;
+
+        let script = Builder::new()
+            .push_int(self.locktime as i64)
+            .push_opcode(OP_CLTV)
+            .push_opcode(OP_VERIFY)
+            .push_key(&pixel_key.0)
+            .push_opcode(OP_CHECKSIG)
+            .into_script();
+
+        Ok(script)
+    }
+
+    pub fn to_script_pubkey(&self) -> Result<Script, TimelockPixelProofError> {
+        Ok(self.to_redeem_script()?.to_v0_p2wsh())
+    }
+}
+
+impl From<TimelockPixelProof> for crate::PixelProof {
+    fn from(value: TimelockPixelProof) -> Self {
+        Self::Timelock(value)
+    }
+}
+
+impl CheckableProof for TimelockPixelProof {
+    type Error = TimelockPixelProofError;
+
+    /// Check that the input reveals the redeem script of the proof and doesn't
+    /// disable the locktime of the transaction.
+    ///
+    /// The locktime itself is enforced by Bitcoin consensus rules, as the
+    /// transaction with the lower `nLockTime` fails the script. So is the
+    /// signature, which is checked by `OP_CHECKSIG` of the redeem script
+    /// against the tweaked key, as its sighash needs the whole transaction and
+    /// the spent output, which aren't known here.
+    fn checked_check_by_input(&self, txin: &TxIn) -> Result<(), Self::Error> {
+        let data = TimelockWitnessData::from_witness(&txin.witness)?;
+
+        let expected = self.to_redeem_script()?;
+        if data.redeem_script != expected {
+            return Err(TimelockPixelProofError::RedeemScriptMismatch {
+                expected,
+                found: data.redeem_script,
+            });
+        }
+
+        if !txin.sequence.enables_absolute_lock_time() {
+            return Err(TimelockPixelProofError::FinalSequence);
+        }
+
+        Ok(())
+    }
+
+    fn checked_check_by_output(&self, txout: &TxOut) -> Result<(), Self::Error> {
+        let expected = self.to_script_pubkey()?;
+
+        if txout.script_pubkey != expected {
+            return Err(TimelockPixelProofError::ScriptPubkeyMismatch {
+                expected,
+                found: txout.script_pubkey.clone(),
+            });
+        }
+
+        Ok(())
+    }
+}
+
+/// Witness of the input that spends the timelocked pixel output.
+pub struct TimelockWitnessData {
+    pub signature: Signature,
+    pub redeem_script: Script,
+}
+
+impl TimelockWitnessData {
+    pub fn new(signature: Signature, redeem_script: Script) -> Self {
+        Self {
+            signature,
+            redeem_script,
+        }
+    }
+
+    pub fn from_witness(witness: &Witness) -> Result<Self, TimelockPixelProofError> {
+        if witness.len() != 2 {
+            return Err(TimelockPixelProofError::InvalidWitnessStructure);
+        }
+
+        let mut witness_iter = witness.iter();
+
+        let signature = witness_iter
+            .next()
+            .ok_or(TimelockPixelProofError::InvalidWitnessStructure)?;
+        let signature = Signature::from_slice(signature)?;
+
+        let redeem_script = witness_iter
+            .next()
+            .ok_or(TimelockPixelProofError::InvalidWitnessStructure)?;
+        let redeem_script = Script::from(redeem_script.to_vec());
+
+        Ok(Self {
+            signature,
+            redeem_script,
+        })
+    }
+}
+
+impl From<TimelockWitnessData> for Witness {
+    fn from(value: TimelockWitnessData) -> Self {
+        let mut witness = Witness::new();
+
+        witness.push_bitcoin_signature(
+            &value.signature.sig.serialize_der(),
+            value.signature.hash_ty,
+        );
+        witness.push(value.redeem_script.into_bytes());
+
+        witness
+    }
+}
@@ -0,0 +1,108 @@
+use core::str::FromStr;
+
+use bdk::miniscript::{Miniscript, Segwitv0};
+use bitcoin::{
+    secp256k1::{ecdsa, PublicKey},
+    EcdsaSighashType, Sequence, TxIn, TxOut, Witness,
+};
+use once_cell::sync::Lazy;
+
+use crate::{Chroma, PixelProof};
+
+use super::*;
+
+const LOCKTIME: u32 = 800_000;
+
+static INNER_KEY: Lazy<PublicKey> = Lazy::new(|| {
+    PublicKey::from_str("036a5e3a83f0b2bdfb2f874c6f4679dc02568deb8987d11314a36bceacb569ad8e")
+        .expect("Should be valid public key")
+});
+
+static CHROMA: Lazy<Chroma> = Lazy::new(|| Chroma::from(INNER_KEY.x_only_public_key().0));
+
+fn proof(locktime: u32) -> TimelockPixelProof {
+    TimelockPixelProof::new(Pixel::new(100, *CHROMA), *INNER_KEY, locktime)
+}
+
+fn txin(proof: &TimelockPixelProof, sequence: Sequence) -> TxIn {
+    let signature = Signature {
+        sig: ecdsa::Signature::from_compact(&[1; 64]).unwrap(),
+        hash_ty: EcdsaSighashType::All,
+    };
+    let redeem_script = proof.to_redeem_script().unwrap();
+
+    TxIn {
+        sequence,
+        witness: Witness::from(TimelockWitnessData::new(signature, redeem_script)),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn test_redeem_script_is_after_miniscript() {
+    let proof = proof(LOCKTIME);
+    let pixel_key = PixelKey::new(proof.pixel, &proof.inner_key).unwrap();
+
+    let miniscript = Miniscript::<bitcoin::PublicKey, Segwitv0>::from_str(&alloc::format!(
+        "and_v(v:after({}),pk({}))",
+        LOCKTIME,
+        pixel_key.0
+    ))
+    .unwrap();
+
+    assert_eq!(proof.to_redeem_script().unwrap(), miniscript.encode());
+}
+
+#[test]
+fn test_timelock_proof_check_by_output() {
+    let proof = proof(LOCKTIME);
+
+    let txout = TxOut {
+        value: 1000,
+        script_pubkey: proof.to_script_pubkey().unwrap(),
+    };
+    assert!(proof.checked_check_by_output(&txout).is_ok());
+
+    // Output locked until the other time isn't the one of the proof.
+    let other_txout = TxOut {
+        value: 1000,
+        script_pubkey: self::proof(LOCKTIME + 1).to_script_pubkey().unwrap(),
+    };
+    assert!(matches!(
+        proof.checked_check_by_output(&other_txout),
+        Err(TimelockPixelProofError::ScriptPubkeyMismatch { .. })
+    ));
+}
+
+#[test]
+fn test_timelock_proof_check_by_input() {
+    let proof = proof(LOCKTIME);
+
+    let txin = txin(&proof, Sequence::ENABLE_LOCKTIME_NO_RBF);
+    assert!(PixelProof::from(proof.clone())
+        .checked_check_by_input(&txin)
+        .is_ok());
+
+    let other_txin = self::txin(&self::proof(LOCKTIME + 1), Sequence::ENABLE_LOCKTIME_NO_RBF);
+    assert!(matches!(
+        proof.checked_check_by_input(&other_txin),
+        Err(TimelockPixelProofError::RedeemScriptMismatch { .. })
+    ));
+
+    // Final sequence disables the locktime, so the input could be spent before it.
+    let final_txin = self::txin(&proof, Sequence::MAX);
+    assert!(matches!(
+        proof.checked_check_by_input(&final_txin),
+        Err(TimelockPixelProofError::FinalSequence)
+    ));
+}
+
+#[test]
+fn test_zero_locktime_is_rejected() {
+    let proof = proof(0);
+
+    assert!(matches!(
+        proof.to_redeem_script(),
+        Err(TimelockPixelProofError::ZeroLocktime)
+    ));
+}
@@ -0,0 +1,209 @@
+//! This module provides the client side batching of the provided proofs, so wallets that finalize
+//! many transactions don't make a round-trip for each of them.
+
+use std::{collections::HashMap, mem};
+
+use bitcoin::Txid;
+use futures::{stream, StreamExt};
+use jsonrpsee::core::Error;
+use yuv_types::YuvTransaction;
+
+use crate::transactions::YuvTransactionsRpcClient;
+
+/// Default number of [`providelistyuvproofs`] requests sent at once, which fits the default rate
+/// limit of the method.
+///
+/// [`providelistyuvproofs`]: YuvTransactionsRpcClient::provide_list_yuv_proofs
+pub const DEFAULT_BATCH_CONCURRENCY: usize = 2;
+
+/// Size in bytes reserved for the JSON RPC request envelope (`jsonrpc`, `id`, `method`) around
+/// the list of transactions.
+const REQUEST_ENVELOPE_SIZE: usize = 128;
+
+/// Result of providing the proofs in batches.
+#[derive(Debug, Default)]
+pub struct ProvideProofsReport {
+    /// Whether the proofs of the transaction were accepted by the node.
+    pub accepted: HashMap<Txid, bool>,
+    /// Chunks of transactions which requests failed, ordered by index.
+    pub failed_chunks: Vec<FailedChunk>,
+}
+
+impl ProvideProofsReport {
+    /// Returns `true` if the proofs of all the transactions were accepted.
+    pub fn is_success(&self) -> bool {
+        self.failed_chunks.is_empty() && self.accepted.values().all(|accepted| *accepted)
+    }
+}
+
+/// Chunk of transactions which [`providelistyuvproofs`] request failed.
+///
+/// [`providelistyuvproofs`]: YuvTransactionsRpcClient::provide_list_yuv_proofs
+#[derive(Debug)]
+pub struct FailedChunk {
+    /// Index of the chunk in the order of the provided transactions.
+    pub index: usize,
+    /// Ids of the transactions in the chunk.
+    pub txids: Vec<Txid>,
+    /// Error of the request.
+    pub error: Error,
+}
+
+/// Extension of the [`YuvTransactionsRpcClient`] with the methods built on top of the RPC ones.
+#[async_trait::async_trait]
+pub trait YuvTransactionsRpcClientExt: YuvTransactionsRpcClient + Sync {
+    /// Provide proofs of the transactions with [`providelistyuvproofs`] requests of at most
+    /// `chunk_size` transactions each, sending [`DEFAULT_BATCH_CONCURRENCY`] requests at once.
+    ///
+    /// See [`Self::provide_yuv_proofs_batched_with_concurrency`].
+    ///
+    /// [`providelistyuvproofs`]: YuvTransactionsRpcClient::provide_list_yuv_proofs
+    async fn provide_yuv_proofs_batched(
+        &self,
+        yuv_txs: Vec<YuvTransaction>,
+        chunk_size: usize,
+    ) -> Result<ProvideProofsReport, Error> {
+        self.provide_yuv_proofs_batched_with_concurrency(
+            yuv_txs,
+            chunk_size,
+            DEFAULT_BATCH_CONCURRENCY,
+        )
+        .await
+    }
+
+    /// Provide proofs of the transactions with [`providelistyuvproofs`] requests of at most
+    /// `chunk_size` transactions each, sending `concurrency` requests at once.
+    ///
+    /// Chunks are also limited by the node's max number of items and size of the request body
+    /// got with [`getrpclimits`]. A failed request doesn't stop the others, it's reported in
+    /// [`ProvideProofsReport::failed_chunks`] instead. Returns an error only if the limits can't
+    /// be got, or the transactions can't be serialized.
+    ///
+    /// [`providelistyuvproofs`]: YuvTransactionsRpcClient::provide_list_yuv_proofs
+    /// [`getrpclimits`]: YuvTransactionsRpcClient::get_rpc_limits
+    async fn provide_yuv_proofs_batched_with_concurrency(
+        &self,
+        yuv_txs: Vec<YuvTransaction>,
+        chunk_size: usize,
+        concurrency: usize,
+    ) -> Result<ProvideProofsReport, Error> {
+        let limits = self.get_rpc_limits().await?;
+
+        let max_items = chunk_size.min(limits.max_items_per_request);
+        let chunks = split_into_chunks(yuv_txs, max_items, limits.max_request_body_size)?;
+
+        let results = stream::iter(chunks.into_iter().enumerate())
+            .map(|(index, chunk)| async move {
+                let txids = chunk
+                    .iter()
+                    .map(|yuv_tx| yuv_tx.bitcoin_tx.txid())
+                    .collect::<Vec<_>>();
+
+                let result = self.provide_list_yuv_proofs(chunk).await;
+
+                (index, txids, result)
+            })
+            .buffer_unordered(concurrency.max(1))
+            .collect::<Vec<_>>()
+            .await;
+
+        let mut report = ProvideProofsReport::default();
+
+        for (index, txids, result) in results {
+            match result {
+                Ok(accepted) => {
+                    report
+                        .accepted
+                        .extend(txids.into_iter().map(|txid| (txid, accepted)));
+                }
+                Err(error) => {
+                    report
+                        .accepted
+                        .extend(txids.iter().map(|txid| (*txid, false)));
+                    report.failed_chunks.push(FailedChunk {
+                        index,
+                        txids,
+                        error,
+                    });
+                }
+            }
+        }
+
+        report.failed_chunks.sort_by_key(|chunk| chunk.index);
+
+        Ok(report)
+    }
+}
+
+impl<T: YuvTransactionsRpcClient + Sync> YuvTransactionsRpcClientExt for T {}
+
+/// Split the transactions into chunks of at most `max_items` transactions, which serialized size
+/// is under `max_body_size`. A transaction that doesn't fit the size alone is put into its own
+/// chunk, so the node reports the error for it.
+fn split_into_chunks(
+    yuv_txs: Vec<YuvTransaction>,
+    max_items: usize,
+    max_body_size: usize,
+) -> Result<Vec<Vec<YuvTransaction>>, serde_json::Error> {
+    let max_items = max_items.max(1);
+    let max_txs_size = max_body_size.saturating_sub(REQUEST_ENVELOPE_SIZE);
+
+    let mut chunks = Vec::new();
+    let mut chunk = Vec::new();
+    let mut chunk_size = 0;
+
+    for yuv_tx in yuv_txs {
+        // Transactions are separated with commas in the list.
+        let tx_size = serde_json::to_vec(&yuv_tx)?.len() + 1;
+
+        if !chunk.is_empty() && (chunk.len() >= max_items || chunk_size + tx_size > max_txs_size) {
+            chunks.push(mem::take(&mut chunk));
+            chunk_size = 0;
+        }
+
+        chunk_size += tx_size;
+        chunk.push(yuv_tx);
+    }
+
+    if !chunk.is_empty() {
+        chunks.push(chunk);
+    }
+
+    Ok(chunks)
+}
+
+#[cfg(test)]
+mod tests {
+    use yuv_types::mocks::yuv_tx;
+
+    use super::*;
+
+    fn chunk_lens(chunks: &[Vec<YuvTransaction>]) -> Vec<usize> {
+        chunks.iter().map(Vec::len).collect()
+    }
+
+    #[test]
+    fn test_chunks_are_limited_by_items() {
+        let yuv_txs = (0..5).map(yuv_tx).collect::<Vec<_>>();
+
+        let chunks = split_into_chunks(yuv_txs, 2, usize::MAX).unwrap();
+
+        assert_eq!(chunk_lens(&chunks), vec![2, 2, 1]);
+    }
+
+    #[test]
+    fn test_chunks_are_limited_by_body_size() {
+        let yuv_txs = (0..5).map(yuv_tx).collect::<Vec<_>>();
+        let tx_size = serde_json::to_vec(&yuv_txs[0]).unwrap().len() + 1;
+
+        // Three transactions fit the body, but the fourth one doesn't.
+        let max_body_size = REQUEST_ENVELOPE_SIZE + tx_size * 3 + tx_size / 2;
+
+        let chunks = split_into_chunks(yuv_txs.clone(), 10, max_body_size).unwrap();
+        assert_eq!(chunk_lens(&chunks), vec![3, 2]);
+
+        // Transaction that doesn't fit the body alone is sent in its own chunk.
+        let chunks = split_into_chunks(yuv_txs, 10, tx_size).unwrap();
+        assert_eq!(chunk_lens(&chunks), vec![1, 1, 1, 1, 1]);
+    }
+}
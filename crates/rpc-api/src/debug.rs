@@ -0,0 +1,133 @@
+use std::fmt::Write;
+
+use bitcoin::Txid;
+use jsonrpsee::{core::RpcResult, proc_macros::rpc};
+
+/// Status of the transaction in the dependency graph of
+/// [`dumpgraph`](DebugRpcServer::dump_graph) RPC method.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GraphNodeStatus {
+    /// Transaction is waiting for its parents to be attached.
+    Pending,
+    /// Parents of the transaction are attached, but it's waiting for enough
+    /// confirmations.
+    Unconfirmed,
+    /// Parent other transactions are waiting for, which isn't received by the
+    /// node.
+    Missing,
+}
+
+/// Transaction in the dependency graph.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
+pub struct GraphNodeResponse {
+    pub txid: Txid,
+    pub status: GraphNodeStatus,
+}
+
+/// Dependency of the transaction on the parent it's waiting for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
+pub struct GraphEdgeResponse {
+    pub txid: Txid,
+    pub parent: Txid,
+}
+
+/// Response for [`dumpgraph`](DebugRpcServer::dump_graph) RPC method.
+#[derive(Debug, Clone, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct DumpGraphResponse {
+    /// Transactions that are not attached yet.
+    pub nodes: Vec<GraphNodeResponse>,
+    /// Dependencies between the `nodes`.
+    pub edges: Vec<GraphEdgeResponse>,
+    /// Some of the transactions are dropped, as there are more of them than
+    /// the limit.
+    pub truncated: bool,
+}
+
+impl DumpGraphResponse {
+    /// Render the graph in the DOT language, e.g. to visualize it with
+    /// Graphviz. Edges are directed from the transaction to its parent.
+    pub fn to_dot(&self) -> String {
+        let mut dot = String::from("digraph yuv {\n");
+
+        for node in &self.nodes {
+            let style = match node.status {
+                GraphNodeStatus::Pending => "solid",
+                GraphNodeStatus::Unconfirmed => "dashed",
+                GraphNodeStatus::Missing => "dotted",
+            };
+
+            let _ = writeln!(
+                dot,
+                "    \"{}\" [label=\"{}\\n{:?}\", style={}];",
+                node.txid, node.txid, node.status, style
+            );
+        }
+
+        for edge in &self.edges {
+            let _ = writeln!(dot, "    \"{}\" -> \"{}\";", edge.txid, edge.parent);
+        }
+
+        dot.push('}');
+
+        dot
+    }
+}
+
+/// RPC methods for debugging the node. They expose its internal state and
+/// prune its storage, so are served only if enabled in the node's config.
+#[cfg_attr(feature = "client", rpc(server, client))]
+#[cfg_attr(not(feature = "client"), rpc(server))]
+#[async_trait::async_trait]
+pub trait DebugRpc {
+    /// Get the dependency graph of the transactions that are waiting to be
+    /// attached, as of the last event handled by the graph builder.
+    ///
+    /// The number of transactions in the graph is limited, see
+    /// [`DumpGraphResponse::truncated`].
+    #[method(name = "dumpgraph")]
+    async fn dump_graph(&self) -> RpcResult<DumpGraphResponse>;
+
+    /// Prune the attached transfers with all outputs spent that are older than the
+    /// retention configured in the node. Pruning is done in the background, so
+    /// `true` means that it's scheduled.
+    #[method(name = "pruneyuvtransactions")]
+    async fn prune_yuv_transactions(&self) -> RpcResult<bool>;
+}
+
+#[cfg(test)]
+mod tests {
+    use bitcoin::hashes::Hash;
+
+    use super::*;
+
+    #[test]
+    fn test_graph_to_dot() {
+        let parent = Txid::from_inner([1; 32]);
+        let txid = Txid::from_inner([2; 32]);
+
+        let graph = DumpGraphResponse {
+            nodes: vec![
+                GraphNodeResponse {
+                    txid: parent,
+                    status: GraphNodeStatus::Missing,
+                },
+                GraphNodeResponse {
+                    txid,
+                    status: GraphNodeStatus::Pending,
+                },
+            ],
+            edges: vec![GraphEdgeResponse { txid, parent }],
+            truncated: false,
+        };
+
+        let dot = graph.to_dot();
+
+        assert!(dot.starts_with("digraph yuv {\n"));
+        assert!(dot.ends_with('}'));
+        assert!(dot.contains(&format!("\"{txid}\" -> \"{parent}\";")));
+        assert!(dot.contains(&format!(
+            "\"{parent}\" [label=\"{parent}\\nMissing\", style=dotted];"
+        )));
+    }
+}
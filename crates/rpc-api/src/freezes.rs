@@ -0,0 +1,113 @@
+use bitcoin::OutPoint;
+use jsonrpsee::{core::SubscriptionResult, proc_macros::rpc};
+use yuv_pixels::Chroma;
+use yuv_types::announcements::FreezeToggle;
+
+/// Filter of the freeze toggles for [`subscribefreezes`](YuvFreezesRpcServer::subscribe_freezes)
+/// RPC subscription. Toggles of all the tokens are matched if none of the fields is set.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
+pub struct FreezeFilter {
+    /// Match only the toggles of the token.
+    #[serde(default)]
+    pub chroma: Option<Chroma>,
+    /// Match only the toggles affecting the output. Chroma-wide toggles are matched only if the
+    /// chroma of the output is known, i.e. it's set in the filter.
+    #[serde(default)]
+    pub outpoint: Option<OutPoint>,
+}
+
+impl FreezeFilter {
+    /// Returns `true` if the toggle affects the outputs matched by the filter.
+    pub fn matches(&self, toggle: &FreezeToggle) -> bool {
+        if let Some(chroma) = &self.chroma {
+            if toggle.chroma != *chroma {
+                return false;
+            }
+        }
+
+        match (&self.outpoint, &toggle.outpoint) {
+            (None, _) => true,
+            (Some(outpoint), Some(toggled)) => outpoint == toggled,
+            (Some(_), None) => self.chroma.is_some(),
+        }
+    }
+}
+
+/// RPC subscriptions for freezes of the outputs.
+#[cfg_attr(feature = "client", rpc(server, client))]
+#[cfg_attr(not(feature = "client"), rpc(server))]
+#[async_trait::async_trait]
+pub trait YuvFreezesRpc {
+    /// Subscribe to the freeze toggles matching the filter, which are pushed once the freeze
+    /// announcements are attached.
+    ///
+    /// Available only over WebSocket.
+    #[subscription(
+        name = "subscribefreezes" => "freeze",
+        unsubscribe = "unsubscribefreezes",
+        item = FreezeToggle
+    )]
+    async fn subscribe_freezes(&self, filter: Option<FreezeFilter>) -> SubscriptionResult;
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use bitcoin::{hashes::Hash, Txid, XOnlyPublicKey};
+
+    use super::*;
+
+    fn chroma(key: &str) -> Chroma {
+        XOnlyPublicKey::from_str(key).unwrap().into()
+    }
+
+    #[test]
+    fn test_filter_matches_toggles() {
+        let chroma1 = chroma("5510996bdb5271f84896eb42ea5b6c4ba3bd96f90a605c70a7f2b402f0afdad0");
+        let chroma2 = chroma("6a5e3a83f0b2bdfb2f874c6f4679dc02568deb8987d11314a36bceacb569ad8e");
+        let outpoint = OutPoint::new(Txid::from_inner([1; 32]), 0);
+
+        let toggle = |chroma, outpoint| FreezeToggle {
+            txid: Txid::from_inner([2; 32]),
+            chroma,
+            outpoint,
+            frozen: true,
+        };
+
+        let output_toggle = toggle(chroma1, Some(outpoint));
+        let other_output_toggle = toggle(chroma1, Some(OutPoint::new(outpoint.txid, 1)));
+        let chroma_toggle = toggle(chroma1, None);
+        let other_chroma_toggle = toggle(chroma2, None);
+
+        let all = FreezeFilter::default();
+        assert!(all.matches(&output_toggle));
+        assert!(all.matches(&other_chroma_toggle));
+
+        let by_chroma = FreezeFilter {
+            chroma: Some(chroma1),
+            outpoint: None,
+        };
+        assert!(by_chroma.matches(&output_toggle));
+        assert!(by_chroma.matches(&chroma_toggle));
+        assert!(!by_chroma.matches(&other_chroma_toggle));
+
+        let by_outpoint = FreezeFilter {
+            chroma: None,
+            outpoint: Some(outpoint),
+        };
+        assert!(by_outpoint.matches(&output_toggle));
+        assert!(!by_outpoint.matches(&other_output_toggle));
+        // Chroma of the output is unknown, so the chroma-wide toggles can't be matched.
+        assert!(!by_outpoint.matches(&chroma_toggle));
+
+        let by_both = FreezeFilter {
+            chroma: Some(chroma1),
+            outpoint: Some(outpoint),
+        };
+        assert!(by_both.matches(&output_toggle));
+        assert!(by_both.matches(&chroma_toggle));
+        assert!(!by_both.matches(&other_output_toggle));
+        assert!(!by_both.matches(&other_chroma_toggle));
+    }
+}
@@ -0,0 +1,72 @@
+use jsonrpsee::{core::RpcResult, proc_macros::rpc};
+
+/// Response for [`health`](HealthRpcServer::health) RPC method.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
+pub struct HealthResponse {
+    /// Node's storage has successfully handled a read request.
+    pub storage_ok: bool,
+    /// Last indexed block is not behind the best block more than by the
+    /// confirmations number.
+    pub indexer_synced: bool,
+    /// Number of connected peers.
+    pub peer_count: usize,
+    /// At least one of the transaction checkers is running.
+    pub checker_alive: bool,
+}
+
+impl HealthResponse {
+    /// Returns `true` if all the critical subsystems of the node are up.
+    ///
+    /// Not synced indexer or absence of peers doesn't make the node dead, only
+    /// not ready to serve the up to date data.
+    pub fn is_alive(&self) -> bool {
+        self.storage_ok && self.checker_alive
+    }
+}
+
+/// Response item for [`getqueuestats`](HealthRpcServer::get_queue_stats) RPC
+/// method.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
+pub struct QueueStatsResponse {
+    /// Name of the message type passed through the queue.
+    pub name: String,
+    /// Number of messages waiting to be handled.
+    pub len: usize,
+    /// Max number of messages in the queue. `None` if the queue is unbounded.
+    pub capacity: Option<usize>,
+}
+
+/// Response item for [`getdeadletters`](HealthRpcServer::get_dead_letters) RPC
+/// method.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
+pub struct DeadLetterResponse {
+    /// Unix timestamp in seconds when the message was dropped.
+    pub timestamp: u64,
+    /// Kind of the message, e.g. `P2P::Inv`.
+    pub kind: String,
+    /// Error the handling of the message failed with.
+    pub error: String,
+}
+
+/// RPC methods for monitoring the node's state.
+#[cfg_attr(feature = "client", rpc(server, client))]
+#[cfg_attr(not(feature = "client"), rpc(server))]
+#[async_trait::async_trait]
+pub trait HealthRpc {
+    /// Get the status of the node's subsystems.
+    ///
+    /// Returns an error with the status in the data only when a critical
+    /// subsystem is down.
+    #[method(name = "health")]
+    async fn health(&self) -> RpcResult<HealthResponse>;
+
+    /// Get the number of messages queued between the node's services, which
+    /// shows the service that is the bottleneck.
+    #[method(name = "getqueuestats")]
+    async fn get_queue_stats(&self) -> RpcResult<Vec<QueueStatsResponse>>;
+
+    /// Get the recent messages the controller failed to handle, from the
+    /// oldest to the newest.
+    #[method(name = "getdeadletters")]
+    async fn get_dead_letters(&self) -> RpcResult<Vec<DeadLetterResponse>>;
+}
@@ -1 +1,6 @@
+#[cfg(feature = "client")]
+pub mod batch;
+pub mod debug;
+pub mod freezes;
+pub mod health;
 pub mod transactions;
@@ -1,8 +1,8 @@
-use bitcoin::Txid;
+use bitcoin::{TxOut, Txid};
 use jsonrpsee::{core::RpcResult, proc_macros::rpc};
-use yuv_pixels::Chroma;
+use yuv_pixels::{Chroma, PixelProof};
 use yuv_types::announcements::ChromaInfo;
-use yuv_types::YuvTransaction;
+use yuv_types::{YuvProofBundle, YuvTransaction};
 
 /// Response for [`getrawyuvtransaction`](YuvTransactionsRpcServer::get_raw_yuv_transaction) RPC
 /// method.
@@ -42,6 +42,89 @@ impl EmulateYuvTransactionResponse {
     pub fn invalid(reason: String) -> Self {
         Self::Invalid { reason }
     }
+
+    pub fn is_valid(&self) -> bool {
+        matches!(self, Self::Valid)
+    }
+}
+
+/// Response for [`emulatelistyuvtransactions`](YuvTransactionsRpcServer::emulate_list_yuv_transactions)
+/// RPC method.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
+pub struct EmulateYuvTransactionsResponse {
+    /// Whether all the transactions could be attached by the node.
+    pub valid: bool,
+    /// Results of the transactions in the order they are provided.
+    pub results: Vec<EmulateYuvTransactionResponse>,
+}
+
+/// Response for [`getinvalidtx`](YuvTransactionsRpcServer::get_invalid_tx) RPC method.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
+pub struct GetInvalidTxResponse {
+    /// Reason of the transaction rejection. `None` for transactions that were rejected before
+    /// the reasons started being stored.
+    pub reason: Option<String>,
+    /// Unix timestamp (in seconds) of the transaction rejection.
+    pub rejected_at: Option<u64>,
+}
+
+/// Response for [`getrpclimits`](YuvTransactionsRpcServer::get_rpc_limits) RPC method.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
+pub struct GetRpcLimitsResponse {
+    /// Max number of items (e.g. transactions or ids) the node accepts or returns per request.
+    pub max_items_per_request: usize,
+    /// Max size of the request body in bytes.
+    pub max_request_body_size: usize,
+}
+
+/// Response for [`getchromaannouncementtx`](YuvTransactionsRpcServer::get_chroma_announcement_tx)
+/// RPC method.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case", tag = "status", content = "data")]
+#[allow(clippy::large_enum_variant)]
+pub enum GetChromaAnnouncementTxResponse {
+    /// Node knows nothing about the chroma.
+    NotFound,
+
+    /// Chroma is issued without the announcement, so its info is implicit.
+    NotAnnounced,
+
+    /// Chroma is announced. The `txid` is `None` if it was announced before the node started
+    /// storing the ids of the announcements, and the `tx` is `None` if the transaction is not
+    /// in the node's storage.
+    Announced {
+        txid: Option<Txid>,
+        tx: Option<YuvTransaction>,
+    },
+}
+
+/// Response for [`providepartialyuvproof`](YuvTransactionsRpcServer::provide_partial_yuv_proof)
+/// RPC method.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case", tag = "status", content = "data")]
+pub enum ProvidePartialYuvProofResponse {
+    /// Proofs are merged with the ones provided before, but the transaction is not complete yet
+    /// for the given reason, e.g. some of its outputs are not proven.
+    Pending { reason: String },
+
+    /// All the proofs are provided, and the transaction is sent to be checked.
+    Provided,
+}
+
+/// Response for [`getoutputproof`](YuvTransactionsRpcServer::get_output_proof) RPC method.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case", tag = "status", content = "data")]
+#[allow(clippy::large_enum_variant)]
+pub enum GetOutputProofResponse {
+    /// Transaction is not attached.
+    TxNotFound,
+
+    /// Transaction is attached, but the output has no proof, e.g. it's a change in Bitcoins, or
+    /// there is no output with this index.
+    NotYuvOutput,
+
+    /// Proof of the output and the output itself.
+    Found { proof: PixelProof, txout: TxOut },
 }
 
 /// RPC methods for transactions.
@@ -57,6 +140,28 @@ pub trait YuvTransactionsRpc {
     #[method(name = "providelistyuvproofs")]
     async fn provide_list_yuv_proofs(&self, yuv_txs: Vec<YuvTransaction>) -> RpcResult<bool>;
 
+    /// Provide part of the proofs to YUV transaction, e.g. the proofs of the outputs of one of
+    /// the multisig participants. The proofs are merged with the ones provided before for the
+    /// same transaction, and it's sent to be checked once it's complete.
+    ///
+    /// Proofs that conflict with the provided ones for the same input or output are rejected.
+    /// Transactions that aren't completed in time are dropped.
+    #[method(name = "providepartialyuvproof")]
+    async fn provide_partial_yuv_proof(
+        &self,
+        yuv_tx: YuvTransaction,
+    ) -> RpcResult<ProvidePartialYuvProofResponse>;
+
+    /// Get the limits of the requests the node accepts, so the clients could split the lists
+    /// they provide or request.
+    #[method(name = "getrpclimits")]
+    async fn get_rpc_limits(&self) -> RpcResult<GetRpcLimitsResponse>;
+
+    /// Provide proofs to YUV transaction without the Bitcoin transaction, which is looked up by
+    /// its id.
+    #[method(name = "provideyuvproofbundle")]
+    async fn provide_yuv_proof_bundle(&self, bundle: YuvProofBundle) -> RpcResult<bool>;
+
     /// Get YUV transaction by id and return its proofs.
     #[method(name = "getrawyuvtransaction")]
     async fn get_raw_yuv_transaction(&self, txid: Txid) -> RpcResult<GetRawYuvTransactionResponse>;
@@ -70,8 +175,21 @@ pub trait YuvTransactionsRpc {
     ) -> RpcResult<Vec<YuvTransaction>>;
 
     /// Get transaction list by page number.
+    ///
+    /// If `limit` or `offset` is set, pages of `limit` transactions (storage page size by
+    /// default) are returned instead of the storage ones, shifted by `offset` transactions.
     #[method(name = "listyuvtransactions")]
-    async fn list_yuv_transactions(&self, page: u64) -> RpcResult<Vec<YuvTransaction>>;
+    async fn list_yuv_transactions(
+        &self,
+        page: u64,
+        limit: Option<u64>,
+        offset: Option<u64>,
+    ) -> RpcResult<Vec<YuvTransaction>>;
+
+    /// Get the proof of the single output of the attached YUV transaction with the output
+    /// itself, so the whole transaction with the unrelated proofs isn't requested.
+    #[method(name = "getoutputproof")]
+    async fn get_output_proof(&self, txid: Txid, vout: u32) -> RpcResult<GetOutputProofResponse>;
 
     /// Send YUV transaction to Bitcoin network.
     #[method(name = "sendrawyuvtransaction")]
@@ -85,18 +203,59 @@ pub trait YuvTransactionsRpc {
     #[method(name = "isyuvtxoutfrozen")]
     async fn is_yuv_txout_frozen(&self, txid: Txid, vout: u32) -> RpcResult<bool>;
 
+    /// Get the id of the attached transaction that spent the YUV output. If the output
+    /// is unspent, or the spending transaction isn't attached yet, `null` is returned.
+    #[method(name = "getspendingtx")]
+    async fn get_spending_tx(&self, txid: Txid, vout: u32) -> RpcResult<Option<Txid>>;
+
     /// Emulate transaction check and attach without actuall broadcasting or
     /// mining it to the network.
     ///
     /// This method is useful for checking if node can immidiatelly check and
     /// attach transaction to internal storage.
+    ///
+    /// If `at_height` is set, the inputs are checked to be frozen as of the block
+    /// at this height instead of the current state, which is useful for auditing.
+    /// It's best-effort: if the height of any freeze is unknown, as it was applied
+    /// before the heights are stored, the current state is used instead.
     #[method(name = "emulateyuvtransaction")]
     async fn emulate_yuv_transaction(
         &self,
         yuv_tx: YuvTransaction,
+        at_height: Option<u64>,
     ) -> RpcResult<EmulateYuvTransactionResponse>;
 
+    /// Emulate check and attach of the transactions as if they were provided together, e.g. a
+    /// chain of an issuance and the transfers spending it.
+    ///
+    /// A transaction could spend the outputs of the ones from the list, in any order, while the
+    /// same output can't be spent twice. The node storage isn't changed.
+    #[method(name = "emulatelistyuvtransactions")]
+    async fn emulate_list_yuv_transactions(
+        &self,
+        yuv_txs: Vec<YuvTransaction>,
+    ) -> RpcResult<EmulateYuvTransactionsResponse>;
+
     /// Get the [ChromaInfo] that contains the information about the token.
     #[method(name = "getchromainfo")]
     async fn get_chroma_info(&self, chroma: Chroma) -> RpcResult<Option<ChromaInfo>>;
+
+    /// Get the id of the transaction that announced the chroma, with the transaction itself, e.g.
+    /// to audit the announced info of the token.
+    #[method(name = "getchromaannouncementtx")]
+    async fn get_chroma_announcement_tx(
+        &self,
+        chroma: Chroma,
+    ) -> RpcResult<GetChromaAnnouncementTxResponse>;
+
+    /// Get the reason why YUV transaction was rejected by the node. If the transaction
+    /// wasn't rejected, or its record has already expired, `null` is returned.
+    #[method(name = "getinvalidtx")]
+    async fn get_invalid_tx(&self, txid: Txid) -> RpcResult<Option<GetInvalidTxResponse>>;
+
+    /// Share the inventory of the attached transactions with the peers right away, instead of
+    /// waiting for the next sharing of the whole inventory. Sharing is done in the background,
+    /// so `true` means that it's scheduled.
+    #[method(name = "shareinventory")]
+    async fn share_inventory(&self, txids: Vec<Txid>) -> RpcResult<bool>;
 }
@@ -0,0 +1,79 @@
+use async_trait::async_trait;
+use event_bus::{typeid, EventBus};
+use jsonrpsee::{
+    core::RpcResult,
+    types::{error::INTERNAL_ERROR_CODE, ErrorObjectOwned},
+};
+use yuv_rpc_api::debug::{
+    DebugRpcServer, DumpGraphResponse, GraphEdgeResponse, GraphNodeResponse, GraphNodeStatus,
+};
+use yuv_tx_attach::{GraphNodeStatus as NodeStatus, GraphSnapshot};
+use yuv_types::GraphBuilderMessage;
+
+/// Controller for the methods exposing the node's internal state.
+pub struct DebugController {
+    /// Snapshot of the graph builder's dependency graph.
+    graph_snapshot: GraphSnapshot,
+    event_bus: EventBus,
+}
+
+impl DebugController {
+    pub fn new(graph_snapshot: GraphSnapshot, full_event_bus: &EventBus) -> Self {
+        let event_bus = full_event_bus
+            .extract(&typeid![GraphBuilderMessage], &typeid![])
+            .expect("event channels must be presented");
+
+        Self {
+            graph_snapshot,
+            event_bus,
+        }
+    }
+}
+
+#[async_trait]
+impl DebugRpcServer for DebugController {
+    async fn dump_graph(&self) -> RpcResult<DumpGraphResponse> {
+        let graph = self.graph_snapshot.get();
+
+        let nodes = graph
+            .nodes
+            .into_iter()
+            .map(|node| GraphNodeResponse {
+                txid: node.txid,
+                status: match node.status {
+                    NodeStatus::Pending => GraphNodeStatus::Pending,
+                    NodeStatus::Unconfirmed => GraphNodeStatus::Unconfirmed,
+                    NodeStatus::Missing => GraphNodeStatus::Missing,
+                },
+            })
+            .collect();
+
+        let edges = graph
+            .edges
+            .into_iter()
+            .map(|(txid, parent)| GraphEdgeResponse { txid, parent })
+            .collect();
+
+        Ok(DumpGraphResponse {
+            nodes,
+            edges,
+            truncated: graph.truncated,
+        })
+    }
+
+    async fn prune_yuv_transactions(&self) -> RpcResult<bool> {
+        self.event_bus
+            .try_send(GraphBuilderMessage::Prune)
+            .await
+            .map_err(|_| {
+                tracing::error!("failed to send message to graph builder");
+                ErrorObjectOwned::owned(
+                    INTERNAL_ERROR_CODE,
+                    "Service is dead",
+                    Option::<Vec<u8>>::None,
+                )
+            })?;
+
+        Ok(true)
+    }
+}
@@ -0,0 +1,88 @@
+use async_trait::async_trait;
+use jsonrpsee::{core::SubscriptionResult, server::SubscriptionMessage, PendingSubscriptionSink};
+use tokio::sync::broadcast::error::RecvError;
+use yuv_rpc_api::freezes::{FreezeFilter, YuvFreezesRpcServer};
+use yuv_storage::TransactionsStorage;
+use yuv_tx_attach::FreezeSinks;
+
+/// Controller for the subscriptions to the freezes.
+pub struct FreezesController<TransactionsStorage> {
+    /// Internal storage of transactions.
+    txs_storage: TransactionsStorage,
+    /// Sinks the freeze toggles are pushed to by the graph builder.
+    freeze_sinks: FreezeSinks,
+}
+
+impl<TXS> FreezesController<TXS>
+where
+    TXS: TransactionsStorage + Send + Sync + 'static,
+{
+    pub fn new(txs_storage: TXS, freeze_sinks: FreezeSinks) -> Self {
+        Self {
+            txs_storage,
+            freeze_sinks,
+        }
+    }
+
+    /// Set the chroma of the filtered output if it's attached, so the chroma-wide toggles
+    /// affecting it are matched too.
+    async fn resolve_filter(&self, mut filter: FreezeFilter) -> FreezeFilter {
+        let (Some(outpoint), None) = (filter.outpoint, filter.chroma) else {
+            return filter;
+        };
+
+        match self.txs_storage.get_yuv_tx(&outpoint.txid).await {
+            Ok(Some(yuv_tx)) => {
+                filter.chroma = yuv_tx
+                    .tx_type
+                    .output_proofs()
+                    .and_then(|proofs| proofs.get(&outpoint.vout))
+                    .map(|proof| proof.pixel().chroma);
+            }
+            Ok(None) => {}
+            Err(err) => tracing::error!("Failed to get transaction: {err}"),
+        }
+
+        filter
+    }
+}
+
+#[async_trait]
+impl<TXS> YuvFreezesRpcServer for FreezesController<TXS>
+where
+    TXS: TransactionsStorage + Send + Sync + 'static,
+{
+    async fn subscribe_freezes(
+        &self,
+        pending: PendingSubscriptionSink,
+        filter: Option<FreezeFilter>,
+    ) -> SubscriptionResult {
+        let filter = self.resolve_filter(filter.unwrap_or_default()).await;
+
+        // Subscribe before accepting, so no toggles are missed after the client is notified.
+        let mut toggles = self.freeze_sinks.subscribe();
+        let sink = pending.accept().await?;
+
+        loop {
+            tokio::select! {
+                toggle = toggles.recv() => {
+                    let toggle = match toggle {
+                        Ok(toggle) => toggle,
+                        Err(RecvError::Lagged(skipped)) => {
+                            tracing::warn!("Freeze subscription skipped {skipped} toggles");
+                            continue;
+                        }
+                        Err(RecvError::Closed) => return Ok(()),
+                    };
+
+                    if !filter.matches(&toggle) {
+                        continue;
+                    }
+
+                    sink.send(SubscriptionMessage::from_json(&toggle)?).await?;
+                }
+                _ = sink.closed() => return Ok(()),
+            }
+        }
+    }
+}
@@ -0,0 +1,185 @@
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+
+use async_trait::async_trait;
+use bitcoin_client::BitcoinRpcApi;
+use event_bus::EventBus;
+use jsonrpsee::{
+    core::RpcResult,
+    types::{error::INTERNAL_ERROR_CODE, ErrorObject},
+};
+use yuv_controller::DeadLetters;
+use yuv_p2p::client::handle::Handle;
+use yuv_rpc_api::health::{
+    DeadLetterResponse, HealthResponse, HealthRpcServer, QueueStatsResponse,
+};
+use yuv_storage::{BlockIndexerStorage, PagesNumberStorage};
+use yuv_tx_check::AliveWorkers;
+
+/// Max time to wait for the list of peers from the P2P client, so the
+/// health check stays cheap even if the P2P client is stuck.
+const GET_PEERS_TIMEOUT: Duration = Duration::from_millis(500);
+
+/// Controller for the node's health check.
+pub struct HealthController<TransactionsStorage, StateStorage, BitcoinClient, P2PHandle> {
+    /// Internal storage of transactions.
+    txs_storage: TransactionsStorage,
+    /// Internal storage of the indexer's state.
+    state_storage: StateStorage,
+    /// Bitcoin RPC Client.
+    bitcoin_client: Arc<BitcoinClient>,
+    /// Handle to the P2P client.
+    p2p_handle: P2PHandle,
+    /// Counter of the running transaction checkers.
+    checkers: AliveWorkers,
+    /// Number of blocks the indexer is allowed to be behind the best block.
+    confirmations_number: u8,
+    /// Event bus with channels between the node's services.
+    full_event_bus: EventBus,
+    /// Log of the messages the controller failed to handle.
+    dead_letters: DeadLetters,
+}
+
+impl<TXS, SS, BC, P> HealthController<TXS, SS, BC, P>
+where
+    TXS: PagesNumberStorage + Send + Sync + 'static,
+    SS: BlockIndexerStorage + Send + Sync + 'static,
+    BC: BitcoinRpcApi + Send + Sync + 'static,
+    P: Handle + 'static,
+{
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        txs_storage: TXS,
+        state_storage: SS,
+        bitcoin_client: Arc<BC>,
+        p2p_handle: P,
+        checkers: AliveWorkers,
+        confirmations_number: u8,
+        full_event_bus: EventBus,
+        dead_letters: DeadLetters,
+    ) -> Self {
+        Self {
+            txs_storage,
+            state_storage,
+            bitcoin_client,
+            p2p_handle,
+            checkers,
+            confirmations_number,
+            full_event_bus,
+            dead_letters,
+        }
+    }
+
+    /// Check that both storages are able to handle a read request.
+    async fn is_storage_ok(&self) -> bool {
+        if let Err(err) = self.txs_storage.get_pages_number().await {
+            tracing::warn!("Transactions storage health check failed: {}", err);
+            return false;
+        }
+
+        if let Err(err) = self.state_storage.get_last_indexed_hash().await {
+            tracing::warn!("State storage health check failed: {}", err);
+            return false;
+        }
+
+        true
+    }
+
+    /// Check that last indexed block is not behind the best one more than by
+    /// confirmations number.
+    async fn is_indexer_synced(&self) -> eyre::Result<bool> {
+        let Some(last_indexed_hash) = self.state_storage.get_last_indexed_hash().await? else {
+            return Ok(false);
+        };
+
+        let indexed_height = self
+            .bitcoin_client
+            .get_block_header_info(&last_indexed_hash)
+            .await?
+            .height as u64;
+        let best_height = self.bitcoin_client.get_block_count().await?;
+
+        Ok(best_height.saturating_sub(indexed_height) <= self.confirmations_number as u64)
+    }
+
+    async fn peer_count(&self) -> usize {
+        match tokio::time::timeout(GET_PEERS_TIMEOUT, self.p2p_handle.get_peers()).await {
+            Ok(Ok(peers)) => peers.len(),
+            Ok(Err(err)) => {
+                tracing::warn!("Failed to get peers for health check: {}", err);
+                0
+            }
+            Err(_) => {
+                tracing::warn!("Timed out getting peers for health check");
+                0
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl<TXS, SS, BC, P> HealthRpcServer for HealthController<TXS, SS, BC, P>
+where
+    TXS: PagesNumberStorage + Send + Sync + 'static,
+    SS: BlockIndexerStorage + Send + Sync + 'static,
+    BC: BitcoinRpcApi + Send + Sync + 'static,
+    P: Handle + 'static,
+{
+    async fn health(&self) -> RpcResult<HealthResponse> {
+        let indexer_synced = self.is_indexer_synced().await.unwrap_or_else(|err| {
+            tracing::warn!("Failed to check if indexer is synced: {}", err);
+            false
+        });
+
+        let response = HealthResponse {
+            storage_ok: self.is_storage_ok().await,
+            indexer_synced,
+            peer_count: self.peer_count().await,
+            checker_alive: self.checkers.is_alive(),
+        };
+
+        if !response.is_alive() {
+            return Err(ErrorObject::owned(
+                INTERNAL_ERROR_CODE,
+                "Critical subsystem of the node is down",
+                Some(response),
+            ));
+        }
+
+        Ok(response)
+    }
+
+    async fn get_queue_stats(&self) -> RpcResult<Vec<QueueStatsResponse>> {
+        let stats = self
+            .full_event_bus
+            .queue_stats()
+            .into_iter()
+            .map(|stats| QueueStatsResponse {
+                name: stats.name.to_string(),
+                len: stats.len,
+                capacity: stats.capacity,
+            })
+            .collect();
+
+        Ok(stats)
+    }
+
+    async fn get_dead_letters(&self) -> RpcResult<Vec<DeadLetterResponse>> {
+        let letters = self
+            .dead_letters
+            .list()
+            .into_iter()
+            .map(|letter| DeadLetterResponse {
+                timestamp: letter
+                    .timestamp
+                    .duration_since(SystemTime::UNIX_EPOCH)
+                    .unwrap_or_default()
+                    .as_secs(),
+                kind: letter.kind.to_string(),
+                error: letter.error,
+            })
+            .collect();
+
+        Ok(letters)
+    }
+}
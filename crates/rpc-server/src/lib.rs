@@ -5,61 +5,144 @@ use event_bus::EventBus;
 use jsonrpsee::server::Server;
 use tokio_util::sync::CancellationToken;
 
-use yuv_rpc_api::transactions::YuvTransactionsRpcServer;
+use yuv_controller::{DeadLetters, PartialProofs};
+use yuv_p2p::client::handle::Handle;
+use yuv_rpc_api::{
+    debug::DebugRpcServer, freezes::YuvFreezesRpcServer, health::HealthRpcServer,
+    transactions::YuvTransactionsRpcServer,
+};
 use yuv_storage::{
-    ChromaInfoStorage, FrozenTxsStorage, PagesStorage, TransactionsStorage, TxStatesStorage,
+    BlockIndexerStorage, ChromaInfoStorage, FrozenTxsStorage, InvalidTxsStorage,
+    PagesNumberStorage, PagesStorage, TransactionsStorage, TxStatesStorage,
 };
+use yuv_tx_attach::{FreezeSinks, GraphSnapshot};
+use yuv_tx_check::AliveWorkers;
+use yuv_types::DEFAULT_CONFIRMATIONS_NUMBER;
 
-use crate::transactions::TransactionsController;
+use crate::{
+    debug::DebugController,
+    freezes::FreezesController,
+    health::HealthController,
+    rate_limit::{PeerAddressLogger, RateLimitConfig, RateLimitLayer, RateLimiter},
+    transactions::TransactionsController,
+};
 
+pub mod debug;
+pub mod freezes;
+pub mod health;
+pub mod rate_limit;
 pub mod transactions;
 
+/// Default max size of the request body in bytes.
+///
+/// Should fit `max_items_per_request` transactions in `providelistyuvproofs`.
+/// The size of the transaction could be got with
+/// [`YuvTransaction::serialized_size`](yuv_types::YuvTransaction::serialized_size),
+/// while the JSON encoded one is about twice bigger.
+pub const DEFAULT_MAX_REQUEST_BODY_SIZE: usize = 20_000_000;
+
 pub struct ServerConfig {
     /// Address at which the server will listen for incoming connections.
     pub address: String,
     /// Max number of items to request/process per incoming request.
     pub max_items_per_request: usize,
+    /// Max size of the request body in bytes.
+    pub max_request_body_size: usize,
+    /// Number of blocks the indexer is allowed to be behind the best block
+    /// to be considered synced.
+    pub confirmations_number: Option<u8>,
+    /// Limits of the rate of requests per method and per client.
+    pub rate_limits: RateLimitConfig,
+    /// Max number of the inputs of the transaction the checkers accept. Unlimited if `None`.
+    pub max_inputs: Option<usize>,
+    /// Max number of the outputs of the transaction the checkers accept. Unlimited if `None`.
+    pub max_outputs: Option<usize>,
 }
 
 /// Runs YUV Node's RPC server.
-pub async fn run_server<S, AS>(
+///
+/// Debug methods are served only if the `graph_snapshot` is set, as they
+/// expose the node's internal state.
+#[allow(clippy::too_many_arguments)]
+pub async fn run_server<S, AS, P>(
     ServerConfig {
         address,
         max_items_per_request,
+        max_request_body_size,
+        confirmations_number,
+        rate_limits,
+        max_inputs,
+        max_outputs,
     }: ServerConfig,
     txs_storage: S,
     frozen_storage: AS,
     full_event_bus: EventBus,
     txs_states_storage: TxStatesStorage,
+    partial_proofs: PartialProofs,
     bitcoin_client: Arc<BitcoinRpcClient>,
+    p2p_handle: P,
+    checkers: AliveWorkers,
+    freeze_sinks: FreezeSinks,
+    dead_letters: DeadLetters,
+    graph_snapshot: Option<GraphSnapshot>,
     cancellation: CancellationToken,
 ) -> eyre::Result<()>
 where
-    S: TransactionsStorage + PagesStorage + Clone + Send + Sync + 'static,
-    AS: FrozenTxsStorage + ChromaInfoStorage + Clone + Send + Sync + 'static,
+    S: TransactionsStorage + PagesStorage + PagesNumberStorage + Clone + Send + Sync + 'static,
+    AS: FrozenTxsStorage
+        + ChromaInfoStorage
+        + BlockIndexerStorage
+        + InvalidTxsStorage
+        + Clone
+        + Send
+        + Sync
+        + 'static,
+    P: Handle + 'static,
 {
-    // The multiplication of average transaction size and max number of items
-    // per request approximately gives the maximum JSON RPC request size.
-    //
-    // See `providelistyuvproofs`
-    let max_request_body_size = 20_000_000;
+    let rate_limit_layer =
+        RateLimitLayer::new(RateLimiter::new(rate_limits), max_request_body_size);
 
     let server = Server::builder()
         .max_request_body_size(max_request_body_size as u32)
+        .set_logger(PeerAddressLogger)
+        .set_middleware(tower::ServiceBuilder::new().layer(rate_limit_layer))
         .build(address)
         .await?;
 
-    let handle = server.start(
-        TransactionsController::new(
+    let mut rpc_module = TransactionsController::new(
+        txs_storage.clone(),
+        full_event_bus.clone(),
+        txs_states_storage,
+        frozen_storage.clone(),
+        bitcoin_client.clone(),
+        max_items_per_request,
+        max_request_body_size,
+    )
+    .with_partial_proofs(partial_proofs)
+    .with_tx_size_limits(max_inputs, max_outputs)
+    .into_rpc();
+
+    rpc_module.merge(FreezesController::new(txs_storage.clone(), freeze_sinks).into_rpc())?;
+
+    rpc_module.merge(
+        HealthController::new(
             txs_storage,
-            full_event_bus,
-            txs_states_storage,
             frozen_storage,
             bitcoin_client,
-            max_items_per_request,
+            p2p_handle,
+            checkers,
+            confirmations_number.unwrap_or(DEFAULT_CONFIRMATIONS_NUMBER),
+            full_event_bus.clone(),
+            dead_letters,
         )
         .into_rpc(),
-    );
+    )?;
+
+    if let Some(graph_snapshot) = graph_snapshot {
+        rpc_module.merge(DebugController::new(graph_snapshot, &full_event_bus).into_rpc())?;
+    }
+
+    let handle = server.start(rpc_module);
 
     // Await until stop message received
     cancellation.cancelled().await;
@@ -0,0 +1,793 @@
+//! This module provides rate limiting of the RPC requests per method and per client.
+//!
+//! Limits are enforced by [`RateLimitLayer`], an HTTP middleware that inspects the JSON-RPC
+//! calls of the request before they reach the controllers, together with [`PeerAddressLogger`]
+//! that provides it the address of the client.
+
+use std::borrow::Cow;
+use std::collections::{HashMap, HashSet};
+use std::error::Error;
+use std::future::Future;
+use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+use std::pin::Pin;
+use std::sync::{Arc, Mutex, OnceLock};
+use std::task::{Context, Poll};
+use std::time::{Duration, Instant};
+
+use hyper::body::HttpBody;
+use hyper::header::{HeaderMap, CONTENT_TYPE, RETRY_AFTER, UPGRADE};
+use hyper::{Body, Request, Response};
+use jsonrpsee::server::logger::{
+    HttpRequest, Logger, MethodKind, Params, SuccessOrError, TransportProtocol,
+};
+use jsonrpsee::types::{
+    error::{OVERSIZED_REQUEST_CODE, OVERSIZED_REQUEST_MSG, SERVER_IS_BUSY_CODE},
+    ErrorObject, ErrorObjectOwned, Id, ResponsePayload,
+};
+use serde::{Deserialize, Serialize};
+use tower::{Layer, Service};
+
+/// Header with the addresses of the client and the proxies the request came through.
+const X_FORWARDED_FOR: &str = "x-forwarded-for";
+/// Header with the address of the client set by the proxy.
+const X_REAL_IP: &str = "x-real-ip";
+
+/// Client of the requests without the address of the connection, e.g. when the server doesn't
+/// use [`PeerAddressLogger`]. They share the bucket, so they're still limited.
+const UNKNOWN_CLIENT: IpAddr = IpAddr::V4(Ipv4Addr::UNSPECIFIED);
+
+/// Max number of buckets to keep before the least recently used ones are removed.
+const MAX_BUCKETS: usize = 10_000;
+/// Number of the least recently used buckets to remove at once when [`MAX_BUCKETS`] is reached.
+const EVICTED_BUCKETS: usize = MAX_BUCKETS / 10;
+
+/// Limit of the rate of requests.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub struct RateLimit {
+    /// Number of requests per second allowed on average.
+    pub requests_per_second: u32,
+    /// Max number of requests allowed at once.
+    pub burst: u32,
+}
+
+impl RateLimit {
+    pub const fn new(requests_per_second: u32, burst: u32) -> Self {
+        Self {
+            requests_per_second,
+            burst,
+        }
+    }
+}
+
+/// Configuration of the rate limits of the RPC methods.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct RateLimitConfig {
+    /// Limit shared by the methods that have no limit of their own.
+    #[serde(default = "default_limit")]
+    pub default: RateLimit,
+    /// Limits of the specific methods. Override the default limits of the expensive methods,
+    /// see [`default_method_limits`].
+    #[serde(default)]
+    pub methods: HashMap<String, RateLimit>,
+    /// Addresses of the reverse proxies whose `X-Forwarded-For` and `X-Real-IP` headers are
+    /// trusted to identify the client. The headers of other peers are ignored.
+    #[serde(default)]
+    pub trusted_proxies: Vec<IpAddr>,
+}
+
+fn default_limit() -> RateLimit {
+    RateLimit::new(50, 100)
+}
+
+/// Default limits of the methods that are expensive for the node to process.
+pub fn default_method_limits() -> HashMap<String, RateLimit> {
+    HashMap::from([
+        ("emulateyuvtransaction".to_string(), RateLimit::new(5, 10)),
+        (
+            "emulatelistyuvtransactions".to_string(),
+            RateLimit::new(2, 5),
+        ),
+        ("providelistyuvproofs".to_string(), RateLimit::new(2, 5)),
+        ("pruneyuvtransactions".to_string(), RateLimit::new(1, 1)),
+    ])
+}
+
+impl Default for RateLimitConfig {
+    fn default() -> Self {
+        Self {
+            default: default_limit(),
+            methods: HashMap::new(),
+            trusted_proxies: Vec::new(),
+        }
+    }
+}
+
+/// Error of the rate limiter, with the method whose limit is exceeded.
+#[derive(Debug, Clone, PartialEq)]
+pub enum RateLimitError {
+    /// Limit is exceeded, the calls will be allowed after the time.
+    Exceeded {
+        method: String,
+        retry_after: Duration,
+    },
+    /// Request has more calls of the method than its burst, so it will never be allowed.
+    TooManyCalls { method: String, burst: u32 },
+}
+
+impl RateLimitError {
+    fn method(&self) -> &str {
+        match self {
+            Self::Exceeded { method, .. } | Self::TooManyCalls { method, .. } => method,
+        }
+    }
+}
+
+/// Bucket of tokens which are refilled with the constant rate, one token per request.
+#[derive(Debug)]
+struct TokenBucket {
+    tokens: f64,
+    /// Time of the last refill, which is done on every use of the bucket.
+    refilled_at: Instant,
+}
+
+impl TokenBucket {
+    fn full(limit: &RateLimit, now: Instant) -> Self {
+        Self {
+            tokens: limit.burst as f64,
+            refilled_at: now,
+        }
+    }
+
+    fn refill(&mut self, limit: &RateLimit, now: Instant) {
+        let elapsed = now
+            .saturating_duration_since(self.refilled_at)
+            .as_secs_f64();
+
+        self.tokens =
+            (self.tokens + elapsed * limit.requests_per_second as f64).min(limit.burst as f64);
+        self.refilled_at = now;
+    }
+
+    /// Return the time after which the number of tokens will be available, if they are not yet.
+    fn wait_for(&self, limit: &RateLimit, tokens: u32) -> Option<Duration> {
+        let missing = tokens as f64 - self.tokens;
+        if missing <= 0.0 {
+            return None;
+        }
+
+        if limit.requests_per_second == 0 {
+            return Some(Duration::MAX);
+        }
+
+        Some(Duration::from_secs_f64(
+            missing / limit.requests_per_second as f64,
+        ))
+    }
+}
+
+/// Key of the bucket: the client and the method with its own limit, or `None` for the
+/// methods that share the default one.
+type BucketKey = (IpAddr, Option<String>);
+
+/// Rate limiter of the RPC calls, which keeps a bucket of tokens per client and method.
+#[derive(Clone, Debug)]
+pub struct RateLimiter {
+    default: RateLimit,
+    methods: Arc<HashMap<String, RateLimit>>,
+    trusted_proxies: Arc<HashSet<IpAddr>>,
+    buckets: Arc<Mutex<HashMap<BucketKey, TokenBucket>>>,
+}
+
+impl RateLimiter {
+    pub fn new(config: RateLimitConfig) -> Self {
+        let mut methods = default_method_limits();
+        methods.extend(config.methods);
+
+        Self {
+            default: config.default,
+            methods: Arc::new(methods),
+            trusted_proxies: Arc::new(config.trusted_proxies.into_iter().collect()),
+            buckets: Default::default(),
+        }
+    }
+
+    /// Take a token for every call of the methods by the client. If any of the limits is
+    /// exceeded, no tokens are taken and all the calls are rejected.
+    pub fn check<'a>(
+        &self,
+        client: IpAddr,
+        methods: impl IntoIterator<Item = &'a str>,
+    ) -> Result<(), RateLimitError> {
+        self.check_at(client, methods, Instant::now())
+    }
+
+    fn check_at<'a>(
+        &self,
+        client: IpAddr,
+        methods: impl IntoIterator<Item = &'a str>,
+        now: Instant,
+    ) -> Result<(), RateLimitError> {
+        let mut calls: HashMap<BucketKey, (&str, RateLimit, u32)> = HashMap::new();
+        for method in methods {
+            let (key, limit) = match self.methods.get(method) {
+                Some(limit) => ((client, Some(method.to_string())), *limit),
+                None => ((client, None), self.default),
+            };
+
+            calls.entry(key).or_insert((method, limit, 0)).2 += 1;
+        }
+
+        for (method, limit, number) in calls.values() {
+            if *number > limit.burst {
+                return Err(RateLimitError::TooManyCalls {
+                    method: method.to_string(),
+                    burst: limit.burst,
+                });
+            }
+        }
+
+        let mut buckets = self.buckets.lock().expect("lock should not be poisoned");
+
+        let new_buckets = calls
+            .keys()
+            .filter(|key| !buckets.contains_key(*key))
+            .count();
+        if buckets.len() + new_buckets > MAX_BUCKETS {
+            remove_least_recently_used(&mut buckets);
+        }
+
+        for (key, (method, limit, number)) in &calls {
+            let bucket = buckets
+                .entry(key.clone())
+                .or_insert_with(|| TokenBucket::full(limit, now));
+
+            bucket.refill(limit, now);
+            if let Some(retry_after) = bucket.wait_for(limit, *number) {
+                return Err(RateLimitError::Exceeded {
+                    method: method.to_string(),
+                    retry_after,
+                });
+            }
+        }
+
+        for (key, (_, _, number)) in calls {
+            if let Some(bucket) = buckets.get_mut(&key) {
+                bucket.tokens -= number as f64;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Get the address of the client connected from the peer. The headers set by the reverse
+    /// proxy are used only if the peer is a trusted proxy.
+    pub fn client_address(&self, peer: IpAddr, headers: &HeaderMap) -> IpAddr {
+        if !self.trusted_proxies.contains(&peer) {
+            return peer;
+        }
+
+        // The last address not added by one of the trusted proxies is the client's, as the
+        // preceding ones are set by the client itself.
+        let forwarded_for = headers
+            .get(X_FORWARDED_FOR)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| {
+                value
+                    .rsplit(',')
+                    .filter_map(|address| address.trim().parse::<IpAddr>().ok())
+                    .find(|address| !self.trusted_proxies.contains(address))
+            });
+
+        let real_ip = || {
+            headers
+                .get(X_REAL_IP)
+                .and_then(|value| value.to_str().ok())
+                .and_then(|address| address.trim().parse().ok())
+        };
+
+        forwarded_for.or_else(real_ip).unwrap_or(peer)
+    }
+}
+
+/// Remove the least recently used buckets to keep the number of them bounded.
+fn remove_least_recently_used(buckets: &mut HashMap<BucketKey, TokenBucket>) {
+    let mut used_at = buckets
+        .values()
+        .map(|bucket| bucket.refilled_at)
+        .collect::<Vec<_>>();
+
+    let evicted = EVICTED_BUCKETS.min(used_at.len());
+    if evicted == 0 {
+        return;
+    }
+
+    let (_, &mut threshold, _) = used_at.select_nth_unstable(evicted - 1);
+    buckets.retain(|_, bucket| bucket.refilled_at > threshold);
+}
+
+/// Slot for the address of the connection the request came from.
+#[derive(Clone, Debug, Default)]
+struct PeerAddress(Arc<OnceLock<SocketAddr>>);
+
+/// Logger of the server that provides the address of the connection to [`RateLimitService`],
+/// as the server doesn't pass it to the middleware.
+///
+/// The server calls [`Logger::on_connect`] for every HTTP request before it's processed.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct PeerAddressLogger;
+
+impl Logger for PeerAddressLogger {
+    type Instant = ();
+
+    fn on_connect(&self, remote_addr: SocketAddr, request: &HttpRequest, _: TransportProtocol) {
+        if let Some(PeerAddress(address)) = request.extensions().get() {
+            let _ = address.set(remote_addr);
+        }
+    }
+
+    fn on_request(&self, _: TransportProtocol) {}
+
+    fn on_call(&self, _: &str, _: Params, _: MethodKind, _: TransportProtocol) {}
+
+    fn on_result(&self, _: &str, _: SuccessOrError, _: Self::Instant, _: TransportProtocol) {}
+
+    fn on_response(&self, _: &str, _: Self::Instant, _: TransportProtocol) {}
+
+    fn on_disconnect(&self, _: SocketAddr, _: TransportProtocol) {}
+}
+
+/// Layer that applies [`RateLimitService`] to the server.
+#[derive(Clone, Debug)]
+pub struct RateLimitLayer {
+    limiter: RateLimiter,
+    max_request_body_size: usize,
+}
+
+impl RateLimitLayer {
+    pub fn new(limiter: RateLimiter, max_request_body_size: usize) -> Self {
+        Self {
+            limiter,
+            max_request_body_size,
+        }
+    }
+}
+
+impl<S> Layer<S> for RateLimitLayer {
+    type Service = RateLimitService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        RateLimitService {
+            inner,
+            limiter: self.limiter.clone(),
+            max_request_body_size: self.max_request_body_size,
+        }
+    }
+}
+
+/// HTTP middleware that rejects the JSON-RPC requests exceeding the rate limits with an error
+/// that has the time to retry after.
+///
+/// The client is identified by the address of the connection, which requires the server to use
+/// [`PeerAddressLogger`]. The `X-Forwarded-For` or `X-Real-IP` headers are used instead only
+/// for the connections of the trusted proxies. The requests without the address share a single
+/// bucket.
+///
+/// Calls made over WebSocket connections are not limited.
+#[derive(Clone, Debug)]
+pub struct RateLimitService<S> {
+    inner: S,
+    limiter: RateLimiter,
+    max_request_body_size: usize,
+}
+
+impl<S> Service<Request<Body>> for RateLimitService<S>
+where
+    S: Service<Request<Body>, Response = Response<Body>> + Clone + Send + 'static,
+    S::Error: Into<Box<dyn Error + Send + Sync>> + 'static,
+    S::Future: Send + 'static,
+{
+    type Response = S::Response;
+    type Error = Box<dyn Error + Send + Sync + 'static>;
+    type Future =
+        Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>> + Send + 'static>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx).map_err(Into::into)
+    }
+
+    fn call(&mut self, request: Request<Body>) -> Self::Future {
+        if request.headers().contains_key(UPGRADE) {
+            let fut = self.inner.call(request);
+            return Box::pin(async move { fut.await.map_err(Into::into) });
+        }
+
+        // The ready service is taken, as the one it was cloned from may be not.
+        let clone = self.inner.clone();
+        let mut inner = std::mem::replace(&mut self.inner, clone);
+        let limiter = self.limiter.clone();
+        let max_request_body_size = self.max_request_body_size;
+
+        Box::pin(async move {
+            let (parts, mut body) = request.into_parts();
+
+            let mut bytes = Vec::new();
+            while let Some(chunk) = body.data().await {
+                bytes.extend_from_slice(&chunk?);
+
+                if bytes.len() > max_request_body_size {
+                    return Ok(oversized_response());
+                }
+            }
+
+            let headers = parts.headers.clone();
+            let peer = PeerAddress::default();
+
+            let mut request = Request::from_parts(parts, Body::from(bytes.clone()));
+            request.extensions_mut().insert(peer.clone());
+
+            // The server sets the address of the connection on the call, while the request is
+            // processed only when the returned future is polled.
+            let fut = inner.call(request);
+
+            let client = match peer.0.get() {
+                Some(peer) => limiter.client_address(peer.ip(), &headers),
+                None => UNKNOWN_CLIENT,
+            };
+            if let Some(response) = check_calls(&limiter, client, &bytes) {
+                return Ok(response);
+            }
+
+            fut.await.map_err(Into::into)
+        })
+    }
+}
+
+/// Call of the JSON-RPC request with only the fields required for the rate limiting.
+#[derive(Deserialize)]
+struct Call<'a> {
+    #[serde(borrow, default)]
+    id: Option<Id<'a>>,
+    #[serde(borrow)]
+    method: Cow<'a, str>,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum Calls<'a> {
+    #[serde(borrow)]
+    Single(Call<'a>),
+    #[serde(borrow)]
+    Batch(Vec<Call<'a>>),
+}
+
+/// Check the limits of all the calls of the request, returning the response to reject it with
+/// if any of them is exceeded. Invalid requests are left to the server to reject.
+fn check_calls(limiter: &RateLimiter, client: IpAddr, body: &[u8]) -> Option<Response<Body>> {
+    let calls = match serde_json::from_slice::<Calls>(body) {
+        Ok(Calls::Single(call)) => vec![call],
+        Ok(Calls::Batch(calls)) => calls,
+        Err(_) => return None,
+    };
+
+    let error = limiter
+        .check(client, calls.iter().map(|call| call.method.as_ref()))
+        .err()?;
+
+    tracing::debug!("Rate limit is exceeded by {}: {:?}", client, error);
+
+    // The whole batch is rejected, so the error is not bound to any of its calls.
+    let id = match &calls[..] {
+        [Call { id: Some(id), .. }] => id.clone(),
+        _ => Id::Null,
+    };
+
+    Some(rate_limited_response(error, id))
+}
+
+fn rate_limited_response(error: RateLimitError, id: Id) -> Response<Body> {
+    let method = error.method();
+
+    let (error, retry_after_ms) = match &error {
+        RateLimitError::Exceeded { retry_after, .. } => {
+            let retry_after_ms = u64::try_from(retry_after.as_millis()).unwrap_or(u64::MAX);
+
+            let error = ErrorObject::owned(
+                SERVER_IS_BUSY_CODE,
+                format!("Rate limit of {method} is exceeded, retry after {retry_after_ms} ms"),
+                Some(serde_json::json!({ "retry_after_ms": retry_after_ms })),
+            );
+
+            (error, Some(retry_after_ms))
+        }
+        RateLimitError::TooManyCalls { burst, .. } => {
+            let error = ErrorObject::owned(
+                SERVER_IS_BUSY_CODE,
+                format!("Request has more calls of {method} than the limit of {burst}"),
+                Option::<()>::None,
+            );
+
+            (error, None)
+        }
+    };
+
+    let mut response = Response::builder().header(CONTENT_TYPE, "application/json");
+    if let Some(retry_after_ms) = retry_after_ms {
+        response = response.header(RETRY_AFTER, retry_after_ms.div_ceil(1000));
+    }
+
+    response
+        .body(json_rpc_error(error, id))
+        .expect("response should be valid")
+}
+
+fn oversized_response() -> Response<Body> {
+    let error = ErrorObject::owned(
+        OVERSIZED_REQUEST_CODE,
+        OVERSIZED_REQUEST_MSG,
+        Option::<()>::None,
+    );
+
+    Response::builder()
+        .header(CONTENT_TYPE, "application/json")
+        .body(json_rpc_error(error, Id::Null))
+        .expect("response should be valid")
+}
+
+fn json_rpc_error(error: ErrorObjectOwned, id: Id) -> Body {
+    let response = jsonrpsee::types::Response::new(ResponsePayload::<()>::error(error), id);
+
+    Body::from(serde_json::to_string(&response).expect("response should be serializable"))
+}
+
+#[cfg(test)]
+mod tests {
+    use jsonrpsee::server::Server;
+    use jsonrpsee::{server::ServerHandle, RpcModule};
+
+    use super::*;
+
+    const CLIENT: IpAddr = IpAddr::V4(Ipv4Addr::LOCALHOST);
+    const OTHER_CLIENT: IpAddr = IpAddr::V4(Ipv4Addr::new(10, 0, 0, 1));
+    const PROXY: IpAddr = IpAddr::V4(Ipv4Addr::new(10, 0, 0, 100));
+
+    fn limiter() -> RateLimiter {
+        RateLimiter::new(RateLimitConfig {
+            default: RateLimit::new(10, 2),
+            methods: HashMap::from([("getrawyuvtransaction".to_string(), RateLimit::new(1, 1))]),
+            trusted_proxies: vec![PROXY],
+        })
+    }
+
+    async fn start_server(limiter: RateLimiter) -> (ServerHandle, SocketAddr) {
+        let server = Server::builder()
+            .set_logger(PeerAddressLogger)
+            .set_middleware(tower::ServiceBuilder::new().layer(RateLimitLayer::new(limiter, 1024)))
+            .build((Ipv4Addr::LOCALHOST, 0))
+            .await
+            .unwrap();
+        let address = server.local_addr().unwrap();
+
+        let mut module = RpcModule::new(());
+        module.register_method("health", |_, _| "ok").unwrap();
+
+        (server.start(module), address)
+    }
+
+    async fn call(address: SocketAddr, forwarded_for: &str) -> serde_json::Value {
+        let request = Request::post(format!("http://{address}"))
+            .header(CONTENT_TYPE, "application/json")
+            .header(X_FORWARDED_FOR, forwarded_for)
+            .body(Body::from(
+                r#"{"jsonrpc": "2.0", "id": 1, "method": "health"}"#,
+            ))
+            .unwrap();
+
+        let response = hyper::Client::new().request(request).await.unwrap();
+        let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+
+        serde_json::from_slice(&body).unwrap()
+    }
+
+    fn exceeded(method: &str, retry_after: Duration) -> Result<(), RateLimitError> {
+        Err(RateLimitError::Exceeded {
+            method: method.to_string(),
+            retry_after,
+        })
+    }
+
+    #[test]
+    fn test_limits_are_per_method_and_client() {
+        let limiter = limiter();
+        let now = Instant::now();
+
+        assert!(limiter
+            .check_at(CLIENT, ["getrawyuvtransaction"], now)
+            .is_ok());
+        assert_eq!(
+            limiter.check_at(CLIENT, ["getrawyuvtransaction"], now),
+            exceeded("getrawyuvtransaction", Duration::from_secs(1))
+        );
+
+        // Other methods and clients have their own buckets.
+        assert!(limiter.check_at(CLIENT, ["health"], now).is_ok());
+        assert!(limiter
+            .check_at(OTHER_CLIENT, ["getrawyuvtransaction"], now)
+            .is_ok());
+
+        assert!(limiter
+            .check_at(
+                CLIENT,
+                ["getrawyuvtransaction"],
+                now + Duration::from_secs(1)
+            )
+            .is_ok());
+    }
+
+    #[test]
+    fn test_default_limit_is_shared_and_refilled() {
+        let limiter = limiter();
+        let now = Instant::now();
+
+        assert!(limiter.check_at(CLIENT, ["health"], now).is_ok());
+        assert!(limiter.check_at(CLIENT, ["getchromainfo"], now).is_ok());
+        assert_eq!(
+            limiter.check_at(CLIENT, ["getqueuestats"], now),
+            exceeded("getqueuestats", Duration::from_millis(100))
+        );
+
+        assert!(limiter
+            .check_at(CLIENT, ["getqueuestats"], now + Duration::from_millis(100))
+            .is_ok());
+    }
+
+    #[test]
+    fn test_expensive_methods_have_default_limits() {
+        let limiter = limiter();
+        let now = Instant::now();
+
+        let emulate_limit = default_method_limits()["emulateyuvtransaction"];
+        for _ in 0..emulate_limit.burst {
+            assert!(limiter
+                .check_at(CLIENT, ["emulateyuvtransaction"], now)
+                .is_ok());
+        }
+        assert!(limiter
+            .check_at(CLIENT, ["emulateyuvtransaction"], now)
+            .is_err());
+    }
+
+    #[test]
+    fn test_rejected_batch_takes_no_tokens() {
+        let limiter = limiter();
+        let now = Instant::now();
+
+        assert!(limiter.check_at(CLIENT, ["health"], now).is_ok());
+        assert_eq!(
+            limiter.check_at(CLIENT, ["health", "getrawyuvtransaction", "health"], now),
+            exceeded("health", Duration::from_millis(100))
+        );
+
+        // Tokens of the rejected batch are left to the following calls.
+        assert!(limiter
+            .check_at(CLIENT, ["health", "getrawyuvtransaction"], now)
+            .is_ok());
+    }
+
+    #[test]
+    fn test_batch_over_burst_is_rejected() {
+        let limiter = limiter();
+
+        let request = br#"[
+            {"jsonrpc": "2.0", "id": 1, "method": "getrawyuvtransaction", "params": []},
+            {"jsonrpc": "2.0", "id": 2, "method": "getrawyuvtransaction", "params": []}
+        ]"#;
+
+        let response = check_calls(&limiter, CLIENT, request).unwrap();
+        assert!(!response.headers().contains_key(RETRY_AFTER));
+
+        // The bucket is left untouched.
+        assert!(limiter.check(CLIENT, ["getrawyuvtransaction"]).is_ok());
+
+        let request = br#"{"jsonrpc": "2.0", "id": 1, "method": "getrawyuvtransaction"}"#;
+        let response = check_calls(&limiter, CLIENT, request).unwrap();
+        assert_eq!(response.headers()[RETRY_AFTER], "1");
+
+        // Not JSON-RPC requests are left to the server.
+        assert!(check_calls(&limiter, CLIENT, b"not a request").is_none());
+    }
+
+    #[test]
+    fn test_least_recently_used_buckets_are_removed() {
+        let limiter = limiter();
+        let now = Instant::now();
+
+        for i in 0..MAX_BUCKETS as u32 {
+            let client = IpAddr::V4(Ipv4Addr::from(i));
+            let used_at = now + Duration::from_millis(i as u64);
+
+            assert!(limiter.check_at(client, ["health"], used_at).is_ok());
+        }
+
+        // The first client is exhausted, while it's still among the oldest ones.
+        let first = IpAddr::V4(Ipv4Addr::from(0));
+        let later = now + Duration::from_secs(100);
+        assert!(limiter.check_at(first, ["health"], now).is_ok());
+        assert!(limiter.check_at(CLIENT, ["health"], later).is_ok());
+
+        let buckets = limiter.buckets.lock().unwrap();
+        assert_eq!(buckets.len(), MAX_BUCKETS - EVICTED_BUCKETS + 1);
+        assert!(!buckets.contains_key(&(first, None)));
+        assert!(buckets.contains_key(&(CLIENT, None)));
+    }
+
+    #[test]
+    fn test_client_address_from_trusted_proxy_headers() {
+        let limiter = limiter();
+
+        let mut headers = HeaderMap::new();
+        assert_eq!(limiter.client_address(PROXY, &headers), PROXY);
+
+        headers.insert(X_REAL_IP, "10.0.0.2".parse().unwrap());
+        assert_eq!(
+            limiter.client_address(PROXY, &headers),
+            "10.0.0.2".parse::<IpAddr>().unwrap()
+        );
+
+        // The addresses before the one added by the proxy are set by the client.
+        headers.insert(
+            X_FORWARDED_FOR,
+            "10.0.0.1, 10.0.0.3, 10.0.0.100".parse().unwrap(),
+        );
+        assert_eq!(
+            limiter.client_address(PROXY, &headers),
+            "10.0.0.3".parse::<IpAddr>().unwrap()
+        );
+
+        // Headers of other peers are ignored.
+        assert_eq!(limiter.client_address(CLIENT, &headers), CLIENT);
+    }
+
+    #[tokio::test]
+    async fn test_server_limits_clients_by_connection_address() {
+        let limiter = RateLimiter::new(RateLimitConfig {
+            default: RateLimit::new(0, 1),
+            ..Default::default()
+        });
+        let (handle, address) = start_server(limiter).await;
+
+        assert_eq!(call(address, "10.0.0.1").await["result"], "ok");
+
+        // Headers set by the client are not trusted, so it can't pretend to be another one.
+        let response = call(address, "10.0.0.2").await;
+        assert_eq!(response["error"]["code"], SERVER_IS_BUSY_CODE);
+
+        handle.stop().unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_requests_without_peer_address_share_bucket() {
+        let limiter = RateLimiter::new(RateLimitConfig {
+            default: RateLimit::new(0, 1),
+            ..Default::default()
+        });
+
+        // Without the logger the address of the connection is unknown.
+        let server = Server::builder()
+            .set_middleware(tower::ServiceBuilder::new().layer(RateLimitLayer::new(limiter, 1024)))
+            .build((Ipv4Addr::LOCALHOST, 0))
+            .await
+            .unwrap();
+        let address = server.local_addr().unwrap();
+
+        let mut module = RpcModule::new(());
+        module.register_method("health", |_, _| "ok").unwrap();
+        let handle = server.start(module);
+
+        assert_eq!(call(address, "10.0.0.1").await["result"], "ok");
+
+        let response = call(address, "10.0.0.2").await;
+        assert_eq!(response["error"]["code"], SERVER_IS_BUSY_CODE);
+
+        handle.stop().unwrap();
+    }
+}
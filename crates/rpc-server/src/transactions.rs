@@ -1,5 +1,5 @@
 use async_trait::async_trait;
-use bitcoin::{Amount, OutPoint, Txid};
+use bitcoin::{Amount, OutPoint, Transaction, Txid};
 use bitcoin_client::BitcoinRpcApi;
 use event_bus::{typeid, EventBus};
 use jsonrpsee::{
@@ -9,18 +9,25 @@ use jsonrpsee::{
         ErrorObject, ErrorObjectOwned,
     },
 };
+use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
+use yuv_controller::{unproven_outputs, PartialProofs};
 use yuv_pixels::Chroma;
 use yuv_rpc_api::transactions::{
-    EmulateYuvTransactionResponse, GetRawYuvTransactionResponse, YuvTransactionsRpcServer,
+    EmulateYuvTransactionResponse, EmulateYuvTransactionsResponse, GetChromaAnnouncementTxResponse,
+    GetInvalidTxResponse, GetOutputProofResponse, GetRawYuvTransactionResponse,
+    GetRpcLimitsResponse, ProvidePartialYuvProofResponse, YuvTransactionsRpcServer,
 };
 use yuv_storage::{
-    ChromaInfoStorage, FrozenTxsStorage, KeyValueError, PagesStorage, TransactionsStorage, TxState,
-    TxStatesStorage,
+    ChromaInfoStorage, FrozenTxsStorage, InvalidTxsStorage, KeyValueError, PagesStorage,
+    TransactionsStorage, TxState, TxStatesStorage,
+};
+use yuv_tx_check::{
+    check_coinbase_maturity, check_transaction, check_tx_size, CheckError, TxCheckerError,
 };
-use yuv_tx_check::{check_transaction, CheckError};
 use yuv_types::{
-    announcements::ChromaInfo, ControllerMessage, ProofMap, YuvTransaction, YuvTxType,
+    announcements::ChromaInfo, ControllerMessage, ProofMap, YuvProofBundle, YuvTransaction,
+    YuvTxType,
 };
 
 // TODO: Rename to "RpcController"
@@ -28,6 +35,8 @@ use yuv_types::{
 pub struct TransactionsController<TransactionsStorage, AnnouncementStorage, BitcoinClient> {
     /// Max items per request
     max_items_per_request: usize,
+    /// Max size of the request body in bytes
+    max_request_body_size: usize,
     /// Internal storage of transactions.
     txs_storage: TransactionsStorage,
     /// Internal storage for announcements.
@@ -36,14 +45,20 @@ pub struct TransactionsController<TransactionsStorage, AnnouncementStorage, Bitc
     event_bus: EventBus,
     /// Internal storage of transactions' states.
     txs_states_storage: TxStatesStorage,
+    /// Transactions which proofs are provided partially.
+    partial_proofs: PartialProofs,
     /// Bitcoin RPC Client.
     bitcoin_client: Arc<BitcoinClient>,
+    /// Max number of the inputs of the transaction the checkers accept. Unlimited if `None`.
+    max_inputs: Option<usize>,
+    /// Max number of the outputs of the transaction the checkers accept. Unlimited if `None`.
+    max_outputs: Option<usize>,
 }
 
 impl<TXS, AS, BC> TransactionsController<TXS, AS, BC>
 where
     TXS: TransactionsStorage + PagesStorage + Send + Sync + 'static,
-    AS: FrozenTxsStorage + ChromaInfoStorage + Send + Sync + 'static,
+    AS: FrozenTxsStorage + ChromaInfoStorage + InvalidTxsStorage + Send + Sync + 'static,
     BC: BitcoinRpcApi + Send + Sync + 'static,
 {
     pub fn new(
@@ -53,6 +68,7 @@ where
         frozen_txs_storage: AS,
         bitcoin_client: Arc<BC>,
         max_items_per_request: usize,
+        max_request_body_size: usize,
     ) -> Self {
         let event_bus = full_event_bus
             .extract(&typeid![ControllerMessage], &typeid![])
@@ -60,13 +76,38 @@ where
 
         Self {
             max_items_per_request,
+            max_request_body_size,
             txs_storage: storage,
             event_bus,
             txs_states_storage,
+            partial_proofs: PartialProofs::default(),
             announcement_storage: frozen_txs_storage,
             bitcoin_client,
+            max_inputs: None,
+            max_outputs: None,
         }
     }
+
+    /// Set the buffer of the partially proven transactions, which is shared with the
+    /// [`Controller`](yuv_controller::Controller) expiring them.
+    pub fn with_partial_proofs(mut self, partial_proofs: PartialProofs) -> Self {
+        self.partial_proofs = partial_proofs;
+
+        self
+    }
+
+    /// Set the limits of the number of the inputs and outputs of the transaction the checkers
+    /// accept, so the partially proven and the emulated transactions are rejected the same way.
+    pub fn with_tx_size_limits(
+        mut self,
+        max_inputs: Option<usize>,
+        max_outputs: Option<usize>,
+    ) -> Self {
+        self.max_inputs = max_inputs;
+        self.max_outputs = max_outputs;
+
+        self
+    }
 }
 
 impl<TXS, FZS, BC> TransactionsController<TXS, FZS, BC>
@@ -75,10 +116,31 @@ where
     FZS: FrozenTxsStorage + ChromaInfoStorage + Send + Sync + 'static,
     BC: BitcoinRpcApi + Send + Sync + 'static,
 {
+    /// Send the transactions to the message handler to be checked, skipping the ones that are
+    /// already handled with the same proofs, so the retries of the requests don't reach the
+    /// checkers.
     async fn send_txs_to_confirm(&self, yuv_txs: Vec<YuvTransaction>) -> RpcResult<()> {
+        let mut new_txs = Vec::with_capacity(yuv_txs.len());
+
+        for yuv_tx in yuv_txs {
+            if self.is_already_handled(&yuv_tx).await? {
+                tracing::debug!(
+                    "Tx {} is already handled with the same proofs",
+                    yuv_tx.bitcoin_tx.txid()
+                );
+                continue;
+            }
+
+            new_txs.push(yuv_tx);
+        }
+
+        if new_txs.is_empty() {
+            return Ok(());
+        }
+
         // Send message to message handler about new tx with proof.
         self.event_bus
-            .try_send(ControllerMessage::ConfirmBatchTx(yuv_txs))
+            .try_send(ControllerMessage::ConfirmBatchTx(new_txs))
             .await
             // If we failed to send message to message handler, then it's dead.
             .map_err(|_| {
@@ -92,13 +154,59 @@ where
 
         Ok(())
     }
+
+    /// Check if the transaction is being checked right now, or is already attached with the same
+    /// proofs.
+    ///
+    /// The transaction with the different proofs is not considered handled, so a resubmission
+    /// after the rejection is still checked.
+    async fn is_already_handled(&self, yuv_tx: &YuvTransaction) -> RpcResult<bool> {
+        let txid = yuv_tx.bitcoin_tx.txid();
+
+        if self.txs_states_storage.get(&txid).await.is_some() {
+            return Ok(true);
+        }
+
+        let attached_tx = self.txs_storage.get_yuv_tx(&txid).await.map_err(|err| {
+            tracing::error!("Failed to get transaction: {err}");
+            ErrorObjectOwned::owned(
+                INTERNAL_ERROR_CODE,
+                "Storage is not available",
+                Option::<Vec<u8>>::None,
+            )
+        })?;
+
+        Ok(attached_tx.is_some_and(|attached_tx| attached_tx.tx_type == yuv_tx.tx_type))
+    }
+
+    /// Find the Bitcoin transaction in the storage, or request it from the Bitcoin node.
+    async fn resolve_bitcoin_tx(&self, txid: Txid) -> RpcResult<Option<Transaction>> {
+        if let Some(yuv_tx) = self.txs_storage.get_yuv_tx(&txid).await.map_err(|err| {
+            tracing::error!("Failed to get transaction: {err}");
+            ErrorObjectOwned::owned(
+                INTERNAL_ERROR_CODE,
+                "Storage is not available",
+                Option::<Vec<u8>>::None,
+            )
+        })? {
+            return Ok(Some(yuv_tx.bitcoin_tx));
+        }
+
+        match self.bitcoin_client.get_raw_transaction(&txid, None).await {
+            Ok(tx) => Ok(Some(tx)),
+            Err(err) => {
+                tracing::debug!("Failed to get transaction {txid} from Bitcoin node: {err}");
+                Ok(None)
+            }
+        }
+    }
 }
 
 #[async_trait]
 impl<TXS, AS, BC> YuvTransactionsRpcServer for TransactionsController<TXS, AS, BC>
 where
     TXS: TransactionsStorage + PagesStorage + Clone + Send + Sync + 'static,
-    AS: FrozenTxsStorage + ChromaInfoStorage + Clone + Send + Sync + 'static,
+    AS: FrozenTxsStorage + ChromaInfoStorage + InvalidTxsStorage + Clone + Send + Sync + 'static,
     BC: BitcoinRpcApi + Send + Sync + 'static,
 {
     /// Handle new YUV transaction with proof to check.
@@ -126,6 +234,84 @@ where
         Ok(true)
     }
 
+    /// Merge the proofs with the ones provided before, and handle the transaction as provided
+    /// proof once all its outputs, except the OP_RETURN ones, are proven. The complete
+    /// transaction which fails the isolated checks is rejected with the check error, but kept, so
+    /// the missing proofs of the inputs could still be provided.
+    async fn provide_partial_yuv_proof(
+        &self,
+        yuv_tx: YuvTransaction,
+    ) -> RpcResult<ProvidePartialYuvProofResponse> {
+        let txid = yuv_tx.bitcoin_tx.txid();
+
+        if let Err(err) = check_tx_size(&yuv_tx.bitcoin_tx, self.max_inputs, self.max_outputs) {
+            return Err(ErrorObjectOwned::owned(
+                INVALID_REQUEST_CODE,
+                format!("Tx {txid} is invalid: {err}"),
+                Option::<Vec<u8>>::None,
+            ));
+        }
+
+        let yuv_tx = self.partial_proofs.merge(yuv_tx).map_err(|err| {
+            ErrorObjectOwned::owned(
+                INVALID_REQUEST_CODE,
+                err.to_string(),
+                Option::<Vec<u8>>::None,
+            )
+        })?;
+
+        let unproven_outputs = unproven_outputs(&yuv_tx);
+        if !unproven_outputs.is_empty() {
+            tracing::debug!("Tx {txid} is not complete yet");
+
+            return Ok(ProvidePartialYuvProofResponse::Pending {
+                reason: format!("outputs {unproven_outputs:?} have no proofs"),
+            });
+        }
+
+        if let Err(err) = check_transaction(&yuv_tx) {
+            return Err(ErrorObjectOwned::owned(
+                INVALID_REQUEST_CODE,
+                format!("Tx {txid} is invalid: {err}"),
+                Option::<Vec<u8>>::None,
+            ));
+        }
+
+        self.partial_proofs.remove(&txid);
+        self.send_txs_to_confirm(vec![yuv_tx]).await?;
+
+        Ok(ProvidePartialYuvProofResponse::Provided)
+    }
+
+    async fn get_rpc_limits(&self) -> RpcResult<GetRpcLimitsResponse> {
+        Ok(GetRpcLimitsResponse {
+            max_items_per_request: self.max_items_per_request,
+            max_request_body_size: self.max_request_body_size,
+        })
+    }
+
+    /// Form YUV transaction from the proofs and the resolved Bitcoin transaction, and handle it
+    /// as provided proof.
+    async fn provide_yuv_proof_bundle(&self, bundle: YuvProofBundle) -> RpcResult<bool> {
+        let txid = bundle.txid;
+
+        let yuv_tx = self
+            .resolve_bitcoin_tx(txid)
+            .await?
+            .and_then(|bitcoin_tx| bundle.into_yuv_tx(bitcoin_tx))
+            .ok_or_else(|| {
+                ErrorObjectOwned::owned(
+                    INVALID_REQUEST_CODE,
+                    format!("Bitcoin transaction {txid} is not found"),
+                    Option::<Vec<u8>>::None,
+                )
+            })?;
+
+        self.send_txs_to_confirm(vec![yuv_tx]).await?;
+
+        Ok(true)
+    }
+
     async fn get_raw_yuv_transaction(&self, txid: Txid) -> RpcResult<GetRawYuvTransactionResponse> {
         if let Some(state) = self.txs_states_storage.get(&txid).await {
             return match state {
@@ -174,23 +360,90 @@ where
         Ok(result)
     }
 
-    async fn list_yuv_transactions(&self, page: u64) -> RpcResult<Vec<YuvTransaction>> {
-        let transactions = match self.txs_storage.get_page_by_num(page).await {
-            Ok(Some(page)) => page,
+    async fn get_output_proof(&self, txid: Txid, vout: u32) -> RpcResult<GetOutputProofResponse> {
+        let tx = self.txs_storage.get_yuv_tx(&txid).await.map_err(|e| {
+            ErrorObject::owned(INTERNAL_ERROR_CODE, e.to_string(), Option::<Vec<u8>>::None)
+        })?;
 
-            // If no transactions for this page, return empty list.
-            Ok(None) => return Ok(Vec::new()),
+        let Some(tx) = tx else {
+            return Ok(GetOutputProofResponse::TxNotFound);
+        };
 
-            // If we failed to get page, then storage is not available.
-            Err(err) => {
-                tracing::error!("Failed to get last page: {err}");
+        let proof = tx
+            .tx_type
+            .output_proofs()
+            .and_then(|proofs| proofs.get(&vout));
+        let txout = tx.bitcoin_tx.output.get(vout as usize);
+
+        match (proof, txout) {
+            (Some(proof), Some(txout)) => Ok(GetOutputProofResponse::Found {
+                proof: proof.clone(),
+                txout: txout.clone(),
+            }),
+            _ => Ok(GetOutputProofResponse::NotYuvOutput),
+        }
+    }
+
+    async fn list_yuv_transactions(
+        &self,
+        page: u64,
+        limit: Option<u64>,
+        offset: Option<u64>,
+    ) -> RpcResult<Vec<YuvTransaction>> {
+        let storage_error = |err: KeyValueError| {
+            tracing::error!("Failed to get page: {err}");
+
+            ErrorObject::owned(
+                INTERNAL_ERROR_CODE,
+                "Storage is not available",
+                Option::<Vec<u8>>::None,
+            )
+        };
+
+        let transactions = if limit.is_none() && offset.is_none() {
+            match self.txs_storage.get_page_by_num(page).await {
+                Ok(Some(page)) => page,
+
+                // If no transactions for this page, return empty list.
+                Ok(None) => return Ok(Vec::new()),
 
+                // If we failed to get page, then storage is not available.
+                Err(err) => return Err(storage_error(err)),
+            }
+        } else {
+            let Some(page_size) = self
+                .txs_storage
+                .get_page_size()
+                .await
+                .map_err(storage_error)?
+            else {
+                // Storage is not paginated yet.
+                return Ok(Vec::new());
+            };
+
+            let max_items = self.max_items_per_request as u64;
+            let limit = limit.unwrap_or(page_size.min(max_items));
+            if limit > max_items {
                 return Err(ErrorObject::owned(
-                    INTERNAL_ERROR_CODE,
-                    "Storage is not available",
+                    INVALID_REQUEST_CODE,
+                    format!(
+                        "Too big limit, max amount is {}",
+                        self.max_items_per_request
+                    ),
                     Option::<Vec<u8>>::None,
                 ));
             }
+
+            let start = page
+                .checked_mul(limit)
+                .and_then(|start| start.checked_add(offset.unwrap_or_default()));
+            let Some(start) = start else {
+                return Ok(Vec::new());
+            };
+
+            get_paginated_txids(&self.txs_storage, start, limit, page_size)
+                .await
+                .map_err(storage_error)?
         };
 
         let mut res = Vec::new();
@@ -199,11 +452,9 @@ where
             match self.txs_storage.get_yuv_tx(&txid).await {
                 // if everything is ok, push transaction to result.
                 Ok(Some(tx)) => res.push(tx),
-                // if transaction not found, then it's not valid.
-                //
-                // TODO: Maybe we should return error here?
+                // if transaction not found, then it's pruned.
                 Ok(None) => {
-                    tracing::error!("Transaction with id {txid} not found in page storage");
+                    tracing::debug!("Transaction with id {txid} from the page is pruned");
                     continue;
                 }
                 // if we failed to get transaction, then storage is not available.
@@ -267,6 +518,20 @@ where
         Ok(frozen_entry.is_frozen())
     }
 
+    async fn get_spending_tx(&self, txid: Txid, vout: u32) -> RpcResult<Option<Txid>> {
+        self.txs_storage
+            .get_spending_tx(&OutPoint::new(txid, vout))
+            .await
+            .map_err(|e| {
+                tracing::error!("Failed to get spending tx: {e}");
+                ErrorObject::owned(
+                    INTERNAL_ERROR_CODE,
+                    "Storage is not available",
+                    Option::<Vec<u8>>::None,
+                )
+            })
+    }
+
     /// Check that transaction could be accpeted by node.
     ///
     /// For that uses [`TransactionEmulator`] to check that transaction is valid
@@ -274,9 +539,15 @@ where
     async fn emulate_yuv_transaction(
         &self,
         yuv_tx: YuvTransaction,
+        at_height: Option<u64>,
     ) -> RpcResult<EmulateYuvTransactionResponse> {
-        let emulator =
-            TransactionEmulator::new(self.txs_storage.clone(), self.announcement_storage.clone());
+        let emulator = TransactionEmulator::new(
+            self.txs_storage.clone(),
+            self.announcement_storage.clone(),
+            self.bitcoin_client.clone(),
+        )
+        .with_height(at_height)
+        .with_tx_size_limits(self.max_inputs, self.max_outputs);
 
         match emulator.emulate_yuv_transaction(&yuv_tx).await {
             // Transaction could be accepted by node.
@@ -291,6 +562,16 @@ where
                     Option::<Vec<u8>>::None,
                 ))
             }
+            // Bitcoin node is dead:
+            Err(EmulateYuvTransactionError::BitcoinNotAvailable(err)) => {
+                tracing::error!("Bitcoin RPC error: {err}");
+
+                Err(ErrorObject::owned(
+                    INTERNAL_ERROR_CODE,
+                    "Bitcoin node is not available",
+                    Option::<Vec<u8>>::None,
+                ))
+            }
             // Error that encountered during emulating:
             Err(err) => Ok(EmulateYuvTransactionResponse::Invalid {
                 reason: err.to_string(),
@@ -298,6 +579,65 @@ where
         }
     }
 
+    /// Check that the transactions could be accepted by node together.
+    ///
+    /// See [`TransactionEmulator::emulate_yuv_transactions`] for more info.
+    async fn emulate_list_yuv_transactions(
+        &self,
+        yuv_txs: Vec<YuvTransaction>,
+    ) -> RpcResult<EmulateYuvTransactionsResponse> {
+        if yuv_txs.len() > self.max_items_per_request {
+            return Err(ErrorObject::owned(
+                INVALID_REQUEST_CODE,
+                format!(
+                    "Too many yuv_txs, max amount is {}",
+                    self.max_items_per_request
+                ),
+                Option::<Vec<u8>>::None,
+            ));
+        }
+
+        let emulator = TransactionEmulator::new(
+            self.txs_storage.clone(),
+            self.announcement_storage.clone(),
+            self.bitcoin_client.clone(),
+        )
+        .with_tx_size_limits(self.max_inputs, self.max_outputs);
+
+        let mut results = Vec::with_capacity(yuv_txs.len());
+        for result in emulator.emulate_yuv_transactions(&yuv_txs).await {
+            let response = match result {
+                Ok(()) => EmulateYuvTransactionResponse::Valid,
+                Err(EmulateYuvTransactionError::StorageNotAvailable(err)) => {
+                    tracing::error!("Storage error: {err}");
+
+                    return Err(ErrorObject::owned(
+                        INTERNAL_ERROR_CODE,
+                        "Storage is not available",
+                        Option::<Vec<u8>>::None,
+                    ));
+                }
+                Err(EmulateYuvTransactionError::BitcoinNotAvailable(err)) => {
+                    tracing::error!("Bitcoin RPC error: {err}");
+
+                    return Err(ErrorObject::owned(
+                        INTERNAL_ERROR_CODE,
+                        "Bitcoin node is not available",
+                        Option::<Vec<u8>>::None,
+                    ));
+                }
+                Err(err) => EmulateYuvTransactionResponse::invalid(err.to_string()),
+            };
+
+            results.push(response);
+        }
+
+        Ok(EmulateYuvTransactionsResponse {
+            valid: results.iter().all(EmulateYuvTransactionResponse::is_valid),
+            results,
+        })
+    }
+
     async fn get_chroma_info(&self, chroma: Chroma) -> RpcResult<Option<ChromaInfo>> {
         self.announcement_storage
             .get_chroma_info(&chroma)
@@ -311,6 +651,107 @@ where
                 )
             })
     }
+
+    async fn get_chroma_announcement_tx(
+        &self,
+        chroma: Chroma,
+    ) -> RpcResult<GetChromaAnnouncementTxResponse> {
+        let storage_error = |e: KeyValueError| {
+            tracing::error!("Failed to get chroma announcement tx: {e}");
+            ErrorObject::owned(
+                INTERNAL_ERROR_CODE,
+                "Storage is not available",
+                Option::<Vec<u8>>::None,
+            )
+        };
+
+        let Some(chroma_info) = self
+            .announcement_storage
+            .get_chroma_info(&chroma)
+            .await
+            .map_err(storage_error)?
+        else {
+            return Ok(GetChromaAnnouncementTxResponse::NotFound);
+        };
+
+        if chroma_info.announcement.is_none() {
+            return Ok(GetChromaAnnouncementTxResponse::NotAnnounced);
+        }
+
+        let tx = match chroma_info.announcement_txid {
+            Some(txid) => self
+                .txs_storage
+                .get_yuv_tx(&txid)
+                .await
+                .map_err(storage_error)?,
+            None => None,
+        };
+
+        Ok(GetChromaAnnouncementTxResponse::Announced {
+            txid: chroma_info.announcement_txid,
+            tx,
+        })
+    }
+
+    async fn get_invalid_tx(&self, txid: Txid) -> RpcResult<Option<GetInvalidTxResponse>> {
+        let storage_error = |e: KeyValueError| {
+            tracing::error!("Failed to get invalid tx: {e}");
+            ErrorObject::owned(
+                INTERNAL_ERROR_CODE,
+                "Storage is not available",
+                Option::<Vec<u8>>::None,
+            )
+        };
+
+        let is_invalid = self
+            .announcement_storage
+            .get_invalid_tx(txid)
+            .await
+            .map_err(storage_error)?
+            .is_some();
+
+        if !is_invalid {
+            return Ok(None);
+        }
+
+        let entry = self
+            .announcement_storage
+            .get_invalid_tx_entry(txid)
+            .await
+            .map_err(storage_error)?;
+
+        Ok(Some(GetInvalidTxResponse {
+            reason: entry.as_ref().map(|entry| entry.reason.clone()),
+            rejected_at: entry.map(|entry| entry.rejected_at),
+        }))
+    }
+
+    async fn share_inventory(&self, txids: Vec<Txid>) -> RpcResult<bool> {
+        if txids.len() > self.max_items_per_request {
+            return Err(ErrorObject::owned(
+                INVALID_REQUEST_CODE,
+                format!(
+                    "Too many txids, max amount is {}",
+                    self.max_items_per_request
+                ),
+                Option::<Vec<u8>>::None,
+            ));
+        }
+
+        self.event_bus
+            .try_send(ControllerMessage::ShareInventory(txids))
+            .await
+            .map_err(|_| {
+                tracing::error!("failed to send message to message handler");
+                ErrorObjectOwned::owned(
+                    INTERNAL_ERROR_CODE,
+                    "Service is dead",
+                    Option::<Vec<u8>>::None,
+                )
+            })?;
+
+        Ok(true)
+    }
 }
 
 /// Entity that emulates transactions by checking if the one violates any of
@@ -318,25 +759,46 @@ where
 ///
 /// 1. All proofs are valid for this transaction;
 /// 2. Transaction is not violating any consideration rules;
-/// 3. None of the inputs are already frozen;
-/// 4. That all parents are already attached in internal node storage.
+/// 3. None of the inputs are already frozen, by themselves or with all the outputs of their
+///    chroma;
+/// 4. That all parents are already attached in internal node storage;
+/// 5. None of the inputs are immature coinbase outputs, whose confirmations are requested
+///    from the Bitcoin node;
+/// 6. Transaction has no more inputs and outputs than the checkers accept, if the limits are
+///    set with [`with_tx_size_limits`](TransactionEmulator::with_tx_size_limits).
+///
+/// With [`with_height`](TransactionEmulator::with_height), inputs are checked to be frozen as of
+/// the given height instead of the current state, which is best-effort (see
+/// [`FrozenTxsStorage::is_frozen_at`]).
 ///
 /// If any of them encountered, return an error on method [`emulate_yuv_transaction`].
 ///
 /// [`emulate_yuv_transaction`]: TransactionEmulator::emulate_yuv_transaction
 // TODO: This could be moved to separate module.
-pub struct TransactionEmulator<TransactionStorage, FreezesStorage> {
+pub struct TransactionEmulator<TransactionStorage, FreezesStorage, BitcoinClient> {
     /// Internal storage of transactions.
     txs_storage: TransactionStorage,
 
     /// Internal storage of frozen transactions.
     frozen_txs_storage: FreezesStorage,
+
+    /// Bitcoin RPC client to get the confirmations of the coinbase parents.
+    bitcoin_client: Arc<BitcoinClient>,
+
+    /// Height as of which the inputs are checked to be frozen. `None` for the current state.
+    at_height: Option<u64>,
+
+    /// Max number of the inputs of the transaction. Unlimited if `None`.
+    max_inputs: Option<usize>,
+
+    /// Max number of the outputs of the transaction. Unlimited if `None`.
+    max_outputs: Option<usize>,
 }
 
 #[derive(Debug, thiserror::Error)]
 pub enum EmulateYuvTransactionError {
     #[error("Transaction check error: {0}")]
-    CheckFailed(#[from] CheckError),
+    CheckFailed(#[from] TxCheckerError),
 
     #[error("Parent transaction is not found: {txid}")]
     ParentTransactionNotFound { txid: Txid },
@@ -347,49 +809,191 @@ pub enum EmulateYuvTransactionError {
     #[error("Parent transaction is frozen: {txid}:{vout}")]
     ParentTransactionFrozen { txid: Txid, vout: u32 },
 
+    #[error("Parent UTXO is already spent by another transaction in the list: {txid}:{vout}")]
+    ParentUtxoSpent { txid: Txid, vout: u32 },
+
     #[error("Storage is not available: {0}")]
     StorageNotAvailable(#[from] KeyValueError),
+
+    #[error("Bitcoin node is not available: {0}")]
+    BitcoinNotAvailable(#[from] bitcoin_client::Error),
 }
 
-impl<TXS, FZS> TransactionEmulator<TXS, FZS>
+impl From<CheckError> for EmulateYuvTransactionError {
+    fn from(err: CheckError) -> Self {
+        Self::CheckFailed(err.into())
+    }
+}
+
+impl<TXS, FZS, BC> TransactionEmulator<TXS, FZS, BC>
 where
     TXS: TransactionsStorage + Send + Sync + 'static,
-    FZS: FrozenTxsStorage + Send + Sync + 'static,
+    FZS: FrozenTxsStorage + ChromaInfoStorage + Send + Sync + 'static,
+    BC: BitcoinRpcApi + Send + Sync + 'static,
 {
-    pub fn new(txs_storage: TXS, frozen_txs_storage: FZS) -> Self {
+    pub fn new(txs_storage: TXS, frozen_txs_storage: FZS, bitcoin_client: Arc<BC>) -> Self {
         Self {
             txs_storage,
             frozen_txs_storage,
+            bitcoin_client,
+            at_height: None,
+            max_inputs: None,
+            max_outputs: None,
         }
     }
 
+    /// Check the inputs to be frozen as of the given height. `None` for the current state.
+    pub fn with_height(mut self, at_height: Option<u64>) -> Self {
+        self.at_height = at_height;
+
+        self
+    }
+
+    /// Reject the transactions with more inputs or outputs than the limits.
+    pub fn with_tx_size_limits(
+        mut self,
+        max_inputs: Option<usize>,
+        max_outputs: Option<usize>,
+    ) -> Self {
+        self.max_inputs = max_inputs;
+        self.max_outputs = max_outputs;
+
+        self
+    }
+
+    /// Check the transaction in isolation: its size, proofs and conservation rules.
+    fn check_isolated(&self, yuv_tx: &YuvTransaction) -> Result<(), TxCheckerError> {
+        check_tx_size(&yuv_tx.bitcoin_tx, self.max_inputs, self.max_outputs)?;
+        check_transaction(yuv_tx)?;
+
+        Ok(())
+    }
+
     /// Emulate transaction check and attach without actuall broadcasting or
     /// mining. See [`TransactionEmulator`] for more info.
     pub async fn emulate_yuv_transaction(
         &self,
         yuv_tx: &YuvTransaction,
     ) -> Result<(), EmulateYuvTransactionError> {
-        // Check first two bullets.
-        check_transaction(yuv_tx)?;
+        // Check the first two bullets and the last one.
+        self.check_isolated(yuv_tx)?;
 
         let Some(parents) = extract_parents(yuv_tx) else {
             return Ok(());
         };
 
-        self.check_parents(parents).await?;
+        self.check_parents(parents, &HashMap::new()).await?;
 
         Ok(())
     }
 
-    /// Check that all parent transactions are not spent or frozen.
+    /// Emulate check and attach of the transactions as if they were provided together, and
+    /// return the result for each of them in the same order.
+    ///
+    /// Transactions are attached to the scratch storage, which is dropped after the emulation,
+    /// so the parents are looked up in it first, and then in the node storage. Like in the
+    /// graph builder, a transaction waits for its parents from the list regardless of the
+    /// order, and the ones whose parents are never attached are rejected. Unlike the separate
+    /// emulations, an output spent by several transactions is caught.
+    pub async fn emulate_yuv_transactions(
+        &self,
+        yuv_txs: &[YuvTransaction],
+    ) -> Vec<Result<(), EmulateYuvTransactionError>> {
+        use EmulateYuvTransactionError as Error;
+
+        let mut results = yuv_txs
+            .iter()
+            .map(|yuv_tx| self.check_isolated(yuv_tx).err().map(|err| Err(err.into())))
+            .collect::<Vec<Option<Result<(), Error>>>>();
+
+        let mut scratch = HashMap::<Txid, YuvTransaction>::new();
+        let mut spent = HashSet::<OutPoint>::new();
+
+        loop {
+            let pending = yuv_txs
+                .iter()
+                .zip(&results)
+                .filter(|(_, result)| result.is_none())
+                .map(|(yuv_tx, _)| yuv_tx.bitcoin_tx.txid())
+                .collect::<HashSet<_>>();
+
+            let mut is_progressed = false;
+
+            for (yuv_tx, result) in yuv_txs.iter().zip(results.iter_mut()) {
+                if result.is_some() {
+                    continue;
+                }
+
+                let parents = extract_parents(yuv_tx).unwrap_or_default();
+
+                // Wait for the parents from the list to be attached first.
+                if parents.iter().any(|parent| pending.contains(&parent.txid)) {
+                    continue;
+                }
+
+                let emulated = match parents.iter().find(|parent| spent.contains(parent)) {
+                    Some(parent) => Err(Error::ParentUtxoSpent {
+                        txid: parent.txid,
+                        vout: parent.vout,
+                    }),
+                    None => self.check_parents(parents.clone(), &scratch).await,
+                };
+
+                if emulated.is_ok() {
+                    spent.extend(parents);
+                    scratch.insert(yuv_tx.bitcoin_tx.txid(), yuv_tx.clone());
+                }
+
+                *result = Some(emulated);
+                is_progressed = true;
+            }
+
+            if !is_progressed {
+                break;
+            }
+        }
+
+        // The rest wait for each other, or for the parents that are rejected.
+        yuv_txs
+            .iter()
+            .zip(results)
+            .map(|(yuv_tx, result)| {
+                result.unwrap_or_else(|| {
+                    let txid = extract_parents(yuv_tx)
+                        .unwrap_or_default()
+                        .iter()
+                        .find(|parent| !scratch.contains_key(&parent.txid))
+                        .map_or(yuv_tx.bitcoin_tx.txid(), |parent| parent.txid);
+
+                    Err(Error::ParentTransactionNotFound { txid })
+                })
+            })
+            .collect()
+    }
+
+    /// Check that all parent transactions are not spent, frozen or immature coinbase. Parents are
+    /// looked up in the `scratch` transactions first, and then in the storage.
     async fn check_parents(
         &self,
         parents: Vec<OutPoint>,
+        scratch: &HashMap<Txid, YuvTransaction>,
     ) -> Result<(), EmulateYuvTransactionError> {
         use EmulateYuvTransactionError as Error;
 
         for parent in parents {
-            let tx_entry = self.txs_storage.get_yuv_tx(&parent.txid).await?;
+            let tx_entry = match scratch.get(&parent.txid) {
+                Some(tx) => Some(tx.clone()),
+                None => {
+                    let tx_entry = self.txs_storage.get_yuv_tx(&parent.txid).await?;
+
+                    // Return an error if parent transaction is immature coinbase.
+                    if let Some(tx) = tx_entry.as_ref().filter(|tx| tx.bitcoin_tx.is_coin_base()) {
+                        self.check_coinbase_maturity(parent, &tx.bitcoin_tx).await?;
+                    }
+
+                    tx_entry
+                }
+            };
 
             // Return an error if parent transaction not found.
             let Some(tx) = tx_entry else {
@@ -401,32 +1005,60 @@ where
             };
 
             // Return an error if parent transaction output not found.
-            if output_proofs.get(&parent.vout).is_none() {
+            let Some(proof) = output_proofs.get(&parent.vout) else {
                 return Err(Error::ParentUtxoNotFound {
                     txid: parent.txid,
                     vout: parent.vout,
                 });
-            }
+            };
 
             // Return an error if parent transaction output is already frozen.
-            self.is_parent_frozen(parent).await?;
+            self.is_parent_frozen(parent, &proof.pixel().chroma).await?;
         }
 
         Ok(())
     }
 
+    /// Check that the coinbase parent has enough confirmations to be spent.
+    async fn check_coinbase_maturity(
+        &self,
+        parent: OutPoint,
+        parent_tx: &Transaction,
+    ) -> Result<(), EmulateYuvTransactionError> {
+        let confirmations = self
+            .bitcoin_client
+            .get_raw_transaction_info(&parent.txid, None)
+            .await?
+            .confirmations
+            .unwrap_or_default();
+
+        check_coinbase_maturity(parent, parent_tx, confirmations)?;
+
+        Ok(())
+    }
+
     /// Check if parent UTXO is frozen or not.
-    async fn is_parent_frozen(&self, parent: OutPoint) -> Result<(), EmulateYuvTransactionError> {
-        let frozen_entry = self
-            .frozen_txs_storage
-            .get_frozen_tx(&OutPoint::new(parent.txid, parent.vout))
-            .await?;
+    async fn is_parent_frozen(
+        &self,
+        parent: OutPoint,
+        chroma: &Chroma,
+    ) -> Result<(), EmulateYuvTransactionError> {
+        let storage = &self.frozen_txs_storage;
 
-        let Some(frozen_entry) = frozen_entry else {
-            return Ok(());
+        let is_frozen = match self.at_height {
+            Some(height) => {
+                storage.is_frozen_at(&parent, height).await?
+                    || storage.is_chroma_frozen_at(chroma, height).await?
+            }
+            None => {
+                let frozen_entry = storage.get_frozen_tx(&parent).await?;
+
+                frozen_entry.is_some_and(|entry| entry.is_frozen())
+                    || storage.is_chroma_frozen(chroma).await?
+            }
         };
 
-        if frozen_entry.is_frozen() {
+        if is_frozen {
             return Err(EmulateYuvTransactionError::ParentTransactionFrozen {
                 txid: parent.txid,
                 vout: parent.vout,
@@ -468,3 +1100,572 @@ fn collect_transfer_parents(yuv_tx: &YuvTransaction, input_proofs: &ProofMap) ->
         })
         .collect::<Vec<_>>()
 }
+
+/// Get up to `limit` ids of the paginated transactions starting from the one with `start` index,
+/// reading as many storage pages of `page_size` as needed.
+async fn get_paginated_txids<S: PagesStorage + Sync>(
+    storage: &S,
+    start: u64,
+    limit: u64,
+    page_size: u64,
+) -> Result<Vec<Txid>, KeyValueError> {
+    let mut txids = Vec::new();
+
+    if page_size == 0 {
+        return Ok(txids);
+    }
+
+    let mut page_num = start / page_size;
+    let mut skip = (start % page_size) as usize;
+
+    while (txids.len() as u64) < limit {
+        let Some(page) = storage.get_page_by_num(page_num).await? else {
+            break;
+        };
+
+        let left = limit as usize - txids.len();
+        txids.extend(page.into_iter().skip(skip).take(left));
+
+        skip = 0;
+        page_num += 1;
+    }
+
+    Ok(txids)
+}
+
+#[cfg(test)]
+mod tests {
+    use bitcoin::hashes::Hash;
+    use bitcoin::secp256k1::{Secp256k1, SecretKey};
+    use bitcoin::{PackedLockTime, TxOut};
+    use bitcoin_client::MockRpcApi;
+    use yuv_pixels::{Pixel, PixelKey, PixelProof, SigPixelProof};
+    use yuv_storage::LevelDB;
+    use yuv_tx_check::COINBASE_MATURITY;
+    use yuv_types::announcements::IssueAnnouncement;
+    use yuv_types::mocks;
+    use yuv_types::AnyAnnouncement;
+
+    use super::*;
+
+    const PAGE_SIZE: u64 = 3;
+
+    /// Event bus with the channel the controller sends to.
+    fn event_bus() -> EventBus {
+        let mut event_bus = EventBus::default();
+        event_bus.register::<ControllerMessage>(Some(100));
+
+        event_bus
+    }
+
+    /// Controller over the `storage` with the default settings, which the tests override with the
+    /// ones they need.
+    fn controller(
+        storage: &LevelDB,
+        event_bus: &EventBus,
+    ) -> TransactionsController<LevelDB, LevelDB, MockRpcApi> {
+        TransactionsController::new(
+            storage.clone(),
+            event_bus.clone(),
+            TxStatesStorage::default(),
+            storage.clone(),
+            Arc::new(MockRpcApi::new()),
+            100,
+            1024,
+        )
+    }
+
+    #[tokio::test]
+    async fn test_paginated_txids_are_sliced_across_pages() -> eyre::Result<()> {
+        let storage = LevelDB::in_memory()?;
+
+        let txids = (0..8u8)
+            .map(|i| Txid::from_inner([i; 32]))
+            .collect::<Vec<_>>();
+        for (page_num, page) in txids.chunks(PAGE_SIZE as usize).enumerate() {
+            storage.put_page(page_num as u64, page.to_vec()).await?;
+        }
+
+        assert_eq!(
+            get_paginated_txids(&storage, 2, 4, PAGE_SIZE).await?,
+            txids[2..6],
+            "slice must cross the pages"
+        );
+        assert_eq!(
+            get_paginated_txids(&storage, 6, 4, PAGE_SIZE).await?,
+            txids[6..],
+            "slice must be trimmed by the last page"
+        );
+        assert_eq!(
+            get_paginated_txids(&storage, 3, 3, PAGE_SIZE).await?,
+            storage.get_page_by_num(1).await?.unwrap(),
+            "slice of the page size must be the same as the page"
+        );
+        assert!(get_paginated_txids(&storage, 9, 2, PAGE_SIZE)
+            .await?
+            .is_empty());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_default_limit_is_bounded_by_max_items() -> eyre::Result<()> {
+        let storage = LevelDB::in_memory()?;
+
+        let txs = (1..=6).map(issuance).collect::<Vec<_>>();
+        for tx in &txs {
+            storage.put_yuv_tx(tx.clone()).await?;
+        }
+        let txids = txs
+            .iter()
+            .map(|tx| tx.bitcoin_tx.txid())
+            .collect::<Vec<_>>();
+        for (page_num, page) in txids.chunks(PAGE_SIZE as usize).enumerate() {
+            storage.put_page(page_num as u64, page.to_vec()).await?;
+        }
+        storage.put_page_size(PAGE_SIZE).await?;
+
+        let event_bus = event_bus();
+
+        let controller = TransactionsController {
+            max_items_per_request: 2,
+            ..controller(&storage, &event_bus)
+        };
+
+        let listed = controller.list_yuv_transactions(1, None, Some(1)).await?;
+        assert_eq!(
+            listed
+                .iter()
+                .map(|tx| tx.bitcoin_tx.txid())
+                .collect::<Vec<_>>(),
+            txids[3..5],
+            "limit must default to the max items per request"
+        );
+
+        Ok(())
+    }
+
+    const ISSUER_KEY: [u8; 32] = [5; 32];
+
+    /// Issuance of the `amount` of tokens to the issuer.
+    fn issuance(amount: u128) -> YuvTransaction {
+        let issuer = SecretKey::from_slice(&ISSUER_KEY).unwrap();
+
+        mocks::issuance(&issuer, amount, issuer.public_key(&Secp256k1::new()))
+    }
+
+    #[tokio::test]
+    async fn test_same_proof_is_not_resubmitted() -> eyre::Result<()> {
+        let storage = LevelDB::in_memory()?;
+        let txs_states_storage = TxStatesStorage::default();
+
+        let event_bus = event_bus();
+        let events = event_bus.subscribe::<ControllerMessage>();
+
+        let controller = TransactionsController {
+            txs_states_storage: txs_states_storage.clone(),
+            ..controller(&storage, &event_bus)
+        };
+
+        let yuv_tx = issuance(1000);
+        let txid = yuv_tx.bitcoin_tx.txid();
+
+        controller.provide_yuv_proof(yuv_tx.clone()).await?;
+        assert_eq!(events.len(), 1, "first submission must be sent to check");
+        events.recv().await?;
+
+        // Controller marks the transaction as pending when it receives it.
+        txs_states_storage.insert(txid, TxState::Pending).await;
+        controller.provide_yuv_proof(yuv_tx.clone()).await?;
+        assert!(events.is_empty(), "pending tx must not be sent again");
+
+        txs_states_storage.remove(&txid).await;
+        storage.put_yuv_tx(yuv_tx.clone()).await?;
+        controller.provide_yuv_proof(yuv_tx.clone()).await?;
+        assert!(events.is_empty(), "attached tx must not be sent again");
+
+        storage.delete_yuv_tx(&txid).await?;
+        let other_tx = YuvTransaction::new(yuv_tx.bitcoin_tx.clone(), issuance(500).tx_type);
+
+        controller.provide_yuv_proof(other_tx).await?;
+        assert_eq!(
+            events.len(),
+            1,
+            "different proof of the rejected tx must be sent to check"
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_output_proof() -> eyre::Result<()> {
+        let storage = LevelDB::in_memory()?;
+
+        let event_bus = event_bus();
+
+        let controller = controller(&storage, &event_bus);
+
+        // The pixel output is the first one, and the announcement is the second one.
+        let yuv_tx = issuance(1000);
+        let txid = yuv_tx.bitcoin_tx.txid();
+        let proof = yuv_tx
+            .tx_type
+            .output_proofs()
+            .expect("issuance must have proofs")[&0]
+            .clone();
+
+        assert_eq!(
+            controller.get_output_proof(txid, 0).await?,
+            GetOutputProofResponse::TxNotFound
+        );
+
+        storage.put_yuv_tx(yuv_tx.clone()).await?;
+
+        assert_eq!(
+            controller.get_output_proof(txid, 0).await?,
+            GetOutputProofResponse::Found {
+                proof,
+                txout: yuv_tx.bitcoin_tx.output[0].clone(),
+            }
+        );
+        for vout in [1, 2] {
+            assert_eq!(
+                controller.get_output_proof(txid, vout).await?,
+                GetOutputProofResponse::NotYuvOutput
+            );
+        }
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_partial_proofs_are_merged() -> eyre::Result<()> {
+        let storage = LevelDB::in_memory()?;
+
+        let event_bus = event_bus();
+        let events = event_bus.subscribe::<ControllerMessage>();
+
+        let partial_proofs = PartialProofs::default();
+        let controller =
+            controller(&storage, &event_bus).with_partial_proofs(partial_proofs.clone());
+
+        let ctx = Secp256k1::new();
+        let issuer = SecretKey::from_slice(&ISSUER_KEY)?;
+        let pubkey = issuer.public_key(&ctx);
+        let chroma = Chroma::from(pubkey.x_only_public_key().0);
+        let announcement = IssueAnnouncement::new(chroma, 1000);
+
+        let proofs = [600, 400]
+            .map(|amount| PixelProof::Sig(SigPixelProof::new(Pixel::new(amount, chroma), pubkey)));
+        let mut outputs = proofs
+            .iter()
+            .map(|proof| TxOut {
+                value: 1000,
+                script_pubkey: PixelKey::new(proof.pixel(), &pubkey)
+                    .unwrap()
+                    .to_p2wpkh()
+                    .unwrap(),
+            })
+            .collect::<Vec<_>>();
+        outputs.push(TxOut {
+            value: 0,
+            script_pubkey: announcement.to_script(),
+        });
+
+        let bitcoin_tx = Transaction {
+            version: 2,
+            lock_time: PackedLockTime::ZERO,
+            input: vec![mocks::signed_input(OutPoint::null(), &issuer, pubkey)],
+            output: outputs,
+        };
+        let txid = bitcoin_tx.txid();
+        let [first_half, second_half] = [(0, &proofs[0]), (1, &proofs[1])].map(|(vout, proof)| {
+            YuvTransaction::new(
+                bitcoin_tx.clone(),
+                YuvTxType::Issue {
+                    output_proofs: Some(ProofMap::from([(vout, proof.clone())])),
+                    announcement: announcement.clone(),
+                },
+            )
+        });
+
+        let response = controller.provide_partial_yuv_proof(first_half).await?;
+        assert!(
+            matches!(response, ProvidePartialYuvProofResponse::Pending { .. }),
+            "tx with the half of the proofs must be kept pending, got {response:?}"
+        );
+        assert!(events.is_empty());
+
+        let conflicting_half = YuvTransaction::new(
+            bitcoin_tx.clone(),
+            YuvTxType::Issue {
+                output_proofs: Some(ProofMap::from([(0, proofs[1].clone())])),
+                announcement: announcement.clone(),
+            },
+        );
+        assert!(
+            controller
+                .provide_partial_yuv_proof(conflicting_half)
+                .await
+                .is_err(),
+            "proof conflicting with the provided one must be rejected"
+        );
+
+        assert_eq!(
+            controller.provide_partial_yuv_proof(second_half).await?,
+            ProvidePartialYuvProofResponse::Provided
+        );
+        assert!(partial_proofs.is_empty());
+
+        let ControllerMessage::ConfirmBatchTx(txs) = events.recv().await? else {
+            panic!("merged tx must be sent to check");
+        };
+        assert_eq!(txs.len(), 1);
+        assert_eq!(txs[0].bitcoin_tx.txid(), txid);
+        assert_eq!(
+            txs[0].tx_type,
+            YuvTxType::Issue {
+                output_proofs: Some(ProofMap::from([
+                    (0, proofs[0].clone()),
+                    (1, proofs[1].clone())
+                ])),
+                announcement,
+            }
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_complete_invalid_partial_proofs_are_rejected() -> eyre::Result<()> {
+        let storage = LevelDB::in_memory()?;
+
+        let event_bus = event_bus();
+        let events = event_bus.subscribe::<ControllerMessage>();
+
+        let partial_proofs = PartialProofs::default();
+        let controller =
+            controller(&storage, &event_bus).with_partial_proofs(partial_proofs.clone());
+
+        let ctx = Secp256k1::new();
+        let issuer = SecretKey::from_slice(&ISSUER_KEY)?;
+        let pubkey = issuer.public_key(&ctx);
+        let chroma = Chroma::from(pubkey.x_only_public_key().0);
+        let announcement = IssueAnnouncement::new(chroma, 1000);
+
+        // The only output has less tokens than announced.
+        let proof = PixelProof::Sig(SigPixelProof::new(Pixel::new(600, chroma), pubkey));
+        let bitcoin_tx = Transaction {
+            version: 2,
+            lock_time: PackedLockTime::ZERO,
+            input: vec![mocks::signed_input(OutPoint::null(), &issuer, pubkey)],
+            output: vec![
+                TxOut {
+                    value: 1000,
+                    script_pubkey: PixelKey::new(proof.pixel(), &pubkey)
+                        .unwrap()
+                        .to_p2wpkh()
+                        .unwrap(),
+                },
+                TxOut {
+                    value: 0,
+                    script_pubkey: announcement.to_script(),
+                },
+            ],
+        };
+        let yuv_tx = YuvTransaction::new(
+            bitcoin_tx,
+            YuvTxType::Issue {
+                output_proofs: Some(ProofMap::from([(0, proof)])),
+                announcement,
+            },
+        );
+
+        let err = controller
+            .provide_partial_yuv_proof(yuv_tx)
+            .await
+            .expect_err("complete tx failing the checks must be rejected");
+        assert!(err.message().contains("is invalid"), "{}", err.message());
+
+        // It's kept until it expires, and nothing is sent to check.
+        assert_eq!(partial_proofs.len(), 1);
+        assert!(events.is_empty());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_oversized_partial_proofs_are_rejected() -> eyre::Result<()> {
+        let storage = LevelDB::in_memory()?;
+
+        let event_bus = event_bus();
+
+        let partial_proofs = PartialProofs::default();
+        let controller = controller(&storage, &event_bus)
+            .with_partial_proofs(partial_proofs.clone())
+            .with_tx_size_limits(None, Some(1));
+
+        // The issuance has the pixel output and the announcement one.
+        let err = controller
+            .provide_partial_yuv_proof(issuance(1000))
+            .await
+            .expect_err("tx with too many outputs must be rejected");
+        assert!(err.message().contains("outputs"), "{}", err.message());
+        assert_eq!(partial_proofs.len(), 0, "oversized tx must not be kept");
+
+        Ok(())
+    }
+
+    /// Transfer of the issued tokens from the first output of the `parent` back to the issuer,
+    /// whose txid is changed with `version`.
+    fn transfer_back(issuer: &SecretKey, parent: &YuvTransaction, version: i32) -> YuvTransaction {
+        let pubkey = issuer.public_key(&Secp256k1::new());
+        let pixel = Pixel::new(1000, Chroma::from(pubkey.x_only_public_key().0));
+
+        mocks::transfer(
+            OutPoint::new(parent.bitcoin_tx.txid(), 0),
+            issuer,
+            pixel,
+            pubkey,
+            version,
+        )
+    }
+
+    #[tokio::test]
+    async fn test_emulate_chain_of_transactions() -> eyre::Result<()> {
+        let storage = LevelDB::in_memory()?;
+        let emulator = TransactionEmulator::new(
+            storage.clone(),
+            storage.clone(),
+            Arc::new(MockRpcApi::new()),
+        );
+
+        let issuer = SecretKey::from_slice(&ISSUER_KEY)?;
+        let issue = issuance(1000);
+        let transfer = transfer_back(&issuer, &issue, 2);
+        let next_transfer = transfer_back(&issuer, &transfer, 2);
+
+        assert!(
+            matches!(
+                emulator.emulate_yuv_transaction(&transfer).await,
+                Err(EmulateYuvTransactionError::ParentTransactionNotFound { .. })
+            ),
+            "transfer must not be attached without its parent"
+        );
+
+        // Parents could be provided after the children.
+        let results = emulator
+            .emulate_yuv_transactions(&[next_transfer.clone(), issue.clone(), transfer.clone()])
+            .await;
+        assert!(
+            results.iter().all(Result::is_ok),
+            "the whole chain must be attached: {results:?}"
+        );
+
+        let double_spend = transfer_back(&issuer, &issue, 3);
+        let results = emulator
+            .emulate_yuv_transactions(&[issue.clone(), transfer.clone(), double_spend])
+            .await;
+        assert!(results[0].is_ok() && results[1].is_ok());
+        assert!(
+            matches!(
+                results[2],
+                Err(EmulateYuvTransactionError::ParentUtxoSpent { .. })
+            ),
+            "output must not be spent twice"
+        );
+
+        let results = emulator
+            .emulate_yuv_transactions(&[next_transfer, transfer])
+            .await;
+        assert!(
+            results.iter().all(|result| matches!(
+                result,
+                Err(EmulateYuvTransactionError::ParentTransactionNotFound { .. })
+            )),
+            "chain without the issuance must not be attached: {results:?}"
+        );
+
+        assert!(
+            storage
+                .get_yuv_tx(&issue.bitcoin_tx.txid())
+                .await?
+                .is_none(),
+            "emulation must not change the storage"
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_emulate_oversized_transaction() -> eyre::Result<()> {
+        let storage = LevelDB::in_memory()?;
+        let emulator = TransactionEmulator::new(
+            storage.clone(),
+            storage.clone(),
+            Arc::new(MockRpcApi::new()),
+        )
+        .with_tx_size_limits(None, Some(1));
+
+        let issue = issuance(1000);
+        let is_too_big = |result: &Result<(), EmulateYuvTransactionError>| {
+            matches!(
+                result,
+                Err(EmulateYuvTransactionError::CheckFailed(
+                    TxCheckerError::TooManyOutputs { count: 2, limit: 1 }
+                ))
+            )
+        };
+
+        let result = emulator.emulate_yuv_transaction(&issue).await;
+        assert!(is_too_big(&result), "{result:?}");
+
+        let results = emulator.emulate_yuv_transactions(&[issue]).await;
+        assert!(is_too_big(&results[0]), "{results:?}");
+
+        Ok(())
+    }
+
+    /// Bitcoin RPC that returns the transactions with the given number of confirmations.
+    fn bitcoin_client(confirmations: u32) -> Arc<MockRpcApi> {
+        Arc::new(MockRpcApi::with_raw_txs(None, Some(confirmations), None))
+    }
+
+    #[tokio::test]
+    async fn test_emulate_spend_of_coinbase() -> eyre::Result<()> {
+        let storage = LevelDB::in_memory()?;
+
+        let issuer = SecretKey::from_slice(&ISSUER_KEY)?;
+        // Issuance spends the null outpoint, so it's a coinbase.
+        let issue = issuance(1000);
+        let transfer = transfer_back(&issuer, &issue, 2);
+        assert!(issue.bitcoin_tx.is_coin_base());
+
+        storage.put_yuv_tx(issue.clone()).await?;
+
+        let immature = COINBASE_MATURITY - 1;
+        let emulator =
+            TransactionEmulator::new(storage.clone(), storage.clone(), bitcoin_client(immature));
+
+        let result = emulator.emulate_yuv_transaction(&transfer).await;
+        assert!(
+            matches!(
+                result,
+                Err(EmulateYuvTransactionError::CheckFailed(TxCheckerError::Check(
+                    CheckError::ImmatureCoinbase { confirmations, .. }
+                ))) if confirmations == immature
+            ),
+            "immature coinbase must not be spent: {result:?}"
+        );
+
+        let emulator = TransactionEmulator::new(
+            storage.clone(),
+            storage.clone(),
+            bitcoin_client(COINBASE_MATURITY),
+        );
+
+        emulator.emulate_yuv_transaction(&transfer).await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,264 @@
+//! This module provides a [`ConsistencyReport`] of the node's storages, which finds the entries
+//! that have drifted from the set of attached transactions, e.g. after a crash.
+
+use std::collections::HashSet;
+
+use bitcoin::{OutPoint, Txid};
+use yuv_types::{YuvTransaction, YuvTxType};
+
+use crate::{FrozenTxsStorage, PagesStorage, TransactionsStorage};
+
+/// Inconsistencies between the pages, attached transactions and freeze entries of the node.
+///
+/// Attached transactions are found through the pages, as the storage can't be iterated, so the
+/// freeze entries are checked only for the outputs of the transactions in the pages.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ConsistencyReport {
+    /// Transactions in the pages that aren't stored, with the number of their page.
+    ///
+    /// Pruning removes the pruned transactions from the pages, so these are left only if the node
+    /// was stopped in the middle of it.
+    pub dangling_page_entries: Vec<(u64, Txid)>,
+    /// Parents of the attached transfers that aren't stored, with the transfer's id.
+    ///
+    /// Pruned transactions can be parents too, so these are expected if the pruning is enabled.
+    pub missing_parents: Vec<(Txid, Txid)>,
+    /// Freeze toggles of the outputs that reference transactions that aren't stored.
+    pub orphaned_freezes: Vec<(OutPoint, Txid)>,
+}
+
+impl ConsistencyReport {
+    /// Check the consistency of the node's storages.
+    ///
+    /// If `fix` is set, dangling page entries are removed from the pages the same way as the
+    /// pruning does, moving the rest to fill the pages up to the page size. Orphaned freeze
+    /// toggles are removed from the entries, which may unfreeze the output. Missing parents
+    /// can't be restored, so they're only reported.
+    pub async fn check<TS, SS>(
+        txs_storage: &TS,
+        state_storage: &SS,
+        fix: bool,
+    ) -> eyre::Result<Self>
+    where
+        TS: TransactionsStorage + PagesStorage + Send + Sync,
+        SS: FrozenTxsStorage + Send + Sync,
+    {
+        let mut report = Self::default();
+
+        let Some(pages_number) = txs_storage.get_pages_number().await? else {
+            return Ok(report);
+        };
+
+        // Pages are filled up to the page size, so the first one is the longest if the size
+        // isn't stored.
+        let mut page_size = txs_storage.get_page_size().await?;
+
+        for page_num in 0..=pages_number {
+            let page = txs_storage
+                .get_page_by_num(page_num)
+                .await?
+                .unwrap_or_default();
+
+            page_size.get_or_insert(page.len() as u64);
+
+            for txid in page {
+                let Some(tx) = txs_storage.get_yuv_tx(&txid).await? else {
+                    report.dangling_page_entries.push((page_num, txid));
+                    continue;
+                };
+
+                report.check_parents(txs_storage, &tx).await?;
+                report
+                    .check_freezes(txs_storage, state_storage, &tx, fix)
+                    .await?;
+            }
+        }
+
+        if fix && !report.dangling_page_entries.is_empty() {
+            let dangling_txids = report
+                .dangling_page_entries
+                .iter()
+                .map(|(_, txid)| *txid)
+                .collect::<HashSet<_>>();
+
+            txs_storage
+                .remove_from_pages(&dangling_txids, page_size.unwrap_or_default())
+                .await?;
+        }
+
+        Ok(report)
+    }
+
+    /// Check that the parents of the transfer are stored.
+    async fn check_parents<TS>(&mut self, txs_storage: &TS, tx: &YuvTransaction) -> eyre::Result<()>
+    where
+        TS: TransactionsStorage + Send + Sync,
+    {
+        let YuvTxType::Transfer { input_proofs, .. } = &tx.tx_type else {
+            return Ok(());
+        };
+
+        let txid = tx.bitcoin_tx.txid();
+        for vin in input_proofs.keys() {
+            let Some(input) = tx.bitcoin_tx.input.get(*vin as usize) else {
+                continue;
+            };
+
+            let parent = input.previous_output.txid;
+            if txs_storage.get_yuv_tx(&parent).await?.is_none() {
+                self.missing_parents.push((txid, parent));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Check that the freeze toggles of the transaction's outputs are stored.
+    async fn check_freezes<TS, SS>(
+        &mut self,
+        txs_storage: &TS,
+        state_storage: &SS,
+        tx: &YuvTransaction,
+        fix: bool,
+    ) -> eyre::Result<()>
+    where
+        TS: TransactionsStorage + Send + Sync,
+        SS: FrozenTxsStorage + Send + Sync,
+    {
+        let Some(output_proofs) = tx.tx_type.output_proofs() else {
+            return Ok(());
+        };
+
+        let txid = tx.bitcoin_tx.txid();
+        for vout in output_proofs.keys() {
+            let outpoint = OutPoint::new(txid, *vout);
+            let Some(entry) = state_storage.get_frozen_tx(&outpoint).await? else {
+                continue;
+            };
+
+            let mut kept = Vec::with_capacity(entry.tx_ids.len());
+            for freeze_txid in entry.tx_ids {
+                if txs_storage.get_yuv_tx(&freeze_txid).await?.is_some() {
+                    kept.push(freeze_txid);
+                } else {
+                    self.orphaned_freezes.push((outpoint, freeze_txid));
+                }
+            }
+
+            let has_orphaned_freezes = self
+                .orphaned_freezes
+                .last()
+                .is_some_and(|(orphaned_outpoint, _)| *orphaned_outpoint == outpoint);
+            if !fix || !has_orphaned_freezes {
+                continue;
+            }
+
+            if kept.is_empty() {
+                state_storage.delete_frozen_tx(&outpoint).await?;
+            } else {
+                state_storage.put_frozen_tx(&outpoint, kept).await?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Check if no inconsistencies were found.
+    pub fn is_consistent(&self) -> bool {
+        self.dangling_page_entries.is_empty()
+            && self.missing_parents.is_empty()
+            && self.orphaned_freezes.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bitcoin::{hashes::Hash, secp256k1};
+    use yuv_pixels::{Chroma, Pixel};
+    use yuv_types::mocks;
+
+    use super::*;
+    use crate::{LevelDB, PagesNumberStorage};
+
+    fn transfer(parent: OutPoint, version: i32) -> YuvTransaction {
+        let owner = secp256k1::SecretKey::from_slice(&[1; 32]).unwrap();
+        let pubkey = owner.public_key(&secp256k1::Secp256k1::new());
+        let pixel = Pixel::new(10, Chroma::from(pubkey.x_only_public_key().0));
+
+        mocks::transfer(parent, &owner, pixel, pubkey, version)
+    }
+
+    #[test]
+    fn test_inconsistencies_are_reported_and_fixed() -> eyre::Result<()> {
+        tokio_test::block_on(async {
+            let txs_storage = LevelDB::in_memory()?;
+            let state_storage = LevelDB::in_memory()?;
+
+            let missing_parent = Txid::from_inner([1; 32]);
+            let parent = transfer(OutPoint::new(missing_parent, 0), 0);
+            let child = transfer(OutPoint::new(parent.bitcoin_tx.txid(), 0), 1);
+            let dangling_txid = Txid::from_inner([2; 32]);
+            let missing_freeze_txid = Txid::from_inner([3; 32]);
+
+            let txids = [parent.bitcoin_tx.txid(), child.bitcoin_tx.txid()];
+            txs_storage.put_yuv_tx(parent).await?;
+            txs_storage.put_yuv_tx(child).await?;
+            txs_storage
+                .put_page(0, vec![txids[0], dangling_txid])
+                .await?;
+            txs_storage.put_page(1, vec![txids[1]]).await?;
+            txs_storage.put_pages_number(1).await?;
+            txs_storage.put_page_size(2).await?;
+
+            // The first toggle is stored, the second one isn't.
+            let frozen_outpoint = OutPoint::new(txids[1], 0);
+            state_storage
+                .put_frozen_tx(&frozen_outpoint, vec![txids[0], missing_freeze_txid])
+                .await?;
+
+            let expected = ConsistencyReport {
+                dangling_page_entries: vec![(0, dangling_txid)],
+                missing_parents: vec![(txids[0], missing_parent)],
+                orphaned_freezes: vec![(frozen_outpoint, missing_freeze_txid)],
+            };
+
+            // Nothing is changed without the fix, so it reports the same.
+            let report = ConsistencyReport::check(&txs_storage, &state_storage, false).await?;
+            assert_eq!(report, expected);
+            let report = ConsistencyReport::check(&txs_storage, &state_storage, true).await?;
+            assert_eq!(report, expected);
+
+            // Only missing parents can't be fixed.
+            let report = ConsistencyReport::check(&txs_storage, &state_storage, false).await?;
+            assert_eq!(
+                report,
+                ConsistencyReport {
+                    missing_parents: vec![(txids[0], missing_parent)],
+                    ..Default::default()
+                }
+            );
+            // The pages are compacted up to the stored page size.
+            assert_eq!(txs_storage.get_page_by_num(0).await?, Some(txids.to_vec()));
+            assert_eq!(txs_storage.get_page_by_num(1).await?, None);
+            assert_eq!(txs_storage.get_pages_number().await?, Some(0));
+            assert_eq!(
+                state_storage.get_frozen_tx(&frozen_outpoint).await?,
+                Some(vec![txids[0]].into())
+            );
+
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn test_empty_storage_is_consistent() -> eyre::Result<()> {
+        tokio_test::block_on(async {
+            let storage = LevelDB::in_memory()?;
+
+            let report = ConsistencyReport::check(&storage, &storage, true).await?;
+            assert!(report.is_consistent());
+
+            Ok(())
+        })
+    }
+}
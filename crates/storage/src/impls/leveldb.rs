@@ -19,6 +19,14 @@ pub struct Options {
     pub path: PathBuf,
     pub create_if_missing: bool,
     pub flush_strategy: FlushStrategy,
+    /// Size of the in-memory buffer of the writes before they are sorted and
+    /// written to the disk. LevelDB's default (4 MiB) if not set.
+    pub write_buffer_bytes: Option<usize>,
+    /// Max number of files LevelDB keeps open. LevelDB's default (1024) if not set.
+    pub max_open_files: Option<usize>,
+    /// Size of the in-memory cache of the read blocks. LevelDB's default (8 MiB)
+    /// if not set.
+    pub block_cache_bytes: Option<usize>,
 }
 
 pub enum FlushStrategy {
@@ -53,11 +61,21 @@ impl LevelDB {
     }
 
     pub fn from_opts(config: Options) -> eyre::Result<Self> {
-        let opt = rusty_leveldb::Options {
+        let mut opt = rusty_leveldb::Options {
             create_if_missing: config.create_if_missing,
             ..Default::default()
         };
 
+        if let Some(write_buffer_bytes) = config.write_buffer_bytes {
+            opt.write_buffer_size = write_buffer_bytes;
+        }
+        if let Some(max_open_files) = config.max_open_files {
+            opt.max_open_files = max_open_files;
+        }
+        if let Some(block_cache_bytes) = config.block_cache_bytes {
+            opt.block_cache_capacity_bytes = block_cache_bytes;
+        }
+
         let db = AsyncDB::new(config.path, opt)?;
         Ok(Self::new(db, config.flush_strategy))
     }
@@ -69,6 +87,12 @@ impl LevelDB {
 
         Ok(Self::new(db, FlushStrategy::Disabled))
     }
+
+    /// Flush the written data to the disk, e.g. before the exit of a short-lived process, which
+    /// doesn't wait for the flush ticker.
+    pub async fn flush(&self) -> eyre::Result<()> {
+        Ok(self.0.flush().await?)
+    }
 }
 
 #[async_trait]
@@ -2,11 +2,17 @@
 mod traits;
 pub use traits::KeyValueError;
 pub use traits::{
-    BlockIndexerStorage, ChromaInfoStorage, FrozenTxsStorage, InvalidTxsStorage, InventoryStorage,
-    IsIndexedStorage, KeyValueResult, KeyValueStorage, PagesNumberStorage, PagesStorage,
-    TransactionsStorage,
+    BlockIndexerStorage, ChromaFreezeEntry, ChromaInfoStorage, FrozenTxsStorage, InvalidTxEntry,
+    InvalidTxsStorage, InventoryStorage, IsIndexedStorage, KeyValueResult, KeyValueStorage,
+    PagesNumberStorage, PagesStorage, TransactionsStorage,
 };
 
+mod consistency;
+pub use consistency::ConsistencyReport;
+
+mod snapshot;
+pub use snapshot::Snapshot;
+
 mod txstates;
 pub use txstates::{TxState, TxStatesStorage};
 
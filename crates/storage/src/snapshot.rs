@@ -0,0 +1,405 @@
+//! This module provides a [`Snapshot`] of the node's storages, which can be exported from a synced
+//! node and imported to a new one to skip the indexing of the history.
+
+use std::collections::BTreeSet;
+use std::io;
+
+use bitcoin::{BlockHash, OutPoint, Txid};
+use eyre::{bail, Context, OptionExt};
+use serde::{Deserialize, Serialize};
+use yuv_pixels::Chroma;
+use yuv_types::announcements::ChromaInfo;
+use yuv_types::{Announcement, YuvTransaction, YuvTxType};
+
+use crate::{
+    BlockIndexerStorage, ChromaFreezeEntry, ChromaInfoStorage, FrozenTxsStorage, IsIndexedStorage,
+    PagesStorage, TransactionsStorage,
+};
+
+/// Snapshot of the attached transactions and the state derived from them at the block with
+/// [`Snapshot::block_hash`].
+///
+/// Importing it lets a node to start indexing from that block instead of the YUV genesis one,
+/// so it's only as trustworthy as its source.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Snapshot {
+    /// Hash of the last indexed block at the moment of the export.
+    pub block_hash: BlockHash,
+    /// Attached transactions in the order of their pagination.
+    pub txs: Vec<YuvTransaction>,
+    /// Information about the issued and announced tokens.
+    pub chroma_infos: Vec<(Chroma, ChromaInfo)>,
+    /// Identifiers of the transactions that tried to freeze the outputs.
+    pub frozen_outputs: Vec<(OutPoint, Vec<Txid>)>,
+    /// Tokens which all outputs are frozen by the issuer.
+    #[serde(default)]
+    pub frozen_chromas: Vec<Chroma>,
+    /// Heights of the blocks the chroma announcements are indexed in.
+    #[serde(default)]
+    pub chroma_announcement_heights: Vec<(Chroma, u64)>,
+    /// Chroma-wide freezes of the tokens in the order they were applied.
+    #[serde(default)]
+    pub chroma_freezes: Vec<(Chroma, Vec<ChromaFreezeEntry>)>,
+    /// Heights of the blocks the freeze announcements are indexed in, so the freezes are active
+    /// when the frozen state is got as of the given height.
+    #[serde(default)]
+    pub freeze_heights: Vec<(Txid, u64)>,
+}
+
+impl Snapshot {
+    /// Export the snapshot of the node's storages at the last indexed block.
+    pub async fn export<TS, SS>(txs_storage: &TS, state_storage: &SS) -> eyre::Result<Self>
+    where
+        TS: TransactionsStorage + PagesStorage + Send + Sync,
+        SS: BlockIndexerStorage + ChromaInfoStorage + FrozenTxsStorage + Send + Sync,
+    {
+        let block_hash = state_storage
+            .get_last_indexed_hash()
+            .await?
+            .ok_or_eyre("Storage has no indexed blocks to export")?;
+
+        let pages_number = txs_storage.get_pages_number().await?.unwrap_or_default();
+
+        let mut txs = Vec::new();
+        let mut chromas = BTreeSet::new();
+        let mut freeze_outpoints = BTreeSet::new();
+
+        for page_num in 0..=pages_number {
+            let page = txs_storage
+                .get_page_by_num(page_num)
+                .await?
+                .unwrap_or_default();
+
+            for txid in page {
+                // Transactions could be pruned by the node.
+                let Some(tx) = txs_storage.get_yuv_tx(&txid).await? else {
+                    continue;
+                };
+
+                match &tx.tx_type {
+                    YuvTxType::Issue { announcement, .. }
+                    | YuvTxType::Announcement(Announcement::Issue(announcement)) => {
+                        chromas.insert(announcement.chroma);
+                    }
+                    YuvTxType::Announcement(Announcement::Chroma(announcement)) => {
+                        chromas.insert(announcement.chroma);
+                    }
+                    YuvTxType::Announcement(Announcement::ChromaFreeze(announcement)) => {
+                        chromas.insert(announcement.chroma);
+                    }
+                    YuvTxType::Announcement(Announcement::Freeze(announcement)) => {
+                        freeze_outpoints.insert(announcement.outpoint);
+                    }
+                    YuvTxType::Transfer { .. } => {}
+                }
+
+                txs.push(tx);
+            }
+        }
+
+        let mut chroma_infos = Vec::new();
+        let mut frozen_chromas = Vec::new();
+        let mut chroma_announcement_heights = Vec::new();
+        let mut chroma_freezes = Vec::new();
+        let mut freeze_txids = BTreeSet::new();
+        for chroma in chromas {
+            if let Some(chroma_info) = state_storage.get_chroma_info(&chroma).await? {
+                chroma_infos.push((chroma, chroma_info));
+            }
+
+            if state_storage.is_chroma_frozen(&chroma).await? {
+                frozen_chromas.push(chroma);
+            }
+
+            if let Some(height) = state_storage
+                .get_chroma_announcement_height(&chroma)
+                .await?
+            {
+                chroma_announcement_heights.push((chroma, height));
+            }
+
+            let entries = state_storage.get_chroma_freezes(&chroma).await?;
+            if !entries.is_empty() {
+                freeze_txids.extend(entries.iter().map(|entry| entry.txid));
+                chroma_freezes.push((chroma, entries));
+            }
+        }
+
+        let mut frozen_outputs = Vec::new();
+        for outpoint in freeze_outpoints {
+            if let Some(entry) = state_storage.get_frozen_tx(&outpoint).await? {
+                freeze_txids.extend(entry.tx_ids.iter().copied());
+                frozen_outputs.push((outpoint, entry.tx_ids));
+            }
+        }
+
+        let mut freeze_heights = Vec::new();
+        for txid in freeze_txids {
+            if let Some(height) = state_storage.get_freeze_height(&txid).await? {
+                freeze_heights.push((txid, height));
+            }
+        }
+
+        Ok(Self {
+            block_hash,
+            txs,
+            chroma_infos,
+            frozen_outputs,
+            frozen_chromas,
+            chroma_announcement_heights,
+            chroma_freezes,
+            freeze_heights,
+        })
+    }
+
+    /// Import the snapshot to the empty node's storages, paginating the transactions by
+    /// `tx_per_page`. The indexing will be continued from the next to [`Snapshot::block_hash`]
+    /// block.
+    ///
+    /// It's up to the caller to check that the block is in the current chain.
+    pub async fn import<TS, SS>(
+        self,
+        txs_storage: &TS,
+        state_storage: &SS,
+        tx_per_page: u64,
+    ) -> eyre::Result<()>
+    where
+        TS: TransactionsStorage + PagesStorage + Send + Sync,
+        SS: BlockIndexerStorage
+            + IsIndexedStorage
+            + ChromaInfoStorage
+            + FrozenTxsStorage
+            + Send
+            + Sync,
+    {
+        if state_storage.get_last_indexed_hash().await?.is_some()
+            || txs_storage.get_pages_number().await?.is_some()
+        {
+            bail!("Snapshot can be imported only to an empty storage");
+        }
+
+        if tx_per_page == 0 {
+            bail!("Number of transactions per page must be positive");
+        }
+
+        let mut pages_number = None;
+        for (page_num, txs) in self.txs.chunks(tx_per_page as usize).enumerate() {
+            let mut page = Vec::with_capacity(txs.len());
+
+            for tx in txs {
+                page.push(tx.bitcoin_tx.txid());
+                txs_storage.put_yuv_tx(tx.clone()).await?;
+            }
+
+            txs_storage.put_page(page_num as u64, page).await?;
+            pages_number = Some(page_num as u64);
+        }
+
+        if let Some(pages_number) = pages_number {
+            txs_storage.put_pages_number(pages_number).await?;
+        }
+        txs_storage.put_page_size(tx_per_page).await?;
+
+        for (chroma, chroma_info) in self.chroma_infos {
+            state_storage
+                .put_chroma_info(
+                    &chroma,
+                    chroma_info.announcement,
+                    chroma_info.announcement_txid,
+                    chroma_info.total_supply,
+                )
+                .await?;
+        }
+
+        for (outpoint, freeze_txids) in self.frozen_outputs {
+            state_storage.put_frozen_tx(&outpoint, freeze_txids).await?;
+        }
+
+        for chroma in self.frozen_chromas {
+            state_storage.put_chroma_frozen(&chroma, true).await?;
+        }
+
+        for (chroma, height) in self.chroma_announcement_heights {
+            state_storage
+                .put_chroma_announcement_height(&chroma, height)
+                .await?;
+        }
+
+        for (chroma, entries) in self.chroma_freezes {
+            state_storage.put_chroma_freezes(&chroma, entries).await?;
+        }
+
+        for (txid, height) in self.freeze_heights {
+            state_storage.put_freeze_height(&txid, height).await?;
+        }
+
+        // The last indexed block is put the last, so the node doesn't resume indexing from the
+        // block of a partially imported snapshot.
+        state_storage.put_is_indexed().await?;
+        state_storage.put_last_indexed_hash(self.block_hash).await?;
+
+        Ok(())
+    }
+
+    /// Write the snapshot encoded in CBOR.
+    pub fn write_to(&self, writer: impl io::Write) -> eyre::Result<()> {
+        ciborium::into_writer(self, writer).wrap_err("failed to encode snapshot")
+    }
+
+    /// Read the snapshot encoded in CBOR.
+    pub fn read_from(reader: impl io::Read) -> eyre::Result<Self> {
+        ciborium::from_reader(reader).wrap_err("failed to decode snapshot")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bitcoin::{hashes::Hash, secp256k1};
+    use yuv_types::{
+        announcements::{ChromaFreezeAnnouncement, FreezeAnnouncement},
+        mocks,
+    };
+
+    use super::*;
+    use crate::{LevelDB, PagesNumberStorage};
+
+    const TX_PER_PAGE: u64 = 2;
+    const FREEZE_HEIGHT: u64 = 60;
+
+    /// Fill the storages as if the node has indexed an issuance, a transfer, a freeze of its
+    /// output and a freeze of the whole token at [`FREEZE_HEIGHT`].
+    async fn synced_storages() -> eyre::Result<(LevelDB, LevelDB)> {
+        let txs_storage = LevelDB::in_memory()?;
+        let state_storage = LevelDB::in_memory()?;
+
+        let issuer = secp256k1::SecretKey::from_slice(&[1; 32])?;
+        let pubkey = issuer.public_key(&secp256k1::Secp256k1::new());
+        let chroma = Chroma::from(pubkey.x_only_public_key().0);
+
+        let issuance = mocks::issuance(&issuer, 100, pubkey);
+        let transfer = mocks::yuv_tx(1);
+        let freeze_outpoint = OutPoint::new(transfer.bitcoin_tx.txid(), 0);
+        let freeze = YuvTransaction::new(
+            mocks::yuv_tx(2).bitcoin_tx,
+            YuvTxType::Announcement(FreezeAnnouncement::new(freeze_outpoint).into()),
+        );
+        let chroma_freeze = YuvTransaction::new(
+            mocks::yuv_tx(3).bitcoin_tx,
+            YuvTxType::Announcement(ChromaFreezeAnnouncement::freeze(chroma).into()),
+        );
+
+        let txids = [&issuance, &transfer, &freeze, &chroma_freeze].map(|tx| tx.bitcoin_tx.txid());
+        for tx in [issuance, transfer, freeze, chroma_freeze] {
+            txs_storage.put_yuv_tx(tx).await?;
+        }
+        txs_storage.put_page(0, txids[..2].to_vec()).await?;
+        txs_storage.put_page(1, txids[2..].to_vec()).await?;
+        txs_storage.put_pages_number(1).await?;
+
+        state_storage
+            .put_chroma_info(&chroma, None, None, 100)
+            .await?;
+        state_storage.put_chroma_frozen(&chroma, true).await?;
+        state_storage
+            .put_chroma_announcement_height(&chroma, 50)
+            .await?;
+        state_storage
+            .put_frozen_tx(&freeze_outpoint, vec![txids[2]])
+            .await?;
+        state_storage
+            .push_chroma_freeze(
+                &chroma,
+                ChromaFreezeEntry {
+                    txid: txids[3],
+                    frozen: true,
+                },
+            )
+            .await?;
+        for txid in &txids[2..] {
+            state_storage.put_freeze_height(txid, FREEZE_HEIGHT).await?;
+        }
+        state_storage
+            .put_last_indexed_hash(BlockHash::all_zeros())
+            .await?;
+
+        Ok((txs_storage, state_storage))
+    }
+
+    #[test]
+    fn test_snapshot_round_trip() -> eyre::Result<()> {
+        tokio_test::block_on(async {
+            let (txs_storage, state_storage) = synced_storages().await?;
+
+            let snapshot = Snapshot::export(&txs_storage, &state_storage).await?;
+            assert_eq!(snapshot.txs.len(), 4);
+            assert_eq!(snapshot.chroma_infos.len(), 1);
+            assert_eq!(snapshot.frozen_outputs.len(), 1);
+            assert_eq!(snapshot.frozen_chromas.len(), 1);
+            assert_eq!(snapshot.chroma_announcement_heights.len(), 1);
+            assert_eq!(snapshot.chroma_freezes.len(), 1);
+            assert_eq!(snapshot.freeze_heights.len(), 2);
+
+            let mut bytes = Vec::new();
+            snapshot.write_to(&mut bytes)?;
+            let decoded = Snapshot::read_from(bytes.as_slice())?;
+            assert_eq!(decoded, snapshot);
+
+            let (new_txs_storage, new_state_storage) =
+                (LevelDB::in_memory()?, LevelDB::in_memory()?);
+            decoded
+                .clone()
+                .import(&new_txs_storage, &new_state_storage, TX_PER_PAGE)
+                .await?;
+
+            assert_eq!(
+                Snapshot::export(&new_txs_storage, &new_state_storage).await?,
+                snapshot
+            );
+            assert_eq!(new_state_storage.get_is_indexed().await?, Some(()));
+
+            // The freezes are active since the height of their block.
+            let (frozen_outpoint, _) = snapshot.frozen_outputs[0];
+            let (chroma, _) = snapshot.chroma_infos[0];
+            assert!(
+                new_state_storage
+                    .is_frozen_at(&frozen_outpoint, FREEZE_HEIGHT)
+                    .await?
+            );
+            assert!(
+                !new_state_storage
+                    .is_frozen_at(&frozen_outpoint, FREEZE_HEIGHT - 1)
+                    .await?
+            );
+            assert!(
+                new_state_storage
+                    .is_chroma_frozen_at(&chroma, FREEZE_HEIGHT)
+                    .await?
+            );
+            assert!(
+                !new_state_storage
+                    .is_chroma_frozen_at(&chroma, FREEZE_HEIGHT - 1)
+                    .await?
+            );
+            assert_eq!(new_txs_storage.get_page_size().await?, Some(TX_PER_PAGE));
+
+            // The storages are not empty anymore.
+            assert!(decoded
+                .import(&new_txs_storage, &new_state_storage, TX_PER_PAGE)
+                .await
+                .is_err());
+
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn test_empty_storage_is_not_exported() -> eyre::Result<()> {
+        tokio_test::block_on(async {
+            let storage = LevelDB::in_memory()?;
+
+            assert!(Snapshot::export(&storage, &storage).await.is_err());
+
+            Ok(())
+        })
+    }
+}
@@ -1,8 +1,10 @@
 use async_trait::async_trait;
+use bitcoin::Txid;
 
 use serde_bytes::ByteArray;
 use yuv_pixels::{Chroma, CHROMA_SIZE};
 use yuv_types::announcements::{ChromaAnnouncement, ChromaInfo};
+use yuv_types::YuvTransaction;
 
 use crate::{KeyValueResult, KeyValueStorage};
 
@@ -20,31 +22,167 @@ fn get_storage_key(chroma: &Chroma) -> ByteArray<KEY_SIZE> {
     ByteArray::new(bytes)
 }
 
+const PENDING_KEY_PREFIX: &str = "chrmp-";
+const PENDING_KEY_PREFIX_SIZE: usize = PENDING_KEY_PREFIX.len();
+
+const PENDING_KEY_SIZE: usize = PENDING_KEY_PREFIX_SIZE + CHROMA_SIZE;
+
+fn get_pending_issuances_key(chroma: &Chroma) -> ByteArray<PENDING_KEY_SIZE> {
+    let mut bytes = [0u8; PENDING_KEY_SIZE];
+
+    bytes[..PENDING_KEY_PREFIX_SIZE].copy_from_slice(PENDING_KEY_PREFIX.as_bytes());
+    bytes[PENDING_KEY_PREFIX_SIZE..].copy_from_slice(&chroma.to_bytes());
+
+    ByteArray::new(bytes)
+}
+
+const FROZEN_KEY_PREFIX: &str = "chrmf-";
+const FROZEN_KEY_PREFIX_SIZE: usize = FROZEN_KEY_PREFIX.len();
+
+const FROZEN_KEY_SIZE: usize = FROZEN_KEY_PREFIX_SIZE + CHROMA_SIZE;
+
+fn get_frozen_key(chroma: &Chroma) -> ByteArray<FROZEN_KEY_SIZE> {
+    let mut bytes = [0u8; FROZEN_KEY_SIZE];
+
+    bytes[..FROZEN_KEY_PREFIX_SIZE].copy_from_slice(FROZEN_KEY_PREFIX.as_bytes());
+    bytes[FROZEN_KEY_PREFIX_SIZE..].copy_from_slice(&chroma.to_bytes());
+
+    ByteArray::new(bytes)
+}
+
+const HEIGHT_KEY_PREFIX: &str = "chrmh-";
+const HEIGHT_KEY_PREFIX_SIZE: usize = HEIGHT_KEY_PREFIX.len();
+
+const HEIGHT_KEY_SIZE: usize = HEIGHT_KEY_PREFIX_SIZE + CHROMA_SIZE;
+
+fn get_height_key(chroma: &Chroma) -> ByteArray<HEIGHT_KEY_SIZE> {
+    let mut bytes = [0u8; HEIGHT_KEY_SIZE];
+
+    bytes[..HEIGHT_KEY_PREFIX_SIZE].copy_from_slice(HEIGHT_KEY_PREFIX.as_bytes());
+    bytes[HEIGHT_KEY_PREFIX_SIZE..].copy_from_slice(&chroma.to_bytes());
+
+    ByteArray::new(bytes)
+}
+
 /// It is a key-value storage for the [`ChromaAnnouncement`] and total supply.
 ///
 /// - key: `b"chrm-"` + [`Chroma`]
 /// - value: [`ChromaInfo`][`ChromaAnnouncement`]
+///
+/// Also, it stores the issuances that wait for the [`ChromaAnnouncement`] of
+/// their [`Chroma`]:
+///
+/// - key: `b"chrmp-"` + [`Chroma`]
+/// - value: list of [`YuvTransaction`]
+///
+/// And whether all the outputs of the [`Chroma`] are frozen by the
+/// [`ChromaFreezeAnnouncement`]:
+///
+/// - key: `b"chrmf-"` + [`Chroma`]
+/// - value: `bool`
+///
+/// And the height of the block the [`ChromaAnnouncement`] is indexed in:
+///
+/// - key: `b"chrmh-"` + [`Chroma`]
+/// - value: `u64`
+///
+/// [`ChromaFreezeAnnouncement`]: yuv_types::announcements::ChromaFreezeAnnouncement
 #[async_trait]
-pub trait ChromaInfoStorage: KeyValueStorage<ByteArray<KEY_SIZE>, ChromaInfo> {
+pub trait ChromaInfoStorage:
+    KeyValueStorage<ByteArray<KEY_SIZE>, ChromaInfo>
+    + KeyValueStorage<ByteArray<PENDING_KEY_SIZE>, Vec<YuvTransaction>>
+    + KeyValueStorage<ByteArray<FROZEN_KEY_SIZE>, bool>
+    + KeyValueStorage<ByteArray<HEIGHT_KEY_SIZE>, u64>
+{
     /// Get the [`ChromaAnnouncement`] for the given [`Chroma`].
     async fn get_chroma_info(&self, chroma: &Chroma) -> KeyValueResult<Option<ChromaInfo>> {
         self.get(get_storage_key(chroma)).await
     }
 
-    /// Put the [`ChromaAnnouncement`] for the given [`Chroma`].
+    /// Put the [`ChromaAnnouncement`] with the id of its transaction for the given [`Chroma`].
     async fn put_chroma_info(
         &self,
         chroma: &Chroma,
         announcement: Option<ChromaAnnouncement>,
+        announcement_txid: Option<Txid>,
         total_supply: u128,
     ) -> KeyValueResult<()> {
         self.put(
             get_storage_key(chroma),
             ChromaInfo {
                 announcement,
+                announcement_txid,
                 total_supply,
             },
         )
         .await
     }
+
+    /// Put the issuance that waits for the [`ChromaAnnouncement`] of the given
+    /// [`Chroma`].
+    async fn put_pending_issuance(
+        &self,
+        chroma: &Chroma,
+        tx: YuvTransaction,
+    ) -> KeyValueResult<()> {
+        let key = get_pending_issuances_key(chroma);
+
+        let mut txs: Vec<YuvTransaction> = self.get(key).await?.unwrap_or_default();
+        if txs
+            .iter()
+            .any(|pending| pending.bitcoin_tx.txid() == tx.bitcoin_tx.txid())
+        {
+            return Ok(());
+        }
+        txs.push(tx);
+
+        self.put(key, txs).await
+    }
+
+    /// Remove and return the issuances that wait for the [`ChromaAnnouncement`]
+    /// of the given [`Chroma`].
+    async fn take_pending_issuances(&self, chroma: &Chroma) -> KeyValueResult<Vec<YuvTransaction>> {
+        let key = get_pending_issuances_key(chroma);
+
+        let txs: Option<Vec<YuvTransaction>> = self.get(key).await?;
+        if txs.is_some() {
+            KeyValueStorage::<_, Vec<YuvTransaction>>::delete(self, key).await?;
+        }
+
+        Ok(txs.unwrap_or_default())
+    }
+
+    /// Check if all the outputs of the given [`Chroma`] are frozen by the issuer.
+    async fn is_chroma_frozen(&self, chroma: &Chroma) -> KeyValueResult<bool> {
+        let frozen: Option<bool> = self.get(get_frozen_key(chroma)).await?;
+
+        Ok(frozen.unwrap_or_default())
+    }
+
+    /// Set whether all the outputs of the given [`Chroma`] are frozen.
+    async fn put_chroma_frozen(&self, chroma: &Chroma, frozen: bool) -> KeyValueResult<()> {
+        let key = get_frozen_key(chroma);
+
+        if frozen {
+            self.put(key, true).await
+        } else {
+            KeyValueStorage::<_, bool>::delete(self, key).await
+        }
+    }
+
+    /// Get the height of the block the [`ChromaAnnouncement`] of the given [`Chroma`] is
+    /// indexed in. `None` if the announcement isn't indexed yet.
+    async fn get_chroma_announcement_height(&self, chroma: &Chroma) -> KeyValueResult<Option<u64>> {
+        self.get(get_height_key(chroma)).await
+    }
+
+    /// Put the height of the block the [`ChromaAnnouncement`] of the given [`Chroma`] is
+    /// indexed in.
+    async fn put_chroma_announcement_height(
+        &self,
+        chroma: &Chroma,
+        height: u64,
+    ) -> KeyValueResult<()> {
+        self.put(get_height_key(chroma), height).await
+    }
 }
@@ -0,0 +1,128 @@
+//! Encoding of the values that could be compressed before they are put to the storage, like the
+//! transactions with many proofs.
+//!
+//! With the `compression` feature, the encoded value is prefixed with a one-byte header which tells
+//! whether the CBOR after it is compressed with zstd or not. Headers are the bytes that are
+//! reserved in CBOR, so no encoded value starts with them, and the values put without a header
+//! (before the feature was enabled) are still read as plain CBOR.
+
+use serde::{de::DeserializeOwned, Serialize};
+
+use super::{cbor_from_slice, cbor_to_vec, KeyValueError, KeyValueResult};
+
+/// Header of the CBOR encoded value that is stored as is, as the compression wouldn't make it
+/// smaller.
+const RAW_HEADER: u8 = 0x1c;
+/// Header of the CBOR encoded value that is compressed with zstd.
+const ZSTD_HEADER: u8 = 0x1d;
+
+/// Encode the value to CBOR, and compress it if the `compression` feature is enabled and it
+/// makes the value smaller.
+#[cfg(feature = "compression")]
+pub(crate) fn encode_value<V: Serialize>(value: &V) -> KeyValueResult<Vec<u8>> {
+    let cbor = cbor_to_vec(value)?;
+
+    let compressed = zstd::bulk::compress(&cbor, zstd::DEFAULT_COMPRESSION_LEVEL)
+        .map_err(KeyValueError::Compression)?;
+
+    if compressed.len() < cbor.len() {
+        let mut value = Vec::with_capacity(compressed.len() + 1);
+        value.push(ZSTD_HEADER);
+        value.extend_from_slice(&compressed);
+
+        return Ok(value);
+    }
+
+    let mut raw = Vec::with_capacity(cbor.len() + 1);
+    raw.push(RAW_HEADER);
+    raw.extend_from_slice(&cbor);
+
+    Ok(raw)
+}
+
+/// Encode the value to CBOR without a header, so it can be read by the nodes without the
+/// `compression` feature.
+#[cfg(not(feature = "compression"))]
+pub(crate) fn encode_value<V: Serialize>(value: &V) -> KeyValueResult<Vec<u8>> {
+    Ok(cbor_to_vec(value)?)
+}
+
+/// Decode the value that is put with or without the header.
+pub(crate) fn decode_value<V: DeserializeOwned>(bytes: &[u8]) -> KeyValueResult<V> {
+    match bytes.split_first() {
+        Some((&RAW_HEADER, cbor)) => Ok(cbor_from_slice(cbor)?),
+        Some((&ZSTD_HEADER, compressed)) => {
+            let cbor = decompress(compressed)?;
+
+            Ok(cbor_from_slice(&cbor)?)
+        }
+        _ => Ok(cbor_from_slice(bytes)?),
+    }
+}
+
+#[cfg(feature = "compression")]
+fn decompress(compressed: &[u8]) -> KeyValueResult<Vec<u8>> {
+    zstd::decode_all(compressed).map_err(KeyValueError::Compression)
+}
+
+#[cfg(not(feature = "compression"))]
+fn decompress(_compressed: &[u8]) -> KeyValueResult<Vec<u8>> {
+    use std::io;
+
+    Err(KeyValueError::Compression(io::Error::new(
+        io::ErrorKind::Unsupported,
+        "value is compressed, but the storage is built without `compression` feature",
+    )))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+    struct Value {
+        bytes: Vec<u8>,
+    }
+
+    #[test]
+    fn test_value_without_header_is_decoded() {
+        let value = Value {
+            bytes: vec![7; 100],
+        };
+
+        let cbor = cbor_to_vec(&value).unwrap();
+        assert!(
+            cbor[0] != RAW_HEADER && cbor[0] != ZSTD_HEADER,
+            "CBOR mustn't start with the header"
+        );
+
+        assert_eq!(decode_value::<Value>(&cbor).unwrap(), value);
+    }
+
+    #[test]
+    fn test_encoded_value_round_trip() {
+        for len in [0, 1000] {
+            let value = Value {
+                bytes: vec![7; len],
+            };
+
+            let bytes = encode_value(&value).unwrap();
+
+            assert_eq!(decode_value::<Value>(&bytes).unwrap(), value);
+        }
+    }
+
+    #[cfg(feature = "compression")]
+    #[test]
+    fn test_value_is_compressed_only_if_smaller() {
+        let small = encode_value(&Value { bytes: vec![] }).unwrap();
+        assert_eq!(small[0], RAW_HEADER);
+
+        let big = encode_value(&Value {
+            bytes: vec![7; 1000],
+        })
+        .unwrap();
+        assert_eq!(big[0], ZSTD_HEADER);
+        assert!(big.len() < 1000);
+    }
+}
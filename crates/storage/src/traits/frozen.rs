@@ -3,6 +3,7 @@ use std::mem::size_of;
 use async_trait::async_trait;
 use bitcoin::{OutPoint, Txid};
 use serde_bytes::ByteArray;
+use yuv_pixels::{Chroma, CHROMA_SIZE};
 
 use crate::{KeyValueResult, KeyValueStorage};
 
@@ -25,9 +26,54 @@ fn frozen_tx_storage_key(outpoint: &OutPoint) -> ByteArray<FROZEN_TX_STORAGE_KEY
     ByteArray::new(bytes)
 }
 
+const HEIGHT_KEY_PREFIX: &str = "frzh-";
+const HEIGHT_KEY_PREFIX_SIZE: usize = HEIGHT_KEY_PREFIX.len();
+
+/// Freeze height storage key size is:
+///
+/// 5 bytes (`HEIGHT_KEY_PREFIX`) + 32 bytes (`txid`) = 37 bytes long
+const FREEZE_HEIGHT_KEY_SIZE: usize = HEIGHT_KEY_PREFIX_SIZE + TXID_SIZE;
+
+fn freeze_height_key(txid: &Txid) -> ByteArray<FREEZE_HEIGHT_KEY_SIZE> {
+    let mut bytes = [0u8; FREEZE_HEIGHT_KEY_SIZE];
+
+    bytes[..HEIGHT_KEY_PREFIX_SIZE].copy_from_slice(HEIGHT_KEY_PREFIX.as_bytes());
+    bytes[HEIGHT_KEY_PREFIX_SIZE..].copy_from_slice(txid);
+
+    ByteArray::new(bytes)
+}
+
+const CHROMA_KEY_PREFIX: &str = "frzc-";
+const CHROMA_KEY_PREFIX_SIZE: usize = CHROMA_KEY_PREFIX.len();
+
+/// Chroma freezes storage key size is:
+///
+/// 5 bytes (`CHROMA_KEY_PREFIX`) + 32 bytes (`chroma`) = 37 bytes long
+const CHROMA_FREEZES_KEY_SIZE: usize = CHROMA_KEY_PREFIX_SIZE + CHROMA_SIZE;
+
+fn chroma_freezes_key(chroma: &Chroma) -> ByteArray<CHROMA_FREEZES_KEY_SIZE> {
+    let mut bytes = [0u8; CHROMA_FREEZES_KEY_SIZE];
+
+    bytes[..CHROMA_KEY_PREFIX_SIZE].copy_from_slice(CHROMA_KEY_PREFIX.as_bytes());
+    bytes[CHROMA_KEY_PREFIX_SIZE..].copy_from_slice(&chroma.to_bytes());
+
+    ByteArray::new(bytes)
+}
+
+/// Storage of the freezes of the outputs:
+///
+/// - key: `b"frz-"` + [`OutPoint`], value: [`TxFreezesEntry`] with the freezes of the output.
+///
+/// To get the frozen state at the given height, it also stores:
+///
+/// - key: `b"frzh-"` + [`Txid`], value: height of the block with the freeze announcement.
+/// - key: `b"frzc-"` + [`Chroma`], value: [`ChromaFreezeEntry`]s of the chroma-wide freezes in
+///   the order they were applied.
 #[async_trait]
 pub trait FrozenTxsStorage:
     KeyValueStorage<ByteArray<FROZEN_TX_STORAGE_KEY_SIZE>, TxFreezesEntry>
+    + KeyValueStorage<ByteArray<FREEZE_HEIGHT_KEY_SIZE>, u64>
+    + KeyValueStorage<ByteArray<CHROMA_FREEZES_KEY_SIZE>, Vec<ChromaFreezeEntry>>
 {
     async fn get_frozen_tx(&self, outpoint: &OutPoint) -> KeyValueResult<Option<TxFreezesEntry>> {
         self.get(frozen_tx_storage_key(outpoint)).await
@@ -46,8 +92,101 @@ pub trait FrozenTxsStorage:
     }
 
     async fn delete_frozen_tx(&self, outpoint: &OutPoint) -> KeyValueResult<()> {
-        self.delete(frozen_tx_storage_key(outpoint)).await
+        KeyValueStorage::<_, TxFreezesEntry>::delete(self, frozen_tx_storage_key(outpoint)).await
+    }
+
+    /// Get the height of the block with the freeze announcement. `None` if the announcement
+    /// wasn't indexed since the heights are stored.
+    async fn get_freeze_height(&self, txid: &Txid) -> KeyValueResult<Option<u64>> {
+        self.get(freeze_height_key(txid)).await
+    }
+
+    async fn put_freeze_height(&self, txid: &Txid, height: u64) -> KeyValueResult<()> {
+        self.put(freeze_height_key(txid), height).await
+    }
+
+    async fn delete_freeze_height(&self, txid: &Txid) -> KeyValueResult<()> {
+        KeyValueStorage::<_, u64>::delete(self, freeze_height_key(txid)).await
+    }
+
+    /// Get the chroma-wide freezes of the [`Chroma`] in the order they were applied.
+    async fn get_chroma_freezes(&self, chroma: &Chroma) -> KeyValueResult<Vec<ChromaFreezeEntry>> {
+        let entries: Option<Vec<ChromaFreezeEntry>> = self.get(chroma_freezes_key(chroma)).await?;
+
+        Ok(entries.unwrap_or_default())
     }
+
+    /// Append the chroma-wide freeze of the [`Chroma`] to the applied ones.
+    async fn push_chroma_freeze(
+        &self,
+        chroma: &Chroma,
+        entry: ChromaFreezeEntry,
+    ) -> KeyValueResult<()> {
+        let mut entries = self.get_chroma_freezes(chroma).await?;
+        entries.push(entry);
+
+        self.put(chroma_freezes_key(chroma), entries).await
+    }
+
+    /// Replace the chroma-wide freezes of the [`Chroma`], e.g. after some of them are reverted.
+    async fn put_chroma_freezes(
+        &self,
+        chroma: &Chroma,
+        entries: Vec<ChromaFreezeEntry>,
+    ) -> KeyValueResult<()> {
+        self.put(chroma_freezes_key(chroma), entries).await
+    }
+
+    /// Check if the output was frozen by itself at the given height, counting only the freezes
+    /// announced at or before it.
+    ///
+    /// It's best-effort: if the height of any freeze is unknown (e.g. applied before the heights
+    /// are stored), the history can't be restored, so the current state is returned.
+    async fn is_frozen_at(&self, outpoint: &OutPoint, height: u64) -> KeyValueResult<bool> {
+        let Some(entry) = self.get_frozen_tx(outpoint).await? else {
+            return Ok(false);
+        };
+
+        let mut freezes = 0;
+        for txid in &entry.tx_ids {
+            match self.get_freeze_height(txid).await? {
+                Some(freeze_height) if freeze_height <= height => freezes += 1,
+                Some(_) => {}
+                None => return Ok(entry.is_frozen()),
+            }
+        }
+
+        Ok(freezes % 2 == 1)
+    }
+
+    /// Check if all the outputs of the [`Chroma`] were frozen at the given height by the last
+    /// chroma-wide freeze announced at or before it.
+    ///
+    /// It's best-effort the same way as [`Self::is_frozen_at`]: the current state is returned if
+    /// the height of any freeze is unknown.
+    async fn is_chroma_frozen_at(&self, chroma: &Chroma, height: u64) -> KeyValueResult<bool> {
+        let entries = self.get_chroma_freezes(chroma).await?;
+
+        let mut frozen = false;
+        for entry in &entries {
+            match self.get_freeze_height(&entry.txid).await? {
+                Some(freeze_height) if freeze_height <= height => frozen = entry.frozen,
+                Some(_) => {}
+                None => return Ok(entries.last().is_some_and(|last| last.frozen)),
+            }
+        }
+
+        Ok(frozen)
+    }
+}
+
+/// Chroma-wide freeze applied to all the outputs of the token.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
+pub struct ChromaFreezeEntry {
+    /// Identifier of the freeze announcement transaction.
+    pub txid: Txid,
+    /// Whether the outputs are frozen by the announcement.
+    pub frozen: bool,
 }
 
 /// Storage entry that stores the transaction identifiers that tried to freeze the output.
@@ -69,3 +208,76 @@ impl TxFreezesEntry {
         self.tx_ids.len() % 2 == 1
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use bitcoin::{hashes::Hash, XOnlyPublicKey};
+
+    use super::*;
+    use crate::LevelDB;
+
+    #[test]
+    fn test_frozen_state_at_height() {
+        tokio_test::block_on(async {
+            let storage = LevelDB::in_memory().unwrap();
+
+            let outpoint = OutPoint::new(Txid::from_inner([1; 32]), 0);
+            let freezes = [2, 3, 4].map(|byte| Txid::from_inner([byte; 32]));
+
+            storage.put_freeze_height(&freezes[0], 100).await.unwrap();
+            storage.put_freeze_height(&freezes[1], 200).await.unwrap();
+            storage
+                .put_frozen_tx(&outpoint, freezes[..2].to_vec())
+                .await
+                .unwrap();
+
+            assert!(!storage.is_frozen_at(&outpoint, 99).await.unwrap());
+            assert!(storage.is_frozen_at(&outpoint, 100).await.unwrap());
+            assert!(!storage.is_frozen_at(&outpoint, 200).await.unwrap());
+
+            // Height of the last freeze is unknown, so the current state is returned.
+            storage
+                .put_frozen_tx(&outpoint, freezes.to_vec())
+                .await
+                .unwrap();
+
+            assert!(storage.is_frozen_at(&outpoint, 99).await.unwrap());
+            assert!(storage.is_frozen_at(&outpoint, 200).await.unwrap());
+
+            let chroma = Chroma::from(
+                XOnlyPublicKey::from_str(
+                    "5510996bdb5271f84896eb42ea5b6c4ba3bd96f90a605c70a7f2b402f0afdad0",
+                )
+                .unwrap(),
+            );
+
+            for (txid, frozen) in [(freezes[0], true), (freezes[1], false)] {
+                storage
+                    .push_chroma_freeze(&chroma, ChromaFreezeEntry { txid, frozen })
+                    .await
+                    .unwrap();
+            }
+
+            assert!(!storage.is_chroma_frozen_at(&chroma, 99).await.unwrap());
+            assert!(storage.is_chroma_frozen_at(&chroma, 150).await.unwrap());
+            assert!(!storage.is_chroma_frozen_at(&chroma, 200).await.unwrap());
+
+            // The last freeze with unknown height makes the current state returned.
+            storage
+                .push_chroma_freeze(
+                    &chroma,
+                    ChromaFreezeEntry {
+                        txid: freezes[2],
+                        frozen: true,
+                    },
+                )
+                .await
+                .unwrap();
+
+            assert!(storage.is_chroma_frozen_at(&chroma, 99).await.unwrap());
+            assert!(storage.is_chroma_frozen_at(&chroma, 200).await.unwrap());
+        });
+    }
+}
@@ -1,4 +1,5 @@
 use std::mem::size_of;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 use async_trait::async_trait;
 use bitcoin::Txid;
@@ -15,6 +16,31 @@ const KEY_PREFIX_SIZE: usize = KEY_PREFIX.len();
 /// 4 bytes (`INVALID_TXS_PREFIX`) + 32 bytes (`txid`) = 36 bytes long
 const INVALID_TXS_KEY_SIZE: usize = KEY_PREFIX_SIZE + size_of::<Txid>();
 
+const ENTRY_KEY_PREFIX: &str = "invr-";
+const ENTRY_KEY_PREFIX_SIZE: usize = ENTRY_KEY_PREFIX.len();
+
+/// Invalid transaction entry key size is:
+///
+/// 5 bytes (`ENTRY_KEY_PREFIX`) + 32 bytes (`txid`) = 37 bytes long
+const INVALID_TX_ENTRY_KEY_SIZE: usize = ENTRY_KEY_PREFIX_SIZE + size_of::<Txid>();
+
+/// The prefix that is used with the bucket number to store the ids of transactions rejected
+/// during the bucket's time span. "invb-1", "invb-2", etc.
+const BUCKET_KEY_PREFIX: &str = "invb-";
+const BUCKET_KEY_PREFIX_SIZE: usize = BUCKET_KEY_PREFIX.len();
+
+/// Expiry bucket key size is:
+///
+/// 5 bytes (`BUCKET_KEY_PREFIX`) + 8 bytes (`bucket number`) = 13 bytes long
+const EXPIRY_BUCKET_KEY_SIZE: usize = BUCKET_KEY_PREFIX_SIZE + size_of::<u64>();
+
+const EXPIRY_CURSOR_KEY_SIZE: usize = 17;
+/// The key where the number of the oldest bucket that is not expired yet is stored.
+const EXPIRY_CURSOR_KEY: &[u8; EXPIRY_CURSOR_KEY_SIZE] = b"inv-expiry-cursor";
+
+/// Time span of one expiry bucket in seconds.
+const EXPIRY_BUCKET_SPAN: u64 = 60;
+
 fn invalid_txs_storage_key(txid: Txid) -> ByteArray<INVALID_TXS_KEY_SIZE> {
     let mut bytes = [0u8; INVALID_TXS_KEY_SIZE];
 
@@ -24,28 +50,142 @@ fn invalid_txs_storage_key(txid: Txid) -> ByteArray<INVALID_TXS_KEY_SIZE> {
     ByteArray::new(bytes)
 }
 
+fn invalid_tx_entry_key(txid: Txid) -> ByteArray<INVALID_TX_ENTRY_KEY_SIZE> {
+    let mut bytes = [0u8; INVALID_TX_ENTRY_KEY_SIZE];
+
+    bytes[..ENTRY_KEY_PREFIX_SIZE].copy_from_slice(ENTRY_KEY_PREFIX.as_bytes());
+    bytes[ENTRY_KEY_PREFIX_SIZE..].copy_from_slice(&txid);
+
+    ByteArray::new(bytes)
+}
+
+fn expiry_bucket_key(bucket: u64) -> ByteArray<EXPIRY_BUCKET_KEY_SIZE> {
+    let mut bytes = [0u8; EXPIRY_BUCKET_KEY_SIZE];
+
+    bytes[..BUCKET_KEY_PREFIX_SIZE].copy_from_slice(BUCKET_KEY_PREFIX.as_bytes());
+    bytes[BUCKET_KEY_PREFIX_SIZE..].copy_from_slice(&bucket.to_be_bytes());
+
+    ByteArray::new(bytes)
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
+
+/// Storage of transactions that were rejected by the node.
+///
+/// Besides the transaction itself, the reason of rejection and its time are stored in a separate
+/// [`InvalidTxEntry`], so the lookup of the transaction stays a single read. Entries are indexed
+/// by the time of rejection, which allows to expire them without iterating over the storage.
 #[async_trait]
 pub trait InvalidTxsStorage:
     KeyValueStorage<ByteArray<INVALID_TXS_KEY_SIZE>, YuvTransaction>
+    + KeyValueStorage<ByteArray<INVALID_TX_ENTRY_KEY_SIZE>, InvalidTxEntry>
+    + KeyValueStorage<ByteArray<EXPIRY_BUCKET_KEY_SIZE>, Vec<Txid>>
+    + KeyValueStorage<[u8; EXPIRY_CURSOR_KEY_SIZE], u64>
 {
     async fn get_invalid_tx(&self, txid: Txid) -> KeyValueResult<Option<YuvTransaction>> {
         self.get(invalid_txs_storage_key(txid)).await
     }
 
+    async fn get_invalid_tx_entry(&self, txid: Txid) -> KeyValueResult<Option<InvalidTxEntry>> {
+        self.get(invalid_tx_entry_key(txid)).await
+    }
+
     async fn put_invalid_tx(&self, tx: YuvTransaction) -> KeyValueResult<()> {
         self.put(invalid_txs_storage_key(tx.bitcoin_tx.txid()), tx)
             .await
     }
 
-    async fn put_invalid_txs(&self, txs: Vec<YuvTransaction>) -> KeyValueResult<()> {
-        for tx in txs {
-            self.put_invalid_tx(tx).await?;
+    /// Put the rejected transaction along with the reason of rejection, and schedule it for
+    /// expiration.
+    async fn put_rejected_tx(&self, tx: YuvTransaction, reason: String) -> KeyValueResult<()> {
+        let txid = tx.bitcoin_tx.txid();
+        let rejected_at = unix_now();
+        let bucket = rejected_at / EXPIRY_BUCKET_SPAN;
+
+        self.put_invalid_tx(tx).await?;
+        self.put(
+            invalid_tx_entry_key(txid),
+            InvalidTxEntry {
+                reason,
+                rejected_at,
+            },
+        )
+        .await?;
+
+        let mut bucket_txids: Vec<Txid> = self
+            .get(expiry_bucket_key(bucket))
+            .await?
+            .unwrap_or_default();
+        bucket_txids.push(txid);
+        self.put(expiry_bucket_key(bucket), bucket_txids).await?;
+
+        if self.get(*EXPIRY_CURSOR_KEY).await?.is_none() {
+            self.put(*EXPIRY_CURSOR_KEY, bucket).await?;
+        }
+
+        Ok(())
+    }
+
+    async fn put_invalid_txs(&self, txs: Vec<(YuvTransaction, String)>) -> KeyValueResult<()> {
+        for (tx, reason) in txs {
+            self.put_rejected_tx(tx, reason).await?;
         }
 
         Ok(())
     }
 
     async fn delete_invalid_tx(&self, txid: Txid) -> KeyValueResult<()> {
-        self.delete(invalid_txs_storage_key(txid)).await
+        KeyValueStorage::<_, InvalidTxEntry>::delete(self, invalid_tx_entry_key(txid)).await?;
+        KeyValueStorage::<_, YuvTransaction>::delete(self, invalid_txs_storage_key(txid)).await
+    }
+
+    /// Delete the invalid transactions that were rejected more than `max_age` ago.
+    ///
+    /// Returns the number of deleted transactions.
+    async fn expire_invalid_txs(&self, max_age: Duration) -> KeyValueResult<usize> {
+        let Some(cursor) = self.get(*EXPIRY_CURSOR_KEY).await? else {
+            return Ok(0);
+        };
+
+        let expired_before = unix_now().saturating_sub(max_age.as_secs()) / EXPIRY_BUCKET_SPAN;
+        let mut expired = 0;
+
+        for bucket in cursor..expired_before {
+            let txids: Option<Vec<Txid>> = self.get(expiry_bucket_key(bucket)).await?;
+
+            for txid in txids.unwrap_or_default() {
+                // The transaction could be rejected again later, so it's kept until its newer
+                // bucket expires.
+                let entry = self.get_invalid_tx_entry(txid).await?;
+                if entry.is_some_and(|entry| entry.rejected_at / EXPIRY_BUCKET_SPAN > bucket) {
+                    continue;
+                }
+
+                self.delete_invalid_tx(txid).await?;
+                expired += 1;
+            }
+
+            KeyValueStorage::<_, Vec<Txid>>::delete(self, expiry_bucket_key(bucket)).await?;
+        }
+
+        if expired_before > cursor {
+            self.put(*EXPIRY_CURSOR_KEY, expired_before).await?;
+        }
+
+        Ok(expired)
     }
 }
+
+/// Storage entry that stores why and when the transaction was rejected.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
+pub struct InvalidTxEntry {
+    /// Reason of the transaction rejection.
+    pub reason: String,
+    /// Unix timestamp (in seconds) of the transaction rejection.
+    pub rejected_at: u64,
+}
@@ -7,7 +7,7 @@ use serde::{de::DeserializeOwned, Serialize};
 pub use transactions::TransactionsStorage;
 
 mod invalid;
-pub use invalid::InvalidTxsStorage;
+pub use invalid::{InvalidTxEntry, InvalidTxsStorage};
 
 mod inventory;
 pub use inventory::InventoryStorage;
@@ -21,11 +21,13 @@ pub use indexed_block::BlockIndexerStorage;
 pub use indexed_block::IsIndexedStorage;
 
 mod frozen;
-pub use frozen::FrozenTxsStorage;
+pub use frozen::{ChromaFreezeEntry, FrozenTxsStorage};
 
 mod chroma_info;
 pub use chroma_info::ChromaInfoStorage;
 
+mod compression;
+
 pub type KeyValueResult<T> = Result<T, KeyValueError>;
 
 #[async_trait]
@@ -63,7 +65,7 @@ where
             return Ok(None);
         };
 
-        let value: V = cbor_from_vec(value)?;
+        let value: V = cbor_from_slice(&value)?;
 
         Ok(Some(value))
     }
@@ -83,8 +85,8 @@ fn cbor_to_vec<K: Serialize>(key: K) -> Result<Vec<u8>, ciborium::ser::Error<io:
     Ok(buf)
 }
 
-fn cbor_from_vec<T: DeserializeOwned>(data: Vec<u8>) -> Result<T, ciborium::de::Error<io::Error>> {
-    ciborium::from_reader(data.as_slice())
+fn cbor_from_slice<T: DeserializeOwned>(data: &[u8]) -> Result<T, ciborium::de::Error<io::Error>> {
+    ciborium::from_reader(data)
 }
 
 #[derive(Debug, thiserror::Error)]
@@ -93,6 +95,8 @@ pub enum KeyValueError {
     Decoding(ciborium::de::Error<io::Error>),
     #[error("Encoding error: {0}")]
     Encoding(ciborium::ser::Error<io::Error>),
+    #[error("Compression error: {0}")]
+    Compression(io::Error),
     #[error("Storage error: {0}")]
     Storage(Box<dyn std::error::Error + Send + Sync + 'static>),
 }
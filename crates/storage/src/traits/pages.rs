@@ -1,3 +1,4 @@
+use std::collections::HashSet;
 use std::mem::size_of;
 
 use crate::{KeyValueResult, KeyValueStorage};
@@ -8,6 +9,11 @@ const PAGES_NUMBER_KEY_SIZE: usize = 12;
 /// The key for the [`KeyValueStorage`] storage where the YUV Node's pages number are stored.
 const PAGES_NUMBER_KEY: &[u8; PAGES_NUMBER_KEY_SIZE] = b"pages-number";
 
+const PAGE_SIZE_KEY_SIZE: usize = 9;
+/// The key for the [`KeyValueStorage`] storage where the number of transactions per page, which
+/// the pages were filled with, is stored.
+const PAGE_SIZE_KEY: &[u8; PAGE_SIZE_KEY_SIZE] = b"page-size";
+
 /// The prefix that is used with the page number to store the page in the
 /// [`KeyValueStorage`]. "page-1", "page-2", etc.
 const PAGES_PREFIX: &str = "page-";
@@ -38,7 +44,9 @@ fn page_key(page_num: u64) -> [u8; PAGE_KEY_SIZE] {
 
 #[async_trait]
 pub trait PagesStorage:
-    KeyValueStorage<[u8; PAGE_KEY_SIZE], Vec<Txid>> + PagesNumberStorage
+    KeyValueStorage<[u8; PAGE_KEY_SIZE], Vec<Txid>>
+    + KeyValueStorage<[u8; PAGE_SIZE_KEY_SIZE], u64>
+    + PagesNumberStorage
 {
     async fn put_page(&self, page_num: u64, page: Vec<Txid>) -> KeyValueResult<()> {
         self.put(page_key(page_num), page).await
@@ -47,4 +55,63 @@ pub trait PagesStorage:
     async fn get_page_by_num(&self, num: u64) -> KeyValueResult<Option<Vec<Txid>>> {
         Ok(self.get(page_key(num)).await?)
     }
+
+    async fn delete_page(&self, page_num: u64) -> KeyValueResult<()> {
+        KeyValueStorage::<[u8; PAGE_KEY_SIZE], Vec<Txid>>::delete(self, page_key(page_num)).await
+    }
+
+    /// Remove the `removed` transactions from the pages, and move the ones after them to fill
+    /// the pages up to `page_size`, so only the last page may be short.
+    async fn remove_from_pages(
+        &self,
+        removed: &HashSet<Txid>,
+        page_size: u64,
+    ) -> KeyValueResult<()> {
+        let pages_number = self.get_pages_number().await?.unwrap_or_default();
+
+        // The pages before the first one with removed transactions stay the same.
+        let mut first_page_num = None;
+        let mut txids = Vec::new();
+        for page_num in 0..=pages_number {
+            let page = self.get_page_by_num(page_num).await?.unwrap_or_default();
+
+            if first_page_num.is_none() {
+                if !page.iter().any(|txid| removed.contains(txid)) {
+                    continue;
+                }
+                first_page_num = Some(page_num);
+            }
+
+            txids.extend(page.into_iter().filter(|txid| !removed.contains(txid)));
+        }
+
+        let Some(mut page_num) = first_page_num else {
+            return Ok(());
+        };
+
+        for page in txids.chunks(page_size.max(1) as usize) {
+            self.put_page(page_num, page.to_vec()).await?;
+            page_num += 1;
+        }
+
+        // Keep at least one page, even an empty one.
+        if page_num == 0 {
+            self.put_page(0, Vec::new()).await?;
+            page_num = 1;
+        }
+
+        for stale_page_num in page_num..=pages_number {
+            self.delete_page(stale_page_num).await?;
+        }
+
+        self.put_pages_number(page_num - 1).await
+    }
+
+    async fn put_page_size(&self, page_size: u64) -> KeyValueResult<()> {
+        self.put(*PAGE_SIZE_KEY, page_size).await
+    }
+
+    async fn get_page_size(&self) -> KeyValueResult<Option<u64>> {
+        Ok(self.get(*PAGE_SIZE_KEY).await?)
+    }
 }
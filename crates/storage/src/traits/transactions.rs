@@ -1,11 +1,13 @@
 use std::mem::size_of;
 
 use async_trait::async_trait;
-use bitcoin::Txid;
+use bitcoin::{OutPoint, Txid};
 use serde_bytes::ByteArray;
-use yuv_types::YuvTransaction;
+use yuv_types::{YuvTransaction, YuvTxType};
 
-use crate::{KeyValueResult, KeyValueStorage};
+use super::cbor_to_vec;
+use super::compression::{decode_value, encode_value};
+use crate::{KeyValueError, KeyValueResult, KeyValueStorage};
 
 const KEY_PREFIX: &str = "txs-";
 const KEY_PREFIX_SIZE: usize = KEY_PREFIX.len();
@@ -22,19 +24,104 @@ fn tx_storage_key(txid: &Txid) -> ByteArray<TXS_STORAGE_KEY_SIZE> {
     ByteArray::new(bytes)
 }
 
+const SPENDING_KEY_PREFIX: &str = "spent-";
+const SPENDING_KEY_PREFIX_SIZE: usize = SPENDING_KEY_PREFIX.len();
+
+/// Spending transaction key size is 6(`SPENDING_KEY_PREFIX`) + 32(`Txid`) + 4(`vout`) = 42 bytes
+/// long
+const SPENDING_TX_KEY_SIZE: usize = SPENDING_KEY_PREFIX_SIZE + size_of::<Txid>() + size_of::<u32>();
+
+fn spending_tx_key(outpoint: &OutPoint) -> ByteArray<SPENDING_TX_KEY_SIZE> {
+    let mut bytes = [0u8; SPENDING_TX_KEY_SIZE];
+
+    let (prefix, rest) = bytes.split_at_mut(SPENDING_KEY_PREFIX_SIZE);
+    let (txid, vout) = rest.split_at_mut(size_of::<Txid>());
+
+    prefix.copy_from_slice(SPENDING_KEY_PREFIX.as_bytes());
+    txid.copy_from_slice(&outpoint.txid);
+    vout.copy_from_slice(&outpoint.vout.to_be_bytes());
+
+    ByteArray::new(bytes)
+}
+
+/// Outpoints of the YUV outputs the transaction spends, which are the inputs with proofs.
+fn spent_outpoints(tx: &YuvTransaction) -> impl Iterator<Item = OutPoint> + '_ {
+    let input_proofs = match &tx.tx_type {
+        YuvTxType::Transfer { input_proofs, .. } => Some(input_proofs),
+        _ => None,
+    };
+
+    input_proofs
+        .into_iter()
+        .flat_map(|proofs| proofs.keys())
+        .filter_map(|input| tx.bitcoin_tx.input.get(*input as usize))
+        .map(|txin| txin.previous_output)
+}
+
+/// Storage of the attached transactions.
+///
+/// Besides the transactions, the index of the spent YUV outputs to the transactions that spent
+/// them is stored, so the history of the coins could be traced forward.
 #[async_trait]
 pub trait TransactionsStorage:
     KeyValueStorage<ByteArray<TXS_STORAGE_KEY_SIZE>, YuvTransaction>
+    + KeyValueStorage<ByteArray<SPENDING_TX_KEY_SIZE>, Txid>
 {
+    /// Get the transaction, decompressing it if it was put compressed.
     async fn get_yuv_tx(&self, txid: &Txid) -> KeyValueResult<Option<YuvTransaction>> {
-        self.get(tx_storage_key(txid)).await
+        let key = cbor_to_vec(tx_storage_key(txid))?;
+
+        let value = KeyValueStorage::<_, YuvTransaction>::raw_get(self, key)
+            .await
+            .map_err(|err| KeyValueError::Storage(Box::new(err)))?;
+
+        value.map(|bytes| decode_value(&bytes)).transpose()
     }
 
+    /// Put the transaction, compressing it if the `compression` feature is enabled.
     async fn put_yuv_tx(&self, tx: YuvTransaction) -> KeyValueResult<()> {
-        self.put(tx_storage_key(&tx.bitcoin_tx.txid()), tx).await
+        let key = cbor_to_vec(tx_storage_key(&tx.bitcoin_tx.txid()))?;
+        let value = encode_value(&tx)?;
+
+        KeyValueStorage::<_, YuvTransaction>::raw_put(self, key, value)
+            .await
+            .map_err(|err| KeyValueError::Storage(Box::new(err)))
     }
 
     async fn delete_yuv_tx(&self, txid: &Txid) -> KeyValueResult<()> {
-        self.delete(tx_storage_key(txid)).await
+        KeyValueStorage::<_, YuvTransaction>::delete(self, tx_storage_key(txid)).await
+    }
+
+    /// Get the id of the attached transaction that spent the YUV output.
+    async fn get_spending_tx(&self, outpoint: &OutPoint) -> KeyValueResult<Option<Txid>> {
+        self.get(spending_tx_key(outpoint)).await
+    }
+
+    /// Index the YUV outputs spent by the transaction. Outputs spent by another transaction
+    /// before (e.g. the one that was reorged out) are indexed to the new one.
+    async fn put_spending_tx(&self, tx: &YuvTransaction) -> KeyValueResult<()> {
+        let txid = tx.bitcoin_tx.txid();
+
+        for outpoint in spent_outpoints(tx) {
+            self.put(spending_tx_key(&outpoint), txid).await?;
+        }
+
+        Ok(())
+    }
+
+    /// Remove the YUV outputs spent by the transaction from the index, if they are still
+    /// indexed to it.
+    async fn delete_spending_tx(&self, tx: &YuvTransaction) -> KeyValueResult<()> {
+        let txid = tx.bitcoin_tx.txid();
+
+        for outpoint in spent_outpoints(tx) {
+            let key = spending_tx_key(&outpoint);
+
+            if self.get(key).await? == Some(txid) {
+                KeyValueStorage::<_, Txid>::delete(self, key).await?;
+            }
+        }
+
+        Ok(())
     }
 }
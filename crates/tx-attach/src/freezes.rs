@@ -0,0 +1,40 @@
+use tokio::sync::broadcast;
+
+use yuv_types::announcements::FreezeToggle;
+
+/// Default number of freeze toggles kept for the subscribers that are behind.
+const DEFAULT_FREEZE_SINKS_CAPACITY: usize = 1000;
+
+/// Sinks of the freeze toggles pushed by the [`GraphBuilder`](crate::GraphBuilder), e.g. the RPC
+/// subscriptions.
+///
+/// Toggles are dropped if there are no subscribers, and the subscriber that is behind by more
+/// than the capacity misses the oldest ones.
+#[derive(Clone, Debug)]
+pub struct FreezeSinks {
+    sender: broadcast::Sender<FreezeToggle>,
+}
+
+impl FreezeSinks {
+    pub fn new(capacity: usize) -> Self {
+        let (sender, _) = broadcast::channel(capacity);
+
+        Self { sender }
+    }
+
+    /// Subscribe to the toggles that are routed after the call.
+    pub fn subscribe(&self) -> broadcast::Receiver<FreezeToggle> {
+        self.sender.subscribe()
+    }
+
+    /// Send the toggle to all the subscribers. Returns the number of them.
+    pub fn notify(&self, toggle: FreezeToggle) -> usize {
+        self.sender.send(toggle).unwrap_or_default()
+    }
+}
+
+impl Default for FreezeSinks {
+    fn default() -> Self {
+        Self::new(DEFAULT_FREEZE_SINKS_CAPACITY)
+    }
+}
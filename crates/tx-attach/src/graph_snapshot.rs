@@ -0,0 +1,79 @@
+use std::sync::{Arc, Mutex};
+
+use bitcoin::Txid;
+
+/// Default max number of the transactions kept in the snapshot of the graph.
+const DEFAULT_GRAPH_SNAPSHOT_MAX_NODES: usize = 10_000;
+
+/// Status of the transaction in the dependency graph of the [`GraphBuilder`](crate::GraphBuilder).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum GraphNodeStatus {
+    /// Transaction is waiting for its parents to be attached.
+    Pending,
+    /// Parents of the transaction are attached, but it's waiting for enough confirmations.
+    Unconfirmed,
+    /// Parent other transactions are waiting for, which isn't received by the graph builder.
+    Missing,
+}
+
+/// Transaction in the dependency graph.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct GraphNode {
+    pub txid: Txid,
+    pub status: GraphNodeStatus,
+}
+
+/// Dependency graph of the transactions that are not attached yet.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct DependencyGraph {
+    /// Transactions sorted by their ids.
+    pub nodes: Vec<GraphNode>,
+    /// Edges from the transaction to the parent it's waiting for, between the `nodes` only.
+    pub edges: Vec<(Txid, Txid)>,
+    /// Some of the transactions are dropped, as there are more of them than the limit.
+    pub truncated: bool,
+}
+
+/// Latest snapshot of the [`GraphBuilder`](crate::GraphBuilder)'s dependency graph, that is kept
+/// in memory for the diagnostics of the stuck transactions.
+///
+/// The snapshot is updated by the graph builder after handling of each event, if it's
+/// [set](crate::GraphBuilder::with_graph_snapshot), and has at most `max_nodes` transactions.
+#[derive(Clone, Debug)]
+pub struct GraphSnapshot {
+    graph: Arc<Mutex<DependencyGraph>>,
+    max_nodes: usize,
+}
+
+impl GraphSnapshot {
+    pub fn new(max_nodes: usize) -> Self {
+        Self {
+            graph: Arc::new(Mutex::new(DependencyGraph::default())),
+            max_nodes,
+        }
+    }
+
+    /// Max number of the transactions in the snapshot.
+    pub fn max_nodes(&self) -> usize {
+        self.max_nodes
+    }
+
+    /// Get the latest snapshot of the graph.
+    pub fn get(&self) -> DependencyGraph {
+        self.graph
+            .lock()
+            .expect("graph snapshot lock is poisoned")
+            .clone()
+    }
+
+    /// Replace the snapshot with the new one.
+    pub(crate) fn update(&self, graph: DependencyGraph) {
+        *self.graph.lock().expect("graph snapshot lock is poisoned") = graph;
+    }
+}
+
+impl Default for GraphSnapshot {
+    fn default() -> Self {
+        Self::new(DEFAULT_GRAPH_SNAPSHOT_MAX_NODES)
+    }
+}
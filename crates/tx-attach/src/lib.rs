@@ -1,26 +1,45 @@
 #![doc = include_str!("../README.md")]
 
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::sync::Arc;
 use std::time::{Duration, SystemTime};
 
-use bitcoin::Txid;
-use event_bus::{typeid, EventBus};
+use bitcoin::{OutPoint, Txid};
+use bitcoin_client::BitcoinRpcApi;
+use event_bus::{typeid, EventBus, SendTimeoutError};
 use eyre::WrapErr;
 use tokio_util::sync::CancellationToken;
+use tracing::instrument;
 
+use yuv_metrics::METRICS;
 use yuv_storage::{PagesStorage, TransactionsStorage};
 
-use yuv_types::{ControllerMessage, GraphBuilderMessage, ProofMap, YuvTransaction, YuvTxType};
+use yuv_types::{
+    announcements::FreezeToggle, ControllerMessage, GraphBuilderMessage, ProofMap,
+    TxCheckerPoolMessage, YuvTransaction, YuvTxType,
+};
+
+mod freezes;
+pub use freezes::FreezeSinks;
+
+mod graph_snapshot;
+pub use graph_snapshot::{DependencyGraph, GraphNode, GraphNodeStatus, GraphSnapshot};
 
 /// Service which handles attaching of transactions to the graph.
 ///
 /// Accepts batches of checked transactions, and attaches
 /// history of transactions, and if all dependencies (parents) are attached,
 /// then marks transaction as attached, and stores it in [`TransactionsStorage`].
-pub struct GraphBuilder<TransactionStorage> {
+///
+/// If minimal number of confirmations is set, transaction with attached parents
+/// is kept _checked_ until its Bitcoin transaction reaches that depth.
+pub struct GraphBuilder<TransactionStorage, BitcoinClient> {
     /// Storage of transactions, where attached transactions are stored.
     tx_storage: TransactionStorage,
 
+    /// Bitcoin RPC client to get the number of confirmations of transactions.
+    bitcoin_client: Arc<BitcoinClient>,
+
     /// Event bus for simplifying communication with services.
     event_bus: EventBus,
 
@@ -36,6 +55,14 @@ pub struct GraphBuilder<TransactionStorage> {
     /// transaction was stored.
     stored_txs: HashMap<Txid, (YuvTransaction, SystemTime)>,
 
+    /// Txs which parents are attached, but which are waiting for enough
+    /// confirmations, with point in time in which transaction was stored.
+    unconfirmed_txs: HashMap<Txid, (YuvTransaction, SystemTime)>,
+
+    /// Minimal number of confirmations of the Bitcoin transaction required
+    /// to mark YUV transaction as attached.
+    min_confirmations: u8,
+
     /// Period of time after which [`Self`] will cleanup transactions
     /// that are _too old_.
     cleanup_period: Duration,
@@ -46,29 +73,108 @@ pub struct GraphBuilder<TransactionStorage> {
 
     /// Amount of transactions that fit one page.
     tx_per_page: u64,
+
+    /// Number of confirmations after which fully spent transfers are pruned
+    /// from the storage. Pruning is disabled if not set.
+    prune_retention_blocks: Option<u32>,
+
+    /// Period of time after which [`Self`] will prune the storage. Only
+    /// manual pruning is done if not set.
+    prune_period: Option<Duration>,
+
+    /// Maximum time to wait for the controller's channel to have capacity
+    /// before keeping the notification to resend it later.
+    send_timeout: Duration,
+
+    /// Attached transactions the controller wasn't notified about because
+    /// its channel was full. They're sent with the next attached ones or on
+    /// the resend tick.
+    unsent_attached_txs: Vec<Txid>,
+
+    /// Source of the current time the transactions are stored at and
+    /// considered _outdated_ by.
+    clock: Clock,
+
+    /// Snapshot of the dependency graph for the diagnostics, which is not
+    /// updated if not set.
+    graph_snapshot: Option<GraphSnapshot>,
+
+    /// Number of the pending transactions above which the checkers are paused, and below which they're resumed. Back-pressure is
+    /// disabled if not set.
+    water_marks: Option<WaterMarks>,
+
+    /// The checkers are paused by the back-pressure.
+    checkers_paused: bool,
+
+    /// Sinks the freeze toggles of the checked announcements are pushed to.
+    freeze_sinks: FreezeSinks,
+}
+
+/// Marks of the number of the transactions waiting for their parents or
+/// confirmations the checkers are paused and resumed at.
+///
+/// If the parents of the pending transactions are queued to the paused
+/// checkers, the pause ends when the transactions are removed by the cleanup.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct WaterMarks {
+    /// The checkers are paused when the number of the pending transactions
+    /// exceeds it.
+    pub high: usize,
+    /// The paused checkers are resumed when the number of the pending
+    /// transactions goes below it.
+    pub low: usize,
 }
 
+/// Source of the current time, [`SystemTime::now`] by default.
+pub type Clock = Arc<dyn Fn() -> SystemTime + Send + Sync>;
+
 const DURATION_ONE_HOUR: Duration = Duration::from_secs(60 * 60);
 const DURATION_ONE_DAY: Duration = Duration::from_secs(60 * 60 * 24);
+const DEFAULT_SEND_TIMEOUT: Duration = Duration::from_secs(1);
+const RESEND_PERIOD: Duration = Duration::from_secs(5);
+/// Maximum number of the transfers which state is requested from the Bitcoin
+/// node in one batch while pruning.
+const PRUNE_BATCH_SIZE: usize = 100;
 
-impl<TS> GraphBuilder<TS>
+impl<TS, BC> GraphBuilder<TS, BC>
 where
     TS: TransactionsStorage + PagesStorage + Send + Sync + 'static,
+    BC: BitcoinRpcApi + Send + Sync + 'static,
 {
-    pub fn new(tx_storage: TS, full_event_bus: &EventBus, tx_per_page: u64) -> Self {
+    pub fn new(
+        tx_storage: TS,
+        full_event_bus: &EventBus,
+        bitcoin_client: Arc<BC>,
+        tx_per_page: u64,
+    ) -> Self {
         let event_bus = full_event_bus
-            .extract(&typeid![ControllerMessage], &typeid![GraphBuilderMessage])
+            .extract(
+                &typeid![ControllerMessage, TxCheckerPoolMessage],
+                &typeid![GraphBuilderMessage],
+            )
             .expect("event channels must be presented");
 
         Self {
             tx_storage,
+            bitcoin_client,
             event_bus,
             inverse_deps: Default::default(),
             deps: Default::default(),
             stored_txs: Default::default(),
+            unconfirmed_txs: Default::default(),
+            min_confirmations: 0,
             tx_per_page,
             cleanup_period: DURATION_ONE_HOUR,
             tx_outdated_duration: DURATION_ONE_DAY,
+            prune_retention_blocks: None,
+            prune_period: None,
+            send_timeout: DEFAULT_SEND_TIMEOUT,
+            unsent_attached_txs: Vec::new(),
+            clock: Arc::new(SystemTime::now),
+            graph_snapshot: None,
+            water_marks: None,
+            checkers_paused: false,
+            freeze_sinks: FreezeSinks::default(),
         }
     }
 
@@ -86,10 +192,118 @@ where
         self
     }
 
+    /// Set minimal number of confirmations of the Bitcoin transaction after
+    /// which YUV transaction is marked as attached. Zero disables the check.
+    pub fn with_min_confirmations(mut self, min_confirmations: u8) -> Self {
+        self.min_confirmations = min_confirmations;
+        self
+    }
+
+    /// Set number of confirmations after which transfers with all outputs
+    /// spent are pruned, see [`Self::prune_attached_txs`].
+    pub fn with_prune_retention(mut self, retention_blocks: u32) -> Self {
+        self.prune_retention_blocks = Some(retention_blocks);
+        self
+    }
+
+    /// Set period of time after each [`Self`] will prune the storage.
+    pub fn with_prune_period(mut self, period: Duration) -> Self {
+        self.prune_period = Some(period);
+        self
+    }
+
+    /// Set maximum time to wait for the controller's channel to have capacity
+    /// when notifying it about attached transactions.
+    pub fn with_send_timeout(mut self, timeout: Duration) -> Self {
+        self.send_timeout = timeout;
+        self
+    }
+
+    /// Set source of the current time, which is used to find _outdated_
+    /// transactions, e.g. to advance it manually in tests.
+    pub fn with_clock(mut self, clock: impl Fn() -> SystemTime + Send + Sync + 'static) -> Self {
+        self.clock = Arc::new(clock);
+        self
+    }
+
+    /// Set snapshot of the dependency graph, which is updated after each
+    /// handled event, see [`GraphSnapshot`].
+    pub fn with_graph_snapshot(mut self, snapshot: GraphSnapshot) -> Self {
+        self.graph_snapshot = Some(snapshot);
+        self
+    }
+
+    /// Set sinks the freeze toggles of the checked announcements are
+    /// pushed to, see [`FreezeSinks`].
+    pub fn with_freeze_sinks(mut self, freeze_sinks: FreezeSinks) -> Self {
+        self.freeze_sinks = freeze_sinks;
+        self
+    }
+
+    /// Set the marks of the number of the pending transactions at which the
+    /// checkers are paused and resumed, so they don't worsen the backlog.
+    pub fn with_water_marks(mut self, water_marks: WaterMarks) -> Self {
+        self.water_marks = Some(water_marks);
+        self
+    }
+
+    /// Check that the storage was paginated with the same number of transactions per page, and
+    /// persist it on the first run.
+    ///
+    /// Pages are filled up to the size they were created with, so changing it between runs
+    /// would mix pages of different sizes. For storages paginated before the size was persisted,
+    /// it's taken from the first page, if there is more than one page.
+    pub async fn init_page_size(&self) -> eyre::Result<()> {
+        let stored_page_size = match self.tx_storage.get_page_size().await? {
+            Some(page_size) => Some(page_size),
+            None => self.infer_page_size().await?,
+        };
+
+        if let Some(page_size) = stored_page_size {
+            if page_size != self.tx_per_page {
+                eyre::bail!(
+                    "Storage is paginated with {page_size} transactions per page, but {} is \
+                    configured. Set `tx_per_page` back to {page_size} or reindex to a clean storage",
+                    self.tx_per_page,
+                );
+            }
+        }
+
+        self.tx_storage.put_page_size(self.tx_per_page).await?;
+
+        Ok(())
+    }
+
+    /// Infer the page size from the first page, which is full if there is a next page.
+    async fn infer_page_size(&self) -> eyre::Result<Option<u64>> {
+        let pages_number = self
+            .tx_storage
+            .get_pages_number()
+            .await?
+            .unwrap_or_default();
+        if pages_number == 0 {
+            return Ok(None);
+        }
+
+        let first_page = self
+            .tx_storage
+            .get_page_by_num(0)
+            .await?
+            .unwrap_or_default();
+
+        Ok(Some(first_page.len() as u64))
+    }
+
     /// Starts attach incoming [`transactions`](YuvTransaction).
     pub async fn run(mut self, cancellation: CancellationToken) {
         let events = self.event_bus.subscribe::<GraphBuilderMessage>();
         let mut timer = tokio::time::interval(self.cleanup_period);
+        // The first tick of the interval is immediate, so the pruning is
+        // delayed by the period not to slow down the start of the node.
+        let mut prune_timer = self
+            .prune_period
+            .map(|period| tokio::time::interval_at(tokio::time::Instant::now() + period, period));
+        let mut resend_timer = tokio::time::interval(RESEND_PERIOD);
 
         loop {
             tokio::select! {
@@ -112,10 +326,105 @@ where
                         tracing::error!("Failed to do cleanup: {:?}", err);
                     }
                 }
+                _ = tick(&mut prune_timer) => {
+                    if let Err(err) = self.handle_prune().await {
+                        tracing::error!("Failed to prune transactions: {:?}", err);
+                    }
+                }
+                _ = resend_timer.tick(), if !self.unsent_attached_txs.is_empty() => {
+                    if let Err(err) = self.send_attached_txs().await {
+                        tracing::error!("Failed to resend attached transactions: {:?}", err);
+                    }
+                }
+            }
+
+            METRICS
+                .graph_builder_pending
+                .set(self.pending_txs_number() as u64);
+
+            self.handle_backpressure().await;
+
+            if let Some(snapshot) = &self.graph_snapshot {
+                snapshot.update(self.dependency_graph(snapshot.max_nodes()));
             }
         }
     }
 
+    /// Number of the transactions waiting for their parents or confirmations.
+    fn pending_txs_number(&self) -> usize {
+        self.stored_txs.len() + self.unconfirmed_txs.len()
+    }
+
+    /// Pause the checkers if the number of the pending transactions exceeds
+    /// the high-water mark, and resume them when it goes below the low-water
+    /// mark.
+    async fn handle_backpressure(&mut self) {
+        let Some(water_marks) = self.water_marks else {
+            return;
+        };
+
+        let pending = self.pending_txs_number();
+
+        if !self.checkers_paused && pending > water_marks.high {
+            tracing::warn!(
+                "{pending} pending transactions exceed the high-water mark {}, pausing the checkers",
+                water_marks.high
+            );
+
+            self.event_bus.send(TxCheckerPoolMessage::Pause).await;
+            self.checkers_paused = true;
+        } else if self.checkers_paused && pending < water_marks.low {
+            tracing::info!(
+                "{pending} pending transactions are below the low-water mark {}, resuming the checkers",
+                water_marks.low
+            );
+
+            self.event_bus.send(TxCheckerPoolMessage::Resume).await;
+            self.checkers_paused = false;
+        }
+    }
+
+    /// Get the dependency graph of the transactions that are not attached
+    /// yet, with at most `max_nodes` of them.
+    pub fn dependency_graph(&self, max_nodes: usize) -> DependencyGraph {
+        let mut nodes = BTreeMap::new();
+
+        for txid in self.inverse_deps.keys() {
+            nodes.insert(*txid, GraphNodeStatus::Missing);
+        }
+        for txid in self.stored_txs.keys() {
+            nodes.insert(*txid, GraphNodeStatus::Pending);
+        }
+        for txid in self.unconfirmed_txs.keys() {
+            nodes.insert(*txid, GraphNodeStatus::Unconfirmed);
+        }
+
+        let truncated = nodes.len() > max_nodes;
+
+        let nodes = nodes
+            .into_iter()
+            .take(max_nodes)
+            .map(|(txid, status)| GraphNode { txid, status })
+            .collect::<Vec<_>>();
+
+        let included = nodes.iter().map(|node| node.txid).collect::<HashSet<_>>();
+
+        let mut edges = self
+            .deps
+            .iter()
+            .filter(|(child, _)| included.contains(*child))
+            .flat_map(|(child, parents)| parents.iter().map(|parent| (*child, *parent)))
+            .filter(|(_, parent)| included.contains(parent))
+            .collect::<Vec<_>>();
+        edges.sort();
+
+        DependencyGraph {
+            nodes,
+            edges,
+            truncated,
+        }
+    }
+
     /// Handles incoming [`events`](GraphBuilderMessage).
     async fn handle_event(&mut self, event: GraphBuilderMessage) -> eyre::Result<()> {
         match event {
@@ -123,18 +432,37 @@ where
                 .attach_txs(&txs)
                 .await
                 .wrap_err("failed to attach transactions")?,
+            GraphBuilderMessage::CheckedFreezes(toggles) => self.handle_freeze_toggles(toggles),
+            GraphBuilderMessage::NewBlock => self
+                .attach_confirmed_txs()
+                .await
+                .wrap_err("failed to attach confirmed transactions")?,
+            GraphBuilderMessage::Prune => self
+                .handle_prune()
+                .await
+                .wrap_err("failed to prune transactions")?,
         }
 
         Ok(())
     }
 
+    /// Push the freeze toggles to the sinks.
+    fn handle_freeze_toggles(&self, toggles: Vec<FreezeToggle>) {
+        for toggle in toggles {
+            let txid = toggle.txid;
+            let receivers = self.freeze_sinks.notify(toggle);
+
+            tracing::debug!("Freeze toggle {txid} is pushed to {receivers} sinks");
+        }
+    }
+
     /// Clean up transactions that are _outdated_ and all transactions that are related to them.
     async fn handle_cleanup(&mut self) -> eyre::Result<()> {
-        let now = SystemTime::now();
+        let now = (self.clock)();
 
         let mut outdated_txs = Vec::new();
 
-        for (txid, (_, created_at)) in self.stored_txs.iter() {
+        for (txid, (_, created_at)) in self.stored_txs.iter().chain(self.unconfirmed_txs.iter()) {
             let since_created_at = now
                 .duration_since(*created_at)
                 .wrap_err("failed to calculate duration since")?;
@@ -152,6 +480,208 @@ where
         Ok(())
     }
 
+    /// Prune the storage if the retention is set.
+    async fn handle_prune(&mut self) -> eyre::Result<()> {
+        let Some(retention_blocks) = self.prune_retention_blocks else {
+            tracing::warn!("Pruning is requested, but the retention is not configured");
+            return Ok(());
+        };
+
+        let pruned = self.prune_attached_txs(retention_blocks).await?;
+
+        tracing::info!("Pruned {pruned} attached transactions");
+
+        Ok(())
+    }
+
+    /// Delete the attached transfers that have at least `retention_blocks`
+    /// confirmations and all outputs with proofs spent, as they are no longer
+    /// needed to attach new transactions.
+    ///
+    /// Issuances and announcements are kept for the supply accounting, as well
+    /// as the parents of the transactions that are waiting to be attached.
+    /// Transactions which state can't be got from the Bitcoin node are kept
+    /// too. Pruned transactions are removed from the pages, and the rest are
+    /// moved to fill them up to the page size.
+    ///
+    /// Returns the number of pruned transactions.
+    pub async fn prune_attached_txs(&self, retention_blocks: u32) -> eyre::Result<usize> {
+        let pending_parents = self.pending_parents();
+        let pages = self.get_pages().await?;
+
+        // Page entries of the transactions pruned before are removed as well.
+        let mut removed = HashSet::new();
+        let mut transfers = Vec::new();
+
+        for txid in pages.iter().flatten() {
+            let Some(tx) = self.tx_storage.get_yuv_tx(txid).await? else {
+                removed.insert(*txid);
+                continue;
+            };
+
+            if matches!(tx.tx_type, YuvTxType::Transfer { .. }) && !pending_parents.contains(txid) {
+                transfers.push(tx);
+            }
+        }
+
+        let mut pruned = 0;
+
+        for chunk in transfers.chunks(PRUNE_BATCH_SIZE) {
+            for tx in self.filter_prunable(chunk, retention_blocks).await {
+                let txid = tx.bitcoin_tx.txid();
+
+                self.tx_storage.delete_spending_tx(tx).await?;
+                self.tx_storage.delete_yuv_tx(&txid).await?;
+                tracing::debug!("Tx {txid} is pruned");
+
+                removed.insert(txid);
+                pruned += 1;
+            }
+        }
+
+        self.tx_storage
+            .remove_from_pages(&removed, self.tx_per_page)
+            .await?;
+
+        Ok(pruned)
+    }
+
+    /// Get all the pages of the attached transactions.
+    async fn get_pages(&self) -> eyre::Result<Vec<Vec<Txid>>> {
+        let pages_number = self
+            .tx_storage
+            .get_pages_number()
+            .await?
+            .unwrap_or_default();
+
+        let mut pages = Vec::new();
+        for page_num in 0..=pages_number {
+            let page = self
+                .tx_storage
+                .get_page_by_num(page_num)
+                .await?
+                .unwrap_or_default();
+
+            pages.push(page);
+        }
+
+        Ok(pages)
+    }
+
+    /// Ids of the transactions that are parents of the ones waiting to be attached.
+    fn pending_parents(&self) -> HashSet<Txid> {
+        self.stored_txs
+            .values()
+            .chain(self.unconfirmed_txs.values())
+            .filter_map(|(tx, _)| match &tx.tx_type {
+                YuvTxType::Transfer { input_proofs, .. } => Some((tx, input_proofs)),
+                _ => None,
+            })
+            .flat_map(|(tx, input_proofs)| {
+                input_proofs
+                    .keys()
+                    .filter_map(|input| tx.bitcoin_tx.input.get(*input as usize))
+                    .map(|input| input.previous_output.txid)
+            })
+            .collect()
+    }
+
+    /// Filter the transfers that are deep enough, and have all outputs with
+    /// proofs spent. The states of the outputs and the transactions are got
+    /// from the Bitcoin node in one batch request each.
+    ///
+    /// Transfers which state can't be got are logged and skipped, e.g. if the
+    /// Bitcoin node has no transaction index.
+    async fn filter_prunable<'a>(
+        &self,
+        txs: &'a [YuvTransaction],
+        retention_blocks: u32,
+    ) -> Vec<&'a YuvTransaction> {
+        let outpoints_by_tx = txs
+            .iter()
+            .map(|tx| {
+                let txid = tx.bitcoin_tx.txid();
+                let YuvTxType::Transfer { output_proofs, .. } = &tx.tx_type else {
+                    return Vec::new();
+                };
+
+                output_proofs
+                    .keys()
+                    .map(|vout| OutPoint::new(txid, *vout))
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+
+        let outpoints = outpoints_by_tx
+            .iter()
+            .flatten()
+            .copied()
+            .collect::<Vec<_>>();
+        let mut txouts = match self
+            .bitcoin_client
+            .get_tx_outs(&outpoints, Some(true))
+            .await
+        {
+            Ok(txouts) => txouts.into_iter(),
+            Err(err) => {
+                tracing::warn!("Failed to get outputs of the transactions to prune: {err}");
+                return Vec::new();
+            }
+        };
+
+        let mut spent = Vec::new();
+        for (tx, outpoints) in txs.iter().zip(outpoints_by_tx) {
+            let mut is_spent = true;
+
+            for (outpoint, txout) in outpoints.iter().zip(txouts.by_ref()) {
+                match txout {
+                    Ok(None) => {}
+                    Ok(Some(_)) => is_spent = false,
+                    Err(err) => {
+                        tracing::warn!("Failed to get output {outpoint} to prune: {err}");
+                        is_spent = false;
+                    }
+                }
+            }
+
+            if is_spent {
+                spent.push(tx);
+            }
+        }
+
+        if spent.is_empty() {
+            return Vec::new();
+        }
+
+        let txids = spent
+            .iter()
+            .map(|tx| tx.bitcoin_tx.txid())
+            .collect::<Vec<_>>();
+        let tx_infos = match self.bitcoin_client.get_raw_transaction_infos(&txids).await {
+            Ok(tx_infos) => tx_infos,
+            Err(err) => {
+                tracing::warn!("Failed to get the transactions to prune: {err}");
+                return Vec::new();
+            }
+        };
+
+        spent
+            .into_iter()
+            .zip(tx_infos)
+            .filter_map(|(tx, tx_info)| match tx_info {
+                Ok(tx_info) => (tx_info.confirmations.unwrap_or_default() >= retention_blocks)
+                    .then_some(tx),
+                Err(err) => {
+                    tracing::warn!(
+                        "Failed to get tx {} to prune, is txindex enabled in the Bitcoin node: {err}",
+                        tx.bitcoin_tx.txid()
+                    );
+                    None
+                }
+            })
+            .collect()
+    }
+
     /// Remove outdated transaction from storage and all transactions that are related to it.
     async fn remove_outdated_tx(&mut self, txid: Txid) -> eyre::Result<()> {
         let mut txs_to_remove = vec![txid];
@@ -163,6 +693,7 @@ where
             let txid = txs_to_remove.remove(0);
 
             self.stored_txs.remove(&txid);
+            self.unconfirmed_txs.remove(&txid);
             self.remove_tx_from_deps(&txid);
 
             let Some(inverse_deps) = self.inverse_deps.remove(&txid) else {
@@ -215,15 +746,8 @@ where
             match &yuv_tx.tx_type {
                 // if issuance is attached, there is no reason to wait for it's parents.
                 YuvTxType::Issue { .. } => {
-                    self.set_tx_attached(yuv_tx.clone(), &mut attached_txs)
+                    self.handle_issue(yuv_tx, child_id, &mut queued_txs, &mut attached_txs)
                         .await?;
-
-                    let Some(ids) = self.inverse_deps.remove(&child_id) else {
-                        continue;
-                    };
-
-                    // Add to queue for next iteration of graph builder.
-                    queued_txs.extend(ids);
                 }
                 YuvTxType::Transfer { input_proofs, .. } => {
                     self.handle_transfer(
@@ -241,40 +765,47 @@ where
             }
         }
 
-        // Attach transactions until there is nothing to do:
-        while !queued_txs.is_empty() {
-            let mut local_queue = HashSet::new();
+        self.attach_queued_txs(queued_txs, attached_txs).await
+    }
 
-            for txid in queued_txs {
-                // Find deps of current node that are attached:
-                let is_empty = self.remove_attached_parents(txid).await?;
+    /// Attach transactions that are waiting for confirmations and have reached
+    /// enough of them, and then their children.
+    pub async fn attach_confirmed_txs(&mut self) -> eyre::Result<()> {
+        let mut queued_txs = HashSet::new();
+        let mut attached_txs = Vec::new();
 
-                // If we still dependent on some transactions, then we can't attach this tx.
-                if !is_empty {
-                    continue;
-                }
+        let txids = self.unconfirmed_txs.keys().copied().collect::<Vec<_>>();
 
-                // Remove from locally stored txs, and deps:
-                let Some((tx, _)) = self.stored_txs.remove(&txid) else {
-                    debug_assert!(
-                        false,
-                        "All parents are attached, but no tx found for {}",
-                        txid
-                    );
-                    continue;
-                };
-                self.deps.remove(&txid);
+        for txid in self.filter_confirmed(txids).await? {
+            let Some((tx, _)) = self.unconfirmed_txs.remove(&txid) else {
+                continue;
+            };
 
-                // Add tx to attached storage:
-                self.set_tx_attached(tx, &mut attached_txs).await?;
+            self.put_attached_tx(tx, &mut attached_txs).await?;
 
-                // Add transactions that depends on this transaction to the queue,
-                // so we can remove their deps on next iteration:
-                let Some(inv_deps) = self.inverse_deps.remove(&txid) else {
-                    continue;
-                };
+            if let Some(ids) = self.inverse_deps.remove(&txid) {
+                tracing::debug!(children = ?ids, "Children are queued");
+
+                queued_txs.extend(ids);
+            }
+        }
+
+        self.attach_queued_txs(queued_txs, attached_txs).await
+    }
+
+    /// Attach transactions from the queue until there is nothing to do, and
+    /// then handle all attached ones.
+    async fn attach_queued_txs(
+        &mut self,
+        mut queued_txs: HashSet<Txid>,
+        mut attached_txs: Vec<Txid>,
+    ) -> eyre::Result<()> {
+        while !queued_txs.is_empty() {
+            let mut local_queue = HashSet::new();
 
-                local_queue.extend(inv_deps);
+            for txid in queued_txs {
+                self.handle_queued_tx(txid, &mut local_queue, &mut attached_txs)
+                    .await?;
             }
 
             queued_txs = local_queue;
@@ -285,6 +816,81 @@ where
         Ok(())
     }
 
+    /// Attach issuance transaction and add its children to the queue.
+    #[instrument(skip_all, fields(txid = %child_id))]
+    async fn handle_issue(
+        &mut self,
+        yuv_tx: &YuvTransaction,
+        child_id: Txid,
+        queued_txs: &mut HashSet<Txid>,
+        attached_txs: &mut Vec<Txid>,
+    ) -> eyre::Result<()> {
+        if !self.set_tx_attached(yuv_tx.clone(), attached_txs).await? {
+            return Ok(());
+        }
+
+        let Some(ids) = self.inverse_deps.remove(&child_id) else {
+            return Ok(());
+        };
+
+        tracing::debug!(children = ?ids, "Children are queued");
+
+        // Add to queue for next iteration of graph builder.
+        queued_txs.extend(ids);
+
+        Ok(())
+    }
+
+    /// Try to attach transaction from the queue, if all of its parents are
+    /// attached, and add its children to the next iteration queue.
+    #[instrument(skip(self, local_queue, attached_txs))]
+    async fn handle_queued_tx(
+        &mut self,
+        txid: Txid,
+        local_queue: &mut HashSet<Txid>,
+        attached_txs: &mut Vec<Txid>,
+    ) -> eyre::Result<()> {
+        // Find deps of current node that are attached:
+        let is_empty = self.remove_attached_parents(txid).await?;
+
+        // If we still dependent on some transactions, then we can't attach this tx.
+        if !is_empty {
+            tracing::debug!(
+                deps = ?self.deps.get(&txid),
+                "Tx is still waiting for parents"
+            );
+            return Ok(());
+        }
+
+        // Remove from locally stored txs, and deps:
+        let Some((tx, _)) = self.stored_txs.remove(&txid) else {
+            debug_assert!(
+                false,
+                "All parents are attached, but no tx found for {}",
+                txid
+            );
+            return Ok(());
+        };
+        self.deps.remove(&txid);
+
+        // Add tx to attached storage:
+        if !self.set_tx_attached(tx, attached_txs).await? {
+            return Ok(());
+        }
+
+        // Add transactions that depends on this transaction to the queue,
+        // so we can remove their deps on next iteration:
+        let Some(inv_deps) = self.inverse_deps.remove(&txid) else {
+            return Ok(());
+        };
+
+        tracing::debug!(children = ?inv_deps, "Children are queued");
+
+        local_queue.extend(inv_deps);
+
+        Ok(())
+    }
+
     /// Handle fully validated transactions, add them to pagination storage and
     /// send event about verified transactions to message handler.
     async fn handle_fully_attached_txs(&mut self, attached_txs: Vec<Txid>) -> eyre::Result<()> {
@@ -300,11 +906,40 @@ where
                 .wrap_err("Failed to store transactions in pages")?;
         }
 
-        self.event_bus
-            .send(ControllerMessage::AttachedTxs(attached_txs))
+        self.unsent_attached_txs.extend(attached_txs);
+
+        self.send_attached_txs().await
+    }
+
+    /// Notify the controller about attached transactions that weren't sent
+    /// yet. If its channel is still full after the timeout, they are kept to
+    /// be sent later, so the notification isn't lost.
+    async fn send_attached_txs(&mut self) -> eyre::Result<()> {
+        if self.unsent_attached_txs.is_empty() {
+            return Ok(());
+        }
+
+        let txids = std::mem::take(&mut self.unsent_attached_txs);
+        let result = self
+            .event_bus
+            .send_timeout(ControllerMessage::AttachedTxs(txids), self.send_timeout)
             .await;
 
-        Ok(())
+        match result {
+            Ok(()) => Ok(()),
+            Err(SendTimeoutError::Timeout(ControllerMessage::AttachedTxs(txids))) => {
+                tracing::warn!(
+                    count = txids.len(),
+                    "Controller is busy, attached transactions will be resent"
+                );
+                self.unsent_attached_txs = txids;
+
+                Ok(())
+            }
+            Err(err) => Err(eyre::eyre!(
+                "failed to notify about attached transactions: {err}"
+            )),
+        }
     }
 
     /// Put attached transactions ids to page storage.
@@ -380,6 +1015,7 @@ where
     ///
     /// If parent of the current tx is attached, skip adding to deps, if all
     /// are attached, then attach current transaction too.
+    #[instrument(skip_all, fields(txid = %child_id))]
     async fn handle_transfer(
         &mut self,
         input_proofs: &ProofMap,
@@ -407,6 +1043,8 @@ where
                     .insert(child_id);
 
                 self.deps.entry(child_id).or_default().insert(parent_txid);
+
+                tracing::debug!(parent = %parent_txid, "Dependency on parent is added");
             }
         }
 
@@ -415,46 +1053,131 @@ where
 
         if all_parents_attached {
             // If all parents are attached, then we can attach this transaction.
-            self.set_tx_attached(yuv_tx.clone(), attached_txs).await?;
-
             self.deps.remove(&child_id);
 
+            if !self.set_tx_attached(yuv_tx.clone(), attached_txs).await? {
+                return Ok(());
+            }
+
             let Some(ids) = self.inverse_deps.remove(&child_id) else {
                 // no reason to add to queue, as there is no deps.
                 return Ok(());
             };
 
+            tracing::debug!(children = ?ids, "Children are queued");
+
             // Add to queue for next iteration of graph builder.
             queued_txs.extend(ids);
 
             return Ok(());
         }
 
+        tracing::debug!("Not all parents are attached, tx is stored until they are");
+
         // If not all parents are attached, then we need to wait for them.
         self.stored_txs
-            .insert(child_id, (yuv_tx.clone(), SystemTime::now()));
+            .insert(child_id, (yuv_tx.clone(), (self.clock)()));
 
         Ok(())
     }
 
-    /// Add transaction to storage and send it to message handler to update an actual inventory
+    /// Add transaction to storage if it has enough confirmations, otherwise
+    /// keep it until it has. Returns `true` if transaction is attached.
     async fn set_tx_attached(
         &mut self,
         tx: YuvTransaction,
         attached_txs: &mut Vec<Txid>,
-    ) -> eyre::Result<()> {
+    ) -> eyre::Result<bool> {
         let txid = tx.bitcoin_tx.txid();
 
-        self.tx_storage.put_yuv_tx(tx.clone()).await?;
+        if !self.is_confirmed(&txid).await? {
+            tracing::debug!(
+                "Tx {txid} is waiting for {} confirmations",
+                self.min_confirmations
+            );
 
-        tracing::info!("Tx {txid} is attached");
+            self.unconfirmed_txs.insert(txid, (tx, (self.clock)()));
 
-        // Add to inventory only if it's not a freeze transaction.
-        attached_txs.push(txid);
+            return Ok(false);
+        }
 
-        Ok(())
+        self.put_attached_tx(tx, attached_txs).await?;
+
+        Ok(true)
     }
-}
+
+    /// Check if Bitcoin transaction has reached minimal number of confirmations.
+    async fn is_confirmed(&self, txid: &Txid) -> eyre::Result<bool> {
+        if self.min_confirmations == 0 {
+            return Ok(true);
+        }
+
+        let tx_info = self
+            .bitcoin_client
+            .get_raw_transaction_info(txid, None)
+            .await
+            .wrap_err("failed to get transaction info")?;
+
+        let confirmations = tx_info.confirmations.unwrap_or_default();
+
+        Ok(confirmations >= self.min_confirmations as u32)
+    }
+
+    /// Filter the Bitcoin transactions that have reached minimal number of
+    /// confirmations, looking them up in one batch request.
+    async fn filter_confirmed(&self, txids: Vec<Txid>) -> eyre::Result<Vec<Txid>> {
+        if self.min_confirmations == 0 || txids.is_empty() {
+            return Ok(txids);
+        }
+
+        let tx_infos = self
+            .bitcoin_client
+            .get_raw_transaction_infos(&txids)
+            .await
+            .wrap_err("failed to get transaction infos")?;
+
+        let mut confirmed = Vec::new();
+        for (txid, tx_info) in txids.into_iter().zip(tx_infos) {
+            let tx_info =
+                tx_info.wrap_err_with(|| format!("failed to get transaction info of {txid}"))?;
+
+            if tx_info.confirmations.unwrap_or_default() >= self.min_confirmations as u32 {
+                confirmed.push(txid);
+            }
+        }
+
+        Ok(confirmed)
+    }
+
+    /// Add transaction to storage and send it to message handler to update an actual inventory
+    async fn put_attached_tx(
+        &mut self,
+        tx: YuvTransaction,
+        attached_txs: &mut Vec<Txid>,
+    ) -> eyre::Result<()> {
+        let txid = tx.bitcoin_tx.txid();
+
+        self.tx_storage.put_spending_tx(&tx).await?;
+        self.tx_storage.put_yuv_tx(tx.clone()).await?;
+
+        tracing::info!("Tx {txid} is attached");
+
+        // Add to inventory only if it's not a freeze transaction.
+        attached_txs.push(txid);
+
+        Ok(())
+    }
+}
+
+/// Tick the interval if it's set, otherwise wait forever.
+async fn tick(interval: &mut Option<tokio::time::Interval>) {
+    match interval {
+        Some(interval) => {
+            interval.tick().await;
+        }
+        None => std::future::pending().await,
+    }
+}
 
 /// Split at array without panic
 fn split_at<T>(txids: &[T], left_space: usize) -> (&[T], &[T]) {
@@ -463,14 +1186,21 @@ fn split_at<T>(txids: &[T], left_space: usize) -> (&[T], &[T]) {
 
 #[cfg(test)]
 mod tests {
+    use std::sync::atomic::{AtomicU32, Ordering};
+    use std::sync::Mutex;
     use std::{collections::BTreeMap, str::FromStr};
 
     use bitcoin::{
-        secp256k1::Secp256k1, PackedLockTime, PrivateKey, PublicKey, Sequence, Transaction, Witness,
+        hashes::Hash, secp256k1::Secp256k1, PackedLockTime, PrivateKey, PublicKey, Sequence,
+        Transaction, Witness,
+    };
+    use bitcoin_client::{
+        json::{GetRawTransactionResult, GetRawTransactionResultVoutScriptPubKey, GetTxOutResult},
+        MockRpcApi,
     };
     use once_cell::sync::Lazy;
     use yuv_pixels::{Pixel, PixelProof, SigPixelProof};
-    use yuv_storage::LevelDB;
+    use yuv_storage::{LevelDB, PagesNumberStorage};
 
     use super::*;
 
@@ -485,15 +1215,69 @@ mod tests {
 
     const TX_PER_PAGE: u64 = 100;
 
-    #[tokio::test]
-    async fn test_example_from_doc() {
-        let storage = LevelDB::in_memory().unwrap();
-
+    /// Event bus with the channels the graph builder sends to and receives from.
+    fn event_bus() -> EventBus {
         let mut event_bus = EventBus::default();
         event_bus.register::<GraphBuilderMessage>(Some(100));
+        event_bus.register::<TxCheckerPoolMessage>(Some(100));
         event_bus.register::<ControllerMessage>(Some(100));
 
-        let mut graph_builder = GraphBuilder::<_>::new(storage.clone(), &event_bus, TX_PER_PAGE);
+        event_bus
+    }
+
+    /// Graph builder with [`TX_PER_PAGE`], which the tests override with the settings they need.
+    fn graph_builder(
+        storage: &LevelDB,
+        event_bus: &EventBus,
+        bitcoin_client: MockRpcApi,
+    ) -> GraphBuilder<LevelDB, MockRpcApi> {
+        GraphBuilder::new(
+            storage.clone(),
+            event_bus,
+            Arc::new(bitcoin_client),
+            TX_PER_PAGE,
+        )
+    }
+
+    /// Bitcoin client that returns every transaction requested with `getrawtransaction` with the
+    /// number of confirmations got by its id.
+    fn raw_txs_client(
+        confirmations: impl Fn(Txid) -> bitcoin_client::Result<u32> + Send + 'static,
+    ) -> MockRpcApi {
+        let mut bitcoin_client = MockRpcApi::new();
+        bitcoin_client
+            .expect_call::<GetRawTransactionResult>()
+            .withf(|method, _| method == "getrawtransaction")
+            .returning(move |_, args| {
+                let txid: Txid = serde_json::from_value(args[0].clone()).unwrap();
+
+                Ok(GetRawTransactionResult {
+                    in_active_chain: None,
+                    hex: vec![],
+                    txid,
+                    hash: bitcoin::Wtxid::all_zeros(),
+                    size: 0,
+                    vsize: 0,
+                    version: 1,
+                    locktime: 0,
+                    vin: vec![],
+                    vout: vec![],
+                    blockhash: None,
+                    confirmations: Some(confirmations(txid)?),
+                    time: None,
+                    blocktime: None,
+                })
+            });
+
+        bitcoin_client
+    }
+
+    #[tokio::test]
+    async fn test_example_from_doc() {
+        let storage = LevelDB::in_memory().unwrap();
+
+        let event_bus = event_bus();
+        let mut graph_builder = graph_builder(&storage, &event_bus, MockRpcApi::new());
 
         let tx1 = YuvTransaction {
             bitcoin_tx: Transaction {
@@ -687,16 +1471,123 @@ mod tests {
     }
 
     #[tokio::test]
-    async fn test_cleanup() -> eyre::Result<()> {
-        let storage = LevelDB::in_memory().unwrap();
+    async fn test_attached_txs_are_resent_if_controller_is_busy() -> eyre::Result<()> {
+        let storage = LevelDB::in_memory()?;
 
+        // The controller channel fits only one notification.
         let mut event_bus = EventBus::default();
         event_bus.register::<GraphBuilderMessage>(Some(100));
-        event_bus.register::<ControllerMessage>(Some(100));
+        event_bus.register::<TxCheckerPoolMessage>(Some(100));
+        event_bus.register::<ControllerMessage>(Some(1));
+        let controller_events = event_bus.subscribe::<ControllerMessage>();
+
+        let mut graph_builder = graph_builder(&storage, &event_bus, MockRpcApi::new())
+            .with_send_timeout(Duration::from_millis(10));
+
+        let txids = (0..3u8)
+            .map(|i| Txid::from_inner([i; 32]))
+            .collect::<Vec<_>>();
+
+        // The controller is backed up, so only the first notification fits the channel.
+        for txid in &txids {
+            graph_builder.handle_fully_attached_txs(vec![*txid]).await?;
+        }
+        assert_eq!(graph_builder.unsent_attached_txs, txids[1..]);
+
+        let received = |event| match event {
+            ControllerMessage::AttachedTxs(txids) => txids,
+            event => panic!("Unexpected event: {event:?}"),
+        };
+        assert_eq!(received(controller_events.recv().await?), txids[..1]);
+
+        graph_builder.send_attached_txs().await?;
+        assert!(graph_builder.unsent_attached_txs.is_empty());
+        assert_eq!(received(controller_events.recv().await?), txids[1..]);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_checked_freezes_are_pushed_to_sinks() -> eyre::Result<()> {
+        let event_bus = event_bus();
+
+        let freeze_sinks = FreezeSinks::default();
+        let mut toggles = freeze_sinks.subscribe();
+
+        let mut graph_builder =
+            graph_builder(&LevelDB::in_memory()?, &event_bus, MockRpcApi::new())
+                .with_freeze_sinks(freeze_sinks);
+
+        let toggle = FreezeToggle {
+            txid: Txid::from_inner([1; 32]),
+            chroma: DUMMY_PIXEL_PROOF.pixel().chroma,
+            outpoint: Some(bitcoin::OutPoint::new(Txid::from_inner([2; 32]), 0)),
+            frozen: true,
+        };
+
+        graph_builder
+            .handle_event(GraphBuilderMessage::CheckedFreezes(vec![toggle.clone()]))
+            .await?;
+
+        assert_eq!(toggles.recv().await?, toggle);
+
+        Ok(())
+    }
 
-        let graph_builder = GraphBuilder::new(storage.clone(), &event_bus, TX_PER_PAGE);
+    #[tokio::test]
+    async fn test_page_size_change_across_restarts_is_rejected() -> eyre::Result<()> {
+        let storage = LevelDB::in_memory()?;
+
+        let event_bus = event_bus();
+
+        let new_graph_builder = |tx_per_page| GraphBuilder {
+            tx_per_page,
+            ..graph_builder(&storage, &event_bus, MockRpcApi::new())
+        };
+
+        let paginated = new_graph_builder(2);
+        paginated.init_page_size().await?;
+
+        let txids = (0..3u8)
+            .map(|i| Txid::from_inner([i; 32]))
+            .collect::<Vec<_>>();
+        paginated.put_txs_ids_to_page(&txids).await?;
+
+        // Restart with the same page size is fine:
+        new_graph_builder(2).init_page_size().await?;
+
+        // But with the different one is not:
+        let err = new_graph_builder(3)
+            .init_page_size()
+            .await
+            .expect_err("page size change must be rejected");
+        assert!(err.to_string().contains("paginated with 2"), "{err}");
+
+        // Storage paginated before the page size was persisted:
+        let legacy_storage = LevelDB::in_memory()?;
+        legacy_storage.put_pages_number(1).await?;
+        legacy_storage.put_page(0, txids[..2].to_vec()).await?;
+        legacy_storage.put_page(1, txids[2..].to_vec()).await?;
 
-        let mut graph_builder = graph_builder
+        let legacy_graph_builder = |tx_per_page| GraphBuilder {
+            tx_per_page,
+            ..graph_builder(&legacy_storage, &event_bus, MockRpcApi::new())
+        };
+
+        assert!(legacy_graph_builder(3).init_page_size().await.is_err());
+        legacy_graph_builder(2).init_page_size().await?;
+        assert_eq!(legacy_storage.get_page_size().await?, Some(2));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_cleanup() -> eyre::Result<()> {
+        let storage = LevelDB::in_memory().unwrap();
+
+        let event_bus = event_bus();
+
+        let mut graph_builder = graph_builder(&storage, &event_bus, MockRpcApi::new())
             .with_cleanup_period(Duration::from_secs(0))
             .with_outdated_duration(Duration::from_secs(0));
 
@@ -895,4 +1786,569 @@ mod tests {
 
         Ok(())
     }
+
+    #[tokio::test]
+    async fn test_cleanup_after_outdated_duration() -> eyre::Result<()> {
+        const OUTDATED_DURATION: Duration = DURATION_ONE_DAY;
+
+        let storage = LevelDB::in_memory()?;
+
+        let event_bus = event_bus();
+
+        let now = Arc::new(Mutex::new(SystemTime::UNIX_EPOCH));
+        let clock = Arc::clone(&now);
+        let advance = |duration: Duration| *now.lock().unwrap() += duration;
+
+        let mut graph_builder = graph_builder(&storage, &event_bus, MockRpcApi::new())
+            .with_outdated_duration(OUTDATED_DURATION)
+            .with_clock(move || *clock.lock().unwrap());
+
+        // Transaction with the parent that is never attached.
+        let tx = YuvTransaction {
+            bitcoin_tx: Transaction {
+                version: 1,
+                lock_time: PackedLockTime::ZERO,
+                input: vec![bitcoin::TxIn {
+                    previous_output: bitcoin::OutPoint::new(Txid::from_inner([1; 32]), 0),
+                    script_sig: bitcoin::Script::default(),
+                    sequence: Sequence(0),
+                    witness: Witness::default(),
+                }],
+                output: vec![],
+            },
+            tx_type: YuvTxType::Transfer {
+                input_proofs: BTreeMap::from([(0, DUMMY_PIXEL_PROOF.clone())]),
+                output_proofs: Default::default(),
+            },
+        };
+        let txid = tx.bitcoin_tx.txid();
+
+        graph_builder.attach_txs(&[tx]).await?;
+        assert!(graph_builder.stored_txs.contains_key(&txid));
+
+        advance(OUTDATED_DURATION);
+        graph_builder.handle_cleanup().await?;
+        assert!(
+            graph_builder.stored_txs.contains_key(&txid),
+            "tx mustn't be outdated right at the outdated duration"
+        );
+
+        advance(Duration::from_secs(1));
+        graph_builder.handle_cleanup().await?;
+        assert!(
+            graph_builder.stored_txs.is_empty(),
+            "tx must be outdated after the outdated duration"
+        );
+        assert!(graph_builder.deps.is_empty());
+        assert!(graph_builder.inverse_deps.is_empty());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_checkers_are_paused_over_high_water_mark() -> eyre::Result<()> {
+        const WATER_MARKS: WaterMarks = WaterMarks { high: 2, low: 1 };
+
+        let storage = LevelDB::in_memory()?;
+
+        let event_bus = event_bus();
+        let pool_events = event_bus.subscribe::<TxCheckerPoolMessage>();
+
+        let now = Arc::new(Mutex::new(SystemTime::UNIX_EPOCH));
+        let clock = Arc::clone(&now);
+
+        let bitcoin_client = MockRpcApi::with_raw_txs(None, Some(0), None);
+        let mut graph_builder = graph_builder(&storage, &event_bus, bitcoin_client)
+            .with_min_confirmations(1)
+            .with_water_marks(WATER_MARKS)
+            .with_clock(move || *clock.lock().unwrap());
+
+        // Transactions without parents that are never mined, so they wait for confirmations.
+        let txs = (1..=WATER_MARKS.high as i32 + 1)
+            .map(|version| YuvTransaction {
+                bitcoin_tx: Transaction {
+                    version,
+                    lock_time: PackedLockTime::ZERO,
+                    input: vec![],
+                    output: vec![],
+                },
+                tx_type: YuvTxType::default(),
+            })
+            .collect::<Vec<_>>();
+
+        let (up_to_high, over_high) = txs.split_at(WATER_MARKS.high);
+
+        graph_builder.attach_txs(up_to_high).await?;
+        graph_builder.handle_backpressure().await;
+        assert!(!graph_builder.checkers_paused);
+        assert!(
+            pool_events.is_empty(),
+            "checkers mustn't be paused at the mark"
+        );
+
+        graph_builder.attach_txs(over_high).await?;
+        graph_builder.handle_backpressure().await;
+        assert!(graph_builder.checkers_paused);
+        assert!(matches!(
+            pool_events.recv().await?,
+            TxCheckerPoolMessage::Pause
+        ));
+
+        // Still over the low-water mark, so the pause is not sent again.
+        graph_builder.handle_backpressure().await;
+        assert!(pool_events.is_empty());
+
+        *now.lock().unwrap() += DURATION_ONE_DAY + Duration::from_secs(1);
+        graph_builder.handle_cleanup().await?;
+        graph_builder.handle_backpressure().await;
+        assert!(!graph_builder.checkers_paused);
+        assert!(matches!(
+            pool_events.recv().await?,
+            TxCheckerPoolMessage::Resume
+        ));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_checkers_are_paused_by_txs_waiting_for_parents() -> eyre::Result<()> {
+        const WATER_MARKS: WaterMarks = WaterMarks { high: 1, low: 0 };
+
+        let storage = LevelDB::in_memory()?;
+
+        let event_bus = event_bus();
+        let pool_events = event_bus.subscribe::<TxCheckerPoolMessage>();
+
+        let mut graph_builder =
+            graph_builder(&storage, &event_bus, MockRpcApi::new()).with_water_marks(WATER_MARKS);
+
+        let parent = YuvTransaction {
+            bitcoin_tx: Transaction {
+                version: 1,
+                lock_time: PackedLockTime::ZERO,
+                input: vec![],
+                output: vec![],
+            },
+            tx_type: YuvTxType::default(),
+        };
+
+        // Children of the parent which is still being checked.
+        let children = (0..=WATER_MARKS.high as u32)
+            .map(|vout| YuvTransaction {
+                bitcoin_tx: Transaction {
+                    version: 1,
+                    lock_time: PackedLockTime::ZERO,
+                    input: vec![bitcoin::TxIn {
+                        previous_output: bitcoin::OutPoint::new(parent.bitcoin_tx.txid(), vout),
+                        script_sig: bitcoin::Script::default(),
+                        sequence: Sequence(0),
+                        witness: Witness::default(),
+                    }],
+                    output: vec![],
+                },
+                tx_type: YuvTxType::Transfer {
+                    input_proofs: BTreeMap::from([(0, DUMMY_PIXEL_PROOF.clone())]),
+                    output_proofs: Default::default(),
+                },
+            })
+            .collect::<Vec<_>>();
+
+        graph_builder.attach_txs(&children).await?;
+        graph_builder.handle_backpressure().await;
+        assert_eq!(graph_builder.stored_txs.len(), children.len());
+        assert!(graph_builder.checkers_paused);
+        assert!(matches!(
+            pool_events.recv().await?,
+            TxCheckerPoolMessage::Pause
+        ));
+
+        // The parent checked before the pause attaches the children.
+        graph_builder.attach_txs(&[parent]).await?;
+        assert!(graph_builder.stored_txs.is_empty());
+        for child in &children {
+            assert!(storage
+                .get_yuv_tx(&child.bitcoin_tx.txid())
+                .await?
+                .is_some());
+        }
+
+        graph_builder.handle_backpressure().await;
+        assert!(!graph_builder.checkers_paused);
+        assert!(matches!(
+            pool_events.recv().await?,
+            TxCheckerPoolMessage::Resume
+        ));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_tx_is_attached_only_after_min_confirmations() -> eyre::Result<()> {
+        const MIN_CONFIRMATIONS: u8 = 3;
+
+        let storage = LevelDB::in_memory().unwrap();
+
+        let event_bus = event_bus();
+
+        let confirmations = Arc::new(AtomicU32::new(MIN_CONFIRMATIONS as u32 - 1));
+
+        let current_confirmations = Arc::clone(&confirmations);
+        let bitcoin_client =
+            raw_txs_client(move |_| Ok(current_confirmations.load(Ordering::SeqCst)));
+
+        let mut graph_builder = graph_builder(&storage, &event_bus, bitcoin_client)
+            .with_min_confirmations(MIN_CONFIRMATIONS);
+
+        let tx1 = YuvTransaction {
+            bitcoin_tx: Transaction {
+                version: 1,
+                lock_time: PackedLockTime::ZERO,
+                input: vec![],
+                output: vec![],
+            },
+
+            tx_type: YuvTxType::default(),
+        };
+        storage.put_yuv_tx(tx1.clone()).await?;
+
+        let tx2 = YuvTransaction {
+            bitcoin_tx: Transaction {
+                version: 2,
+                lock_time: PackedLockTime::ZERO,
+                input: vec![bitcoin::TxIn {
+                    previous_output: bitcoin::OutPoint::new(tx1.bitcoin_tx.txid(), 0),
+                    script_sig: bitcoin::Script::default(),
+                    sequence: Sequence(0),
+                    witness: Witness::default(),
+                }],
+                output: vec![],
+            },
+
+            tx_type: YuvTxType::Transfer {
+                input_proofs: {
+                    let mut map = BTreeMap::new();
+
+                    map.insert(0, DUMMY_PIXEL_PROOF.clone());
+
+                    map
+                },
+                output_proofs: Default::default(),
+            },
+        };
+        let txid2 = tx2.bitcoin_tx.txid();
+
+        graph_builder.attach_txs(&[tx2]).await?;
+
+        assert!(
+            storage.get_yuv_tx(&txid2).await?.is_none(),
+            "Tx mustn't be attached with one confirmation less than required"
+        );
+        assert!(graph_builder.unconfirmed_txs.contains_key(&txid2));
+
+        confirmations.store(MIN_CONFIRMATIONS as u32, Ordering::SeqCst);
+        graph_builder.attach_confirmed_txs().await?;
+
+        assert!(
+            storage.get_yuv_tx(&txid2).await?.is_some(),
+            "Tx must be attached with required number of confirmations"
+        );
+        assert!(graph_builder.unconfirmed_txs.is_empty());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_spending_tx_is_indexed() -> eyre::Result<()> {
+        let storage = LevelDB::in_memory()?;
+
+        let event_bus = event_bus();
+        let mut graph_builder = graph_builder(&storage, &event_bus, MockRpcApi::new());
+
+        let parent = prune_test_transfer(20, &[]);
+        let parent_txid = parent.bitcoin_tx.txid();
+        storage.put_yuv_tx(parent).await?;
+
+        let outpoint = bitcoin::OutPoint::new(parent_txid, 0);
+        assert_eq!(storage.get_spending_tx(&outpoint).await?, None);
+
+        let child = prune_test_transfer(21, &[parent_txid]);
+        let child_txid = child.bitcoin_tx.txid();
+        graph_builder
+            .attach_txs(std::slice::from_ref(&child))
+            .await?;
+
+        assert!(storage.get_yuv_tx(&child_txid).await?.is_some());
+        assert_eq!(storage.get_spending_tx(&outpoint).await?, Some(child_txid));
+
+        // The output spent by the other transaction (e.g. after reorg) is indexed to it, and
+        // removing the replaced one doesn't affect the index.
+        let replacement = prune_test_transfer(22, &[parent_txid]);
+        let replacement_txid = replacement.bitcoin_tx.txid();
+        graph_builder
+            .attach_txs(std::slice::from_ref(&replacement))
+            .await?;
+
+        storage.delete_spending_tx(&child).await?;
+        assert_eq!(
+            storage.get_spending_tx(&outpoint).await?,
+            Some(replacement_txid)
+        );
+
+        storage.delete_spending_tx(&replacement).await?;
+        assert_eq!(storage.get_spending_tx(&outpoint).await?, None);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_dependency_graph() -> eyre::Result<()> {
+        let storage = LevelDB::in_memory()?;
+
+        let event_bus = event_bus();
+        let mut graph_builder = graph_builder(&storage, &event_bus, MockRpcApi::new());
+
+        // Chain of the transfers, whose first parent is never received.
+        let missing = Txid::from_inner([1; 32]);
+        let parent_tx = prune_test_transfer(1, &[missing]);
+        let parent = parent_tx.bitcoin_tx.txid();
+        let child_tx = prune_test_transfer(2, &[parent]);
+        let child = child_tx.bitcoin_tx.txid();
+
+        graph_builder.attach_txs(&[parent_tx, child_tx]).await?;
+
+        let graph = graph_builder.dependency_graph(usize::MAX);
+
+        let mut expected_nodes = vec![
+            GraphNode {
+                txid: missing,
+                status: GraphNodeStatus::Missing,
+            },
+            GraphNode {
+                txid: parent,
+                status: GraphNodeStatus::Pending,
+            },
+            GraphNode {
+                txid: child,
+                status: GraphNodeStatus::Pending,
+            },
+        ];
+        expected_nodes.sort_by_key(|node| node.txid);
+
+        let mut expected_edges = vec![(parent, missing), (child, parent)];
+        expected_edges.sort();
+
+        assert_eq!(
+            graph,
+            DependencyGraph {
+                nodes: expected_nodes.clone(),
+                edges: expected_edges,
+                truncated: false,
+            }
+        );
+
+        let graph = graph_builder.dependency_graph(2);
+        assert!(graph.truncated);
+        assert_eq!(graph.nodes, expected_nodes[..2]);
+        assert!(
+            graph.edges.iter().all(|(child, parent)| graph
+                .nodes
+                .iter()
+                .any(|node| node.txid == *child)
+                && graph.nodes.iter().any(|node| node.txid == *parent)),
+            "edges must be between the included nodes only"
+        );
+
+        Ok(())
+    }
+
+    fn prune_test_transfer(version: i32, parents: &[Txid]) -> YuvTransaction {
+        YuvTransaction {
+            bitcoin_tx: Transaction {
+                version,
+                lock_time: PackedLockTime::ZERO,
+                input: parents
+                    .iter()
+                    .map(|parent| bitcoin::TxIn {
+                        previous_output: bitcoin::OutPoint::new(*parent, 0),
+                        script_sig: bitcoin::Script::default(),
+                        sequence: Sequence(0),
+                        witness: Witness::default(),
+                    })
+                    .collect(),
+                output: vec![],
+            },
+
+            tx_type: YuvTxType::Transfer {
+                input_proofs: (0..parents.len() as u32)
+                    .map(|input| (input, DUMMY_PIXEL_PROOF.clone()))
+                    .collect(),
+                output_proofs: BTreeMap::from([(0, DUMMY_PIXEL_PROOF.clone())]),
+            },
+        }
+    }
+
+    #[tokio::test]
+    async fn test_prune_spent_transfers() -> eyre::Result<()> {
+        const RETENTION_BLOCKS: u32 = 6;
+
+        let storage = LevelDB::in_memory().unwrap();
+
+        let event_bus = event_bus();
+
+        let spent = prune_test_transfer(10, &[]);
+        let unspent = prune_test_transfer(11, &[]);
+        let shallow = prune_test_transfer(12, &[]);
+        let pending_parent = prune_test_transfer(13, &[]);
+        let announcement = YuvTransaction {
+            bitcoin_tx: Transaction {
+                version: 14,
+                lock_time: PackedLockTime::ZERO,
+                input: vec![],
+                output: vec![],
+            },
+            tx_type: YuvTxType::Announcement(
+                yuv_types::announcements::FreezeAnnouncement::new(bitcoin::OutPoint::new(
+                    spent.bitcoin_tx.txid(),
+                    0,
+                ))
+                .into(),
+            ),
+        };
+
+        let unspent_txid = unspent.bitcoin_tx.txid();
+        let shallow_txid = shallow.bitcoin_tx.txid();
+
+        let mut bitcoin_client = raw_txs_client(move |txid| {
+            Ok(if txid == shallow_txid {
+                RETENTION_BLOCKS - 1
+            } else {
+                RETENTION_BLOCKS
+            })
+        });
+        bitcoin_client
+            .expect_call::<serde_json::Value>()
+            .withf(|method, _| method == "gettxout")
+            .returning(move |_, args| {
+                let txid: Txid = serde_json::from_value(args[0].clone()).unwrap();
+                if txid != unspent_txid {
+                    return Ok(serde_json::Value::Null);
+                }
+
+                Ok(serde_json::to_value(GetTxOutResult {
+                    bestblock: bitcoin::BlockHash::all_zeros(),
+                    confirmations: RETENTION_BLOCKS,
+                    value: bitcoin::Amount::ZERO,
+                    script_pub_key: GetRawTransactionResultVoutScriptPubKey {
+                        asm: String::new(),
+                        hex: vec![],
+                        req_sigs: None,
+                        type_: None,
+                        addresses: None,
+                    },
+                    coinbase: false,
+                })
+                .unwrap())
+            });
+
+        let mut graph_builder = graph_builder(&storage, &event_bus, bitcoin_client);
+
+        let txs = [
+            spent.clone(),
+            unspent,
+            shallow,
+            pending_parent.clone(),
+            announcement,
+        ];
+        let txids = txs
+            .iter()
+            .map(|tx| tx.bitcoin_tx.txid())
+            .collect::<Vec<_>>();
+        for tx in txs {
+            storage.put_yuv_tx(tx).await?;
+        }
+        storage.put_page(0, txids.clone()).await?;
+        storage.put_pages_number(0).await?;
+
+        // The child is waiting for the other parent, which is not attached yet.
+        let pending_child =
+            prune_test_transfer(15, &[pending_parent.bitcoin_tx.txid(), Txid::all_zeros()]);
+        graph_builder.attach_txs(&[pending_child]).await?;
+
+        assert_eq!(graph_builder.prune_attached_txs(RETENTION_BLOCKS).await?, 1);
+
+        assert!(storage
+            .get_yuv_tx(&spent.bitcoin_tx.txid())
+            .await?
+            .is_none());
+        for txid in &txids[1..] {
+            assert!(
+                storage.get_yuv_tx(txid).await?.is_some(),
+                "Tx {txid} must be kept"
+            );
+        }
+
+        // The pruned transaction is removed from the page.
+        assert_eq!(storage.get_page_by_num(0).await?, Some(txids[1..].to_vec()));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_prune_compacts_pages_and_skips_failed_txs() -> eyre::Result<()> {
+        const RETENTION_BLOCKS: u32 = 6;
+        const TX_PER_PAGE: u64 = 2;
+
+        let storage = LevelDB::in_memory().unwrap();
+
+        let event_bus = event_bus();
+
+        let txs = (30..35)
+            .map(|version| prune_test_transfer(version, &[]))
+            .collect::<Vec<_>>();
+        let txids = txs
+            .iter()
+            .map(|tx| tx.bitcoin_tx.txid())
+            .collect::<Vec<_>>();
+
+        // The first transaction can't be found by the Bitcoin node, the rest are spent.
+        let failed_txid = txids[0];
+        let already_pruned =
+            Txid::from_str("0000000000000000000000000000000000000000000000000000000000000001")?;
+
+        let mut bitcoin_client = raw_txs_client(move |txid| {
+            if txid == failed_txid {
+                return Err(bitcoin_client::Error::UnexpectedStructure);
+            }
+
+            Ok(RETENTION_BLOCKS)
+        });
+        bitcoin_client
+            .expect_call::<serde_json::Value>()
+            .withf(|method, _| method == "gettxout")
+            .returning(|_, _| Ok(serde_json::Value::Null));
+
+        let graph_builder = GraphBuilder {
+            tx_per_page: TX_PER_PAGE,
+            ..graph_builder(&storage, &event_bus, bitcoin_client)
+        };
+
+        for tx in txs {
+            storage.put_yuv_tx(tx).await?;
+        }
+        storage.put_page(0, vec![txids[0], already_pruned]).await?;
+        storage.put_page(1, vec![txids[1], txids[2]]).await?;
+        storage.put_page(2, vec![txids[3], txids[4]]).await?;
+        storage.put_pages_number(2).await?;
+
+        // The transaction which state can't be got is kept.
+        assert_eq!(graph_builder.prune_attached_txs(RETENTION_BLOCKS).await?, 4);
+        assert!(storage.get_yuv_tx(&failed_txid).await?.is_some());
+
+        assert_eq!(storage.get_page_by_num(0).await?, Some(vec![failed_txid]));
+        assert_eq!(storage.get_pages_number().await?, Some(0));
+        assert_eq!(storage.get_page_by_num(1).await?, None);
+        assert_eq!(storage.get_page_by_num(2).await?, None);
+
+        Ok(())
+    }
 }
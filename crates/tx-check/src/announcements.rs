@@ -1,6 +1,10 @@
 use bitcoin::Txid;
+use yuv_pixels::Chroma;
 use yuv_storage::{ChromaInfoStorage, FrozenTxsStorage, InvalidTxsStorage, TransactionsStorage};
-use yuv_types::announcements::{ChromaAnnouncement, FreezeAnnouncement, IssueAnnouncement};
+use yuv_types::announcements::{
+    ChromaAnnouncement, FreezeAnnouncement, FreezeToggle, IssueAnnouncement,
+};
+use yuv_types::{Announcement, GraphBuilderMessage, TxCheckerMessage, YuvTransaction, YuvTxType};
 
 use crate::TxCheckerWorker;
 
@@ -9,9 +13,10 @@ where
     TS: TransactionsStorage + Clone + Send + Sync + 'static,
     SS: InvalidTxsStorage + FrozenTxsStorage + ChromaInfoStorage + Clone + Send + Sync + 'static,
 {
-    /// Update chroma announcements in storage.
+    /// Update chroma announcements in storage, recording the id of the announcement transaction.
     pub(crate) async fn add_chroma_announcements(
         &self,
+        txid: Txid,
         announcement: &ChromaAnnouncement,
     ) -> eyre::Result<()> {
         let chroma_info = self
@@ -38,6 +43,7 @@ where
             .put_chroma_info(
                 &announcement.chroma,
                 Some(announcement.clone()),
+                Some(txid),
                 total_supply,
             )
             .await?;
@@ -50,12 +56,33 @@ where
         Ok(())
     }
 
-    /// For each freeze toggle, update entry in freeze state storage.
+    /// Send the issuances that waited for the chroma announcement to be checked again.
+    pub(crate) async fn resubmit_pending_issuances(&self, chroma: &Chroma) -> eyre::Result<()> {
+        let txs = self.state_storage.take_pending_issuances(chroma).await?;
+        if txs.is_empty() {
+            return Ok(());
+        }
+
+        tracing::debug!(
+            "Resubmitting {} pending issuances of Chroma {}",
+            txs.len(),
+            chroma
+        );
+
+        self.event_bus
+            .send(TxCheckerMessage::NewTxs { txs, sender: None })
+            .await;
+
+        Ok(())
+    }
+
+    /// For each freeze toggle, update entry in freeze state storage. Returns whether the output
+    /// is frozen after the toggle.
     pub(crate) async fn update_freezes(
         &self,
         txid: Txid,
         freeze: &FreezeAnnouncement,
-    ) -> eyre::Result<()> {
+    ) -> eyre::Result<bool> {
         let freeze_outpoint = &freeze.freeze_outpoint();
 
         let mut freeze_entry = self
@@ -73,20 +100,153 @@ where
             freeze_entry.tx_ids,
         );
 
+        let is_frozen = freeze_entry.tx_ids.len() % 2 == 1;
+
         self.state_storage
             .put_frozen_tx(freeze_outpoint, freeze_entry.tx_ids)
             .await?;
 
+        Ok(is_frozen)
+    }
+
+    /// Revert the toggles of the freeze announcements disconnected by a reorg, and send the
+    /// toggles of the outputs and chromas which frozen state is changed by it.
+    pub(crate) async fn revert_freezes(&self, txs: Vec<YuvTransaction>) -> eyre::Result<()> {
+        let mut toggles = Vec::new();
+
+        for tx in txs {
+            let txid = tx.bitcoin_tx.txid();
+
+            let toggle = match &tx.tx_type {
+                YuvTxType::Announcement(Announcement::Freeze(freeze)) => {
+                    self.revert_freeze(txid, freeze).await?
+                }
+                YuvTxType::Announcement(Announcement::ChromaFreeze(freeze)) => {
+                    self.revert_chroma_freeze(txid, &freeze.chroma).await?
+                }
+                _ => None,
+            };
+
+            toggles.extend(toggle);
+        }
+
+        if !toggles.is_empty() {
+            self.event_bus
+                .send(GraphBuilderMessage::CheckedFreezes(toggles))
+                .await;
+        }
+
         Ok(())
     }
 
+    /// Remove the freeze from the freezes of the output. Returns the toggle if the output is
+    /// unfrozen or frozen back by it.
+    async fn revert_freeze(
+        &self,
+        txid: Txid,
+        freeze: &FreezeAnnouncement,
+    ) -> eyre::Result<Option<FreezeToggle>> {
+        let outpoint = freeze.freeze_outpoint();
+
+        let Some(mut freeze_entry) = self.state_storage.get_frozen_tx(&outpoint).await? else {
+            return Ok(None);
+        };
+
+        let was_frozen = freeze_entry.is_frozen();
+        freeze_entry
+            .tx_ids
+            .retain(|freeze_txid| *freeze_txid != txid);
+        let is_frozen = freeze_entry.is_frozen();
+
+        if freeze_entry.tx_ids.is_empty() {
+            self.state_storage.delete_frozen_tx(&outpoint).await?;
+        } else {
+            self.state_storage
+                .put_frozen_tx(&outpoint, freeze_entry.tx_ids)
+                .await?;
+        }
+
+        if was_frozen == is_frozen {
+            return Ok(None);
+        }
+
+        tracing::info!(
+            "Output {} is {} after the reorg of freeze tx {}",
+            outpoint,
+            if is_frozen { "frozen" } else { "unfrozen" },
+            txid,
+        );
+
+        let chroma = self
+            .txs_storage
+            .get_yuv_tx(&outpoint.txid)
+            .await?
+            .and_then(|tx| {
+                tx.tx_type
+                    .output_proofs()
+                    .and_then(|proofs| proofs.get(&outpoint.vout))
+                    .map(|proof| proof.pixel().chroma)
+            });
+
+        Ok(chroma.map(|chroma| FreezeToggle {
+            txid,
+            chroma,
+            outpoint: Some(outpoint),
+            frozen: is_frozen,
+        }))
+    }
+
+    /// Remove the chroma-wide freeze from the freezes of the chroma. Returns the toggle if the
+    /// outputs of the chroma are unfrozen or frozen back by it.
+    async fn revert_chroma_freeze(
+        &self,
+        txid: Txid,
+        chroma: &Chroma,
+    ) -> eyre::Result<Option<FreezeToggle>> {
+        let mut entries = self.state_storage.get_chroma_freezes(chroma).await?;
+        if !entries.iter().any(|entry| entry.txid == txid) {
+            return Ok(None);
+        }
+
+        let was_frozen = self.state_storage.is_chroma_frozen(chroma).await?;
+
+        entries.retain(|entry| entry.txid != txid);
+        let is_frozen = entries.last().is_some_and(|entry| entry.frozen);
+
+        self.state_storage
+            .put_chroma_freezes(chroma, entries)
+            .await?;
+        self.state_storage
+            .put_chroma_frozen(chroma, is_frozen)
+            .await?;
+
+        if was_frozen == is_frozen {
+            return Ok(None);
+        }
+
+        tracing::info!(
+            "Chroma {} is {} after the reorg of freeze tx {}",
+            chroma,
+            if is_frozen { "frozen" } else { "unfrozen" },
+            txid,
+        );
+
+        Ok(Some(FreezeToggle {
+            txid,
+            chroma: *chroma,
+            outpoint: None,
+            frozen: is_frozen,
+        }))
+    }
+
     pub(crate) async fn update_supply(&self, issue: &IssueAnnouncement) -> eyre::Result<()> {
         if let Some(chroma_info) = self.state_storage.get_chroma_info(&issue.chroma).await? {
             self.state_storage
                 .put_chroma_info(
                     &issue.chroma,
                     chroma_info.announcement,
-                    chroma_info.total_supply + issue.amount,
+                    chroma_info.announcement_txid,
+                    chroma_info.total_supply.saturating_add(issue.amount),
                 )
                 .await?;
 
@@ -94,7 +254,7 @@ where
         }
 
         self.state_storage
-            .put_chroma_info(&issue.chroma, None, issue.amount)
+            .put_chroma_info(&issue.chroma, None, None, issue.amount)
             .await?;
 
         tracing::debug!("Updated supply for chroma {}", issue.chroma);
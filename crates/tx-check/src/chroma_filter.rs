@@ -0,0 +1,66 @@
+use std::collections::HashSet;
+use std::sync::{Arc, RwLock};
+
+use yuv_pixels::Chroma;
+use yuv_types::YuvTransaction;
+
+/// Lists of the chromas which transactions are checked by the node.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ChromaLists {
+    /// If set, only the transactions of these chromas are checked.
+    pub allowlist: Option<HashSet<Chroma>>,
+    /// Transactions of these chromas are not checked, even if they're in the allowlist.
+    pub denylist: HashSet<Chroma>,
+}
+
+impl ChromaLists {
+    pub fn is_allowed(&self, chroma: &Chroma) -> bool {
+        let is_allowlisted = match &self.allowlist {
+            Some(allowlist) => allowlist.contains(chroma),
+            None => true,
+        };
+
+        is_allowlisted && !self.denylist.contains(chroma)
+    }
+}
+
+/// Filter of the issuances and transfers by their chromas, which is shared by the checkers and
+/// could be updated while they're running, e.g. on reload of the node's config.
+///
+/// Announcements are not filtered, as they're confirmed on-chain anyway.
+#[derive(Clone, Debug, Default)]
+pub struct ChromaFilter(Arc<RwLock<ChromaLists>>);
+
+impl ChromaFilter {
+    pub fn new(lists: ChromaLists) -> Self {
+        Self(Arc::new(RwLock::new(lists)))
+    }
+
+    /// Replace the lists, so the next transactions are filtered by the new ones.
+    pub fn update(&self, lists: ChromaLists) {
+        *self.0.write().expect("chroma filter lock is poisoned") = lists;
+    }
+
+    /// Get the current lists.
+    pub fn lists(&self) -> ChromaLists {
+        self.0
+            .read()
+            .expect("chroma filter lock is poisoned")
+            .clone()
+    }
+
+    /// Check if all the chromas of the transaction are allowed. The transaction is allowed
+    /// if it has no chromas, e.g. it moves only the empty pixels.
+    pub fn is_tx_allowed(&self, tx: &YuvTransaction) -> bool {
+        let lists = self.0.read().expect("chroma filter lock is poisoned");
+
+        if lists.allowlist.is_none() && lists.denylist.is_empty() {
+            return true;
+        }
+
+        tx.tx_type
+            .chromas()
+            .iter()
+            .all(|chroma| lists.is_allowed(chroma))
+    }
+}
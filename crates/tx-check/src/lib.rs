@@ -1,16 +1,27 @@
 #![doc = include_str!("../README.md")]
 
+mod chroma_filter;
+pub use chroma_filter::{ChromaFilter, ChromaLists};
+
 mod errors;
-pub use errors::CheckError;
+pub use errors::TxCheckerError;
+
+pub use yuv_verify::{
+    check_coinbase_maturity, check_transaction, check_transaction_collect_errors, CheckError,
+    ProofErrors, ProofKind, COINBASE_MATURITY,
+};
+
+mod policy;
+pub use policy::{check_proof_kinds, check_tx_size};
 
-mod isolated_checks;
-pub use isolated_checks::check_transaction;
+mod pause;
+pub use pause::CheckerPause;
 
 mod worker;
 pub use worker::{Config, TxCheckerWorker};
 
 mod worker_pool;
-pub use worker_pool::TxCheckerWorkerPool;
+pub use worker_pool::{AliveWorkers, TxCheckerWorkerPool};
 
 mod announcements;
 
@@ -0,0 +1,40 @@
+use std::sync::Arc;
+
+use tokio::sync::watch;
+
+/// Flag shared by the [`TxCheckerWorker`]s of the pool, which stops them from taking new
+/// transactions to check while the graph builder is backed up.
+///
+/// [`TxCheckerWorker`]: crate::TxCheckerWorker
+#[derive(Clone, Debug)]
+pub struct CheckerPause(Arc<watch::Sender<bool>>);
+
+impl CheckerPause {
+    /// Stop the workers from taking new transactions. The ones being checked
+    /// are finished.
+    pub fn pause(&self) {
+        self.0.send_replace(true);
+    }
+
+    pub fn resume(&self) {
+        self.0.send_replace(false);
+    }
+
+    pub fn is_paused(&self) -> bool {
+        *self.0.borrow()
+    }
+
+    /// Subscribe to the changes of the flag, so the paused worker is woken up
+    /// when it's resumed.
+    pub(crate) fn subscribe(&self) -> watch::Receiver<bool> {
+        self.0.subscribe()
+    }
+}
+
+impl Default for CheckerPause {
+    fn default() -> Self {
+        let (sender, _) = watch::channel(false);
+
+        Self(Arc::new(sender))
+    }
+}
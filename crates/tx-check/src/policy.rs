@@ -0,0 +1,59 @@
+use bitcoin::Transaction;
+use yuv_types::YuvTxType;
+use yuv_verify::ProofKind;
+
+use crate::errors::TxCheckerError;
+
+/// Checks that the transaction doesn't have more inputs or outputs than the limits, if they're
+/// set.
+///
+/// Standardness rules bound the transactions relayed by Bitcoin nodes, but not the ones with
+/// the proofs provided directly to the checker, so it's done before any per-proof work.
+pub fn check_tx_size(
+    bitcoin_tx: &Transaction,
+    max_inputs: Option<usize>,
+    max_outputs: Option<usize>,
+) -> Result<(), TxCheckerError> {
+    let count = bitcoin_tx.input.len();
+    if let Some(limit) = max_inputs.filter(|limit| count > *limit) {
+        return Err(TxCheckerError::TooManyInputs { count, limit });
+    }
+
+    let count = bitcoin_tx.output.len();
+    if let Some(limit) = max_outputs.filter(|limit| count > *limit) {
+        return Err(TxCheckerError::TooManyOutputs { count, limit });
+    }
+
+    Ok(())
+}
+
+/// Checks that all the proofs of the transaction are of the allowed kinds.
+///
+/// Cheap enough to be done before the proofs verification.
+pub fn check_proof_kinds(
+    tx_type: &YuvTxType,
+    allowed_kinds: &[ProofKind],
+) -> Result<(), TxCheckerError> {
+    let (input_proofs, output_proofs) = match tx_type {
+        YuvTxType::Issue { output_proofs, .. } => (None, output_proofs.as_ref()),
+        YuvTxType::Transfer {
+            input_proofs,
+            output_proofs,
+        } => (Some(input_proofs), Some(output_proofs)),
+        YuvTxType::Announcement(_) => return Ok(()),
+    };
+
+    for proof in input_proofs
+        .into_iter()
+        .chain(output_proofs)
+        .flat_map(|proofs| proofs.values())
+    {
+        let kind = ProofKind::from(proof);
+
+        if !allowed_kinds.contains(&kind) {
+            return Err(TxCheckerError::DisallowedProofType(kind));
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,144 @@
+//! Keys, transactions and the worker shared by the tests of the checker, built on the
+//! transactions of [`yuv_types::mocks`].
+
+use bitcoin::{
+    secp256k1::{Secp256k1, SecretKey},
+    OutPoint, PackedLockTime, Transaction, TxOut,
+};
+use event_bus::{EventBus, Receiver};
+use yuv_pixels::{Chroma, Pixel};
+use yuv_storage::LevelDB;
+use yuv_types::{
+    announcements::{
+        ChromaAnnouncement, ChromaFreezeAnnouncement, FreezeAnnouncement, FreezeToggle,
+    },
+    mocks, Announcement, ControllerMessage, GraphBuilderMessage, TxCheckerMessage, YuvTransaction,
+    YuvTxType,
+};
+
+use crate::{ChromaFilter, Config, TxCheckerWorker};
+
+pub(super) const AMOUNT: u128 = 1000;
+
+pub(super) const ISSUER_KEY: [u8; 32] = [3; 32];
+pub(super) const STRANGER_KEY: [u8; 32] = [4; 32];
+
+/// Secret key and the chroma it issues.
+pub(super) fn signer(key: [u8; 32]) -> (SecretKey, Chroma) {
+    let secret = SecretKey::from_slice(&key).unwrap();
+    let chroma = Chroma::new(secret.x_only_public_key(&Secp256k1::new()).0);
+
+    (secret, chroma)
+}
+
+/// Event bus with the channels the worker sends to.
+pub(super) fn event_bus() -> EventBus {
+    let mut event_bus = EventBus::default();
+    event_bus.register::<TxCheckerMessage>(Some(100));
+    event_bus.register::<GraphBuilderMessage>(Some(100));
+    event_bus.register::<ControllerMessage>(Some(100));
+
+    event_bus
+}
+
+/// Config of the checker with the optional checks disabled, which the tests
+/// override with the ones they need.
+pub(super) fn checker_config(event_bus: &EventBus, storage: &LevelDB) -> Config<LevelDB, LevelDB> {
+    Config {
+        full_event_bus: event_bus.clone(),
+        txs_storage: storage.clone(),
+        state_storage: storage.clone(),
+        allowed_proof_kinds: None,
+        require_chroma_announcement: false,
+        max_dependency_depth: None,
+        max_inputs: None,
+        max_outputs: None,
+        chroma_filter: ChromaFilter::default(),
+    }
+}
+
+/// Worker with the [`checker_config`].
+pub(super) fn worker(storage: &LevelDB, event_bus: &EventBus) -> TxCheckerWorker<LevelDB, LevelDB> {
+    TxCheckerWorker::from_config(&checker_config(event_bus, storage), None)
+}
+
+/// Freeze toggles sent to the graph builder since the last call.
+pub(super) async fn received_toggles(events: &Receiver<GraphBuilderMessage>) -> Vec<FreezeToggle> {
+    let mut toggles = Vec::new();
+    while !events.is_empty() {
+        if let GraphBuilderMessage::CheckedFreezes(checked) = events.recv().await.unwrap() {
+            toggles.extend(checked);
+        }
+    }
+
+    toggles
+}
+
+/// Issuance of [`AMOUNT`] tokens to the issuer.
+pub(super) fn issuance(issuer: &SecretKey) -> YuvTransaction {
+    mocks::issuance(issuer, AMOUNT, issuer.public_key(&Secp256k1::new()))
+}
+
+/// Transfer of the issued pixel back to the issuer. Versions differ the transfers of the same
+/// output.
+pub(super) fn transfer(
+    issuer: &SecretKey,
+    chroma: Chroma,
+    parent: &Transaction,
+    version: i32,
+) -> YuvTransaction {
+    mocks::transfer(
+        OutPoint::new(parent.txid(), 0),
+        issuer,
+        Pixel::new(AMOUNT, chroma),
+        issuer.public_key(&Secp256k1::new()),
+        version,
+    )
+}
+
+pub(super) fn chroma_announcement(issuer: &SecretKey, chroma: Chroma) -> YuvTransaction {
+    let announcement =
+        ChromaAnnouncement::new(chroma, "Token".to_string(), "TKN".to_string(), 0, 0, true)
+            .unwrap();
+
+    announcement_tx(issuer, announcement, 2)
+}
+
+pub(super) fn chroma_freeze(signer: &SecretKey, chroma: Chroma, frozen: bool) -> YuvTransaction {
+    let mut tx = announcement_tx(signer, ChromaFreezeAnnouncement::new(chroma, frozen), 2);
+    // Differ the txids of the freeze and the unfreeze.
+    tx.bitcoin_tx.lock_time = PackedLockTime(frozen as u32);
+
+    tx
+}
+
+pub(super) fn freeze(signer: &SecretKey, outpoint: OutPoint, version: i32) -> YuvTransaction {
+    announcement_tx(signer, FreezeAnnouncement::new(outpoint), version)
+}
+
+/// Transaction of the `announcement` signed by the `signer`, who is the issuer if it's the key
+/// of the announced chroma.
+fn announcement_tx(
+    signer: &SecretKey,
+    announcement: impl Into<Announcement>,
+    version: i32,
+) -> YuvTransaction {
+    let announcement = announcement.into();
+
+    YuvTransaction::new(
+        Transaction {
+            version,
+            lock_time: PackedLockTime::ZERO,
+            input: vec![mocks::signed_input(
+                OutPoint::null(),
+                signer,
+                signer.public_key(&Secp256k1::new()),
+            )],
+            output: vec![TxOut {
+                value: 0,
+                script_pubkey: announcement.to_script(),
+            }],
+        },
+        YuvTxType::Announcement(announcement),
+    )
+}
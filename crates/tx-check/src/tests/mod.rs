@@ -1,58 +1,884 @@
-use once_cell::sync::Lazy;
-use yuv_types::YuvTransaction;
-
-use crate::check_transaction;
-
-static VALID_MULTICHROMA_TRANSFER: Lazy<YuvTransaction> = Lazy::new(|| {
-    serde_json::from_str::<YuvTransaction>(include_str!("./assets/multichroma_valid_transfer.json"))
-        .expect("JSON was not well-formatted")
-});
-
-static VALID_SINGLECHROMA_TRANSFER: Lazy<YuvTransaction> = Lazy::new(|| {
-    serde_json::from_str::<YuvTransaction>(include_str!(
-        "./assets/singlechroma_valid_transfer.json"
-    ))
-    .expect("JSON was not well-formatted")
-});
-
-static INVALID_MULTICHROMA_TRANSFER: Lazy<YuvTransaction> = Lazy::new(|| {
-    serde_json::from_str::<YuvTransaction>(include_str!(
-        "./assets/multichroma_invalid_transfer.json"
-    ))
-    .expect("JSON was not well-formatted")
-});
-
-static INVALID_SINGLECHROMA_TRANSFER: Lazy<YuvTransaction> = Lazy::new(|| {
-    serde_json::from_str::<YuvTransaction>(include_str!(
-        "./assets/singlechroma_invalid_transfer.json"
-    ))
-    .expect("JSON was not well-formatted")
-});
-
-#[tokio::test]
-async fn test_tx_checker_validates_multichroma_transfer() {
-    let result = check_transaction(&VALID_MULTICHROMA_TRANSFER);
-
-    assert!(result.is_ok(), "expected the tx to pass the check");
+mod fixtures;
+
+mod chroma_announcement {
+    use bitcoin::OutPoint;
+    use yuv_storage::{
+        ChromaFreezeEntry, ChromaInfoStorage, FrozenTxsStorage, InvalidTxsStorage, LevelDB,
+        TransactionsStorage,
+    };
+    use yuv_types::{announcements::FreezeToggle, GraphBuilderMessage, TxCheckerMessage};
+
+    use super::fixtures::{
+        checker_config, chroma_announcement, chroma_freeze, event_bus, freeze, issuance,
+        received_toggles, signer, transfer, worker, AMOUNT, ISSUER_KEY, STRANGER_KEY,
+    };
+    use crate::{Config, TxCheckerWorker};
+
+    #[tokio::test]
+    async fn test_issuance_of_unconfirmed_chroma_is_kept_pending() {
+        let (issuer, chroma) = signer(ISSUER_KEY);
+
+        let storage = LevelDB::in_memory().unwrap();
+        let event_bus = event_bus();
+
+        let config = Config {
+            require_chroma_announcement: true,
+            ..checker_config(&event_bus, &storage)
+        };
+        let mut worker = TxCheckerWorker::from_config(&config, None);
+
+        let issue_tx = issuance(&issuer);
+        let txid = issue_tx.bitcoin_tx.txid();
+
+        worker
+            .check_txs(vec![issue_tx.clone()], None)
+            .await
+            .unwrap();
+
+        assert!(
+            storage.get_yuv_tx(&txid).await.unwrap().is_none(),
+            "expected the issuance of unconfirmed chroma not to be accepted"
+        );
+        assert!(
+            storage.get_invalid_tx(txid).await.unwrap().is_none(),
+            "expected the issuance of unconfirmed chroma not to be rejected"
+        );
+
+        let events = event_bus.subscribe::<TxCheckerMessage>();
+
+        // The announcement is indexed from the confirmed block.
+        storage
+            .put_chroma_announcement_height(&chroma, 100)
+            .await
+            .unwrap();
+        worker
+            .check_txs(vec![chroma_announcement(&issuer, chroma)], None)
+            .await
+            .unwrap();
+
+        let Ok(TxCheckerMessage::NewTxs { txs, .. }) = events.recv().await else {
+            panic!("expected the issuance to be resubmitted");
+        };
+        assert_eq!(
+            txs,
+            vec![issue_tx],
+            "expected the issuance to be resubmitted"
+        );
+        assert!(
+            storage
+                .take_pending_issuances(&chroma)
+                .await
+                .unwrap()
+                .is_empty(),
+            "expected no pending issuances to be left"
+        );
+
+        worker.check_txs(txs, None).await.unwrap();
+
+        assert!(
+            storage.get_yuv_tx(&txid).await.unwrap().is_some(),
+            "expected the issuance to be accepted after the chroma announcement"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_issuance_of_not_indexed_chroma_announcement_is_kept_pending() {
+        let (issuer, chroma) = signer(ISSUER_KEY);
+
+        let storage = LevelDB::in_memory().unwrap();
+        let event_bus = event_bus();
+
+        let config = Config {
+            require_chroma_announcement: true,
+            ..checker_config(&event_bus, &storage)
+        };
+        let mut worker = TxCheckerWorker::from_config(&config, None);
+
+        let events = event_bus.subscribe::<TxCheckerMessage>();
+
+        // The announcement is received from a peer before its block is indexed.
+        let announcement_tx = chroma_announcement(&issuer, chroma);
+        worker
+            .check_txs(vec![announcement_tx.clone()], None)
+            .await
+            .unwrap();
+
+        let issue_tx = issuance(&issuer);
+        let txid = issue_tx.bitcoin_tx.txid();
+        worker
+            .check_txs(vec![issue_tx.clone()], None)
+            .await
+            .unwrap();
+
+        assert!(
+            storage.get_yuv_tx(&txid).await.unwrap().is_none(),
+            "expected the issuance of not indexed chroma announcement not to be accepted"
+        );
+        assert!(
+            storage.get_invalid_tx(txid).await.unwrap().is_none(),
+            "expected the issuance of not indexed chroma announcement not to be rejected"
+        );
+        assert!(events.is_empty());
+
+        // The indexer stores the height of the announcement and sends it to be checked again.
+        storage
+            .put_chroma_announcement_height(&chroma, 100)
+            .await
+            .unwrap();
+        worker.check_txs(vec![announcement_tx], None).await.unwrap();
+
+        let Ok(TxCheckerMessage::NewTxs { txs, .. }) = events.recv().await else {
+            panic!("expected the issuance to be resubmitted");
+        };
+        assert_eq!(
+            txs,
+            vec![issue_tx],
+            "expected the issuance to be resubmitted"
+        );
+
+        worker.check_txs(txs, None).await.unwrap();
+
+        assert!(
+            storage.get_yuv_tx(&txid).await.unwrap().is_some(),
+            "expected the issuance to be accepted after the announcement is indexed"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_chroma_announcement_txid_is_stored() {
+        let (issuer, chroma) = signer(ISSUER_KEY);
+        let (other_issuer, other_chroma) = signer(STRANGER_KEY);
+
+        let storage = LevelDB::in_memory().unwrap();
+        let event_bus = event_bus();
+        let mut worker = worker(&storage, &event_bus);
+
+        let announcement_tx = chroma_announcement(&issuer, chroma);
+        worker
+            .check_txs(vec![announcement_tx.clone()], None)
+            .await
+            .unwrap();
+        // The id is kept when the supply of the chroma is updated.
+        worker
+            .check_txs(vec![issuance(&issuer)], None)
+            .await
+            .unwrap();
+
+        let chroma_info = storage.get_chroma_info(&chroma).await.unwrap().unwrap();
+        assert!(chroma_info.announcement.is_some());
+        assert_eq!(
+            chroma_info.announcement_txid,
+            Some(announcement_tx.bitcoin_tx.txid()),
+            "expected the id of the announcement tx to be stored"
+        );
+        assert_eq!(chroma_info.total_supply, AMOUNT);
+
+        // Issuance of the chroma without the announcement makes its info implicit.
+        worker
+            .check_txs(vec![issuance(&other_issuer)], None)
+            .await
+            .unwrap();
+
+        let chroma_info = storage
+            .get_chroma_info(&other_chroma)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(chroma_info.announcement, None);
+        assert_eq!(chroma_info.announcement_txid, None);
+    }
+
+    #[tokio::test]
+    async fn test_chroma_freeze_freezes_all_outputs() {
+        let (issuer, chroma) = signer(ISSUER_KEY);
+        let (stranger, _) = signer(STRANGER_KEY);
+
+        let storage = LevelDB::in_memory().unwrap();
+        let event_bus = event_bus();
+        let mut worker = worker(&storage, &event_bus);
+
+        let issue_tx = issuance(&issuer);
+        worker
+            .check_txs(vec![issue_tx.clone()], None)
+            .await
+            .unwrap();
+
+        // Only the issuer can freeze the chroma.
+        let stranger_freeze = chroma_freeze(&stranger, chroma, true);
+        worker
+            .check_txs(vec![stranger_freeze.clone()], None)
+            .await
+            .unwrap();
+
+        assert!(
+            storage
+                .get_invalid_tx(stranger_freeze.bitcoin_tx.txid())
+                .await
+                .unwrap()
+                .is_some(),
+            "expected the chroma freeze not signed by the issuer to be rejected"
+        );
+        assert!(!storage.is_chroma_frozen(&chroma).await.unwrap());
+
+        worker
+            .check_txs(vec![chroma_freeze(&issuer, chroma, true)], None)
+            .await
+            .unwrap();
+        assert!(storage.is_chroma_frozen(&chroma).await.unwrap());
+
+        // The output isn't frozen by itself, but its chroma is.
+        let frozen_transfer = transfer(&issuer, chroma, &issue_tx.bitcoin_tx, 2);
+        worker
+            .check_txs(vec![frozen_transfer.clone()], None)
+            .await
+            .unwrap();
+
+        assert!(
+            storage
+                .get_invalid_tx(frozen_transfer.bitcoin_tx.txid())
+                .await
+                .unwrap()
+                .is_some(),
+            "expected the transfer of the frozen chroma to be rejected"
+        );
+
+        worker
+            .check_txs(vec![chroma_freeze(&issuer, chroma, false)], None)
+            .await
+            .unwrap();
+        assert!(!storage.is_chroma_frozen(&chroma).await.unwrap());
+
+        let transfer = transfer(&issuer, chroma, &issue_tx.bitcoin_tx, 3);
+        worker
+            .check_txs(vec![transfer.clone()], None)
+            .await
+            .unwrap();
+
+        assert!(
+            storage
+                .get_invalid_tx(transfer.bitcoin_tx.txid())
+                .await
+                .unwrap()
+                .is_none(),
+            "expected the transfer to be accepted after the chroma is unfrozen"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_chroma_freeze_checked_twice_is_recorded_once() {
+        let (issuer, chroma) = signer(ISSUER_KEY);
+
+        let storage = LevelDB::in_memory().unwrap();
+        let event_bus = event_bus();
+        let mut worker = worker(&storage, &event_bus);
+
+        let announcement = chroma_freeze(&issuer, chroma, true);
+        for _ in 0..2 {
+            worker
+                .check_txs(vec![announcement.clone()], None)
+                .await
+                .unwrap();
+        }
+
+        let freezes = storage.get_chroma_freezes(&chroma).await.unwrap();
+        assert_eq!(
+            freezes,
+            vec![ChromaFreezeEntry {
+                txid: announcement.bitcoin_tx.txid(),
+                frozen: true,
+            }]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_freeze_toggles_are_sent_to_graph_builder() {
+        let (issuer, chroma) = signer(ISSUER_KEY);
+
+        let storage = LevelDB::in_memory().unwrap();
+        let event_bus = event_bus();
+        let mut worker = worker(&storage, &event_bus);
+
+        // The frozen output must be attached to be toggled.
+        let issue_tx = issuance(&issuer);
+        storage.put_yuv_tx(issue_tx.clone()).await.unwrap();
+
+        let outpoint = OutPoint::new(issue_tx.bitcoin_tx.txid(), 0);
+        let txs = vec![
+            freeze(&issuer, outpoint, 1),
+            freeze(&issuer, outpoint, 2),
+            chroma_freeze(&issuer, chroma, true),
+        ];
+        let txids = txs
+            .iter()
+            .map(|tx| tx.bitcoin_tx.txid())
+            .collect::<Vec<_>>();
+
+        for tx in txs {
+            worker.check_txs(vec![tx], None).await.unwrap();
+        }
+
+        let events = event_bus.subscribe::<GraphBuilderMessage>();
+
+        assert_eq!(
+            received_toggles(&events).await,
+            vec![
+                FreezeToggle {
+                    txid: txids[0],
+                    chroma,
+                    outpoint: Some(outpoint),
+                    frozen: true,
+                },
+                FreezeToggle {
+                    txid: txids[1],
+                    chroma,
+                    outpoint: Some(outpoint),
+                    frozen: false,
+                },
+                FreezeToggle {
+                    txid: txids[2],
+                    chroma,
+                    outpoint: None,
+                    frozen: true,
+                },
+            ]
+        );
+    }
+}
+
+mod chroma_filter {
+    use std::collections::HashSet;
+
+    use yuv_storage::{InvalidTxsStorage, LevelDB, TransactionsStorage};
+    use yuv_types::ControllerMessage;
+
+    use super::fixtures::{checker_config, event_bus, issuance, signer, ISSUER_KEY, STRANGER_KEY};
+    use crate::{ChromaFilter, ChromaLists, Config, TxCheckerWorker};
+
+    #[tokio::test]
+    async fn test_tx_of_denylisted_chroma_is_dropped() {
+        let (issuer, chroma) = signer(ISSUER_KEY);
+        let (other_issuer, _) = signer(STRANGER_KEY);
+
+        let storage = LevelDB::in_memory().unwrap();
+        let event_bus = event_bus();
+
+        let chroma_filter = ChromaFilter::new(ChromaLists {
+            allowlist: None,
+            denylist: HashSet::from([chroma]),
+        });
+        let config = Config {
+            chroma_filter: chroma_filter.clone(),
+            ..checker_config(&event_bus, &storage)
+        };
+        let mut worker = TxCheckerWorker::from_config(&config, None);
+
+        let denylisted = issuance(&issuer);
+        let allowed = issuance(&other_issuer);
+        let denylisted_txid = denylisted.bitcoin_tx.txid();
+
+        worker
+            .check_txs(vec![denylisted.clone(), allowed.clone()], None)
+            .await
+            .unwrap();
+
+        assert!(
+            storage
+                .get_yuv_tx(&denylisted_txid)
+                .await
+                .unwrap()
+                .is_none(),
+            "expected the issuance of the denylisted chroma to be dropped"
+        );
+        assert!(storage
+            .get_invalid_tx(denylisted_txid)
+            .await
+            .unwrap()
+            .is_none());
+        assert!(storage
+            .get_yuv_tx(&allowed.bitcoin_tx.txid())
+            .await
+            .unwrap()
+            .is_some());
+
+        let controller_events = event_bus.subscribe::<ControllerMessage>();
+        let mut ignored = Vec::new();
+        while !controller_events.is_empty() {
+            if let ControllerMessage::IgnoredTxs(txids) = controller_events.recv().await.unwrap() {
+                ignored.extend(txids);
+            }
+        }
+        assert_eq!(ignored, vec![denylisted_txid]);
+
+        // The updated lists are applied to the next transactions.
+        chroma_filter.update(ChromaLists::default());
+        worker.check_txs(vec![denylisted], None).await.unwrap();
+
+        assert!(storage
+            .get_yuv_tx(&denylisted_txid)
+            .await
+            .unwrap()
+            .is_some());
+    }
+}
+
+mod dependency_depth {
+    use std::time::Duration;
+
+    use event_bus::EventBus;
+    use yuv_storage::{InvalidTxsStorage, LevelDB};
+    use yuv_types::{messages::p2p::Inventory, ControllerMessage, YuvTransaction};
+
+    use super::fixtures::{checker_config, event_bus, issuance, signer, transfer, ISSUER_KEY};
+    use crate::{worker::DependencyDepths, Config, TxCheckerWorker};
+
+    const MAX_DEPTH: u32 = 2;
+
+    fn worker(storage: &LevelDB, event_bus: &EventBus) -> TxCheckerWorker<LevelDB, LevelDB> {
+        let config = Config {
+            max_dependency_depth: Some(MAX_DEPTH),
+            ..checker_config(event_bus, storage)
+        };
+
+        TxCheckerWorker::from_config(&config, None)
+    }
+
+    /// Chain of transfers, which is received from the newest one, so each of them has the
+    /// missing parent.
+    fn chain() -> Vec<YuvTransaction> {
+        let (issuer, chroma) = signer(ISSUER_KEY);
+
+        let mut chain = vec![issuance(&issuer)];
+        for version in 2..6 {
+            let parent = &chain.last().unwrap().bitcoin_tx;
+            chain.push(transfer(&issuer, chroma, parent, version));
+        }
+
+        chain
+    }
+
+    #[tokio::test]
+    async fn test_too_deep_chain_of_missing_parents_is_rejected() {
+        let peer = "127.0.0.1:8333".parse().unwrap();
+
+        let storage = LevelDB::in_memory().unwrap();
+
+        let event_bus = event_bus();
+        let events = event_bus.subscribe::<ControllerMessage>();
+        let mut worker = worker(&storage, &event_bus);
+
+        let chain = chain();
+
+        for depth in 0..MAX_DEPTH {
+            let tx = &chain[chain.len() - 1 - depth as usize];
+            worker
+                .check_txs(vec![tx.clone()], Some(peer))
+                .await
+                .unwrap();
+
+            assert!(
+                storage
+                    .get_invalid_tx(tx.bitcoin_tx.txid())
+                    .await
+                    .unwrap()
+                    .is_none(),
+                "expected the transfer within the depth limit to be accepted"
+            );
+
+            let parent_txid = tx.bitcoin_tx.input[0].previous_output.txid;
+            match events.recv().await.unwrap() {
+                ControllerMessage::GetData { inv, receiver } => {
+                    assert_eq!(inv, vec![Inventory::Ytx(parent_txid)]);
+                    assert_eq!(receiver, peer);
+                }
+                event => panic!("Unexpected event: {event:?}"),
+            }
+        }
+
+        let too_deep_tx = &chain[chain.len() - 1 - MAX_DEPTH as usize];
+        worker
+            .check_txs(vec![too_deep_tx.clone()], Some(peer))
+            .await
+            .unwrap();
+
+        let invalid = storage
+            .get_invalid_tx_entry(too_deep_tx.bitcoin_tx.txid())
+            .await
+            .unwrap()
+            .expect("expected the transfer deeper than the limit to be rejected");
+        assert!(
+            invalid.reason.contains("deeper than 2"),
+            "{}",
+            invalid.reason
+        );
+
+        while !events.is_empty() {
+            let event = events.recv().await.unwrap();
+            assert!(
+                !matches!(event, ControllerMessage::GetData { .. }),
+                "expected the parents of the rejected transfer not to be requested"
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn test_depths_of_parents_expire() {
+        let peer = "127.0.0.1:8333".parse().unwrap();
+
+        let storage = LevelDB::in_memory().unwrap();
+
+        let event_bus = event_bus();
+        let mut worker = worker(&storage, &event_bus)
+            .with_dependency_depths(DependencyDepths::with_ttl(Duration::ZERO));
+
+        // The depths are forgotten right away, so the chain is never too deep.
+        for tx in chain().iter().skip(1).rev() {
+            worker
+                .check_txs(vec![tx.clone()], Some(peer))
+                .await
+                .unwrap();
+
+            assert!(
+                storage
+                    .get_invalid_tx(tx.bitcoin_tx.txid())
+                    .await
+                    .unwrap()
+                    .is_none(),
+                "expected the transfer with the expired depth to be accepted"
+            );
+        }
+    }
 }
 
-#[tokio::test]
-async fn test_tx_checker_validates_singlechroma_transfer() {
-    let result = check_transaction(&VALID_SINGLECHROMA_TRANSFER);
+mod freezes {
+    use bitcoin::OutPoint;
+    use yuv_storage::{
+        ChromaInfoStorage, FrozenTxsStorage, InvalidTxsStorage, LevelDB, TransactionsStorage,
+    };
+    use yuv_types::{announcements::FreezeToggle, GraphBuilderMessage};
+
+    use super::fixtures::{
+        chroma_freeze, event_bus, freeze, issuance, received_toggles, signer, transfer, worker,
+        ISSUER_KEY, STRANGER_KEY,
+    };
+
+    #[tokio::test]
+    async fn test_output_is_unfrozen_after_freeze_becomes_invalid() {
+        let (issuer, chroma) = signer(ISSUER_KEY);
+        let (stranger, _) = signer(STRANGER_KEY);
+
+        let storage = LevelDB::in_memory().unwrap();
+
+        let event_bus = event_bus();
+        let mut worker = worker(&storage, &event_bus);
+
+        let issue_tx = issuance(&issuer);
+        storage.put_yuv_tx(issue_tx.clone()).await.unwrap();
+
+        // The output is frozen by the freeze tx which is no longer valid, as none of its inputs
+        // is signed by the issuer.
+        let outpoint = OutPoint::new(issue_tx.bitcoin_tx.txid(), 0);
+        let invalid_freeze = freeze(&stranger, outpoint, 1);
+        let invalid_freeze_txid = invalid_freeze.bitcoin_tx.txid();
+        storage.put_yuv_tx(invalid_freeze).await.unwrap();
+        storage
+            .put_frozen_tx(&outpoint, vec![invalid_freeze_txid])
+            .await
+            .unwrap();
+
+        let events = event_bus.subscribe::<GraphBuilderMessage>();
+
+        let transfer = transfer(&issuer, chroma, &issue_tx.bitcoin_tx, 2);
+        worker
+            .check_txs(vec![transfer.clone()], None)
+            .await
+            .unwrap();
+
+        assert!(
+            storage
+                .get_invalid_tx(transfer.bitcoin_tx.txid())
+                .await
+                .unwrap()
+                .is_none(),
+            "expected the transfer of the unfrozen output to be accepted"
+        );
+        assert!(storage
+            .get_yuv_tx(&invalid_freeze_txid)
+            .await
+            .unwrap()
+            .is_none());
+
+        let toggles = received_toggles(&events).await;
+
+        assert_eq!(
+            toggles,
+            vec![FreezeToggle {
+                txid: invalid_freeze_txid,
+                chroma,
+                outpoint: Some(outpoint),
+                frozen: false,
+            }],
+            "expected the graph builder to be notified about the unfreeze"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_freezes_reorged_out_are_reverted() {
+        let (issuer, chroma) = signer(ISSUER_KEY);
+
+        let storage = LevelDB::in_memory().unwrap();
+        let event_bus = event_bus();
+        let mut worker = worker(&storage, &event_bus);
+
+        let issue_tx = issuance(&issuer);
+        storage.put_yuv_tx(issue_tx.clone()).await.unwrap();
 
-    assert!(result.is_ok(), "expected the tx to pass the check");
+        let outpoint = OutPoint::new(issue_tx.bitcoin_tx.txid(), 0);
+        let output_freeze = freeze(&issuer, outpoint, 1);
+        let chroma_freeze = chroma_freeze(&issuer, chroma, true);
+        let txids = [
+            output_freeze.bitcoin_tx.txid(),
+            chroma_freeze.bitcoin_tx.txid(),
+        ];
+
+        let reorged = vec![output_freeze, chroma_freeze];
+        for tx in reorged.clone() {
+            worker.check_txs(vec![tx], None).await.unwrap();
+        }
+        assert!(storage.is_chroma_frozen(&chroma).await.unwrap());
+
+        let events = event_bus.subscribe::<GraphBuilderMessage>();
+        received_toggles(&events).await;
+
+        worker.revert_freezes(reorged.clone()).await.unwrap();
+
+        assert!(storage.get_frozen_tx(&outpoint).await.unwrap().is_none());
+        assert!(storage
+            .get_chroma_freezes(&chroma)
+            .await
+            .unwrap()
+            .is_empty());
+        assert!(!storage.is_chroma_frozen(&chroma).await.unwrap());
+
+        assert_eq!(
+            received_toggles(&events).await,
+            vec![
+                FreezeToggle {
+                    txid: txids[0],
+                    chroma,
+                    outpoint: Some(outpoint),
+                    frozen: false,
+                },
+                FreezeToggle {
+                    txid: txids[1],
+                    chroma,
+                    outpoint: None,
+                    frozen: false,
+                },
+            ],
+            "expected the graph builder to be notified about the unfreezes"
+        );
+
+        // Once reverted, the freezes are applied again when confirmed in the new best chain.
+        for tx in reorged {
+            worker.check_txs(vec![tx], None).await.unwrap();
+        }
+        assert!(storage
+            .get_frozen_tx(&outpoint)
+            .await
+            .unwrap()
+            .is_some_and(|entry| entry.is_frozen()));
+        assert!(storage.is_chroma_frozen(&chroma).await.unwrap());
+    }
 }
 
-#[tokio::test]
-async fn test_tx_checker_fails_invalid_multichroma_transfer() {
-    let result = check_transaction(&INVALID_MULTICHROMA_TRANSFER);
+mod tx_size {
+    use bitcoin::{OutPoint, PackedLockTime, Transaction, TxIn, TxOut};
+    use yuv_storage::{InvalidTxsStorage, LevelDB};
+    use yuv_types::{
+        announcements::FreezeAnnouncement, Announcement, ProofMap, YuvTransaction, YuvTxType,
+    };
+
+    use super::fixtures::{checker_config, event_bus};
+    use crate::{check_tx_size, Config, TxCheckerError, TxCheckerWorker};
+
+    const LIMIT: usize = 3;
+
+    fn transaction(inputs: usize, outputs: usize) -> Transaction {
+        Transaction {
+            version: 2,
+            lock_time: PackedLockTime::ZERO,
+            input: vec![TxIn::default(); inputs],
+            output: vec![TxOut::default(); outputs],
+        }
+    }
+
+    #[test]
+    fn test_tx_at_limits_is_accepted() {
+        assert!(check_tx_size(&transaction(LIMIT, LIMIT), Some(LIMIT), Some(LIMIT)).is_ok());
+        assert!(check_tx_size(&transaction(LIMIT + 1, LIMIT + 1), None, None).is_ok());
+    }
+
+    #[test]
+    fn test_tx_over_limits_is_rejected() {
+        let result = check_tx_size(&transaction(LIMIT + 1, LIMIT), Some(LIMIT), Some(LIMIT));
+        assert!(
+            matches!(
+                result,
+                Err(TxCheckerError::TooManyInputs {
+                    count: 4,
+                    limit: LIMIT
+                })
+            ),
+            "expected too many inputs, got: {result:?}"
+        );
+
+        let result = check_tx_size(&transaction(LIMIT, LIMIT + 1), Some(LIMIT), Some(LIMIT));
+        assert!(
+            matches!(
+                result,
+                Err(TxCheckerError::TooManyOutputs {
+                    count: 4,
+                    limit: LIMIT
+                })
+            ),
+            "expected too many outputs, got: {result:?}"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_oversized_tx_is_marked_invalid() {
+        let storage = LevelDB::in_memory().unwrap();
+        let event_bus = event_bus();
+
+        let config = Config {
+            max_outputs: Some(LIMIT),
+            ..checker_config(&event_bus, &storage)
+        };
+        let mut worker = TxCheckerWorker::from_config(&config, None);
+
+        // Proofs are not checked, so the tx is rejected by its size only.
+        let yuv_tx = YuvTransaction::new(
+            transaction(1, LIMIT + 1),
+            YuvTxType::Transfer {
+                input_proofs: ProofMap::new(),
+                output_proofs: ProofMap::new(),
+            },
+        );
+
+        worker.check_txs(vec![yuv_tx.clone()], None).await.unwrap();
+
+        let entry = storage
+            .get_invalid_tx_entry(yuv_tx.bitcoin_tx.txid())
+            .await
+            .unwrap()
+            .expect("oversized tx should be marked invalid");
 
-    assert!(result.is_err(), "expected the tx to fail the check");
+        assert_eq!(
+            entry.reason,
+            TxCheckerError::TooManyOutputs {
+                count: LIMIT + 1,
+                limit: LIMIT
+            }
+            .to_string()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_oversized_announcement_is_marked_invalid() {
+        let storage = LevelDB::in_memory().unwrap();
+        let event_bus = event_bus();
+
+        let config = Config {
+            max_inputs: Some(LIMIT),
+            ..checker_config(&event_bus, &storage)
+        };
+        let mut worker = TxCheckerWorker::from_config(&config, None);
+
+        let announcement = FreezeAnnouncement::new(OutPoint::null());
+        let yuv_tx = YuvTransaction::new(
+            transaction(LIMIT + 1, 1),
+            YuvTxType::Announcement(Announcement::Freeze(announcement)),
+        );
+
+        worker.check_txs(vec![yuv_tx.clone()], None).await.unwrap();
+
+        let entry = storage
+            .get_invalid_tx_entry(yuv_tx.bitcoin_tx.txid())
+            .await
+            .unwrap()
+            .expect("oversized announcement should be marked invalid");
+
+        assert_eq!(
+            entry.reason,
+            TxCheckerError::TooManyInputs {
+                count: LIMIT + 1,
+                limit: LIMIT
+            }
+            .to_string()
+        );
+    }
 }
 
-#[tokio::test]
-async fn test_tx_checker_fails_invalid_singlechroma_transfer() {
-    let result = check_transaction(&INVALID_SINGLECHROMA_TRANSFER);
+mod proof_kinds {
+    use yuv_storage::{InvalidTxsStorage, LevelDB};
+
+    use super::fixtures::{checker_config, event_bus, issuance, signer, ISSUER_KEY};
+    use crate::{check_proof_kinds, Config, ProofKind, TxCheckerError, TxCheckerWorker};
+
+    #[test]
+    fn test_disallowed_proof_kind_is_rejected() {
+        let (issuer, _) = signer(ISSUER_KEY);
+        let tx = issuance(&issuer);
+
+        check_proof_kinds(&tx.tx_type, &[ProofKind::Sig]).expect("sig proofs should be allowed");
+
+        let result = check_proof_kinds(&tx.tx_type, &[ProofKind::EmptyPixel]);
+        assert!(
+            matches!(
+                result,
+                Err(TxCheckerError::DisallowedProofType(ProofKind::Sig))
+            ),
+            "expected sig proofs to be disallowed, got: {result:?}"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_tx_is_invalid_if_its_proof_kind_is_disallowed() {
+        let (issuer, _) = signer(ISSUER_KEY);
+
+        let storage = LevelDB::in_memory().unwrap();
+        let event_bus = event_bus();
+
+        let config = Config {
+            allowed_proof_kinds: Some(vec![ProofKind::EmptyPixel]),
+            ..checker_config(&event_bus, &storage)
+        };
+        let mut worker = TxCheckerWorker::from_config(&config, None);
+
+        // The issuance has the sig proof only.
+        let yuv_tx = issuance(&issuer);
+        worker.check_txs(vec![yuv_tx.clone()], None).await.unwrap();
+
+        let invalid_tx = storage
+            .get_invalid_tx(yuv_tx.bitcoin_tx.txid())
+            .await
+            .unwrap();
+
+        assert_eq!(
+            invalid_tx,
+            Some(yuv_tx.clone()),
+            "expected the tx with disallowed proofs to be marked invalid"
+        );
+
+        let entry = storage
+            .get_invalid_tx_entry(yuv_tx.bitcoin_tx.txid())
+            .await
+            .unwrap()
+            .expect("reason of rejection should be stored");
 
-    assert!(result.is_err(), "expected the tx to fail the check");
+        assert_eq!(
+            entry.reason,
+            TxCheckerError::DisallowedProofType(ProofKind::Sig).to_string()
+        );
+    }
 }
@@ -1,31 +1,60 @@
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, HashMap};
 use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 
 use bitcoin::{OutPoint, Txid};
-use event_bus::{typeid, EventBus};
+use event_bus::{typeid, EventBus, SendTimeoutError};
 use eyre::{eyre, Context, Result};
 use tokio_util::sync::CancellationToken;
 
-use yuv_pixels::PixelProof;
-use yuv_storage::{ChromaInfoStorage, FrozenTxsStorage, InvalidTxsStorage, TransactionsStorage};
+use yuv_metrics::METRICS;
+use yuv_pixels::{Chroma, PixelProof};
+use yuv_storage::{
+    ChromaFreezeEntry, ChromaInfoStorage, FrozenTxsStorage, InvalidTxsStorage, TransactionsStorage,
+};
 use yuv_types::announcements::{
-    ChromaAnnouncement, ChromaInfo, FreezeAnnouncement, IssueAnnouncement,
+    ChromaAnnouncement, ChromaFreezeAnnouncement, ChromaInfo, FreezeAnnouncement, FreezeToggle,
+    IssueAnnouncement,
 };
 use yuv_types::messages::p2p::Inventory;
 use yuv_types::{
     Announcement, ControllerMessage, GraphBuilderMessage, ProofMap, TxCheckerMessage,
     YuvTransaction, YuvTxType,
 };
-
-use crate::errors::CheckError;
-use crate::isolated_checks::{
-    check_issue_isolated, check_transfer_isolated, find_issuer_in_txinputs,
+use yuv_verify::{
+    check_issue_isolated, check_transfer_isolated, find_issuer_in_txinputs, CheckError, ProofKind,
 };
 
+use crate::chroma_filter::ChromaFilter;
+use crate::errors::TxCheckerError;
+use crate::pause::CheckerPause;
+use crate::policy::{check_proof_kinds, check_tx_size};
+
+/// Maximum time to wait for the graph builder's channel to have capacity before keeping the
+/// checked transactions to resend them later.
+const SEND_TIMEOUT: Duration = Duration::from_secs(1);
+const RESEND_PERIOD: Duration = Duration::from_secs(5);
+
 pub struct Config<TxsStorage, StateStorage> {
     pub full_event_bus: EventBus,
     pub txs_storage: TxsStorage,
     pub state_storage: StateStorage,
+    /// Kinds of proofs the checker accepts. If `None`, all kinds are accepted.
+    pub allowed_proof_kinds: Option<Vec<ProofKind>>,
+    /// Require the [`ChromaAnnouncement`] of the chroma to be indexed from a
+    /// confirmed block before accepting its issuance.
+    pub require_chroma_announcement: bool,
+    /// Max number of the missing parents in a chain the checker requests from
+    /// the peers before the transfer is rejected. Unlimited if `None`.
+    pub max_dependency_depth: Option<u32>,
+    /// Max number of the inputs of the transaction. Unlimited if `None`.
+    pub max_inputs: Option<usize>,
+    /// Max number of the outputs of the transaction. Unlimited if `None`.
+    pub max_outputs: Option<usize>,
+    /// Filter of the issuances and transfers by their chromas. Filtered out
+    /// transactions are ignored.
+    pub chroma_filter: ChromaFilter,
 }
 
 /// Async implementation of [`TxChecker`] for node implementation.
@@ -44,7 +73,43 @@ pub struct TxCheckerWorker<TxsStorage, StateStorage> {
     pub(crate) state_storage: StateStorage,
 
     /// Event bus for simplifying communication with services
-    event_bus: EventBus,
+    pub(crate) event_bus: EventBus,
+
+    /// Kinds of proofs the checker accepts. If `None`, all kinds are accepted.
+    allowed_proof_kinds: Option<Vec<ProofKind>>,
+
+    /// Require the [`ChromaAnnouncement`] of the chroma to be indexed from a
+    /// confirmed block before accepting its issuance. Otherwise, the issuance
+    /// is kept pending until the indexed announcement is checked.
+    require_chroma_announcement: bool,
+
+    /// Max number of the missing parents in a chain the checker requests
+    /// before the transfer is rejected. Unlimited if `None`.
+    max_dependency_depth: Option<u32>,
+
+    /// Max number of the inputs of the transaction, which is checked before
+    /// the proofs. Unlimited if `None`.
+    max_inputs: Option<usize>,
+
+    /// Max number of the outputs of the transaction, which is checked before
+    /// the proofs. Unlimited if `None`.
+    max_outputs: Option<usize>,
+
+    /// Depths of the transactions requested as the missing parents.
+    dependency_depths: DependencyDepths,
+
+    /// Filter of the issuances and transfers by their chromas. Transactions of
+    /// the filtered out chromas are neither stored nor sent further.
+    chroma_filter: ChromaFilter,
+
+    /// Checked transactions the graph builder didn't receive because its
+    /// channel was full. They're sent with the next checked ones or on the
+    /// resend tick.
+    unsent_checked_txs: Vec<YuvTransaction>,
+
+    /// Flag shared by the workers of the pool. New transactions are not taken
+    /// while it's set.
+    pause: CheckerPause,
 }
 
 impl<TS, SS> TxCheckerWorker<TS, SS>
@@ -56,7 +121,7 @@ where
         let event_bus = config
             .full_event_bus
             .extract(
-                &typeid![GraphBuilderMessage, ControllerMessage],
+                &typeid![GraphBuilderMessage, ControllerMessage, TxCheckerMessage],
                 &typeid![TxCheckerMessage],
             )
             .expect("event channels must be presented");
@@ -66,15 +131,42 @@ where
             event_bus,
             txs_storage: config.txs_storage.clone(),
             state_storage: config.state_storage.clone(),
+            allowed_proof_kinds: config.allowed_proof_kinds.clone(),
+            require_chroma_announcement: config.require_chroma_announcement,
+            max_dependency_depth: config.max_dependency_depth,
+            max_inputs: config.max_inputs,
+            max_outputs: config.max_outputs,
+            dependency_depths: DependencyDepths::default(),
+            chroma_filter: config.chroma_filter.clone(),
+            unsent_checked_txs: Vec::new(),
+            pause: CheckerPause::default(),
         }
     }
 
+    /// Share the depths of the requested parents with the other workers, as
+    /// a transaction and its parents could be checked by different ones.
+    pub(crate) fn with_dependency_depths(mut self, dependency_depths: DependencyDepths) -> Self {
+        self.dependency_depths = dependency_depths;
+        self
+    }
+
+    /// Share the pause flag with the other workers of the pool, see
+    /// [`CheckerPause`].
+    pub(crate) fn with_pause(mut self, pause: CheckerPause) -> Self {
+        self.pause = pause;
+        self
+    }
+
     pub async fn run(mut self, cancellation: CancellationToken) {
         let events = self.event_bus.subscribe::<TxCheckerMessage>();
+        let mut resend_timer = tokio::time::interval(RESEND_PERIOD);
+        let mut pause = self.pause.subscribe();
 
         loop {
+            let paused = *pause.borrow_and_update();
+
             tokio::select! {
-                event_received = events.recv() => {
+                event_received = events.recv(), if !paused => {
                     let Ok(event) = event_received else {
                         tracing::trace!(index = self.index, "All incoming events senders are dropped");
                         return;
@@ -88,6 +180,13 @@ where
                     tracing::trace!(index = self.index, "Cancellation received, stopping TxCheckerWorker");
                     return;
                 }
+                _ = resend_timer.tick(), if !self.unsent_checked_txs.is_empty() => {
+                    if let Err(err) = self.send_checked_txs().await {
+                        tracing::error!(index = self.index, "Failed to resend checked transactions: {}", err);
+                    }
+                }
+                // Wait for the resume, as no new transactions are taken until then.
+                _ = pause.changed(), if paused => {}
             }
         }
     }
@@ -98,6 +197,10 @@ where
                 .check_txs(txs, sender)
                 .await
                 .wrap_err("failed to check transactions")?,
+            TxCheckerMessage::ReorgedFreezes(txs) => self
+                .revert_freezes(txs)
+                .await
+                .wrap_err("failed to revert reorged freezes")?,
         }
 
         Ok(())
@@ -115,11 +218,24 @@ where
         let mut checked_txs = BTreeMap::new();
         let mut invalid_txs = Vec::new();
         let mut not_found_parents = Vec::new();
+        let mut ignored_txs = Vec::new();
 
         tracing::debug!("Checking txs: {:?}", txs);
+        METRICS.txs_checked.inc_by(txs.len() as u64);
 
         for tx in txs {
-            let is_valid = self
+            if !self.chroma_filter.is_tx_allowed(&tx) {
+                tracing::debug!(
+                    index = self.index,
+                    "Transaction {} is ignored, as its chroma is filtered out",
+                    tx.bitcoin_tx.txid()
+                );
+
+                ignored_txs.push(tx.bitcoin_tx.txid());
+                continue;
+            }
+
+            let verdict = self
                 .check_transaction(
                     tx.clone(),
                     &mut invalid_txs,
@@ -128,32 +244,45 @@ where
                 )
                 .await?;
 
+            // Parents of the transaction are requested already, if it has missing ones:
+            self.dependency_depths.remove(&tx.bitcoin_tx.txid());
+
             // There is no sense to put it into storage or mark as an invalid tx if it's an
             // announcement.
             if let YuvTxType::Announcement { .. } = &tx.tx_type {
                 continue;
             }
 
-            if !is_valid {
-                invalid_txs.push(tx.clone());
-                continue;
+            match verdict {
+                Verdict::Valid => {}
+                Verdict::Invalid(reason) => {
+                    invalid_txs.push((tx.clone(), reason));
+                    continue;
+                }
+                Verdict::Pending => continue,
             }
 
             checked_txs.insert(tx.bitcoin_tx.txid(), tx);
         }
 
-        // Send checked transactions to next worker:
-        if !checked_txs.is_empty() {
+        if !ignored_txs.is_empty() {
             self.event_bus
-                .send(GraphBuilderMessage::CheckedTxs(
-                    checked_txs.values().cloned().collect::<Vec<_>>(),
-                ))
+                .send(ControllerMessage::IgnoredTxs(ignored_txs))
                 .await;
         }
 
+        // Send checked transactions to next worker:
+        self.unsent_checked_txs.extend(checked_txs.into_values());
+        self.send_checked_txs().await?;
+
         // Notify about invalid transactions:
         if !invalid_txs.is_empty() {
-            let invalid_txs_ids = invalid_txs.iter().map(|tx| tx.bitcoin_tx.txid()).collect();
+            METRICS.txs_invalid.inc_by(invalid_txs.len() as u64);
+
+            let invalid_txs_ids = invalid_txs
+                .iter()
+                .map(|(tx, _reason)| tx.bitcoin_tx.txid())
+                .collect();
             self.event_bus
                 .send(ControllerMessage::InvalidTxs {
                     tx_ids: invalid_txs_ids,
@@ -184,15 +313,57 @@ where
         Ok(())
     }
 
+    /// Send checked transactions that weren't sent yet to the graph builder. If its channel is
+    /// still full after the timeout, they are kept to be sent later, so they aren't lost.
+    async fn send_checked_txs(&mut self) -> Result<()> {
+        if self.unsent_checked_txs.is_empty() {
+            return Ok(());
+        }
+
+        let txs = std::mem::take(&mut self.unsent_checked_txs);
+        let result = self
+            .event_bus
+            .send_timeout(GraphBuilderMessage::CheckedTxs(txs), SEND_TIMEOUT)
+            .await;
+
+        match result {
+            Ok(()) => Ok(()),
+            Err(SendTimeoutError::Timeout(GraphBuilderMessage::CheckedTxs(txs))) => {
+                tracing::warn!(
+                    index = self.index,
+                    count = txs.len(),
+                    "Graph builder is busy, checked transactions will be resent"
+                );
+                self.unsent_checked_txs = txs;
+
+                Ok(())
+            }
+            Err(err) => Err(eyre!("failed to send checked transactions: {err}")),
+        }
+    }
+
     /// Do the corresponding checks for the transaction based on its type.
     async fn check_transaction(
         &mut self,
         tx: YuvTransaction,
-        invalid_txs: &mut Vec<YuvTransaction>,
+        invalid_txs: &mut Vec<(YuvTransaction, String)>,
         checked_txs: &mut BTreeMap<Txid, YuvTransaction>,
         not_found_parents: &mut Vec<Txid>,
-    ) -> Result<bool> {
-        let is_valid = match &tx.tx_type {
+    ) -> Result<Verdict> {
+        if let Err(err) = self.check_limits(&tx) {
+            tracing::debug!(txid = %tx.bitcoin_tx.txid(), "Transaction is rejected: {}", err);
+            let verdict = Verdict::invalid(err);
+
+            // The announcement isn't checked further, so it's recorded as invalid here.
+            if let YuvTxType::Announcement(_) = &tx.tx_type {
+                self.handle_announcement_verdict(&tx, &verdict, invalid_txs)
+                    .await;
+            }
+
+            return Ok(verdict);
+        }
+
+        let verdict = match &tx.tx_type {
             YuvTxType::Issue {
                 announcement,
                 output_proofs,
@@ -220,7 +391,19 @@ where
             }
         };
 
-        Ok(is_valid)
+        Ok(verdict)
+    }
+
+    /// Check that the transaction doesn't exceed the size limits and has only the allowed kinds
+    /// of proofs.
+    fn check_limits(&self, tx: &YuvTransaction) -> Result<(), TxCheckerError> {
+        check_tx_size(&tx.bitcoin_tx, self.max_inputs, self.max_outputs)?;
+
+        if let Some(allowed_proof_kinds) = &self.allowed_proof_kinds {
+            check_proof_kinds(&tx.tx_type, allowed_proof_kinds)?;
+        }
+
+        Ok(())
     }
 
     async fn check_issuance(
@@ -228,18 +411,19 @@ where
         tx: &YuvTransaction,
         output_proofs: &Option<ProofMap>,
         announcement: &IssueAnnouncement,
-    ) -> Result<bool> {
-        if !self.check_issue_announcement(tx, announcement).await? {
-            return Ok(false);
+    ) -> Result<Verdict> {
+        let verdict = self.check_issue_announcement(tx, announcement).await?;
+        if !matches!(verdict, Verdict::Valid) {
+            return Ok(verdict);
         }
 
-        if check_issue_isolated(&tx.bitcoin_tx, output_proofs, announcement).is_err() {
-            return Ok(false);
+        if let Err(err) = check_issue_isolated(&tx.bitcoin_tx, output_proofs, announcement) {
+            return Ok(Verdict::invalid(err));
         }
 
         self.txs_storage.put_yuv_tx(tx.clone()).await?;
 
-        Ok(true)
+        Ok(Verdict::Valid)
     }
 
     async fn check_transfer(
@@ -249,11 +433,13 @@ where
         output_proofs: &ProofMap,
         checked_txs: &BTreeMap<Txid, YuvTransaction>,
         not_found_parents: &mut Vec<Txid>,
-    ) -> Result<bool> {
-        if check_transfer_isolated(&tx.bitcoin_tx, input_proofs, output_proofs).is_err() {
-            return Ok(false);
+    ) -> Result<Verdict> {
+        if let Err(err) = check_transfer_isolated(&tx.bitcoin_tx, input_proofs, output_proofs) {
+            return Ok(Verdict::invalid(err));
         }
 
+        let mut missing_parents = Vec::new();
+
         for (parent_id, proof) in input_proofs {
             let Some(txin) = tx.bitcoin_tx.input.get(*parent_id as usize) else {
                 return Err(CheckError::InputNotFound.into());
@@ -269,19 +455,50 @@ where
                     parent,
                 );
 
-                return Ok(false);
+                return Ok(Verdict::invalid(format!("output {parent} is frozen")));
             }
 
             let is_in_storage = self.txs_storage.get_yuv_tx(&parent.txid).await?.is_some();
             if !is_in_storage && !checked_txs.contains_key(&parent.txid) {
-                not_found_parents.push(parent.txid);
+                missing_parents.push(parent.txid);
             }
         }
 
-        Ok(true)
+        if missing_parents.is_empty() {
+            return Ok(Verdict::Valid);
+        }
+
+        // Depth is approximated by the number of rounds of the parents requests the chain has
+        // triggered, as the transactions of the chain are received one round at a time.
+        let txid = tx.bitcoin_tx.txid();
+        let depth = self.dependency_depths.get(&txid) + 1;
+
+        if let Some(max_depth) = self.max_dependency_depth {
+            if depth > max_depth {
+                tracing::info!(
+                    index = self.index,
+                    "Transfer tx {} is invalid: chain of missing parents is too deep",
+                    txid,
+                );
+
+                return Ok(Verdict::invalid(TxCheckerError::DependencyTooDeep(
+                    max_depth,
+                )));
+            }
+        }
+
+        for parent_txid in missing_parents {
+            self.dependency_depths.raise(parent_txid, depth);
+            not_found_parents.push(parent_txid);
+        }
+
+        Ok(Verdict::Valid)
     }
 
-    /// Check if transaction is frozen.
+    /// Check if transaction is frozen, either by itself or with all the outputs of its chroma.
+    ///
+    /// Freeze txs that are no longer valid are removed, and if that toggles the output, the
+    /// controller is notified about it, as there is no freeze tx for the toggle to be sent with.
     async fn is_output_frozen(&self, outpoint: &OutPoint, proof: &PixelProof) -> Result<bool> {
         let chroma = &proof.pixel().chroma;
 
@@ -293,6 +510,10 @@ where
             }
         }
 
+        if self.state_storage.is_chroma_frozen(chroma).await? {
+            return Ok(true);
+        }
+
         let freeze_entry = self.state_storage.get_frozen_tx(outpoint).await?;
 
         // Issuer haven't attempted to freeze this output, so it's not frozen:
@@ -301,6 +522,7 @@ where
         };
 
         let mut checked_freezes = Vec::new();
+        let mut removed_freezes = Vec::new();
 
         // TODO: optimize this approach.
         for freeze_txid in &freeze_entry.tx_ids {
@@ -318,6 +540,7 @@ where
                 );
 
                 self.txs_storage.delete_yuv_tx(freeze_txid).await?;
+                removed_freezes.push(*freeze_txid);
 
                 continue;
             }
@@ -331,6 +554,26 @@ where
             .put_frozen_tx(outpoint, checked_freezes)
             .await?;
 
+        let was_frozen = freeze_entry.tx_ids.len() % 2 == 1;
+
+        if let Some(txid) = removed_freezes.last().filter(|_| was_frozen != is_frozen) {
+            tracing::info!(
+                index = self.index,
+                "Output {} is {} after removal of the invalid freeze txs",
+                outpoint,
+                if is_frozen { "frozen" } else { "unfrozen" },
+            );
+
+            self.event_bus
+                .send(GraphBuilderMessage::CheckedFreezes(vec![FreezeToggle {
+                    txid: *txid,
+                    chroma: *chroma,
+                    outpoint: Some(*outpoint),
+                    frozen: is_frozen,
+                }]))
+                .await;
+        }
+
         Ok(is_frozen)
     }
 
@@ -340,16 +583,17 @@ where
     ///
     /// # Returns
     ///
-    /// - `Ok(true)` - if all the announcements are valid.
-    /// - `Ok(false)` - if at least one of the announcements is invalid.
+    /// - `Ok(Verdict::Valid)` - if all the announcements are valid.
+    /// - `Ok(Verdict::Invalid(reason))` - if at least one of the announcements is invalid.
+    /// - `Ok(Verdict::Pending)` - if the issue announcement waits for the chroma announcement.
     /// - `Err(err)` - if an error occurred during the check.
     async fn check_announcements(
         &self,
         tx: &YuvTransaction,
         announcement: &Announcement,
-        invalid_txs: &mut Vec<YuvTransaction>,
-    ) -> Result<bool> {
-        let is_checked = match announcement {
+        invalid_txs: &mut Vec<(YuvTransaction, String)>,
+    ) -> Result<Verdict> {
+        let verdict = match announcement {
             Announcement::Chroma(announcement) => {
                 self.check_chroma_announcement(tx, announcement).await?
             }
@@ -359,18 +603,33 @@ where
             Announcement::Issue(announcement) => {
                 self.check_issue_announcement(tx, announcement).await?
             }
+            Announcement::ChromaFreeze(announcement) => {
+                self.check_chroma_freeze_announcement(tx, announcement)
+                    .await?
+            }
         };
 
+        self.handle_announcement_verdict(tx, &verdict, invalid_txs)
+            .await;
+
+        Ok(verdict)
+    }
+
+    /// Notify the controller that the announcement is checked, and record it as invalid if the
+    /// `verdict` is.
+    async fn handle_announcement_verdict(
+        &self,
+        tx: &YuvTransaction,
+        verdict: &Verdict,
+        invalid_txs: &mut Vec<(YuvTransaction, String)>,
+    ) {
         self.event_bus
             .send(ControllerMessage::CheckedAnnouncement(tx.bitcoin_tx.txid()))
             .await;
 
-        if !is_checked {
-            invalid_txs.push(tx.clone());
-            return Ok(false);
+        if let Verdict::Invalid(reason) = verdict {
+            invalid_txs.push((tx.clone(), reason.clone()));
         }
-
-        Ok(true)
     }
 
     /// Check that [ChromaAnnouncement] is valid.
@@ -382,7 +641,7 @@ where
         &self,
         announcement_tx: &YuvTransaction,
         announcement: &ChromaAnnouncement,
-    ) -> Result<bool> {
+    ) -> Result<Verdict> {
         let announcement_tx_inputs = &announcement_tx.bitcoin_tx.input;
         let chroma = &announcement.chroma;
 
@@ -393,7 +652,7 @@ where
                 announcement_tx.bitcoin_tx.txid(),
             );
 
-            return Ok(false);
+            return Ok(Verdict::invalid(CheckError::IssuerNotOwner));
         }
 
         if let Some(chroma_info) = self
@@ -410,13 +669,18 @@ where
                     announcement.max_supply,
                 );
 
-                return Ok(false);
+                return Ok(Verdict::invalid(format!(
+                    "current total supply {} exceeds max supply {}",
+                    chroma_info.total_supply, announcement.max_supply,
+                )));
             }
         };
 
-        self.add_chroma_announcements(announcement).await?;
+        self.add_chroma_announcements(announcement_tx.bitcoin_tx.txid(), announcement)
+            .await?;
+        self.resubmit_pending_issuances(chroma).await?;
 
-        Ok(true)
+        Ok(Verdict::Valid)
     }
 
     /// Check that [FrezeAnnouncement] is valid.
@@ -433,13 +697,13 @@ where
         &self,
         announcement_tx: &YuvTransaction,
         announcement: &FreezeAnnouncement,
-    ) -> Result<bool> {
+    ) -> Result<Verdict> {
         let freeze_txid = announcement.freeze_txid();
         let freeze_vout = announcement.freeze_vout();
 
         let Some(freeze_tx) = self.txs_storage.get_yuv_tx(&freeze_txid).await? else {
             // TODO: If there is no transactions, worker should wait its appearance for check.
-            return Ok(true);
+            return Ok(Verdict::Valid);
         };
 
         let Some(output_proofs) = get_output_proofs(&freeze_tx) else {
@@ -452,7 +716,7 @@ where
                 freeze_txid,
             );
 
-            return Ok(true);
+            return Ok(Verdict::Valid);
         };
 
         let Some(output) = output_proofs.get(&freeze_vout) else {
@@ -465,7 +729,7 @@ where
                 announcement.freeze_outpoint(),
             );
 
-            return Ok(true);
+            return Ok(Verdict::Valid);
         };
 
         let chroma = &output.pixel().chroma;
@@ -479,7 +743,9 @@ where
                         chroma,
                     );
 
-                    return Ok(false);
+                    return Ok(Verdict::invalid(format!(
+                        "chroma {chroma} doesn't allow freezes"
+                    )));
                 }
             }
         }
@@ -492,13 +758,110 @@ where
                 freeze_txid,
             );
 
-            return Ok(false);
+            return Ok(Verdict::invalid(CheckError::IssuerNotOwner));
+        }
+
+        let txid = announcement_tx.bitcoin_tx.txid();
+        let frozen = self.update_freezes(txid, announcement).await?;
+
+        self.event_bus
+            .send(GraphBuilderMessage::CheckedFreezes(vec![FreezeToggle {
+                txid,
+                chroma: *chroma,
+                outpoint: Some(announcement.freeze_outpoint()),
+                frozen,
+            }]))
+            .await;
+
+        Ok(Verdict::Valid)
+    }
+
+    /// Check that [ChromaFreezeAnnouncement] is valid.
+    ///
+    /// The chroma freeze announcement is considered valid if:
+    /// 1. One of the inputs of the announcement transaction is signed by the issuer of the chroma.
+    /// 2. The freezes are allowed by the Chroma announcement.
+    async fn check_chroma_freeze_announcement(
+        &self,
+        announcement_tx: &YuvTransaction,
+        announcement: &ChromaFreezeAnnouncement,
+    ) -> Result<Verdict> {
+        let txid = announcement_tx.bitcoin_tx.txid();
+        let chroma = &announcement.chroma;
+
+        if find_issuer_in_txinputs(&announcement_tx.bitcoin_tx.input, chroma).is_none() {
+            tracing::info!(
+                index = self.index,
+                "Chroma freeze tx {} is invalid: none of the inputs has issuer, removing it",
+                txid,
+            );
+
+            return Ok(Verdict::invalid(CheckError::IssuerNotOwner));
+        }
+
+        if let Some(chroma_info) = self.state_storage.get_chroma_info(chroma).await? {
+            if let Some(chroma_announcement) = chroma_info.announcement {
+                if !chroma_announcement.is_freezable {
+                    tracing::info!(
+                        index = self.index,
+                        "Chroma freeze tx {} is invalid: chroma {} doesn't allow freezes, removing it",
+                        txid,
+                        chroma,
+                    );
+
+                    return Ok(Verdict::invalid(format!(
+                        "chroma {chroma} doesn't allow freezes"
+                    )));
+                }
+            }
         }
 
-        self.update_freezes(announcement_tx.bitcoin_tx.txid(), announcement)
+        self.state_storage
+            .put_chroma_frozen(chroma, announcement.frozen)
             .await?;
 
-        Ok(true)
+        // The announcement could be checked again, e.g. if it's received from another peer
+        // before it's attached, so it's recorded only once.
+        let is_recorded = self
+            .state_storage
+            .get_chroma_freezes(chroma)
+            .await?
+            .iter()
+            .any(|entry| entry.txid == txid);
+        if !is_recorded {
+            self.state_storage
+                .push_chroma_freeze(
+                    chroma,
+                    ChromaFreezeEntry {
+                        txid,
+                        frozen: announcement.frozen,
+                    },
+                )
+                .await?;
+        }
+
+        self.event_bus
+            .send(GraphBuilderMessage::CheckedFreezes(vec![FreezeToggle {
+                txid,
+                chroma: *chroma,
+                outpoint: None,
+                frozen: announcement.frozen,
+            }]))
+            .await;
+
+        tracing::debug!(
+            index = self.index,
+            "Chroma {} is {} by tx {}",
+            chroma,
+            if announcement.frozen {
+                "frozen"
+            } else {
+                "unfrozen"
+            },
+            txid,
+        );
+
+        Ok(Verdict::Valid)
     }
 
     /// Check that [IssueAnnouncement] is valid.
@@ -508,11 +871,16 @@ where
     /// chroma.
     /// 2. Issue amount doesn't exceed the max supply specified in the chroma announcement (if
     /// announced).
+    /// 3. The chroma announcement is indexed from a confirmed block, if the config requires it.
+    ///
+    /// Issuance of the chroma that is not announced yet is kept pending until the announcement.
+    /// The announcement received from the peers or RPC before it's indexed doesn't count, as it
+    /// could be not confirmed yet.
     async fn check_issue_announcement(
         &self,
         announcement_yuv_tx: &YuvTransaction,
         announcement: &IssueAnnouncement,
-    ) -> Result<bool> {
+    ) -> Result<Verdict> {
         let announcement_tx = &announcement_yuv_tx.bitcoin_tx;
         let chroma = &announcement.chroma;
         let issue_amount = announcement.amount;
@@ -523,7 +891,7 @@ where
             .await?
             .is_some();
         if is_tx_already_exists {
-            return Ok(true);
+            return Ok(Verdict::Valid);
         }
 
         if find_issuer_in_txinputs(&announcement_tx.input, chroma).is_none() {
@@ -533,7 +901,29 @@ where
                 announcement_yuv_tx.bitcoin_tx.txid(),
             );
 
-            return Ok(false);
+            return Ok(Verdict::invalid(CheckError::IssuerNotOwner));
+        }
+
+        let chroma_info_opt = self.state_storage.get_chroma_info(chroma).await?;
+        let is_chroma_announced = chroma_info_opt
+            .as_ref()
+            .is_some_and(|chroma_info| chroma_info.announcement.is_some());
+
+        if self.require_chroma_announcement
+            && !(is_chroma_announced && self.is_chroma_announcement_indexed(chroma).await?)
+        {
+            tracing::debug!(
+                index = self.index,
+                "Issue announcement tx {} is pending: chroma {} announcement is not indexed yet",
+                announcement_tx.txid(),
+                chroma,
+            );
+
+            self.state_storage
+                .put_pending_issuance(chroma, announcement_yuv_tx.clone())
+                .await?;
+
+            return Ok(Verdict::Pending);
         }
 
         // Bulletproof issuance announcements don't update the total supply so they can be skipped.
@@ -543,16 +933,28 @@ where
             self.handle_checked_issue_announcement(announcement_yuv_tx, announcement)
                 .await?;
 
-            return Ok(true);
+            return Ok(Verdict::Valid);
         }
 
-        let chroma_info_opt = self.state_storage.get_chroma_info(chroma).await?;
         if let Some(ChromaInfo {
             announcement: Some(ChromaAnnouncement { max_supply, .. }),
             total_supply,
+            ..
         }) = chroma_info_opt
         {
-            let new_total_supply = total_supply + issue_amount;
+            let Some(new_total_supply) = total_supply.checked_add(issue_amount) else {
+                tracing::info!(
+                    index = self.index,
+                    "Issue announcement tx {} is invalid: current supply {} + announcement amount {} overflows",
+                    announcement_tx.txid(),
+                    total_supply,
+                    issue_amount,
+                );
+
+                return Ok(Verdict::invalid(format!(
+                    "current supply {total_supply} + announcement amount {issue_amount} overflows"
+                )));
+            };
 
             if max_supply != 0 && max_supply < new_total_supply {
                 tracing::info!(
@@ -564,14 +966,26 @@ where
                     max_supply,
                 );
 
-                return Ok(false);
+                return Ok(Verdict::invalid(format!(
+                    "current supply {total_supply} + announcement amount {issue_amount} is higher than the max supply {max_supply}"
+                )));
             }
         }
 
         self.handle_checked_issue_announcement(announcement_yuv_tx, announcement)
             .await?;
 
-        Ok(true)
+        Ok(Verdict::Valid)
+    }
+
+    /// Check if the [`ChromaAnnouncement`] of the chroma is indexed from a confirmed block.
+    async fn is_chroma_announcement_indexed(&self, chroma: &Chroma) -> Result<bool> {
+        let height = self
+            .state_storage
+            .get_chroma_announcement_height(chroma)
+            .await?;
+
+        Ok(height.is_some())
     }
 
     async fn handle_checked_issue_announcement(
@@ -588,6 +1002,89 @@ where
     }
 }
 
+/// Time after which the depth of the requested parent is forgotten if the parent never arrives,
+/// the same as the default time the graph builder keeps the transactions waiting for it.
+const DEPENDENCY_DEPTH_TTL: Duration = Duration::from_secs(60 * 60 * 24);
+
+/// Depths of the transactions requested as the missing parents of the transfers, i.e. the number
+/// of rounds of the parents requests the chain has triggered before them.
+///
+/// The depths are shared between the workers of the pool, and are removed when the transaction is
+/// checked, or expire after the TTL if it never arrives.
+#[derive(Clone)]
+pub(crate) struct DependencyDepths {
+    depths: Arc<Mutex<HashMap<Txid, (u32, Instant)>>>,
+    ttl: Duration,
+}
+
+impl Default for DependencyDepths {
+    fn default() -> Self {
+        Self::with_ttl(DEPENDENCY_DEPTH_TTL)
+    }
+}
+
+impl DependencyDepths {
+    pub(crate) fn with_ttl(ttl: Duration) -> Self {
+        Self {
+            depths: Arc::default(),
+            ttl,
+        }
+    }
+
+    /// Depth of the transaction, zero if it wasn't requested as a parent.
+    fn get(&self, txid: &Txid) -> u32 {
+        let depths = self
+            .depths
+            .lock()
+            .expect("dependency depths lock is poisoned");
+
+        depths
+            .get(txid)
+            .filter(|(_, requested_at)| requested_at.elapsed() < self.ttl)
+            .map(|(depth, _)| *depth)
+            .unwrap_or_default()
+    }
+
+    /// Set the depth of the transaction if it's bigger than the current one, and remove the
+    /// expired ones.
+    fn raise(&self, txid: Txid, depth: u32) {
+        let mut depths = self
+            .depths
+            .lock()
+            .expect("dependency depths lock is poisoned");
+
+        depths.retain(|_, (_, requested_at)| requested_at.elapsed() < self.ttl);
+
+        let (current, requested_at) = depths.entry(txid).or_insert((0, Instant::now()));
+        *current = (*current).max(depth);
+        *requested_at = Instant::now();
+    }
+
+    fn remove(&self, txid: &Txid) {
+        let mut depths = self
+            .depths
+            .lock()
+            .expect("dependency depths lock is poisoned");
+
+        depths.remove(txid);
+    }
+}
+
+/// Result of the transaction check.
+enum Verdict {
+    Valid,
+    /// Transaction is rejected for the given reason.
+    Invalid(String),
+    /// Transaction can't be checked yet, and is kept until it can be.
+    Pending,
+}
+
+impl Verdict {
+    fn invalid(reason: impl ToString) -> Self {
+        Self::Invalid(reason.to_string())
+    }
+}
+
 fn get_output_proofs(yuv_tx: &YuvTransaction) -> Option<&ProofMap> {
     match yuv_tx.tx_type {
         YuvTxType::Issue {
@@ -1,13 +1,25 @@
-use crate::TxCheckerWorker;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
 
-use crate::worker::Config;
+use crate::{CheckerPause, TxCheckerWorker};
+
+use crate::worker::{Config, DependencyDepths};
 use bitcoin_client::Error as BitcoinRpcError;
+use event_bus::{typeid, EventBus};
 use tokio_util::sync::CancellationToken;
 use tokio_util::task::TaskTracker;
 use yuv_storage::{ChromaInfoStorage, FrozenTxsStorage, InvalidTxsStorage, TransactionsStorage};
+use yuv_types::TxCheckerPoolMessage;
 
 pub struct TxCheckerWorkerPool<TransactoinsStorage, StateStorage> {
     workers: Vec<TxCheckerWorker<TransactoinsStorage, StateStorage>>,
+    /// Number of workers which are currently running.
+    alive_workers: AliveWorkers,
+    /// Flag shared by the workers, which is toggled by the graph builder's
+    /// [`TxCheckerPoolMessage`]s.
+    pause: CheckerPause,
+    /// Event bus to receive the [`TxCheckerPoolMessage`]s.
+    event_bus: EventBus,
 }
 
 impl<TS, SS> TxCheckerWorkerPool<TS, SS>
@@ -19,21 +31,129 @@ where
         pool_size: usize,
         worker_config: Config<TS, SS>,
     ) -> Result<Self, BitcoinRpcError> {
+        let event_bus = worker_config
+            .full_event_bus
+            .extract(&typeid![], &typeid![TxCheckerPoolMessage])
+            .expect("event channels must be presented");
+
+        let dependency_depths = DependencyDepths::default();
+        let pause = CheckerPause::default();
+
         let workers = (0..pool_size)
-            .map(|i| TxCheckerWorker::from_config(&worker_config, Some(i)))
+            .map(|i| {
+                TxCheckerWorker::from_config(&worker_config, Some(i))
+                    .with_dependency_depths(dependency_depths.clone())
+                    .with_pause(pause.clone())
+            })
             .collect::<Vec<TxCheckerWorker<TS, SS>>>();
 
-        Ok(Self { workers })
+        Ok(Self {
+            workers,
+            alive_workers: AliveWorkers::default(),
+            pause,
+            event_bus,
+        })
+    }
+
+    /// Get the flag which pauses the workers, e.g. to check if they're
+    /// paused.
+    pub fn pause(&self) -> CheckerPause {
+        self.pause.clone()
+    }
+
+    /// Get the counter of running workers, which can be used to check the
+    /// liveness of the pool.
+    pub fn alive_workers(&self) -> AliveWorkers {
+        self.alive_workers.clone()
     }
 
     pub async fn run(self, cancellation: CancellationToken) {
         let task_tracker = TaskTracker::new();
 
         for worker in self.workers {
-            task_tracker.spawn(worker.run(cancellation.child_token()));
+            let guard = self.alive_workers.guard();
+            let cancellation = cancellation.child_token();
+
+            task_tracker.spawn(async move {
+                worker.run(cancellation).await;
+                drop(guard);
+            });
         }
 
         task_tracker.close();
+
+        // Stop listening to the pause events as soon as all the workers are
+        // stopped, otherwise wait for them to finish after the cancellation.
+        tokio::select! {
+            _ = task_tracker.wait() => return,
+            _ = handle_pause_events(&self.event_bus, &self.pause, &cancellation) => {}
+        }
+
         task_tracker.wait().await;
     }
 }
+
+/// Toggle the pause of the workers on the [`TxCheckerPoolMessage`]s until
+/// the cancellation.
+async fn handle_pause_events(
+    event_bus: &EventBus,
+    pause: &CheckerPause,
+    cancellation: &CancellationToken,
+) {
+    let events = event_bus.subscribe::<TxCheckerPoolMessage>();
+
+    loop {
+        tokio::select! {
+            event_received = events.recv() => {
+                let Ok(event) = event_received else {
+                    tracing::trace!("All pool events senders are dropped");
+                    return;
+                };
+
+                match event {
+                    TxCheckerPoolMessage::Pause => {
+                        tracing::warn!("Graph builder is backed up, pausing the checkers");
+                        pause.pause();
+                    }
+                    TxCheckerPoolMessage::Resume => {
+                        tracing::info!("Graph builder is drained, resuming the checkers");
+                        pause.resume();
+                    }
+                }
+            }
+            _ = cancellation.cancelled() => return,
+        }
+    }
+}
+
+/// Shared counter of the running [`TxCheckerWorker`]s of the pool.
+#[derive(Clone, Debug, Default)]
+pub struct AliveWorkers(Arc<AtomicUsize>);
+
+impl AliveWorkers {
+    /// Number of workers which are currently running.
+    pub fn count(&self) -> usize {
+        self.0.load(Ordering::Relaxed)
+    }
+
+    /// Returns `true` if at least one worker is running.
+    pub fn is_alive(&self) -> bool {
+        self.count() > 0
+    }
+
+    /// Increment the counter, which is decremented back when the guard is
+    /// dropped, even if the worker has panicked.
+    fn guard(&self) -> AliveWorkerGuard {
+        self.0.fetch_add(1, Ordering::Relaxed);
+
+        AliveWorkerGuard(self.0.clone())
+    }
+}
+
+struct AliveWorkerGuard(Arc<AtomicUsize>);
+
+impl Drop for AliveWorkerGuard {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::Relaxed);
+    }
+}
@@ -5,9 +5,24 @@ use std::collections::HashMap;
 use std::sync::Arc;
 use std::time::{Duration, SystemTime};
 use tokio_util::sync::CancellationToken;
-use yuv_types::{TxCheckerMessage, TxConfirmMessage, YuvTransaction, DEFAULT_CONFIRMATIONS_NUMBER};
+use yuv_types::{
+    TxCheckerMessage, TxConfirmMessage, YuvTransaction, YuvTxType, DEFAULT_CONFIRMATIONS_NUMBER,
+};
+
+/// Default max number of the transactions, that haven't been seen in the indexed blocks, to look
+/// up through Bitcoin RPC at each clean up.
+pub const DEFAULT_LOOKUP_BATCH_SIZE: usize = 10;
 
 /// `TxConfirmator` is responsible for waiting confirmations of transactions in Bitcoin.
+///
+/// The indexer pulls only the blocks that already have enough confirmations, so the transaction
+/// is confirmed once it's seen in the indexed block. It's looked up through Bitcoin RPC only when
+/// it's received, and at clean ups in batches while it hasn't been mined, e.g. if it was mined
+/// before the node's start.
+///
+/// Announcements and issuances mined below the activation height are dropped, as the YUV
+/// protocol wasn't active at that time, whether they were received from the indexer, p2p or
+/// JSON RPC.
 pub struct TxConfirmator<BC>
 where
     BC: BitcoinRpcApi + Send + Sync + 'static,
@@ -22,6 +37,11 @@ where
     clean_up_interval: Duration,
     /// Contains the number of confirmations required to consider a transaction as confirmed.
     confirmations_number: u8,
+    /// Max number of the transactions, that haven't been seen in the indexed blocks, to look up
+    /// through Bitcoin RPC at each clean up.
+    lookup_batch_size: usize,
+    /// Height of the first block which announcements and issuances are confirmed.
+    activation_height: u64,
 }
 
 impl<BC> TxConfirmator<BC>
@@ -48,9 +68,26 @@ where
             bitcoin_client,
             clean_up_interval,
             confirmations_number,
+            lookup_batch_size: DEFAULT_LOOKUP_BATCH_SIZE,
+            activation_height: 0,
         }
     }
 
+    /// Set the height of the first block which announcements and issuances are confirmed.
+    pub fn with_activation_height(mut self, activation_height: u64) -> Self {
+        self.activation_height = activation_height;
+
+        self
+    }
+
+    /// Set the max number of the transactions to look up through Bitcoin RPC at each clean up.
+    /// Zero disables the lookups, so only the indexed blocks are used.
+    pub fn with_lookup_batch_size(mut self, lookup_batch_size: usize) -> Self {
+        self.lookup_batch_size = lookup_batch_size;
+
+        self
+    }
+
     pub async fn run(mut self, cancellation_token: CancellationToken) {
         let mut timer = tokio::time::interval(self.clean_up_interval);
         let events = self.event_bus.subscribe::<TxConfirmMessage>();
@@ -87,26 +124,34 @@ where
                     self.handle_tx_to_confirm(yuv_tx).await?;
                 }
             }
-            TxConfirmMessage::ConfirmedTxIds(tx_ids) => {
-                // Find the transactions that are waiting confirmation in the queue to confirm them.
-                let yuv_txs: Vec<YuvTransaction> = tx_ids
-                    .iter()
-                    .filter_map(|tx_id| {
-                        self.queue
-                            .get(tx_id)
-                            .map(|unconfirmed_tx| unconfirmed_tx.yuv_tx.clone())
-                    })
-                    .collect::<Vec<YuvTransaction>>();
-
-                for yuv_tx in yuv_txs {
-                    self.new_confirmed_tx(yuv_tx).await;
-                }
-            }
+            TxConfirmMessage::ConfirmedTxIds {
+                block_height,
+                tx_ids,
+            } => self.handle_new_block(block_height, tx_ids).await,
         }
 
         Ok(())
     }
 
+    /// Send the transactions in the queue that are found in the new indexed block with `tx_ids`
+    /// to the checkers. The indexer pulls the block only when it has enough confirmations, so
+    /// its transactions are confirmed.
+    async fn handle_new_block(&mut self, block_height: u64, tx_ids: Vec<Txid>) {
+        let confirmed_txs = tx_ids
+            .iter()
+            .filter_map(|tx_id| self.queue.get(tx_id))
+            .map(|unconfirmed_tx| unconfirmed_tx.yuv_tx.clone())
+            .collect::<Vec<_>>();
+
+        for yuv_tx in confirmed_txs {
+            if self.is_active_at(&yuv_tx, block_height) {
+                self.new_confirmed_tx(yuv_tx).await;
+            } else {
+                self.drop_inactive_tx(&yuv_tx, block_height);
+            }
+        }
+    }
+
     /// Handle new transaction to confirm it. If transaction is already confirmed, then it will be
     /// sent to the `TxChecker`. Otherwise it will be added to the queue.
     async fn handle_tx_to_confirm(&mut self, yuv_tx: YuvTransaction) -> eyre::Result<()> {
@@ -115,11 +160,23 @@ where
             .get_raw_transaction_info(&yuv_tx.bitcoin_tx.txid(), None)
             .await?;
 
-        if let Some(confirmations) = got_tx.confirmations {
-            if confirmations >= self.confirmations_number as u32 {
-                self.new_confirmed_tx(yuv_tx).await;
-                return Ok(());
+        let confirmations = got_tx.confirmations.unwrap_or_default();
+        if confirmations >= self.confirmations_number as u32 {
+            if let Some(block_hash) = got_tx
+                .blockhash
+                .filter(|_| self.requires_activation(&yuv_tx))
+            {
+                let block = self.bitcoin_client.get_block_info(&block_hash).await?;
+                let block_height = block.block_data.height as u64;
+
+                if !self.is_active_at(&yuv_tx, block_height) {
+                    self.drop_inactive_tx(&yuv_tx, block_height);
+                    return Ok(());
+                }
             }
+
+            self.new_confirmed_tx(yuv_tx).await;
+            return Ok(());
         }
 
         tracing::debug!(
@@ -127,41 +184,96 @@ where
             yuv_tx.bitcoin_tx.txid()
         );
 
-        self.queue
-            .entry(yuv_tx.bitcoin_tx.txid())
-            .or_insert(UnconfirmedTransaction {
-                yuv_tx,
-                created_at: SystemTime::now(),
-            });
+        let now = SystemTime::now();
+
+        let unconfirmed_tx =
+            self.queue
+                .entry(yuv_tx.bitcoin_tx.txid())
+                .or_insert(UnconfirmedTransaction {
+                    yuv_tx,
+                    created_at: now,
+                    looked_up_at: now,
+                    confirmations,
+                });
+        unconfirmed_tx.looked_up_at = now;
+        unconfirmed_tx.confirmations = unconfirmed_tx.confirmations.max(confirmations);
 
         Ok(())
     }
 
-    /// Find transactions that are waiting confirmation in the block. If transaction is appeared in
-    /// the block, then it is confirmed and can be sent to the checkers. Otherwise it will be
-    /// removed from the queue if it is waiting confirmation for too long.
+    /// Remove the transactions that are waiting confirmation for too long from the queue, and
+    /// look up the ones that haven't been seen in the indexed blocks yet, at most
+    /// `lookup_batch_size` of the least recently looked up ones.
     pub async fn clean_up_waiting_txs(&mut self) -> eyre::Result<()> {
         if self.queue.is_empty() {
             return Ok(());
         }
 
         // Remove transactions that are waiting confirmation for too long.
-        for (txid, unconfirmed_tx) in self.queue.clone().into_iter() {
-            if unconfirmed_tx.created_at.elapsed().unwrap() > self.max_confirmation_time {
+        self.queue.retain(|txid, unconfirmed_tx| {
+            let is_outdated = unconfirmed_tx.created_at.elapsed().unwrap_or_default()
+                > self.max_confirmation_time;
+
+            if is_outdated {
                 tracing::debug!(
                     "Transaction {:?} is waiting confirmation for too long. Removing from queue.",
                     txid
                 );
-
-                self.queue.remove(&txid);
-            } else {
-                self.handle_tx_to_confirm(unconfirmed_tx.yuv_tx).await?;
             }
+
+            !is_outdated
+        });
+
+        let mut unseen_txs = self
+            .queue
+            .values()
+            .filter(|unconfirmed_tx| unconfirmed_tx.confirmations == 0)
+            .collect::<Vec<_>>();
+        unseen_txs.sort_by_key(|unconfirmed_tx| unconfirmed_tx.looked_up_at);
+
+        let batch = unseen_txs
+            .into_iter()
+            .take(self.lookup_batch_size)
+            .map(|unconfirmed_tx| unconfirmed_tx.yuv_tx.clone())
+            .collect::<Vec<_>>();
+
+        for yuv_tx in batch {
+            self.handle_tx_to_confirm(yuv_tx).await?;
         }
 
         Ok(())
     }
 
+    /// Returns `true` if the transaction is an announcement or issuance, which are confirmed only
+    /// in the blocks from the activation height.
+    fn requires_activation(&self, yuv_tx: &YuvTransaction) -> bool {
+        self.activation_height > 0
+            && matches!(
+                yuv_tx.tx_type,
+                YuvTxType::Announcement(_) | YuvTxType::Issue { .. }
+            )
+    }
+
+    /// Returns `true` if the transaction mined in the block at `block_height` could be confirmed.
+    fn is_active_at(&self, yuv_tx: &YuvTransaction, block_height: u64) -> bool {
+        block_height >= self.activation_height || !self.requires_activation(yuv_tx)
+    }
+
+    /// Remove the transaction mined below the activation height from the queue without sending
+    /// it to the checkers.
+    fn drop_inactive_tx(&mut self, yuv_tx: &YuvTransaction, block_height: u64) {
+        let txid = yuv_tx.bitcoin_tx.txid();
+
+        tracing::debug!(
+            "Transaction {} is mined at height {} below the activation height {}, dropping it",
+            txid,
+            block_height,
+            self.activation_height,
+        );
+
+        self.queue.remove(&txid);
+    }
+
     async fn new_confirmed_tx(&mut self, yuv_tx: YuvTransaction) {
         tracing::debug!("Transaction confirmed: {:?}", yuv_tx.bitcoin_tx.txid());
         self.queue.remove(&yuv_tx.bitcoin_tx.txid());
@@ -181,5 +293,245 @@ where
 #[derive(Clone)]
 struct UnconfirmedTransaction {
     pub created_at: SystemTime,
+    /// Time of the last lookup of the transaction through Bitcoin RPC.
+    pub looked_up_at: SystemTime,
+    /// Number of confirmations the transaction has got, zero if it hasn't been mined yet.
+    pub confirmations: u32,
     pub yuv_tx: YuvTransaction,
 }
+
+#[cfg(test)]
+mod tests {
+    use bitcoin::hashes::Hash;
+    use bitcoin::{BlockHash, OutPoint, PackedLockTime, Transaction, TxMerkleNode, TxOut};
+    use bitcoin_client::json::{BlockData, GetBlockResult};
+    use bitcoin_client::MockRpcApi;
+    use yuv_types::announcements::FreezeAnnouncement;
+    use yuv_types::mocks::yuv_tx;
+    use yuv_types::AnyAnnouncement;
+
+    use super::*;
+
+    const CONFIRMATIONS_NUMBER: u8 = 2;
+    const ACTIVATION_HEIGHT: u64 = 100;
+
+    /// Bitcoin RPC that returns the transactions as not mined, and expects `lookups` of them.
+    fn bitcoin_client(lookups: usize) -> Arc<MockRpcApi> {
+        Arc::new(MockRpcApi::with_raw_txs(None, None, Some(lookups)))
+    }
+
+    /// Bitcoin RPC that returns the transactions as mined deep enough in the block at `height`.
+    fn mined_bitcoin_client(height: usize) -> Arc<MockRpcApi> {
+        let mut client = MockRpcApi::with_raw_txs(
+            Some(BlockHash::all_zeros()),
+            Some(CONFIRMATIONS_NUMBER as u32),
+            None,
+        );
+
+        client
+            .expect_call::<GetBlockResult>()
+            .withf(|method, _| method == "getblock")
+            .returning(move |_, _| {
+                Ok(GetBlockResult {
+                    block_data: BlockData {
+                        hash: BlockHash::all_zeros(),
+                        confirmations: CONFIRMATIONS_NUMBER as u32,
+                        size: 0,
+                        strippedsize: None,
+                        weight: 0,
+                        height,
+                        version: 2,
+                        version_hex: None,
+                        merkleroot: TxMerkleNode::all_zeros(),
+                        time: 0,
+                        mediantime: None,
+                        nonce: 0,
+                        bits: String::new(),
+                        difficulty: 0.0,
+                        chainwork: Vec::new(),
+                        n_tx: 0,
+                        previousblockhash: None,
+                        nextblockhash: None,
+                    },
+                    tx: Vec::new(),
+                })
+            });
+
+        Arc::new(client)
+    }
+
+    fn announcement() -> YuvTransaction {
+        let announcement = FreezeAnnouncement::new(OutPoint::default());
+
+        YuvTransaction::new(
+            Transaction {
+                version: 2,
+                lock_time: PackedLockTime::ZERO,
+                input: Vec::new(),
+                output: vec![TxOut {
+                    value: 0,
+                    script_pubkey: announcement.to_script(),
+                }],
+            },
+            YuvTxType::Announcement(announcement.into()),
+        )
+    }
+
+    fn event_bus() -> EventBus {
+        let mut event_bus = EventBus::default();
+        event_bus.register::<TxCheckerMessage>(Some(100));
+        event_bus.register::<TxConfirmMessage>(Some(100));
+
+        event_bus
+    }
+
+    #[tokio::test]
+    async fn test_txs_of_indexed_blocks_are_confirmed() {
+        let event_bus = event_bus();
+        let checker_events = event_bus.subscribe::<TxCheckerMessage>();
+
+        // Transactions are looked up only when they are received.
+        let mut confirmator = TxConfirmator::new(
+            &event_bus,
+            bitcoin_client(2),
+            Duration::from_secs(60),
+            Duration::from_secs(1),
+            Some(CONFIRMATIONS_NUMBER),
+        );
+
+        let (mined_tx, unmined_tx) = (yuv_tx(1), yuv_tx(2));
+        let mined_txid = mined_tx.bitcoin_tx.txid();
+
+        confirmator
+            .handle_event(TxConfirmMessage::TxsToConfirm(vec![mined_tx, unmined_tx]))
+            .await
+            .unwrap();
+
+        // Nothing is looked up with zero batch size, txs are confirmed from the blocks.
+        confirmator = confirmator.with_lookup_batch_size(0);
+        confirmator.clean_up_waiting_txs().await.unwrap();
+        assert!(checker_events.is_empty());
+
+        // The indexed block is already deep enough, so the tx is confirmed at once.
+        confirmator
+            .handle_event(TxConfirmMessage::ConfirmedTxIds {
+                block_height: 1,
+                tx_ids: vec![mined_txid],
+            })
+            .await
+            .unwrap();
+
+        let Ok(TxCheckerMessage::NewTxs { txs, .. }) = checker_events.recv().await else {
+            panic!("confirmed tx must be sent to the checkers");
+        };
+        assert_eq!(txs[0].bitcoin_tx.txid(), mined_txid);
+        assert!(checker_events.is_empty());
+        assert_eq!(
+            confirmator.queue.len(),
+            1,
+            "unmined tx must be kept waiting"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_unseen_txs_are_looked_up_in_batches() {
+        let event_bus = event_bus();
+
+        // Three lookups when the transactions are received, and two at the clean up.
+        let mut confirmator = TxConfirmator::new(
+            &event_bus,
+            bitcoin_client(5),
+            Duration::from_secs(60),
+            Duration::from_secs(1),
+            Some(CONFIRMATIONS_NUMBER),
+        )
+        .with_lookup_batch_size(2);
+
+        confirmator
+            .handle_event(TxConfirmMessage::TxsToConfirm(vec![
+                yuv_tx(1),
+                yuv_tx(2),
+                yuv_tx(3),
+            ]))
+            .await
+            .unwrap();
+
+        confirmator.clean_up_waiting_txs().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_announcements_below_activation_height_are_dropped() {
+        for (block_height, is_active) in [(ACTIVATION_HEIGHT - 1, false), (ACTIVATION_HEIGHT, true)]
+        {
+            let event_bus = event_bus();
+            let checker_events = event_bus.subscribe::<TxCheckerMessage>();
+
+            let mut confirmator = TxConfirmator::new(
+                &event_bus,
+                mined_bitcoin_client(block_height as usize),
+                Duration::from_secs(60),
+                Duration::from_secs(1),
+                Some(CONFIRMATIONS_NUMBER),
+            )
+            .with_activation_height(ACTIVATION_HEIGHT);
+
+            // Txs received through JSON RPC or p2p, which are already mined deep enough.
+            let (announcement, transfer) = (announcement(), yuv_tx(1));
+            confirmator
+                .handle_event(TxConfirmMessage::TxsToConfirm(vec![
+                    announcement.clone(),
+                    transfer.clone(),
+                ]))
+                .await
+                .unwrap();
+
+            let mut confirmed = Vec::new();
+            while !checker_events.is_empty() {
+                if let Ok(TxCheckerMessage::NewTxs { txs, .. }) = checker_events.recv().await {
+                    confirmed.extend(txs);
+                }
+            }
+
+            let expected = if is_active {
+                vec![announcement, transfer]
+            } else {
+                vec![transfer]
+            };
+            assert_eq!(confirmed, expected, "block height {block_height}");
+            assert!(confirmator.queue.is_empty());
+        }
+    }
+
+    #[tokio::test]
+    async fn test_announcements_of_indexed_blocks_below_activation_height_are_dropped() {
+        let event_bus = event_bus();
+        let checker_events = event_bus.subscribe::<TxCheckerMessage>();
+
+        let mut confirmator = TxConfirmator::new(
+            &event_bus,
+            bitcoin_client(1),
+            Duration::from_secs(60),
+            Duration::from_secs(1),
+            Some(CONFIRMATIONS_NUMBER),
+        )
+        .with_activation_height(ACTIVATION_HEIGHT);
+
+        let announcement = announcement();
+        let txid = announcement.bitcoin_tx.txid();
+        confirmator
+            .handle_event(TxConfirmMessage::TxsToConfirm(vec![announcement]))
+            .await
+            .unwrap();
+
+        confirmator
+            .handle_event(TxConfirmMessage::ConfirmedTxIds {
+                block_height: ACTIVATION_HEIGHT - 1,
+                tx_ids: vec![txid],
+            })
+            .await
+            .unwrap();
+
+        assert!(checker_events.is_empty());
+        assert!(confirmator.queue.is_empty());
+    }
+}
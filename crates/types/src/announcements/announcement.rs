@@ -2,13 +2,15 @@ use alloc::string::String;
 use alloc::vec::Vec;
 
 use bitcoin::blockdata::script::Builder;
-use bitcoin::Script;
+use bitcoin::{Script, Witness, XOnlyPublicKey};
 
 use core::fmt;
 
+use crate::announcements::envelope::envelope_script;
 use crate::announcements::issue::IssueAnnouncement;
 use crate::announcements::{
-    parse_op_return_script, ChromaAnnouncement, FreezeAnnouncement, ParseOpReturnError,
+    parse_op_return_script, parse_witness_envelope, ChromaAnnouncement, ChromaFreezeAnnouncement,
+    FreezeAnnouncement, ParseOpReturnError, ParseWitnessError,
 };
 use bitcoin::blockdata::opcodes::all::OP_RETURN;
 #[cfg(feature = "consensus")]
@@ -47,6 +49,7 @@ pub enum Announcement {
     Chroma(ChromaAnnouncement),
     Freeze(FreezeAnnouncement),
     Issue(IssueAnnouncement),
+    ChromaFreeze(ChromaFreezeAnnouncement),
 }
 
 impl Announcement {
@@ -56,6 +59,7 @@ impl Announcement {
             Self::Chroma(inner) => inner,
             Self::Freeze(inner) => inner,
             Self::Issue(inner) => inner,
+            Self::ChromaFreeze(inner) => inner,
         }
     }
 
@@ -73,6 +77,12 @@ impl Announcement {
     pub fn to_bytes(&self) -> Vec<u8> {
         self.inner().to_bytes()
     }
+
+    /// Convert the announcement message to the tapscript leaf with the envelope, which is
+    /// spendable by the `pubkey`.
+    pub fn to_envelope_script(&self, pubkey: &XOnlyPublicKey) -> Script {
+        self.inner().to_envelope_script(pubkey)
+    }
 }
 
 impl fmt::Display for Announcement {
@@ -81,6 +91,7 @@ impl fmt::Display for Announcement {
             Self::Chroma(_) => write!(f, "ChromaAnnouncement"),
             Self::Freeze(_) => write!(f, "FreezeAnnouncement"),
             Self::Issue(_) => write!(f, "IssueAnnouncement"),
+            Self::ChromaFreeze(_) => write!(f, "ChromaFreezeAnnouncement"),
         }
     }
 }
@@ -150,6 +161,22 @@ pub trait AnyAnnouncement {
             .into_script()
     }
 
+    /// Parse the announcement message from the envelope in the tapscript of the Taproot
+    /// script-path spend [`Witness`].
+    fn from_witness(witness: &Witness) -> Result<Self, ParseWitnessError>
+    where
+        Self: Sized,
+    {
+        parse_witness_envelope(witness, Self::from_bytes)
+    }
+
+    /// Convert the announcement message to the tapscript leaf with the envelope, which is
+    /// spendable by the `pubkey`. The announcement is revealed in the witness once the leaf is
+    /// spent.
+    fn to_envelope_script(&self, pubkey: &XOnlyPublicKey) -> Script {
+        envelope_script(pubkey, &self.to_bytes())
+    }
+
     /// Parse the announcement message from bytes from `OP_RETURN` Script.
     fn from_bytes(value: &[u8]) -> Result<Self, AnnouncementParseError>
     where
@@ -164,9 +191,19 @@ pub trait AnyAnnouncement {
             return Err(AnnouncementParseError::InvalidPrefix);
         }
 
+        let kind = [value[3], value[4]];
         let announcement =
             Self::from_announcement_data_bytes(&value[ANNOUNCEMENT_MINIMAL_LENGTH..])?;
 
+        // Data of other kind of announcement could be of the same shape, so it
+        // mustn't be parsed as this one.
+        if announcement.kind() != kind {
+            return Err(AnnouncementParseError::UnexpectedAnnouncementKind {
+                expected: announcement.kind(),
+                got: kind,
+            });
+        }
+
         Ok(announcement)
     }
 
@@ -195,6 +232,13 @@ pub enum AnnouncementParseError {
     ///
     /// [announcement kind]: AnnouncementKind`
     UnknownAnnouncementKind,
+    /// The [announcement kind] differs from the one of the parsed announcement.
+    ///
+    /// [announcement kind]: AnnouncementKind
+    UnexpectedAnnouncementKind {
+        expected: AnnouncementKind,
+        got: AnnouncementKind,
+    },
     /// Failed to decode the announcement data.
     InvalidAnnouncementData(String),
 }
@@ -218,6 +262,13 @@ impl fmt::Display for AnnouncementParseError {
             Self::UnknownAnnouncementKind => {
                 write!(f, "unknown announcement kind")
             }
+            Self::UnexpectedAnnouncementKind { expected, got } => {
+                write!(
+                    f,
+                    "unexpected announcement kind, expected {:?}, got {:?}",
+                    expected, got
+                )
+            }
             Self::InvalidAnnouncementData(e) => {
                 write!(f, "failed to decode the announcement data: {}", e)
             }
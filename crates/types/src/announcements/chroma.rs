@@ -3,6 +3,7 @@ use alloc::string::{FromUtf8Error, String, ToString};
 use alloc::vec::Vec;
 use alloc::{format, vec};
 use bitcoin::consensus::{encode, ReadExt};
+use bitcoin::Txid;
 use core::fmt;
 use core::mem::size_of;
 use core2::io;
@@ -64,6 +65,10 @@ pub struct ChromaAnnouncement {
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ChromaInfo {
     pub announcement: Option<ChromaAnnouncement>,
+    /// Id of the transaction with the [`announcement`](Self::announcement). `None` if the chroma
+    /// is not announced, or it was announced before the ids were stored.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub announcement_txid: Option<Txid>,
     pub total_supply: u128,
 }
 
@@ -96,6 +101,109 @@ impl ChromaAnnouncement {
 
         Ok(result)
     }
+
+    /// Format the raw amount of the token using its [`decimal`](Self::decimal) places.
+    pub fn format_amount(&self, raw: u128) -> String {
+        format_amount(raw, self.decimal)
+    }
+
+    /// Parse the human readable amount of the token (e.g. `10.5`) into the raw amount using its
+    /// [`decimal`](Self::decimal) places.
+    pub fn parse_amount(&self, amount: &str) -> Result<u128, AmountParseError> {
+        parse_amount(amount, self.decimal)
+    }
+}
+
+impl ChromaInfo {
+    /// The number of decimal places for the token. Defaults to 0 if the token wasn't announced.
+    pub fn decimals(&self) -> u8 {
+        self.announcement
+            .as_ref()
+            .map(|announcement| announcement.decimal)
+            .unwrap_or_default()
+    }
+
+    /// Format the raw amount of the token using its [`decimals`](Self::decimals).
+    pub fn format_amount(&self, raw: u128) -> String {
+        format_amount(raw, self.decimals())
+    }
+
+    /// Parse the human readable amount of the token (e.g. `10.5`) into the raw amount using its
+    /// [`decimals`](Self::decimals).
+    pub fn parse_amount(&self, amount: &str) -> Result<u128, AmountParseError> {
+        parse_amount(amount, self.decimals())
+    }
+}
+
+/// Format the raw amount of the token as a decimal number with the given number of decimal places.
+/// Trailing zeros of the fractional part are omitted, e.g. `10500` with 3 decimals is `10.5`.
+pub fn format_amount(raw: u128, decimals: u8) -> String {
+    let decimals = decimals as usize;
+    if decimals == 0 {
+        return raw.to_string();
+    }
+
+    let digits = format!("{:0>width$}", raw, width = decimals + 1);
+    let (integer, fraction) = digits.split_at(digits.len() - decimals);
+    let fraction = fraction.trim_end_matches('0');
+
+    if fraction.is_empty() {
+        integer.to_string()
+    } else {
+        format!("{}.{}", integer, fraction)
+    }
+}
+
+/// Parse the decimal number with at most `decimals` fractional digits into the raw amount of the
+/// token, e.g. `10.5` with 3 decimals is `10500`.
+pub fn parse_amount(amount: &str, decimals: u8) -> Result<u128, AmountParseError> {
+    let (integer, fraction) = amount.split_once('.').unwrap_or((amount, ""));
+
+    let is_digits = |s: &str| s.bytes().all(|b| b.is_ascii_digit());
+    if (integer.is_empty() && fraction.is_empty()) || !is_digits(integer) || !is_digits(fraction) {
+        return Err(AmountParseError::InvalidFormat);
+    }
+
+    if fraction.len() > decimals as usize {
+        return Err(AmountParseError::TooManyDecimals(decimals));
+    }
+
+    let digits = format!(
+        "{}{:0<width$}",
+        integer,
+        fraction,
+        width = decimals as usize
+    );
+
+    digits.parse().map_err(|_| AmountParseError::Overflow)
+}
+
+/// Error parsing the human readable amount of the token.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AmountParseError {
+    /// The amount is not a decimal number.
+    InvalidFormat,
+    /// The amount has more fractional digits than the token's decimal places.
+    TooManyDecimals(u8),
+    /// The amount doesn't fit into `u128`.
+    Overflow,
+}
+
+#[cfg(not(feature = "no-std"))]
+impl std::error::Error for AmountParseError {}
+
+impl fmt::Display for AmountParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidFormat => write!(f, "the amount must be a decimal number"),
+            Self::TooManyDecimals(decimals) => write!(
+                f,
+                "the amount has too many decimal places, at most {} allowed",
+                decimals
+            ),
+            Self::Overflow => write!(f, "the amount is too large"),
+        }
+    }
 }
 
 #[cfg_attr(feature = "serde", typetag::serde(name = "chroma_announcement"))]
@@ -504,4 +612,62 @@ mod tests {
             assert!(ChromaAnnouncement::from_script(&announcement.script).is_ok());
         }
     }
+
+    #[test]
+    fn test_format_amount() {
+        assert_eq!(format_amount(10500, 3), "10.5");
+        assert_eq!(format_amount(10000, 3), "10");
+        assert_eq!(format_amount(5, 3), "0.005");
+        assert_eq!(format_amount(0, 3), "0");
+        assert_eq!(format_amount(10500, 0), "10500");
+        assert_eq!(
+            format_amount(u128::MAX, 38),
+            "3.40282366920938463463374607431768211455"
+        );
+    }
+
+    #[test]
+    fn test_parse_amount() {
+        assert_eq!(parse_amount("10.5", 3), Ok(10500));
+        assert_eq!(parse_amount("10", 3), Ok(10000));
+        assert_eq!(parse_amount(".005", 3), Ok(5));
+        assert_eq!(parse_amount("10.", 3), Ok(10000));
+        assert_eq!(parse_amount("10500", 0), Ok(10500));
+        assert_eq!(
+            parse_amount("10.5", 0),
+            Err(AmountParseError::TooManyDecimals(0))
+        );
+        assert_eq!(
+            parse_amount("0.0001", 3),
+            Err(AmountParseError::TooManyDecimals(3))
+        );
+        assert_eq!(parse_amount("", 3), Err(AmountParseError::InvalidFormat));
+        assert_eq!(parse_amount(".", 3), Err(AmountParseError::InvalidFormat));
+        assert_eq!(
+            parse_amount("1.2.3", 3),
+            Err(AmountParseError::InvalidFormat)
+        );
+        assert_eq!(parse_amount("-1", 3), Err(AmountParseError::InvalidFormat));
+        assert_eq!(
+            parse_amount(&u128::MAX.to_string(), 1),
+            Err(AmountParseError::Overflow)
+        );
+
+        for raw in [0, 1, 10500, 123456789] {
+            assert_eq!(parse_amount(&format_amount(raw, 6), 6), Ok(raw));
+        }
+    }
+
+    #[test]
+    fn test_chroma_info_defaults_to_zero_decimals() {
+        let info = ChromaInfo {
+            announcement: None,
+            announcement_txid: None,
+            total_supply: 0,
+        };
+
+        assert_eq!(info.decimals(), 0);
+        assert_eq!(info.format_amount(10500), "10500");
+        assert_eq!(info.parse_amount("10500"), Ok(10500));
+    }
 }
@@ -0,0 +1,224 @@
+use alloc::string::ToString;
+use alloc::vec::Vec;
+use core::fmt;
+
+use crate::{Announcement, AnyAnnouncement};
+use yuv_pixels::{Chroma, ChromaParseError, CHROMA_SIZE};
+
+use crate::announcements::{AnnouncementKind, AnnouncementParseError};
+
+/// The two bytes that represents the [`chroma freeze announcement`]'s kind.
+///
+/// [`chroma freeze announcement`]: ChromaFreezeAnnouncement
+pub const CHROMA_FREEZE_ANNOUNCEMENT_KIND: AnnouncementKind = [0, 3];
+/// The size of chroma freeze announcement data in bytes.
+pub const CHROMA_FREEZE_ANNOUNCEMENT_SIZE: usize = CHROMA_SIZE + 1;
+
+/// Chroma freeze announcement. It appears when issuer declares that all the outputs of the token,
+/// both current and future ones, are frozen or unfrozen.
+///
+/// Unlike the [`FreezeAnnouncement`], it's not a toggle, so broadcasting it twice doesn't revert
+/// the state.
+///
+/// # Structure
+///
+/// - `chroma` - 32 bytes [`Chroma`] of the token that is frozen or unfrozen.
+/// - `frozen` - 1 byte, `1` if the token is frozen, `0` if it's unfrozen.
+///
+/// [`FreezeAnnouncement`]: crate::announcements::FreezeAnnouncement
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ChromaFreezeAnnouncement {
+    /// Chroma of the token that is frozen or unfrozen.
+    pub chroma: Chroma,
+    /// Whether the outputs of the token are frozen after the announcement.
+    pub frozen: bool,
+}
+
+impl ChromaFreezeAnnouncement {
+    /// Create a new chroma freeze announcement.
+    pub fn new(chroma: Chroma, frozen: bool) -> Self {
+        Self { chroma, frozen }
+    }
+
+    /// Create an announcement that freezes all the outputs of the token.
+    pub fn freeze(chroma: Chroma) -> Self {
+        Self::new(chroma, true)
+    }
+
+    /// Create an announcement that unfreezes the outputs of the token.
+    pub fn unfreeze(chroma: Chroma) -> Self {
+        Self::new(chroma, false)
+    }
+}
+
+#[cfg_attr(feature = "serde", typetag::serde(name = "chroma_freeze_announcement"))]
+impl AnyAnnouncement for ChromaFreezeAnnouncement {
+    fn kind(&self) -> AnnouncementKind {
+        CHROMA_FREEZE_ANNOUNCEMENT_KIND
+    }
+
+    fn from_announcement_data_bytes(data: &[u8]) -> Result<Self, AnnouncementParseError> {
+        if data.len() != CHROMA_FREEZE_ANNOUNCEMENT_SIZE {
+            return Err(ChromaFreezeAnnouncementParseError::InvalidSize(data.len()))?;
+        }
+
+        let chroma = Chroma::from_bytes(&data[..CHROMA_SIZE])
+            .map_err(ChromaFreezeAnnouncementParseError::from)?;
+
+        let frozen = match data[CHROMA_SIZE] {
+            0 => false,
+            1 => true,
+            flag => return Err(ChromaFreezeAnnouncementParseError::InvalidFlag(flag))?,
+        };
+
+        Ok(Self { chroma, frozen })
+    }
+
+    fn to_announcement_data_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(CHROMA_FREEZE_ANNOUNCEMENT_SIZE);
+
+        bytes.extend_from_slice(&self.chroma.to_bytes());
+        bytes.push(self.frozen as u8);
+
+        bytes
+    }
+}
+
+impl From<ChromaFreezeAnnouncement> for Announcement {
+    fn from(announcement: ChromaFreezeAnnouncement) -> Self {
+        Self::ChromaFreeze(announcement)
+    }
+}
+
+/// Errors that can occur when parsing [`ChromaFreezeAnnouncement`].
+#[derive(Debug)]
+pub enum ChromaFreezeAnnouncementParseError {
+    InvalidSize(usize),
+    InvalidChroma(ChromaParseError),
+    /// The frozen flag is neither `0` nor `1`.
+    InvalidFlag(u8),
+}
+
+impl fmt::Display for ChromaFreezeAnnouncementParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidSize(size) => write!(
+                f,
+                "invalid bytes size should be {}, got {}",
+                CHROMA_FREEZE_ANNOUNCEMENT_SIZE, size
+            ),
+            Self::InvalidChroma(e) => write!(f, "invalid chroma: {}", e),
+            Self::InvalidFlag(flag) => {
+                write!(f, "invalid frozen flag should be 0 or 1, got {}", flag)
+            }
+        }
+    }
+}
+
+#[cfg(not(feature = "no-std"))]
+impl std::error::Error for ChromaFreezeAnnouncementParseError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::InvalidChroma(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<ChromaParseError> for ChromaFreezeAnnouncementParseError {
+    fn from(err: ChromaParseError) -> Self {
+        Self::InvalidChroma(err)
+    }
+}
+
+impl From<ChromaFreezeAnnouncementParseError> for AnnouncementParseError {
+    fn from(err: ChromaFreezeAnnouncementParseError) -> Self {
+        AnnouncementParseError::InvalidAnnouncementData(err.to_string())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::announcements::chroma_freeze::CHROMA_FREEZE_ANNOUNCEMENT_SIZE;
+    use crate::announcements::{
+        announcement_from_bytes, announcement_from_script, AnnouncementParseError,
+        ChromaFreezeAnnouncement,
+    };
+    use crate::{Announcement, AnyAnnouncement};
+    use alloc::string::{String, ToString};
+    use alloc::vec::Vec;
+    use alloc::{format, vec};
+    use yuv_pixels::Chroma;
+
+    pub const TEST_CHROMA: &str =
+        "bcrt1p4v5dxtlzrrfuk57nxr3d6gwmtved47ulc55kcsk30h93e43ma2eqvrek30";
+
+    #[test]
+    fn test_serialize_desirialize() {
+        let chroma = Chroma::from_address(TEST_CHROMA).expect("valid chroma");
+
+        for announcement in [
+            ChromaFreezeAnnouncement::freeze(chroma),
+            ChromaFreezeAnnouncement::unfreeze(chroma),
+        ] {
+            let data = announcement.to_announcement_data_bytes();
+            let parsed = ChromaFreezeAnnouncement::from_announcement_data_bytes(&data).unwrap();
+            assert_eq!(parsed, announcement);
+
+            let bytes = announcement.to_bytes();
+            let parsed = announcement_from_bytes(&bytes).unwrap();
+            assert_eq!(parsed, Announcement::ChromaFreeze(announcement.clone()));
+
+            let script = announcement.to_script();
+            let parsed = ChromaFreezeAnnouncement::from_script(&script).unwrap();
+            assert_eq!(parsed, announcement);
+
+            let parsed = announcement_from_script(&script).unwrap();
+            assert_eq!(parsed, Announcement::ChromaFreeze(announcement));
+            assert_eq!(parsed.to_script(), script);
+        }
+    }
+
+    #[test]
+    fn parse_invalid_bytes() {
+        struct TestData {
+            bytes: Vec<u8>,
+            err: String,
+        }
+
+        let chroma = Chroma::from_address(TEST_CHROMA).expect("valid chroma");
+        let mut invalid_flag = chroma.to_bytes().to_vec();
+        invalid_flag.push(2);
+
+        let test_vector = vec![
+            TestData {
+                bytes: vec![0],
+                err: format!(
+                    "invalid bytes size should be {}, got 1",
+                    CHROMA_FREEZE_ANNOUNCEMENT_SIZE
+                ),
+            },
+            TestData {
+                bytes: vec![0; CHROMA_FREEZE_ANNOUNCEMENT_SIZE],
+                err: "invalid chroma: Invalid x only public key structure: malformed public key"
+                    .to_string(),
+            },
+            TestData {
+                bytes: invalid_flag,
+                err: "invalid frozen flag should be 0 or 1, got 2".to_string(),
+            },
+        ];
+
+        for test in test_vector {
+            match ChromaFreezeAnnouncement::from_announcement_data_bytes(&test.bytes) {
+                Err(AnnouncementParseError::InvalidAnnouncementData(err)) => {
+                    assert_eq!(err, test.err);
+                }
+                err => {
+                    panic!("Unexpected result: {:?}", err);
+                }
+            }
+        }
+    }
+}
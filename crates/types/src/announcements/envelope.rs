@@ -0,0 +1,443 @@
+//! Announcements committed in the Taproot script-path witness, inscription-style, instead of the
+//! `OP_RETURN` output.
+//!
+//! The envelope is a branch of the tapscript that is never executed:
+//!
+//! ```text
+//! OP_FALSE OP_IF <announcement bytes> OP_ENDIF
+//! ```
+//!
+//! The announcement bytes could be split into several pushes, as the size of one is limited by
+//! [`MAX_SCRIPT_ELEMENT_SIZE`].
+
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::fmt;
+
+use bitcoin::blockdata::constants::MAX_SCRIPT_ELEMENT_SIZE;
+use bitcoin::blockdata::opcodes::all::{OP_CHECKSIG, OP_ENDIF, OP_IF};
+use bitcoin::blockdata::opcodes::OP_FALSE;
+use bitcoin::blockdata::script::{self, Builder, Instruction};
+use bitcoin::util::taproot::{ControlBlock, TAPROOT_ANNEX_PREFIX};
+use bitcoin::{Script, Witness, XOnlyPublicKey};
+
+use crate::announcements::{announcement_from_bytes, is_announcement, Announcement};
+
+/// Parse the Bitcoin [`Witness`] of the Taproot script-path spend into an [`Announcement`]
+/// without specification of the [announcement kind].
+///
+/// # Returns
+///
+/// Returns the parsed announcement message or an error if there is no announcement envelope in
+/// the tapscript, or the data is invalid.
+///
+/// [announcement kind]: crate::announcements::AnnouncementKind
+pub fn announcement_from_witness(witness: &Witness) -> Result<Announcement, ParseWitnessError> {
+    parse_witness_envelope(witness, announcement_from_bytes)
+}
+
+/// Pull the bytes from the first announcement envelope in the tapscript of the [`Witness`] and
+/// parse them with the provided function.
+///
+/// Envelopes of the other protocols are skipped.
+///
+/// # Returns
+///
+/// Returns the parsed value or an [error] if the witness is not a Taproot script-path spend, the
+/// tapscript has no announcement envelope, or the parsing function returns an error.
+///
+/// [error]: ParseWitnessError
+pub fn parse_witness_envelope<T, ParseError, ParseFn>(
+    witness: &Witness,
+    parse_fn: ParseFn,
+) -> Result<T, ParseWitnessError>
+where
+    ParseError: fmt::Display,
+    ParseFn: FnOnce(&[u8]) -> Result<T, ParseError>,
+{
+    let tapscript = tapscript(witness).ok_or(ParseWitnessError::NoScriptPath)?;
+
+    let instructions = tapscript.instructions().collect::<Result<Vec<_>, _>>()?;
+
+    for bytes in envelopes(&instructions) {
+        if is_announcement(&bytes) {
+            return parse_fn(&bytes)
+                .map_err(|err| ParseWitnessError::InvalidEnvelopeData(err.to_string()));
+        }
+    }
+
+    Err(ParseWitnessError::NoEnvelope)
+}
+
+/// Get the key which signed the Taproot script-path spend of the envelope tapscript, i.e. the
+/// `<pubkey> OP_CHECKSIG` one [built] with the announcement envelope.
+///
+/// The key check must be followed only by the `OP_FALSE OP_IF ... OP_ENDIF` envelopes, as any
+/// other opcode could make the script spendable without the signature of the key (e.g.
+/// `<pubkey> OP_CHECKSIG OP_NOT` is spent with an empty one).
+///
+/// # Returns
+///
+/// Returns `None` if the witness is not a Taproot script-path spend, or the tapscript isn't the
+/// key check followed by the envelopes.
+///
+/// [built]: crate::announcements::AnyAnnouncement::to_envelope_script
+pub fn envelope_signer(witness: &Witness) -> Option<XOnlyPublicKey> {
+    let tapscript = tapscript(witness)?;
+    let instructions = tapscript
+        .instructions()
+        .collect::<Result<Vec<_>, _>>()
+        .ok()?;
+
+    let [Instruction::PushBytes(pubkey), checksig, rest @ ..] = instructions.as_slice() else {
+        return None;
+    };
+
+    if *checksig != Instruction::Op(OP_CHECKSIG) || !is_envelopes_only(rest) {
+        return None;
+    }
+
+    XOnlyPublicKey::from_slice(pubkey).ok()
+}
+
+/// Build the tapscript leaf which is spendable by the `pubkey` and has the envelope with the
+/// `bytes`.
+pub(crate) fn envelope_script(pubkey: &XOnlyPublicKey, bytes: &[u8]) -> Script {
+    let mut builder = Builder::new()
+        .push_x_only_key(pubkey)
+        .push_opcode(OP_CHECKSIG)
+        .push_opcode(OP_FALSE)
+        .push_opcode(OP_IF);
+
+    for chunk in bytes.chunks(MAX_SCRIPT_ELEMENT_SIZE) {
+        builder = builder.push_slice(chunk);
+    }
+
+    builder.push_opcode(OP_ENDIF).into_script()
+}
+
+/// Get the tapscript from the witness of the Taproot script-path spend.
+///
+/// The witness ends with the tapscript and the control block, which could be followed by the
+/// annex.
+fn tapscript(witness: &Witness) -> Option<Script> {
+    let mut elements = witness.to_vec();
+
+    if elements.len() >= 2
+        && elements
+            .last()
+            .is_some_and(|last| last.first() == Some(&TAPROOT_ANNEX_PREFIX))
+    {
+        elements.pop();
+    }
+
+    let control_block = elements.pop()?;
+    let tapscript = elements.pop()?;
+
+    ControlBlock::from_slice(&control_block).ok()?;
+
+    Some(Script::from(tapscript))
+}
+
+/// Get the concatenated pushes of each `OP_FALSE OP_IF ... OP_ENDIF` envelope.
+fn envelopes(instructions: &[Instruction]) -> Vec<Vec<u8>> {
+    let mut envelopes = Vec::new();
+    let mut i = 0;
+
+    while i + 1 < instructions.len() {
+        let is_envelope_start = matches!(instructions[i], Instruction::PushBytes(bytes) if bytes.is_empty())
+            && instructions[i + 1] == Instruction::Op(OP_IF);
+
+        if !is_envelope_start {
+            i += 1;
+            continue;
+        }
+
+        let mut bytes = Vec::new();
+        let mut j = i + 2;
+
+        while let Some(Instruction::PushBytes(push)) = instructions.get(j) {
+            bytes.extend_from_slice(push);
+            j += 1;
+        }
+
+        if instructions.get(j) == Some(&Instruction::Op(OP_ENDIF)) {
+            envelopes.push(bytes);
+        }
+
+        i = j;
+    }
+
+    envelopes
+}
+
+/// Check that the instructions are only the `OP_FALSE OP_IF ... OP_ENDIF` envelopes with the
+/// data pushes inside.
+fn is_envelopes_only(mut instructions: &[Instruction]) -> bool {
+    while !instructions.is_empty() {
+        let [Instruction::PushBytes(start), op_if, rest @ ..] = instructions else {
+            return false;
+        };
+
+        if !start.is_empty() || *op_if != Instruction::Op(OP_IF) {
+            return false;
+        }
+
+        let pushes = rest
+            .iter()
+            .take_while(|instruction| matches!(instruction, Instruction::PushBytes(_)))
+            .count();
+
+        let [op_endif, rest @ ..] = &rest[pushes..] else {
+            return false;
+        };
+
+        if *op_endif != Instruction::Op(OP_ENDIF) {
+            return false;
+        }
+
+        instructions = rest;
+    }
+
+    true
+}
+
+/// Error that can occur during the parsing of the announcement envelope from the Bitcoin
+/// [`Witness`].
+#[derive(Debug)]
+pub enum ParseWitnessError {
+    /// The witness is not a Taproot script-path spend.
+    NoScriptPath,
+    /// The tapscript has no announcement envelope.
+    NoEnvelope,
+    ScriptError(script::Error),
+    InvalidEnvelopeData(String),
+}
+
+impl fmt::Display for ParseWitnessError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NoScriptPath => write!(f, "witness is not a taproot script path spend"),
+            Self::NoEnvelope => write!(f, "no announcement envelope in tapscript"),
+            Self::ScriptError(e) => write!(f, "script error: {}", e),
+            Self::InvalidEnvelopeData(e) => write!(f, "invalid announcement: {}", e),
+        }
+    }
+}
+
+#[cfg(not(feature = "no-std"))]
+impl std::error::Error for ParseWitnessError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::ScriptError(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<script::Error> for ParseWitnessError {
+    fn from(err: script::Error) -> Self {
+        Self::ScriptError(err)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+    use core::str::FromStr;
+
+    use bitcoin::blockdata::opcodes::all::{OP_DROP, OP_NOT, OP_PUSHNUM_1};
+    use bitcoin::hashes::Hash;
+    use bitcoin::secp256k1::Secp256k1;
+    use bitcoin::util::taproot::{LeafVersion, TaprootBuilder};
+    use bitcoin::{OutPoint, Txid};
+    use yuv_pixels::Chroma;
+
+    use super::*;
+    use crate::announcements::{
+        ChromaAnnouncement, FreezeAnnouncement, IssueAnnouncement, ParseOpReturnError,
+    };
+    use crate::AnyAnnouncement;
+
+    static PUBKEY: &str = "5510996bdb5271f84896eb42ea5b6c4ba3bd96f90a605c70a7f2b402f0afdad0";
+
+    fn pubkey() -> XOnlyPublicKey {
+        XOnlyPublicKey::from_str(PUBKEY).unwrap()
+    }
+
+    /// Witness of the script-path spend of the `script`, with the dummy signature.
+    fn witness(script: &Script) -> Witness {
+        let secp = Secp256k1::verification_only();
+
+        let spend_info = TaprootBuilder::new()
+            .add_leaf(0, script.clone())
+            .unwrap()
+            .finalize(&secp, pubkey())
+            .unwrap();
+        let control_block = spend_info
+            .control_block(&(script.clone(), LeafVersion::TapScript))
+            .unwrap();
+
+        Witness::from_vec(vec![
+            vec![1; 64],
+            script.to_bytes(),
+            control_block.serialize(),
+        ])
+    }
+
+    fn announcements() -> Vec<Announcement> {
+        let chroma = Chroma::from(pubkey());
+
+        vec![
+            ChromaAnnouncement::new(chroma, "Test Token".into(), "TEST".into(), 8, 1000, true)
+                .unwrap()
+                .into(),
+            FreezeAnnouncement::new(OutPoint::new(Txid::all_zeros(), 1)).into(),
+            IssueAnnouncement::new(chroma, 1000).into(),
+        ]
+    }
+
+    #[test]
+    fn test_witness_envelope_round_trip() {
+        for announcement in announcements() {
+            let script = announcement.to_envelope_script(&pubkey());
+            let witness = witness(&script);
+
+            let parsed = announcement_from_witness(&witness).unwrap();
+            assert_eq!(parsed, announcement);
+
+            // Envelope isn't an `OP_RETURN` output.
+            assert!(matches!(
+                crate::announcements::announcement_from_script(&script),
+                Err(ParseOpReturnError::NoOpReturn)
+            ));
+        }
+
+        let announcement = IssueAnnouncement::new(Chroma::from(pubkey()), 1000);
+        let witness = witness(&announcement.to_envelope_script(&pubkey()));
+
+        assert_eq!(
+            IssueAnnouncement::from_witness(&witness).unwrap(),
+            announcement
+        );
+    }
+
+    #[test]
+    fn test_witness_envelope_with_annex() {
+        let announcement = IssueAnnouncement::new(Chroma::from(pubkey()), 1000);
+
+        let mut elements = witness(&announcement.to_envelope_script(&pubkey())).to_vec();
+        elements.push(vec![TAPROOT_ANNEX_PREFIX, 1, 2, 3]);
+
+        let parsed = announcement_from_witness(&Witness::from_vec(elements)).unwrap();
+        assert_eq!(parsed, announcement.into());
+    }
+
+    #[test]
+    fn test_witness_envelope_split_into_pushes() {
+        let bytes = (0..MAX_SCRIPT_ELEMENT_SIZE * 2 + 10)
+            .map(|i| i as u8)
+            .collect::<Vec<_>>();
+        let witness = witness(&envelope_script(&pubkey(), &bytes));
+
+        let parsed =
+            parse_witness_envelope(&witness, |data| Ok::<_, ParseWitnessError>(data.to_vec()));
+
+        // Data without the announcement prefix isn't the announcement envelope.
+        assert!(matches!(parsed, Err(ParseWitnessError::NoEnvelope)));
+
+        let mut bytes = bytes;
+        bytes[..3].copy_from_slice(&crate::announcements::ANNOUNCEMENT_PREFIX);
+        let witness = self::witness(&envelope_script(&pubkey(), &bytes));
+
+        let parsed =
+            parse_witness_envelope(&witness, |data| Ok::<_, ParseWitnessError>(data.to_vec()))
+                .unwrap();
+        assert_eq!(parsed, bytes);
+    }
+
+    #[test]
+    fn test_envelope_signer() {
+        let announcement = IssueAnnouncement::new(Chroma::from(pubkey()), 1000);
+        let witness = witness(&announcement.to_envelope_script(&pubkey()));
+
+        assert_eq!(envelope_signer(&witness), Some(pubkey()));
+
+        let key_path = Witness::from_vec(vec![vec![1; 64]]);
+        assert_eq!(envelope_signer(&key_path), None);
+
+        let without_key_check =
+            self::witness(&Builder::new().push_opcode(OP_CHECKSIG).into_script());
+        assert_eq!(envelope_signer(&without_key_check), None);
+
+        let key_check = Builder::new()
+            .push_x_only_key(&pubkey())
+            .push_opcode(OP_CHECKSIG)
+            .into_script();
+        assert_eq!(envelope_signer(&self::witness(&key_check)), Some(pubkey()));
+    }
+
+    #[test]
+    fn test_envelope_signer_with_trailing_opcodes() {
+        let envelope = IssueAnnouncement::new(Chroma::from(pubkey()), 1000)
+            .to_envelope_script(&pubkey())
+            .to_bytes();
+
+        // `<pubkey> OP_CHECKSIG OP_NOT` is spendable with an empty signature.
+        let mut negated = envelope.clone();
+        negated.push(OP_NOT.to_u8());
+        assert_eq!(envelope_signer(&witness(&Script::from(negated))), None);
+
+        let mut dropped = envelope.clone();
+        dropped.extend([OP_DROP.to_u8(), OP_PUSHNUM_1.to_u8()]);
+        assert_eq!(envelope_signer(&witness(&Script::from(dropped))), None);
+
+        let negated_before_envelope = Builder::new()
+            .push_x_only_key(&pubkey())
+            .push_opcode(OP_CHECKSIG)
+            .push_opcode(OP_NOT)
+            .push_opcode(OP_FALSE)
+            .push_opcode(OP_IF)
+            .push_slice(&[1, 2, 3])
+            .push_opcode(OP_ENDIF)
+            .into_script();
+        assert_eq!(envelope_signer(&witness(&negated_before_envelope)), None);
+
+        let unclosed_envelope = Builder::new()
+            .push_x_only_key(&pubkey())
+            .push_opcode(OP_CHECKSIG)
+            .push_opcode(OP_FALSE)
+            .push_opcode(OP_IF)
+            .push_slice(&[1, 2, 3])
+            .into_script();
+        assert_eq!(envelope_signer(&witness(&unclosed_envelope)), None);
+
+        let executed_branch = Builder::new()
+            .push_x_only_key(&pubkey())
+            .push_opcode(OP_CHECKSIG)
+            .push_int(1)
+            .push_opcode(OP_IF)
+            .push_slice(&[1, 2, 3])
+            .push_opcode(OP_ENDIF)
+            .into_script();
+        assert_eq!(envelope_signer(&witness(&executed_branch)), None);
+    }
+
+    #[test]
+    fn test_witness_without_envelope() {
+        let key_path = Witness::from_vec(vec![vec![1; 64]]);
+        assert!(matches!(
+            announcement_from_witness(&key_path),
+            Err(ParseWitnessError::NoScriptPath)
+        ));
+
+        let script = Builder::new()
+            .push_x_only_key(&pubkey())
+            .push_opcode(OP_CHECKSIG)
+            .into_script();
+        assert!(matches!(
+            announcement_from_witness(&witness(&script)),
+            Err(ParseWitnessError::NoEnvelope)
+        ));
+    }
+}
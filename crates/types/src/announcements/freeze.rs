@@ -6,6 +6,7 @@ use core::mem::size_of;
 use crate::{Announcement, AnyAnnouncement};
 use bitcoin::hashes::Hash;
 use bitcoin::{OutPoint, Txid};
+use yuv_pixels::Chroma;
 
 use crate::announcements::{AnnouncementKind, AnnouncementParseError};
 
@@ -102,6 +103,24 @@ impl From<FreezeAnnouncement> for OutPoint {
     }
 }
 
+/// Change of the frozen state caused by an attached freeze announcement, either of the single
+/// output ([`FreezeAnnouncement`]) or of all the outputs of the token
+/// ([`ChromaFreezeAnnouncement`]).
+///
+/// [`ChromaFreezeAnnouncement`]: crate::announcements::ChromaFreezeAnnouncement
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FreezeToggle {
+    /// Id of the announcement transaction that toggled the state.
+    pub txid: Txid,
+    /// Chroma of the token which outputs are affected.
+    pub chroma: Chroma,
+    /// Output that is toggled. `None` if the toggle affects all the outputs of the token.
+    pub outpoint: Option<OutPoint>,
+    /// Whether the affected outputs are frozen after the toggle.
+    pub frozen: bool,
+}
+
 /// Errors that can occur when parsing [freeze announcement].
 ///
 /// [freeze announcement]: FreezeAnnouncement
@@ -151,12 +170,14 @@ mod test {
     use crate::announcements::freeze::FREEZE_ENTRY_SIZE;
     use crate::announcements::{
         announcement_from_bytes, announcement_from_script, AnnouncementParseError,
-        FreezeAnnouncement,
+        FreezeAnnouncement, FREEZE_ANNOUNCEMENT_KIND, ISSUE_ANNOUNCEMENT_KIND,
     };
     use crate::{Announcement, AnyAnnouncement};
     use alloc::string::{String, ToString};
     use alloc::vec::Vec;
     use alloc::{format, vec};
+    use bitcoin::blockdata::opcodes::all::OP_RETURN;
+    use bitcoin::blockdata::script::Builder;
     use bitcoin::hashes::hex::FromHex;
     use bitcoin::{OutPoint, Script, Txid};
     use core::str::FromStr;
@@ -239,4 +260,38 @@ mod test {
         assert!(FreezeAnnouncement::from_announcement_data_bytes(&valid_announcement_data).is_ok());
         assert!(FreezeAnnouncement::from_script(&valid_announcement_script).is_ok());
     }
+
+    #[test]
+    fn test_announcement_of_other_kind_is_not_parsed_as_freeze() {
+        let outpoint = OutPoint {
+            txid: Txid::from_str(TEST_TXID).unwrap(),
+            vout: 34,
+        };
+
+        // Issue announcement which data has the shape of the freeze entry.
+        let mut bytes = FreezeAnnouncement { outpoint }.to_bytes();
+        bytes[3..5].copy_from_slice(&ISSUE_ANNOUNCEMENT_KIND);
+
+        let script = Builder::new()
+            .push_opcode(OP_RETURN)
+            .push_slice(&bytes)
+            .into_script();
+
+        match FreezeAnnouncement::from_bytes(&bytes) {
+            Err(AnnouncementParseError::UnexpectedAnnouncementKind { expected, got }) => {
+                assert_eq!(expected, FREEZE_ANNOUNCEMENT_KIND);
+                assert_eq!(got, ISSUE_ANNOUNCEMENT_KIND);
+            }
+            result => panic!("Unexpected result: {:?}", result),
+        }
+
+        assert!(FreezeAnnouncement::from_script(&script).is_err());
+        assert!(
+            !matches!(
+                announcement_from_script(&script),
+                Ok(Announcement::Freeze(_))
+            ),
+            "Announcement of other kind mustn't be indexed as a freeze"
+        );
+    }
 }
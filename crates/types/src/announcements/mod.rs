@@ -10,20 +10,33 @@ use bitcoin::blockdata::script;
 use bitcoin::blockdata::script::Instruction;
 use bitcoin::Script;
 pub use chroma::{
-    ChromaAnnouncement, ChromaInfo, CHROMA_ANNOUNCEMENT_KIND, MAX_CHROMA_ANNOUNCEMENT_SIZE,
-    MAX_NAME_SIZE, MAX_SYMBOL_SIZE, MIN_CHROMA_ANNOUNCEMENT_SIZE, MIN_NAME_SIZE, MIN_SYMBOL_SIZE,
+    format_amount, parse_amount, AmountParseError, ChromaAnnouncement, ChromaInfo,
+    CHROMA_ANNOUNCEMENT_KIND, MAX_CHROMA_ANNOUNCEMENT_SIZE, MAX_NAME_SIZE, MAX_SYMBOL_SIZE,
+    MIN_CHROMA_ANNOUNCEMENT_SIZE, MIN_NAME_SIZE, MIN_SYMBOL_SIZE,
+};
+pub use chroma_freeze::{
+    ChromaFreezeAnnouncement, ChromaFreezeAnnouncementParseError, CHROMA_FREEZE_ANNOUNCEMENT_KIND,
 };
 use core::fmt;
-pub use freeze::{FreezeAnnouncement, FreezeAnnouncementParseError, FREEZE_ANNOUNCEMENT_KIND};
+pub use envelope::{
+    announcement_from_witness, envelope_signer, parse_witness_envelope, ParseWitnessError,
+};
+pub use freeze::{
+    FreezeAnnouncement, FreezeAnnouncementParseError, FreezeToggle, FREEZE_ANNOUNCEMENT_KIND,
+};
 
 pub use issue::{IssueAnnouncement, ISSUE_ANNOUNCEMENT_KIND};
+pub use op_return::{FreezeToggleAnnouncement, YuvOpReturn};
 
 use crate::announcements::announcement::ANNOUNCEMENT_INSTRUCTION_NUMBER;
 
 mod announcement;
 mod chroma;
+mod chroma_freeze;
+mod envelope;
 mod freeze;
 mod issue;
+mod op_return;
 
 /// Parse the bytes into an [`Announcement`] without specification of the [announcement kind].
 ///
@@ -56,6 +69,9 @@ pub fn announcement_from_bytes(bytes: &[u8]) -> Result<Announcement, Announcemen
         ISSUE_ANNOUNCEMENT_KIND => Ok(Announcement::Issue(
             IssueAnnouncement::from_announcement_data_bytes(announcement_data)?,
         )),
+        CHROMA_FREEZE_ANNOUNCEMENT_KIND => Ok(Announcement::ChromaFreeze(
+            ChromaFreezeAnnouncement::from_announcement_data_bytes(announcement_data)?,
+        )),
         _ => Err(AnnouncementParseError::UnknownAnnouncementKind),
     }
 }
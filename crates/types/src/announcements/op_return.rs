@@ -0,0 +1,220 @@
+use bitcoin::Script;
+
+use crate::announcements::{
+    announcement_from_bytes, parse_op_return_script, AnnouncementParseError,
+    ChromaFreezeAnnouncement, FreezeAnnouncement, ParseOpReturnError,
+};
+use crate::Announcement;
+
+/// Payload of the `OP_RETURN` output, which is parsed once and shared by all its consumers (the
+/// indexer and the checker), so they don't disagree on what the output carries.
+///
+/// New kinds of the payload are added here as a new variant.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum YuvOpReturn {
+    /// Announcement of the token, e.g. its info or issuance.
+    Announcement(Announcement),
+    /// Announcement that toggles the frozen state of the outputs.
+    FreezeToggle(FreezeToggleAnnouncement),
+    /// `OP_RETURN` that isn't a YUV announcement known to this node, e.g. of other protocol.
+    Unknown,
+}
+
+/// Announcement that freezes or unfreezes either the single output or all the outputs of the
+/// token.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum FreezeToggleAnnouncement {
+    Output(FreezeAnnouncement),
+    Chroma(ChromaFreezeAnnouncement),
+}
+
+impl YuvOpReturn {
+    /// Parse the payload of the `OP_RETURN` script.
+    ///
+    /// # Returns
+    ///
+    /// Returns [`YuvOpReturn::Unknown`] if the script is `OP_RETURN`, but isn't an announcement
+    /// or the [announcement kind] is unknown. Returns an error if the script is not `OP_RETURN`
+    /// ([`ParseOpReturnError::NoOpReturn`]) or the data of the known announcement is invalid
+    /// ([`ParseOpReturnError::InvaliOpReturnData`]).
+    ///
+    /// [announcement kind]: crate::announcements::AnnouncementKind
+    pub fn from_script(script: &Script) -> Result<Self, ParseOpReturnError> {
+        let result = parse_op_return_script(script, |bytes| match announcement_from_bytes(bytes) {
+            Ok(announcement) => Ok(Self::from(announcement)),
+            Err(AnnouncementParseError::UnknownAnnouncementKind) => Ok(Self::Unknown),
+            Err(err) => Err(err),
+        });
+
+        match result {
+            Ok(op_return) => Ok(op_return),
+            Err(
+                err @ (ParseOpReturnError::NoOpReturn | ParseOpReturnError::InvaliOpReturnData(_)),
+            ) => Err(err),
+            Err(_) => Ok(Self::Unknown),
+        }
+    }
+
+    /// Return the announcement of any kind, or `None` if the payload is unknown.
+    pub fn into_announcement(self) -> Option<Announcement> {
+        match self {
+            Self::Announcement(announcement) => Some(announcement),
+            Self::FreezeToggle(toggle) => Some(toggle.into()),
+            Self::Unknown => None,
+        }
+    }
+}
+
+impl From<Announcement> for YuvOpReturn {
+    fn from(announcement: Announcement) -> Self {
+        match announcement {
+            Announcement::Freeze(freeze) => {
+                Self::FreezeToggle(FreezeToggleAnnouncement::Output(freeze))
+            }
+            Announcement::ChromaFreeze(freeze) => {
+                Self::FreezeToggle(FreezeToggleAnnouncement::Chroma(freeze))
+            }
+            announcement @ (Announcement::Chroma(_) | Announcement::Issue(_)) => {
+                Self::Announcement(announcement)
+            }
+        }
+    }
+}
+
+impl From<FreezeToggleAnnouncement> for Announcement {
+    fn from(toggle: FreezeToggleAnnouncement) -> Self {
+        match toggle {
+            FreezeToggleAnnouncement::Output(freeze) => Announcement::Freeze(freeze),
+            FreezeToggleAnnouncement::Chroma(freeze) => Announcement::ChromaFreeze(freeze),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use alloc::string::ToString;
+    use bitcoin::blockdata::opcodes::all::{OP_PUSHNUM_1, OP_RETURN};
+    use bitcoin::blockdata::script::Builder;
+    use bitcoin::{OutPoint, Script, Txid};
+    use core::str::FromStr;
+    use yuv_pixels::Chroma;
+
+    use crate::announcements::{
+        ChromaAnnouncement, ChromaFreezeAnnouncement, FreezeAnnouncement, FreezeToggleAnnouncement,
+        IssueAnnouncement, ParseOpReturnError, YuvOpReturn, ANNOUNCEMENT_PREFIX,
+        FREEZE_ANNOUNCEMENT_KIND,
+    };
+    use crate::{Announcement, AnyAnnouncement};
+
+    const TEST_CHROMA: &str = "bcrt1p4v5dxtlzrrfuk57nxr3d6gwmtved47ulc55kcsk30h93e43ma2eqvrek30";
+    const TEST_TXID: &str = "abc0000000000000000000000000000000000000000000000000000000000abc";
+
+    fn op_return(bytes: &[u8]) -> Script {
+        Builder::new()
+            .push_opcode(OP_RETURN)
+            .push_slice(bytes)
+            .into_script()
+    }
+
+    #[test]
+    fn test_parse_announcements() {
+        let chroma = Chroma::from_address(TEST_CHROMA).expect("valid chroma");
+
+        let chroma_announcement = Announcement::Chroma(
+            ChromaAnnouncement::new(chroma, "Token".to_string(), "TKN".to_string(), 2, 0, true)
+                .unwrap(),
+        );
+        let issue_announcement = Announcement::Issue(IssueAnnouncement::new(chroma, 100));
+
+        for announcement in [chroma_announcement, issue_announcement] {
+            assert_eq!(
+                YuvOpReturn::from_script(&announcement.to_script()).unwrap(),
+                YuvOpReturn::Announcement(announcement)
+            );
+        }
+    }
+
+    #[test]
+    fn test_parse_freeze_toggles() {
+        let chroma = Chroma::from_address(TEST_CHROMA).expect("valid chroma");
+        let outpoint = OutPoint::new(Txid::from_str(TEST_TXID).unwrap(), 1);
+
+        let freeze = FreezeAnnouncement { outpoint };
+        assert_eq!(
+            YuvOpReturn::from_script(&freeze.to_script()).unwrap(),
+            YuvOpReturn::FreezeToggle(FreezeToggleAnnouncement::Output(freeze.clone()))
+        );
+
+        for chroma_freeze in [
+            ChromaFreezeAnnouncement::freeze(chroma),
+            ChromaFreezeAnnouncement::unfreeze(chroma),
+        ] {
+            let parsed = YuvOpReturn::from_script(&chroma_freeze.to_script()).unwrap();
+
+            assert_eq!(
+                parsed,
+                YuvOpReturn::FreezeToggle(FreezeToggleAnnouncement::Chroma(chroma_freeze.clone()))
+            );
+            assert_eq!(
+                parsed.into_announcement(),
+                Some(Announcement::ChromaFreeze(chroma_freeze))
+            );
+        }
+
+        assert_eq!(
+            YuvOpReturn::from_script(&freeze.to_script())
+                .unwrap()
+                .into_announcement(),
+            Some(Announcement::Freeze(freeze))
+        );
+    }
+
+    #[test]
+    fn test_parse_unknown() {
+        let mut unknown_kind = ANNOUNCEMENT_PREFIX.to_vec();
+        unknown_kind.extend_from_slice(&[255, 255, 1, 2, 3]);
+
+        let other_protocol = op_return(b"other protocol");
+        let empty = Builder::new().push_opcode(OP_RETURN).into_script();
+        let opcode_instead_of_data = Builder::new()
+            .push_opcode(OP_RETURN)
+            .push_opcode(OP_PUSHNUM_1)
+            .into_script();
+        let many_pushes = Builder::new()
+            .push_opcode(OP_RETURN)
+            .push_slice(&ANNOUNCEMENT_PREFIX)
+            .push_slice(&ANNOUNCEMENT_PREFIX)
+            .into_script();
+
+        for script in [
+            op_return(&unknown_kind),
+            other_protocol,
+            empty,
+            opcode_instead_of_data,
+            many_pushes,
+        ] {
+            let parsed = YuvOpReturn::from_script(&script).unwrap();
+
+            assert_eq!(parsed, YuvOpReturn::Unknown, "script: {script}");
+            assert_eq!(parsed.into_announcement(), None);
+        }
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let not_op_return = Builder::new().push_opcode(OP_PUSHNUM_1).into_script();
+        assert!(matches!(
+            YuvOpReturn::from_script(&not_op_return),
+            Err(ParseOpReturnError::NoOpReturn)
+        ));
+
+        let mut invalid_freeze = ANNOUNCEMENT_PREFIX.to_vec();
+        invalid_freeze.extend_from_slice(&FREEZE_ANNOUNCEMENT_KIND);
+        invalid_freeze.extend_from_slice(&[0; 3]);
+
+        assert!(matches!(
+            YuvOpReturn::from_script(&op_return(&invalid_freeze)),
+            Err(ParseOpReturnError::InvaliOpReturnData(_))
+        ));
+    }
+}
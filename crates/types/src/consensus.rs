@@ -3,7 +3,7 @@ use alloc::vec::Vec;
 
 use bitcoin::consensus::encode::Error as EncodeError;
 use bitcoin::consensus::{encode, Decodable, Encodable};
-use bitcoin::Transaction;
+use bitcoin::{Transaction, Txid};
 
 use core2::io;
 use yuv_pixels::PixelProof;
@@ -11,7 +11,7 @@ use yuv_pixels::PixelProof;
 use crate::announcements::IssueAnnouncement;
 #[cfg(all(feature = "messages", feature = "std"))]
 use crate::messages::p2p::Inventory;
-use crate::{Announcement, ProofMap, YuvTransaction, YuvTxType};
+use crate::{Announcement, ProofMap, YuvProofBundle, YuvTransaction, YuvTxType};
 
 const ISSUE_CONSENSUS_FLAG: u8 = 0u8;
 const TRANSFER_CONSENSUS_FLAG: u8 = 1u8;
@@ -206,6 +206,26 @@ impl Decodable for YuvTransaction {
     }
 }
 
+impl Encodable for YuvProofBundle {
+    fn consensus_encode<W: io::Write + ?Sized>(&self, writer: &mut W) -> Result<usize, io::Error> {
+        let mut len = 0;
+
+        len += self.txid.consensus_encode(writer)?;
+        len += self.tx_type.consensus_encode(writer)?;
+
+        Ok(len)
+    }
+}
+
+impl Decodable for YuvProofBundle {
+    fn consensus_decode<R: io::Read + ?Sized>(reader: &mut R) -> Result<Self, EncodeError> {
+        let txid: Txid = Decodable::consensus_decode(reader)?;
+        let tx_type: YuvTxType = Decodable::consensus_decode(reader)?;
+
+        Ok(YuvProofBundle { txid, tx_type })
+    }
+}
+
 pub(crate) struct YuvTxsWrapper(pub Vec<YuvTransaction>);
 
 impl Encodable for YuvTxsWrapper {
@@ -224,16 +244,61 @@ impl Encodable for YuvTxsWrapper {
 
 impl Decodable for YuvTxsWrapper {
     fn consensus_decode<R: io::Read + ?Sized>(reader: &mut R) -> Result<Self, EncodeError> {
-        let len: u32 = Decodable::consensus_decode(reader)?;
-
-        let txs: Vec<YuvTransaction> = (0..len)
-            .map(|_i| Decodable::consensus_decode(reader))
-            .collect::<Result<Vec<_>, EncodeError>>()?;
+        let txs: Vec<YuvTransaction> =
+            YuvTxsDecoder::new(reader)?.collect::<Result<Vec<_>, EncodeError>>()?;
 
         Ok(YuvTxsWrapper(txs))
     }
 }
 
+/// Streaming decoder of the consensus encoded list of [`YuvTransaction`]s.
+///
+/// Reads the length prefix on creation, and then decodes transactions one at
+/// a time from the reader, so they could be processed as they arrive without
+/// allocating the whole list. Stops after the first decoding error.
+pub struct YuvTxsDecoder<R> {
+    reader: R,
+    /// Number of transactions left to decode.
+    remaining: u32,
+}
+
+impl<R: io::Read> YuvTxsDecoder<R> {
+    /// Create a new decoder by reading the number of transactions from the reader.
+    pub fn new(mut reader: R) -> Result<Self, EncodeError> {
+        let remaining: u32 = Decodable::consensus_decode(&mut reader)?;
+
+        Ok(Self { reader, remaining })
+    }
+
+    /// Number of transactions left to decode.
+    pub fn remaining(&self) -> u32 {
+        self.remaining
+    }
+}
+
+impl<R: io::Read> Iterator for YuvTxsDecoder<R> {
+    type Item = Result<YuvTransaction, EncodeError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+
+        let result = YuvTransaction::consensus_decode(&mut self.reader);
+
+        self.remaining = match result {
+            Ok(_) => self.remaining - 1,
+            Err(_) => 0,
+        };
+
+        Some(result)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, Some(self.remaining as usize))
+    }
+}
+
 #[cfg(all(feature = "messages", feature = "std"))]
 impl Encodable for Inventory {
     fn consensus_encode<W: io::Write + ?Sized>(&self, writer: &mut W) -> Result<usize, io::Error> {
@@ -300,10 +365,11 @@ mod tests {
     use alloc::vec;
     use alloc::vec::Vec;
 
-    use bitcoin::consensus::{Decodable, Encodable};
+    use bitcoin::consensus::{serialize, Decodable, Encodable};
     use once_cell::sync::Lazy;
 
-    use crate::{messages::p2p::Inventory, YuvTransaction};
+    use super::{YuvTxsDecoder, YuvTxsWrapper};
+    use crate::{messages::p2p::Inventory, YuvProofBundle, YuvTransaction};
 
     static YUV_TXS: Lazy<Vec<YuvTransaction>> = Lazy::new(|| {
         vec![
@@ -328,6 +394,69 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_yuv_tx_serialized_size() {
+        for tx in &*YUV_TXS {
+            assert_eq!(tx.serialized_size(), serialize(tx).len());
+            assert_eq!(
+                tx.serialized_size(),
+                serialize(&tx.bitcoin_tx).len() + tx.serialized_proof_size(),
+                "Size should be the sum of the Bitcoin tx and proofs sizes"
+            );
+        }
+    }
+
+    #[test]
+    fn test_yuv_proof_bundle_consensus_encode() {
+        for tx in &*YUV_TXS {
+            let bundle = YuvProofBundle::from(tx.clone());
+
+            let bytes = serialize(&bundle);
+            assert!(
+                bytes.len() < serialize(tx).len(),
+                "Bundle should be smaller than the tx"
+            );
+
+            let decoded_bundle = YuvProofBundle::consensus_decode(&mut bytes.as_slice())
+                .expect("failed to decode the bundle");
+
+            assert_eq!(
+                bundle, decoded_bundle,
+                "Converting back and forth should work"
+            );
+            assert_eq!(
+                decoded_bundle.into_yuv_tx(tx.bitcoin_tx.clone()).as_ref(),
+                Some(tx),
+                "Bundle should form the same tx"
+            );
+        }
+    }
+
+    #[test]
+    fn test_yuv_proof_bundle_cbor_encode() {
+        for tx in &*YUV_TXS {
+            let bundle = YuvProofBundle::from(tx.clone());
+
+            let mut bytes: Vec<u8> = Vec::new();
+            ciborium::into_writer(&bundle, &mut bytes).expect("failed to encode the bundle");
+
+            let decoded_bundle: YuvProofBundle =
+                ciborium::from_reader(bytes.as_slice()).expect("failed to decode the bundle");
+
+            assert_eq!(
+                bundle, decoded_bundle,
+                "Converting back and forth should work"
+            );
+        }
+    }
+
+    #[test]
+    fn test_yuv_proof_bundle_rejects_other_bitcoin_tx() {
+        let bundle = YuvProofBundle::from(YUV_TXS[0].clone());
+
+        assert_eq!(bundle.into_yuv_tx(YUV_TXS[1].bitcoin_tx.clone()), None);
+    }
+
     #[test]
     fn test_inventory_consensus_encode() {
         for tx in &*YUV_TXS {
@@ -346,4 +475,42 @@ mod tests {
             )
         }
     }
+
+    #[test]
+    fn test_yuv_txs_decoder_yields_txs_one_by_one() {
+        let bytes = serialize(&YuvTxsWrapper(YUV_TXS.clone()));
+
+        let mut decoder = YuvTxsDecoder::new(bytes.as_slice()).expect("failed to read length");
+        assert_eq!(decoder.remaining(), YUV_TXS.len() as u32);
+
+        for tx in &*YUV_TXS {
+            let decoded_tx = decoder
+                .next()
+                .expect("decoder must yield a tx")
+                .expect("failed to decode the tx");
+
+            assert_eq!(tx, &decoded_tx, "Decoded txs should be the same as encoded");
+        }
+
+        assert!(
+            decoder.next().is_none(),
+            "Decoder must stop after the last tx"
+        );
+    }
+
+    #[test]
+    fn test_yuv_txs_decoder_stops_after_error() {
+        let bytes = serialize(&YuvTxsWrapper(YUV_TXS.clone()));
+
+        // Cut the last transaction, so it can't be decoded.
+        let mut decoder =
+            YuvTxsDecoder::new(&bytes[..bytes.len() - 10]).expect("failed to read length");
+
+        for _ in 0..YUV_TXS.len() - 1 {
+            assert!(matches!(decoder.next(), Some(Ok(_))));
+        }
+
+        assert!(matches!(decoder.next(), Some(Err(_))));
+        assert!(decoder.next().is_none(), "Decoder must stop after an error");
+    }
 }
@@ -4,15 +4,17 @@ extern crate alloc;
 extern crate core;
 
 pub use announcements::{Announcement, AnyAnnouncement};
+#[cfg(feature = "consensus")]
+pub use consensus::YuvTxsDecoder;
 #[cfg(all(feature = "messages", feature = "std"))]
 pub use messages::{
     ControllerMessage, ControllerP2PMessage, GraphBuilderMessage, TxCheckerMessage,
-    TxConfirmMessage,
+    TxCheckerPoolMessage, TxConfirmMessage,
 };
 #[cfg(feature = "bulletproof")]
 pub use proofs::is_bulletproof;
 pub use proofs::{ProofMap, TransferProofs};
-pub use transactions::{YuvTransaction, YuvTxType};
+pub use transactions::{YuvProofBundle, YuvTransaction, YuvTxType};
 
 #[cfg(not(any(feature = "std", feature = "no-std")))]
 compile_error!("at least one of the `std` or `no-std` features must be enabled");
@@ -28,5 +30,8 @@ pub mod messages;
 
 mod proofs;
 
+#[cfg(feature = "mocks")]
+pub mod mocks;
+
 /// The default number of confirmations required to consider a block or transaction as confirmed.
 pub const DEFAULT_CONFIRMATIONS_NUMBER: u8 = 6;
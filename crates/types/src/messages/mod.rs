@@ -5,7 +5,7 @@ use core::fmt::Debug;
 use event_bus::Event;
 use std::net::SocketAddr;
 
-use crate::YuvTransaction;
+use crate::{announcements::FreezeToggle, YuvTransaction};
 
 use self::p2p::Inventory;
 
@@ -30,12 +30,21 @@ pub enum ControllerMessage {
     },
     /// Send signed transactions for on-chain confirmation.
     ConfirmBatchTx(Vec<YuvTransaction>),
+    /// Transactions that are ignored by the checker without the check, e.g. as
+    /// their chromas are filtered out.
+    IgnoredTxs(Vec<Txid>),
+    /// Chroma filter of the checkers is reloaded, so the ignored transactions
+    /// could be received again.
+    ChromaFilterReloaded,
     /// Remove checked announcement from handling transactions.
     CheckedAnnouncement(Txid),
     /// New inventory to share with peers.
     AttachedTxs(Vec<Txid>),
     /// Data that is received from p2p.
     P2P(ControllerP2PMessage),
+    /// Share the inventory of the attached transactions with peers right away, independently of
+    /// the sharing interval.
+    ShareInventory(Vec<Txid>),
 }
 
 /// Message from P2P to Controller.
@@ -73,6 +82,20 @@ pub enum TxCheckerMessage {
         /// * None if transactions received via json rpc
         sender: Option<SocketAddr>,
     },
+    /// Freeze announcements of the blocks disconnected from the best chain by a reorg, which
+    /// toggles are reverted.
+    ReorgedFreezes(Vec<YuvTransaction>),
+}
+
+/// Message to the pool of TxChecker workers.
+#[derive(Clone, Debug, Event)]
+pub enum TxCheckerPoolMessage {
+    /// Graph builder has too many pending transactions, so the workers stop
+    /// taking new transactions to check.
+    Pause,
+    /// Pending transactions of the graph builder are drained, so the workers
+    /// resume checking.
+    Resume,
 }
 
 /// Message to GraphBuilder service.
@@ -80,6 +103,14 @@ pub enum TxCheckerMessage {
 pub enum GraphBuilderMessage {
     /// Transactions to attach that already have been checked.
     CheckedTxs(Vec<YuvTransaction>),
+    /// Toggles of the frozen state applied by the checked freeze announcements, which are
+    /// pushed to the freeze sinks.
+    CheckedFreezes(Vec<FreezeToggle>),
+    /// New block was indexed, so transactions that are waiting for enough
+    /// confirmations could be attached.
+    NewBlock,
+    /// Prune the attached transactions that are no longer needed.
+    Prune,
 }
 
 /// Message to ConfirmationIndexer.
@@ -87,6 +118,9 @@ pub enum GraphBuilderMessage {
 pub enum TxConfirmMessage {
     /// Transactions that should be confirmed before sending to the tx checker.
     TxsToConfirm(Vec<YuvTransaction>),
-    /// Transactions that are confirmed.
-    ConfirmedTxIds(Vec<Txid>),
+    /// Transactions that are confirmed in the block at `block_height`.
+    ConfirmedTxIds {
+        block_height: u64,
+        tx_ids: Vec<Txid>,
+    },
 }
@@ -0,0 +1,116 @@
+//! Transactions shared by the tests of the crates which depend on `yuv-types`.
+
+use bitcoin::{
+    secp256k1::{Message, PublicKey, Secp256k1, SecretKey},
+    EcdsaSig, OutPoint, PackedLockTime, Transaction, TxIn, TxOut, Witness,
+};
+use yuv_pixels::{Chroma, P2WPKHWintessData, Pixel, PixelKey, PixelProof, SigPixelProof};
+
+use crate::{
+    announcements::IssueAnnouncement, AnyAnnouncement, ProofMap, YuvTransaction, YuvTxType,
+};
+
+/// Value of the pixel outputs in satoshis.
+pub const PIXEL_OUTPUT_VALUE: u64 = 1000;
+
+/// Transaction of the default type without inputs and outputs. Versions differ the txids.
+pub fn yuv_tx(version: i32) -> YuvTransaction {
+    YuvTransaction::new(
+        Transaction {
+            version,
+            lock_time: PackedLockTime::ZERO,
+            input: Vec::new(),
+            output: Vec::new(),
+        },
+        YuvTxType::default(),
+    )
+}
+
+/// Input spending the `previous_output` with the witness of the `pubkey`. Signatures of the
+/// inputs aren't verified by the checks, so an arbitrary message is signed.
+pub fn signed_input(previous_output: OutPoint, secret: &SecretKey, pubkey: PublicKey) -> TxIn {
+    let signature = Secp256k1::new().sign_ecdsa(&Message::from_slice(&[1; 32]).unwrap(), secret);
+
+    TxIn {
+        previous_output,
+        witness: Witness::from(P2WPKHWintessData::new(
+            EcdsaSig::sighash_all(signature),
+            bitcoin::PublicKey::new(pubkey),
+        )),
+        ..Default::default()
+    }
+}
+
+/// Issuance of the `amount` of the `issuer`'s tokens to the `recipient`. The issuance spends the
+/// null outpoint, so it's a coinbase transaction.
+pub fn issuance(issuer: &SecretKey, amount: u128, recipient: PublicKey) -> YuvTransaction {
+    let pubkey = issuer.public_key(&Secp256k1::new());
+    let chroma = Chroma::from(pubkey.x_only_public_key().0);
+
+    let pixel = Pixel::new(amount, chroma);
+    let announcement = IssueAnnouncement::new(chroma, amount);
+
+    YuvTransaction::new(
+        Transaction {
+            version: 2,
+            lock_time: PackedLockTime::ZERO,
+            input: vec![signed_input(OutPoint::null(), issuer, pubkey)],
+            output: vec![
+                pixel_output(pixel, recipient),
+                TxOut {
+                    value: 0,
+                    script_pubkey: announcement.to_script(),
+                },
+            ],
+        },
+        YuvTxType::Issue {
+            output_proofs: Some(ProofMap::from([(
+                0,
+                PixelProof::Sig(SigPixelProof::new(pixel, recipient)),
+            )])),
+            announcement,
+        },
+    )
+}
+
+/// Transfer of the `pixel` from the `parent` output of the `owner` to the `recipient`. Versions
+/// differ the transfers of the same output.
+pub fn transfer(
+    parent: OutPoint,
+    owner: &SecretKey,
+    pixel: Pixel,
+    recipient: PublicKey,
+    version: i32,
+) -> YuvTransaction {
+    let owner_pubkey = owner.public_key(&Secp256k1::new());
+    let pixel_key = PixelKey::new(pixel, &owner_pubkey).unwrap();
+
+    YuvTransaction::new(
+        Transaction {
+            version,
+            lock_time: PackedLockTime::ZERO,
+            input: vec![signed_input(parent, owner, pixel_key.0.inner)],
+            output: vec![pixel_output(pixel, recipient)],
+        },
+        YuvTxType::Transfer {
+            input_proofs: ProofMap::from([(
+                0,
+                PixelProof::Sig(SigPixelProof::new(pixel, owner_pubkey)),
+            )]),
+            output_proofs: ProofMap::from([(
+                0,
+                PixelProof::Sig(SigPixelProof::new(pixel, recipient)),
+            )]),
+        },
+    )
+}
+
+fn pixel_output(pixel: Pixel, recipient: PublicKey) -> TxOut {
+    TxOut {
+        value: PIXEL_OUTPUT_VALUE,
+        script_pubkey: PixelKey::new(pixel, &recipient)
+            .unwrap()
+            .to_p2wpkh()
+            .unwrap(),
+    }
+}
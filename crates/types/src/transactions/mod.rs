@@ -1,4 +1,7 @@
-use bitcoin::Transaction;
+use alloc::vec::Vec;
+
+use bitcoin::{Transaction, Txid};
+use yuv_pixels::{Chroma, PixelProof};
 
 use crate::announcements::{Announcement, IssueAnnouncement};
 use crate::ProofMap;
@@ -34,6 +37,56 @@ impl YuvTransaction {
             None => false,
         }
     }
+
+    /// Size of the consensus encoded transaction in bytes, including the proofs.
+    #[cfg(feature = "consensus")]
+    pub fn serialized_size(&self) -> usize {
+        bitcoin::consensus::serialize(self).len()
+    }
+
+    /// Size of the consensus encoded proofs (or announcement) of the transaction in bytes,
+    /// without the Bitcoin transaction.
+    #[cfg(feature = "consensus")]
+    pub fn serialized_proof_size(&self) -> usize {
+        bitcoin::consensus::serialize(&self.tx_type).len()
+    }
+}
+
+/// Proofs of the YUV transaction without the Bitcoin transaction itself.
+///
+/// Used to hand the proofs to a party that already has the Bitcoin transaction,
+/// which is resolved by the `txid` to form the [`YuvTransaction`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct YuvProofBundle {
+    pub txid: Txid,
+    pub tx_type: YuvTxType,
+}
+
+impl YuvProofBundle {
+    /// Create a new YUV proof bundle.
+    pub fn new(txid: Txid, tx_type: YuvTxType) -> Self {
+        Self { txid, tx_type }
+    }
+
+    /// Form the [`YuvTransaction`] from the bundle and resolved Bitcoin
+    /// transaction.
+    ///
+    /// Returns `None` if the id of the Bitcoin transaction doesn't match the
+    /// bundle's one.
+    pub fn into_yuv_tx(self, bitcoin_tx: Transaction) -> Option<YuvTransaction> {
+        if bitcoin_tx.txid() != self.txid {
+            return None;
+        }
+
+        Some(YuvTransaction::new(bitcoin_tx, self.tx_type))
+    }
+}
+
+impl From<YuvTransaction> for YuvProofBundle {
+    fn from(yuv_tx: YuvTransaction) -> Self {
+        Self::new(yuv_tx.bitcoin_tx.txid(), yuv_tx.tx_type)
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
@@ -68,6 +121,40 @@ impl YuvTxType {
             _ => None,
         }
     }
+
+    /// Chromas of the issuance or transfer, extracted from the announcement and the proofs.
+    pub fn chromas(&self) -> Vec<Chroma> {
+        let (announced, proofs): (Option<Chroma>, Vec<&PixelProof>) = match self {
+            Self::Issue {
+                announcement,
+                output_proofs,
+            } => (
+                Some(announcement.chroma),
+                output_proofs.iter().flat_map(ProofMap::values).collect(),
+            ),
+            Self::Transfer {
+                input_proofs,
+                output_proofs,
+            } => (
+                None,
+                input_proofs
+                    .values()
+                    .chain(output_proofs.values())
+                    .collect(),
+            ),
+            Self::Announcement(_) => (None, Vec::new()),
+        };
+
+        announced
+            .into_iter()
+            .chain(
+                proofs
+                    .into_iter()
+                    .filter(|proof| !proof.is_empty_pixelproof())
+                    .map(|proof| proof.pixel().chroma),
+            )
+            .collect()
+    }
 }
 
 impl Default for YuvTxType {
@@ -0,0 +1,47 @@
+//! Verify the YUV transaction from the JSON file in isolation, e.g.:
+//!
+//! ```sh
+//! cargo run -p yuv-verify --example verify_json -- \
+//!     crates/verify/src/tests/assets/singlechroma_valid_transfer.json
+//! ```
+use std::{env, fs, process};
+
+use yuv_verify::{check_transaction, check_transaction_collect_errors, YuvTransaction};
+
+fn main() {
+    let Some(path) = env::args().nth(1) else {
+        eprintln!("Usage: verify_json <path to YUV transaction JSON>");
+        process::exit(2);
+    };
+
+    let json = fs::read_to_string(&path).unwrap_or_else(|err| {
+        eprintln!("Failed to read {path}: {err}");
+        process::exit(2);
+    });
+
+    let yuv_tx: YuvTransaction = serde_json::from_str(&json).unwrap_or_else(|err| {
+        eprintln!("Failed to parse YUV transaction: {err}");
+        process::exit(2);
+    });
+
+    let txid = yuv_tx.bitcoin_tx.txid();
+
+    let Err(err) = check_transaction(&yuv_tx) else {
+        println!("Transaction {txid} is valid");
+        return;
+    };
+
+    println!("Transaction {txid} is invalid: {err}");
+
+    // Point to every invalid proof, not only the first one.
+    if let Ok(errors) = check_transaction_collect_errors(&yuv_tx) {
+        for (vin, err) in errors.inputs {
+            println!("  input {vin}: {err}");
+        }
+        for (vout, err) in errors.outputs {
+            println!("  output {vout}: {err}");
+        }
+    }
+
+    process::exit(1);
+}
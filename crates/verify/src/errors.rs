@@ -0,0 +1,232 @@
+use alloc::boxed::Box;
+use core::fmt::{self, Display};
+
+use bitcoin::{EcdsaSigError, OutPoint, Txid};
+
+use yuv_pixels::{PixelProof, PixelProofError};
+
+use crate::ProofKind;
+
+/// Errors that can occur during the transaction checking.
+#[derive(Debug)]
+pub enum CheckError {
+    /// Proof provided to transaction is not valid.
+    InvalidProof {
+        /// Proof that is not valid.
+        ///
+        /// `Box` is used here to reduce size of the enum.
+        proof: Box<PixelProof>,
+        /// Number of output in the transaction.
+        vout: u32,
+        /// Error that occurred during transaction checking.
+        error: PixelProofError,
+    },
+
+    NotEnoughProofs {
+        provided: usize,
+        required: usize,
+    },
+
+    /// There is no signature and/or pubkey in p2wpkh transaction.
+    InvalidWitness,
+
+    /// Input and/or output proofs has different chroma.
+    NotSameChroma,
+
+    /// Invalid public key.
+    InvalidKey(bitcoin::util::key::Error),
+
+    /// Invalid signature (in witness).
+    InvalidSignature(EcdsaSigError),
+
+    /// Sum of inputs is not equal to sum of outputs.
+    ConservationRulesViolated,
+
+    /// Sum of the amounts of the proofs overflows, so it can't be compared in the conservation
+    /// rules.
+    AmountOverflow,
+
+    /// Issuer of tokens is not the owner of the chroma.
+    IssuerNotOwner,
+
+    EmptyOutputs,
+
+    EmptyInputs,
+
+    InputNotFound,
+
+    OutputNotFound,
+
+    /// Proof mapped to not existing input or outputm, which is considered as
+    /// invalid proof for that transaction.
+    ProofMappedToNotExistingInputOutput,
+
+    /// Transaction has the bulletproof pixel proofs and non-bulletproof one
+    MixedBulletproofsAndNonBulletproofs,
+
+    /// To verify transaction, at least one commitment is needed.
+    AtLeastOneCommitment,
+
+    InvalidVerifier,
+
+    TxNotFound(Txid),
+
+    InvalidPublicKey,
+
+    PublicKeyNotFound,
+
+    MessageKeyNotFound,
+
+    #[cfg(feature = "bulletproof")]
+    NotBulletproof,
+
+    /// Range proof doesn't prove that the committed value is in the `[0, 2^128)` range.
+    #[cfg(feature = "bulletproof")]
+    RangeProofInvalid,
+
+    AnnouncedAmountDoesNotMatch(u128, u128),
+
+    IssueAnnouncementMismatch,
+
+    IssueAnnouncementNotProvided,
+
+    /// Proof of the issuance is mapped to the `OP_RETURN` output, which can't hold tokens.
+    ProofMappedToOpReturn(u32),
+
+    /// Two proven inputs spend the same outpoint, so its amount would be counted twice.
+    DuplicateInput(OutPoint),
+
+    /// Pixel proof of the output has zero amount. Outputs without tokens must be proven with the
+    /// empty pixel proof.
+    ZeroAmountOutput(u32),
+
+    /// Input spends the coinbase output which can't be spent yet by Bitcoin consensus rules.
+    ImmatureCoinbase {
+        outpoint: OutPoint,
+        confirmations: u32,
+    },
+
+    /// Type of the output's script can't match the proof, e.g. P2WSH output with the single
+    /// signature proof, so the proof is rejected without deriving its tweaked key.
+    UnexpectedOutputScript {
+        vout: u32,
+        kind: ProofKind,
+    },
+
+    /// Output's script doesn't commit to the hash of the pixel key tweaked by the proof, so the
+    /// proof is rejected before the other proofs of the transaction are checked.
+    OutputKeyMismatch {
+        vout: u32,
+        kind: ProofKind,
+    },
+}
+
+impl Display for CheckError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CheckError::InvalidProof { proof, vout, error } => {
+                write!(f, "Invalid proof {proof:?} for {vout}: {error}")
+            }
+            CheckError::NotEnoughProofs { .. } => write!(
+                f,
+                "Number of pixel proofs must be the same as the number of Bitcoin outputs"
+            ),
+            CheckError::InvalidWitness => write!(f, "Invalid witness structure"),
+            CheckError::NotSameChroma => write!(f, "Chroma of proofs is not the same"),
+            CheckError::InvalidKey(e) => write!(f, "Invalid public key: {e}"),
+            CheckError::InvalidSignature(e) => write!(f, "Invalid signature : {e}"),
+            CheckError::ConservationRulesViolated => {
+                write!(f, "Sum of inputs is not equal to sum of outputs")
+            }
+            CheckError::AmountOverflow => write!(f, "Sum of the amounts overflows"),
+            CheckError::IssuerNotOwner => write!(f, "Issuer is not the owner of the chroma"),
+            CheckError::EmptyOutputs => write!(f, "Empty outputs"),
+            CheckError::EmptyInputs => write!(f, "Empty inputs"),
+            CheckError::InputNotFound => write!(f, "Input transaction not found"),
+            CheckError::OutputNotFound => write!(f, "Transaction output not found"),
+            CheckError::ProofMappedToNotExistingInputOutput => {
+                write!(f, "Proof mapped to not existing input/output")
+            }
+            CheckError::MixedBulletproofsAndNonBulletproofs => {
+                write!(f, "Mixed bulletproofs and non-bulletproofs")
+            }
+            CheckError::AtLeastOneCommitment => write!(
+                f,
+                "To verify transaction, at least one commitment is needed"
+            ),
+            CheckError::InvalidVerifier => write!(f, "Invalid verifier"),
+            CheckError::TxNotFound(txid) => write!(f, "Tx not found {txid}"),
+            CheckError::InvalidPublicKey => {
+                write!(f, "Commitments result in an invalid public key")
+            }
+            CheckError::PublicKeyNotFound => {
+                write!(f, "Public key to verify a signature not found")
+            }
+            CheckError::MessageKeyNotFound => write!(f, "Message to verify a signature not found"),
+            #[cfg(feature = "bulletproof")]
+            CheckError::NotBulletproof => write!(f, "Transaction type is not bulletproof"),
+            #[cfg(feature = "bulletproof")]
+            CheckError::RangeProofInvalid => write!(f, "Range proof of the bulletproof is invalid"),
+            CheckError::AnnouncedAmountDoesNotMatch(announced, proven) => write!(
+                f,
+                "Announced amount {announced} does not match to amount in pixel proofs {proven}"
+            ),
+            CheckError::IssueAnnouncementMismatch => write!(
+                f,
+                "Provided announcement mismatch with the announcement in transaction"
+            ),
+            CheckError::IssueAnnouncementNotProvided => {
+                write!(f, "Provided transaction doesn't have an announcement")
+            }
+            CheckError::ProofMappedToOpReturn(vout) => {
+                write!(f, "Proof is mapped to OP_RETURN output {vout}")
+            }
+            CheckError::DuplicateInput(outpoint) => {
+                write!(f, "Outpoint {outpoint} is spent by more than one input")
+            }
+            CheckError::ZeroAmountOutput(vout) => write!(
+                f,
+                "Proof of output {vout} has zero amount, outputs without tokens must have empty pixel proof"
+            ),
+            CheckError::ImmatureCoinbase {
+                outpoint,
+                confirmations,
+            } => write!(
+                f,
+                "Output {outpoint} is an immature coinbase output with {confirmations} confirmations"
+            ),
+            CheckError::UnexpectedOutputScript { vout, kind } => write!(
+                f,
+                "Script type of output {vout} doesn't match its proof of type {kind:?}"
+            ),
+            CheckError::OutputKeyMismatch { vout, kind } => write!(
+                f,
+                "Script of output {vout} doesn't commit to the pixel key of its proof of type {kind:?}"
+            ),
+        }
+    }
+}
+
+#[cfg(not(feature = "no-std"))]
+impl std::error::Error for CheckError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            CheckError::InvalidProof { error, .. } => Some(error),
+            CheckError::InvalidKey(e) => Some(e),
+            CheckError::InvalidSignature(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<bitcoin::util::key::Error> for CheckError {
+    fn from(err: bitcoin::util::key::Error) -> Self {
+        CheckError::InvalidKey(err)
+    }
+}
+
+impl From<EcdsaSigError> for CheckError {
+    fn from(err: EcdsaSigError) -> Self {
+        CheckError::InvalidSignature(err)
+    }
+}
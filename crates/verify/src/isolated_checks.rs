@@ -1,6 +1,9 @@
-use std::collections::HashMap;
+use alloc::boxed::Box;
+use alloc::vec::Vec;
 
-use bitcoin::{self, secp256k1::Secp256k1, Transaction, TxIn, TxOut};
+use hashbrown::{HashMap, HashSet};
+
+use bitcoin::{self, secp256k1::Secp256k1, OutPoint, Script, Transaction, TxIn, TxOut};
 
 #[cfg(feature = "bulletproof")]
 use {
@@ -10,20 +13,24 @@ use {
     },
     yuv_pixels::{
         k256::{elliptic_curve::group::GroupEncoding, ProjectivePoint},
-        Bulletproof,
+        verify_bulletproof, Bulletproof,
     },
     yuv_types::is_bulletproof,
 };
 
-use yuv_types::{AnyAnnouncement, ProofMap};
+use yuv_types::ProofMap;
 
 use yuv_pixels::{
-    CheckableProof, Chroma, P2WPKHWintessData, Pixel, PixelKey, PixelProof, ToEvenPublicKey,
+    CheckableProof, Chroma, P2WPKHWintessData, Pixel, PixelKey, PixelProof, PixelProofError,
+    ToEvenPublicKey,
 };
 
-use yuv_types::{announcements::IssueAnnouncement, YuvTransaction, YuvTxType};
+use yuv_types::{
+    announcements::{envelope_signer, IssueAnnouncement, YuvOpReturn},
+    Announcement, AnyAnnouncement, YuvTransaction, YuvTxType,
+};
 
-use crate::errors::CheckError;
+use crate::{errors::CheckError, ProofKind};
 
 /// Checks transactions' correctness in terms of conservation rules and provided proofs.
 pub fn check_transaction(yuv_tx: &YuvTransaction) -> Result<(), CheckError> {
@@ -42,7 +49,86 @@ pub fn check_transaction(yuv_tx: &YuvTransaction) -> Result<(), CheckError> {
     }
 }
 
-pub(crate) fn check_issue_isolated(
+/// Errors of the invalid proofs found by [`check_transaction_collect_errors`], with the
+/// indexes of the inputs and outputs they are mapped to.
+#[derive(Debug, Default)]
+pub struct ProofErrors {
+    /// Errors of the input proofs.
+    pub inputs: Vec<(u32, PixelProofError)>,
+    /// Errors of the output proofs.
+    pub outputs: Vec<(u32, PixelProofError)>,
+}
+
+impl ProofErrors {
+    /// Check if all the proofs are valid.
+    pub fn is_empty(&self) -> bool {
+        self.inputs.is_empty() && self.outputs.is_empty()
+    }
+}
+
+/// Checks all the proofs of the transaction, collecting the errors of every invalid one instead
+/// of stopping at the first. Conservation rules are not checked, so use [`check_transaction`]
+/// for the full check.
+///
+/// Returns an error if the proofs can't be checked at all, e.g. when they are mapped to
+/// not existing inputs or outputs.
+pub fn check_transaction_collect_errors(
+    yuv_tx: &YuvTransaction,
+) -> Result<ProofErrors, CheckError> {
+    let tx = &yuv_tx.bitcoin_tx;
+
+    let (input_proofs, output_proofs) = match &yuv_tx.tx_type {
+        YuvTxType::Issue { output_proofs, .. } => {
+            let Some(output_proofs) = output_proofs else {
+                return Err(CheckError::NotEnoughProofs {
+                    provided: 0,
+                    required: tx.output.len(),
+                });
+            };
+
+            check_no_proofs_mapped_to_op_return(tx, output_proofs)?;
+
+            (None, output_proofs)
+        }
+        YuvTxType::Transfer {
+            input_proofs,
+            output_proofs,
+        } => (Some(input_proofs), output_proofs),
+        YuvTxType::Announcement(_) => return Ok(ProofErrors::default()),
+    };
+
+    check_number_of_proofs(tx, output_proofs)?;
+
+    let mut errors = ProofErrors::default();
+
+    if let Some(input_proofs) = input_proofs {
+        for ProofForCheck {
+            inner,
+            vout,
+            statement: txin,
+        } in extract_from_iterable_by_proof_map(input_proofs, &tx.input)?
+        {
+            if let Err(error) = inner.checked_check_by_input(txin) {
+                errors.inputs.push((vout, error));
+            }
+        }
+    }
+
+    for ProofForCheck {
+        inner,
+        vout,
+        statement: txout,
+    } in extract_from_iterable_by_proof_map(output_proofs, &tx.output)?
+    {
+        if let Err(error) = inner.checked_check_by_output(txout) {
+            errors.outputs.push((vout, error));
+        }
+    }
+
+    Ok(errors)
+}
+
+pub fn check_issue_isolated(
     tx: &Transaction,
     output_proofs_opt: &Option<ProofMap>,
     announcement: &IssueAnnouncement,
@@ -55,10 +141,13 @@ pub(crate) fn check_issue_isolated(
     };
 
     let announced_amount = check_issue_announcement(tx, announcement)?;
+    check_no_proofs_mapped_to_op_return(tx, output_proofs)?;
     check_number_of_proofs(tx, output_proofs)?;
+    check_no_zero_amount_outputs(output_proofs)?;
     check_same_chroma_proofs(&output_proofs.values().collect::<Vec<_>>())?;
 
     let gathered_outputs = extract_from_iterable_by_proof_map(output_proofs, &tx.output)?;
+    precheck_output_scripts(&gathered_outputs)?;
 
     for ProofForCheck {
         inner,
@@ -66,10 +155,6 @@ pub(crate) fn check_issue_isolated(
         statement,
     } in gathered_outputs.iter()
     {
-        if statement.script_pubkey.is_op_return() {
-            continue;
-        }
-
         inner
             .checked_check_by_output(statement)
             .map_err(|error| CheckError::InvalidProof {
@@ -86,10 +171,12 @@ pub(crate) fn check_issue_isolated(
         return Ok(());
     }
 
-    let total_amount = output_proofs
-        .values()
-        .map(|proof| proof.pixel().luma.amount)
-        .sum::<u128>();
+    let total_amount = gathered_outputs
+        .iter()
+        .try_fold(0u128, |sum, output| {
+            sum.checked_add(output.inner.pixel().luma.amount)
+        })
+        .ok_or(CheckError::AmountOverflow)?;
 
     if total_amount != announced_amount {
         return Err(CheckError::AnnouncedAmountDoesNotMatch(
@@ -106,7 +193,21 @@ fn check_issue_announcement(
     provided_announcement: &IssueAnnouncement,
 ) -> Result<u128, CheckError> {
     for output in bitcoin_tx.output.iter() {
-        if let Ok(found_announcement) = IssueAnnouncement::from_script(&output.script_pubkey) {
+        if let Ok(YuvOpReturn::Announcement(Announcement::Issue(found_announcement))) =
+            YuvOpReturn::from_script(&output.script_pubkey)
+        {
+            if found_announcement.ne(provided_announcement) {
+                return Err(CheckError::IssueAnnouncementMismatch);
+            }
+
+            return Ok(found_announcement.amount);
+        }
+    }
+
+    // Announcements in `OP_RETURN` outputs take precedence over the ones committed in the
+    // Taproot witness envelopes of the inputs.
+    for input in bitcoin_tx.input.iter() {
+        if let Ok(found_announcement) = IssueAnnouncement::from_witness(&input.witness) {
             if found_announcement.ne(provided_announcement) {
                 return Err(CheckError::IssueAnnouncementMismatch);
             }
@@ -118,16 +219,28 @@ fn check_issue_announcement(
     Ok(0)
 }
 
-pub(crate) fn check_transfer_isolated(
+/// Checks the proofs of the transfer and its conservation rules.
+///
+/// Only the inputs present in `inputs` are YUV ones, and every one of them must be proven by
+/// its proof, so the amounts they claim are backed. Inputs absent from the map (e.g. the ones
+/// that pay the fee) are treated as non-YUV: they aren't checked and don't add to the sum of
+/// inputs in the conservation rules. YUV coins of the output spent by such input are burnt.
+pub fn check_transfer_isolated(
     tx: &Transaction,
     inputs: &ProofMap,
     outputs: &ProofMap,
 ) -> Result<(), CheckError> {
     check_number_of_proofs(tx, outputs)?;
+    check_no_zero_amount_outputs(outputs)?;
 
+    // Only the claimed YUV inputs are gathered, so each of them is checked below and
+    // the other inputs are skipped.
     let gathered_inputs = extract_from_iterable_by_proof_map(inputs, &tx.input)?;
     let gathered_outputs = extract_from_iterable_by_proof_map(outputs, &tx.output)?;
 
+    check_unique_inputs(&gathered_inputs)?;
+    precheck_output_scripts(&gathered_outputs)?;
+
     for ProofForCheck {
         inner,
         vout,
@@ -169,6 +282,139 @@ pub(crate) fn check_transfer_isolated(
     Ok(())
 }
 
+/// Rejects the outputs which script can't match their proofs, so the transactions with such
+/// outputs are rejected without checking the other proofs: the single signature proof requires
+/// P2WPKH or P2SH-wrapped P2WPKH output, and the empty pixel and bulletproof ones require P2WPKH
+/// output, which commits to the hash of the pixel key tweaked by the proof. Other proofs are left
+/// to the full check.
+fn precheck_output_scripts(outputs: &[ProofForCheck<&TxOut>]) -> Result<(), CheckError> {
+    for ProofForCheck {
+        inner,
+        vout,
+        statement: txout,
+    } in outputs
+    {
+        let (pixel, inner_key, allows_p2sh) = match inner {
+            PixelProof::Sig(proof) => (proof.pixel, &proof.inner_key, true),
+            PixelProof::EmptyPixel(proof) => (Pixel::empty(), &proof.inner_key, false),
+            #[cfg(feature = "bulletproof")]
+            PixelProof::Bulletproof(proof) => (proof.pixel, &proof.inner_key, false),
+            _ => continue,
+        };
+
+        let script = &txout.script_pubkey;
+        let kind = ProofKind::from(*inner);
+
+        let is_p2sh = allows_p2sh && script.is_p2sh();
+        if !script.is_v0_p2wpkh() && !is_p2sh {
+            return Err(CheckError::UnexpectedOutputScript { vout: *vout, kind });
+        }
+
+        let pubkey_hash = PixelKey::new(pixel, inner_key)
+            .ok()
+            .and_then(|pixel_key| pixel_key.wpubkey_hash());
+
+        let is_expected_key = pubkey_hash.is_some_and(|pubkey_hash| {
+            let p2wpkh = Script::new_v0_p2wpkh(&pubkey_hash);
+
+            if is_p2sh {
+                *script == p2wpkh.to_p2sh()
+            } else {
+                *script == p2wpkh
+            }
+        });
+
+        if !is_expected_key {
+            return Err(CheckError::OutputKeyMismatch { vout: *vout, kind });
+        }
+    }
+
+    Ok(())
+}
+
+/// Checks that the proven inputs spend distinct outpoints, so none of them is counted twice in
+/// the conservation rules.
+fn check_unique_inputs(inputs: &[ProofForCheck<&TxIn>]) -> Result<(), CheckError> {
+    let mut outpoints = HashSet::with_capacity(inputs.len());
+
+    for input in inputs {
+        if !outpoints.insert(input.statement.previous_output) {
+            return Err(CheckError::DuplicateInput(input.statement.previous_output));
+        }
+    }
+
+    Ok(())
+}
+
+/// Number of confirmations after which the coinbase output can be spent by the transaction
+/// in the next block.
+pub const COINBASE_MATURITY: u32 = 100;
+
+/// Checks that the `outpoint` of the `parent` transaction with the given number of
+/// `confirmations` could be spent, i.e. it's not an immature coinbase output.
+///
+/// The transactions attached by the node are already mined, so the check is needed for the
+/// ones which are not, e.g. on emulation, as spending an immature coinbase output makes the
+/// transaction never mined.
+pub fn check_coinbase_maturity(
+    outpoint: OutPoint,
+    parent: &Transaction,
+    confirmations: u32,
+) -> Result<(), CheckError> {
+    if parent.is_coin_base() && confirmations < COINBASE_MATURITY {
+        return Err(CheckError::ImmatureCoinbase {
+            outpoint,
+            confirmations,
+        });
+    }
+
+    Ok(())
+}
+
+/// Check that none of the proofs is mapped to the `OP_RETURN` output, e.g. the announcement one.
+fn check_no_proofs_mapped_to_op_return(
+    bitcoin_tx: &Transaction,
+    proofs: &ProofMap,
+) -> Result<(), CheckError> {
+    for vout in proofs.keys() {
+        let is_op_return = bitcoin_tx
+            .output
+            .get(*vout as usize)
+            .is_some_and(|output| output.script_pubkey.is_op_return());
+
+        if is_op_return {
+            return Err(CheckError::ProofMappedToOpReturn(*vout));
+        }
+    }
+
+    Ok(())
+}
+
+/// Check that none of the output proofs has a pixel with zero amount.
+///
+/// Zero-amount pixel doesn't move any tokens, so the outputs without them (e.g. the change in
+/// satoshis) must be proven with the [empty pixel proof] instead, and the transaction can't be
+/// padded with the pixel proofs that aren't counted in the conservation rules. Bulletproofs are
+/// skipped, as their amounts are hidden.
+///
+/// Inputs aren't checked, so the zero-amount outputs created before the rule can be spent.
+///
+/// [empty pixel proof]: PixelProof::EmptyPixel
+fn check_no_zero_amount_outputs(proofs: &ProofMap) -> Result<(), CheckError> {
+    for (vout, proof) in proofs {
+        #[cfg(feature = "bulletproof")]
+        if proof.is_bulletproof() {
+            continue;
+        }
+
+        if !proof.is_empty_pixelproof() && proof.pixel().luma.amount == 0 {
+            return Err(CheckError::ZeroAmountOutput(*vout));
+        }
+    }
+
+    Ok(())
+}
+
 fn check_number_of_proofs(bitcoin_tx: &Transaction, proofs: &ProofMap) -> Result<(), CheckError> {
     if bitcoin_tx
         .output
@@ -233,8 +479,8 @@ pub(crate) fn check_transfer_conservation_rules(
     inputs: &[ProofForCheck<&TxIn>],
     outputs: &[ProofForCheck<&TxOut>],
 ) -> Result<(), CheckError> {
-    let input_chromas = sum_amount_by_chroma(inputs);
-    let output_chromas = sum_amount_by_chroma(outputs);
+    let input_chromas = sum_amount_by_chroma(inputs)?;
+    let output_chromas = sum_amount_by_chroma(outputs)?;
 
     if input_chromas != output_chromas {
         return Err(CheckError::ConservationRulesViolated);
@@ -243,7 +489,14 @@ pub(crate) fn check_transfer_conservation_rules(
     Ok(())
 }
 
-fn sum_amount_by_chroma<T>(proofs: &[ProofForCheck<T>]) -> HashMap<Chroma, u128> {
+/// Sum the amounts of the proofs by chroma. Empty pixel proofs and zero-amount pixels (which are
+/// only allowed in the inputs, see [`check_no_zero_amount_outputs`]) are skipped.
+///
+/// Returns [`CheckError::AmountOverflow`] if the sum of any chroma overflows, as the wrapped one
+/// could match the sum of the other side.
+fn sum_amount_by_chroma<T>(
+    proofs: &[ProofForCheck<T>],
+) -> Result<HashMap<Chroma, u128>, CheckError> {
     let mut chromas: HashMap<Chroma, u128> = HashMap::new();
 
     for proof in proofs {
@@ -254,10 +507,12 @@ fn sum_amount_by_chroma<T>(proofs: &[ProofForCheck<T>]) -> HashMap<Chroma, u128>
         }
 
         let chroma_sum = chromas.entry(pixel.chroma).or_insert(0);
-        *chroma_sum += pixel.luma.amount;
+        *chroma_sum = chroma_sum
+            .checked_add(pixel.luma.amount)
+            .ok_or(CheckError::AmountOverflow)?;
     }
 
-    chromas
+    Ok(chromas)
 }
 
 /// Check that proofs of the issuance do not violate conservation rules (that chroma (asset type)
@@ -306,17 +561,22 @@ fn check_same_chroma_proofs(proofs: &[&PixelProof]) -> Result<(), CheckError> {
 }
 
 /// Find issuer of the transaction in the inputs by chroma.
-pub(crate) fn find_issuer_in_txinputs<'a>(inputs: &'a [TxIn], chroma: &Chroma) -> Option<&'a TxIn> {
+pub fn find_issuer_in_txinputs<'a>(inputs: &'a [TxIn], chroma: &Chroma) -> Option<&'a TxIn> {
     let ctx = Secp256k1::new();
     inputs.iter().find(|input| {
-        // Skip entry if it's not p2wpkh
+        // Skip entry if it's neither p2wpkh nor the Taproot spend of the announcement envelope.
+        // Witness of P2SH-wrapped p2wpkh input has the same structure, so such inputs are
+        // recognized too.
         //
         // TODO: may be, in future, we should support other types of inputs.
-        let Ok(witness) = P2WPKHWintessData::from_witness(&input.witness) else {
-            return false;
+        let xonly_public_key = match P2WPKHWintessData::from_witness(&input.witness) {
+            Ok(witness) => witness.pubkey.inner.x_only_public_key().0,
+            Err(_) => match envelope_signer(&input.witness) {
+                Some(signer) => signer,
+                None => return false,
+            },
         };
 
-        let (xonly_public_key, _parity) = witness.pubkey.inner.x_only_public_key();
         // It's also necessary to check if the witness pubkey matches the pixel key made with an empty pixel,
         // as an issuance transaction can also spend tweaked UTXOs.
         let (pixel_pubkey, _parity) = PixelKey::new(Pixel::empty(), &chroma.public_key().inner)
@@ -374,10 +634,16 @@ fn proof_map_to_bulletproofs(
 }
 
 #[cfg(feature = "bulletproof")]
-fn check_bulletproof_conservation_rules(
+pub fn check_bulletproof_conservation_rules(
     inputs_proofs: Vec<yuv_pixels::Bulletproof>,
     outputs_proofs: Vec<yuv_pixels::Bulletproof>,
 ) -> Result<(), CheckError> {
+    // Commitments to values out of range (e.g. "negative" ones) could be used to
+    // forge the sum, so each of them must be range-proven before combining.
+    for proof in inputs_proofs.iter().chain(outputs_proofs.iter()) {
+        check_range_proof(proof)?;
+    }
+
     // Derive the public key to verify the general signature.
     let general_xonly = derive_pubkey(&inputs_proofs, &outputs_proofs, |_p| true)?;
 
@@ -387,7 +653,7 @@ fn check_bulletproof_conservation_rules(
     let chromas = inputs_proofs
         .iter()
         .map(|proof| proof.pixel.chroma)
-        .collect::<std::collections::BTreeSet<Chroma>>();
+        .collect::<alloc::collections::BTreeSet<Chroma>>();
 
     // Derive the public keys to verify the signatures for each `Chroma`.
     for chroma in chromas {
@@ -434,6 +700,16 @@ fn check_bulletproof_conservation_rules(
     Ok(())
 }
 
+/// Check that the range proof bounds the committed value to the `[0, 2^128)` range.
+#[cfg(feature = "bulletproof")]
+fn check_range_proof(proof: &Bulletproof) -> Result<(), CheckError> {
+    if !verify_bulletproof(proof.commitment, proof.proof.clone()) {
+        return Err(CheckError::RangeProofInvalid);
+    }
+
+    Ok(())
+}
+
 #[cfg(feature = "bulletproof")]
 fn verify_signatures(
     proof: &Bulletproof,
@@ -0,0 +1,28 @@
+#![doc = include_str!("../README.md")]
+#![cfg_attr(feature = "no-std", no_std)]
+
+extern crate alloc;
+
+mod errors;
+pub use errors::CheckError;
+
+mod isolated_checks;
+#[cfg(feature = "bulletproof")]
+pub use isolated_checks::check_bulletproof_conservation_rules;
+pub use isolated_checks::{
+    check_coinbase_maturity, check_issue_isolated, check_transaction,
+    check_transaction_collect_errors, check_transfer_isolated, find_issuer_in_txinputs,
+    ProofErrors, COINBASE_MATURITY,
+};
+
+mod proof_kind;
+pub use proof_kind::ProofKind;
+
+pub use yuv_types::announcements::{self, YuvOpReturn};
+pub use yuv_types::{Announcement, AnyAnnouncement, ProofMap, YuvTransaction, YuvTxType};
+
+#[cfg(test)]
+mod tests;
+
+#[cfg(not(any(feature = "std", feature = "no-std")))]
+compile_error!("at least one of the `std` or `no-std` features must be enabled");
@@ -0,0 +1,33 @@
+use yuv_pixels::PixelProof;
+
+/// Kind of the [`PixelProof`] without the proof data itself.
+///
+/// Used to configure which proofs the checker accepts. The names are the same
+/// as the `type` tag of the serialized [`PixelProof`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, serde::Deserialize, serde::Serialize)]
+pub enum ProofKind {
+    EmptyPixel,
+    Sig,
+    Multisig,
+    Lightning,
+    Bulletproof,
+    LightningHtlc,
+    Timelock,
+    GenericScript,
+}
+
+impl From<&PixelProof> for ProofKind {
+    fn from(proof: &PixelProof) -> Self {
+        match proof {
+            PixelProof::EmptyPixel(_) => Self::EmptyPixel,
+            PixelProof::Sig(_) => Self::Sig,
+            PixelProof::Multisig(_) => Self::Multisig,
+            PixelProof::Lightning(_) => Self::Lightning,
+            #[cfg(feature = "bulletproof")]
+            PixelProof::Bulletproof(_) => Self::Bulletproof,
+            PixelProof::LightningHtlc(_) => Self::LightningHtlc,
+            PixelProof::Timelock(_) => Self::Timelock,
+            PixelProof::GenericScript(_) => Self::GenericScript,
+        }
+    }
+}
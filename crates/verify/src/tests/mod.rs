@@ -0,0 +1,996 @@
+use bitcoin::{
+    hashes::Hash,
+    secp256k1::{Secp256k1, SecretKey},
+    OutPoint, PublicKey, Transaction, TxIn, TxOut, Txid,
+};
+use once_cell::sync::Lazy;
+use yuv_pixels::{Chroma, EmptyPixelProof, Pixel, PixelKey, PixelProof, SigPixelProof};
+use yuv_types::{announcements::IssueAnnouncement, mocks, ProofMap, YuvTransaction, YuvTxType};
+
+use crate::{check_transaction, check_transaction_collect_errors};
+
+static VALID_MULTICHROMA_TRANSFER: Lazy<YuvTransaction> = Lazy::new(|| {
+    serde_json::from_str::<YuvTransaction>(include_str!("./assets/multichroma_valid_transfer.json"))
+        .expect("JSON was not well-formatted")
+});
+
+static VALID_SINGLECHROMA_TRANSFER: Lazy<YuvTransaction> = Lazy::new(|| {
+    serde_json::from_str::<YuvTransaction>(include_str!(
+        "./assets/singlechroma_valid_transfer.json"
+    ))
+    .expect("JSON was not well-formatted")
+});
+
+static INVALID_MULTICHROMA_TRANSFER: Lazy<YuvTransaction> = Lazy::new(|| {
+    serde_json::from_str::<YuvTransaction>(include_str!(
+        "./assets/multichroma_invalid_transfer.json"
+    ))
+    .expect("JSON was not well-formatted")
+});
+
+static INVALID_SINGLECHROMA_TRANSFER: Lazy<YuvTransaction> = Lazy::new(|| {
+    serde_json::from_str::<YuvTransaction>(include_str!(
+        "./assets/singlechroma_invalid_transfer.json"
+    ))
+    .expect("JSON was not well-formatted")
+});
+
+const AMOUNT: u128 = 1000;
+const ISSUER_KEY: [u8; 32] = [1; 32];
+const RECIPIENT_KEY: [u8; 32] = [2; 32];
+
+/// Secret and public keys of the issuer, and its chroma.
+fn issuer() -> (SecretKey, PublicKey, Chroma) {
+    let secret = SecretKey::from_slice(&ISSUER_KEY).unwrap();
+    let pubkey = PublicKey::new(secret.public_key(&Secp256k1::new()));
+
+    (
+        secret,
+        pubkey,
+        Chroma::from(pubkey.inner.x_only_public_key().0),
+    )
+}
+
+/// Issuance of [`AMOUNT`] tokens of the [`issuer`] to itself, split to the parts the isolated
+/// checks take.
+fn issuance() -> (Transaction, ProofMap, IssueAnnouncement) {
+    let (secret, pubkey, _) = issuer();
+
+    let tx = mocks::issuance(&secret, AMOUNT, pubkey.inner);
+    let YuvTxType::Issue {
+        output_proofs: Some(output_proofs),
+        announcement,
+    } = tx.tx_type
+    else {
+        unreachable!("mock issuance has the output proofs");
+    };
+
+    (tx.bitcoin_tx, output_proofs, announcement)
+}
+
+/// Transfer of the issued pixel of the `parent` back to the [`issuer`], with its input and
+/// output proofs.
+fn transfer(parent: &Transaction) -> (Transaction, ProofMap, ProofMap) {
+    let (secret, pubkey, chroma) = issuer();
+
+    let tx = mocks::transfer(
+        OutPoint::new(parent.txid(), 0),
+        &secret,
+        Pixel::new(AMOUNT, chroma),
+        pubkey.inner,
+        2,
+    );
+    let YuvTxType::Transfer {
+        input_proofs,
+        output_proofs,
+    } = tx.tx_type
+    else {
+        unreachable!("mock transfer is a transfer");
+    };
+
+    (tx.bitcoin_tx, input_proofs, output_proofs)
+}
+
+/// Input spending the output with the `pixel`, which witness has the tweaked key.
+fn pixel_input(secret: &SecretKey, pubkey: PublicKey, pixel: Pixel, vout: u32) -> TxIn {
+    let pixel_key = PixelKey::new(pixel, &pubkey.inner).unwrap();
+
+    mocks::signed_input(
+        OutPoint::new(Txid::all_zeros(), vout),
+        secret,
+        pixel_key.0.inner,
+    )
+}
+
+/// Output with the pixel and its proof. If the `pixel` is empty, the proof is the empty one.
+fn output(pixel: Pixel, pubkey: PublicKey) -> (TxOut, PixelProof) {
+    let script_pubkey = PixelKey::new(pixel, &pubkey.inner)
+        .unwrap()
+        .to_p2wpkh()
+        .unwrap();
+
+    let proof = if pixel == Pixel::empty() {
+        PixelProof::EmptyPixel(EmptyPixelProof::new(pubkey.inner))
+    } else {
+        PixelProof::Sig(SigPixelProof::new(pixel, pubkey.inner))
+    };
+
+    (
+        TxOut {
+            value: 1000,
+            script_pubkey,
+        },
+        proof,
+    )
+}
+
+#[test]
+fn test_tx_checker_validates_multichroma_transfer() {
+    let result = check_transaction(&VALID_MULTICHROMA_TRANSFER);
+
+    assert!(result.is_ok(), "expected the tx to pass the check");
+}
+
+#[test]
+fn test_tx_checker_validates_singlechroma_transfer() {
+    let result = check_transaction(&VALID_SINGLECHROMA_TRANSFER);
+
+    assert!(result.is_ok(), "expected the tx to pass the check");
+}
+
+#[test]
+fn test_tx_checker_fails_invalid_multichroma_transfer() {
+    let result = check_transaction(&INVALID_MULTICHROMA_TRANSFER);
+
+    assert!(result.is_err(), "expected the tx to fail the check");
+}
+
+#[test]
+fn test_tx_checker_fails_invalid_singlechroma_transfer() {
+    let result = check_transaction(&INVALID_SINGLECHROMA_TRANSFER);
+
+    assert!(result.is_err(), "expected the tx to fail the check");
+}
+
+#[test]
+fn test_collect_errors_reports_every_invalid_proof() {
+    let errors = check_transaction_collect_errors(&VALID_MULTICHROMA_TRANSFER).unwrap();
+    assert!(errors.is_empty(), "unexpected errors: {errors:?}");
+
+    let mut tx = VALID_MULTICHROMA_TRANSFER.clone();
+    let YuvTxType::Transfer {
+        input_proofs,
+        output_proofs,
+    } = &mut tx.tx_type
+    else {
+        panic!("transaction must be a transfer");
+    };
+
+    // Proofs with changed amounts don't match the scripts anymore.
+    let proofs = input_proofs
+        .iter_mut()
+        .filter(|(vout, _)| **vout == 1)
+        .chain(
+            output_proofs
+                .iter_mut()
+                .filter(|(vout, _)| **vout == 0 || **vout == 2),
+        );
+
+    for (_, proof) in proofs {
+        let PixelProof::Sig(proof) = proof else {
+            panic!("proof must be a signature one");
+        };
+
+        proof.pixel.luma.amount += 1;
+    }
+
+    let errors = check_transaction_collect_errors(&tx).unwrap();
+
+    let input_vouts = errors
+        .inputs
+        .iter()
+        .map(|(vout, _)| *vout)
+        .collect::<Vec<_>>();
+    let output_vouts = errors
+        .outputs
+        .iter()
+        .map(|(vout, _)| *vout)
+        .collect::<Vec<_>>();
+
+    assert_eq!(input_vouts, vec![1]);
+    assert_eq!(output_vouts, vec![0, 2]);
+}
+
+mod p2sh_wpkh {
+    use bitcoin::{
+        blockdata::script::Builder,
+        secp256k1::{Message, Secp256k1, SecretKey},
+        util::sighash::SighashCache,
+        EcdsaSig, EcdsaSighashType, OutPoint, PackedLockTime, PublicKey, Script, Transaction, TxIn,
+        TxOut, Witness,
+    };
+    use yuv_pixels::{
+        P2WPKHWintessData, Pixel, PixelKey, PixelPrivateKey, PixelProof, SigPixelProof,
+    };
+    use yuv_types::{announcements::IssueAnnouncement, AnyAnnouncement, ProofMap};
+
+    use super::{issuer, AMOUNT, RECIPIENT_KEY};
+    use crate::{check_issue_isolated, check_transfer_isolated};
+
+    const SATOSHIS: u64 = 1000;
+
+    /// Issue the tokens to the P2SH-wrapped pixel output of the recipient. The
+    /// issuer spends its P2SH-wrapped output too, so it's found by the nested
+    /// witness.
+    fn issuance(
+        pixel: Pixel,
+        issuer: &SecretKey,
+        recipient: &SecretKey,
+    ) -> (Transaction, ProofMap) {
+        let ctx = Secp256k1::new();
+
+        let issuer_pubkey = PublicKey::new(issuer.public_key(&ctx));
+        let issuer_redeem_script = Script::new_v0_p2wpkh(&issuer_pubkey.wpubkey_hash().unwrap());
+
+        // Signatures of the inputs are not verified by the checker.
+        let signature = ctx.sign_ecdsa(&Message::from_slice(&[1; 32]).unwrap(), issuer);
+
+        let pixel_key = PixelKey::new(pixel, &recipient.public_key(&ctx)).unwrap();
+        let announcement = IssueAnnouncement::new(pixel.chroma, AMOUNT);
+
+        let tx = Transaction {
+            version: 2,
+            lock_time: PackedLockTime::ZERO,
+            input: vec![TxIn {
+                script_sig: Builder::new()
+                    .push_slice(issuer_redeem_script.as_bytes())
+                    .into_script(),
+                witness: Witness::from(P2WPKHWintessData::new(
+                    EcdsaSig::sighash_all(signature),
+                    issuer_pubkey,
+                )),
+                ..Default::default()
+            }],
+            output: vec![
+                TxOut {
+                    value: SATOSHIS,
+                    script_pubkey: pixel_key.to_p2sh_p2wpkh().unwrap(),
+                },
+                TxOut {
+                    value: 0,
+                    script_pubkey: announcement.to_script(),
+                },
+            ],
+        };
+
+        let proof = SigPixelProof::new(pixel, recipient.public_key(&ctx));
+
+        (tx, ProofMap::from([(0, PixelProof::Sig(proof))]))
+    }
+
+    /// Spend the P2SH-wrapped pixel output of the parent back to the native
+    /// segwit output of the same owner.
+    fn transfer(parent: &Transaction, pixel: Pixel, owner: &SecretKey) -> Transaction {
+        let ctx = Secp256k1::new();
+
+        let tweaked_key = PixelPrivateKey::new(pixel, owner).unwrap();
+        let tweaked_pubkey = PublicKey::new(tweaked_key.0.public_key(&ctx));
+        let redeem_script = Script::new_v0_p2wpkh(&tweaked_pubkey.wpubkey_hash().unwrap());
+
+        let pixel_key = PixelKey::new(pixel, &owner.public_key(&ctx)).unwrap();
+
+        let mut tx = Transaction {
+            version: 2,
+            lock_time: PackedLockTime::ZERO,
+            input: vec![TxIn {
+                previous_output: OutPoint::new(parent.txid(), 0),
+                script_sig: Builder::new()
+                    .push_slice(redeem_script.as_bytes())
+                    .into_script(),
+                ..Default::default()
+            }],
+            output: vec![TxOut {
+                value: SATOSHIS / 2,
+                script_pubkey: pixel_key.to_p2wpkh().unwrap(),
+            }],
+        };
+
+        let sighash = SighashCache::new(&tx)
+            .segwit_signature_hash(
+                0,
+                &redeem_script.p2wpkh_script_code().unwrap(),
+                SATOSHIS,
+                EcdsaSighashType::All,
+            )
+            .unwrap();
+
+        let signature = ctx.sign_ecdsa(&Message::from_slice(&sighash[..]).unwrap(), &tweaked_key.0);
+
+        tx.input[0].witness = Witness::from(P2WPKHWintessData::new(
+            EcdsaSig::sighash_all(signature),
+            tweaked_pubkey,
+        ));
+
+        tx
+    }
+
+    #[test]
+    fn test_p2sh_wrapped_pixel_round_trip() {
+        let (issuer, _, chroma) = issuer();
+        let recipient = SecretKey::from_slice(&RECIPIENT_KEY).unwrap();
+        let pixel = Pixel::new(AMOUNT, chroma);
+
+        let (parent, issue_proofs) = issuance(pixel, &issuer, &recipient);
+
+        check_issue_isolated(
+            &parent,
+            &Some(issue_proofs.clone()),
+            &IssueAnnouncement::new(chroma, AMOUNT),
+        )
+        .expect("P2SH-wrapped pixel output should pass the check");
+
+        let child = transfer(&parent, pixel, &recipient);
+
+        check_transfer_isolated(&child, &issue_proofs, &issue_proofs)
+            .expect("transfer spending P2SH-wrapped pixel should pass the check");
+    }
+
+    #[test]
+    fn test_p2sh_wrapped_pixel_with_invalid_redeem_script_fails() {
+        let (issuer, _, chroma) = issuer();
+        let recipient = SecretKey::from_slice(&RECIPIENT_KEY).unwrap();
+        let pixel = Pixel::new(AMOUNT, chroma);
+
+        let (parent, issue_proofs) = issuance(pixel, &issuer, &recipient);
+
+        let mut child = transfer(&parent, pixel, &recipient);
+        child.input[0].script_sig = Builder::new()
+            .push_slice(&Script::new().to_v0_p2wsh()[..])
+            .into_script();
+
+        let result = check_transfer_isolated(&child, &issue_proofs, &issue_proofs);
+
+        assert!(result.is_err(), "expected the tx to fail the check");
+    }
+}
+
+mod announcement_output_proofs {
+    use yuv_types::ProofMap;
+
+    use super::issuance;
+    use crate::{check_issue_isolated, CheckError};
+
+    #[test]
+    fn test_proof_mapped_to_announcement_output_is_rejected() {
+        let (tx, mut issue_proofs, announcement) = issuance();
+        let proof = issue_proofs[&0].clone();
+
+        // The proof is keyed to the announcement output in addition to the pixel one.
+        issue_proofs.insert(1, proof.clone());
+        let result = check_issue_isolated(&tx, &Some(issue_proofs), &announcement);
+        assert!(
+            matches!(result, Err(CheckError::ProofMappedToOpReturn(1))),
+            "expected the proof on the OP_RETURN output to be rejected, got {result:?}"
+        );
+
+        // The proof is keyed to the announcement output instead of the pixel one.
+        let result = check_issue_isolated(&tx, &Some(ProofMap::from([(1, proof)])), &announcement);
+        assert!(
+            matches!(result, Err(CheckError::ProofMappedToOpReturn(1))),
+            "expected the proof on the OP_RETURN output to be rejected, got {result:?}"
+        );
+    }
+}
+
+mod non_yuv_inputs {
+    use bitcoin::{OutPoint, TxIn};
+    use yuv_types::ProofMap;
+
+    use super::{issuance, transfer};
+    use crate::{check_transfer_isolated, CheckError};
+
+    #[test]
+    fn test_transfer_with_non_yuv_inputs_passes() {
+        let (parent, _, _) = issuance();
+        let (mut child, input_proofs, output_proofs) = transfer(&parent);
+        let proof = input_proofs[&0].clone();
+
+        // The satoshis input which pays the fee goes first, and has no proof.
+        child.input.insert(
+            0,
+            TxIn {
+                previous_output: OutPoint::new(parent.txid(), 1),
+                ..Default::default()
+            },
+        );
+
+        let input_proofs = ProofMap::from([(1, proof.clone())]);
+
+        check_transfer_isolated(&child, &input_proofs, &output_proofs)
+            .expect("transfer with non-YUV inputs should pass the check");
+
+        // The claimed YUV input must be proven, so the proof can't be moved to
+        // the non-YUV one.
+        let result = check_transfer_isolated(&child, &ProofMap::from([(0, proof)]), &output_proofs);
+        assert!(
+            matches!(result, Err(CheckError::InvalidProof { vout: 0, .. })),
+            "expected the proof of the non-YUV input to be rejected, got {result:?}"
+        );
+    }
+}
+
+mod duplicate_inputs {
+    use bitcoin::OutPoint;
+    use yuv_types::ProofMap;
+
+    use super::{issuance, transfer};
+    use crate::{check_transfer_isolated, CheckError};
+
+    #[test]
+    fn test_transfer_with_duplicate_input_is_rejected() {
+        let (parent, _, _) = issuance();
+        let (mut child, input_proofs, output_proofs) = transfer(&parent);
+        let proof = input_proofs[&0].clone();
+
+        // The same pixel output is spent twice, so its amount would be counted twice.
+        child.input.push(child.input[0].clone());
+
+        let input_proofs = ProofMap::from([(0, proof.clone()), (1, proof)]);
+
+        let result = check_transfer_isolated(&child, &input_proofs, &output_proofs);
+        assert!(
+            matches!(result, Err(CheckError::DuplicateInput(outpoint)) if outpoint == OutPoint::new(parent.txid(), 0)),
+            "expected the duplicate input to be rejected, got {result:?}"
+        );
+    }
+}
+
+mod envelope {
+    use bitcoin::{
+        secp256k1::Secp256k1,
+        util::taproot::{LeafVersion, TaprootBuilder},
+        PackedLockTime, Transaction, TxIn, Witness,
+    };
+    use yuv_pixels::Pixel;
+    use yuv_types::{
+        announcements::IssueAnnouncement, AnyAnnouncement, ProofMap, YuvTransaction, YuvTxType,
+    };
+
+    use super::{issuer, output, AMOUNT};
+    use crate::check_transaction;
+
+    /// Create the issuance which announcement is committed in the envelope of the issuer's
+    /// Taproot input instead of the `OP_RETURN` output. Signatures of the inputs are not
+    /// verified by the checker.
+    fn envelope_issuance() -> YuvTransaction {
+        let (_, pubkey, chroma) = issuer();
+        let issuer_xonly = pubkey.inner.x_only_public_key().0;
+        let announcement = IssueAnnouncement::new(chroma, AMOUNT);
+
+        let script = announcement.to_envelope_script(&issuer_xonly);
+        let control_block = TaprootBuilder::new()
+            .add_leaf(0, script.clone())
+            .unwrap()
+            .finalize(&Secp256k1::new(), issuer_xonly)
+            .unwrap()
+            .control_block(&(script.clone(), LeafVersion::TapScript))
+            .unwrap();
+
+        let (issued, issued_proof) = output(Pixel::new(AMOUNT, chroma), pubkey);
+
+        YuvTransaction::new(
+            Transaction {
+                version: 2,
+                lock_time: PackedLockTime::ZERO,
+                input: vec![TxIn {
+                    witness: Witness::from_vec(vec![
+                        vec![1; 64],
+                        script.to_bytes(),
+                        control_block.serialize(),
+                    ]),
+                    ..Default::default()
+                }],
+                output: vec![issued],
+            },
+            YuvTxType::Issue {
+                output_proofs: Some(ProofMap::from([(0, issued_proof)])),
+                announcement,
+            },
+        )
+    }
+
+    #[test]
+    fn test_envelope_issuance_is_accepted() {
+        check_transaction(&envelope_issuance()).expect("envelope issuance should pass the check");
+    }
+}
+
+mod zero_amount {
+    use bitcoin::{OutPoint, PackedLockTime, Transaction, TxOut};
+    use yuv_pixels::{Pixel, PixelProof, SigPixelProof};
+    use yuv_types::{announcements::IssueAnnouncement, mocks, AnyAnnouncement, ProofMap};
+
+    use super::{issuer, output, AMOUNT};
+    use crate::{check_issue_isolated, check_transfer_isolated, CheckError};
+
+    /// Issuance of [`AMOUNT`] tokens with an extra output of the `extra` pixel.
+    fn issuance_with_extra_output(extra: Pixel) -> (Transaction, ProofMap, IssueAnnouncement) {
+        let (secret, pubkey, chroma) = issuer();
+        let announcement = IssueAnnouncement::new(chroma, AMOUNT);
+
+        let (issued, issued_proof) = output(Pixel::new(AMOUNT, chroma), pubkey);
+        let (extra, extra_proof) = output(extra, pubkey);
+
+        let tx = Transaction {
+            version: 2,
+            lock_time: PackedLockTime::ZERO,
+            input: vec![mocks::signed_input(OutPoint::null(), &secret, pubkey.inner)],
+            output: vec![
+                issued,
+                extra,
+                TxOut {
+                    value: 0,
+                    script_pubkey: announcement.to_script(),
+                },
+            ],
+        };
+
+        (
+            tx,
+            ProofMap::from([(0, issued_proof), (1, extra_proof)]),
+            announcement,
+        )
+    }
+
+    #[test]
+    fn test_issuance_with_zero_amount_output_is_rejected() {
+        let (_, _, chroma) = issuer();
+        let (tx, proofs, announcement) = issuance_with_extra_output(Pixel::new(0, chroma));
+
+        let result = check_issue_isolated(&tx, &Some(proofs), &announcement);
+
+        assert!(
+            matches!(result, Err(CheckError::ZeroAmountOutput(1))),
+            "expected the zero-amount output to be rejected, got: {:?}",
+            result
+        );
+    }
+
+    #[test]
+    fn test_issuance_with_empty_pixel_output_is_accepted() {
+        let (tx, proofs, announcement) = issuance_with_extra_output(Pixel::empty());
+
+        check_issue_isolated(&tx, &Some(proofs), &announcement)
+            .expect("output without tokens must have the empty pixel proof");
+    }
+
+    #[test]
+    fn test_transfer_with_zero_amount_output_is_rejected() {
+        let (secret, pubkey, chroma) = issuer();
+
+        let input_proof =
+            PixelProof::Sig(SigPixelProof::new(Pixel::new(AMOUNT, chroma), pubkey.inner));
+        let (transferred, transferred_proof) = output(Pixel::new(AMOUNT, chroma), pubkey);
+        let (zero, zero_proof) = output(Pixel::new(0, chroma), pubkey);
+
+        let tx = Transaction {
+            version: 2,
+            lock_time: PackedLockTime::ZERO,
+            input: vec![mocks::signed_input(OutPoint::null(), &secret, pubkey.inner)],
+            output: vec![transferred, zero],
+        };
+
+        let result = check_transfer_isolated(
+            &tx,
+            &ProofMap::from([(0, input_proof)]),
+            &ProofMap::from([(0, transferred_proof), (1, zero_proof)]),
+        );
+
+        assert!(
+            matches!(result, Err(CheckError::ZeroAmountOutput(1))),
+            "expected the zero-amount output to be rejected, got: {:?}",
+            result
+        );
+    }
+}
+
+mod empty_pixel_inputs {
+    use bitcoin::{PackedLockTime, Transaction};
+    use yuv_pixels::{EmptyPixelProof, Pixel, PixelProof, SigPixelProof};
+    use yuv_types::ProofMap;
+
+    use super::{issuer, output, pixel_input, AMOUNT};
+    use crate::{check_transfer_isolated, CheckError};
+
+    #[test]
+    fn test_transfer_spending_empty_pixel_input_is_accepted() {
+        let (secret, pubkey, chroma) = issuer();
+
+        let pixel_proof =
+            PixelProof::Sig(SigPixelProof::new(Pixel::new(AMOUNT, chroma), pubkey.inner));
+        let empty_proof = PixelProof::EmptyPixel(EmptyPixelProof::new(pubkey.inner));
+        let (transferred, transferred_proof) = output(Pixel::new(AMOUNT, chroma), pubkey);
+        let (change, change_proof) = output(Pixel::empty(), pubkey);
+
+        let tx = Transaction {
+            version: 2,
+            lock_time: PackedLockTime::ZERO,
+            input: vec![
+                pixel_input(&secret, pubkey, Pixel::new(AMOUNT, chroma), 0),
+                pixel_input(&secret, pubkey, Pixel::empty(), 1),
+            ],
+            output: vec![transferred, change],
+        };
+
+        check_transfer_isolated(
+            &tx,
+            &ProofMap::from([(0, pixel_proof), (1, empty_proof)]),
+            &ProofMap::from([(0, transferred_proof), (1, change_proof)]),
+        )
+        .expect("empty pixel input and output must carry no tokens");
+    }
+
+    #[test]
+    fn test_empty_pixel_input_carries_no_tokens() {
+        let (secret, pubkey, chroma) = issuer();
+
+        let empty_proof = PixelProof::EmptyPixel(EmptyPixelProof::new(pubkey.inner));
+        let (transferred, transferred_proof) = output(Pixel::new(AMOUNT, chroma), pubkey);
+
+        let tx = Transaction {
+            version: 2,
+            lock_time: PackedLockTime::ZERO,
+            input: vec![pixel_input(&secret, pubkey, Pixel::empty(), 0)],
+            output: vec![transferred],
+        };
+
+        let result = check_transfer_isolated(
+            &tx,
+            &ProofMap::from([(0, empty_proof)]),
+            &ProofMap::from([(0, transferred_proof)]),
+        );
+
+        assert!(
+            matches!(result, Err(CheckError::ConservationRulesViolated)),
+            "expected the tokens out of the empty pixel input to be rejected, got: {:?}",
+            result
+        );
+    }
+}
+
+mod amount_overflow {
+    use bitcoin::{OutPoint, PackedLockTime, Transaction, TxOut};
+    use yuv_pixels::{Pixel, PixelProof, SigPixelProof};
+    use yuv_types::{announcements::IssueAnnouncement, mocks, AnyAnnouncement, ProofMap};
+
+    use super::{issuer, output, pixel_input, AMOUNT};
+    use crate::{check_issue_isolated, check_transfer_isolated, CheckError};
+
+    #[test]
+    fn test_transfer_with_wrapping_outputs_is_rejected() {
+        let (secret, pubkey, chroma) = issuer();
+
+        let input_proof =
+            PixelProof::Sig(SigPixelProof::new(Pixel::new(AMOUNT, chroma), pubkey.inner));
+        // `u128::MAX + (AMOUNT + 1)` wraps to `AMOUNT`, which equals the input.
+        let (first, first_proof) = output(Pixel::new(u128::MAX, chroma), pubkey);
+        let (second, second_proof) = output(Pixel::new(AMOUNT + 1, chroma), pubkey);
+
+        let tx = Transaction {
+            version: 2,
+            lock_time: PackedLockTime::ZERO,
+            input: vec![pixel_input(&secret, pubkey, Pixel::new(AMOUNT, chroma), 0)],
+            output: vec![first, second],
+        };
+
+        let result = check_transfer_isolated(
+            &tx,
+            &ProofMap::from([(0, input_proof)]),
+            &ProofMap::from([(0, first_proof), (1, second_proof)]),
+        );
+
+        assert!(
+            matches!(result, Err(CheckError::AmountOverflow)),
+            "expected the wrapping sum of outputs to be rejected, got: {:?}",
+            result
+        );
+    }
+
+    #[test]
+    fn test_transfer_with_wrapping_inputs_is_rejected() {
+        let (secret, pubkey, chroma) = issuer();
+
+        // `u128::MAX + 2` wraps to `1`, which equals the output.
+        let inputs = [Pixel::new(u128::MAX, chroma), Pixel::new(2, chroma)];
+        let (transferred, transferred_proof) = output(Pixel::new(1, chroma), pubkey);
+
+        let tx = Transaction {
+            version: 2,
+            lock_time: PackedLockTime::ZERO,
+            input: vec![
+                pixel_input(&secret, pubkey, inputs[0], 0),
+                pixel_input(&secret, pubkey, inputs[1], 1),
+            ],
+            output: vec![transferred],
+        };
+
+        let result = check_transfer_isolated(
+            &tx,
+            &ProofMap::from([
+                (
+                    0,
+                    PixelProof::Sig(SigPixelProof::new(inputs[0], pubkey.inner)),
+                ),
+                (
+                    1,
+                    PixelProof::Sig(SigPixelProof::new(inputs[1], pubkey.inner)),
+                ),
+            ]),
+            &ProofMap::from([(0, transferred_proof)]),
+        );
+
+        assert!(
+            matches!(result, Err(CheckError::AmountOverflow)),
+            "expected the wrapping sum of inputs to be rejected, got: {:?}",
+            result
+        );
+    }
+
+    #[test]
+    fn test_issuance_with_wrapping_outputs_is_rejected() {
+        let (secret, pubkey, chroma) = issuer();
+        let announcement = IssueAnnouncement::new(chroma, AMOUNT);
+
+        // `u128::MAX + (AMOUNT + 1)` wraps to the announced `AMOUNT`.
+        let (first, first_proof) = output(Pixel::new(u128::MAX, chroma), pubkey);
+        let (second, second_proof) = output(Pixel::new(AMOUNT + 1, chroma), pubkey);
+
+        let tx = Transaction {
+            version: 2,
+            lock_time: PackedLockTime::ZERO,
+            input: vec![mocks::signed_input(OutPoint::null(), &secret, pubkey.inner)],
+            output: vec![
+                first,
+                second,
+                TxOut {
+                    value: 0,
+                    script_pubkey: announcement.to_script(),
+                },
+            ],
+        };
+
+        let result = check_issue_isolated(
+            &tx,
+            &Some(ProofMap::from([(0, first_proof), (1, second_proof)])),
+            &announcement,
+        );
+
+        assert!(
+            matches!(result, Err(CheckError::AmountOverflow)),
+            "expected the wrapping sum of issued amounts to be rejected, got: {:?}",
+            result
+        );
+    }
+}
+
+mod output_script_precheck {
+    use bitcoin::hashes::Hash;
+    use bitcoin::{Script, WPubkeyHash, WScriptHash};
+
+    use crate::{check_transaction, CheckError, ProofKind};
+
+    use super::VALID_SINGLECHROMA_TRANSFER;
+
+    #[test]
+    fn test_output_of_other_script_type_is_rejected_early() {
+        let mut tx = VALID_SINGLECHROMA_TRANSFER.clone();
+        tx.bitcoin_tx.output[0].script_pubkey = Script::new_v0_p2wsh(&WScriptHash::all_zeros());
+
+        let result = check_transaction(&tx);
+        assert!(
+            matches!(
+                result,
+                Err(CheckError::UnexpectedOutputScript {
+                    vout: 0,
+                    kind: ProofKind::Sig,
+                })
+            ),
+            "expected unexpected output script, got: {result:?}"
+        );
+    }
+
+    #[test]
+    fn test_output_of_other_key_is_rejected_early() {
+        let mut tx = VALID_SINGLECHROMA_TRANSFER.clone();
+        tx.bitcoin_tx.output[0].script_pubkey = Script::new_v0_p2wpkh(&WPubkeyHash::all_zeros());
+
+        let result = check_transaction(&tx);
+        assert!(
+            matches!(
+                result,
+                Err(CheckError::OutputKeyMismatch {
+                    vout: 0,
+                    kind: ProofKind::Sig,
+                })
+            ),
+            "expected output key mismatch, got: {result:?}"
+        );
+    }
+}
+
+mod coinbase_maturity {
+    use bitcoin::{hashes::Hash, OutPoint, PackedLockTime, Transaction, TxIn, TxOut, Txid};
+
+    use crate::{check_coinbase_maturity, CheckError, COINBASE_MATURITY};
+
+    fn transaction(previous_output: OutPoint) -> Transaction {
+        Transaction {
+            version: 2,
+            lock_time: PackedLockTime::ZERO,
+            input: vec![TxIn {
+                previous_output,
+                ..Default::default()
+            }],
+            output: vec![TxOut::default()],
+        }
+    }
+
+    #[test]
+    fn test_immature_coinbase_is_rejected() {
+        let coinbase = transaction(OutPoint::null());
+        let outpoint = OutPoint::new(coinbase.txid(), 0);
+
+        let result = check_coinbase_maturity(outpoint, &coinbase, COINBASE_MATURITY - 1);
+
+        assert!(
+            matches!(
+                result,
+                Err(CheckError::ImmatureCoinbase { outpoint: spent, confirmations })
+                    if spent == outpoint && confirmations == COINBASE_MATURITY - 1
+            ),
+            "expected the immature coinbase to be rejected, got: {:?}",
+            result
+        );
+    }
+
+    #[test]
+    fn test_mature_coinbase_and_regular_outputs_are_accepted() {
+        let coinbase = transaction(OutPoint::null());
+        let outpoint = OutPoint::new(coinbase.txid(), 0);
+
+        check_coinbase_maturity(outpoint, &coinbase, COINBASE_MATURITY)
+            .expect("mature coinbase should be spendable");
+
+        let regular = transaction(OutPoint::new(Txid::all_zeros(), 1));
+        let outpoint = OutPoint::new(regular.txid(), 0);
+
+        check_coinbase_maturity(outpoint, &regular, 0)
+            .expect("unconfirmed regular output should be spendable");
+    }
+}
+
+#[cfg(feature = "bulletproof")]
+mod bulletproof {
+    use bitcoin::{
+        hashes::{sha256, Hash, HashEngine},
+        secp256k1::{KeyPair, Message, Scalar, Secp256k1, SecretKey},
+        PackedLockTime, Script, Transaction, TxIn, TxOut, WPubkeyHash,
+    };
+    use yuv_pixels::{generate_bulletproof, Bulletproof, Chroma, Luma, Pixel, PixelProof};
+    use yuv_types::{ProofMap, YuvTransaction, YuvTxType};
+
+    use crate::{check_bulletproof_conservation_rules, check_transaction, CheckError, ProofKind};
+
+    const AMOUNT: u128 = 100;
+    const INPUT_BLINDING: [u8; 32] = [1; 32];
+    const OUTPUT_BLINDING: [u8; 32] = [2; 32];
+
+    /// Create an input and an output bulletproofs for the same amount, which are
+    /// signed by the key derived from the difference of their commitments.
+    fn signed_bulletproofs() -> (Bulletproof, Bulletproof) {
+        let ctx = Secp256k1::new();
+
+        let input_key = SecretKey::from_slice(&INPUT_BLINDING).expect("valid secret key");
+        let output_key = SecretKey::from_slice(&OUTPUT_BLINDING).expect("valid secret key");
+
+        let chroma = Chroma::new(input_key.x_only_public_key(&ctx).0);
+        let pixel = Pixel::new(Luma::from(AMOUNT), chroma);
+
+        let (input_range_proof, input_commitment) = generate_bulletproof(AMOUNT, INPUT_BLINDING);
+        let (output_range_proof, output_commitment) = generate_bulletproof(AMOUNT, OUTPUT_BLINDING);
+
+        // Sum of input commitments minus sum of output ones is `(r_in - r_out) * G`.
+        let signing_key = input_key
+            .add_tweak(&Scalar::from(output_key.negate()))
+            .expect("valid tweak");
+        let keypair = KeyPair::from_secret_key(&ctx, &signing_key);
+
+        let mut engine = sha256::Hash::engine();
+        engine.input(&pixel.luma.to_bytes());
+        engine.input(&pixel.luma.to_bytes());
+        let message = Message::from_hashed_data::<sha256::Hash>(&sha256::Hash::from_engine(engine));
+
+        // There is only one chroma, so the chroma signature is the same as the general one.
+        let signature = ctx.sign_schnorr_no_aux_rand(&message, &keypair);
+
+        let pubkey = input_key.public_key(&ctx);
+
+        let input = Bulletproof::new(
+            pixel,
+            pubkey,
+            pubkey,
+            input_commitment,
+            input_range_proof,
+            signature,
+            signature,
+        );
+        let output = Bulletproof::new(
+            pixel,
+            pubkey,
+            pubkey,
+            output_commitment,
+            output_range_proof,
+            signature,
+            signature,
+        );
+
+        (input, output)
+    }
+
+    #[test]
+    fn test_signed_bulletproofs_pass_conservation_rules() {
+        let (input, output) = signed_bulletproofs();
+
+        let result = check_bulletproof_conservation_rules(vec![input], vec![output]);
+
+        assert!(result.is_ok(), "expected the proofs to pass the check");
+    }
+
+    #[test]
+    fn test_invalid_range_proof_fails_conservation_rules() {
+        let (input, mut output) = signed_bulletproofs();
+
+        // Signatures are still valid, but the range proof doesn't match the commitment.
+        output.proof = input.proof.clone();
+
+        let result = check_bulletproof_conservation_rules(vec![input], vec![output]);
+
+        assert!(
+            matches!(result, Err(CheckError::RangeProofInvalid)),
+            "expected the range proof check to fail, got: {result:?}"
+        );
+    }
+
+    #[test]
+    fn test_bulletproof_output_of_other_key_is_rejected_early() {
+        let (input, output) = signed_bulletproofs();
+
+        let yuv_tx = YuvTransaction::new(
+            Transaction {
+                version: 2,
+                lock_time: PackedLockTime::ZERO,
+                input: vec![TxIn::default()],
+                output: vec![TxOut {
+                    value: 1000,
+                    script_pubkey: Script::new_v0_p2wpkh(&WPubkeyHash::all_zeros()),
+                }],
+            },
+            YuvTxType::Transfer {
+                input_proofs: ProofMap::from([(0, PixelProof::Bulletproof(Box::new(input)))]),
+                output_proofs: ProofMap::from([(0, PixelProof::Bulletproof(Box::new(output)))]),
+            },
+        );
+
+        let result = check_transaction(&yuv_tx);
+        assert!(
+            matches!(
+                result,
+                Err(CheckError::OutputKeyMismatch {
+                    vout: 0,
+                    kind: ProofKind::Bulletproof,
+                })
+            ),
+            "expected output key mismatch, got: {result:?}"
+        );
+    }
+}
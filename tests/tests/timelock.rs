@@ -0,0 +1,131 @@
+//! This module provides intergration tests for spending timelocked YUV outputs.
+
+use bdk::{bitcoincore_rpc::RpcApi, miniscript::ToPublicKey};
+use bitcoin::{secp256k1::Secp256k1, OutPoint, PrivateKey};
+use once_cell::sync::Lazy;
+use ydk::{types::FeeRateStrategy, wallet::SyncOptions};
+use yuv_rpc_api::transactions::YuvTransactionsRpcClient;
+
+mod common;
+use common::*;
+
+static ISSUER_PRIVATE_KEY: Lazy<PrivateKey> = Lazy::new(|| {
+    "cNMMXcLoM65N5GaULU7ct2vexmQnJ5i5j3Sjc6iNnEF18vY7gzn9"
+        .parse()
+        .expect("Should be valid key")
+});
+
+static ALICE_PRIVATE_KEY: Lazy<PrivateKey> = Lazy::new(|| {
+    "cUK2ZdLQWWpKeFcrrD7BBjiUsEns9M3MFBTkmLTXyzs66TQN72eX"
+        .parse()
+        .expect("Should be valid key")
+});
+
+static CAROL_PUBKEY: Lazy<bitcoin::secp256k1::PublicKey> = Lazy::new(|| {
+    "0373fde54e72b074ba8f56b30acb3d90bbac25e4f1bc62f6918d96badbca1a69b1"
+        .parse()
+        .expect("Should be valid key")
+});
+
+/// Number of blocks after the issuance's block the output is locked for.
+const LOCK_BLOCKS: u64 = 20;
+
+#[tokio::test]
+async fn test_spend_timelocked_output() -> eyre::Result<()> {
+    let blockchain_rpc = setup_rpc_blockchain(&ISSUER_PRIVATE_KEY)?;
+
+    let provider_cfg = bitcoin_provider_config(false);
+    let blockchain = setup_blockchain(&provider_cfg);
+
+    let yuv_client = setup_yuv_client(YUV_NODE_URL)?;
+
+    let issuer = setup_wallet_from_provider(*ISSUER_PRIVATE_KEY, provider_cfg.clone()).await?;
+
+    let alice = setup_wallet_from_provider(*ALICE_PRIVATE_KEY, provider_cfg.clone()).await?;
+
+    let secp = Secp256k1::new();
+
+    blockchain_rpc.generate_to_address(101, &issuer.address()?)?;
+
+    issuer.sync(SyncOptions::default()).await?;
+
+    let alice_pubkey = ALICE_PRIVATE_KEY.public_key(&secp);
+
+    let locktime = (blockchain_rpc.get_block_count()? + LOCK_BLOCKS) as u32;
+
+    const ISSUANCE_AMOUNT: u128 = 1000;
+
+    let fee_rate_strategy = FeeRateStrategy::Manual { fee_rate: 2.0 };
+
+    // Create issuance with one output to Alice, that is locked until the locktime
+    let issuance = {
+        let mut builder = issuer.build_issuance()?;
+
+        builder
+            .add_timelocked_recipient(&alice_pubkey.inner, ISSUANCE_AMOUNT, 1000, locktime)
+            // Fund Alice with bitcoins too
+            .add_sats_recipient(&alice_pubkey.inner, 10000)
+            .set_fee_rate_strategy(fee_rate_strategy);
+
+        builder.finish(&blockchain).await?
+    };
+
+    let txid = issuance.bitcoin_tx.txid();
+
+    yuv_client.send_raw_yuv_tx(issuance, None).await?;
+
+    // Add block with issuance to the chain
+    blockchain_rpc.generate_to_address(7, &issuer.address()?)?;
+
+    let tx = wait_until_reject_or_attach(txid, &yuv_client).await?;
+
+    assert_attached!(tx, "Issuance was not accepted by YUV node");
+
+    alice.sync(SyncOptions::default()).await?;
+
+    // Create transfer that spends the timelocked output and sends all tokens to Carol
+    let transfer = {
+        let chroma = ISSUER_PRIVATE_KEY
+            .public_key(&secp)
+            .to_x_only_pubkey()
+            .into();
+
+        let mut builder = alice.build_transfer()?;
+
+        builder
+            .add_timelocked_pixel_input(OutPoint::new(txid, 0))
+            .add_recipient(chroma, &CAROL_PUBKEY, ISSUANCE_AMOUNT, 1000)
+            .set_fee_rate_strategy(fee_rate_strategy);
+
+        builder.finish(&blockchain).await?
+    };
+
+    assert_eq!(transfer.bitcoin_tx.lock_time.0, locktime);
+
+    // The transfer can't be mined before the locktime.
+    let err = blockchain_rpc
+        .send_raw_transaction(&transfer.bitcoin_tx)
+        .expect_err("Transfer must be rejected before the locktime");
+    assert!(
+        err.to_string().contains("non-final"),
+        "Unexpected error: {}",
+        err
+    );
+
+    // Mine blocks past the locktime.
+    blockchain_rpc.generate_to_address(LOCK_BLOCKS, &alice.address()?)?;
+
+    let txid = transfer.bitcoin_tx.txid();
+
+    yuv_client.send_raw_yuv_tx(transfer, None).await?;
+
+    // Add block with transfer to the chain
+    blockchain_rpc.generate_to_address(1, &alice.address()?)?;
+
+    // Check that the transfer was accepted by YUV node
+    let tx = wait_until_reject_or_attach(txid, &yuv_client).await?;
+
+    assert_attached!(tx, "Transfer was not accepted by YUV node");
+
+    Ok(())
+}